@@ -0,0 +1,255 @@
+//! The smoke benchmarks of the conversions on the hot paths, the containers of the prelude and the
+//! derived conversions, for the regressions between the releases, run with
+//! `cargo bench --features derive,test-util`. The inputs are made with the generators of
+//! `proto_conve::test_util`, which the other crates use to run the same shapes on their types.
+
+use std::collections::HashMap;
+use std::hint::black_box;
+
+use chrono::{DateTime, Utc};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use proto_conve::test_util::{self, Synthetic};
+use proto_conve::{impl_traits, FromProto, ToProto};
+
+mod proto {
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Timestamp {
+        pub seconds: i64,
+        pub nanos: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Reading {
+        pub sensor: u64,
+        pub value: f64,
+        pub valid: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Event {
+        pub id: String,
+        pub source: String,
+        pub created: Option<Timestamp>,
+        pub tags: Vec<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Inventory {
+        pub readings: HashMap<String, Reading>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Customer {
+        pub name: String,
+        pub email: Option<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Line {
+        pub sku: String,
+        pub quantity: u32,
+        pub price_cents: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Order {
+        pub id: String,
+        pub customer: Option<Customer>,
+        pub created: Option<Timestamp>,
+        pub status: i32,
+        pub lines: Vec<Line>,
+        pub notes: Option<String>,
+        pub attributes: HashMap<String, String>,
+    }
+}
+
+use proto::Timestamp;
+
+impl_traits!(prelude);
+impl_traits!(Timestamp, chrono::DateTime<chrono::Utc>);
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "proto::Reading")]
+pub struct Reading {
+    sensor: u64,
+    value: f64,
+    valid: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "proto::Event")]
+pub struct Event {
+    id: String,
+    source: String,
+    created: DateTime<Utc>,
+    tags: Vec<String>,
+}
+
+/// The id of the sensor, sent as the string key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SensorId(u64);
+
+mod sensor_key {
+    use super::SensorId;
+
+    pub fn from_proto(key: String) -> anyhow::Result<SensorId> {
+        let id = key
+            .strip_prefix("sensor-")
+            .ok_or_else(|| anyhow::anyhow!("The sensor key {key:?} has no prefix"))?;
+        Ok(SensorId(id.parse()?))
+    }
+
+    pub fn to_proto(id: &SensorId) -> String {
+        format!("sensor-{}", id.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "proto::Inventory")]
+pub struct Inventory {
+    #[proto(key_with = "sensor_key")]
+    readings: HashMap<SensorId, Reading>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "proto::Customer")]
+pub struct Customer {
+    name: String,
+    email: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "proto::Line")]
+pub struct Line {
+    sku: String,
+    quantity: u32,
+    price_cents: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, FromProto, ToProto)]
+pub enum Status {
+    Pending = 0,
+    Paid = 1,
+    Shipped = 2,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "proto::Order")]
+pub struct Order {
+    id: String,
+    customer: Customer,
+    created: DateTime<Utc>,
+    status: Status,
+    lines: Vec<Line>,
+    notes: Option<String>,
+    attributes: HashMap<String, String>,
+}
+
+fn reading(index: usize, values: &mut Synthetic) -> proto::Reading {
+    proto::Reading {
+        sensor: index as u64,
+        value: values.f64_in(-40.0..50.0),
+        valid: values.bool(),
+    }
+}
+
+fn timestamp(values: &mut Synthetic) -> proto::Timestamp {
+    let (seconds, nanos) = values.timestamp_parts();
+    proto::Timestamp { seconds, nanos }
+}
+
+fn event(index: usize, values: &mut Synthetic) -> proto::Event {
+    proto::Event {
+        id: format!("event-{index}"),
+        source: values.word(4..16),
+        created: Some(timestamp(values)),
+        tags: (0..values.i64_in(0..4))
+            .map(|_| values.word(3..8))
+            .collect(),
+    }
+}
+
+fn order(index: usize, values: &mut Synthetic) -> proto::Order {
+    proto::Order {
+        id: format!("order-{index}"),
+        customer: Some(proto::Customer {
+            name: values.word(5..20),
+            email: values
+                .bool()
+                .then(|| format!("{}@example.com", values.word(3..10))),
+        }),
+        created: Some(timestamp(values)),
+        status: values.i64_in(0..3) as i32,
+        lines: (0..values.i64_in(1..20))
+            .map(|_| proto::Line {
+                sku: values.word(8..9),
+                quantity: values.i64_in(1..10) as u32,
+                price_cents: values.i64_in(100..100_000),
+            })
+            .collect(),
+        notes: values.bool().then(|| values.word(10..60)),
+        attributes: (0..values.i64_in(0..6))
+            .map(|index| (format!("attribute-{index}"), values.word(3..12)))
+            .collect(),
+    }
+}
+
+/// Benchmark `from_proto` of the clones of the `proto` and `to_proto` of the converted value
+fn convert<P>(criterion: &mut Criterion, name: &str, elements: usize, proto: P)
+where
+    P: FromProto + Clone,
+    P::Result: ToProto,
+{
+    let mut group = criterion.benchmark_group(name);
+    group.throughput(Throughput::Elements(elements as u64));
+    group.bench_function("from_proto", |bencher| {
+        bencher.iter_batched(
+            || proto.clone(),
+            |proto| black_box(proto.from_proto().unwrap()),
+            BatchSize::LargeInput,
+        )
+    });
+    let value = proto.from_proto().unwrap();
+    group.bench_function("to_proto", |bencher| {
+        bencher.iter(|| black_box(value.to_proto()))
+    });
+    group.finish();
+}
+
+fn scalar_messages(criterion: &mut Criterion) {
+    let readings = test_util::synthetic(test_util::SCALAR_MESSAGES, reading);
+    convert(criterion, "scalar_messages", readings.len(), readings);
+}
+
+fn timestamp_messages(criterion: &mut Criterion) {
+    let events = test_util::synthetic(test_util::TIMESTAMP_MESSAGES, event);
+    convert(criterion, "timestamp_messages", events.len(), events);
+}
+
+fn typed_keys(criterion: &mut Criterion) {
+    let readings = test_util::synthetic_map(test_util::MAP_ENTRIES, |index, values| {
+        (format!("sensor-{index}"), reading(index, values))
+    });
+    convert(
+        criterion,
+        "typed_keys",
+        readings.len(),
+        proto::Inventory { readings },
+    );
+}
+
+fn nested_struct(criterion: &mut Criterion) {
+    let mut values = Synthetic::new(test_util::SEED);
+    convert(criterion, "nested_struct", 1, order(0, &mut values));
+}
+
+criterion_group!(
+    benches,
+    scalar_messages,
+    timestamp_messages,
+    typed_keys,
+    nested_struct
+);
+criterion_main!(benches);
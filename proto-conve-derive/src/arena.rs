@@ -0,0 +1,96 @@
+//! Generating the conversions of the proto messages in to the structs allocated in an arena, see
+//! `proto_conve::arena`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, GenericParam};
+
+use crate::attr::Container;
+use crate::expand;
+
+/// `FromProtoIn` of the proto type converting in to the struct with `#[proto(arena)]`
+pub fn from_proto_in(input: &DeriveInput, container: &Container) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let params = &input.generics.params;
+    let lifetime = match params.first() {
+        Some(GenericParam::Lifetime(param)) if params.len() == 1 => &param.lifetime,
+        _ => return Err(syn::Error::new(
+            ident.span(),
+            "the `arena` structs have only the lifetime of their arena, like `struct Event<'bump>`",
+        )),
+    };
+    if !matches!(input.data, syn::Data::Struct(_))
+        || container.validate.is_some()
+        || container.redact
+        || container.deny_unknown_fields
+        || container.context.is_some()
+        || container.project_from.is_some()
+    {
+        return Err(syn::Error::new(
+            ident.span(),
+            "`arena` can only be used on structs, without `validate`, `redact`, \
+             `deny_unknown_fields`, `context` and `project_from`",
+        ));
+    }
+    let targets = container.targets()?;
+    let [target] = targets.as_slice() else {
+        return Err(syn::Error::new(
+            ident.span(),
+            "the `arena` structs convert from the proto type of `type`, without the named mappings",
+        ));
+    };
+    let proto_type = target.proto_type;
+    let from_proto_in = container.trait_path("FromProtoIn");
+    let from_proto_field_in = container.trait_path("FromProtoFieldIn");
+
+    let fields = expand::fields(input, container, None, None)?;
+    let mut values = Vec::new();
+    for field in &fields {
+        let name = field.ident;
+        if field.skip {
+            values.push(quote!(#name: ::core::default::Default::default()));
+            continue;
+        }
+        if field.default.is_some()
+            || field.default_from_ctx
+            || field.from_with.is_some()
+            || field.key_with.is_some()
+            || !field.parents.is_empty()
+            || field.set
+            || field.boxed
+            || field.validate.is_some()
+            || field.implicit
+            || field.transform
+            || field.enumeration.is_some()
+            || field.dual.is_some()
+            || field.deprecated.is_some()
+            || field.sorted.is_some()
+            || field.async_with.is_some()
+            || !field.first_of.is_empty()
+            || !field.siblings.is_empty()
+        {
+            return Err(syn::Error::new(
+                name.span(),
+                "the fields of the `arena` structs convert with `FromProtoIn`, only `rename` and \
+                 `skip` can be used on them",
+            ));
+        }
+        let proto_ident = &field.proto_ident;
+        let proto_name = &field.proto_name;
+        values.push(quote! {
+            #name: #from_proto_field_in::from_proto_field_in(self.#proto_ident, bump, #proto_name)?
+        });
+    }
+    Ok(quote! {
+        impl<#lifetime> #from_proto_in<#lifetime> for #proto_type {
+            type Result = #ident<#lifetime>;
+
+            fn from_proto_in(
+                self,
+                bump: &#lifetime ::proto_conve::arena::Bump,
+            ) -> ::proto_conve::__private::anyhow::Result<Self::Result> {
+                Ok(#ident { #(#values,)* })
+            }
+        }
+    })
+}
@@ -0,0 +1,1497 @@
+//! Parsing of the `#[proto(...)]` attributes.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Attribute, DeriveInput, Ident, LitStr, Path, Token, WherePredicate};
+
+use crate::case::RenameRule;
+
+/// The attributes of the derived type
+pub struct Container {
+    /// The span of the type name, the errors about the whole type point at it
+    span: Span,
+    /// The proto type this type converts from and to
+    proto_type: Option<Path>,
+    /// The module with the traits generated by the prelude, if they are not in scope
+    pub traits: Option<Path>,
+    /// The case of the proto field names
+    pub rename_all: Option<RenameRule>,
+    /// The prost generated enum, the variants of enums get their values from
+    pub proto_enum: Option<Path>,
+    /// What to do with the unknown enum values
+    pub unknown: Unknown,
+    /// The enum is converted from and to the prost generated oneof enum
+    pub oneof: Option<Path>,
+    /// The enum can be converted in to `OpenEnum`, keeping the unknown values
+    pub open: bool,
+    /// The enum is converted from and to the proto strings of the variant names too
+    pub string: bool,
+    /// The proto strings of the enum match the names in any case
+    pub case_insensitive: bool,
+    /// The function validating the converted value
+    pub validate: Option<Path>,
+    /// The value is validated before it is converted in to proto value too
+    pub validate_on_to_proto: bool,
+    /// The bounds of the `FromProto` impl replacing the inferred ones
+    pub from_proto_bound: Option<Vec<WherePredicate>>,
+    /// The bounds of the `ToProto` impl replacing the inferred ones
+    pub to_proto_bound: Option<Vec<WherePredicate>>,
+    /// The oldest schema version supported by `FromProtoVersioned`
+    pub min_version: Option<u32>,
+    /// The newest schema version supported by `FromProtoVersioned`
+    pub max_version: Option<u32>,
+    /// The struct is converted from and to the repeated key and value entries
+    pub attr_bag: bool,
+    /// The proto message renders for the logs with `ProtoRedact`, also without the sensitive fields
+    pub redact: bool,
+    /// All the fields of the proto message have to be mapped, the added ones fail to compile
+    pub deny_unknown_fields: bool,
+    /// The proto fields left out on purpose with `deny_unknown_fields`
+    pub unmapped: Vec<Ident>,
+    /// The other proto types, given with `#[proto(type = "...", name = "...")]`
+    mappings: Vec<Mapping>,
+    /// The proto type, the struct converts from only some fields of
+    pub project_from: Option<Path>,
+    /// The type of the full mapping of `project_from`, which has all the projected fields
+    pub full: Option<Path>,
+    /// The context of the lookups of the `async_with` fields, `AsyncFromProtoWith` converts with
+    pub context: Option<syn::Type>,
+    /// The struct is converted in to the arena with `FromProtoIn` instead of `FromProto`
+    pub arena: bool,
+    /// The single field of the wrapper message, the newtype converts from and to
+    pub wrapper: Option<Ident>,
+}
+
+/// The named mapping of the type to another proto type
+struct Mapping {
+    name: String,
+    proto_type: Path,
+}
+
+/// The proto type, the impls are generated for
+pub struct Target<'a> {
+    /// The proto type
+    pub proto_type: &'a Path,
+    /// The name of the mapping, `None` for the `type` without a name
+    pub name: Option<&'a str>,
+}
+
+/// Handling of the unknown enum values
+pub enum Unknown {
+    /// The conversion fails
+    Error,
+    /// The value is converted in to the variant
+    Variant(Ident),
+}
+
+impl Container {
+    pub fn from_ast(input: &DeriveInput) -> syn::Result<Self> {
+        let mut proto_type = None;
+        let mut traits = None;
+        let mut rename_all = None;
+        let mut proto_enum = None;
+        let mut unknown = Unknown::Error;
+        let mut oneof = None;
+        let mut open = false;
+        let mut string = false;
+        let mut case_insensitive = false;
+        let mut validate = None;
+        let mut validate_on_to_proto = false;
+        let mut from_proto_bound = None;
+        let mut to_proto_bound = None;
+        let mut min_version = None;
+        let mut max_version = None;
+        let mut attr_bag = false;
+        let mut redact = false;
+        let mut deny_unknown_fields = false;
+        let mut unmapped = Vec::new();
+        let mut mappings = Vec::new();
+        let mut project_from = None;
+        let mut full = None;
+        let mut context = None;
+        let mut arena = false;
+        let mut wrapper = None;
+        for attr in proto_attrs(&input.attrs) {
+            let mut attr_type = None;
+            let mut name: Option<LitStr> = None;
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("type") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    attr_type = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("name") {
+                    name = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("traits") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    traits = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("rename_all") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    rename_all = Some(RenameRule::from_lit(&value)?);
+                    Ok(())
+                } else if meta.path.is_ident("enum") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    proto_enum = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("oneof") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    oneof = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("validate") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    validate = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("validate_on_to_proto") {
+                    validate_on_to_proto = true;
+                    Ok(())
+                } else if meta.path.is_ident("bound") {
+                    if meta.input.peek(syn::Token![=]) {
+                        let bound = parse_bound(&meta.value()?.parse()?)?;
+                        from_proto_bound = Some(bound.clone());
+                        to_proto_bound = Some(bound);
+                        return Ok(());
+                    }
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("from_proto") {
+                            from_proto_bound = Some(parse_bound(&meta.value()?.parse()?)?);
+                            Ok(())
+                        } else if meta.path.is_ident("to_proto") {
+                            to_proto_bound = Some(parse_bound(&meta.value()?.parse()?)?);
+                            Ok(())
+                        } else {
+                            Err(meta.error("unknown bound, expected `from_proto` or `to_proto`"))
+                        }
+                    })
+                } else if meta.path.is_ident("min_version") {
+                    let value: syn::LitInt = meta.value()?.parse()?;
+                    min_version = Some(value.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("max_version") {
+                    let value: syn::LitInt = meta.value()?.parse()?;
+                    max_version = Some(value.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("open") {
+                    open = true;
+                    Ok(())
+                } else if meta.path.is_ident("string") {
+                    string = true;
+                    Ok(())
+                } else if meta.path.is_ident("case_insensitive") {
+                    case_insensitive = true;
+                    Ok(())
+                } else if meta.path.is_ident("attr_bag") {
+                    attr_bag = true;
+                    Ok(())
+                } else if meta.path.is_ident("redact") {
+                    redact = true;
+                    Ok(())
+                } else if meta.path.is_ident("arena") {
+                    arena = true;
+                    Ok(())
+                } else if meta.path.is_ident("deny_unknown_fields") {
+                    deny_unknown_fields = true;
+                    Ok(())
+                } else if meta.path.is_ident("unmapped") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    let fields =
+                        value.parse_with(Punctuated::<Ident, Token![,]>::parse_terminated)?;
+                    unmapped.extend(fields);
+                    Ok(())
+                } else if meta.path.is_ident("project_from") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    project_from = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("full") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    full = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("wrapper") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    wrapper = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("context") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    context = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("unknown") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    unknown = match value.value().as_str() {
+                        "error" => Unknown::Error,
+                        _ => Unknown::Variant(value.parse()?),
+                    };
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown proto attribute"))
+                }
+            })?;
+            match (name, attr_type) {
+                (Some(name), Some(proto_type)) => mappings.push(Mapping {
+                    name: name.value(),
+                    proto_type,
+                }),
+                (Some(name), None) => {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        "the named mapping needs its `type = \"path::to::ProtoType\"`",
+                    ))
+                }
+                (None, Some(attr_type)) => proto_type = Some(attr_type),
+                (None, None) => {}
+            }
+        }
+        if case_insensitive && !string {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "`case_insensitive` matches the proto strings of `#[proto(string)]`",
+            ));
+        }
+        if let (Some(field), false) = (unmapped.first(), deny_unknown_fields) {
+            return Err(syn::Error::new(
+                field.span(),
+                "`unmapped` lists the proto fields left out with `deny_unknown_fields`",
+            ));
+        }
+        if full.is_some() && project_from.is_none() {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "`full` names the full mapping of the proto type of `project_from`",
+            ));
+        }
+        if project_from.is_some() && (proto_type.is_some() || !mappings.is_empty()) {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "`project_from` can not be used with `type`, the projection converts only from its \
+                 proto type",
+            ));
+        }
+        if project_from.is_some() && (deny_unknown_fields || redact) {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "`project_from` can not be used with `deny_unknown_fields` or `redact`",
+            ));
+        }
+        Ok(Self {
+            span: input.ident.span(),
+            proto_type,
+            traits,
+            rename_all,
+            proto_enum,
+            unknown,
+            oneof,
+            open,
+            string,
+            case_insensitive,
+            validate,
+            validate_on_to_proto,
+            from_proto_bound,
+            to_proto_bound,
+            min_version,
+            max_version,
+            attr_bag,
+            redact,
+            deny_unknown_fields,
+            unmapped,
+            mappings,
+            project_from,
+            full,
+            context,
+            arena,
+            wrapper,
+        })
+    }
+
+    /// The proto types to generate the impls for, the `type` and the named mappings
+    pub fn targets(&self) -> syn::Result<Vec<Target<'_>>> {
+        let mut targets: Vec<_> = self
+            .mappings
+            .iter()
+            .map(|mapping| Target {
+                proto_type: &mapping.proto_type,
+                name: Some(&mapping.name),
+            })
+            .collect();
+        if self.proto_type.is_some() || targets.is_empty() {
+            targets.insert(
+                0,
+                Target {
+                    proto_type: self.proto_type()?,
+                    name: None,
+                },
+            );
+        }
+        Ok(targets)
+    }
+
+    /// The proto type, which is required for structs
+    pub fn proto_type(&self) -> syn::Result<&Path> {
+        if self.project_from.is_some() {
+            return Err(syn::Error::new(
+                self.span,
+                "the projections of `project_from` only derive `FromProto`",
+            ));
+        }
+        self.proto_type.as_ref().ok_or_else(|| {
+            syn::Error::new(
+                self.span,
+                "missing `#[proto(type = \"path::to::ProtoType\")]` attribute",
+            )
+        })
+    }
+
+    /// The wire type of the enum, `i32` if not given
+    pub fn enum_type(&self) -> TokenStream {
+        match &self.proto_type {
+            Some(proto_type) => quote!(#proto_type),
+            None => quote!(i32),
+        }
+    }
+
+    /// The path to one of the traits generated by the prelude
+    pub fn trait_path(&self, name: &str) -> TokenStream {
+        let name = Ident::new(name, Span::call_site());
+        match &self.traits {
+            Some(traits) => quote!(#traits::#name),
+            None => quote!(#name),
+        }
+    }
+}
+
+/// The attributes of a struct field
+pub struct Field<'a> {
+    /// The rust field name
+    pub ident: &'a Ident,
+    /// The field type
+    pub ty: &'a syn::Type,
+    /// The proto field name
+    pub proto_ident: Ident,
+    /// The proto field name as shown in the errors
+    pub proto_name: String,
+    /// The field has no proto counterpart
+    pub skip: bool,
+    /// The value used, when the optional proto field is missing
+    pub default: Option<FieldDefault>,
+    /// The missing proto field takes the default of the `DefaultsProvider` of the conversion, before
+    /// the static `default`
+    pub default_from_ctx: bool,
+    /// The function converting the proto field, instead of the traits
+    pub from_with: Option<Path>,
+    /// The function converting in to the proto field, instead of the traits
+    pub to_with: Option<Path>,
+    /// The conversion of the keys of the map field
+    pub key_with: Option<KeyWith>,
+    /// The repeated proto field is collected in to a set
+    pub set: bool,
+    /// The rust field is boxed, while the proto one is not
+    pub boxed: bool,
+    /// The function validating the converted field value
+    pub validate: Option<Path>,
+    /// The default value is converted in to the missing proto field and back
+    pub skip_default: bool,
+    /// The predicate leaving the optional proto field out, before the value is converted
+    pub omit_if: Option<Path>,
+    /// The old function converting the proto field, run with the traits in the dual mode, see
+    /// `proto_conve::dual`
+    pub dual: Option<Path>,
+    /// The nested message paths of the field mask are merged in to the field
+    pub merge: bool,
+    /// The nested proto messages containing the field, from the outermost, given with `path`
+    pub parents: Vec<(Ident, String)>,
+    /// The map of the attribute bag, which keeps the unknown keys
+    pub leftover: bool,
+    /// The field is decoded and encoded with the `ProtoTransform` of the conversion
+    pub transform: bool,
+    /// The proto default of the field is reported by `FromProtoLossy`, as the schema may have drifted
+    pub required_nonzero: bool,
+    /// The proto field has no presence, like the plain proto3 scalars, its default value is missing
+    pub implicit: bool,
+    /// The message of the deprecated field, its present proto values record the deprecation notices
+    pub deprecated: Option<LitStr>,
+    /// The prost enum of the raw `i32` field, converted with `proto_conve::ProtoEnumField`
+    pub enumeration: Option<Path>,
+    /// The value of the proto field is redacted in the rendering for the logs
+    pub sensitive: bool,
+    /// The field is only moved in to the proto field, with the derived `IntoProto`, never cloned
+    pub move_only: bool,
+    /// The proto list is sorted before it is set
+    pub sorted: Option<FieldSort>,
+    /// The `async` function converting the proto field with the lookups in the context, given with
+    /// `async_with`
+    pub async_with: Option<Path>,
+    /// The alternative proto fields, in the order of their preference, given with `first_of`, the
+    /// first one is the proto field
+    pub first_of: Vec<(Ident, String)>,
+    /// More than one of the present `first_of` fields is an error
+    pub strict: bool,
+    /// The sibling proto fields of the variants of the oneof with `sibling`, given with `siblings`,
+    /// in the order of the variants
+    pub siblings: Vec<(Ident, String)>,
+}
+
+/// The conversion of the map keys
+pub enum KeyWith {
+    /// The module with `from_proto` and `to_proto` of the keys
+    Module(Path),
+    /// The `i32` keys are converted in to the enums with `FromProtoAlias` and back with `ToProtoAlias`
+    Enum(Span),
+    /// The `i32` keys are the values of the prost enum, converted with `proto_conve::key::EnumKey`
+    ProtoEnum {
+        enumeration: Path,
+        /// The variant of `proto_conve::key::UnknownKeys`
+        unknown: Ident,
+    },
+}
+
+impl KeyWith {
+    /// The span of the attribute, the generated conversions are spanned at it
+    pub fn span(&self) -> Span {
+        match self {
+            KeyWith::Module(module) => module.span(),
+            KeyWith::Enum(span) => *span,
+            KeyWith::ProtoEnum { enumeration, .. } => enumeration.span(),
+        }
+    }
+}
+
+/// The order of the sorted proto list
+pub enum FieldSort {
+    /// The `Ord` of the proto elements
+    Ord(Span),
+    /// The key of the proto elements given by the function
+    Key(Path),
+}
+
+/// The fallback for the missing proto field
+pub enum FieldDefault {
+    /// Use `Default::default()`
+    Trait,
+    /// Call the function
+    Function(Path),
+}
+
+impl<'a> Field<'a> {
+    /// The attributes of the field for the named `mapping`, the attributes with `mapping = "..."` are
+    /// used only for theirs. The attributes with `since` or `until` are used only in the schema
+    /// `version` in their range, and never without the version.
+    pub fn from_ast(
+        field: &'a syn::Field,
+        container: &Container,
+        mapping: Option<&str>,
+        version: Option<u32>,
+    ) -> syn::Result<Self> {
+        let ident = field.ident.as_ref().unwrap();
+        let mut rename: Option<LitStr> = None;
+        let mut skip = false;
+        let mut default = None;
+        let mut default_from_ctx = false;
+        let mut from_with = None;
+        let mut to_with = None;
+        let mut key_with = None;
+        let mut set = false;
+        let mut boxed = false;
+        let mut validate = None;
+        let mut skip_default = false;
+        let mut omit_if: Option<Path> = None;
+        let mut dual: Option<Path> = None;
+        let mut merge = false;
+        let mut leftover = false;
+        let mut intern = false;
+        let mut nullable_values = false;
+        let mut wire: Option<LitStr> = None;
+        let mut cached = false;
+        let mut transform = false;
+        let mut required_nonzero = false;
+        let mut deprecated: Option<LitStr> = None;
+        let mut implicit = false;
+        let mut enumeration: Option<Path> = None;
+        let mut sensitive = false;
+        let mut move_only = false;
+        let mut sorted = None;
+        let mut clone_cheap = false;
+        let mut path: Option<LitStr> = None;
+        let mut async_with = None;
+        let mut key_enum: Option<Path> = None;
+        let mut unknown_keys: Option<LitStr> = None;
+        let mut first_of: Vec<LitStr> = Vec::new();
+        let mut strict: Option<Span> = None;
+        let mut siblings: Vec<LitStr> = Vec::new();
+        if let Some(inner) = option_inner(&field.ty) {
+            if last_ident_is(inner, "Vec") {
+                return Err(syn::Error::new_spanned(
+                    &field.ty,
+                    "repeated proto fields can not be optional, use `Vec` instead of `Option<Vec<..>>`",
+                ));
+            }
+        }
+        // prost boxes the messages inside the option, the options and lists are never boxed
+        if let Some(inner) = type_argument(&field.ty, "Box") {
+            if last_ident_is(inner, "Option") || last_ident_is(inner, "Vec") {
+                return Err(syn::Error::new_spanned(
+                    &field.ty,
+                    "only the proto messages can be boxed, use `Option<Box<..>>` or `Vec<..>` instead \
+                     of boxing the `Option` or `Vec`",
+                ));
+            }
+        }
+        let mut attrs = Vec::new();
+        for attr in proto_attrs(&field.attrs) {
+            attrs.push((attr, attr_scope(attr)?));
+        }
+        // the attributes of the versions override the ones without a version
+        attrs.sort_by_key(|(_, scope)| scope.since.is_some() || scope.until.is_some());
+        for (attr, scope) in attrs {
+            if scope.since.is_some() || scope.until.is_some() {
+                let in_range = version.is_some_and(|version| {
+                    scope.since.is_none_or(|since| since <= version)
+                        && scope.until.is_none_or(|until| version <= until)
+                });
+                if !in_range {
+                    continue;
+                }
+            }
+            if let Some(name) = scope.mapping {
+                if !container
+                    .mappings
+                    .iter()
+                    .any(|mapping| mapping.name == name.value())
+                {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        format!("unknown mapping `{}`", name.value()),
+                    ));
+                }
+                if mapping != Some(name.value().as_str()) {
+                    continue;
+                }
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("mapping") {
+                    let _: LitStr = meta.value()?.parse()?;
+                    Ok(())
+                } else if meta.path.is_ident("since") || meta.path.is_ident("until") {
+                    let _: syn::LitInt = meta.value()?.parse()?;
+                    Ok(())
+                } else if meta.path.is_ident("rename") {
+                    rename = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("merge") {
+                    merge = true;
+                    Ok(())
+                } else if meta.path.is_ident("leftover") {
+                    if !container.attr_bag {
+                        return Err(meta.error(
+                            "`leftover` can only be used in the structs with `#[proto(attr_bag)]`",
+                        ));
+                    }
+                    leftover = true;
+                    Ok(())
+                } else if meta.path.is_ident("skip_default") {
+                    if is_option(&field.ty) {
+                        return Err(meta.error(
+                            "`skip_default` can not be used on `Option` fields, they are already \
+                             converted in to the optional proto field",
+                        ));
+                    }
+                    skip_default = true;
+                    Ok(())
+                } else if meta.path.is_ident("omit_if") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    omit_if = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("dual") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    dual = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    if is_option(&field.ty) {
+                        return Err(meta.error(
+                            "`default` can not be used on `Option` fields, the missing proto field is \
+                             already converted in to `None`",
+                        ));
+                    }
+                    default = Some(if meta.input.peek(syn::Token![=]) {
+                        let value: LitStr = meta.value()?.parse()?;
+                        FieldDefault::Function(value.parse()?)
+                    } else {
+                        FieldDefault::Trait
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("default_from_ctx") {
+                    if is_option(&field.ty) {
+                        return Err(meta.error(
+                            "`default_from_ctx` can not be used on `Option` fields, the missing proto \
+                             field is already converted in to `None`",
+                        ));
+                    }
+                    default_from_ctx = true;
+                    Ok(())
+                } else if meta.path.is_ident("with") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    let module: Path = value.parse()?;
+                    from_with = Some(with_function(&module, "from_proto", value.span()));
+                    to_with = Some(with_function(&module, "to_proto", value.span()));
+                    Ok(())
+                } else if meta.path.is_ident("async_with") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    let module: Path = value.parse()?;
+                    async_with = Some(with_function(&module, "from_proto", value.span()));
+                    to_with = Some(with_function(&module, "to_proto", value.span()));
+                    Ok(())
+                } else if meta.path.is_ident("from_with") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    from_with = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("to_with") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    to_with = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("key_with") || meta.path.is_ident("key") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    if key_with.is_some() || key_enum.is_some() {
+                        return Err(meta.error(
+                            "the keys are converted with only one `key`, `key_with` or `key_enum`",
+                        ));
+                    }
+                    key_with = Some(match value.value().as_str() {
+                        _ if meta.path.is_ident("key_with") => KeyWith::Module(value.parse()?),
+                        "uuid" => KeyWith::Module(syn::parse_quote!(::proto_conve::key::uuid)),
+                        "enum" => KeyWith::Enum(value.span()),
+                        _ => {
+                            return Err(syn::Error::new(
+                                value.span(),
+                                "unknown key conversion, expected `uuid` or `enum`, other keys are \
+                                 converted with `key_with`",
+                            ))
+                        }
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("key_enum") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    if key_with.is_some() || key_enum.is_some() {
+                        return Err(meta.error(
+                            "the keys are converted with only one `key`, `key_with` or `key_enum`",
+                        ));
+                    }
+                    key_enum = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("unknown_keys") {
+                    unknown_keys = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("set") {
+                    set = true;
+                    Ok(())
+                } else if meta.path.is_ident("intern") {
+                    intern = true;
+                    Ok(())
+                } else if meta.path.is_ident("nullable_values") {
+                    nullable_values = true;
+                    Ok(())
+                } else if meta.path.is_ident("wire") {
+                    wire = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("cached") {
+                    cached = true;
+                    Ok(())
+                } else if meta.path.is_ident("transform") {
+                    transform = true;
+                    Ok(())
+                } else if meta.path.is_ident("enumeration") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    enumeration = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("required_nonzero") {
+                    required_nonzero = true;
+                    Ok(())
+                } else if meta.path.is_ident("presence") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    implicit = match value.value().as_str() {
+                        "explicit" => false,
+                        "implicit" => true,
+                        _ => {
+                            return Err(syn::Error::new(
+                                value.span(),
+                                "unknown presence, expected `explicit` or `implicit`",
+                            ))
+                        }
+                    };
+                    Ok(())
+                } else if meta.path.is_ident("deprecated") {
+                    deprecated = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("sensitive") {
+                    sensitive = true;
+                    Ok(())
+                } else if meta.path.is_ident("sorted") {
+                    sorted = Some(if meta.input.peek(syn::Token![=]) {
+                        let value: LitStr = meta.value()?.parse()?;
+                        FieldSort::Key(value.parse()?)
+                    } else {
+                        FieldSort::Ord(meta.path.span())
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("move_only") {
+                    move_only = true;
+                    Ok(())
+                } else if meta.path.is_ident("clone_cheap") {
+                    // documents the field cloned by `ToProto`, it only conflicts with `move_only`
+                    clone_cheap = true;
+                    Ok(())
+                } else if meta.path.is_ident("boxed") {
+                    boxed = true;
+                    Ok(())
+                } else if meta.path.is_ident("validate") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    validate = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("path") {
+                    path = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("first_of") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    first_of = content
+                        .parse_terminated(<LitStr as syn::parse::Parse>::parse, Token![,])?
+                        .into_iter()
+                        .collect();
+                    if first_of.len() < 2 {
+                        return Err(meta.error(
+                            "`first_of` needs at least two proto fields, like \
+                             `first_of(\"created_at_ts\", \"created_at_millis\")`",
+                        ));
+                    }
+                    Ok(())
+                } else if meta.path.is_ident("strict") {
+                    strict = Some(meta.path.span());
+                    Ok(())
+                } else if meta.path.is_ident("siblings") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    siblings = content
+                        .parse_terminated(<LitStr as syn::parse::Parse>::parse, Token![,])?
+                        .into_iter()
+                        .collect();
+                    if siblings.is_empty() {
+                        return Err(meta.error(
+                            "`siblings` needs the proto fields of the variants, like \
+                             `siblings(\"cancelled_at\")`",
+                        ));
+                    }
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown proto attribute"))
+                }
+            })?;
+        }
+        // the map of the proto `Value`s with the explicit nulls, see `proto_conve::list_value`
+        if nullable_values {
+            if from_with.is_some()
+                || to_with.is_some()
+                || key_with.is_some()
+                || key_enum.is_some()
+                || intern
+                || transform
+                || cached
+                || enumeration.is_some()
+                || set
+            {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`nullable_values` converts the map of the proto `Value`s, it can not be combined \
+                     with `with`, `key_with`, `set` or the other conversions",
+                ));
+            }
+            from_with = Some(syn::parse_quote!(
+                ::proto_conve::list_value::nullable_values::from_proto
+            ));
+            to_with = Some(syn::parse_quote!(
+                ::proto_conve::list_value::nullable_values::to_proto
+            ));
+        }
+        // the checked integers of the wire type, see `proto_conve::wire`
+        if let Some(wire) = wire {
+            const WIRE_TYPES: [&str; 10] = [
+                "int32", "int64", "uint32", "uint64", "sint32", "sint64", "fixed32", "fixed64",
+                "sfixed32", "sfixed64",
+            ];
+            if !WIRE_TYPES.contains(&wire.value().as_str()) {
+                return Err(syn::Error::new(
+                    wire.span(),
+                    format!(
+                        "unknown wire type `{}`, expected one of {}",
+                        wire.value(),
+                        WIRE_TYPES.join(", ")
+                    ),
+                ));
+            }
+            if from_with.is_some()
+                || to_with.is_some()
+                || nullable_values
+                || intern
+                || transform
+                || cached
+                || enumeration.is_some()
+            {
+                return Err(syn::Error::new(
+                    wire.span(),
+                    "`wire` converts the proto integer, it can not be combined with `with` or the \
+                     other conversions",
+                ));
+            }
+            let module = Ident::new(&wire.value(), wire.span());
+            from_with = Some(syn::parse_quote!(::proto_conve::wire::#module::from_proto));
+            to_with = Some(syn::parse_quote!(::proto_conve::wire::#module::to_proto));
+        }
+        if (intern || transform || cached)
+            && (from_with.is_some()
+                || to_with.is_some()
+                || key_with.is_some()
+                || set
+                || default.is_some()
+                || usize::from(intern) + usize::from(transform) + usize::from(cached) > 1)
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`intern`, `transform` and `cached` can not be combined with each other, `with`, \
+                 `key_with`, `set` or `default`",
+            ));
+        }
+        if enumeration.is_some()
+            && (intern
+                || transform
+                || cached
+                || from_with.is_some()
+                || to_with.is_some()
+                || key_with.is_some()
+                || set
+                || default.is_some())
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`enumeration` can not be combined with `with`, `key_with`, `set`, `default`, `intern`, \
+                 `transform` or `cached`, the field is converted by the prost enum",
+            ));
+        }
+        if let Some(enumeration) = key_enum {
+            key_with = Some(key_enum_with(field, enumeration, unknown_keys)?);
+        } else if let Some(unknown_keys) = unknown_keys {
+            return Err(syn::Error::new(
+                unknown_keys.span(),
+                "`unknown_keys` is the policy of the keys of `key_enum`",
+            ));
+        }
+        if implicit
+            && (skip
+                || skip_default
+                || boxed
+                || set
+                || intern
+                || transform
+                || cached
+                || enumeration.is_some()
+                || from_with.is_some()
+                || to_with.is_some()
+                || key_with.is_some())
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`presence = \"implicit\"` can only be used on the fields converted with the traits, \
+                 without `skip_default`, `boxed` or `set`",
+            ));
+        }
+        // the fields of `OpenEnum` keep the unknown values
+        if let Some(enumeration) = &enumeration {
+            let open = last_ident_is(&field.ty, "OpenEnum");
+            let (from_proto, to_proto) = match open {
+                true => (quote!(from_proto_open), quote!(to_proto_open)),
+                false => (quote!(from_proto), quote!(to_proto)),
+            };
+            from_with =
+                Some(syn::parse_quote!(::proto_conve::enumeration::#from_proto::<#enumeration>));
+            to_with =
+                Some(syn::parse_quote!(::proto_conve::enumeration::#to_proto::<#enumeration>));
+        }
+        // the encoding can fail, so it is a part of `TryToProto`
+        if transform {
+            from_with = Some(syn::parse_quote!(::proto_conve::transform::from_proto));
+        }
+        if intern {
+            from_with = Some(syn::parse_quote!(::proto_conve::intern::from_proto));
+            to_with = Some(syn::parse_quote!(::proto_conve::intern::to_proto));
+        }
+        // the cached fields are converted with the traits of the prelude
+        if cached {
+            let cached_field = container.trait_path("ProtoCachedField");
+            from_with = Some(syn::parse_quote!(#cached_field::from_proto_cached_field));
+            to_with = Some(syn::parse_quote!(#cached_field::to_proto_cached_field));
+        }
+        if async_with.is_some()
+            && (skip
+                || default.is_some()
+                || boxed
+                || set
+                || implicit
+                || merge
+                || sorted.is_some()
+                || dual.is_some()
+                || from_with.is_some()
+                || key_with.is_some()
+                || path.is_some())
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`async_with` converts the proto field as it is, it can not be combined with the other \
+                 conversions, `default`, `boxed`, `set`, `presence`, `merge`, `sorted` or `path`",
+            ));
+        }
+        if skip && validate.is_some() {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`validate` can not be used on skipped fields, they are not converted",
+            ));
+        }
+        if let (true, Some(deprecated)) = (skip, &deprecated) {
+            return Err(syn::Error::new(
+                deprecated.span(),
+                "`deprecated` can not be used on skipped fields, there is no proto field",
+            ));
+        }
+        if sorted.is_some() && (skip || key_with.is_some() || to_with.is_some()) {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`sorted` can only be used on the repeated fields converted with the traits, not on \
+                 skipped or map fields, or with `with`",
+            ));
+        }
+        if move_only && clone_cheap {
+            return Err(syn::Error::new(
+                ident.span(),
+                "the field can not be both `move_only` and `clone_cheap`",
+            ));
+        }
+        if move_only
+            && (skip
+                || boxed
+                || transform
+                || omit_if.is_some()
+                || to_with.is_some()
+                || key_with.is_some())
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "the `move_only` fields are moved in to the proto fields as they are, not with `skip`, \
+                 `boxed`, `transform`, `omit_if`, `with` or `key_with`",
+            ));
+        }
+        if skip && sensitive {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`sensitive` can not be used on skipped fields, there is no proto field",
+            ));
+        }
+        if skip_default && (skip || key_with.is_some() || set) {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`skip_default` can not be used on skipped, map or set fields",
+            ));
+        }
+        if omit_if.is_some() && (skip || skip_default || implicit || key_with.is_some() || set) {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`omit_if` can only be used on the fields converted in to the optional proto fields, \
+                 not on skipped, map or set fields, or with `skip_default` or `presence`",
+            ));
+        }
+        if dual.is_some()
+            && (skip
+                || default.is_some()
+                || implicit
+                || from_with.is_some()
+                || key_with.is_some()
+                || set)
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`dual` can only be used on the fields converted with the traits, which are compared \
+                 with the old function, not with `default`, `presence`, `with`, map or set fields",
+            ));
+        }
+        if merge && (skip || from_with.is_some() || key_with.is_some() || set) {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`merge` can only be used on the fields of nested messages converted with the traits",
+            ));
+        }
+        // the missing proto field is the default value
+        if skip_default && default.is_none() && from_with.is_none() {
+            default = Some(FieldDefault::Trait);
+        }
+        if default.is_some() && from_with.is_some() {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`default` can not be combined with `with`, the conversion function gets the proto field \
+                 as it is",
+            ));
+        }
+        if default_from_ctx
+            && (skip
+                || from_with.is_some()
+                || key_with.is_some()
+                || set
+                || dual.is_some()
+                || !first_of.is_empty()
+                || intern
+                || transform
+                || cached)
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`default_from_ctx` is the proto value of the missing field converted with the traits, \
+                 it can not be combined with `skip`, `with`, `key_with`, `set`, `dual`, `first_of` or \
+                 the other conversions",
+            ));
+        }
+
+        if let (Some(strict), true) = (strict, first_of.is_empty()) {
+            return Err(syn::Error::new(
+                strict,
+                "`strict` is the ambiguity check of the `first_of` fields",
+            ));
+        }
+        if !first_of.is_empty()
+            && (skip
+                || rename.is_some()
+                || path.is_some()
+                || default.is_some()
+                || from_with.is_some()
+                || key_with.is_some()
+                || set
+                || boxed
+                || skip_default
+                || implicit
+                || required_nonzero
+                || merge
+                || dual.is_some()
+                || deprecated.is_some()
+                || sorted.is_some()
+                || async_with.is_some()
+                || intern
+                || transform
+                || cached)
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`first_of` converts the present alternative with the traits, it can not be combined \
+                 with `skip`, `rename`, `path`, `default`, `with`, `key_with`, `set`, `boxed`, \
+                 `presence`, `merge`, `dual`, `deprecated`, `sorted` or the other conversions",
+            ));
+        }
+        if !siblings.is_empty()
+            && (skip
+                || path.is_some()
+                || default.is_some()
+                || default_from_ctx
+                || from_with.is_some()
+                || key_with.is_some()
+                || set
+                || boxed
+                || skip_default
+                || implicit
+                || required_nonzero
+                || merge
+                || dual.is_some()
+                || deprecated.is_some()
+                || sorted.is_some()
+                || async_with.is_some()
+                || !first_of.is_empty()
+                || intern
+                || transform
+                || cached)
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`siblings` converts the oneof with the sibling fields of its variants, it can not be \
+                 combined with `skip`, `path`, `default`, `with`, `key_with`, `set`, `boxed`, \
+                 `presence`, `merge`, `dual`, `deprecated`, `sorted`, `first_of` or the other \
+                 conversions",
+            ));
+        }
+        let siblings = siblings
+            .iter()
+            .map(|name| {
+                let value = name.value();
+                Ok((proto_ident_from(&value, name.span())?, unraw(&value)))
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+        let first_of = first_of
+            .iter()
+            .map(|name| {
+                let value = name.value();
+                Ok((proto_ident_from(&value, name.span())?, unraw(&value)))
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+        // the rust value is converted in to the first alternative
+        if let Some((first, _)) = first_of.first() {
+            rename = Some(LitStr::new(&first.to_string(), first.span()));
+        }
+
+        let mut parents = Vec::new();
+        if let Some(path) = path {
+            if rename.is_some() {
+                return Err(syn::Error::new(
+                    path.span(),
+                    "`path` can not be combined with `rename`, the last segment of the path is the \
+                     proto field name",
+                ));
+            }
+            let value = path.value();
+            for name in value.split('.') {
+                parents.push((proto_ident_from(name, path.span())?, unraw(name)));
+            }
+            // the last segment is the field itself
+            rename = parents.pop().map(|(ident, _)| {
+                let name = ident.to_string();
+                LitStr::new(&name, path.span())
+            });
+        }
+        let (proto_ident, proto_name) = match rename {
+            // the keys of attribute bags are not field names
+            Some(rename) if container.attr_bag => (ident.clone(), rename.value()),
+            Some(rename) => {
+                let value = rename.value();
+                (proto_ident_from(&value, rename.span())?, unraw(&value))
+            }
+            None => {
+                let name = unraw(&ident.to_string());
+                match container.rename_all {
+                    Some(rule) => {
+                        let name = rule.apply(&name);
+                        (proto_ident_from(&name, ident.span())?, name)
+                    }
+                    None => (ident.clone(), name),
+                }
+            }
+        };
+        Ok(Self {
+            ident,
+            ty: &field.ty,
+            proto_ident,
+            proto_name,
+            skip,
+            default,
+            default_from_ctx,
+            from_with,
+            to_with,
+            key_with,
+            set,
+            boxed,
+            validate,
+            skip_default,
+            omit_if,
+            dual,
+            merge,
+            parents,
+            leftover,
+            transform,
+            required_nonzero,
+            implicit,
+            deprecated,
+            enumeration,
+            sensitive,
+            move_only,
+            sorted,
+            async_with,
+            first_of,
+            strict: strict.is_some(),
+            siblings,
+        })
+    }
+}
+
+/// The conversions a field attribute is used for
+#[derive(Default)]
+struct Scope {
+    /// The named mapping, given with `mapping = "..."`
+    mapping: Option<LitStr>,
+    /// The first schema version, given with `since = 3`
+    since: Option<u32>,
+    /// The last schema version, given with `until = 2`
+    until: Option<u32>,
+}
+
+/// The conversions the field attribute is for
+fn attr_scope(attr: &Attribute) -> syn::Result<Scope> {
+    let mut scope = Scope::default();
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("mapping") {
+            scope.mapping = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("since") {
+            let value: syn::LitInt = meta.value()?.parse()?;
+            scope.since = Some(value.base10_parse()?);
+        } else if meta.path.is_ident("until") {
+            let value: syn::LitInt = meta.value()?.parse()?;
+            scope.until = Some(value.base10_parse()?);
+        } else if meta.input.peek(Token![=]) {
+            let _: syn::Expr = meta.value()?.parse()?;
+        } else if meta.input.peek(syn::token::Paren) {
+            // the list of `first_of` or `siblings`
+            let content;
+            syn::parenthesized!(content in meta.input);
+            let _: TokenStream = content.parse()?;
+        }
+        Ok(())
+    })?;
+    if let (Some(since), Some(until)) = (scope.since, scope.until) {
+        if since > until {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "`since` has to be before `until`, the attribute is used in no version",
+            ));
+        }
+    }
+    Ok(scope)
+}
+
+/// The first schema versions, in which the attributes of the field change
+pub fn version_bounds(field: &syn::Field) -> syn::Result<Vec<u32>> {
+    let mut bounds = Vec::new();
+    for attr in proto_attrs(&field.attrs) {
+        let scope = attr_scope(attr)?;
+        bounds.extend(scope.since);
+        bounds.extend(scope.until.and_then(|until| until.checked_add(1)));
+    }
+    Ok(bounds)
+}
+
+/// The where predicates given in `#[proto(bound = "...")]`
+fn parse_bound(value: &LitStr) -> syn::Result<Vec<WherePredicate>> {
+    let predicates = value.parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated)?;
+    Ok(predicates.into_iter().collect())
+}
+
+/// The function `name` in the module given by `with`, spanned at the attribute
+/// The conversion of the keys of `key_enum`, the `OpenEnum` keys are `open`, the others `error` by
+/// default
+fn key_enum_with(
+    field: &syn::Field,
+    enumeration: Path,
+    unknown_keys: Option<LitStr>,
+) -> syn::Result<KeyWith> {
+    let open = map_key(&field.ty).is_some_and(|key| last_ident_is(key, "OpenEnum"));
+    let unknown = match (unknown_keys, open) {
+        (None, false) => "Error",
+        (None, true) => "Open",
+        (Some(policy), _) => match (policy.value().as_str(), open) {
+            ("error", false) => "Error",
+            ("skip", false) => "Skip",
+            ("open", true) => "Open",
+            ("error" | "skip", true) => {
+                return Err(syn::Error::new(
+                    policy.span(),
+                    "the `OpenEnum` keys keep the unknown keys, their policy is `open`",
+                ))
+            }
+            ("open", false) => {
+                return Err(syn::Error::new(
+                    policy.span(),
+                    "the unknown keys are kept only in the `OpenEnum` keys",
+                ))
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    policy.span(),
+                    "unknown policy of the unknown keys, expected `error`, `skip` or `open`",
+                ))
+            }
+        },
+    };
+    Ok(KeyWith::ProtoEnum {
+        enumeration,
+        unknown: Ident::new(unknown, Span::call_site()),
+    })
+}
+
+/// The key type of the map type, its first type argument
+fn map_key(ty: &syn::Type) -> Option<&syn::Type> {
+    ["HashMap", "BTreeMap"]
+        .iter()
+        .find_map(|name| type_argument(ty, name))
+}
+
+fn with_function(module: &Path, name: &str, span: Span) -> Path {
+    let mut path = module.clone();
+    path.segments.push(Ident::new(name, span).into());
+    path
+}
+
+/// The attributes of an enum variant
+pub struct Variant<'a> {
+    /// The rust variant
+    pub variant: &'a syn::Variant,
+    /// The rust variant name
+    pub ident: &'a Ident,
+    /// The name of the variant in the prost generated enum
+    pub proto_ident: Ident,
+    /// The wire value given explicitly
+    pub value: Option<syn::Expr>,
+    /// The canonical proto string of `#[proto(string)]`, the screaming snake case of the proto name
+    /// by default
+    pub name: LitStr,
+    /// The other proto strings of the variant, like the legacy ones
+    pub aliases: Vec<LitStr>,
+    /// The sibling proto field of the oneof with the value of the variant, whose proto variant is an
+    /// empty message, given with `sibling`
+    pub sibling: Option<(Ident, String)>,
+}
+
+impl<'a> Variant<'a> {
+    pub fn from_ast(variant: &'a syn::Variant) -> syn::Result<Self> {
+        let mut proto_ident = variant.ident.clone();
+        let mut value = None;
+        let mut name = None;
+        let mut aliases = Vec::new();
+        let mut sibling = None;
+        for attr in proto_attrs(&variant.attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("value") {
+                    value = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("name") {
+                    name = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("alias") {
+                    aliases.push(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("rename") {
+                    let rename: LitStr = meta.value()?.parse()?;
+                    proto_ident = proto_ident_from(&rename.value(), rename.span())?;
+                    Ok(())
+                } else if meta.path.is_ident("sibling") {
+                    let name: LitStr = meta.value()?.parse()?;
+                    let value = name.value();
+                    sibling = Some((proto_ident_from(&value, name.span())?, unraw(&value)));
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown proto attribute"))
+                }
+            })?;
+        }
+        let name = name.unwrap_or_else(|| {
+            let name = oneof_field_name(&proto_ident).to_uppercase();
+            LitStr::new(&name, variant.ident.span())
+        });
+        Ok(Self {
+            variant,
+            ident: &variant.ident,
+            proto_ident,
+            value,
+            name,
+            aliases,
+            sibling,
+        })
+    }
+}
+
+/// The type is `Option<..>`
+pub fn is_option(ty: &syn::Type) -> bool {
+    last_ident_is(ty, "Option")
+}
+
+/// The type is `Vec<u8>`, converted as the proto `bytes`
+pub fn is_bytes(ty: &syn::Type) -> bool {
+    let syn::Type::Path(path) = ty else {
+        return false;
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return false;
+    };
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if segment.ident == "Vec" => {
+            match args.args.first() {
+                Some(syn::GenericArgument::Type(ty)) => {
+                    args.args.len() == 1 && last_ident_is(ty, "u8")
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// The last segment of the type path is `name`
+pub fn last_ident_is(ty: &syn::Type, name: &str) -> bool {
+    match ty {
+        syn::Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == name),
+        _ => false,
+    }
+}
+
+/// The `T` of `Option<T>`, also of `::core::option::Option<T>`
+pub fn option_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    type_argument(ty, "Option")
+}
+
+/// The `T` of the type `name<T>`, the name is the last segment of the path
+fn type_argument<'a>(ty: &'a syn::Type, name: &str) -> Option<&'a syn::Type> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != name {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first()? {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Create the identifier of the proto field, keywords have to be given as raw identifiers
+fn proto_ident_from(name: &str, span: Span) -> syn::Result<Ident> {
+    let mut ident: Ident = syn::parse_str(name).map_err(|_| {
+        syn::Error::new(
+            span,
+            format!(
+                "`{name}` is not a valid field name, keywords have to be written as `r#{name}`"
+            ),
+        )
+    })?;
+    ident.set_span(span);
+    Ok(ident)
+}
+
+/// The proto field name of the oneof variant, prost turns the snake case field names in to the pascal
+/// case variant names
+pub fn oneof_field_name(variant: &Ident) -> String {
+    let mut result = String::new();
+    for (i, c) in unraw(&variant.to_string()).chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                result.push('_');
+            }
+            result.extend(c.to_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+fn unraw(name: &str) -> String {
+    name.strip_prefix("r#").unwrap_or(name).to_string()
+}
+
+fn proto_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("proto"))
+}
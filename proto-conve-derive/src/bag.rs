@@ -0,0 +1,230 @@
+//! Generating the conversions of structs mapped to the repeated key and value entries.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::DeriveInput;
+
+use crate::attr::{is_option, Container, Field, FieldDefault};
+use crate::expand::validate_container;
+
+/// Check the bag can be converted, the fields are single values or the leftover map
+fn check(input: &DeriveInput, container: &Container, fields: &[Field]) -> syn::Result<()> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "attribute bags can not be generic",
+        ));
+    }
+    if container.targets()?.len() > 1 || container.validate_on_to_proto {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "attribute bags can not have named mappings or `validate_on_to_proto`",
+        ));
+    }
+    let mut leftovers = fields.iter().filter(|field| field.leftover);
+    if let (Some(_), Some(second)) = (leftovers.next(), leftovers.next()) {
+        return Err(syn::Error::new(
+            second.ident.span(),
+            "only one field can keep the leftover keys",
+        ));
+    }
+    for field in fields {
+        let nested = !field.parents.is_empty() || field.merge || field.boxed;
+        let collection = field.key_with.is_some()
+            || field.set
+            || field.skip_default
+            || field.default_from_ctx
+            || !field.siblings.is_empty()
+            || field.transform
+            || field.required_nonzero
+            || field.enumeration.is_some();
+        let leftover =
+            field.leftover && (field.skip || field.default.is_some() || field.from_with.is_some());
+        if nested || collection || leftover {
+            return Err(syn::Error::new(
+                field.ident.span(),
+                "the fields of attribute bags have a single value of a key, converted with \
+                 `rename`, `default`, `with` or `validate`, or they keep the `leftover` keys",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// `FromProto` of the repeated entries converting in to the struct
+pub fn from_proto(
+    input: &DeriveInput,
+    container: &Container,
+    fields: &[Field],
+) -> syn::Result<TokenStream> {
+    check(input, container, fields)?;
+    let ident = &input.ident;
+    let proto_type = container.proto_type()?;
+    let from_proto = container.trait_path("FromProto");
+
+    let mut slots = Vec::new();
+    let mut arms = Vec::new();
+    let mut values = Vec::new();
+    let mut leftover = None;
+    for field in fields {
+        let name = field.ident;
+        if field.skip {
+            values.push(quote!(#name: ::core::default::Default::default()));
+            continue;
+        }
+        if field.leftover {
+            leftover = Some(quote! {
+                _ => ::proto_conve::bag::leftover(&mut __leftover, key, value)?,
+            });
+            values.push(quote!(#name: __leftover));
+            continue;
+        }
+        let key = &field.proto_name;
+        let local = format_ident!("__field_{}", name);
+        slots.push(quote! {
+            let mut #local: ::core::option::Option<::std::string::String> = None;
+        });
+        arms.push(quote!(#key => ::proto_conve::bag::assign(&mut #local, &key, value)?,));
+
+        let convert = match &field.from_with {
+            Some(function) => quote!(#function),
+            None => quote!(::proto_conve::bag::parse),
+        };
+        let in_field = quote!(|error| ::proto_conve::error::in_field(error, #key));
+        let value = match &field.default {
+            _ if is_option(field.ty) => quote_spanned! {field.ty.span()=>
+                #local.map(#convert).transpose().map_err(#in_field)?
+            },
+            None => quote_spanned! {field.ty.span()=>
+                match #local {
+                    Some(value) => #convert(value).map_err(#in_field)?,
+                    None => {
+                        return Err(::proto_conve::trace::failed::<Self, Self::Result>(
+                            ::proto_conve::error::missing_field(#key),
+                        ))
+                    }
+                }
+            },
+            Some(default) => {
+                let default = match default {
+                    FieldDefault::Trait => quote!(::core::default::Default::default()),
+                    FieldDefault::Function(path) => quote!(#path()),
+                };
+                quote_spanned! {field.ty.span()=>
+                    match #local {
+                        Some(value) => #convert(value).map_err(#in_field)?,
+                        None => #default,
+                    }
+                }
+            }
+        };
+        let value = match &field.validate {
+            None => value,
+            Some(validate) => {
+                let ty = field.ty;
+                quote_spanned! {validate.span()=>
+                    {
+                        let value: #ty = #value;
+                        #validate(&value).map_err(#in_field)?;
+                        value
+                    }
+                }
+            }
+        };
+        values.push(quote!(#name: #value));
+    }
+    let (leftovers, leftover) = match leftover {
+        Some(leftover) => (
+            Some(quote!(let mut __leftover = ::std::collections::HashMap::new();)),
+            leftover,
+        ),
+        // the unknown keys are ignored, like the unknown fields of messages
+        None => (None, quote!(_ => {})),
+    };
+    let result = match &container.validate {
+        None => quote!(Ok(#ident { #(#values,)* })),
+        Some(validate) => {
+            let validate = validate_container(validate, ident, quote!(&result));
+            quote! {
+                let result = #ident {
+                    #(#values,)*
+                };
+                #validate
+                Ok(result)
+            }
+        }
+    };
+
+    Ok(quote! {
+        impl #from_proto for ::std::vec::Vec<#proto_type> {
+            type Result = #ident;
+
+            fn from_proto(self) -> ::proto_conve::__private::anyhow::Result<Self::Result> {
+                #(#slots)*
+                #leftovers
+                for entry in self {
+                    let #proto_type { key, value } = entry;
+                    match key.as_str() {
+                        #(#arms)*
+                        #leftover
+                    }
+                }
+                #result
+            }
+        }
+    })
+}
+
+/// `ToProto` of the struct converting in to the entries in the order of the fields, followed by the
+/// leftover ones sorted by the key
+pub fn to_proto(
+    input: &DeriveInput,
+    container: &Container,
+    fields: &[Field],
+) -> syn::Result<TokenStream> {
+    check(input, container, fields)?;
+    let ident = &input.ident;
+    let proto_type = container.proto_type()?;
+    let to_proto = container.trait_path("ToProto");
+
+    let entries = fields.iter().filter(|field| !field.skip).map(|field| {
+        let name = field.ident;
+        if field.leftover {
+            return quote! {
+                for (key, value) in ::proto_conve::bag::sorted(&self.#name) {
+                    result.push(entry(::core::clone::Clone::clone(key), ::core::clone::Clone::clone(value)));
+                }
+            };
+        }
+        let key = &field.proto_name;
+        let convert = match &field.to_with {
+            Some(function) => quote!(#function),
+            None => quote!(::std::string::ToString::to_string),
+        };
+        if is_option(field.ty) {
+            quote_spanned! {field.ty.span()=>
+                if let Some(value) = &self.#name {
+                    result.push(entry(#key.to_owned(), #convert(value)));
+                }
+            }
+        } else {
+            quote_spanned! {field.ty.span()=>
+                result.push(entry(#key.to_owned(), #convert(&self.#name)));
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #to_proto for #ident {
+            type Result = ::std::vec::Vec<#proto_type>;
+
+            fn to_proto(&self) -> Self::Result {
+                let entry = |key, value| #proto_type { key, value };
+                let mut result = ::std::vec::Vec::new();
+                #(#entries)*
+                result
+            }
+        }
+    })
+}
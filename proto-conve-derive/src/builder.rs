@@ -0,0 +1,167 @@
+//! Generating the builders of the proto messages, setting the fields from the rust values.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput};
+
+use crate::attr::{is_option, option_inner, Container, Field};
+use crate::expand;
+
+pub fn to_proto_builder(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    if !matches!(input.data, Data::Struct(_)) {
+        return Err(syn::Error::new(
+            ident.span(),
+            "`ToProtoBuilder` can only be derived for structs",
+        ));
+    }
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "`ToProtoBuilder` can not be derived for generic structs",
+        ));
+    }
+    let container = Container::from_ast(input)?;
+    if container.attr_bag {
+        return Err(syn::Error::new(
+            ident.span(),
+            "`ToProtoBuilder` can not be derived for attribute bags",
+        ));
+    }
+    let proto_type = container.proto_type()?;
+    let fields = expand::fields(input, &container, None, None)?;
+    let builder = format_ident!("{}ProtoBuilder", ident);
+    let to_proto_field = container.trait_path("ToProtoField");
+
+    let mut setters = Vec::new();
+    let mut required = Vec::new();
+    for field in fields.iter().filter(|field| !field.skip) {
+        if field.transform {
+            return Err(syn::Error::new(
+                field.ident.span(),
+                "`transform` fields can not be set by `ToProtoBuilder`, their encoding can fail",
+            ));
+        }
+        let name = field.ident;
+        let name_string = name.to_string();
+        let name_string = name_string.strip_prefix("r#").unwrap_or(&name_string);
+        let maybe = format_ident!("maybe_{}", name_string);
+        let assign = assign(field);
+        let names = field
+            .parents
+            .iter()
+            .map(|(_, name)| name.as_str())
+            .chain([field.proto_name.as_str()])
+            .collect::<Vec<_>>()
+            .join(".");
+        let doc = format!("Set `{names}` converted from `{name_string}`");
+        let maybe_doc = format!("Set `{names}` converted from `{name_string}`, if it is there");
+
+        let (ty, value) = match option_inner(field.ty) {
+            Some(_) if field.to_with.is_some() || field.boxed => {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "the optional fields of `ToProtoBuilder` are converted with the traits, they can \
+                     not have `with`, `intern`, `cached`, `enumeration` or `boxed`",
+                ));
+            }
+            Some(inner) => (inner, quote!(#to_proto_field::to_proto_field(value))),
+            None => (
+                field.ty,
+                expand::to_proto_value(field, &container, quote!(value)),
+            ),
+        };
+        let value = non_default(field, value);
+        if is_option(field.ty) || field.default.is_some() {
+            setters.push(quote! {
+                #[doc = #doc]
+                pub fn #name(self, value: &#ty) -> Self {
+                    self.#maybe(::core::option::Option::Some(value))
+                }
+
+                #[doc = #maybe_doc]
+                pub fn #maybe(mut self, value: ::core::option::Option<&#ty>) -> Self {
+                    let value = match value {
+                        ::core::option::Option::Some(value) => #value,
+                        ::core::option::Option::None => ::core::default::Default::default(),
+                    };
+                    #assign
+                    self
+                }
+            });
+            continue;
+        }
+
+        let index = required.len();
+        required.push(names);
+        setters.push(quote! {
+            #[doc = #doc]
+            pub fn #name(mut self, value: &#ty) -> Self {
+                let value = #value;
+                #assign
+                self.set[#index] = true;
+                self
+            }
+        });
+    }
+
+    let vis = &input.vis;
+    let count = required.len();
+    let indexes = 0..count;
+    let builder_doc = format!(
+        "The builder of `{}` setting the fields from the ones of `{ident}`",
+        quote!(#proto_type).to_string().replace(' ', "")
+    );
+    Ok(quote! {
+        #[doc = #builder_doc]
+        #vis struct #builder {
+            proto: #proto_type,
+            set: [bool; #count],
+        }
+
+        impl #builder {
+            /// The builder without any fields set
+            pub fn new() -> Self {
+                Self {
+                    proto: ::core::default::Default::default(),
+                    set: [false; #count],
+                }
+            }
+
+            #(#setters)*
+
+            /// The proto message, the required fields have to be set
+            pub fn build(self) -> ::proto_conve::__private::anyhow::Result<#proto_type> {
+                ::proto_conve::builder::check(&[#((#required, self.set[#indexes]),)*])?;
+                Ok(self.proto)
+            }
+        }
+
+        impl ::core::default::Default for #builder {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    })
+}
+
+/// Assign the converted `value` to the proto field, creating its nested messages
+fn assign(field: &Field) -> TokenStream {
+    let proto_ident = &field.proto_ident;
+    let parents = field.parents.iter().map(|(ident, _)| {
+        quote!(let parent = parent.#ident.get_or_insert_with(::core::default::Default::default);)
+    });
+    quote! {
+        let parent = &mut self.proto;
+        #(#parents)*
+        parent.#proto_ident = value;
+    }
+}
+
+/// The proto value, which is set only when it is not the default, with `skip_default`
+fn non_default(field: &Field, value: TokenStream) -> TokenStream {
+    match field.skip_default {
+        true => quote!(::proto_conve::__private::non_default(#value)),
+        false => value,
+    }
+}
@@ -0,0 +1,61 @@
+//! Case transformations of the field names for `#[proto(rename_all = "...")]`.
+
+use syn::LitStr;
+
+/// The case of the proto field names
+#[derive(Clone, Copy)]
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+}
+
+impl RenameRule {
+    pub fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        match lit.value().as_str() {
+            "lowercase" => Ok(RenameRule::Lower),
+            "UPPERCASE" => Ok(RenameRule::Upper),
+            "PascalCase" => Ok(RenameRule::Pascal),
+            "camelCase" => Ok(RenameRule::Camel),
+            "snake_case" => Ok(RenameRule::Snake),
+            "SCREAMING_SNAKE_CASE" => Ok(RenameRule::ScreamingSnake),
+            _ => Err(syn::Error::new(
+                lit.span(),
+                "unknown rename rule, expected one of \"lowercase\", \"UPPERCASE\", \"PascalCase\", \
+                 \"camelCase\", \"snake_case\", \"SCREAMING_SNAKE_CASE\"",
+            )),
+        }
+    }
+
+    /// Apply the rule to the snake case rust field name
+    pub fn apply(self, name: &str) -> String {
+        match self {
+            RenameRule::Lower | RenameRule::Snake => name.to_string(),
+            RenameRule::Upper | RenameRule::ScreamingSnake => name.to_uppercase(),
+            RenameRule::Pascal => pascal(name),
+            RenameRule::Camel => {
+                let pascal = pascal(name);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_lowercase().chain(chars).collect(),
+                    None => pascal,
+                }
+            }
+        }
+    }
+}
+
+fn pascal(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,186 @@
+//! Generating the CBOR encoding of the structs and enums with the field mapping of their proto
+//! conversions.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields};
+
+use crate::attr::{Container, FieldDefault};
+use crate::expand::{self, validate_container};
+
+pub fn proto_cbor(input: &DeriveInput) -> syn::Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "`ProtoCbor` can not be derived for generic types",
+        ));
+    }
+    let container = Container::from_ast(input)?;
+    match &input.data {
+        Data::Enum(data) => {
+            if container.oneof.is_some()
+                || data
+                    .variants
+                    .iter()
+                    .any(|variant| !matches!(variant.fields, Fields::Unit))
+            {
+                return Err(syn::Error::new(
+                    input.ident.span(),
+                    "`ProtoCbor` can only be derived for the fieldless enums",
+                ));
+            }
+            Ok(enum_cbor(input, &container))
+        }
+        _ => struct_cbor(input, &container),
+    }
+}
+
+/// The enums are their proto enum values, converted with `FromProtoAlias` and `ToProtoAlias`
+fn enum_cbor(input: &DeriveInput, container: &Container) -> TokenStream {
+    let ident = &input.ident;
+    let wire = container.enum_type();
+    let to_cbor = container.trait_path("ToCbor");
+    let from_cbor = container.trait_path("FromCbor");
+    let to_proto_alias = container.trait_path("ToProtoAlias");
+    let from_proto_alias = container.trait_path("FromProtoAlias");
+    quote! {
+        impl #to_cbor for #ident {
+            fn to_cbor_value(&self) -> ::proto_conve::__private::ciborium::Value {
+                #to_cbor::to_cbor_value(&#to_proto_alias::<#wire>::to_proto_alias(self))
+            }
+        }
+
+        impl #from_cbor for #ident {
+            fn from_cbor_value(
+                value: ::proto_conve::__private::ciborium::Value,
+            ) -> ::proto_conve::__private::anyhow::Result<Self> {
+                #from_proto_alias::<#wire>::from_proto_alias(
+                    <#wire as #from_cbor>::from_cbor_value(value)?,
+                )
+            }
+
+            // the missing field is the default proto value
+            fn from_cbor_missing() -> ::core::option::Option<Self> {
+                #from_proto_alias::<#wire>::from_proto_alias(::core::default::Default::default()).ok()
+            }
+        }
+    }
+}
+
+/// The structs are the maps of their fields keyed by the proto field names
+fn struct_cbor(input: &DeriveInput, container: &Container) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let fields = expand::fields(input, container, None, None)?;
+    let to_cbor = container.trait_path("ToCbor");
+    let from_cbor = container.trait_path("FromCbor");
+
+    let mut writes = Vec::new();
+    let mut values = Vec::new();
+    for field in &fields {
+        let name = field.ident;
+        if field.skip {
+            values.push(quote!(#name: ::core::default::Default::default()));
+            continue;
+        }
+        if !field.parents.is_empty()
+            || field.key_with.is_some()
+            || field.set
+            || field.validate.is_some()
+            || field.implicit
+            || field.default_from_ctx
+            || !field.siblings.is_empty()
+        {
+            return Err(syn::Error::new(
+                name.span(),
+                "`ProtoCbor` does not support `path`, `key`, `key_with`, `set`, `validate`, \
+                 `presence`, `default_from_ctx` and `siblings` on the fields",
+            ));
+        }
+        if field.from_with.is_some() != field.to_with.is_some() {
+            return Err(syn::Error::new(
+                name.span(),
+                "`ProtoCbor` converts the fields with `with` in both directions, or with the traits",
+            ));
+        }
+        let proto_name = &field.proto_name;
+
+        let value = match &field.to_with {
+            Some(to_with) => quote!(#to_cbor::to_cbor_value(&#to_with(&self.#name))),
+            None => quote!(#to_cbor::to_cbor_value(&self.#name)),
+        };
+        let write = quote!(fields.push((#proto_name, #value)););
+        writes.push(match field.skip_default {
+            true => quote! {
+                if self.#name != ::core::default::Default::default() {
+                    #write
+                }
+            },
+            false => write,
+        });
+
+        let missing = quote!(::proto_conve::error::missing_field(#proto_name));
+        let value = match &field.from_with {
+            Some(from_with) => quote! {
+                match fields.convert(#proto_name, |value| #from_with(#from_cbor::from_cbor_value(value)?))? {
+                    ::core::option::Option::Some(value) => value,
+                    ::core::option::Option::None => {
+                        let proto = #from_cbor::from_cbor_missing().ok_or_else(|| #missing)?;
+                        #from_with(proto)
+                            .map_err(|error| ::proto_conve::error::in_field(error, #proto_name))?
+                    }
+                }
+            },
+            None => {
+                let missing = match &field.default {
+                    Some(FieldDefault::Trait) => quote!(::core::default::Default::default()),
+                    Some(FieldDefault::Function(function)) => quote!(#function()),
+                    None => quote! {
+                        match #from_cbor::from_cbor_missing() {
+                            ::core::option::Option::Some(value) => value,
+                            ::core::option::Option::None => return Err(#missing),
+                        }
+                    },
+                };
+                quote! {
+                    match fields.convert(#proto_name, #from_cbor::from_cbor_value)? {
+                        ::core::option::Option::Some(value) => value,
+                        ::core::option::Option::None => #missing,
+                    }
+                }
+            }
+        };
+        values.push(quote!(#name: #value));
+    }
+
+    let result = match &container.validate {
+        None => quote!(Ok(#ident { #(#values,)* })),
+        Some(validate) => {
+            let validate = validate_container(validate, ident, quote!(&result));
+            quote! {
+                let result = #ident { #(#values,)* };
+                #validate
+                Ok(result)
+            }
+        }
+    };
+    Ok(quote! {
+        impl #to_cbor for #ident {
+            fn to_cbor_value(&self) -> ::proto_conve::__private::ciborium::Value {
+                #[allow(unused_mut)]
+                let mut fields = ::std::vec::Vec::new();
+                #(#writes)*
+                ::proto_conve::cbor::map(fields)
+            }
+        }
+
+        impl #from_cbor for #ident {
+            #[allow(unused_mut, unused_variables)]
+            fn from_cbor_value(
+                value: ::proto_conve::__private::ciborium::Value,
+            ) -> ::proto_conve::__private::anyhow::Result<Self> {
+                let mut fields = ::proto_conve::cbor::Fields::new(value)?;
+                #result
+            }
+        }
+    })
+}
@@ -0,0 +1,54 @@
+//! Generating the shared default instances and the field wise default checks of the proto messages.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields};
+
+pub fn proto_default(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    // the statics of the default instances can not be generic
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "`ProtoDefault` can not be derived for generic types, implement it by hand",
+        ));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`ProtoDefault` can only be derived for the structs of the proto messages",
+            ))
+        }
+    };
+    let checks: Vec<_> = match fields {
+        Fields::Named(named) => named
+            .named
+            .iter()
+            .map(|field| {
+                let name = &field.ident;
+                quote!(&& ::proto_conve::proto_default::ProtoDefault::is_proto_default(&self.#name))
+            })
+            .collect(),
+        Fields::Unnamed(unnamed) => (0..unnamed.unnamed.len())
+            .map(|index| {
+                let index = syn::Index::from(index);
+                quote!(&& ::proto_conve::proto_default::ProtoDefault::is_proto_default(&self.#index))
+            })
+            .collect(),
+        Fields::Unit => Vec::new(),
+    };
+    Ok(quote! {
+        impl ::proto_conve::proto_default::ProtoDefault for #ident {
+            fn proto_default() -> &'static Self {
+                static DEFAULT: ::std::sync::OnceLock<#ident> = ::std::sync::OnceLock::new();
+                DEFAULT.get_or_init(::core::default::Default::default)
+            }
+
+            fn is_proto_default(&self) -> bool {
+                true #(#checks)*
+            }
+        }
+    })
+}
@@ -0,0 +1,438 @@
+//! Generating the conversions of fieldless enums mapped to the proto enum values.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{DataEnum, DeriveInput, Fields, Ident};
+
+use crate::attr::{Container, Unknown, Variant};
+
+/// The variants with their attributes, all of them have to be fieldless
+fn variants(data: &DataEnum) -> syn::Result<Vec<Variant<'_>>> {
+    data.variants
+        .iter()
+        .map(|variant| {
+            if !matches!(variant.fields, Fields::Unit) {
+                return Err(syn::Error::new_spanned(
+                    &variant.fields,
+                    "only fieldless enums can be converted in to proto enum values, enums with data \
+                     need `#[proto(oneof = \"path::to::Oneof\")]`",
+                ));
+            }
+            Variant::from_ast(variant)
+        })
+        .collect()
+}
+
+/// The name of the const conversions, like `to_proto_i32`, when the wire type is a primitive integer
+fn const_name(container: &Container, direction: &str) -> Option<Ident> {
+    let wire_type = match container.proto_type() {
+        Ok(proto_type) => proto_type.get_ident()?.to_string(),
+        Err(_) => "i32".to_owned(),
+    };
+    let integers = ["i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64"];
+    integers
+        .contains(&wire_type.as_str())
+        .then(|| format_ident!("{}_proto_{}", direction, wire_type))
+}
+
+/// The wire value of the variant
+fn value(input: &DeriveInput, container: &Container, variant: &Variant) -> TokenStream {
+    let wire_type = container.enum_type();
+    if let Some(value) = &variant.value {
+        return quote!(#value);
+    }
+    if let Some(proto_enum) = &container.proto_enum {
+        let proto_ident = &variant.proto_ident;
+        return quote!(#proto_enum::#proto_ident as #wire_type);
+    }
+    let ident = &input.ident;
+    let variant = variant.ident;
+    quote!(#ident::#variant as #wire_type)
+}
+
+/// The proto strings of two variants are the same, also in the other case with `case_insensitive`
+fn check_names(variants: &[Variant], case_insensitive: bool) -> syn::Result<()> {
+    let mut seen: Vec<(String, &Ident)> = Vec::new();
+    let names = variants.iter().flat_map(|variant| {
+        std::iter::once(&variant.name)
+            .chain(&variant.aliases)
+            .map(move |name| (name, variant.ident))
+    });
+    for (name, ident) in names {
+        let key = match case_insensitive {
+            true => name.value().to_ascii_lowercase(),
+            false => name.value(),
+        };
+        match seen.iter().find(|(seen, _)| *seen == key) {
+            Some((_, other)) if *other != ident => {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!(
+                        "the proto string {:?} of `{ident}` is ambiguous, it is also the one of `{other}`",
+                        name.value()
+                    ),
+                ));
+            }
+            Some(_) => {}
+            None => seen.push((key, ident)),
+        }
+    }
+    Ok(())
+}
+
+/// The conversions from the proto strings of `#[proto(string)]`
+fn from_proto_string(
+    ident: &Ident,
+    variants: &[Variant],
+    container: &Container,
+) -> syn::Result<TokenStream> {
+    check_names(variants, container.case_insensitive)?;
+    let name = ident.to_string();
+    let from_proto_alias = container.trait_path("FromProtoAlias");
+    let case_insensitive = container.case_insensitive;
+    let names = variants.iter().map(|variant| {
+        let canonical = &variant.name;
+        let aliases = &variant.aliases;
+        let message = format!("use {:?}, the canonical name of {name}", canonical.value());
+        quote!((#canonical, &[#(#aliases),*], #message))
+    });
+    let arms = variants.iter().enumerate().map(|(index, variant)| {
+        let variant = variant.ident;
+        quote!(#index => #ident::#variant)
+    });
+    let canonical = variants.iter().map(|variant| &variant.name);
+    let index_arms = variants.iter().enumerate().map(|(index, variant)| {
+        let variant = variant.ident;
+        quote!(#ident::#variant => #index)
+    });
+    Ok(quote! {
+        #[allow(dead_code)]
+        impl #ident {
+            /// The variant of the proto string and how it matched, the lenient matches record the
+            /// deprecation notice
+            pub fn from_proto_name(
+                value: &str,
+            ) -> ::proto_conve::__private::anyhow::Result<(Self, ::proto_conve::enumeration::NameMatch)> {
+                const NAMES: &[::proto_conve::enumeration::VariantNames] = &[#(#names),*];
+                let (index, matched) = ::proto_conve::enumeration::from_proto_name(
+                    value,
+                    #name,
+                    NAMES,
+                    #case_insensitive,
+                )?;
+                let variant = match index {
+                    #(#arms,)*
+                    _ => unreachable!("the index of the names"),
+                };
+                Ok((variant, matched))
+            }
+        }
+
+        impl #from_proto_alias<::std::string::String> for #ident {
+            fn from_proto_alias(
+                value: ::std::string::String,
+            ) -> ::proto_conve::__private::anyhow::Result<Self> {
+                Self::from_proto_name(&value).map(|(variant, _)| variant)
+            }
+        }
+
+        impl ::proto_conve::enumeration::ProtoNamed for #ident {
+            const NAMES: &'static [&'static str] = &[#(#canonical),*];
+
+            fn name_index(&self) -> usize {
+                match self {
+                    #(#index_arms,)*
+                }
+            }
+
+            fn match_name(
+                value: &str,
+            ) -> ::proto_conve::__private::anyhow::Result<(Self, ::proto_conve::enumeration::NameMatch)> {
+                Self::from_proto_name(value)
+            }
+        }
+    })
+}
+
+pub fn from_proto(
+    input: &DeriveInput,
+    data: &DataEnum,
+    container: &Container,
+) -> syn::Result<TokenStream> {
+    let variants = variants(data)?;
+    let ident = &input.ident;
+    let name = ident.to_string();
+    let wire_type = container.enum_type();
+    let from_proto_alias = container.trait_path("FromProtoAlias");
+
+    let mut consts = Vec::new();
+    let mut constants = Vec::new();
+    let mut arms = Vec::new();
+    let mut const_arms = Vec::new();
+    let mut names = Vec::new();
+    for (index, variant) in variants.iter().enumerate() {
+        let constant = format_ident!("__VALUE_{}", index);
+        let value = value(input, container, variant);
+        let variant = variant.ident;
+        consts.push(quote!(const #constant: #wire_type = #value;));
+        arms.push(quote!(#constant => Ok(#ident::#variant)));
+        const_arms.push(quote!(#constant => Some(#ident::#variant)));
+        names.push(variant.to_string());
+        constants.push(constant);
+    }
+    // the duplicate values fail to compile, instead of the later variants being unreachable
+    let check = quote! {
+        #[allow(clippy::unnecessary_cast)]
+        const _: () = {
+            #(#consts)*
+            ::proto_conve::__private::check_enum_values(
+                #name,
+                &[#(#names),*],
+                &[#(#constants as i64),*],
+            )
+        };
+    };
+    let const_from = const_name(container, "from").map(|from| {
+        quote! {
+            #[allow(dead_code)]
+            impl #ident {
+                /// The variant of the proto value, `None` for the unknown values
+                pub const fn #from(value: #wire_type) -> Option<Self> {
+                    #(#consts)*
+                    match value {
+                        #(#const_arms,)*
+                        _ => None,
+                    }
+                }
+            }
+        }
+    });
+    let known = match constants.is_empty() {
+        true => quote!(false),
+        false => quote!(matches!(value, #(#constants)|*)),
+    };
+    let unknown = match &container.unknown {
+        Unknown::Error => quote! {
+            value => Err(::proto_conve::enumeration::unknown_value(value, #name))
+        },
+        Unknown::Variant(variant) if container.open => {
+            return Err(syn::Error::new(
+                variant.span(),
+                "`unknown` can not be combined with `open`, the unknown values are kept by `OpenEnum`",
+            ));
+        }
+        Unknown::Variant(variant) => {
+            if !variants.iter().any(|v| v.ident == variant) {
+                return Err(syn::Error::new(
+                    variant.span(),
+                    format!("`{variant}` is not a variant of `{ident}`"),
+                ));
+            }
+            quote!(_ => Ok(#ident::#variant))
+        }
+    };
+
+    let open = container.open.then(|| {
+        quote! {
+            /// The unknown values are kept, so the conversion never fails
+            impl #from_proto_alias<i32> for ::proto_conve::OpenEnum<#ident> {
+                fn from_proto_alias(value: i32) -> ::proto_conve::__private::anyhow::Result<Self> {
+                    Ok(match <#ident as #from_proto_alias<i32>>::from_proto_alias(value) {
+                        Ok(known) => ::proto_conve::OpenEnum::Known(known),
+                        Err(_) => ::proto_conve::OpenEnum::Unknown(value),
+                    })
+                }
+            }
+        }
+    });
+
+    let string = match container.string {
+        true => Some(from_proto_string(ident, &variants, container)?),
+        false => None,
+    };
+
+    // the enums of the `i32` values are the elements of `EnumSet`
+    let i32_values = match container.proto_type() {
+        Ok(proto_type) => proto_type.is_ident("i32"),
+        Err(_) => true,
+    };
+    let enum_set = i32_values.then(|| {
+        let open = container.open;
+        let to_arms = variants.iter().zip(&constants).map(|(variant, constant)| {
+            let variant = variant.ident;
+            quote!(#ident::#variant => #constant)
+        });
+        quote! {
+            impl ::proto_conve::enum_set::EnumSetValue for #ident {
+                const VALUES: &'static [i32] = {
+                    #(#consts)*
+                    &[#(#constants),*]
+                };
+                const OPEN: bool = #open;
+
+                fn to_value(&self) -> i32 {
+                    #(#consts)*
+                    match *self {
+                        #(#to_arms,)*
+                    }
+                }
+
+                fn from_value(value: i32) -> ::core::option::Option<Self> {
+                    #(#consts)*
+                    match value {
+                        #(#const_arms,)*
+                        _ => None,
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #check
+
+        #const_from
+
+        #enum_set
+
+        #string
+
+        impl #from_proto_alias<#wire_type> for #ident {
+            fn from_proto_alias(value: #wire_type) -> ::proto_conve::__private::anyhow::Result<Self> {
+                #(#consts)*
+                match value {
+                    #(#arms,)*
+                    #unknown,
+                }
+            }
+
+            /// The lenient mode converts the unknown values in to the variant of the default value
+            fn from_proto_alias_mode(
+                value: #wire_type,
+                mode: ::proto_conve::mode::ConversionMode,
+            ) -> ::proto_conve::__private::anyhow::Result<Self> {
+                match <Self as #from_proto_alias<#wire_type>>::from_proto_alias(value) {
+                    Err(error) if mode.is_lenient() => {
+                        <Self as #from_proto_alias<#wire_type>>::from_proto_alias(
+                            ::core::default::Default::default(),
+                        )
+                        .map_err(|_| error)
+                    }
+                    result => result,
+                }
+            }
+
+            /// The unknown values are reported, they are converted in to the variant for them or the
+            /// variant of the default value
+            #[allow(clippy::unnecessary_cast)]
+            fn from_proto_alias_lossy(
+                value: #wire_type,
+            ) -> ::proto_conve::__private::anyhow::Result<(Self, ::proto_conve::lossy::ConversionReport)> {
+                #(#consts)*
+                let result = <Self as #from_proto_alias<#wire_type>>::from_proto_alias_mode(
+                    value,
+                    ::proto_conve::mode::ConversionMode::Lenient,
+                )?;
+                let report = if #known {
+                    ::proto_conve::lossy::ConversionReport::new()
+                } else {
+                    ::proto_conve::lossy::ConversionReport::event(
+                        ::proto_conve::lossy::LossyEvent::UnknownEnum { raw: value as i64 },
+                    )
+                };
+                Ok((result, report))
+            }
+        }
+
+        #open
+    })
+}
+
+pub fn to_proto(
+    input: &DeriveInput,
+    data: &DataEnum,
+    container: &Container,
+) -> syn::Result<TokenStream> {
+    let variants = variants(data)?;
+    let ident = &input.ident;
+    let wire_type = container.enum_type();
+    let to_proto_alias = container.trait_path("ToProtoAlias");
+
+    let arms: Vec<_> = variants
+        .iter()
+        .map(|variant| {
+            let value = value(input, container, variant);
+            let variant = variant.ident;
+            quote!(#ident::#variant => #value)
+        })
+        .collect();
+    // the match is exhaustive, so the added variants have to be mapped
+    let const_to = const_name(container, "to").map(|to| {
+        quote! {
+            #[allow(dead_code)]
+            impl #ident {
+                /// The proto value of the variant
+                pub const fn #to(self) -> #wire_type {
+                    match self {
+                        #(#arms,)*
+                    }
+                }
+            }
+        }
+    });
+
+    let open = container.open.then(|| {
+        quote! {
+            /// The unknown values are written back as they were received
+            impl #to_proto_alias<i32> for ::proto_conve::OpenEnum<#ident> {
+                fn to_proto_alias(&self) -> i32 {
+                    match self {
+                        ::proto_conve::OpenEnum::Known(known) => #to_proto_alias::<i32>::to_proto_alias(known),
+                        ::proto_conve::OpenEnum::Unknown(value) => *value,
+                    }
+                }
+            }
+        }
+    });
+
+    let string = container.string.then(|| {
+        let arms = variants.iter().map(|variant| {
+            let name = &variant.name;
+            let variant = variant.ident;
+            quote!(#ident::#variant => #name)
+        });
+        quote! {
+            #[allow(dead_code)]
+            impl #ident {
+                /// The canonical proto string of the variant
+                pub const fn proto_name(&self) -> &'static str {
+                    match self {
+                        #(#arms,)*
+                    }
+                }
+            }
+
+            impl #to_proto_alias<::std::string::String> for #ident {
+                fn to_proto_alias(&self) -> ::std::string::String {
+                    ::std::borrow::ToOwned::to_owned(self.proto_name())
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #const_to
+
+        #string
+
+        impl #to_proto_alias<#wire_type> for #ident {
+            fn to_proto_alias(&self) -> #wire_type {
+                match self {
+                    #(#arms,)*
+                }
+            }
+        }
+
+        #open
+    })
+}
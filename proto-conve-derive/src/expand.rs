@@ -0,0 +1,1618 @@
+//! Generating the conversion impls.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Fields, FieldsNamed, Ident};
+
+use crate::attr::{
+    is_bytes, is_option, version_bounds, Container, Field, FieldDefault, FieldSort, KeyWith, Target,
+};
+use crate::generics::{self, Impl};
+use crate::{arena, bag, enumeration, oneof, redact, reflect, verify, wrapper};
+
+/// How the proto fields are converted
+#[derive(Clone, Copy)]
+enum Conversion<'a> {
+    /// With `FromProto`
+    Plain,
+    /// With `FromProtoVersioned` in the version
+    Versioned(&'a TokenStream),
+    /// With `FromProtoLossy`, adding the events to the report
+    Lossy(&'a TokenStream),
+    /// With `AsyncFromProtoWith`, the `async_with` fields awaiting their lookups in the context
+    Async(&'a TokenStream),
+}
+
+/// The named fields of the struct with their attributes for the mapping and the schema version
+pub fn fields<'a>(
+    input: &'a DeriveInput,
+    container: &Container,
+    mapping: Option<&str>,
+    version: Option<u32>,
+) -> syn::Result<Vec<Field<'a>>> {
+    named_fields(input)?
+        .named
+        .iter()
+        .map(|field| Field::from_ast(field, container, mapping, version))
+        .collect()
+}
+
+/// The named fields of the struct, the only shape supported for now
+fn named_fields(input: &DeriveInput) -> syn::Result<&FieldsNamed> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(fields),
+            _ => Err(syn::Error::new(
+                input.ident.span(),
+                "only structs with named fields can be converted",
+            )),
+        },
+        _ => Err(syn::Error::new(
+            input.ident.span(),
+            "only structs and enums can be converted",
+        )),
+    }
+}
+
+pub fn from_proto(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let container = Container::from_ast(input)?;
+    // the lifetime of the arena is the only parameter of the arena structs
+    if container.arena {
+        return arena::from_proto_in(input, &container);
+    }
+    generics::check(input)?;
+    if let Some(field) = &container.wrapper {
+        return wrapper::from_proto(input, &container, field);
+    }
+    if container.attr_bag {
+        let fields = fields(input, &container, None, None)?;
+        if redact::enabled(&container, &fields) {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "the sensitive fields and `redact` can not be used with `attr_bag`",
+            ));
+        }
+        return bag::from_proto(input, &container, &fields);
+    }
+    if let Data::Enum(data) = &input.data {
+        if container.redact {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "`redact` can only be used on structs",
+            ));
+        }
+        if let Some(oneof) = &container.oneof {
+            return oneof::from_proto(input, data, &container, oneof);
+        }
+        return enumeration::from_proto(input, data, &container);
+    }
+    if let Some(project_from) = &container.project_from {
+        return struct_project_from(input, &container, project_from);
+    }
+    let mut result = TokenStream::new();
+    let targets = container.targets()?;
+    for target in &targets {
+        result.extend(struct_from_proto(input, &container, target)?);
+        let fields = fields(input, &container, target.name, None)?;
+        result.extend(verify::struct_fields(input, target, &fields));
+    }
+    // the mapping of the proto type of `type`
+    if let Some(target) = targets.iter().find(|target| target.name.is_none()) {
+        let fields = fields(input, &container, None, None)?;
+        result.extend(reflect::has_proto_mapping(input, target, &fields));
+    }
+    Ok(result)
+}
+
+/// `FromProto` of the proto type converting in to the struct
+fn struct_from_proto(
+    input: &DeriveInput,
+    container: &Container,
+    target: &Target,
+) -> syn::Result<TokenStream> {
+    let fields = fields(input, container, target.name, None)?;
+    if fields.iter().any(|field| field.async_with.is_some()) {
+        return struct_from_proto_async(input, container, target, &fields);
+    }
+    let exhaustive = exhaustive(container, &fields)?;
+    let ident = &input.ident;
+    let proto_type = target.proto_type;
+    let from_proto = container.trait_path("FromProto");
+    let generics = Impl::new(
+        input,
+        container,
+        "FromProto",
+        container.from_proto_bound.as_deref(),
+        true,
+    );
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let result = generics
+        .projection
+        .ty(&syn::parse_quote!(#ident #ty_generics));
+
+    let (body, nested) = from_proto_body(
+        input,
+        container,
+        &generics,
+        &fields,
+        Conversion::Plain,
+        None,
+    );
+    // the rendering of the message is attached to the errors of the conversion in
+    // `redact::attach_to_errors`
+    let (body, redact) = match redact::enabled(container, &fields) {
+        true => (
+            quote! {
+                let rendered = ::proto_conve::redact::render_for_errors(&self);
+                ::proto_conve::redact::attach(rendered, move || { #body })
+            },
+            Some(redact::proto_redact(input, target, &fields)?),
+        ),
+        false => (body, None),
+    };
+
+    let params = &generics.params;
+    let where_clause = generics.where_clause();
+    // the nested fields are converted in closures returning their results
+    let allow = nested.then(|| quote!(#[allow(clippy::needless_question_mark)]));
+    Ok(quote! {
+        impl #params #from_proto for #proto_type #where_clause {
+            type Result = #result;
+
+            #allow
+            fn from_proto(self) -> ::proto_conve::__private::anyhow::Result<Self::Result> {
+                #exhaustive
+                #body
+            }
+        }
+
+        #redact
+    })
+}
+
+/// `AsyncFromProtoWith` of the proto type converting in to the struct with the lookups of the
+/// `async_with` fields in the `context`, the other fields are converted as in `FromProto`
+fn struct_from_proto_async(
+    input: &DeriveInput,
+    container: &Container,
+    target: &Target,
+    fields: &[Field],
+) -> syn::Result<TokenStream> {
+    let Some(context) = &container.context else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "the `async_with` fields need the `context` of their lookups, like \
+             `#[proto(context = \"Db\")]`",
+        ));
+    };
+    let exhaustive = exhaustive(container, fields)?;
+    let ident = &input.ident;
+    let proto_type = target.proto_type;
+    let async_from_proto = container.trait_path("AsyncFromProtoWith");
+    let generics = Impl::new(
+        input,
+        container,
+        "FromProto",
+        container.from_proto_bound.as_deref(),
+        true,
+    );
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let result = generics
+        .projection
+        .ty(&syn::parse_quote!(#ident #ty_generics));
+
+    let ctx = quote!(ctx);
+    let (body, nested) = from_proto_body(
+        input,
+        container,
+        &generics,
+        fields,
+        Conversion::Async(&ctx),
+        None,
+    );
+    // the lookups can not be awaited in the closure of `redact::attach`, so the result is attached
+    let (body, redact) = match redact::enabled(container, fields) {
+        true => (
+            quote! {
+                let rendered = ::proto_conve::redact::render_for_errors(&self);
+                let result: ::proto_conve::__private::anyhow::Result<Self::Result> =
+                    async move { #body }.await;
+                ::proto_conve::redact::attach(rendered, move || result)
+            },
+            Some(redact::proto_redact(input, target, fields)?),
+        ),
+        false => (body, None),
+    };
+
+    let params = &generics.params;
+    let where_clause = generics.where_clause();
+    let allow = nested.then(|| quote!(#[allow(clippy::needless_question_mark)]));
+    Ok(quote! {
+        impl #params #async_from_proto<#context> for #proto_type #where_clause {
+            type Result = #result;
+
+            #allow
+            async fn from_proto_async(
+                self,
+                #ctx: &#context,
+            ) -> ::proto_conve::__private::anyhow::Result<Self::Result> {
+                #exhaustive
+                #body
+            }
+        }
+
+        #redact
+    })
+}
+
+/// `FromProtoAlias` of the full proto type converting only the fields of the projection, the other
+/// proto fields are dropped without converting them
+fn struct_project_from(
+    input: &DeriveInput,
+    container: &Container,
+    proto_type: &syn::Path,
+) -> syn::Result<TokenStream> {
+    let fields = fields(input, container, None, None)?;
+    let ident = &input.ident;
+    let from_proto_alias = container.trait_path("FromProtoAlias");
+    let generics = Impl::new(
+        input,
+        container,
+        "FromProto",
+        container.from_proto_bound.as_deref(),
+        true,
+    );
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let this = generics
+        .projection
+        .ty(&syn::parse_quote!(#ident #ty_generics));
+    let proto_type = generics.projection.ty(&syn::parse_quote!(#proto_type));
+    let proto_path = generics
+        .projection
+        .expr_path(&syn::parse_quote!(#proto_type));
+
+    let (body, nested) = from_proto_body(
+        input,
+        container,
+        &generics,
+        &fields,
+        Conversion::Plain,
+        Some(&proto_path),
+    );
+    // the closure is never called, the fields missing in the full type fail to compile
+    let full = container.full.as_ref().map(|full| {
+        let names = fields.iter().filter(|field| !field.skip).map(|field| {
+            let name = field.ident;
+            quote_spanned!(name.span()=> let _ = &full.#name;)
+        });
+        quote! {
+            let _ = |full: &#full| {
+                #(#names)*
+            };
+        }
+    });
+
+    let params = &generics.params;
+    let where_clause = generics.where_clause();
+    let allow = nested.then(|| quote!(#[allow(clippy::needless_question_mark)]));
+    Ok(quote! {
+        impl #params #from_proto_alias<#proto_type> for #this #where_clause {
+            #allow
+            fn from_proto_alias(
+                proto: #proto_type,
+            ) -> ::proto_conve::__private::anyhow::Result<Self> {
+                #full
+                #body
+            }
+        }
+    })
+}
+
+/// With `deny_unknown_fields` the proto message is built with all of its fields in a closure, which is
+/// never called, so the proto fields added later, which are not mapped, fail to compile as missing
+fn exhaustive(container: &Container, fields: &[Field]) -> syn::Result<Option<TokenStream>> {
+    if !container.deny_unknown_fields {
+        return Ok(None);
+    }
+    // the fields with `path` are in the outermost parent message
+    let mapped: Vec<(&Ident, &Field)> = fields
+        .iter()
+        .filter(|field| !field.skip)
+        .map(|field| {
+            let name = field
+                .parents
+                .first()
+                .map_or(&field.proto_ident, |(parent, _)| parent);
+            (name, field)
+        })
+        .collect();
+    let mut names: Vec<&Ident> = Vec::new();
+    for (name, field) in &mapped {
+        // the first alternative is the proto field, the others are mapped too, as the siblings
+        let alternatives = field
+            .first_of
+            .iter()
+            .chain(&field.siblings)
+            .map(|(alternative, _)| alternative);
+        for name in std::iter::once(*name).chain(alternatives) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    for unmapped in &container.unmapped {
+        if let Some((_, field)) = mapped.iter().find(|(name, _)| *name == unmapped) {
+            return Err(syn::Error::new(
+                unmapped.span(),
+                format!(
+                    "the proto field `{unmapped}` is mapped by `{}`",
+                    field.ident
+                ),
+            ));
+        }
+        names.push(unmapped);
+    }
+    Ok(Some(quote! {
+        let _ = || -> Self {
+            Self {
+                #(#names: ::proto_conve::exhaustive::unmapped(),)*
+            }
+        };
+    }))
+}
+
+pub fn from_proto_versioned(input: &DeriveInput) -> syn::Result<TokenStream> {
+    generics::check(input)?;
+    let container = Container::from_ast(input)?;
+    if let Data::Enum(_) = &input.data {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`FromProtoVersioned` can only be derived for structs",
+        ));
+    }
+    let mut result = TokenStream::new();
+    for target in container.targets()? {
+        result.extend(struct_from_proto_versioned(input, &container, &target)?);
+    }
+    Ok(result)
+}
+
+/// `FromProtoVersioned` of the proto type, converting with the field attributes of the version
+fn struct_from_proto_versioned(
+    input: &DeriveInput,
+    container: &Container,
+    target: &Target,
+) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let proto_type = target.proto_type;
+    let from_proto_versioned = container.trait_path("FromProtoVersioned");
+    let generics = Impl::new(
+        input,
+        container,
+        "FromProto",
+        container.from_proto_bound.as_deref(),
+        true,
+    );
+    let min = container.min_version.unwrap_or(0);
+    let max = container.max_version;
+    if max.is_some_and(|max| max < min) {
+        return Err(syn::Error::new(
+            ident.span(),
+            "`max_version` has to be after `min_version`",
+        ));
+    }
+
+    // the versions are split in to the ranges with the same field attributes
+    let mut starts = vec![min];
+    for field in &named_fields(input)?.named {
+        starts.extend(version_bounds(field)?);
+    }
+    starts.retain(|&start| min <= start && max.is_none_or(|max| start <= max));
+    starts.sort_unstable();
+    starts.dedup();
+    let version = quote!(version);
+    let mut arms = Vec::new();
+    let mut nested = false;
+    for (index, &start) in starts.iter().enumerate() {
+        let end = match starts.get(index + 1) {
+            Some(next) => Some(next - 1),
+            None => max,
+        };
+        let fields = fields(input, container, target.name, Some(start))?;
+        let conversion = Conversion::Versioned(&version);
+        let (body, has_nested) =
+            from_proto_body(input, container, &generics, &fields, conversion, None);
+        nested |= has_nested;
+        let pattern = match end {
+            Some(end) if end == start => quote!(#start),
+            Some(end) => quote!(#start..=#end),
+            None => quote!(#start..),
+        };
+        arms.push(quote!(#pattern => { #body }));
+    }
+    if min > 0 || max.is_some() {
+        let type_name = ident.to_string();
+        arms.push(quote! {
+            _ => ::core::result::Result::Err(::proto_conve::__private::anyhow::Error::new(
+                ::proto_conve::version::UnsupportedVersion {
+                    version: #version,
+                    type_name: #type_name,
+                },
+            )),
+        });
+    }
+
+    let params = &generics.params;
+    let where_clause = generics.where_clause();
+    let allow = nested.then(|| quote!(#[allow(clippy::needless_question_mark)]));
+    Ok(quote! {
+        impl #params #from_proto_versioned for #proto_type #where_clause {
+            #allow
+            fn from_proto_versioned(
+                self,
+                #version: ::proto_conve::version::SchemaVersion,
+            ) -> ::proto_conve::__private::anyhow::Result<Self::Result> {
+                match #version.get() {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+pub fn from_proto_lossy(input: &DeriveInput) -> syn::Result<TokenStream> {
+    generics::check(input)?;
+    let container = Container::from_ast(input)?;
+    if let Data::Enum(_) = &input.data {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`FromProtoLossy` can only be derived for structs, the enums convert lossily with \
+             `FromProtoAlias`",
+        ));
+    }
+    let mut result = TokenStream::new();
+    for target in container.targets()? {
+        result.extend(struct_from_proto_lossy(input, &container, &target)?);
+    }
+    Ok(result)
+}
+
+pub fn validate_from_proto(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let container = Container::from_ast(input)?;
+    let Data::Struct(_) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`ValidateFromProto` can only be derived for structs, the enums are checked by converting \
+             them",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "`ValidateFromProto` can not be derived for generic structs",
+        ));
+    }
+    if container.arena
+        || container.attr_bag
+        || container.wrapper.is_some()
+        || container.project_from.is_some()
+    {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`ValidateFromProto` can not be derived for the `arena`, `attr_bag`, `wrapper` or \
+             `project_from` structs",
+        ));
+    }
+    let targets = container.targets()?;
+    let Some(target) = targets.iter().find(|target| target.name.is_none()) else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`ValidateFromProto` checks the proto type of `type`, it has no named mappings",
+        ));
+    };
+    struct_validate_from_proto(input, &container, target)
+}
+
+/// The field is converted with the traits only, so the nested messages are checked field by field
+fn converted_with_traits(field: &Field) -> bool {
+    field.from_with.is_none()
+        && field.default.is_none()
+        && !field.default_from_ctx
+        && field.key_with.is_none()
+        && !field.set
+        && !field.boxed
+        && field.validate.is_none()
+        && !field.implicit
+        && field.deprecated.is_none()
+        && field.enumeration.is_none()
+        && field.dual.is_none()
+        && field.first_of.is_empty()
+        && field.siblings.is_empty()
+        && !is_bytes(field.ty)
+}
+
+/// `ValidateFromProto` of the proto type, checking the fields as `FromProto` converts them
+fn struct_validate_from_proto(
+    input: &DeriveInput,
+    container: &Container,
+    target: &Target,
+) -> syn::Result<TokenStream> {
+    let fields = fields(input, container, None, None)?;
+    if let Some(field) = fields
+        .iter()
+        .find(|field| !field.parents.is_empty() || field.async_with.is_some())
+    {
+        return Err(syn::Error::new(
+            field.ident.span(),
+            "`ValidateFromProto` can not check the `path` and `async_with` fields",
+        ));
+    }
+    let proto_type = target.proto_type;
+    let validate_from_proto = container.trait_path("ValidateFromProto");
+    let validate_field = container.trait_path("__validate_field");
+    let generics = Impl::new(input, container, "FromProto", None, true);
+
+    let checks = fields.iter().filter(|field| !field.skip).map(|field| {
+        let proto_ident = &field.proto_ident;
+        let proto_name = &field.proto_name;
+        let ty = field.ty;
+        if converted_with_traits(field) {
+            return quote_spanned! {field.ty.span()=>
+                (&&::proto_conve::dry_run::Check::<#ty, _>::new(&self.#proto_ident))
+                    .validate_field(#proto_name, &mut violations);
+            };
+        }
+        // the other fields are converted from their clones, as `FromProto` converts them
+        let clone = |ident: &Ident| quote!(::core::clone::Clone::clone(&self.#ident));
+        let local = if !field.first_of.is_empty() {
+            let alternatives = field
+                .first_of
+                .iter()
+                .map(|(alternative, _)| clone(alternative));
+            quote!((#(#alternatives,)*))
+        } else if !field.siblings.is_empty() {
+            let oneof = clone(proto_ident);
+            let siblings = field.siblings.iter().map(|(sibling, _)| clone(sibling));
+            quote!((#oneof, (#(#siblings,)*)))
+        } else {
+            clone(proto_ident)
+        };
+        let value = field_from_proto(field, container, &generics, local, Conversion::Plain);
+        quote! {
+            ::proto_conve::dry_run::check::<#ty>(&mut violations, || Ok(#value));
+        }
+    });
+    // the checks of the struct need the converted value
+    let validate = container.validate.as_ref().map(|_| {
+        let from_proto = container.trait_path("FromProto");
+        quote! {
+            if violations.is_empty() {
+                ::proto_conve::dry_run::check(&mut violations, || {
+                    #from_proto::from_proto(::core::clone::Clone::clone(self))
+                });
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #validate_from_proto for #proto_type {
+            // the checks of the fields dispatch on the autoref, see `proto_conve::dry_run::Check`
+            #[allow(clippy::needless_borrow, clippy::needless_question_mark)]
+            fn validate_proto(&self) -> ::std::vec::Vec<::proto_conve::violation::FieldViolation> {
+                #[allow(unused_imports)]
+                use #validate_field::{ViaConvert, ViaOptional, ViaRepeated, ViaRequired};
+
+                let mut violations = ::std::vec::Vec::new();
+                #(#checks)*
+                #validate
+                violations
+            }
+        }
+    })
+}
+
+/// `FromProtoLossy` of the proto type, converting the fields lossily in to the report
+fn struct_from_proto_lossy(
+    input: &DeriveInput,
+    container: &Container,
+    target: &Target,
+) -> syn::Result<TokenStream> {
+    let fields = fields(input, container, target.name, None)?;
+    let proto_type = target.proto_type;
+    let from_proto_lossy = container.trait_path("FromProtoLossy");
+    let generics = Impl::new(
+        input,
+        container,
+        "FromProto",
+        container.from_proto_bound.as_deref(),
+        true,
+    );
+    let report = quote!(__report);
+    let conversion = Conversion::Lossy(&report);
+    let (body, nested) = from_proto_body(input, container, &generics, &fields, conversion, None);
+
+    let params = &generics.params;
+    let where_clause = generics.where_clause();
+    let allow = nested.then(|| quote!(#[allow(clippy::needless_question_mark)]));
+    Ok(quote! {
+        impl #params #from_proto_lossy for #proto_type #where_clause {
+            #allow
+            fn from_proto_lossy(
+                self,
+            ) -> ::proto_conve::__private::anyhow::Result<(
+                Self::Result,
+                ::proto_conve::lossy::ConversionReport,
+            )> {
+                #body
+            }
+        }
+    })
+}
+
+/// The body of `from_proto` converting `self` in to the struct, and if there are nested fields converted
+/// in closures. With the `proto` path of the projection, it converts the `proto` local instead.
+fn from_proto_body(
+    input: &DeriveInput,
+    container: &Container,
+    generics: &Impl,
+    fields: &[Field],
+    conversion: Conversion,
+    proto: Option<&syn::Path>,
+) -> (TokenStream, bool) {
+    let ident = &input.ident;
+    let validate = container
+        .validate
+        .as_ref()
+        .map(|validate| validate_container(validate, ident, quote!(&result)));
+
+    let mut bindings = Vec::new();
+    let mut parents = Vec::new();
+    let mut unwraps = Vec::new();
+    let mut values = Vec::new();
+    for field in fields {
+        let name = field.ident;
+        if field.skip {
+            values.push(quote!(#name: ::core::default::Default::default()));
+            continue;
+        }
+        let proto_ident = &field.proto_ident;
+        let local = match field.parents.last() {
+            // the alternatives are converted together
+            None if !field.first_of.is_empty() => {
+                let locals: Vec<_> = (0..field.first_of.len())
+                    .map(|index| format_ident!("__field_{}_{}", name, index))
+                    .collect();
+                for ((alternative, _), local) in field.first_of.iter().zip(&locals) {
+                    bindings.push(quote!(#alternative: #local));
+                }
+                quote!((#(#locals,)*))
+            }
+            // the oneof is converted with its siblings
+            None if !field.siblings.is_empty() => {
+                let local = format_ident!("__field_{}", name);
+                bindings.push(quote!(#proto_ident: #local));
+                let siblings: Vec<_> = (0..field.siblings.len())
+                    .map(|index| format_ident!("__field_{}_{}", name, index))
+                    .collect();
+                for ((sibling, _), local) in field.siblings.iter().zip(&siblings) {
+                    bindings.push(quote!(#sibling: #local));
+                }
+                quote!((#local, (#(#siblings,)*)))
+            }
+            None => {
+                let local = format_ident!("__field_{}", name);
+                bindings.push(quote!(#proto_ident: #local));
+                quote!(#local)
+            }
+            Some(_) => {
+                let parent = unwrap_parents(field, &mut parents, &mut bindings, &mut unwraps);
+                quote!(#parent.#proto_ident)
+            }
+        };
+        let value = field_from_proto(field, container, generics, local, conversion);
+        let value = if field.parents.is_empty() {
+            value
+        } else {
+            let names = field.parents.iter().map(|(_, name)| name);
+            quote! {
+                ::proto_conve::error::nested(&[#(#names),*], || Ok(#value))?
+            }
+        };
+        values.push(quote!(#name: #value));
+    }
+
+    // the lossy conversion returns the report with the value
+    let (report, ok) = match conversion {
+        Conversion::Lossy(report) => (
+            Some(quote! {
+                #[allow(unused_mut)]
+                let mut #report = ::proto_conve::lossy::ConversionReport::new();
+            }),
+            Some(report),
+        ),
+        _ => (None, None),
+    };
+    let ok = |value: TokenStream| match ok {
+        Some(report) => quote!(Ok((#value, #report))),
+        None => quote!(Ok(#value)),
+    };
+    let result = match validate {
+        None => ok(quote!(#ident { #(#values,)* })),
+        Some(validate) => {
+            let ok = ok(quote!(result));
+            quote! {
+                let result = #ident {
+                    #(#values,)*
+                };
+                #validate
+                #ok
+            }
+        }
+    };
+    let destructure = match proto {
+        None => quote!(let Self { #(#bindings,)* .. } = self;),
+        Some(proto) => quote!(let #proto { #(#bindings,)* .. } = proto;),
+    };
+    let body = quote! {
+        #destructure
+        #report
+        #(#unwraps)*
+        #result
+    };
+    (body, !parents.is_empty())
+}
+
+/// The conversion of the proto field value `local` in to the field, returning early with the error
+fn field_from_proto(
+    field: &Field,
+    container: &Container,
+    generics: &Impl,
+    local: TokenStream,
+    conversion: Conversion,
+) -> TokenStream {
+    let proto_name = &field.proto_name;
+    // the path of the lossy events from the outermost parent message, the errors get it on the way out
+    let parents = field
+        .parents
+        .iter()
+        .map(|(_, name)| quote!(::proto_conve::error::PathSegment::Field(#name)));
+    let parents = quote!(#(#parents,)*);
+    let path = quote!(#parents ::proto_conve::error::PathSegment::Field(#proto_name),);
+    // the element `value` of a repeated or map field at the `segment`
+    let convert = |segment: TokenStream| match conversion {
+        Conversion::Plain | Conversion::Async(_) => {
+            let from_proto = container.trait_path("FromProto");
+            quote!(#from_proto::from_proto(value))
+        }
+        Conversion::Versioned(version) => {
+            let from_proto = container.trait_path("FromProtoVersioned");
+            quote!(#from_proto::from_proto_versioned(value, #version))
+        }
+        Conversion::Lossy(report) => {
+            let from_proto = container.trait_path("FromProtoLossy");
+            quote! {
+                ::proto_conve::lossy::merge(
+                    #from_proto::from_proto_lossy(value),
+                    &mut #report,
+                    &[#path #segment],
+                )
+            }
+        }
+    };
+    let convert_named = |value: TokenStream, proto_name: &str| match conversion {
+        Conversion::Plain | Conversion::Async(_) => {
+            let from_proto_field = container.trait_path("FromProtoField");
+            quote!(#from_proto_field::from_proto_field(#value, #proto_name))
+        }
+        Conversion::Versioned(version) => {
+            let from_proto_field = container.trait_path("FromProtoVersionedField");
+            quote!(#from_proto_field::from_proto_versioned_field(#value, #proto_name, #version))
+        }
+        Conversion::Lossy(report) => {
+            let from_proto_field = container.trait_path("FromProtoLossyField");
+            quote! {
+                ::proto_conve::lossy::merge(
+                    #from_proto_field::from_proto_lossy_field(#value, #proto_name),
+                    &mut #report,
+                    &[#parents],
+                )
+            }
+        }
+    };
+    let convert_field = |value: TokenStream| convert_named(value, proto_name);
+    // the proto default is reported, but converted as any other value
+    let local = match conversion {
+        Conversion::Lossy(report) if field.required_nonzero => quote! {
+            ::proto_conve::lossy::suspicious_default(&mut #report, &[#path], #local)
+        },
+        _ => local,
+    };
+    // the present deprecated value is noticed, but converted as any other value
+    let local = match &field.deprecated {
+        Some(message) => quote! {
+            ::proto_conve::deprecation::deprecated(&[#path], #local, #message)
+        },
+        None => local,
+    };
+    // the default of the field without presence is missing
+    let local = match field.implicit {
+        true => quote!(::proto_conve::__private::non_default(#local)),
+        false => local,
+    };
+    let ty = generics.projection.ty(field.ty);
+    let value = match (&field.from_with, &field.default) {
+        // the local is the tuple of the alternatives, see `proto_conve::first_of`
+        _ if !field.first_of.is_empty() => {
+            let alternatives: Vec<_> = (0..field.first_of.len())
+                .map(|index| format_ident!("__alternative_{}", index))
+                .collect();
+            let names = field.first_of.iter().map(|(_, name)| name);
+            let values = field.first_of.iter().zip(&alternatives).map(|((_, name), alternative)| {
+                let value = convert_named(quote!(value), name);
+                quote!(.or(::proto_conve::__private::non_default(#alternative).map(|value| #value)))
+            });
+            let strict = field.strict.then(|| quote!(.strict()));
+            quote_spanned! {field.ty.span()=>
+                {
+                    let (#(#alternatives,)*) = #local;
+                    let value: ::proto_conve::__private::anyhow::Result<#ty> =
+                        ::proto_conve::first_of::FirstOf::new(&[#(#names),*])
+                            #strict
+                            #(#values)*
+                            .finish();
+                    value?
+                }
+            }
+        }
+        // the local is the oneof with the tuple of the siblings, see `from_proto_siblings` of the enum
+        _ if !field.siblings.is_empty() => {
+            let names = field.siblings.iter().map(|(_, name)| name);
+            let enum_name = quote!(#ty).to_string();
+            match conversion {
+                Conversion::Plain | Conversion::Async(_) => quote_spanned! {field.ty.span()=>
+                    {
+                        const _: () = ::proto_conve::__private::check_siblings(
+                            #enum_name,
+                            <#ty>::__PROTO_SIBLINGS,
+                            &[#(#names),*],
+                        );
+                        let (oneof, siblings) = #local;
+                        <#ty>::__from_proto_siblings(oneof, #proto_name, siblings)?
+                    }
+                },
+                _ => quote_spanned! {field.ty.span()=>
+                    ::core::compile_error!(
+                        "the `siblings` fields are only converted with the derived `FromProto`"
+                    )
+                },
+            }
+        }
+        _ if field.async_with.is_some() => {
+            let async_with = field.async_with.as_ref().unwrap();
+            match conversion {
+                Conversion::Async(ctx) => quote_spanned! {async_with.span()=>
+                    {
+                        let value: ::proto_conve::__private::anyhow::Result<#ty> =
+                            #async_with(#local, #ctx).await;
+                        value.map_err(|error| ::proto_conve::error::in_field(error, #proto_name))?
+                    }
+                },
+                _ => quote_spanned! {async_with.span()=>
+                    ::core::compile_error!(
+                        "the `async_with` fields are only converted with `AsyncFromProtoWith` of the \
+                         derived `FromProto`"
+                    )
+                },
+            }
+        }
+        _ if matches!(field.key_with, Some(KeyWith::ProtoEnum { .. })) => {
+            let Some(KeyWith::ProtoEnum {
+                enumeration,
+                unknown,
+            }) = &field.key_with
+            else {
+                unreachable!()
+            };
+            let convert_value = convert(quote! {
+                ::proto_conve::error::PathSegment::Key(::std::string::ToString::to_string(&key))
+            });
+            // the raw key is in the paths of the lossy events
+            let key = match conversion {
+                Conversion::Lossy(_) => quote!(key),
+                _ => quote!(_),
+            };
+            // the skipped keys are reported by `FromProtoLossy`
+            let report = match conversion {
+                Conversion::Lossy(report) => Some(quote! {
+                    for key in &skipped {
+                        ::proto_conve::lossy::skipped_key(&mut #report, &[#path], *key);
+                    }
+                }),
+                _ => None,
+            };
+            quote_spanned! {enumeration.span()=>
+                {
+                    let (map, skipped): (#ty, ::std::vec::Vec<i32>) =
+                        ::proto_conve::key::from_proto_enum_keys::<#enumeration, _, _, _, _>(
+                            #local,
+                            ::proto_conve::key::UnknownKeys::#unknown,
+                            |#key, value| #convert_value,
+                        )
+                        .map_err(|error| ::proto_conve::error::in_field(error, #proto_name))?;
+                    #report
+                    let _ = skipped;
+                    map
+                }
+            }
+        }
+        _ if field.key_with.is_some() => {
+            let key_with = field.key_with.as_ref().unwrap();
+            let convert_key = match key_with {
+                KeyWith::Module(module) => {
+                    quote!(#module::from_proto(::core::clone::Clone::clone(&key)))
+                }
+                KeyWith::Enum(_) => {
+                    let from_proto_alias = container.trait_path("FromProtoAlias");
+                    quote!(#from_proto_alias::from_proto_alias(::core::clone::Clone::clone(&key)))
+                }
+                KeyWith::ProtoEnum { .. } => unreachable!(),
+            };
+            let convert_value = convert(quote! {
+                ::proto_conve::error::PathSegment::Key(::std::string::ToString::to_string(&key))
+            });
+            // the raw keys converting in to the same key are an error, instead of overwriting
+            quote_spanned! {key_with.span()=>
+                #local
+                    .into_iter()
+                    .try_fold(
+                        <#ty as ::core::default::Default>::default(),
+                        |mut map, (key, value)| -> ::proto_conve::__private::anyhow::Result<_> {
+                            let converted = #convert_key
+                                .map_err(|error| ::proto_conve::error::at_key(error, &key))?;
+                            let value = #convert_value
+                                .map_err(|error| ::proto_conve::error::at_key(error, &key))?;
+                            ::proto_conve::key::insert(&mut map, &key, converted, value)?;
+                            Ok(map)
+                        },
+                    )
+                    .map_err(|error| ::proto_conve::error::in_field(error, #proto_name))?
+            }
+        }
+        _ if field.set => {
+            let convert_value = convert(quote!(::proto_conve::error::PathSegment::Index(index)));
+            quote_spanned! {field.ty.span()=>
+            #local
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    #convert_value
+                        .map_err(|error| ::proto_conve::error::at_index(error, index))
+                })
+                .collect::<::proto_conve::__private::anyhow::Result<_>>()
+                .map_err(|error| ::proto_conve::error::in_field(error, #proto_name))?
+            }
+        }
+        // the old function is primary, see `proto_conve::dual`
+        _ if field.dual.is_some() => {
+            let dual = field.dual.as_ref().unwrap();
+            let value = convert_field(quote!(value));
+            quote_spanned! {dual.span()=>
+                {
+                    let value: ::proto_conve::__private::anyhow::Result<#ty> =
+                        ::proto_conve::dual::dual_field(
+                            #local,
+                            #proto_name,
+                            |value| {
+                                #dual(value)
+                                    .map_err(|error| ::proto_conve::error::in_field(error, #proto_name))
+                            },
+                            |value| #value,
+                        );
+                    value?
+                }
+            }
+        }
+        (Some(function), _) => quote_spanned! {function.span()=>
+            {
+                let value: ::proto_conve::__private::anyhow::Result<#ty> = #function(#local);
+                value.map_err(|error| ::proto_conve::error::in_field(error, #proto_name))?
+            }
+        },
+        // the buffer is moved, not converted per byte
+        (None, None) if is_bytes(field.ty) => quote_spanned! {field.ty.span()=>
+            ::proto_conve::proto_bytes::from_proto(#local)
+        },
+        // the missing field error, the `Required` marker converts only with `FromProto`
+        // the present value, the default of the context, the static default, the missing field error,
+        // see `proto_conve::defaults`
+        (None, default) if field.default_from_ctx => {
+            let fallback = match default {
+                Some(FieldDefault::Trait) => quote!(::core::default::Default::default()),
+                Some(FieldDefault::Function(path)) => quote!(#path()),
+                None => quote!(return Err(::proto_conve::error::missing_field(#proto_name))),
+            };
+            let fallback = match (conversion, default) {
+                (Conversion::Lossy(report), Some(_)) => {
+                    quote!(::proto_conve::lossy::defaulted(&mut #report, &[#path], #fallback))
+                }
+                _ => fallback,
+            };
+            let value = convert_field(quote!(value));
+            quote_spanned! {field.ty.span()=>
+                match match #local {
+                    Some(value) => Some(value),
+                    None => ::proto_conve::defaults::ctx_default(#proto_name)?,
+                } {
+                    Some(value) => #value?,
+                    None => #fallback,
+                }
+            }
+        }
+        (None, None) if field.implicit && !is_option(field.ty) => {
+            let value = convert_field(quote!(value));
+            quote_spanned! {field.ty.span()=>
+                match #local {
+                    Some(value) => #value?,
+                    None => return Err(::proto_conve::error::missing_field(#proto_name)),
+                }
+            }
+        }
+        (None, None) => {
+            let value = convert_field(local);
+            quote_spanned! {field.ty.span()=>
+                #value?
+            }
+        }
+        (None, Some(default)) => {
+            let default = match default {
+                FieldDefault::Trait => quote!(::core::default::Default::default()),
+                FieldDefault::Function(path) => quote!(#path()),
+            };
+            let default = match conversion {
+                Conversion::Lossy(report) => {
+                    quote!(::proto_conve::lossy::defaulted(&mut #report, &[#path], #default))
+                }
+                _ => default,
+            };
+            let value = convert_field(quote!(value));
+            quote_spanned! {field.ty.span()=>
+                match #local {
+                    Some(value) => #value?,
+                    None => #default,
+                }
+            }
+        }
+    };
+    let value = match (field.boxed, is_option(field.ty)) {
+        (false, _) => value,
+        (true, false) => quote!(::std::boxed::Box::new(#value)),
+        (true, true) => quote! {
+            {
+                let value: ::core::option::Option<_> = #value;
+                value.map(::std::boxed::Box::new)
+            }
+        },
+    };
+    match &field.validate {
+        None => value,
+        Some(validate) => quote_spanned! {validate.span()=>
+            {
+                let value: #ty = #value;
+                #validate(&value)
+                    .map_err(|error| ::proto_conve::error::in_field(error, #proto_name))?;
+                value
+            }
+        },
+    }
+}
+
+pub fn to_proto(input: &DeriveInput) -> syn::Result<TokenStream> {
+    generics::check(input)?;
+    let container = Container::from_ast(input)?;
+    if let Some(field) = &container.wrapper {
+        return wrapper::to_proto(input, &container, field);
+    }
+    if container.attr_bag {
+        let fields = fields(input, &container, None, None)?;
+        return bag::to_proto(input, &container, &fields);
+    }
+    if let Data::Enum(data) = &input.data {
+        if let Some(oneof) = &container.oneof {
+            return oneof::to_proto(input, data, &container, oneof);
+        }
+        return enumeration::to_proto(input, data, &container);
+    }
+    let mut result = TokenStream::new();
+    for target in container.targets()? {
+        result.extend(struct_to_proto(input, &container, &target, false)?);
+    }
+    Ok(result)
+}
+
+/// `IntoProto` of the struct with the `move_only` fields, which are moved in to the proto type
+pub fn into_proto(input: &DeriveInput) -> syn::Result<TokenStream> {
+    generics::check(input)?;
+    let container = Container::from_ast(input)?;
+    if container.attr_bag || matches!(input.data, Data::Enum(_)) {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`IntoProto` can only be derived for the structs converting in to the proto messages",
+        ));
+    }
+    let targets = container.targets()?;
+    if targets.iter().any(|target| target.name.is_some()) {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`IntoProto` can not be derived with named mappings",
+        ));
+    }
+    let mut result = TokenStream::new();
+    for target in targets {
+        let fields = fields(input, &container, None, None)?;
+        // the other types have `IntoProto` of their `ToProto`
+        if !fields.iter().any(|field| field.move_only) {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "`IntoProto` is derived only for the structs with `move_only` fields, the other ones \
+                 derive `ToProto`, which gives them `IntoProto`",
+            ));
+        }
+        result.extend(struct_to_proto(input, &container, &target, true)?);
+    }
+    Ok(result)
+}
+
+/// `ToProto` of the struct converting in to the proto type, or `ToProtoAlias` of the named mapping.
+/// When `owned`, it is `IntoProto` moving the `move_only` fields.
+fn struct_to_proto(
+    input: &DeriveInput,
+    container: &Container,
+    target: &Target,
+    owned: bool,
+) -> syn::Result<TokenStream> {
+    let fields = fields(input, container, target.name, None)?;
+    let ident = &input.ident;
+    let proto_type = target.proto_type;
+    // `ToProto` borrows the value, so it would clone the field
+    if let Some(field) = fields.iter().find(|field| field.move_only && !owned) {
+        return Err(syn::Error::new(
+            field.ident.span(),
+            format!(
+                "`ToProto` can not be derived with the `move_only` field `{}`, which is never \
+                 cloned, derive `IntoProto` instead",
+                field.ident
+            ),
+        ));
+    }
+    if owned && (container.validate_on_to_proto || fields.iter().any(|field| field.transform)) {
+        return Err(syn::Error::new(
+            ident.span(),
+            "`IntoProto` can not be derived with `validate_on_to_proto` or the `transform` fields",
+        ));
+    }
+    if container.validate_on_to_proto && target.name.is_some() {
+        return Err(syn::Error::new(
+            ident.span(),
+            "`validate_on_to_proto` can not be used with named mappings",
+        ));
+    }
+    if container.validate_on_to_proto
+        && container.validate.is_none()
+        && fields.iter().all(|field| field.validate.is_none())
+    {
+        return Err(syn::Error::new(
+            ident.span(),
+            "`validate_on_to_proto` needs a `validate` function on the struct or its fields",
+        ));
+    }
+    let transformed = fields.iter().any(|field| field.transform);
+    if transformed && target.name.is_some() {
+        return Err(syn::Error::new(
+            ident.span(),
+            "the `transform` fields can not be used with named mappings",
+        ));
+    }
+    let to_proto = container.trait_path("ToProto");
+    let mut generics = Impl::new(
+        input,
+        container,
+        "ToProto",
+        container.to_proto_bound.as_deref(),
+        false,
+    );
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let result = generics.projection.ty(&syn::parse_quote!(#proto_type));
+    let proto_path = generics.projection.expr_path(proto_type);
+    if generics.is_generic() && container.to_proto_bound.is_none() {
+        // the fields missing in the rust type are filled with the default
+        generics
+            .predicates
+            .push(syn::parse_quote!(#result: ::core::default::Default));
+    }
+    let params = &generics.params;
+    let where_clause = generics.where_clause();
+
+    let mut values = Vec::new();
+    let mut nested = Vec::new();
+    let mut siblings_set = Vec::new();
+    for field in fields.iter().filter(|field| !field.skip) {
+        let name = field.ident;
+        let proto_ident = &field.proto_ident;
+        // the oneof sets its siblings
+        if !field.siblings.is_empty() {
+            let siblings: Vec<_> = field.siblings.iter().map(|(sibling, _)| sibling).collect();
+            let ty = field.ty;
+            siblings_set.push(quote_spanned! {field.ty.span()=>
+                let (#proto_ident, (#(#siblings,)*)) = <#ty>::__to_proto_siblings(&self.#name);
+            });
+            values.push(quote!(#proto_ident: ::core::option::Option::Some(#proto_ident)));
+            values.extend(siblings.iter().map(|sibling| quote!(#sibling)));
+            continue;
+        }
+        let value = match field.move_only {
+            true => quote_spanned! {field.ty.span()=>
+                ::core::convert::Into::into(self.#name)
+            },
+            false => to_proto_value(field, container, quote!(&self.#name)),
+        };
+        // the proto elements are sorted, so the rust ones need no `Ord`
+        let value = match &field.sorted {
+            Some(FieldSort::Ord(span)) => quote_spanned! {*span=>
+                ::proto_conve::__private::sorted(#value)
+            },
+            Some(FieldSort::Key(key)) => quote_spanned! {key.span()=>
+                ::proto_conve::__private::sorted_by_key(#value, #key)
+            },
+            None => value,
+        };
+        let value = if field.skip_default {
+            quote!(::proto_conve::__private::non_default(#value))
+        } else if field.implicit && is_option(field.ty) {
+            quote!(::core::option::Option::unwrap_or_default(#value))
+        } else {
+            value
+        };
+        // the predicate decides before the conversion, the one of `Option` gets the value in it
+        let value = match &field.omit_if {
+            Some(omit_if) if is_option(field.ty) => quote_spanned! {omit_if.span()=>
+                match &self.#name {
+                    ::core::option::Option::Some(value) if #omit_if(value) => ::core::option::Option::None,
+                    _ => #value,
+                }
+            },
+            Some(omit_if) => quote_spanned! {omit_if.span()=>
+                if #omit_if(&self.#name) {
+                    ::core::option::Option::None
+                } else {
+                    #value
+                }
+            },
+            None => value,
+        };
+        if field.parents.is_empty() {
+            values.push(quote!(#proto_ident: #value));
+            continue;
+        }
+        // the nested messages are created by the first field in them
+        let parents = field.parents.iter().map(|(ident, _)| {
+            quote!(let parent = parent.#ident.get_or_insert_with(::core::default::Default::default);)
+        });
+        nested.push(quote! {
+            {
+                let parent = &mut result;
+                #(#parents)*
+                parent.#proto_ident = #value;
+            }
+        });
+    }
+    let body = if nested.is_empty() {
+        quote! {
+            #(#siblings_set)*
+            #proto_path {
+                #(#values,)*
+                ..::core::default::Default::default()
+            }
+        }
+    } else {
+        quote! {
+            #(#siblings_set)*
+            let mut result = #proto_path {
+                #(#values,)*
+                ..::core::default::Default::default()
+            };
+            #(#nested)*
+            result
+        }
+    };
+
+    if container.validate_on_to_proto || transformed {
+        let try_to_proto = container.trait_path("TryToProto");
+        let validate_fields = fields.iter().filter_map(|field| {
+            let validate = field.validate.as_ref()?;
+            let name = field.ident;
+            let names = field
+                .parents
+                .iter()
+                .map(|(_, name)| name)
+                .chain([&field.proto_name]);
+            Some(quote_spanned! {validate.span()=>
+                #validate(&self.#name)
+                    .map_err(|error| ::proto_conve::error::in_fields(error, &[#(#names),*]))?;
+            })
+        });
+        let validate = container
+            .validate
+            .as_ref()
+            .map(|validate| validate_container(validate, ident, quote!(self)));
+        return Ok(quote! {
+            impl #params #try_to_proto for #ident #ty_generics #where_clause {
+                type Result = #result;
+
+                #[allow(clippy::needless_update)]
+                fn try_to_proto(&self) -> ::proto_conve::__private::anyhow::Result<Self::Result> {
+                    #(#validate_fields)*
+                    #validate
+                    Ok({ #body })
+                }
+            }
+        });
+    }
+
+    if target.name.is_some() {
+        let to_proto_alias = container.trait_path("ToProtoAlias");
+        return Ok(quote! {
+            impl #params #to_proto_alias<#result> for #ident #ty_generics #where_clause {
+                #[allow(clippy::needless_update)]
+                fn to_proto_alias(&self) -> #result {
+                    #body
+                }
+            }
+        });
+    }
+
+    if owned {
+        let into_proto = container.trait_path("IntoProto");
+        return Ok(quote! {
+            impl #params #into_proto for #ident #ty_generics #where_clause {
+                type Result = #result;
+
+                #[allow(clippy::needless_update)]
+                fn into_proto(self) -> Self::Result {
+                    #body
+                }
+            }
+        });
+    }
+
+    Ok(quote! {
+        impl #params #to_proto for #ident #ty_generics #where_clause {
+            type Result = #result;
+
+            #[allow(clippy::needless_update)]
+            fn to_proto(&self) -> Self::Result {
+                #body
+            }
+        }
+    })
+}
+
+/// The proto value of the field, converted from the `value` referencing the rust one
+pub fn to_proto_value(field: &Field, container: &Container, value: TokenStream) -> TokenStream {
+    let to_proto = container.trait_path("ToProto");
+    let to_proto_field = container.trait_path("ToProtoField");
+    match &field.to_with {
+        _ if field.transform => {
+            let proto_name = &field.proto_name;
+            quote_spanned! {field.ty.span()=>
+                ::proto_conve::transform::to_proto(#value)
+                    .map_err(|error| ::proto_conve::error::in_field(error, #proto_name))?
+            }
+        }
+        _ if field.key_with.is_some() => {
+            let key_with = field.key_with.as_ref().unwrap();
+            let convert_key = match key_with {
+                KeyWith::Module(module) => quote!(#module::to_proto(key)),
+                KeyWith::Enum(_) => {
+                    let to_proto_alias = container.trait_path("ToProtoAlias");
+                    quote!(#to_proto_alias::to_proto_alias(key))
+                }
+                KeyWith::ProtoEnum { enumeration, .. } => quote! {
+                    ::proto_conve::key::EnumKey::<#enumeration>::to_raw(key)
+                },
+            };
+            quote_spanned! {key_with.span()=>
+                (#value)
+                    .iter()
+                    .map(|(key, value)| (#convert_key, #to_proto::to_proto(value)))
+                    .collect()
+            }
+        }
+        Some(function) => quote_spanned! {function.span()=>
+            #function(#value)
+        },
+        None if field.boxed && is_option(field.ty) => quote_spanned! {field.ty.span()=>
+            (#value).as_ref().map(|value| #to_proto::to_proto(&**value))
+        },
+        None if field.boxed => quote_spanned! {field.ty.span()=>
+            #to_proto_field::to_proto_field(&**(#value))
+        },
+        None if is_bytes(field.ty) => quote_spanned! {field.ty.span()=>
+            ::proto_conve::proto_bytes::to_proto(#value)
+        },
+        None => quote_spanned! {field.ty.span()=>
+            #to_proto_field::to_proto_field(#value)
+        },
+    }
+}
+
+pub fn merge_from_proto(input: &DeriveInput) -> syn::Result<TokenStream> {
+    generics::check(input)?;
+    let container = Container::from_ast(input)?;
+    if let Data::Enum(_) = &input.data {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`MergeFromProto` can only be derived for structs",
+        ));
+    }
+    let mut result = TokenStream::new();
+    for target in container.targets()? {
+        result.extend(struct_merge_from_proto(input, &container, &target)?);
+    }
+    Ok(result)
+}
+
+/// `MergeFromProto` of the proto type in to the struct, assigning the masked fields
+fn struct_merge_from_proto(
+    input: &DeriveInput,
+    container: &Container,
+    target: &Target,
+) -> syn::Result<TokenStream> {
+    let fields = fields(input, container, target.name, None)?;
+    let ident = &input.ident;
+    let proto_type = target.proto_type;
+    let merge_from_proto = container.trait_path("MergeFromProto");
+    let generics = Impl::new(
+        input,
+        container,
+        "FromProto",
+        container.from_proto_bound.as_deref(),
+        true,
+    );
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let this = generics
+        .projection
+        .ty(&syn::parse_quote!(#ident #ty_generics));
+    let proto_path = generics.projection.expr_path(proto_type);
+
+    let mut bindings = Vec::new();
+    let mut names = Vec::new();
+    let mut messages = Vec::new();
+    let mut merges = Vec::new();
+    for field in fields.iter().filter(|field| !field.skip) {
+        if let Some((ident, _)) = field.parents.first() {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`path` fields can not be merged, merge the nested message instead",
+            ));
+        }
+        if !field.first_of.is_empty() {
+            return Err(syn::Error::new(
+                field.ident.span(),
+                "`first_of` fields can not be merged, the field mask names one proto field",
+            ));
+        }
+        if !field.siblings.is_empty() {
+            return Err(syn::Error::new(
+                field.ident.span(),
+                "`siblings` fields can not be merged, the field mask names one proto field",
+            ));
+        }
+        let name = field.ident;
+        let proto_ident = &field.proto_ident;
+        let proto_name = &field.proto_name;
+        let local = format_ident!("__field_{}", name);
+        bindings.push(quote!(#proto_ident: #local));
+        names.push(proto_name);
+        let value = field_from_proto(
+            field,
+            container,
+            &generics,
+            quote!(#local),
+            Conversion::Plain,
+        );
+        let nested = field.merge.then(|| {
+            messages.push(proto_name);
+            quote! {
+                else if let Some(mask) = mask.nested(#proto_name) {
+                    #merge_from_proto::merge_from_proto(&mut self.#name, #local, &mask)
+                        .map_err(|error| ::proto_conve::error::in_field(error, #proto_name))?;
+                }
+            }
+        });
+        merges.push(quote! {
+            if mask.contains(#proto_name) {
+                self.#name = #value;
+            }
+            #nested
+        });
+    }
+    let validate = container
+        .validate
+        .as_ref()
+        .map(|validate| validate_container(validate, ident, quote!(self)));
+
+    let params = &generics.params;
+    let where_clause = generics.where_clause();
+    Ok(quote! {
+        impl #params #merge_from_proto<#proto_type> for #this #where_clause {
+            fn merge_from_proto(
+                &mut self,
+                proto: #proto_type,
+                mask: &::proto_conve::mask::FieldMaskSet,
+            ) -> ::proto_conve::__private::anyhow::Result<()> {
+                mask.check(&[#(#names),*], &[#(#messages),*])?;
+                let #proto_path { #(#bindings,)* .. } = proto;
+                #(#merges)*
+                #validate
+                Ok(())
+            }
+        }
+    })
+}
+
+/// Unwrap the nested messages containing the field, which are not unwrapped yet, returns the local with
+/// the innermost one
+fn unwrap_parents(
+    field: &Field,
+    parents: &mut Vec<String>,
+    bindings: &mut Vec<TokenStream>,
+    unwraps: &mut Vec<TokenStream>,
+) -> syn::Ident {
+    let mut outer: Option<syn::Ident> = None;
+    for (depth, (ident, name)) in field.parents.iter().enumerate() {
+        let names: Vec<&str> = field.parents[..=depth]
+            .iter()
+            .map(|(_, name)| name.as_str())
+            .collect();
+        let local = format_ident!("__path_{}", names.join("__"));
+        let path = names.join(".");
+        if !parents.contains(&path) {
+            parents.push(path);
+            let outer_names = &names[..depth];
+            let value = match &outer {
+                None => {
+                    bindings.push(quote!(#ident: #local));
+                    quote!(#local)
+                }
+                Some(outer) => quote!(#outer.#ident),
+            };
+            // the missing parent is converted in to `anyhow::Error` once, where it is returned
+            unwraps.push(quote! {
+                let #local = match #value {
+                    Some(value) => value,
+                    None => {
+                        return Err(::proto_conve::error::in_fields(
+                            ::proto_conve::error::MissingField::new(#name).into(),
+                            &[#(#outer_names),*],
+                        ))
+                    }
+                };
+            });
+        }
+        outer = Some(local);
+    }
+    outer.unwrap()
+}
+
+/// Call the container `validate` function, adding the struct name to the error
+pub fn validate_container(
+    validate: &syn::Path,
+    ident: &syn::Ident,
+    value: TokenStream,
+) -> TokenStream {
+    let context = format!("Invalid {ident}");
+    quote_spanned! {validate.span()=>
+        #validate(#value).map_err(|error| error.context(#context))?;
+    }
+}
@@ -0,0 +1,158 @@
+//! Generic structs. The type parameters stand for the proto types in `FromProto`, where the rust type
+//! has their `FromProto::Result` instead, and for the rust types in `ToProto`, where the proto type has
+//! their `ToProto::Result`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::visit_mut::{self, VisitMut};
+use syn::{parse_quote, DeriveInput, GenericParam, Ident, Path, Type, WherePredicate};
+
+use crate::attr::Container;
+
+/// Check the generics are the supported ones
+pub fn check(input: &DeriveInput) -> syn::Result<()> {
+    let generics = &input.generics;
+    if let Some(lifetime) = generics.lifetimes().next() {
+        return Err(syn::Error::new_spanned(
+            lifetime,
+            "types with lifetime parameters can not be converted",
+        ));
+    }
+    if !matches!(input.data, syn::Data::Struct(_)) && !generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            generics,
+            "generic enums can not be converted",
+        ));
+    }
+    Ok(())
+}
+
+/// The parts of the generated impl
+pub struct Impl {
+    /// The parameters of the impl
+    pub params: TokenStream,
+    /// The where predicates of the impl
+    pub predicates: Vec<WherePredicate>,
+    /// Replaces the type parameters with their conversion results
+    pub projection: Projection,
+}
+
+impl Impl {
+    /// The impl with the predicates of the type and its parameters projected, and the bounds needed for
+    /// the conversion, `T: FromProto` or `T: ToProto` unless given with `#[proto(bound = "...")]`.
+    pub fn new(
+        input: &DeriveInput,
+        container: &Container,
+        name: &str,
+        bound: Option<&[WherePredicate]>,
+        project_type: bool,
+    ) -> Self {
+        let trait_path = container.trait_path(name);
+        let mut generics = input.generics.clone();
+        let projection = Projection {
+            params: generics
+                .type_params()
+                .map(|param| param.ident.clone())
+                .collect(),
+            trait_path: trait_path.clone(),
+        };
+        let mut predicates: Vec<WherePredicate> = Vec::new();
+        for param in generics.params.iter_mut() {
+            if let GenericParam::Type(param) = param {
+                param.default = None;
+                param.eq_token = None;
+                if project_type && !param.bounds.is_empty() {
+                    let ident = &param.ident;
+                    let bounds = &param.bounds;
+                    let mut predicate: WherePredicate = parse_quote!(#ident: #bounds);
+                    projection.clone().visit_where_predicate_mut(&mut predicate);
+                    predicates.push(predicate);
+                    param.bounds.clear();
+                    param.colon_token = None;
+                }
+            }
+        }
+        if let Some(where_clause) = generics.where_clause.take() {
+            for mut predicate in where_clause.predicates {
+                if project_type {
+                    projection.clone().visit_where_predicate_mut(&mut predicate);
+                }
+                predicates.push(predicate);
+            }
+        }
+        match bound {
+            Some(bound) => predicates.extend(bound.iter().cloned()),
+            None => predicates.extend(
+                projection
+                    .params
+                    .iter()
+                    .map(|param| parse_quote!(#param: #trait_path)),
+            ),
+        }
+
+        let (params, _, _) = generics.split_for_impl();
+        Self {
+            params: quote!(#params),
+            predicates,
+            projection,
+        }
+    }
+
+    /// The type is generic
+    pub fn is_generic(&self) -> bool {
+        !self.projection.params.is_empty()
+    }
+
+    /// The where clause of the impl
+    pub fn where_clause(&self) -> TokenStream {
+        let predicates = &self.predicates;
+        if predicates.is_empty() {
+            quote!()
+        } else {
+            quote!(where #(#predicates,)*)
+        }
+    }
+}
+
+/// Replaces the type parameters in the types with the projection `<T as Trait>::Result`
+#[derive(Clone)]
+pub struct Projection {
+    params: Vec<Ident>,
+    trait_path: TokenStream,
+}
+
+impl Projection {
+    /// The type with the parameters projected
+    pub fn ty(&self, ty: &Type) -> Type {
+        let mut ty = ty.clone();
+        self.clone().visit_type_mut(&mut ty);
+        ty
+    }
+
+    /// The path with the parameters projected, written as expression, so it can construct the struct
+    pub fn expr_path(&self, path: &Path) -> Path {
+        let mut path = path.clone();
+        self.clone().visit_path_mut(&mut path);
+        for segment in path.segments.iter_mut() {
+            if let syn::PathArguments::AngleBracketed(arguments) = &mut segment.arguments {
+                arguments.colon2_token = Some(Default::default());
+            }
+        }
+        path
+    }
+}
+
+impl VisitMut for Projection {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let Type::Path(path) = ty {
+            if let Some(ident) = path.path.get_ident() {
+                if path.qself.is_none() && self.params.contains(ident) {
+                    let trait_path = &self.trait_path;
+                    *ty = parse_quote!(<#ident as #trait_path>::Result);
+                    return;
+                }
+            }
+        }
+        visit_mut::visit_type_mut(self, ty);
+    }
+}
@@ -0,0 +1,82 @@
+//! Generating the canonical traversal of the proto messages and oneof enums fed to the hashers.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Data, DeriveInput, Fields};
+
+pub fn proto_hashable(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let hashable = quote!(::proto_conve::hash::ProtoHashable);
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, hashes) = fields(&data.fields);
+            quote! {
+                let #ident #pattern = self;
+                #(#hashes)*
+            }
+        }
+        // the variant is its index in the declaration order
+        Data::Enum(data) => {
+            let arms = data.variants.iter().enumerate().map(|(index, variant)| {
+                let name = &variant.ident;
+                let index = index as u32;
+                let (pattern, hashes) = fields(&variant.fields);
+                quote! {
+                    #ident::#name #pattern => {
+                        #hashable::hash_proto(&#index, hasher);
+                        #(#hashes)*
+                    }
+                }
+            });
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`ProtoHashable` can only be derived for structs and enums",
+            ))
+        }
+    };
+
+    let mut generics = input.generics.clone();
+    let params: Vec<_> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let predicates = &mut generics.make_where_clause().predicates;
+    for param in params {
+        predicates.push(parse_quote!(#param: #hashable));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #hashable for #ident #ty_generics #where_clause {
+            fn hash_proto<H: ::core::hash::Hasher + ?::core::marker::Sized>(&self, hasher: &mut H) {
+                #body
+            }
+        }
+    })
+}
+
+/// The pattern binding the fields and their hashes in the declaration order
+fn fields(fields: &Fields) -> (TokenStream, Vec<TokenStream>) {
+    let bindings: Vec<_> = (0..fields.len())
+        .map(|index| format_ident!("field_{}", index))
+        .collect();
+    let hashes = bindings
+        .iter()
+        .map(|binding| quote!(::proto_conve::hash::ProtoHashable::hash_proto(#binding, hasher);))
+        .collect();
+    let pattern = match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|field| &field.ident);
+            quote!({ #(#names: #bindings),* })
+        }
+        Fields::Unnamed(_) => quote!(( #(#bindings),* )),
+        Fields::Unit => quote!(),
+    };
+    (pattern, hashes)
+}
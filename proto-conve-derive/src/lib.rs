@@ -0,0 +1,408 @@
+//! Derive macros for the conversion traits generated by `proto_conve::impl_traits!(prelude)`.
+//! Use them through the `derive` feature of `proto-conve`.
+//!
+//! ```ignore
+//! #[derive(FromProto, ToProto)]
+//! #[proto(type = "proto::User")]
+//! struct User {
+//!     name: String,
+//!     address: Address,
+//! }
+//! ```
+//! The traits from the prelude have to be in scope, or their module has to be given with
+//! `#[proto(traits = "crate::proto")]`.
+//!
+//! The proto field names can differ from the rust ones with `#[proto(rename = "uid")]` on the field, or
+//! `#[proto(rename_all = "camelCase")]` on the type. The errors use the proto field names.
+//!
+//! Fields without proto counterpart are marked with `#[proto(skip)]`, they are filled with `Default` when
+//! converting from proto. The optional proto fields are required, unless the field has
+//! `#[proto(default)]`, or `#[proto(default = "path::to::fn")]`, giving the value for the missing field.
+//! With `#[proto(skip_default)]`, the optional proto field is set only when the value is not the
+//! default, like for the patch messages, and the missing proto field is converted in to the default.
+//! The proto value is checked with `proto_conve::proto_default::ProtoDefault`, which the proto messages
+//! of these fields derive.
+//! With `#[proto(default_from_ctx)]`, the missing proto field takes the proto value of the
+//! `DefaultsProvider` of `proto_conve::defaults::scope`, like the default currency of the tenant, and
+//! without it the `default` of the field, or the missing field error, see `proto_conve::defaults`.
+//! With `#[proto(omit_if = "path::to::fn")]`, the optional proto field is left out, when the function
+//! taking the reference of the value returns true, without converting the value. The `Option` fields
+//! give it the value in them, `None` is left out as always.
+//!
+//! The `Option` fields are converted from the proto3 `optional` fields with the explicit presence, the
+//! `Option<i32>` of prost, so `None` stays `None` and `Some(0)` stays `Some(0)`. The plain proto3
+//! scalars, `i32` of prost, have no presence, they are converted with `#[proto(presence = "implicit")]`,
+//! then their default is missing: `None` of the `Option` fields, otherwise the missing field error, or
+//! the value of `#[proto(default)]`. `None` is converted back in to the proto default, and
+//! `#[proto(presence = "explicit")]` is the same as no presence given.
+//!
+//! Custom conversion of a field is done with `#[proto(with = "module")]`, where the module has
+//! `fn from_proto(P) -> anyhow::Result<T>` and `fn to_proto(&T) -> P`. Only one direction can be
+//! overridden with `#[proto(from_with = "path::to::fn")]` or `#[proto(to_with = "path::to::fn")]`.
+//!
+//! The fields looked up while converting, like by their ids in a database, are converted with
+//! `#[proto(async_with = "module")]`, where the module has `async fn from_proto(P, &Ctx) ->
+//! anyhow::Result<T>` and `fn to_proto(&T) -> P`. The context is given on the struct with
+//! `#[proto(context = "Ctx")]`, and the derived `FromProto` is `AsyncFromProtoWith<Ctx>` instead, which
+//! converts the other fields as always, see `proto_conve::asynchronous`.
+//!
+//! The rewritten conversion of a field is audited with `#[proto(dual = "path::to::old_fn")]`, the old
+//! function and the traits convert the proto field, their divergences are reported to the observer of
+//! `proto_conve::dual::set_observer`, and the result of the old function is used.
+//!
+//! The field migrated between the proto fields, like from the legacy epoch millis `int64` to the
+//! `Timestamp` message, is converted with `#[proto(first_of("created_at_ts", "created_at_millis"))]`
+//! from the first of the alternatives, which is present and converts, and in to the first one. The
+//! missing alternatives are an error listing all of them, with `strict` also more than one present,
+//! see `proto_conve::first_of`.
+//!
+//! Repeated and map fields are converted element wise, the errors have the index or the key in the path.
+//! The order of the repeated fields is kept, with `#[proto(sorted)]` the proto list is sorted stably by
+//! the `Ord` of the proto elements, and with `#[proto(sorted = "path::to::fn")]` by the key, which the
+//! function taking the reference of the proto element returns, the rust value is not changed.
+//! The keys of a map are converted with `#[proto(key_with = "module")]`, where the module has
+//! `fn from_proto(K) -> anyhow::Result<T>` and `fn to_proto(&T) -> K`. The `map<string, V>` fields
+//! keyed by `uuid::Uuid` use `#[proto(key = "uuid")]` with the `uuid` feature, and the `map<int32, V>`
+//! fields keyed by the enums use `#[proto(key = "enum")]`, the unknown keys are converted the same as
+//! the enum values. The keys of the prost enum `Status` use `#[proto(key_enum = "path::Status")]`, with
+//! `unknown_keys = "error"`, `"skip"` or `"open"` for the `OpenEnum<Status>` keys, see
+//! `proto_conve::key::UnknownKeys`. The raw keys converting in to the same key are an error. A repeated field is collected
+//! in to a `HashSet` or `BTreeSet` with `#[proto(set)]`, the duplicate elements are merged. The
+//! `Vec<u8>` fields are the proto `bytes`, their buffers are moved through `proto_conve::ProtoBytes`,
+//! so the proto field can be also `bytes::Bytes` with the `bytes` feature. The fields of the types
+//! which only implement `ToProtoOption`, like the values sent when they are shared, are converted in
+//! to the `Option` proto fields, and their `Vec` in to the repeated fields without the absent ones.
+//!
+//! The `map<string, google.protobuf.Value>` fields convert in to the maps of `serde_json::Value`, where
+//! the explicit nulls are `serde_json::Value::Null` as the empty values, and with
+//! `#[proto(nullable_values)]` in to the maps of `Option<serde_json::Value>` keeping the explicit
+//! nulls, see `proto_conve::list_value`.
+//!
+//! The integer fields with `#[proto(wire = "sint32")]`, or of the other integer wire types, convert
+//! from the proto values checked against the range of the field, so the negative `sint32` is an error
+//! for the `u32` field, and in to them without a loss, see `proto_conve::wire`.
+//!
+//! The strings repeated across the messages, like the labels, are interned with `#[proto(intern)]` on
+//! the `Arc<str>`, `Option<Arc<str>>` or `Vec<Arc<str>>` fields, see `proto_conve::intern`.
+//! The sub-messages repeated across the messages are converted once with `#[proto(cached)]` on the
+//! `Arc<T>`, `Option<Arc<T>>` or `Vec<Arc<T>>` fields, the values of the equal proto messages share
+//! the `Arc`, see `proto_conve::cache`.
+//! The `String` fields with `#[proto(transform)]`, like the encrypted personal data, are decoded and
+//! encoded with the `ProtoTransform` of `proto_conve::transform::scope`, the struct derives
+//! `TryToProto` instead of `ToProto`.
+//!
+//! With the `reflect` feature, the structs deriving `FromProto` implement
+//! `proto_conve::reflect::HasProtoMapping`, the names of their proto type and of the proto fields, which
+//! of them are required or skipped, and the paths of their `with` functions, for the generators of the
+//! API documents. With the `coverage` feature, the derived `FromProto` and `ToProto` register their
+//! proto types for the reports of the proto messages without the conversions, see
+//! `proto_conve::coverage`.
+//!
+//! The batches of the short lived values, like the ingested events, are converted in to an arena with
+//! `#[proto(arena)]` on a struct with only the lifetime of the arena, like `struct Event<'bump>`. It
+//! derives `FromProtoIn` instead of `FromProto`, the strings are `&'bump str`, the `bytes` are
+//! `&'bump [u8]` and the repeated fields are `ArenaVec<'bump, T>`, see `proto_conve::arena`.
+//!
+//! The proto messages built by hand, like the requests, are built with `#[derive(ToProtoBuilder)]`,
+//! which generates `UserProtoBuilder` for `User`, with a setter of the rust value per field, converted
+//! the same as with `ToProto`. The `Option` fields and the ones with `default` get also the `maybe_`
+//! setters of `Option<&T>`, `build` fails naming the other fields, which are not set.
+//!
+//! Only one direction is derived, when only one of the derives is used. A type converts to more
+//! proto types with named mappings, `#[proto(type = "proto::CreateUserRequest", name = "create")]`,
+//! which derive `ToProtoAlias<proto::CreateUserRequest>` instead of `ToProto`. The field attributes with
+//! `mapping = "create"` are used only for that mapping, like `#[proto(skip, mapping = "create")]`.
+//!
+//! The read paths needing only some fields of a big message derive the projections,
+//! `#[proto(project_from = "proto::Order")]` on a struct with just these fields and the same field
+//! attributes, which derive `FromProtoAlias<proto::Order>`. The fields of the projection are moved out
+//! of the proto message and converted, the other ones are dropped without converting them. With
+//! `full = "Order"`, the projected fields have to be the fields of the same names of the full type
+//! mapping the message, the other ones fail to compile.
+//!
+//! A field of a nested proto message is read and written with `#[proto(path = "profile.display_name")]`.
+//! The nested messages are required when converting from proto, the error has the path of the missing
+//! one, and they are created once for all their fields when converting in to proto.
+//!
+//! Boxed fields of recursive messages are converted through the `Box` impls. When only the rust field
+//! is boxed, mark it with `#[proto(boxed)]`. The boxes nest in the containers, like
+//! `Vec<Option<Box<Expr>>>`, and the errors get the whole path, like `items[3].left.right.value`, each
+//! level adds its segment in constant time. The conversion recurses once per level of the message, the
+//! depth of the untrusted messages is limited with `ConversionBudget::max_depth` of
+//! `proto_conve::budget`, which fails the conversion with `BudgetExceeded` before the stack runs out.
+//!
+//! The message fields boxed only by prost-build, with its `boxed` option, are converted in to the
+//! fields without the `Box`, like `Option<Box<proto::Expr>>` in to `Expr` or `Option<Expr>`, and the
+//! maps of its `btree_map` option in to `BTreeMap`. The types are recognised by the last segment of
+//! their paths, so the generated `::core::option::Option` or `::prost::alloc::vec::Vec` are the same as
+//! `Option` and `Vec`. The boxed `Option` or `Vec` is an error, the proto messages are boxed inside
+//! them.
+//!
+//! The repeated key and value entries, like `repeated Attribute { string key; string value; }`, are
+//! converted in to the struct with `#[proto(attr_bag, type = "proto::Attribute")]`, which derives
+//! `FromProto` for `Vec<proto::Attribute>`. The fields are the values of their keys, parsed with
+//! `FromStr` and written with `Display`, or converted with `#[proto(with = "module")]` from and to the
+//! `String`. The missing keys are an error, unless the field is an `Option` or it has a `default`, the
+//! duplicate keys are an error. The unknown keys are kept in the `HashMap<String, String>` field marked
+//! with `#[proto(leftover)]`, or ignored. The entries are written in the order of the fields, followed
+//! by the leftover ones sorted by the key.
+//!
+//! The newtypes convert from and to the wrapper messages of a single field, like `message Quantity {
+//! uint32 value = 1; }`, with `#[proto(type = "proto::Quantity", wrapper = "value")]` on `struct
+//! Quantity(Bounded<u32, 1, 999>)`. The field converts in to the type of the newtype with
+//! `FromProtoField`, its errors are at the path of the field, and the other fields of the message are
+//! left with their defaults, see `proto_conve::wrapper`.
+//!
+//! Fieldless enums are converted from and to the `i32` proto enum values through `FromProtoAlias<i32>`
+//! and `ToProtoAlias<i32>`. The values are given per variant with `#[proto(value = 3)]`, or taken from
+//! the prost generated enum with `#[proto(enum = "path::ProtoStatus")]` on the enum. Unknown values are
+//! an error, unless `#[proto(unknown = "Variant")]` names the variant to use for them. In the lenient
+//! `ConversionMode`, they are converted in to the variant of the default value `0`. With
+//! `#[proto(open)]`, the fields of `OpenEnum<Enum>` keep the unknown values instead.
+//! The variants of the same value fail to compile, naming both of them. For the primitive integer wire
+//! types, the enums also get the const conversions, like `const fn to_proto_i32(self) -> i32` and
+//! `const fn from_proto_i32(i32) -> Option<Self>`. The `FromProto` of the enums of the `i32` values
+//! implements `EnumSetValue`, so the repeated enum fields convert in to `EnumSet<Enum>`, the bits of their
+//! values in `0..64`, see `proto_conve::enum_set`.
+//!
+//! With `#[proto(string)]`, the enums convert from and to the proto strings too, with
+//! `FromProtoAlias<String>` and `ToProtoAlias<String>`. The canonical string of a variant is the
+//! screaming snake case of its name, or `#[proto(name = "SOLD_OUT")]`, the legacy ones are accepted
+//! with `#[proto(alias = "enabled", alias = "on")]`, and with `#[proto(string, case_insensitive)]` in
+//! any case. The lenient matches record a deprecation notice, `from_proto_name` tells how the string
+//! matched, and the same string of two variants fails to compile, see `proto_conve::enumeration`.
+//! Their `FromProto` implements `ProtoNamed`, so the repeated string tags convert in to
+//! `TaggedSet<Enum>` of the known variants and the free-form tags, see `proto_conve::tagged_set`.
+//!
+//! The raw `i32` fields of the prost enums are converted with `#[proto(enumeration = "path::Status")]`
+//! through `proto_conve::ProtoEnumField`, instead of the getters turning the unknown values in to the
+//! default variant. The unknown values are an error naming the field, the fields of
+//! `OpenEnum<Status>` keep them.
+//!
+//! Enums with data are converted from and to the prost generated oneof enums with
+//! `#[proto(oneof = "path::to::Oneof")]`. The variants are mapped by name and their single field is
+//! converted with the traits, unit variants are mapped to variants with empty messages. The variant
+//! of an empty marker message with `#[proto(sibling = "delivered_at")]` carries the value of the
+//! sibling field of the parent message instead, the struct field of the oneof lists the siblings of its
+//! variants, in their order, with `#[proto(siblings("delivered_at"))]`, the enums with such variants
+//! are converted only in these fields. The missing sibling of the set variant is the missing field,
+//! the siblings of the other variants are ignored and not written. The oneofs of the value or the
+//! error are the `Result` fields, with `impl_proto_result!` of the oneof.
+//!
+//! The converted value is checked with `#[proto(validate = "path::to::fn")]` on the struct or on a
+//! field, where the function is `fn(&T) -> anyhow::Result<()>`. The struct errors are wrapped with the
+//! struct name, the field errors get the field in the path. With `#[proto(validate_on_to_proto)]` on
+//! the struct, the same checks run before converting in to proto, `TryToProto` is derived instead of
+//! `ToProto` for it.
+//!
+//! The update requests with a field mask are merged in to the existing value with
+//! `#[derive(MergeFromProto)]`. Only the fields in the mask are converted and assigned, the unknown
+//! paths are an error. The paths of nested messages, like `address.city`, are merged in to the fields
+//! marked with `#[proto(merge)]`, which derive `MergeFromProto` too. On an error, the fields before
+//! the failing one are already merged.
+//!
+//! The clients with different schema versions are supported with `#[derive(FromProtoVersioned)]`. The
+//! field attributes with `#[proto(since = 3)]` or `#[proto(until = 2, with = "legacy")]` are used only
+//! in the versions in their range, and the other conversions use only the attributes without a version.
+//! The versions out of `#[proto(min_version = 1, max_version = 3)]` are an `UnsupportedVersion` error.
+//! The version is passed to the fields and their elements with `FromProtoVersioned` too.
+//!
+//! The data migrations convert as much as possible with `#[derive(FromProtoLossy)]`. The malformed
+//! values are converted leniently, the unknown enum values in to the variant of the default value and
+//! the missing required fields in to their defaults, the report has all of them with their paths.
+//! The fields with `#[proto(required_nonzero)]`, like the ids, report the proto default values, `0`,
+//! `""` or `None`, as they are expected after a proto field was renumbered or retyped, the other
+//! conversions convert them as they are.
+//!
+//! `#[derive(ValidateFromProto)]` checks the proto message would convert with the derived `FromProto`,
+//! with the violations of all of its fields and of the fields of its nested messages, which derive it
+//! too, without building the value, see `proto_conve::dry_run`. The `path` and `async_with` fields
+//! can not be checked.
+//!
+//! The fields deprecated with `#[proto(deprecated = "use new_field instead")]` are converted as the
+//! others, but their present values, not the proto defaults, record the deprecation notices with the
+//! field paths, which are collected with `proto_conve::deprecation::collect`.
+//!
+//! The personal data is kept out of the logs with `#[proto(sensitive)]` on the fields. The derived
+//! `FromProto` implements `proto_conve::redact::ProtoRedact` of the proto message, its
+//! `render_proto_for_log()` shows the sensitive values as `[redacted]` and the nested messages with
+//! their own `ProtoRedact`. The messages without sensitive fields, nesting the ones with them, render
+//! with `#[proto(redact)]` on the struct. In `proto_conve::redact::attach_to_errors`, the failed
+//! conversions attach the rendering to their errors.
+//!
+//! The huge fields, like the blobs of the payloads, are marked with `#[proto(move_only)]`, they are
+//! never cloned. The struct with them can not derive `ToProto`, which borrows the value, it derives
+//! `IntoProto`, moving these fields in to the proto fields with `Into`, and converting the other ones
+//! as `ToProto` does. `#[proto(clone_cheap)]` documents the fields cloned on purpose, it changes
+//! nothing, but it can not be combined with `move_only`.
+//!
+//! With `#[proto(deny_unknown_fields)]` on the struct, the derived `FromProto` builds the proto message
+//! with all of its fields without `..`, so the proto fields added later, which are not mapped, fail to
+//! compile. The
+//! ones left out on purpose are listed with `#[proto(unmapped = "legacy_id, internal_note")]`.
+//! With the `verify` feature of `proto-conve` the derived `FromProto` references every mapped proto
+//! field, with `rename`, `path` and `first_of`, and the mapped variants of the oneofs, in a function,
+//! which is never called, so the proto fields renamed or removed fail to compile at the derived field,
+//! see `proto_conve::verify`.
+//!
+//! With the `cbor` feature of `proto-conve`, `#[derive(ProtoCbor)]` encodes the structs in to CBOR as
+//! the maps keyed by the proto field names, with the same `rename`, `skip`, `default` and `with` of
+//! the fields, see `proto_conve::cbor`. The fieldless enums are their proto enum values.
+//!
+//! With the `text-format` feature of `proto-conve`, the proto messages and their oneof enums derive
+//! `TextProto`, which reads them from the protobuf text format by the names of their fields. The
+//! `#[prost(enumeration = "...")]` fields are read by the names of the enum values, the
+//! `#[prost(bytes)]` ones from the strings, and the `#[prost(oneof = "...")]` ones by the fields of
+//! their variants, see `proto_conve::text_format`.
+//!
+//! Generic structs are converted from and to generic proto types, `#[proto(type = "proto::Page<T>")]`.
+//! When converting from proto, the parameters are the proto types and the fields get their
+//! `FromProto::Result`, when converting in to proto, the proto type gets their `ToProto::Result`. The
+//! impls are bound by `T: FromProto` and `T: ToProto`, which is replaced with
+//! `#[proto(bound = "T: FromProto, T::Result: Ord")]`, or per direction with
+//! `#[proto(bound(from_proto = "...", to_proto = "..."))]`.
+
+mod arena;
+mod attr;
+mod bag;
+mod builder;
+mod case;
+mod cbor;
+mod default;
+mod enumeration;
+mod expand;
+mod generics;
+mod hash;
+mod oneof;
+mod redact;
+mod reflect;
+mod text_format;
+mod verify;
+mod wrapper;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Derive `FromProto` for the proto type, converting in to this type
+#[proc_macro_derive(FromProto, attributes(proto))]
+pub fn derive_from_proto(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand::from_proto(&input)
+        .map(|mut result| {
+            result.extend(reflect::register_conversions(&input, "FromProto"));
+            result
+        })
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `FromProtoVersioned` of the proto type, converting with the field attributes of the schema
+/// version
+#[proc_macro_derive(FromProtoVersioned, attributes(proto))]
+pub fn derive_from_proto_versioned(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand::from_proto_versioned(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `FromProtoLossy` of the proto type, converting the fields as much as possible and reporting
+/// what was lossy
+#[proc_macro_derive(FromProtoLossy, attributes(proto))]
+pub fn derive_from_proto_lossy(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand::from_proto_lossy(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `ValidateFromProto` of the proto type, checking it would convert with the derived
+/// `FromProto`, with the violations of all of its fields, see `proto_conve::dry_run`
+#[proc_macro_derive(ValidateFromProto, attributes(proto))]
+pub fn derive_validate_from_proto(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand::validate_from_proto(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `MergeFromProto` of the proto type, merging the fields in the field mask in to this type
+#[proc_macro_derive(MergeFromProto, attributes(proto))]
+pub fn derive_merge_from_proto(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand::merge_from_proto(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `ToProto` for this type, converting in to the proto type
+#[proc_macro_derive(ToProto, attributes(proto))]
+pub fn derive_to_proto(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand::to_proto(&input)
+        .map(|mut result| {
+            result.extend(reflect::register_conversions(&input, "ToProto"));
+            result
+        })
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `IntoProto` for this type with the `move_only` fields, moving them in to the proto type
+#[proc_macro_derive(IntoProto, attributes(proto))]
+pub fn derive_into_proto(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand::into_proto(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive the builder of the proto type, `TypeProtoBuilder`, setting the proto fields from the rust
+/// values of the fields
+#[proc_macro_derive(ToProtoBuilder, attributes(proto))]
+pub fn derive_to_proto_builder(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    builder::to_proto_builder(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `ToCbor` and `FromCbor` of this type, encoding it in to CBOR with the field mapping of its
+/// proto conversions
+#[proc_macro_derive(ProtoCbor, attributes(proto))]
+pub fn derive_proto_cbor(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    cbor::proto_cbor(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `ProtoDefault` of the proto message, with the default instance in a static and the default
+/// check of all the fields, see `proto_conve::proto_default`
+#[proc_macro_derive(ProtoDefault)]
+pub fn derive_proto_default(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    default::proto_default(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `TextProto` of the proto message, or `TextOneof` of the oneof enum, merging the protobuf
+/// text format in to its fields, see `proto_conve::text_format`
+#[proc_macro_derive(TextProto, attributes(prost))]
+pub fn derive_text_proto(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    text_format::text_proto(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `ProtoHashable` of the proto message or oneof enum, feeding its fields to the hasher in the
+/// declaration order
+#[proc_macro_derive(ProtoHashable)]
+pub fn derive_proto_hashable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    hash::proto_hashable(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
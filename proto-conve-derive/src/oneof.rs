@@ -0,0 +1,313 @@
+//! Generating the conversions of enums with data mapped to the prost generated oneof enums.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{DataEnum, DeriveInput, Fields, Path};
+
+use crate::attr::{oneof_field_name, Container, Variant};
+use crate::verify;
+
+/// The variants with their attributes, each has a single unnamed field or no fields at all
+fn variants(data: &DataEnum) -> syn::Result<Vec<Variant<'_>>> {
+    data.variants
+        .iter()
+        .map(|variant| {
+            match &variant.fields {
+                Fields::Unit => {}
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {}
+                fields => {
+                    return Err(syn::Error::new_spanned(
+                        fields,
+                        "oneof variants have to have a single unnamed field, or no fields",
+                    ))
+                }
+            }
+            let variant = Variant::from_ast(variant)?;
+            if let (Some((sibling, _)), Fields::Unit) = (&variant.sibling, &variant.variant.fields)
+            {
+                return Err(syn::Error::new(
+                    sibling.span(),
+                    "`sibling` is the proto field with the value of the variant, it needs a single \
+                     unnamed field",
+                ));
+            }
+            Ok(variant)
+        })
+        .collect()
+}
+
+/// The type of the single field of the variant with `sibling`
+fn sibling_type<'a>(variant: &Variant<'a>) -> &'a syn::Type {
+    match &variant.variant.fields {
+        Fields::Unnamed(fields) => &fields.unnamed[0].ty,
+        _ => unreachable!("the variants with `sibling` have a single unnamed field"),
+    }
+}
+
+pub fn from_proto(
+    input: &DeriveInput,
+    data: &DataEnum,
+    container: &Container,
+    oneof: &Path,
+) -> syn::Result<TokenStream> {
+    let variants = variants(data)?;
+    let ident = &input.ident;
+    let from_proto = container.trait_path("FromProto");
+    let from_proto_field = container.trait_path("FromProtoField");
+    if variants.iter().any(|variant| variant.sibling.is_some()) {
+        return from_proto_siblings(ident, &variants, container, oneof);
+    }
+
+    let arms = variants.iter().map(|variant| {
+        let rust_ident = variant.ident;
+        let proto_ident = &variant.proto_ident;
+        match &variant.variant.fields {
+            Fields::Unit => quote!(#oneof::#proto_ident(_) => Ok(#ident::#rust_ident)),
+            fields => {
+                let name = oneof_field_name(proto_ident);
+                let value = quote_spanned! {fields.span()=>
+                    #from_proto_field::from_proto_field(value, #name)?
+                };
+                quote!(#oneof::#proto_ident(value) => Ok(#ident::#rust_ident(#value)))
+            }
+        }
+    });
+
+    let verify = verify::oneof_variants(oneof, &variants);
+    Ok(quote! {
+        impl #from_proto for #oneof {
+            type Result = #ident;
+
+            fn from_proto(self) -> ::proto_conve::__private::anyhow::Result<Self::Result> {
+                match self {
+                    #(#arms,)*
+                }
+            }
+        }
+
+        #verify
+    })
+}
+
+/// With the `sibling` variants the oneof is converted together with the sibling fields of the parent
+/// message, by the derived struct with `siblings`, see `from_proto_siblings` of the enum
+fn from_proto_siblings(
+    ident: &syn::Ident,
+    variants: &[Variant],
+    container: &Container,
+    oneof: &Path,
+) -> syn::Result<TokenStream> {
+    let from_proto_field = container.trait_path("FromProtoField");
+    let siblings: Vec<_> = variants
+        .iter()
+        .filter(|variant| variant.sibling.is_some())
+        .collect();
+    let locals: Vec<_> = (0..siblings.len())
+        .map(|index| format_ident!("__sibling_{}", index))
+        .collect();
+    let protos: Vec<_> = (0..siblings.len())
+        .map(|index| format_ident!("S{}", index))
+        .collect();
+    let markers: Vec<_> = (0..siblings.len())
+        .map(|index| format_ident!("M{}", index))
+        .collect();
+    let bounds = siblings
+        .iter()
+        .zip(&protos)
+        .zip(&markers)
+        .map(|((variant, proto), marker)| {
+            let ty = sibling_type(variant);
+            quote!(::core::option::Option<#proto>: #from_proto_field<#ty, #marker>)
+        });
+    let names = siblings
+        .iter()
+        .map(|variant| &variant.sibling.as_ref().unwrap().1);
+
+    let mut sibling_locals = locals.iter();
+    let arms: Vec<_> = variants
+        .iter()
+        .map(|variant| {
+            let rust_ident = variant.ident;
+            let proto_ident = &variant.proto_ident;
+            match (&variant.sibling, &variant.variant.fields) {
+                (Some((_, name)), fields) => {
+                    let local = sibling_locals.next().unwrap();
+                    let value = quote_spanned! {fields.span()=>
+                        #from_proto_field::from_proto_field(#local, #name)?
+                    };
+                    quote!(#oneof::#proto_ident(_) => Ok(#ident::#rust_ident(#value)))
+                }
+                (None, Fields::Unit) => quote!(#oneof::#proto_ident(_) => Ok(#ident::#rust_ident)),
+                (None, fields) => {
+                    let variant_name = oneof_field_name(proto_ident);
+                    let value = quote_spanned! {fields.span()=>
+                        #from_proto_field::from_proto_field(value, #variant_name)
+                            .map_err(|error| ::proto_conve::error::in_field(error, name))?
+                    };
+                    quote!(#oneof::#proto_ident(value) => Ok(#ident::#rust_ident(#value)))
+                }
+            }
+        })
+        .collect();
+
+    let verify = verify::oneof_variants(oneof, variants);
+    Ok(quote! {
+        impl #ident {
+            /// The `sibling` fields of the variants, in their order, checked against the `siblings` of
+            /// the derived struct field
+            #[doc(hidden)]
+            pub const __PROTO_SIBLINGS: &'static [&'static str] = &[#(#names),*];
+
+            /// Convert the oneof field `name` with the sibling fields of the `sibling` variants, the
+            /// siblings of the other variants are ignored
+            #[doc(hidden)]
+            pub fn __from_proto_siblings<#(#protos,)* #(#markers,)*>(
+                oneof: ::core::option::Option<#oneof>,
+                name: &'static str,
+                siblings: (#(::core::option::Option<#protos>,)*),
+            ) -> ::proto_conve::__private::anyhow::Result<Self>
+            where
+                #(#bounds,)*
+            {
+                #[allow(unused_variables)]
+                let (#(#locals,)*) = siblings;
+                let ::core::option::Option::Some(oneof) = oneof else {
+                    return Err(::proto_conve::error::missing_field(name));
+                };
+                match oneof {
+                    #(#arms,)*
+                }
+            }
+        }
+
+        #verify
+    })
+}
+
+pub fn to_proto(
+    input: &DeriveInput,
+    data: &DataEnum,
+    container: &Container,
+    oneof: &Path,
+) -> syn::Result<TokenStream> {
+    let variants = variants(data)?;
+    let ident = &input.ident;
+    let to_proto = container.trait_path("ToProto");
+    let to_proto_field = container.trait_path("ToProtoField");
+    if variants.iter().any(|variant| variant.sibling.is_some()) {
+        return Ok(to_proto_siblings(ident, &variants, container, oneof));
+    }
+
+    let arms = variants.iter().map(|variant| {
+        let rust_ident = variant.ident;
+        let proto_ident = &variant.proto_ident;
+        match &variant.variant.fields {
+            Fields::Unit => quote! {
+                #ident::#rust_ident => #oneof::#proto_ident(::core::default::Default::default())
+            },
+            fields => {
+                let value = quote_spanned! {fields.span()=>
+                    #to_proto_field::to_proto_field(value)
+                };
+                quote!(#ident::#rust_ident(value) => #oneof::#proto_ident(#value))
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #to_proto for #ident {
+            type Result = #oneof;
+
+            fn to_proto(&self) -> Self::Result {
+                match self {
+                    #(#arms,)*
+                }
+            }
+        }
+    })
+}
+
+/// The oneof and the sibling fields of the `sibling` variants, the siblings of the other variants are
+/// `None`
+fn to_proto_siblings(
+    ident: &syn::Ident,
+    variants: &[Variant],
+    container: &Container,
+    oneof: &Path,
+) -> TokenStream {
+    let to_proto_field = container.trait_path("ToProtoField");
+    let siblings: Vec<_> = variants
+        .iter()
+        .filter(|variant| variant.sibling.is_some())
+        .collect();
+    let protos: Vec<_> = (0..siblings.len())
+        .map(|index| format_ident!("S{}", index))
+        .collect();
+    let markers: Vec<_> = (0..siblings.len())
+        .map(|index| format_ident!("M{}", index))
+        .collect();
+    let bounds = siblings
+        .iter()
+        .zip(&protos)
+        .zip(&markers)
+        .map(|((variant, proto), marker)| {
+            let ty = sibling_type(variant);
+            quote!(#ty: #to_proto_field<#proto, #marker>)
+        });
+    let none: Vec<_> = siblings
+        .iter()
+        .map(|_| quote!(::core::option::Option::None))
+        .collect();
+
+    let mut position = 0;
+    let arms = variants.iter().map(|variant| {
+        let rust_ident = variant.ident;
+        let proto_ident = &variant.proto_ident;
+        match (&variant.sibling, &variant.variant.fields) {
+            (Some(_), fields) => {
+                let mut values = none.clone();
+                values[position] = quote_spanned! {fields.span()=>
+                    ::core::option::Option::Some(#to_proto_field::to_proto_field(value))
+                };
+                position += 1;
+                quote! {
+                    #ident::#rust_ident(value) => (
+                        #oneof::#proto_ident(::core::default::Default::default()),
+                        (#(#values,)*),
+                    )
+                }
+            }
+            (None, Fields::Unit) => quote! {
+                #ident::#rust_ident => (
+                    #oneof::#proto_ident(::core::default::Default::default()),
+                    (#(#none,)*),
+                )
+            },
+            (None, fields) => {
+                let value = quote_spanned! {fields.span()=>
+                    #to_proto_field::to_proto_field(value)
+                };
+                quote!(#ident::#rust_ident(value) => (#oneof::#proto_ident(#value), (#(#none,)*)))
+            }
+        }
+    });
+
+    quote! {
+        impl #ident {
+            /// The oneof and the sibling fields of the `sibling` variants, the siblings of the other
+            /// variants are `None`
+            #[doc(hidden)]
+            pub fn __to_proto_siblings<#(#protos,)* #(#markers,)*>(
+                &self,
+            ) -> (#oneof, (#(::core::option::Option<#protos>,)*))
+            where
+                #(#bounds,)*
+            {
+                match self {
+                    #(#arms,)*
+                }
+            }
+        }
+    }
+}
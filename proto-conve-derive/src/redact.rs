@@ -0,0 +1,62 @@
+//! Generating the rendering of the proto messages for the logs, with the sensitive fields redacted.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::DeriveInput;
+
+use crate::attr::{Container, Field, Target};
+
+/// Whether the derived `FromProto` implements `ProtoRedact` of the proto message, for the structs with
+/// the sensitive fields and the ones with `#[proto(redact)]`
+pub fn enabled(container: &Container, fields: &[Field]) -> bool {
+    container.redact || fields.iter().any(|field| field.sensitive)
+}
+
+/// `ProtoRedact` of the proto message, rendering the fields of the struct in their order
+pub fn proto_redact(
+    input: &DeriveInput,
+    target: &Target,
+    fields: &[Field],
+) -> syn::Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "the sensitive fields and `redact` can not be used in generic structs",
+        ));
+    }
+    let proto_type = target.proto_type;
+    let name = proto_type
+        .segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+        .unwrap_or_default();
+    let fields = fields
+        .iter()
+        .filter(|field| !field.skip && field.parents.is_empty())
+        .map(|field| {
+            let proto_ident = &field.proto_ident;
+            let proto_name = &field.proto_name;
+            match field.sensitive {
+                true => quote! {
+                    .field(#proto_name, &::proto_conve::redact::RedactedDebug(&self.#proto_ident))
+                },
+                false => quote! {
+                    .field(#proto_name, &(&&Field(&self.#proto_ident)).field())
+                },
+            }
+        });
+    Ok(quote! {
+        impl ::proto_conve::redact::ProtoRedact for #proto_type {
+            fn fmt_redacted(
+                &self,
+                f: &mut ::core::fmt::Formatter<'_>,
+            ) -> ::core::fmt::Result {
+                #[allow(unused_imports)]
+                use ::proto_conve::redact::__private::{Field, ViaDebug as _, ViaRedact as _};
+                f.debug_struct(#name)
+                    #(#fields)*
+                    .finish_non_exhaustive()
+            }
+        }
+    })
+}
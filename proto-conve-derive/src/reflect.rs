@@ -0,0 +1,101 @@
+//! Generating the mappings of the derived structs to their proto types, see `proto_conve::reflect`.
+
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::DeriveInput;
+
+use crate::attr::{is_option, last_ident_is, Container, Field, Target};
+
+/// `HasProtoMapping` of the struct, in `proto_conve::__reflect!`, so it is left out without the
+/// `reflect` feature
+pub fn has_proto_mapping(input: &DeriveInput, target: &Target, fields: &[Field]) -> TokenStream {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let rust_type = ident.to_string();
+    let proto_type = path_string(target.proto_type);
+    let fields = fields.iter().map(|field| {
+        let rust_name = field.ident.to_string();
+        let proto_name = field
+            .parents
+            .iter()
+            .map(|(_, name)| name.as_str())
+            .chain([field.proto_name.as_str()])
+            .collect::<Vec<_>>()
+            .join(".");
+        let required = !field.skip
+            && field.default.is_none()
+            && !is_option(field.ty)
+            && !["Vec", "HashMap", "BTreeMap", "HashSet", "BTreeSet"]
+                .iter()
+                .any(|collection| last_ident_is(field.ty, collection));
+        let skipped = field.skip;
+        let transform = match field.from_with.as_ref().or(field.async_with.as_ref()) {
+            Some(function) => {
+                let function = path_string(function);
+                quote!(::core::option::Option::Some(#function))
+            }
+            None if field.transform => quote!(::core::option::Option::Some("transform")),
+            None => quote!(::core::option::Option::None),
+        };
+        quote! {
+            ::proto_conve::reflect::FieldMapping {
+                rust_name: #rust_name,
+                proto_name: #proto_name,
+                required: #required,
+                skipped: #skipped,
+                transform: #transform,
+            }
+        }
+    });
+    quote! {
+        ::proto_conve::__reflect! {
+            impl #impl_generics ::proto_conve::reflect::HasProtoMapping for #ident #ty_generics #where_clause {
+                fn proto_mapping() -> &'static ::proto_conve::reflect::ProtoMapping {
+                    static MAPPING: ::proto_conve::reflect::ProtoMapping =
+                        ::proto_conve::reflect::ProtoMapping {
+                            rust_type: #rust_type,
+                            proto_type: #proto_type,
+                            fields: &[#(#fields),*],
+                        };
+                    &MAPPING
+                }
+            }
+        }
+    }
+}
+
+/// The registrations of the derived conversions of the `direction`, `FromProto` or `ToProto`, for
+/// `proto_conve::coverage`, of the proto types of the structs and the prost enums, the oneofs and the
+/// projections are not the whole messages, the invalid attributes are reported by the conversions
+pub fn register_conversions(input: &DeriveInput, direction: &str) -> TokenStream {
+    let Ok(container) = Container::from_ast(input) else {
+        return TokenStream::new();
+    };
+    let direction = syn::Ident::new(direction, proc_macro2::Span::call_site());
+    let proto_types = match &input.data {
+        syn::Data::Enum(_) if container.oneof.is_some() => Vec::new(),
+        syn::Data::Enum(_) => container.proto_enum.iter().collect(),
+        _ if container.project_from.is_some() => Vec::new(),
+        _ => container
+            .targets()
+            .map(|targets| {
+                targets
+                    .into_iter()
+                    .map(|target| target.proto_type)
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    proto_types
+        .into_iter()
+        .map(|proto_type| quote!(::proto_conve::register_conversion!(#proto_type => #direction);))
+        .collect()
+}
+
+/// The path as it is written, without the spaces of the tokens
+fn path_string(path: &syn::Path) -> String {
+    path.to_token_stream()
+        .to_string()
+        .split_whitespace()
+        .collect()
+}
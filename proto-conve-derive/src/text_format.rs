@@ -0,0 +1,182 @@
+//! Generating the merges of the protobuf text format in to the proto messages and oneof enums, see
+//! `proto_conve::text_format`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Fields, Lit, Path};
+
+use crate::attr::oneof_field_name;
+
+/// How the field is merged, from its `#[prost(...)]` attributes
+enum Kind {
+    Plain,
+    /// The raw `i32` of the prost enum
+    Enumeration(Path),
+    Bytes,
+    Oneof,
+}
+
+pub fn text_proto(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    match &input.data {
+        Data::Struct(data) => {
+            let Fields::Named(named) = &data.fields else {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`TextProto` can only be derived for the structs of the proto messages with the \
+                     named fields",
+                ));
+            };
+            let arms = named
+                .named
+                .iter()
+                .map(|field| {
+                    let member = field.ident.as_ref().expect("the named field");
+                    let name = member.to_string();
+                    let name = name.strip_prefix("r#").unwrap_or(&name);
+                    let merge = match kind(&field.attrs)? {
+                        Kind::Oneof => {
+                            return Ok(quote! {
+                                name if ::proto_conve::text_format::has_oneof_field(&self.#member, name) => {
+                                    ::core::option::Option::Some(
+                                        ::proto_conve::text_format::TextOneof::merge_text_oneof(
+                                            &mut self.#member,
+                                            name,
+                                            value,
+                                        ),
+                                    )
+                                }
+                            })
+                        }
+                        kind => merge(&kind, quote!(&mut self.#member)),
+                    };
+                    Ok(quote! {
+                        #name => ::core::option::Option::Some(
+                            #merge.map_err(|error| ::proto_conve::error::in_field(error, #name)),
+                        ),
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            let message = format!("`{ident}`");
+            Ok(quote! {
+                impl #impl_generics ::proto_conve::text_format::TextProto for #ident #ty_generics #where_clause {
+                    fn merge_text(
+                        &mut self,
+                        value: ::proto_conve::text_format::TextValue,
+                    ) -> ::proto_conve::__private::anyhow::Result<()> {
+                        ::proto_conve::text_format::merge_fields(value, #message, |name, value| match name {
+                            #(#arms)*
+                            _ => ::core::option::Option::None,
+                        })
+                    }
+                }
+            })
+        }
+        // the prost enums are the raw `i32` of the fields, there is nothing to derive
+        Data::Enum(data)
+            if data
+                .variants
+                .iter()
+                .all(|variant| variant.fields.is_empty()) =>
+        {
+            Ok(TokenStream::new())
+        }
+        Data::Enum(data) => {
+            let mut names = Vec::new();
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    if !matches!(&variant.fields, Fields::Unnamed(fields) if fields.unnamed.len() == 1)
+                    {
+                        return Err(syn::Error::new(
+                            variant.ident.span(),
+                            "`TextProto` can only be derived for the oneof enums, with one field in \
+                             every variant",
+                        ));
+                    }
+                    let variant_ident = &variant.ident;
+                    let name = oneof_field_name(variant_ident);
+                    let merge = merge(&kind(&variant.attrs)?, quote!(&mut field));
+                    names.push(name.clone());
+                    Ok(quote! {
+                        #name => {
+                            let mut field = match oneof.take() {
+                                ::core::option::Option::Some(Self::#variant_ident(field)) => field,
+                                _ => ::core::default::Default::default(),
+                            };
+                            let result = #merge;
+                            *oneof = ::core::option::Option::Some(Self::#variant_ident(field));
+                            result.map_err(|error| ::proto_conve::error::in_field(error, #name))
+                        }
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            Ok(quote! {
+                impl #impl_generics ::proto_conve::text_format::TextOneof for #ident #ty_generics #where_clause {
+                    fn has_text_field(name: &str) -> bool {
+                        matches!(name, #(#names)|*)
+                    }
+
+                    fn merge_text_oneof(
+                        oneof: &mut ::core::option::Option<Self>,
+                        name: &str,
+                        value: ::proto_conve::text_format::TextValue,
+                    ) -> ::proto_conve::__private::anyhow::Result<()> {
+                        match name {
+                            #(#arms)*
+                            _ => ::core::result::Result::Ok(()),
+                        }
+                    }
+                }
+            })
+        }
+        Data::Union(_) => Err(syn::Error::new(
+            ident.span(),
+            "`TextProto` can only be derived for structs and enums",
+        )),
+    }
+}
+
+/// The merge of the `value` in to the `place` of the field
+fn merge(kind: &Kind, place: TokenStream) -> TokenStream {
+    match kind {
+        Kind::Enumeration(enumeration) => quote! {
+            ::proto_conve::text_format::TextEnum::merge_text_enum(#place, value, |text_name| {
+                #enumeration::from_str_name(text_name).map(i32::from)
+            })
+        },
+        Kind::Bytes => quote! {
+            ::proto_conve::text_format::TextBytes::merge_text_bytes(#place, value)
+        },
+        Kind::Plain | Kind::Oneof => quote! {
+            ::proto_conve::text_format::TextProto::merge_text(#place, value)
+        },
+    }
+}
+
+/// The kind of the field in its prost attributes, like `#[prost(enumeration = "Status", tag = "2")]`
+fn kind(attrs: &[Attribute]) -> syn::Result<Kind> {
+    let mut kind = Kind::Plain;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("prost")) {
+        attr.parse_nested_meta(|meta| {
+            let value = match meta.input.peek(syn::Token![=]) {
+                true => Some(meta.value()?.parse::<Lit>()?),
+                false => None,
+            };
+            if meta.path.is_ident("enumeration") {
+                let Some(Lit::Str(enumeration)) = value else {
+                    return Err(meta.error("expected `enumeration = \"path::Enum\"`"));
+                };
+                kind = Kind::Enumeration(enumeration.parse()?);
+            } else if meta.path.is_ident("bytes") {
+                kind = Kind::Bytes;
+            } else if meta.path.is_ident("oneof") {
+                kind = Kind::Oneof;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(kind)
+}
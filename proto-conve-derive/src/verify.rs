@@ -0,0 +1,84 @@
+//! Generating the verifiers of the mapped proto fields, in `proto_conve::__verify!`, so they are left
+//! out without the `verify` feature, see `proto_conve::verify`.
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{DeriveInput, Ident, Path};
+
+use crate::attr::{Field, Target, Variant};
+
+/// The function referencing the proto fields of the struct, with the spans of the derived fields, so
+/// the missing ones fail to compile there
+pub fn struct_fields(input: &DeriveInput, target: &Target, fields: &[Field]) -> TokenStream {
+    let (impl_generics, _, where_clause) = input.generics.split_for_impl();
+    let proto_type = target.proto_type;
+    let mut names: Vec<&Ident> = Vec::new();
+    let mut nested = Vec::new();
+    for field in fields.iter().filter(|field| !field.skip) {
+        match field.parents.split_first() {
+            None => {
+                // the alternatives of `first_of` and the `siblings` are the proto fields too
+                let alternatives = field
+                    .first_of
+                    .iter()
+                    .chain(&field.siblings)
+                    .map(|(alternative, _)| alternative);
+                for name in std::iter::once(&field.proto_ident).chain(alternatives) {
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+            }
+            // the nested messages of `path` are optional
+            Some(((outer, _), inner)) => {
+                if !names.contains(&outer) {
+                    names.push(outer);
+                }
+                let mut parent = quote!(proto.#outer.as_ref());
+                for (ident, _) in inner {
+                    parent = quote_spanned!(ident.span()=> #parent.and_then(|value| value.#ident.as_ref()));
+                }
+                let name = &field.proto_ident;
+                nested.push(quote_spanned! {name.span()=>
+                    let _ = #parent.map(|value| &value.#name);
+                });
+            }
+        }
+    }
+    let names = names
+        .iter()
+        .map(|name| quote_spanned!(name.span()=> let _ = &proto.#name;));
+    quote! {
+        ::proto_conve::__verify! {
+            const _: () = {
+                #[allow(dead_code)]
+                fn verify #impl_generics (proto: &#proto_type) #where_clause {
+                    #(#names)*
+                    #(#nested)*
+                }
+            };
+        }
+    }
+}
+
+/// The function matching the mapped variants of the prost oneof enum
+pub fn oneof_variants(oneof: &Path, variants: &[Variant]) -> TokenStream {
+    let arms = variants.iter().map(|variant| {
+        let proto_ident = &variant.proto_ident;
+        quote_spanned!(proto_ident.span()=> #oneof::#proto_ident(_) => {})
+    });
+    quote! {
+        ::proto_conve::__verify! {
+            const _: () = {
+                // the variants, which are not mapped, are left to the conversions
+                #[allow(dead_code, unreachable_patterns)]
+                fn verify(proto: &#oneof) {
+                    match proto {
+                        #(#arms,)*
+                        _ => {}
+                    }
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Generating the conversions of the newtypes mapped to the wrapper messages of a single field.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Ident, Type};
+
+use crate::attr::Container;
+
+/// The type of the single field of the newtype
+fn inner<'a>(input: &'a DeriveInput, container: &Container) -> syn::Result<&'a Type> {
+    let newtype = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => fields.unnamed.first(),
+            _ => None,
+        },
+        _ => None,
+    };
+    let Some(field) = newtype else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`wrapper` can only be used on the newtypes, like `struct UserId(String)`",
+        ));
+    };
+    if !input.generics.params.is_empty() || container.targets()?.len() > 1 {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "the `wrapper` newtypes can not be generic or have named mappings",
+        ));
+    }
+    Ok(&field.ty)
+}
+
+/// `FromProto` of the wrapper message converting its field in to the newtype
+pub fn from_proto(
+    input: &DeriveInput,
+    container: &Container,
+    field: &Ident,
+) -> syn::Result<TokenStream> {
+    let ty = inner(input, container)?;
+    let ident = &input.ident;
+    let proto_type = container.proto_type()?;
+    let from_proto = container.trait_path("FromProto");
+    let from_proto_field = container.trait_path("FromProtoField");
+    let name = field.to_string();
+    Ok(quote! {
+        impl #from_proto for #proto_type {
+            type Result = #ident;
+
+            fn from_proto(self) -> ::proto_conve::__private::anyhow::Result<Self::Result> {
+                #from_proto_field::<#ty, _>::from_proto_field(self.#field, #name).map(#ident)
+            }
+        }
+    })
+}
+
+/// `ToProto` of the newtype constructing the wrapper message
+pub fn to_proto(
+    input: &DeriveInput,
+    container: &Container,
+    field: &Ident,
+) -> syn::Result<TokenStream> {
+    inner(input, container)?;
+    let ident = &input.ident;
+    let proto_type = container.proto_type()?;
+    let to_proto = container.trait_path("ToProto");
+    let to_proto_field = container.trait_path("ToProtoField");
+    Ok(quote! {
+        impl #to_proto for #ident {
+            type Result = #proto_type;
+
+            #[allow(clippy::needless_update)]
+            fn to_proto(&self) -> Self::Result {
+                #proto_type {
+                    #field: #to_proto_field::to_proto_field(&self.0),
+                    ..::core::default::Default::default()
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,88 @@
+//! The allocations of the derived conversions, counted by the global allocator of this test binary.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use proto_conve::error::{ConversionError, MissingField, PathSegment};
+use proto_conve::{impl_traits, FromProto};
+
+/// Counts the allocations of the current thread
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// The number of the allocations made by `f` in this thread
+fn allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+mod proto {
+    #[derive(Debug, Clone, Default)]
+    pub struct Sensor {
+        pub id: u64,
+    }
+
+    #[derive(Debug, Clone, Default)]
+    pub struct Reading {
+        pub sensor: Option<Sensor>,
+        pub value: f64,
+    }
+}
+
+impl_traits!(prelude);
+
+#[derive(Debug, PartialEq, FromProto)]
+#[proto(type = "proto::Sensor")]
+pub struct Sensor {
+    id: u64,
+}
+
+#[derive(Debug, PartialEq, FromProto)]
+#[proto(type = "proto::Reading")]
+pub struct Reading {
+    sensor: Sensor,
+    value: f64,
+}
+
+#[test]
+fn test_missing_required() {
+    let present = proto::Reading {
+        sensor: Some(proto::Sensor { id: 1 }),
+        value: 2.5,
+    };
+    let (reading, count) = allocations(|| present.from_proto());
+    assert!(reading.is_ok());
+    assert_eq!(count, 0);
+
+    // the first error initializes the lazy state of the errors, like the backtrace capture
+    let _ = anyhow::Error::from(MissingField::new("sensor"));
+    let (_, boundary) = allocations(|| anyhow::Error::from(MissingField::new("sensor")));
+    // the missing field is converted in to `anyhow::Error` once, where `from_proto` returns it
+    let (error, count) = allocations(|| proto::Reading::default().from_proto());
+    assert_eq!(count, boundary);
+    let error = error.unwrap_err();
+    assert_eq!(error.to_string(), "sensor: Required is missing");
+    let error = error.downcast::<ConversionError>().unwrap();
+    assert_eq!(
+        error.path().collect::<Vec<_>>(),
+        [&PathSegment::Field("sensor")]
+    );
+}
@@ -0,0 +1,5882 @@
+#![allow(dead_code)]
+
+use proto_conve::arena::{ArenaVec, Bump};
+use proto_conve::{
+    impl_traits, FromProto, FromProtoLossy, FromProtoVersioned, MergeFromProto, ToProto,
+    ToProtoBuilder, ValidateFromProto,
+};
+
+mod proto {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Address {
+        pub street: String,
+        pub number: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct User {
+        pub id: u64,
+        pub name: String,
+        pub active: bool,
+        pub score: f64,
+        pub address: Option<Address>,
+        pub previous: Vec<Address>,
+        pub tags: Vec<String>,
+        pub nickname: Option<String>,
+    }
+}
+
+impl_traits!(prelude);
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "proto::Address")]
+pub struct Address {
+    street: String,
+    number: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "proto::User")]
+pub struct User {
+    id: u64,
+    name: String,
+    active: bool,
+    score: f64,
+    address: Address,
+    previous: Vec<Address>,
+    tags: Vec<String>,
+    nickname: Option<String>,
+}
+
+fn proto_user() -> proto::User {
+    proto::User {
+        id: 7,
+        name: "Anna".to_string(),
+        active: true,
+        score: 0.5,
+        address: Some(proto::Address {
+            street: "Long".to_string(),
+            number: 12,
+        }),
+        previous: vec![proto::Address {
+            street: "Short".to_string(),
+            number: 1,
+        }],
+        tags: vec!["admin".to_string()],
+        nickname: None,
+    }
+}
+
+#[test]
+fn test_from_proto() {
+    let user = proto_user().from_proto().unwrap();
+    assert_eq!(
+        user,
+        User {
+            id: 7,
+            name: "Anna".to_string(),
+            active: true,
+            score: 0.5,
+            address: Address {
+                street: "Long".to_string(),
+                number: 12,
+            },
+            previous: vec![Address {
+                street: "Short".to_string(),
+                number: 1,
+            }],
+            tags: vec!["admin".to_string()],
+            nickname: None,
+        }
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    let proto = proto_user();
+    assert_eq!(proto.clone().from_proto().unwrap().to_proto(), proto);
+}
+
+#[test]
+fn test_missing_required() {
+    let proto = proto::User {
+        address: None,
+        ..proto_user()
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "address: Required is missing");
+}
+
+mod renamed {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Member {
+        pub uid: u64,
+        pub r#type: String,
+    }
+
+    #[allow(non_snake_case)]
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Profile {
+        pub displayName: String,
+        pub avatarUrl: Option<super::proto::Address>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "renamed::Member")]
+pub struct Member {
+    #[proto(rename = "uid")]
+    user_id: u64,
+    #[proto(rename = "r#type")]
+    kind: String,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "renamed::Profile", rename_all = "camelCase")]
+pub struct Profile {
+    display_name: String,
+    #[proto(rename = "avatarUrl")]
+    avatar: Address,
+}
+
+#[test]
+fn test_rename() {
+    let proto = renamed::Member {
+        uid: 3,
+        r#type: "owner".to_string(),
+    };
+    let member = proto.clone().from_proto().unwrap();
+    assert_eq!(
+        member,
+        Member {
+            user_id: 3,
+            kind: "owner".to_string()
+        }
+    );
+    assert_eq!(member.to_proto(), proto);
+}
+
+#[test]
+fn test_rename_all() {
+    let proto = renamed::Profile {
+        displayName: "Anna".to_string(),
+        avatarUrl: Some(proto::Address::default()),
+    };
+    let profile = proto.clone().from_proto().unwrap();
+    assert_eq!(profile.display_name, "Anna");
+    assert_eq!(profile.to_proto(), proto);
+}
+
+#[test]
+fn test_rename_error() {
+    let proto = renamed::Profile {
+        displayName: "Anna".to_string(),
+        avatarUrl: None,
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "avatarUrl: Required is missing");
+}
+
+mod defaults {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Settings {
+        pub name: String,
+        pub retries: Option<u32>,
+        pub timeout: Option<u64>,
+    }
+}
+
+fn default_timeout() -> u64 {
+    30
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "defaults::Settings")]
+pub struct Settings {
+    name: String,
+    #[proto(default)]
+    retries: u32,
+    #[proto(default = "default_timeout")]
+    timeout: u64,
+    #[proto(skip)]
+    cache: Vec<String>,
+}
+
+#[test]
+fn test_default() {
+    let settings = defaults::Settings {
+        name: "a".to_string(),
+        retries: None,
+        timeout: None,
+    }
+    .from_proto()
+    .unwrap();
+    assert_eq!(
+        settings,
+        Settings {
+            name: "a".to_string(),
+            retries: 0,
+            timeout: 30,
+            cache: Vec::new(),
+        }
+    );
+
+    let settings = defaults::Settings {
+        name: "a".to_string(),
+        retries: Some(3),
+        timeout: Some(5),
+    }
+    .from_proto()
+    .unwrap();
+    assert_eq!((settings.retries, settings.timeout), (3, 5));
+}
+
+#[test]
+fn test_skip() {
+    let settings = Settings {
+        name: "a".to_string(),
+        retries: 1,
+        timeout: 2,
+        cache: vec!["x".to_string()],
+    };
+    let proto = settings.to_proto();
+    assert_eq!(
+        proto,
+        defaults::Settings {
+            name: "a".to_string(),
+            retries: Some(1),
+            timeout: Some(2),
+        }
+    );
+    assert!(proto.from_proto().unwrap().cache.is_empty());
+}
+
+mod encoded {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Blob {
+        pub data: String,
+        pub label: String,
+    }
+}
+
+/// Minimal base64 for the tests
+mod base64 {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    pub fn from_proto(value: String) -> anyhow::Result<Vec<u8>> {
+        let mut bits = 0u32;
+        let mut count = 0;
+        let mut result = Vec::new();
+        for c in value.bytes().filter(|c| *c != b'=') {
+            let Some(index) = ALPHABET.iter().position(|a| *a == c) else {
+                anyhow::bail!("Invalid base64 character {:?}", c as char);
+            };
+            bits = (bits << 6) | index as u32;
+            count += 6;
+            if count >= 8 {
+                count -= 8;
+                result.push((bits >> count) as u8);
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn to_proto(value: &[u8]) -> String {
+        let mut result = String::new();
+        for chunk in value.chunks(3) {
+            let bits = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |bits, (i, b)| bits | (*b as u32) << (16 - 8 * i));
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    result.push(ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char);
+                } else {
+                    result.push('=');
+                }
+            }
+        }
+        result
+    }
+}
+
+fn shout(value: &str) -> String {
+    value.to_uppercase()
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "encoded::Blob")]
+pub struct Blob {
+    #[proto(with = "base64")]
+    data: Vec<u8>,
+    #[proto(to_with = "shout")]
+    label: String,
+}
+
+#[test]
+fn test_with() {
+    let blob = encoded::Blob {
+        data: "aGVsbG8=".to_string(),
+        label: "small".to_string(),
+    }
+    .from_proto()
+    .unwrap();
+    assert_eq!(blob.data, b"hello");
+    assert_eq!(blob.label, "small");
+
+    assert_eq!(
+        blob.to_proto(),
+        encoded::Blob {
+            data: "aGVsbG8=".to_string(),
+            label: "SMALL".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_with_error() {
+    let error = encoded::Blob {
+        data: "a?".to_string(),
+        label: String::new(),
+    }
+    .from_proto()
+    .unwrap_err();
+    assert_eq!(error.to_string(), "data: Invalid base64 character '?'");
+}
+
+mod status {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    #[repr(i32)]
+    pub enum ProtoStatus {
+        Unspecified = 0,
+        Active = 1,
+        Suspended = 2,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Account {
+        pub status: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Subscription {
+        pub status: i32,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, FromProto, ToProto)]
+#[proto(enum = "status::ProtoStatus")]
+pub enum Status {
+    Unspecified,
+    Active,
+    #[proto(rename = "Suspended")]
+    Blocked,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, FromProto, ToProto)]
+#[proto(type = "i32", unknown = "Other")]
+pub enum Level {
+    #[proto(value = 1)]
+    Low,
+    #[proto(value = 5)]
+    High,
+    #[proto(value = -1)]
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "status::Account")]
+pub struct Account {
+    status: Status,
+}
+
+#[test]
+fn test_enum() {
+    for (proto, value) in [
+        (status::ProtoStatus::Unspecified, Status::Unspecified),
+        (status::ProtoStatus::Active, Status::Active),
+        (status::ProtoStatus::Suspended, Status::Blocked),
+    ] {
+        assert_eq!(Status::from_proto_alias(proto as i32).unwrap(), value);
+        assert_eq!(ToProtoAlias::<i32>::to_proto_alias(&value), proto as i32);
+    }
+    assert_eq!(Level::from_proto_alias(5).unwrap(), Level::High);
+    assert_eq!(ToProtoAlias::<i32>::to_proto_alias(&Level::Other), -1);
+    assert_eq!(
+        Vec::<Status>::from_proto_alias(vec![0, 2]).unwrap(),
+        vec![Status::Unspecified, Status::Blocked]
+    );
+}
+
+#[test]
+fn test_enum_unknown() {
+    let error = Status::from_proto_alias(7).unwrap_err();
+    assert_eq!(error.to_string(), "Unknown value 7 of Status");
+    assert_eq!(Level::from_proto_alias(7).unwrap(), Level::Other);
+}
+
+#[test]
+fn test_enum_mode() {
+    use proto_conve::mode::ConversionMode;
+
+    let error = Status::from_proto_alias_mode(7, ConversionMode::Strict).unwrap_err();
+    assert_eq!(error.to_string(), "Unknown value 7 of Status");
+    assert_eq!(
+        Status::from_proto_alias_mode(7, ConversionMode::Lenient).unwrap(),
+        Status::Unspecified
+    );
+    assert_eq!(
+        Vec::<Status>::from_proto_alias_mode(vec![1, 9], ConversionMode::Lenient).unwrap(),
+        [Status::Active, Status::Unspecified]
+    );
+    assert_eq!(
+        Level::from_proto_alias_mode(7, ConversionMode::Strict).unwrap(),
+        Level::Other
+    );
+}
+
+#[test]
+fn test_enum_field() {
+    let proto = status::Account { status: 1 };
+    let account = proto.clone().from_proto().unwrap();
+    assert_eq!(
+        account,
+        Account {
+            status: Status::Active
+        }
+    );
+    assert_eq!(account.to_proto(), proto);
+
+    let error = status::Account { status: 3 }.from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "status: Unknown value 3 of Status");
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, FromProto, ToProto)]
+#[proto(enum = "status::ProtoStatus", open)]
+pub enum OpenStatus {
+    Unspecified,
+    Active,
+    Suspended,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "status::Subscription")]
+pub struct Subscription {
+    status: proto_conve::OpenEnum<OpenStatus>,
+}
+
+#[test]
+fn test_open_enum() {
+    use proto_conve::OpenEnum;
+
+    let subscription = status::Subscription { status: 2 }.from_proto().unwrap();
+    assert_eq!(subscription.status, OpenEnum::Known(OpenStatus::Suspended));
+
+    let proto = status::Subscription { status: 42 };
+    let subscription = proto.clone().from_proto().unwrap();
+    assert_eq!(subscription.status, OpenEnum::Unknown(42));
+    assert_eq!(subscription.to_proto(), proto);
+}
+
+mod presence {
+    /// The enum as generated by prost, with its `TryFrom<i32>` and `From<Presence> for i32`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(i32)]
+    pub enum Presence {
+        Unspecified = 0,
+        Online = 1,
+        Away = 2,
+    }
+
+    impl TryFrom<i32> for Presence {
+        type Error = i32;
+
+        fn try_from(value: i32) -> Result<Self, i32> {
+            match value {
+                0 => Ok(Presence::Unspecified),
+                1 => Ok(Presence::Online),
+                2 => Ok(Presence::Away),
+                value => Err(value),
+            }
+        }
+    }
+
+    impl From<Presence> for i32 {
+        fn from(value: Presence) -> i32 {
+            value as i32
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Device {
+        pub presence: i32,
+        pub reported: i32,
+    }
+
+    impl Device {
+        /// The getter generated by prost, the unknown values are the default variant
+        pub fn presence(&self) -> Presence {
+            Presence::try_from(self.presence).unwrap_or(Presence::Unspecified)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "presence::Device")]
+pub struct Device {
+    #[proto(enumeration = "presence::Presence")]
+    presence: presence::Presence,
+    #[proto(enumeration = "presence::Presence")]
+    reported: proto_conve::OpenEnum<presence::Presence>,
+}
+
+#[test]
+fn test_enumeration_field() {
+    use proto_conve::OpenEnum;
+
+    let proto = presence::Device {
+        presence: 2,
+        reported: 1,
+    };
+    let device = proto.clone().from_proto().unwrap();
+    assert_eq!(device.presence, presence::Presence::Away);
+    assert_eq!(device.reported, OpenEnum::Known(presence::Presence::Online));
+    assert_eq!(device.to_proto(), proto);
+
+    let proto = presence::Device {
+        presence: 9,
+        reported: 0,
+    };
+    assert_eq!(proto.presence(), presence::Presence::Unspecified);
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "presence: Unknown value 9 of Presence");
+
+    let proto = presence::Device {
+        presence: 1,
+        reported: 9,
+    };
+    let device = proto.clone().from_proto().unwrap();
+    assert_eq!(device.reported, OpenEnum::Unknown(9));
+    assert_eq!(device.to_proto(), proto);
+}
+
+mod event {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Cancelled {}
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Payload {
+        Created(super::proto::Address),
+        Renamed(String),
+        Cancelled(Cancelled),
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Event {
+        pub id: u64,
+        pub payload: Option<Payload>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(oneof = "event::Payload")]
+pub enum Payload {
+    Created(Address),
+    #[proto(rename = "Renamed")]
+    NameChanged(String),
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "event::Event")]
+pub struct Event {
+    id: u64,
+    payload: Payload,
+}
+
+#[test]
+fn test_oneof() {
+    for (proto, payload) in [
+        (
+            event::Payload::Created(proto::Address {
+                street: "Long".to_string(),
+                number: 1,
+            }),
+            Payload::Created(Address {
+                street: "Long".to_string(),
+                number: 1,
+            }),
+        ),
+        (
+            event::Payload::Renamed("new".to_string()),
+            Payload::NameChanged("new".to_string()),
+        ),
+        (
+            event::Payload::Cancelled(event::Cancelled {}),
+            Payload::Cancelled,
+        ),
+    ] {
+        let proto = event::Event {
+            id: 1,
+            payload: Some(proto),
+        };
+        let event = proto.clone().from_proto().unwrap();
+        assert_eq!(event, Event { id: 1, payload });
+        assert_eq!(event.to_proto(), proto);
+    }
+}
+
+#[test]
+fn test_oneof_missing() {
+    let error = event::Event {
+        id: 1,
+        payload: None,
+    }
+    .from_proto()
+    .unwrap_err();
+    assert_eq!(error.to_string(), "payload: Required is missing");
+}
+
+mod handovers {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Delivered {}
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum State {
+        InTransit(String),
+        Delivered(Delivered),
+        Lost(super::proto::Address),
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Handover {
+        pub id: u64,
+        pub state: Option<State>,
+        pub delivered_at: Option<super::Timestamp>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(oneof = "handovers::State")]
+pub enum HandoverState {
+    InTransit(String),
+    #[proto(sibling = "delivered_at")]
+    Delivered(chrono::DateTime<chrono::Utc>),
+    Lost(Address),
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "handovers::Handover")]
+pub struct Handover {
+    id: u64,
+    #[proto(siblings("delivered_at"))]
+    state: HandoverState,
+}
+
+#[test]
+fn test_oneof_sibling() {
+    let delivered_at = Timestamp {
+        seconds: 1_700_000_000,
+        nanos: 0,
+    };
+    let proto = handovers::Handover {
+        id: 1,
+        state: Some(handovers::State::Delivered(handovers::Delivered {})),
+        delivered_at: Some(delivered_at.clone()),
+    };
+    let handover = proto.clone().from_proto().unwrap();
+    let expected = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+    assert_eq!(handover.state, HandoverState::Delivered(expected));
+    assert_eq!(handover.to_proto(), proto);
+
+    // the sibling of the other variants is ignored, and not written
+    let proto = handovers::Handover {
+        id: 2,
+        state: Some(handovers::State::InTransit("Vienna".to_string())),
+        delivered_at: Some(delivered_at),
+    };
+    let handover = proto.clone().from_proto().unwrap();
+    assert_eq!(
+        handover.state,
+        HandoverState::InTransit("Vienna".to_string())
+    );
+    assert_eq!(handover.to_proto().delivered_at, None);
+
+    let error = handovers::Handover {
+        id: 3,
+        state: Some(handovers::State::Delivered(handovers::Delivered {})),
+        delivered_at: None,
+    }
+    .from_proto()
+    .unwrap_err();
+    assert_eq!(error.to_string(), "delivered_at: Required is missing");
+
+    let error = handovers::Handover::default().from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "state: Required is missing");
+}
+
+mod catalog {
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Item {
+        pub name: String,
+        pub owner: Option<super::proto::Address>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Catalog {
+        pub items: Vec<Item>,
+        pub by_slot: HashMap<String, Item>,
+        pub labels: HashMap<String, String>,
+        pub tags: Vec<String>,
+    }
+}
+
+/// Map keys in the form `slot-<number>`
+mod slot {
+    pub fn from_proto(key: String) -> anyhow::Result<u32> {
+        match key.strip_prefix("slot-").map(str::parse) {
+            Some(Ok(slot)) => Ok(slot),
+            _ => anyhow::bail!("Invalid slot"),
+        }
+    }
+
+    pub fn to_proto(slot: &u32) -> String {
+        format!("slot-{slot}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "catalog::Item")]
+pub struct Item {
+    name: String,
+    owner: Address,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "catalog::Catalog")]
+pub struct Catalog {
+    items: Vec<Item>,
+    #[proto(key_with = "slot")]
+    by_slot: std::collections::BTreeMap<u32, Item>,
+    labels: std::collections::HashMap<String, String>,
+    #[proto(set)]
+    tags: std::collections::BTreeSet<String>,
+}
+
+fn catalog_item(name: &str) -> catalog::Item {
+    catalog::Item {
+        name: name.to_string(),
+        owner: Some(proto::Address::default()),
+    }
+}
+
+#[test]
+fn test_repeated_and_map() {
+    let proto = catalog::Catalog {
+        items: vec![catalog_item("a"), catalog_item("b")],
+        by_slot: [("slot-2".to_string(), catalog_item("c"))].into(),
+        labels: [("env".to_string(), "prod".to_string())].into(),
+        tags: vec!["x".to_string(), "y".to_string(), "x".to_string()],
+    };
+    let catalog = proto.clone().from_proto().unwrap();
+    assert_eq!(catalog.items.len(), 2);
+    assert_eq!(catalog.items[1].name, "b");
+    assert_eq!(catalog.by_slot[&2].name, "c");
+    assert_eq!(catalog.labels["env"], "prod");
+    assert_eq!(
+        catalog.tags,
+        ["x".to_string(), "y".to_string()].into_iter().collect()
+    );
+
+    let back = catalog.to_proto();
+    assert_eq!(back.items, proto.items);
+    assert_eq!(back.by_slot, proto.by_slot);
+    assert_eq!(back.labels, proto.labels);
+    assert_eq!(back.tags, vec!["x".to_string(), "y".to_string()]);
+}
+
+#[test]
+fn test_repeated_element_error() {
+    let proto = catalog::Catalog {
+        items: vec![
+            catalog_item("a"),
+            catalog::Item {
+                name: "b".to_string(),
+                owner: None,
+            },
+        ],
+        ..Default::default()
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "items[1].owner: Required is missing");
+}
+
+#[test]
+fn test_map_key_error() {
+    let proto = catalog::Catalog {
+        by_slot: [("box-1".to_string(), catalog_item("a"))].into(),
+        ..Default::default()
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "by_slot[\"box-1\"]: Invalid slot");
+}
+
+#[test]
+fn test_map_duplicate_key() {
+    let proto = catalog::Catalog {
+        by_slot: [
+            ("slot-1".to_string(), catalog_item("a")),
+            ("slot-01".to_string(), catalog_item("b")),
+        ]
+        .into(),
+        ..Default::default()
+    };
+    let error = proto.from_proto().unwrap_err();
+    let message = error.to_string();
+    // the raw key found second is reported, the order of the proto map is not given
+    assert!(
+        message == "by_slot[\"slot-1\"]: The key converts in to the same key as another one"
+            || message
+                == "by_slot[\"slot-01\"]: The key converts in to the same key as another one",
+        "{message}"
+    );
+}
+
+mod quotas {
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Quotas {
+        pub by_owner: HashMap<String, u32>,
+        pub by_tier: HashMap<i32, u32>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct OpenQuotas {
+        pub by_tier: HashMap<i32, u32>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct FallbackQuotas {
+        pub by_tier: HashMap<i32, u32>,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, FromProto, ToProto)]
+#[proto(type = "i32")]
+pub enum Tier {
+    #[proto(value = 1)]
+    Free,
+    #[proto(value = 2)]
+    Pro,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, FromProto, ToProto)]
+#[proto(type = "i32", open)]
+pub enum OpenTier {
+    #[proto(value = 1)]
+    Free,
+    #[proto(value = 2)]
+    Pro,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, FromProto, ToProto)]
+#[proto(type = "i32", unknown = "Other")]
+pub enum FallbackTier {
+    #[proto(value = 1)]
+    Free,
+    #[proto(value = -1)]
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "quotas::Quotas")]
+pub struct Quotas {
+    #[proto(key = "uuid")]
+    by_owner: std::collections::HashMap<uuid::Uuid, u32>,
+    #[proto(key = "enum")]
+    by_tier: std::collections::BTreeMap<Tier, u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "quotas::OpenQuotas")]
+pub struct OpenQuotas {
+    #[proto(key = "enum")]
+    by_tier: std::collections::BTreeMap<proto_conve::OpenEnum<OpenTier>, u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "quotas::FallbackQuotas")]
+pub struct FallbackQuotas {
+    #[proto(key = "enum")]
+    by_tier: std::collections::HashMap<FallbackTier, u32>,
+}
+
+const OWNER: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+
+#[test]
+fn test_uuid_keys() {
+    let proto = quotas::Quotas {
+        by_owner: [(OWNER.to_string(), 10)].into(),
+        by_tier: [(1, 5), (2, 50)].into(),
+    };
+    let quotas = proto.clone().from_proto().unwrap();
+    assert_eq!(quotas.by_owner[&uuid::Uuid::parse_str(OWNER).unwrap()], 10);
+    assert_eq!(quotas.by_tier[&Tier::Pro], 50);
+    assert_eq!(quotas.to_proto(), proto);
+
+    // the upper case keys are written back in lower case
+    let proto = quotas::Quotas {
+        by_owner: [(OWNER.to_uppercase(), 10)].into(),
+        ..Default::default()
+    };
+    let quotas = proto.from_proto().unwrap();
+    assert_eq!(quotas.to_proto().by_owner[OWNER], 10);
+
+    let proto = quotas::Quotas {
+        by_owner: [("not-a-uuid".to_string(), 1)].into(),
+        ..Default::default()
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("by_owner[\"not-a-uuid\"]: The key is not a UUID: "),
+        "{error}"
+    );
+
+    let proto = quotas::Quotas {
+        by_owner: [(OWNER.to_string(), 1), (OWNER.to_uppercase(), 2)].into(),
+        ..Default::default()
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert!(error
+        .to_string()
+        .ends_with("The key converts in to the same key as another one"));
+}
+
+#[test]
+fn test_enum_keys() {
+    use proto_conve::OpenEnum;
+
+    // closed enums fail on the unknown keys
+    let proto = quotas::Quotas {
+        by_tier: [(1, 5), (7, 70)].into(),
+        ..Default::default()
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "by_tier[\"7\"]: Unknown value 7 of Tier");
+
+    // open enums keep them
+    let proto = quotas::OpenQuotas {
+        by_tier: [(1, 5), (7, 70)].into(),
+    };
+    let quotas = proto.clone().from_proto().unwrap();
+    assert_eq!(quotas.by_tier[&OpenEnum::Unknown(7)], 70);
+    assert_eq!(quotas.by_tier[&OpenEnum::Known(OpenTier::Free)], 5);
+    assert_eq!(quotas.to_proto(), proto);
+
+    // the variant for the unknown keys, the second unknown key is a duplicate of it
+    let proto = quotas::FallbackQuotas {
+        by_tier: [(1, 5), (7, 70)].into(),
+    };
+    let quotas = proto.from_proto().unwrap();
+    assert_eq!(quotas.by_tier[&FallbackTier::Other], 70);
+    let proto = quotas::FallbackQuotas {
+        by_tier: [(7, 70), (8, 80)].into(),
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert!(error
+        .to_string()
+        .ends_with("The key converts in to the same key as another one"));
+}
+
+mod tree {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Node {
+        pub value: i64,
+        pub left: Option<Box<Node>>,
+        pub right: Option<Box<Node>>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Link {
+        pub value: u32,
+        pub next: Option<Box<Link>>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Wrapper {
+        pub inner: Option<super::proto::Address>,
+        pub extra: Option<super::proto::Address>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "tree::Node")]
+pub struct Node {
+    value: i64,
+    left: Option<Box<Node>>,
+    right: Option<Box<Node>>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "tree::Link")]
+pub struct Link {
+    value: u32,
+    next: Option<Box<Link>>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "tree::Wrapper")]
+pub struct Wrapper {
+    #[proto(boxed)]
+    inner: Box<Address>,
+    #[proto(boxed)]
+    extra: Option<Box<Address>>,
+}
+
+fn full_tree(depth: u32, value: i64) -> tree::Node {
+    let child = |value| (depth > 1).then(|| Box::new(full_tree(depth - 1, value)));
+    tree::Node {
+        value,
+        left: child(value * 2),
+        right: child(value * 2 + 1),
+    }
+}
+
+#[test]
+fn test_tree() {
+    let proto = full_tree(3, 1);
+    let node = proto.clone().from_proto().unwrap();
+    assert_eq!(node.right.as_ref().unwrap().left.as_ref().unwrap().value, 6);
+    assert_eq!(node.to_proto(), proto);
+}
+
+#[test]
+fn test_deep_list() {
+    // the stack of the test thread is only just enough for the debug builds, so it is given as for the
+    // other deep messages
+    let convert = || {
+        let mut proto = tree::Link::default();
+        for value in 1..5000 {
+            proto = tree::Link {
+                value,
+                next: Some(Box::new(proto)),
+            };
+        }
+        let link = proto.clone().from_proto().unwrap();
+        assert_eq!(link.value, 4999);
+        assert_eq!(link.to_proto(), proto);
+    };
+    std::thread::Builder::new()
+        .stack_size(8 << 20)
+        .spawn(convert)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn test_boxed() {
+    let proto = tree::Wrapper {
+        inner: Some(proto::Address::default()),
+        extra: None,
+    };
+    let wrapper = proto.clone().from_proto().unwrap();
+    assert_eq!(wrapper.inner.street, "");
+    assert!(wrapper.extra.is_none());
+    assert_eq!(wrapper.to_proto(), proto);
+}
+
+mod syntax {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Expr {
+        pub value: i32,
+        pub left: Option<Box<Expr>>,
+        pub right: Option<Box<Expr>>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Program {
+        pub items: Vec<Option<Box<Expr>>>,
+        // the boxed list of a recursive wrapper message
+        #[allow(clippy::box_collection)]
+        pub extra: Option<Box<Vec<Expr>>>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "syntax::Expr")]
+pub struct Expr {
+    value: i16,
+    left: Option<Box<Expr>>,
+    right: Option<Box<Expr>>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "syntax::Program")]
+pub struct Program {
+    items: Vec<Option<Box<Expr>>>,
+    #[allow(clippy::box_collection)]
+    extra: Option<Box<Vec<Expr>>>,
+}
+
+fn leaf(value: i32) -> Option<Box<syntax::Expr>> {
+    Some(Box::new(syntax::Expr {
+        value,
+        ..Default::default()
+    }))
+}
+
+#[test]
+fn test_boxed_containers() {
+    let bad = syntax::Expr {
+        value: 1,
+        left: Some(Box::new(syntax::Expr {
+            value: 2,
+            left: leaf(3),
+            right: leaf(i32::MAX),
+        })),
+        right: None,
+    };
+    let mut proto = syntax::Program {
+        items: vec![leaf(1), None, leaf(2), Some(Box::new(bad))],
+        extra: Some(Box::new(vec![syntax::Expr::default()])),
+    };
+    let error = proto.clone().from_proto().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "items[3].left.right.value: The value 2147483647 does not fit in to i16"
+    );
+
+    proto.items.pop();
+    let program = proto.clone().from_proto().unwrap();
+    assert_eq!(program.items[2].as_ref().unwrap().value, 2);
+    assert!(program.items[1].is_none());
+    assert_eq!(program.extra.as_ref().unwrap().len(), 1);
+    assert_eq!(program.to_proto(), proto);
+}
+
+/// The right-leaning chain of `depth` levels, the last one has the `value`
+fn chain(depth: usize, value: i32) -> syntax::Expr {
+    let mut proto = syntax::Expr {
+        value,
+        ..Default::default()
+    };
+    for value in 1..depth as i32 {
+        proto = syntax::Expr {
+            value,
+            left: leaf(value),
+            right: Some(Box::new(proto)),
+        };
+    }
+    proto
+}
+
+#[test]
+fn test_deep_expr() {
+    use proto_conve::budget::{self, BudgetLimit, ConversionBudget};
+
+    // the conversion recurses once per level, the depth budget fails it before the stack of the thread
+    // runs out
+    let budget = ConversionBudget::unlimited().max_depth(256);
+    let error = budget::limit(budget, || chain(10_000, 0).from_proto()).unwrap_err();
+    let exceeded = budget::exceeded(&error).unwrap();
+    assert_eq!(exceeded.limit(), BudgetLimit::Depth);
+    let error = error
+        .downcast::<proto_conve::error::ConversionError>()
+        .unwrap();
+    // the path of the field exceeding the budget, one deeper than it
+    assert_eq!(error.path().count(), 257);
+
+    // within the budget the chain converts, and the error deep in it gets the whole path
+    const DEPTH: usize = 200;
+    let proto = chain(DEPTH, 0);
+    let expr = budget::limit(budget, || proto.clone().from_proto()).unwrap();
+    assert_eq!(expr.value, DEPTH as i16 - 1);
+    assert_eq!(expr.to_proto(), proto);
+    let error = budget::limit(budget, || chain(DEPTH, i32::MAX).from_proto()).unwrap_err();
+    let error = error
+        .downcast::<proto_conve::error::ConversionError>()
+        .unwrap();
+    assert_eq!(error.path().count(), DEPTH);
+}
+
+mod booking {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Booking {
+        pub guest: String,
+        pub from: u32,
+        pub to: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Reservation {
+        pub guest: String,
+        pub from: u32,
+        pub to: u32,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "booking::Booking", validate = "check_booking")]
+pub struct Booking {
+    #[proto(validate = "check_guest")]
+    guest: String,
+    from: u32,
+    to: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(
+    type = "booking::Reservation",
+    validate = "check_checked_booking",
+    validate_on_to_proto
+)]
+pub struct CheckedBooking {
+    #[proto(validate = "check_guest")]
+    guest: String,
+    from: u32,
+    to: u32,
+}
+
+fn check_booking(booking: &Booking) -> anyhow::Result<()> {
+    anyhow::ensure!(booking.from < booking.to, "The stay ends before it starts");
+    Ok(())
+}
+
+fn check_checked_booking(booking: &CheckedBooking) -> anyhow::Result<()> {
+    anyhow::ensure!(booking.from < booking.to, "The stay ends before it starts");
+    Ok(())
+}
+
+fn check_guest(guest: &str) -> anyhow::Result<()> {
+    anyhow::ensure!(!guest.is_empty(), "The guest is empty");
+    Ok(())
+}
+
+#[test]
+fn test_validate() {
+    let proto = booking::Booking {
+        guest: "Ann".into(),
+        from: 1,
+        to: 3,
+    };
+    let booking = proto.clone().from_proto().unwrap();
+    assert_eq!(booking.to_proto(), proto);
+
+    let error = booking::Booking { to: 1, ..proto }
+        .from_proto()
+        .unwrap_err();
+    assert_eq!(
+        format!("{error:#}"),
+        "Invalid Booking: The stay ends before it starts"
+    );
+}
+
+#[test]
+fn test_validate_field() {
+    let proto = booking::Booking {
+        guest: String::new(),
+        from: 1,
+        to: 3,
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "guest: The guest is empty");
+}
+
+#[test]
+fn test_validate_on_to_proto() {
+    let proto = booking::Reservation {
+        guest: "Ann".into(),
+        from: 1,
+        to: 3,
+    };
+    let booking: CheckedBooking = proto.clone().from_proto().unwrap();
+    assert_eq!(booking.try_to_proto().unwrap(), proto);
+
+    let error = CheckedBooking { to: 0, ..booking }
+        .try_to_proto()
+        .unwrap_err();
+    assert_eq!(
+        format!("{error:#}"),
+        "Invalid CheckedBooking: The stay ends before it starts"
+    );
+    let error = CheckedBooking {
+        guest: String::new(),
+        from: 1,
+        to: 3,
+    }
+    .try_to_proto()
+    .unwrap_err();
+    assert_eq!(error.to_string(), "guest: The guest is empty");
+}
+
+mod paging {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Page<T> {
+        pub items: Vec<T>,
+        pub next_token: Option<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Tags<T> {
+        pub values: Vec<T>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "paging::Page<T>")]
+pub struct Page<T> {
+    items: Vec<T>,
+    next_token: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(
+    type = "paging::Tags<T>",
+    bound(from_proto = "T: FromProto, T::Result: Ord")
+)]
+pub struct Tags<T: Ord> {
+    #[proto(set)]
+    values: std::collections::BTreeSet<T>,
+}
+
+#[test]
+fn test_generic() {
+    let proto = paging::Page {
+        items: vec![
+            proto::Address {
+                street: "Main".to_string(),
+                number: 1,
+            },
+            proto::Address {
+                street: "Side".to_string(),
+                number: 2,
+            },
+        ],
+        next_token: Some("next".into()),
+    };
+    let page: Page<Address> = proto.clone().from_proto().unwrap();
+    assert_eq!(page.items[1].street, "Side");
+    assert_eq!(page.to_proto(), proto);
+
+    let proto = paging::Page {
+        items: vec!["a".to_string(), "b".to_string()],
+        next_token: None,
+    };
+    let page: Page<String> = proto.clone().from_proto().unwrap();
+    assert_eq!(page.items, ["a", "b"]);
+    assert_eq!(page.to_proto(), proto);
+}
+
+#[test]
+fn test_generic_error() {
+    let proto = paging::Page {
+        items: vec![
+            catalog_item("a"),
+            catalog::Item {
+                name: "b".to_string(),
+                owner: None,
+            },
+        ],
+        next_token: None,
+    };
+    let error = FromProto::from_proto(proto).unwrap_err();
+    assert_eq!(error.to_string(), "items[1].owner: Required is missing");
+}
+
+#[test]
+fn test_generic_bound() {
+    let proto = paging::Tags {
+        values: vec![3u32, 1, 3],
+    };
+    let tags: Tags<u32> = proto.from_proto().unwrap();
+    assert_eq!(tags.values, std::collections::BTreeSet::from([1, 3]));
+    assert_eq!(tags.to_proto().values, [1, 3]);
+}
+
+mod account {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Links {
+        pub avatar_url: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Profile {
+        pub display_name: String,
+        pub links: Option<Links>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Member {
+        pub id: u64,
+        pub profile: Option<Profile>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Invite {
+        pub id: u64,
+        pub profile: Option<Profile>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "account::Member")]
+pub struct FlatMember {
+    id: u64,
+    #[proto(path = "profile.display_name")]
+    display_name: String,
+    #[proto(path = "profile.links.avatar_url")]
+    avatar_url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(
+    type = "account::Invite",
+    validate = "check_invite",
+    validate_on_to_proto
+)]
+pub struct FlatInvite {
+    id: u64,
+    #[proto(path = "profile.display_name", validate = "check_guest")]
+    display_name: String,
+}
+
+fn check_invite(invite: &FlatInvite) -> anyhow::Result<()> {
+    anyhow::ensure!(invite.id > 0, "The invite has no id");
+    Ok(())
+}
+
+fn account_member() -> account::Member {
+    account::Member {
+        id: 3,
+        profile: Some(account::Profile {
+            display_name: "Ann".to_string(),
+            links: Some(account::Links {
+                avatar_url: "https://avatar".to_string(),
+            }),
+        }),
+    }
+}
+
+#[test]
+fn test_path() {
+    let member = account_member().from_proto().unwrap();
+    assert_eq!(
+        member,
+        FlatMember {
+            id: 3,
+            display_name: "Ann".to_string(),
+            avatar_url: "https://avatar".to_string(),
+        }
+    );
+    assert_eq!(member.to_proto(), account_member());
+}
+
+#[test]
+fn test_path_missing() {
+    let proto = account::Member {
+        profile: None,
+        ..account_member()
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "profile: Required is missing");
+
+    let mut proto = account_member();
+    proto.profile.as_mut().unwrap().links = None;
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "profile.links: Required is missing");
+}
+
+#[test]
+fn test_path_validate() {
+    let invite = FlatInvite {
+        id: 1,
+        display_name: String::new(),
+    };
+    let error = invite.try_to_proto().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "profile.display_name: The guest is empty"
+    );
+
+    let proto = account::Invite {
+        id: 1,
+        profile: Some(account::Profile::default()),
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "profile.display_name: The guest is empty"
+    );
+}
+
+mod registry {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct CreateCustomerRequest {
+        pub name: String,
+        pub email: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Customer {
+        pub id: u64,
+        pub name: String,
+        pub email: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct DeleteCustomerRequest {
+        pub id: u64,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "registry::CreateCustomerRequest", name = "create")]
+#[proto(type = "registry::Customer", name = "entity")]
+pub struct Customer {
+    #[proto(skip, mapping = "create")]
+    id: u64,
+    name: String,
+    #[proto(rename = "email", mapping = "entity")]
+    #[proto(rename = "email", mapping = "create")]
+    mail: String,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(type = "registry::DeleteCustomerRequest")]
+pub struct DeleteCustomer {
+    id: u64,
+}
+
+#[test]
+fn test_named_mappings() {
+    let customer = Customer {
+        id: 4,
+        name: "Ann".to_string(),
+        mail: "ann@example.com".to_string(),
+    };
+    let create: registry::CreateCustomerRequest = customer.to_proto_alias();
+    assert_eq!(
+        create,
+        registry::CreateCustomerRequest {
+            name: "Ann".to_string(),
+            email: "ann@example.com".to_string(),
+        }
+    );
+    let entity: registry::Customer = customer.to_proto_alias();
+    assert_eq!(
+        entity,
+        registry::Customer {
+            id: 4,
+            name: "Ann".to_string(),
+            email: "ann@example.com".to_string(),
+        }
+    );
+
+    let created = create.from_proto().unwrap();
+    assert_eq!(created.id, 0);
+    assert_eq!(created.mail, "ann@example.com");
+    assert_eq!(entity.from_proto().unwrap(), customer);
+}
+
+#[test]
+fn test_from_proto_only() {
+    let proto = registry::DeleteCustomerRequest { id: 9 };
+    assert_eq!(proto.from_proto().unwrap(), DeleteCustomer { id: 9 });
+}
+
+mod survey {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Survey {
+        pub completion: u32,
+        pub scores: Vec<u32>,
+        pub limit: Option<u32>,
+    }
+}
+
+type Percent = proto_conve::Bounded<u8, 0, 100>;
+
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(type = "survey::Survey")]
+pub struct Survey {
+    completion: Percent,
+    scores: Vec<Percent>,
+    limit: Option<Percent>,
+}
+
+#[test]
+fn test_bounded() {
+    let proto = survey::Survey {
+        completion: 100,
+        scores: vec![0, 55],
+        limit: None,
+    };
+    let survey = proto.from_proto().unwrap();
+    assert_eq!(survey.completion.get(), 100);
+    assert_eq!(survey.scores[1].get(), 55);
+    assert_eq!(survey.limit, None);
+
+    let proto = survey::Survey {
+        completion: 10,
+        scores: vec![20, 101],
+        limit: None,
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "scores[1]: The value 101 is out of range 0..=100"
+    );
+}
+
+mod signup {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Signup {
+        pub name: String,
+        pub email: String,
+        pub note: String,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "signup::Signup")]
+pub struct Signup {
+    name: proto_conve::text::NonEmptyString,
+    email: proto_conve::text::Normalized<proto_conve::text::Lowercase>,
+    note: proto_conve::text::TrimmedString,
+}
+
+#[test]
+fn test_strings() {
+    let proto = signup::Signup {
+        name: " Ann ".to_string(),
+        email: "Ann@Example.com ".to_string(),
+        note: " hi".to_string(),
+    };
+    let signup = proto.from_proto().unwrap();
+    assert_eq!(signup.name.as_str(), "Ann");
+    assert_eq!(
+        signup.to_proto(),
+        signup::Signup {
+            name: "Ann".to_string(),
+            email: "ann@example.com".to_string(),
+            note: "hi".to_string(),
+        }
+    );
+
+    let proto = signup::Signup {
+        name: "  ".to_string(),
+        ..Default::default()
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "name: The value is empty");
+}
+
+mod patch {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct UpdateProfile {
+        pub age: Option<u32>,
+        pub name: Option<String>,
+        pub tags: Option<Vec<String>>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "patch::UpdateProfile")]
+pub struct UpdateProfile {
+    #[proto(skip_default)]
+    age: u32,
+    #[proto(skip_default)]
+    name: String,
+    #[proto(skip_default)]
+    tags: Vec<String>,
+}
+
+#[test]
+fn test_skip_default() {
+    let update = UpdateProfile {
+        age: 0,
+        name: "Ann".to_string(),
+        tags: Vec::new(),
+    };
+    let proto = update.to_proto();
+    assert_eq!(
+        proto,
+        patch::UpdateProfile {
+            age: None,
+            name: Some("Ann".to_string()),
+            tags: None,
+        }
+    );
+    assert_eq!(proto.from_proto().unwrap(), update);
+
+    let update = UpdateProfile {
+        age: 30,
+        name: String::new(),
+        tags: vec!["a".to_string()],
+    };
+    let proto = update.to_proto();
+    assert_eq!(proto.age, Some(30));
+    assert_eq!(proto.name, None);
+    assert_eq!(proto.tags, Some(vec!["a".to_string()]));
+    assert_eq!(proto.from_proto().unwrap(), update);
+}
+
+mod directory {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Location {
+        pub city: String,
+        pub zip: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Person {
+        pub name: String,
+        pub age: u32,
+        pub email: String,
+        pub location: Option<Location>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, MergeFromProto)]
+#[proto(type = "directory::Location")]
+pub struct Location {
+    city: String,
+    zip: String,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, MergeFromProto)]
+#[proto(type = "directory::Person")]
+pub struct Person {
+    name: String,
+    age: u32,
+    email: String,
+    #[proto(merge)]
+    location: Location,
+}
+
+fn person() -> Person {
+    Person {
+        name: "Ann".to_string(),
+        age: 30,
+        email: "ann@example.com".to_string(),
+        location: Location {
+            city: "Brno".to_string(),
+            zip: "60200".to_string(),
+        },
+    }
+}
+
+#[test]
+fn test_merge() {
+    use proto_conve::mask::FieldMaskSet;
+
+    let mut person = person();
+    let update = directory::Person {
+        name: "Anna".to_string(),
+        age: 31,
+        email: String::new(),
+        location: None,
+    };
+    person
+        .merge_from_proto(update, &FieldMaskSet::new(["name", "age"]))
+        .unwrap();
+    assert_eq!(
+        person,
+        Person {
+            name: "Anna".to_string(),
+            age: 31,
+            ..self::person()
+        }
+    );
+
+    let update = directory::Person {
+        location: Some(directory::Location {
+            city: "Praha".to_string(),
+            zip: "11000".to_string(),
+        }),
+        ..Default::default()
+    };
+    person
+        .merge_from_proto(update, &FieldMaskSet::new(["location.city"]))
+        .unwrap();
+    assert_eq!(person.location.city, "Praha");
+    assert_eq!(person.location.zip, "60200");
+    assert_eq!(person.name, "Anna");
+}
+
+#[test]
+fn test_merge_unknown_path() {
+    use proto_conve::mask::FieldMaskSet;
+
+    let mut person = person();
+    let mask = FieldMaskSet::new(["name", "nick", "age.value", "location.street"]);
+    let error = person
+        .merge_from_proto(directory::Person::default(), &mask)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Unknown field mask paths: age.value, nick"
+    );
+    assert_eq!(person, self::person());
+
+    let mask = FieldMaskSet::new(["location.street"]);
+    let error = person
+        .merge_from_proto(directory::Person::default(), &mask)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "location: Unknown field mask paths: street"
+    );
+    let error = person
+        .merge_from_proto(
+            directory::Person::default(),
+            &FieldMaskSet::new(["location"]),
+        )
+        .unwrap_err();
+    assert_eq!(error.to_string(), "location: Required is missing");
+}
+
+mod scheduler {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Step {
+        pub delay: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Job {
+        pub timeout: i64,
+        pub priority: i32,
+        pub steps: Vec<Step>,
+    }
+}
+
+mod timeouts {
+    use std::time::Duration;
+
+    pub fn seconds(value: i64) -> anyhow::Result<Duration> {
+        Ok(Duration::from_secs(value.try_into()?))
+    }
+
+    pub fn millis(value: i64) -> anyhow::Result<Duration> {
+        Ok(Duration::from_millis(value.try_into()?))
+    }
+
+    /// The priorities were `1` high and `2` low until the version 2
+    pub fn legacy_priority(value: i32) -> anyhow::Result<super::Priority> {
+        match value {
+            1 => Ok(super::Priority::High),
+            2 => Ok(super::Priority::Low),
+            _ => anyhow::bail!("Unknown priority {value}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, FromProto)]
+#[proto(type = "i32")]
+pub enum Priority {
+    #[proto(value = 1)]
+    Low,
+    #[proto(value = 2)]
+    High,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, FromProtoVersioned)]
+#[proto(type = "scheduler::Step")]
+pub struct Step {
+    #[proto(from_with = "timeouts::seconds")]
+    #[proto(until = 2, from_with = "timeouts::millis")]
+    delay: std::time::Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, FromProtoVersioned)]
+#[proto(type = "scheduler::Job", min_version = 2, max_version = 3)]
+pub struct Job {
+    #[proto(until = 2, from_with = "timeouts::millis")]
+    #[proto(from_with = "timeouts::seconds")]
+    timeout: std::time::Duration,
+    #[proto(until = 2, from_with = "timeouts::legacy_priority")]
+    priority: Priority,
+    steps: Vec<Step>,
+}
+
+#[test]
+fn test_versioned() {
+    use proto_conve::version::SchemaVersion;
+    use std::time::Duration;
+
+    let proto = scheduler::Job {
+        timeout: 5,
+        priority: 1,
+        steps: vec![scheduler::Step { delay: 2 }],
+    };
+    let v2 = proto
+        .clone()
+        .from_proto_versioned(SchemaVersion::new(2))
+        .unwrap();
+    assert_eq!(
+        v2,
+        Job {
+            timeout: Duration::from_millis(5),
+            priority: Priority::High,
+            steps: vec![Step {
+                delay: Duration::from_millis(2)
+            }],
+        }
+    );
+    let v3 = proto
+        .clone()
+        .from_proto_versioned(SchemaVersion::new(3))
+        .unwrap();
+    assert_eq!(
+        v3,
+        Job {
+            timeout: Duration::from_secs(5),
+            priority: Priority::Low,
+            steps: vec![Step {
+                delay: Duration::from_secs(2)
+            }],
+        }
+    );
+    assert_eq!(proto.from_proto().unwrap(), v3);
+}
+
+#[test]
+fn test_unsupported_version() {
+    use proto_conve::version::{SchemaVersion, UnsupportedVersion};
+
+    for version in [1, 4] {
+        let error = scheduler::Job::default()
+            .from_proto_versioned(SchemaVersion::new(version))
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<UnsupportedVersion>(),
+            Some(&UnsupportedVersion {
+                version: SchemaVersion::new(version),
+                type_name: "Job",
+            })
+        );
+    }
+    let proto = scheduler::Job {
+        priority: 3,
+        ..Default::default()
+    };
+    let error = proto
+        .from_proto_versioned(SchemaVersion::new(2))
+        .unwrap_err();
+    assert_eq!(error.to_string(), "priority: Unknown priority 3");
+}
+
+mod migration {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Timestamp {
+        pub seconds: i64,
+        pub nanos: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Owner {
+        pub name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Record {
+        pub owner: Option<Owner>,
+        pub created: Option<Timestamp>,
+        pub status: i32,
+        pub history: Vec<Timestamp>,
+    }
+}
+
+use migration::Timestamp;
+impl_traits!(Timestamp, chrono::DateTime<chrono::Utc>);
+
+#[derive(Debug, Clone, PartialEq, Default, FromProto, FromProtoLossy)]
+#[proto(type = "migration::Owner")]
+pub struct Owner {
+    name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, FromProtoLossy)]
+#[proto(type = "migration::Record")]
+pub struct Record {
+    owner: Owner,
+    created: chrono::DateTime<chrono::Utc>,
+    status: Status,
+    history: Vec<chrono::DateTime<chrono::Utc>>,
+}
+
+#[test]
+fn test_lossy() {
+    use proto_conve::lossy::LossyEvent;
+
+    let proto = migration::Record {
+        owner: None,
+        created: Some(migration::Timestamp {
+            seconds: i64::MAX,
+            nanos: 0,
+        }),
+        ..Default::default()
+    };
+    assert!(proto.clone().from_proto().is_err());
+    let (record, report) = proto.from_proto_lossy().unwrap();
+    assert_eq!(record.owner, Owner::default());
+    assert_eq!(
+        record.created.timestamp(),
+        chrono::DateTime::<chrono::Utc>::MAX_UTC.timestamp()
+    );
+    assert_eq!(report.len(), 2);
+    assert_eq!(
+        report.events("owner").collect::<Vec<_>>(),
+        vec![&LossyEvent::MissingDefaulted]
+    );
+    assert!(report.contains("created"));
+    assert_eq!(
+        report.to_string(),
+        "2 lossy conversions\n  owner: The missing value was defaulted\n  created: The value \
+         9223372036854775807 was clamped to 8210266876799"
+    );
+}
+
+#[test]
+fn test_lossy_enum_and_elements() {
+    use proto_conve::lossy::LossyEvent;
+
+    let timestamp = |seconds| migration::Timestamp { seconds, nanos: 0 };
+    let proto = migration::Record {
+        owner: Some(migration::Owner {
+            name: "Eve".to_owned(),
+        }),
+        created: Some(timestamp(0)),
+        status: 7,
+        history: vec![timestamp(1), timestamp(i64::MIN)],
+    };
+    let (record, report) = proto.from_proto_lossy().unwrap();
+    assert_eq!(record.status, Status::Unspecified);
+    assert_eq!(
+        report.iter().collect::<Vec<_>>(),
+        vec![
+            ("status".to_owned(), &LossyEvent::UnknownEnum { raw: 7 }),
+            (
+                "history[1]".to_owned(),
+                &LossyEvent::Clamped {
+                    from: i64::MIN.to_string(),
+                    to: "-8334601228800".to_owned()
+                }
+            ),
+        ]
+    );
+}
+
+mod labels {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Attribute {
+        pub key: String,
+        pub value: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Asset {
+        pub name: String,
+        pub attributes: Vec<Attribute>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "labels::Attribute", attr_bag)]
+pub struct Labels {
+    color: String,
+    #[proto(rename = "max-size")]
+    size: Option<u32>,
+    #[proto(default)]
+    priority: i32,
+    #[proto(leftover)]
+    other: std::collections::HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "labels::Asset")]
+pub struct Asset {
+    name: String,
+    attributes: Labels,
+}
+
+fn attributes(entries: &[(&str, &str)]) -> Vec<labels::Attribute> {
+    entries
+        .iter()
+        .map(|(key, value)| labels::Attribute {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+        .collect()
+}
+
+#[test]
+fn test_attr_bag() {
+    let proto = labels::Asset {
+        name: "chair".to_string(),
+        attributes: attributes(&[
+            ("zone", "b"),
+            ("max-size", "40"),
+            ("color", "red"),
+            ("owner", "eve"),
+        ]),
+    };
+    let asset = proto.clone().from_proto().unwrap();
+    assert_eq!(
+        asset.attributes,
+        Labels {
+            color: "red".to_string(),
+            size: Some(40),
+            priority: 0,
+            other: [("zone", "b"), ("owner", "eve")]
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }
+    );
+    // the known keys in the order of the fields, the leftover ones sorted
+    assert_eq!(
+        asset.to_proto().attributes,
+        attributes(&[
+            ("color", "red"),
+            ("max-size", "40"),
+            ("priority", "0"),
+            ("owner", "eve"),
+            ("zone", "b"),
+        ])
+    );
+    assert_eq!(asset.to_proto().from_proto().unwrap(), asset);
+}
+
+#[test]
+fn test_attr_bag_errors() {
+    let error = attributes(&[("color", "red"), ("color", "blue")])
+        .from_proto()
+        .unwrap_err();
+    assert_eq!(error.to_string(), "The key color is duplicated");
+    let error = attributes(&[("color", "red"), ("zone", "a"), ("zone", "b")])
+        .from_proto()
+        .unwrap_err();
+    assert_eq!(error.to_string(), "The key zone is duplicated");
+
+    let proto = labels::Asset {
+        name: "chair".to_string(),
+        attributes: attributes(&[("max-size", "40")]),
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "attributes.color: Required is missing");
+
+    let error = attributes(&[("color", "red"), ("max-size", "big")])
+        .from_proto()
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "max-size: Invalid value \"big\": invalid digit found in string"
+    );
+}
+
+mod files {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Attachment {
+        pub name: String,
+        pub content: Vec<u8>,
+        pub thumbnail: Vec<u8>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto, FromProtoLossy)]
+#[proto(type = "files::Attachment")]
+pub struct Attachment {
+    name: String,
+    content: Vec<u8>,
+    thumbnail: proto_conve::ProtoBytes,
+}
+
+#[test]
+fn test_bytes() {
+    let proto = files::Attachment {
+        name: "photo".to_string(),
+        content: vec![1; 1 << 20],
+        thumbnail: vec![2; 64],
+    };
+    let expected = proto.clone();
+    let (content, thumbnail) = (proto.content.as_ptr(), proto.thumbnail.as_ptr());
+    let attachment = proto.from_proto().unwrap();
+    // the buffers are moved, not collected per byte
+    assert_eq!(attachment.content.as_ptr(), content);
+    assert_eq!(attachment.thumbnail.as_ptr(), thumbnail);
+    assert_eq!(attachment.to_proto(), expected);
+
+    let (attachment, report) = expected.from_proto_lossy().unwrap();
+    assert!(report.is_empty());
+    assert_eq!(attachment.content.len(), 1 << 20);
+}
+
+mod tasks {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Task {
+        pub name: String,
+        pub deadline: Option<super::Timestamp>,
+        pub expires_ms: i64,
+    }
+}
+
+impl_traits!(sentinel_none deadline: Timestamp, seconds = [0]);
+impl_traits!(sentinel_none expires: i64, millis = [0, i64::MAX]);
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "tasks::Task")]
+pub struct Task {
+    name: String,
+    #[proto(with = "deadline")]
+    deadline: Option<chrono::DateTime<chrono::Utc>>,
+    #[proto(with = "expires")]
+    expires_ms: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[test]
+fn test_sentinel_none() {
+    let proto = tasks::Task {
+        name: "backup".to_string(),
+        deadline: Some(Timestamp {
+            seconds: 0,
+            nanos: 0,
+        }),
+        expires_ms: i64::MAX,
+    };
+    let task = proto.clone().from_proto().unwrap();
+    assert_eq!(task.deadline, None);
+    assert_eq!(task.expires_ms, None);
+    // the first sentinel is written for `None`
+    assert_eq!(
+        task.to_proto(),
+        tasks::Task {
+            expires_ms: 0,
+            ..proto
+        }
+    );
+}
+
+mod metrics {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Sample {
+        pub host: String,
+        pub region: Option<String>,
+        pub labels: Vec<String>,
+        pub value: f64,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "metrics::Sample")]
+pub struct Sample {
+    #[proto(intern)]
+    host: std::sync::Arc<str>,
+    #[proto(intern)]
+    region: Option<std::sync::Arc<str>>,
+    #[proto(intern)]
+    labels: Vec<std::sync::Arc<str>>,
+    value: f64,
+}
+
+#[test]
+fn test_intern() {
+    let proto = metrics::Sample {
+        host: "web-1".to_string(),
+        region: Some("eu".to_string()),
+        labels: vec!["prod".to_string(), "web-1".to_string()],
+        value: 0.5,
+    };
+    let samples = vec![proto.clone(); 10_000].from_proto().unwrap();
+    let first = &samples[0];
+    assert!(samples.iter().all(|sample| {
+        std::sync::Arc::ptr_eq(&sample.host, &first.host)
+            && std::sync::Arc::ptr_eq(&sample.labels[0], &first.labels[0])
+    }));
+    assert!(std::sync::Arc::ptr_eq(&first.host, &first.labels[1]));
+    assert_eq!(proto_conve::intern::with(|interner| interner.len()), 3);
+    assert_eq!(first.to_proto(), proto);
+    proto_conve::intern::clear();
+}
+
+mod readings {
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+    pub struct Sensor {
+        pub name: String,
+        pub unit: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Reading {
+        pub sensor: Option<Sensor>,
+        pub calibration: Option<Sensor>,
+        pub neighbours: Vec<Sensor>,
+        pub value: f64,
+    }
+}
+
+#[derive(Debug, PartialEq, FromProto, ToProto)]
+#[proto(type = "readings::Sensor")]
+pub struct Sensor {
+    name: String,
+    unit: String,
+}
+
+#[derive(Debug, PartialEq, FromProto, ToProto)]
+#[proto(type = "readings::Reading")]
+pub struct Reading {
+    #[proto(cached)]
+    sensor: std::sync::Arc<Sensor>,
+    #[proto(cached)]
+    calibration: Option<std::sync::Arc<Sensor>>,
+    #[proto(cached)]
+    neighbours: Vec<std::sync::Arc<Sensor>>,
+    value: f64,
+}
+
+#[test]
+fn test_cached() {
+    let sensor = |name: &str| readings::Sensor {
+        name: name.to_string(),
+        unit: "kPa".to_string(),
+    };
+    let proto = readings::Reading {
+        sensor: Some(sensor("p1")),
+        calibration: None,
+        neighbours: vec![sensor("p2"), sensor("p1")],
+        value: 101.3,
+    };
+    let readings = vec![proto.clone(); 100].from_proto().unwrap();
+    let first = &readings[0];
+    assert!(readings
+        .iter()
+        .all(|reading| std::sync::Arc::ptr_eq(&reading.sensor, &first.sensor)));
+    assert!(std::sync::Arc::ptr_eq(&first.sensor, &first.neighbours[1]));
+    assert!(!std::sync::Arc::ptr_eq(&first.sensor, &first.neighbours[0]));
+    assert_eq!(first.to_proto(), proto);
+
+    let error = readings::Reading {
+        sensor: None,
+        ..proto
+    }
+    .from_proto()
+    .unwrap_err();
+    assert_eq!(error.to_string(), "sensor: Required is missing");
+    proto_conve::cache::clear();
+}
+
+mod shelves {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Books {
+        pub items: Vec<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Shelf {
+        pub books: Option<Books>,
+        pub archived: Option<Books>,
+    }
+}
+
+use shelves::Books;
+impl_traits!(Books, wrapper Vec<String>);
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "shelves::Shelf")]
+pub struct Shelf {
+    books: Vec<String>,
+    #[proto(default)]
+    archived: Vec<String>,
+}
+
+#[test]
+fn test_wrapper() {
+    let proto = shelves::Shelf {
+        books: Some(Books {
+            items: vec!["dune".to_string()],
+        }),
+        archived: None,
+    };
+    let shelf = proto.clone().from_proto().unwrap();
+    assert_eq!(shelf.books, ["dune"]);
+    assert!(shelf.archived.is_empty());
+    assert_eq!(shelf.to_proto().archived, Some(Books { items: Vec::new() }));
+
+    let error = shelves::Shelf::default().from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "books: Required is missing");
+}
+
+mod customers {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Customer {
+        pub name: String,
+        pub email: String,
+        pub phones: Vec<String>,
+    }
+}
+
+/// Encodes the plain values reversed
+struct Reverse;
+
+impl proto_conve::transform::ProtoTransform for Reverse {
+    fn encode(&self, plaintext: &str) -> anyhow::Result<String> {
+        Ok(plaintext.chars().rev().collect())
+    }
+
+    fn decode(&self, encoded: &str) -> anyhow::Result<String> {
+        anyhow::ensure!(!encoded.is_empty(), "The encoded value is empty");
+        Ok(encoded.chars().rev().collect())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "customers::Customer")]
+pub struct Contact {
+    name: String,
+    #[proto(transform)]
+    email: String,
+    #[proto(transform)]
+    phones: Vec<String>,
+}
+
+#[test]
+fn test_transform() {
+    use proto_conve::transform;
+    let proto = customers::Customer {
+        name: "eve".to_string(),
+        email: "gro.elpmaxe@eve".to_string(),
+        phones: vec!["1".to_string(), "32".to_string()],
+    };
+    let reverse = std::sync::Arc::new(Reverse);
+    let contact = transform::scope(reverse.clone(), || proto.clone().from_proto()).unwrap();
+    assert_eq!(contact.email, "eve@example.org");
+    assert_eq!(contact.phones, ["1", "23"]);
+    let back = transform::scope(reverse.clone(), || contact.try_to_proto()).unwrap();
+    assert_eq!(back, proto);
+
+    let error = proto.clone().from_proto().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "email: The field is transformed, but it is not converted in a scope with the \
+         ProtoTransform"
+    );
+    let error = contact.try_to_proto().unwrap_err();
+    assert!(error
+        .to_string()
+        .starts_with("email: The field is transformed"));
+
+    let mut proto = proto;
+    proto.phones.push(String::new());
+    let error = transform::scope(reverse, || proto.from_proto()).unwrap_err();
+    assert_eq!(error.to_string(), "phones[2]: The encoded value is empty");
+}
+
+mod listing {
+    use super::proto::User;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ListUsersResponse {
+        pub items: Vec<User>,
+        pub next_page_token: String,
+        pub total_size: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Directory {
+        pub users: Option<ListUsersResponse>,
+    }
+}
+
+proto_conve::impl_proto_page!(ListUsersResponse, items: Vec<User>);
+use listing::ListUsersResponse;
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "listing::Directory")]
+pub struct Directory {
+    users: proto_conve::Page<User>,
+}
+
+#[test]
+fn test_page() {
+    let proto = listing::Directory {
+        users: Some(ListUsersResponse {
+            items: vec![proto_user()],
+            next_page_token: String::new(),
+            total_size: 1,
+        }),
+    };
+    let directory = proto.clone().from_proto().unwrap();
+    assert_eq!(directory.users.items, [proto_user().from_proto().unwrap()]);
+    assert!(directory.users.is_last());
+    assert_eq!(directory.to_proto(), proto);
+}
+
+/// The variants in the blocks of ten starting at the hundreds, the values are the discriminants
+#[derive(Debug, Clone, Copy, PartialEq, FromProto, ToProto)]
+pub enum Country {
+    Afghanistan = 0,
+    Albania,
+    Algeria,
+    Andorra,
+    Angola,
+    Argentina,
+    Armenia,
+    Australia,
+    Austria,
+    Azerbaijan,
+    Bahamas = 100,
+    Bahrain,
+    Bangladesh,
+    Barbados,
+    Belarus,
+    Belgium,
+    Belize,
+    Benin,
+    Bhutan,
+    Bolivia,
+    Botswana = 200,
+    Brazil,
+    Brunei,
+    Bulgaria,
+    Burundi,
+    Cambodia,
+    Cameroon,
+    Canada,
+    Chad,
+    Chile,
+    China = 300,
+    Colombia,
+    Comoros,
+    Croatia,
+    Cuba,
+    Cyprus,
+    Czechia,
+    Denmark,
+    Djibouti,
+    Dominica,
+}
+
+const CANADA: i32 = Country::Canada.to_proto_i32();
+const BENIN: Option<Country> = Country::from_proto_i32(107);
+
+#[test]
+fn test_enum_const() {
+    assert_eq!(CANADA, 207);
+    assert_eq!(BENIN, Some(Country::Benin));
+    assert_eq!(Country::from_proto_i32(40), None);
+    assert_eq!(Level::from_proto_i32(-1), Some(Level::Other));
+    assert_eq!(Status::Blocked.to_proto_i32(), 2);
+
+    let mut count = 0;
+    for value in -1..400 {
+        let Some(country) = Country::from_proto_i32(value) else {
+            assert!(Country::from_proto_alias(value).is_err());
+            continue;
+        };
+        assert_eq!(Country::from_proto_alias(value).unwrap(), country);
+        assert_eq!(ToProtoAlias::<i32>::to_proto_alias(&country), value);
+        assert_eq!(country.to_proto_i32(), value);
+        count += 1;
+    }
+    assert_eq!(count, 40);
+}
+
+mod workers {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Duration {
+        pub seconds: i64,
+        pub nanos: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Job {
+        pub timeout: Option<Duration>,
+    }
+}
+
+impl_traits!(Duration, chrono::Duration);
+use workers::Duration;
+
+mod limits {
+    use super::{FromProtoBounded, Required};
+
+    pub fn at_most_day(
+        proto: Option<super::workers::Duration>,
+    ) -> anyhow::Result<chrono::Duration> {
+        proto
+            .required()?
+            .from_proto_bounded(chrono::Duration::days(1))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(type = "workers::Job")]
+pub struct WorkerJob {
+    #[proto(from_with = "limits::at_most_day")]
+    timeout: chrono::Duration,
+}
+
+#[test]
+fn test_from_proto_bounded() {
+    let job = |seconds| workers::Job {
+        timeout: Some(Duration { seconds, nanos: 0 }),
+    };
+    let within = job(60).from_proto().unwrap();
+    assert_eq!(within.timeout, chrono::Duration::minutes(1));
+    // the timeout of 10 years is attributed to its field
+    let error = job(315_360_000).from_proto().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "timeout: The duration 315360000s is over the maximum 86400s"
+    );
+}
+
+mod staging {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Member {
+        pub id: u64,
+        pub handle: String,
+        pub nickname: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Team {
+        pub owner_id: u64,
+        pub members: Vec<Member>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, FromProtoLossy)]
+#[proto(type = "staging::Member")]
+pub struct StagingMember {
+    #[proto(required_nonzero)]
+    id: u64,
+    #[proto(required_nonzero)]
+    handle: String,
+    nickname: String,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, FromProtoLossy)]
+#[proto(type = "staging::Team")]
+pub struct StagingTeam {
+    #[proto(required_nonzero)]
+    owner_id: u64,
+    members: Vec<StagingMember>,
+}
+
+#[test]
+fn test_suspicious_default() {
+    use proto_conve::lossy::LossyEvent;
+
+    let proto_member = |id, handle: &str| staging::Member {
+        id,
+        handle: handle.to_owned(),
+        nickname: String::new(),
+    };
+    let (member, report) = proto_member(7, "eve").from_proto_lossy().unwrap();
+    assert_eq!(member.id, 7);
+    assert!(report.is_empty());
+
+    let (member, report) = staging::Member::default().from_proto_lossy().unwrap();
+    assert_eq!(member.id, 0);
+    assert_eq!(
+        report.iter().collect::<Vec<_>>(),
+        vec![
+            ("id".to_owned(), &LossyEvent::SuspiciousDefault),
+            ("handle".to_owned(), &LossyEvent::SuspiciousDefault),
+        ]
+    );
+
+    let proto = staging::Team {
+        owner_id: 1,
+        members: vec![
+            proto_member(1, "eve"),
+            proto_member(0, "bob"),
+            proto_member(3, ""),
+        ],
+    };
+    // the strict conversion does not check them
+    assert!(proto.clone().from_proto().is_ok());
+    let (team, report) = proto.from_proto_lossy().unwrap();
+    assert_eq!(team.members.len(), 3);
+    assert_eq!(
+        report.to_string(),
+        "2 lossy conversions\n  members[1].id: The value is the proto default, the schema may have \
+         drifted\n  members[2].handle: The value is the proto default, the schema may have drifted"
+    );
+}
+
+mod tagging {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Tag {
+        pub key: String,
+        pub value: String,
+        pub description: String,
+        pub alias: String,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "tagging::Tag")]
+pub struct Tag {
+    key: std::sync::Arc<str>,
+    value: Box<str>,
+    description: std::borrow::Cow<'static, str>,
+    alias: std::rc::Rc<str>,
+}
+
+#[test]
+fn test_str_fields() {
+    let proto = tagging::Tag {
+        key: "team".to_string(),
+        value: "core".to_string(),
+        description: "The owners".to_string(),
+        alias: "group".to_string(),
+    };
+    let tag = proto.clone().from_proto().unwrap();
+    assert_eq!(&*tag.key, "team");
+    assert_eq!(&*tag.value, "core");
+    assert_eq!(tag.description, "The owners");
+    assert_eq!(&*tag.alias, "group");
+    assert_eq!(tag.to_proto(), proto);
+}
+
+mod services {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Service {
+        pub name: String,
+        pub settings: std::collections::HashMap<String, String>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceConfig {
+    retries: u32,
+    verbose: bool,
+}
+
+proto_conve::impl_proto_settings!(ServiceConfig {
+    (retries: u32, default = 3),
+    (verbose: bool, key = "log.verbose"),
+});
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "services::Service")]
+pub struct Service {
+    name: String,
+    settings: ServiceConfig,
+}
+
+#[test]
+fn test_settings_field() {
+    let proto = services::Service {
+        name: "api".to_string(),
+        settings: [("log.verbose".to_string(), "true".to_string())].into(),
+    };
+    let service = proto.clone().from_proto().unwrap();
+    assert_eq!(
+        service.settings,
+        ServiceConfig {
+            retries: 3,
+            verbose: true
+        }
+    );
+    assert_eq!(service.to_proto().settings["retries"], "3");
+
+    let proto = services::Service {
+        settings: [("retries".to_string(), "many".to_string())].into(),
+        ..proto
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "settings[\"retries\"]: Invalid value \"many\": invalid digit found in string"
+    );
+}
+
+mod clocks {
+    /// The duration of another API, split in to days
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Timedelta {
+        pub days: i32,
+        pub seconds: i32,
+        pub nanos: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Schedule {
+        pub interval: Option<super::Duration>,
+        pub grace: Option<Timedelta>,
+        pub pause: Option<super::Duration>,
+    }
+}
+
+impl_traits!(
+    Timedelta,
+    chrono::Duration {
+        days,
+        seconds,
+        nanos
+    }
+);
+use clocks::Timedelta;
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "clocks::Schedule")]
+pub struct Schedule {
+    interval: chrono::Duration,
+    grace: chrono::Duration,
+    pause: Option<chrono::Duration>,
+}
+
+#[test]
+fn test_duration_proto_types() {
+    let schedule = Schedule {
+        interval: chrono::Duration::hours(30),
+        grace: chrono::Duration::hours(30),
+        pause: None,
+    };
+    let proto = schedule.to_proto();
+    assert_eq!(
+        proto.interval,
+        Some(Duration {
+            seconds: 108_000,
+            nanos: 0
+        })
+    );
+    assert_eq!(
+        proto.grace,
+        Some(Timedelta {
+            days: 1,
+            seconds: 21_600,
+            nanos: 0
+        })
+    );
+    assert_eq!(proto.pause, None);
+    assert_eq!(proto.from_proto().unwrap(), schedule);
+}
+
+mod edits {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct NullableString {
+        pub null: bool,
+        pub value: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct UpdateContact {
+        pub email: Option<String>,
+        pub phone: Option<NullableString>,
+    }
+}
+
+proto_conve::impl_proto_patch!(NullableString, null, value: String);
+use edits::NullableString;
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "edits::UpdateContact")]
+pub struct UpdateContact {
+    email: proto_conve::Patch<String>,
+    phone: proto_conve::Patch<String>,
+}
+
+#[test]
+fn test_patch_fields() {
+    use proto_conve::Patch;
+
+    let proto = edits::UpdateContact {
+        email: Some("eve@example.com".to_string()),
+        phone: Some(NullableString {
+            null: true,
+            value: String::new(),
+        }),
+    };
+    let update = proto.clone().from_proto().unwrap();
+    assert_eq!(
+        update,
+        UpdateContact {
+            email: Patch::Set("eve@example.com".to_string()),
+            phone: Patch::Clear,
+        }
+    );
+    assert_eq!(update.to_proto(), proto);
+
+    let update = edits::UpdateContact::default().from_proto().unwrap();
+    assert!(update.email.is_unchanged() && update.phone.is_unchanged());
+
+    let mut phone = Some("123".to_string());
+    let mut email = Some("bob@example.com".to_string());
+    let update = proto.from_proto().unwrap();
+    update.phone.apply(&mut phone);
+    update.email.apply(&mut email);
+    assert_eq!(phone, None);
+    assert_eq!(email.as_deref(), Some("eve@example.com"));
+}
+
+mod stays {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Date {
+        pub year: i32,
+        pub month: i32,
+        pub day: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct DateRange {
+        pub start: Option<Date>,
+        pub end: Option<Date>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Stay {
+        pub guest: String,
+        pub dates: Option<DateRange>,
+        pub checkout: Option<Date>,
+    }
+}
+
+impl_traits!(Date, chrono::NaiveDate);
+impl_traits!(DateRange, std::ops::RangeInclusive<chrono::NaiveDate> { start, end });
+use stays::{Date, DateRange};
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "stays::Stay")]
+pub struct Stay {
+    guest: String,
+    dates: std::ops::RangeInclusive<chrono::NaiveDate>,
+    checkout: Option<chrono::NaiveDate>,
+}
+
+#[test]
+fn test_date_range_field() {
+    let date = |day| Date {
+        year: 2024,
+        month: 5,
+        day,
+    };
+    let proto = stays::Stay {
+        guest: "Ann".into(),
+        dates: Some(DateRange {
+            start: Some(date(1)),
+            end: Some(date(3)),
+        }),
+        checkout: None,
+    };
+    let stay = proto.clone().from_proto().unwrap();
+    let day = |day| chrono::NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+    assert_eq!(stay.dates, day(1)..=day(3));
+    assert_eq!(stay.checkout, None);
+    assert_eq!(stay.to_proto(), proto);
+
+    let proto = stays::Stay {
+        dates: Some(DateRange {
+            start: Some(date(3)),
+            end: Some(date(32)),
+        }),
+        ..proto
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "dates.end: The date 2024-05-32 is invalid"
+    );
+}
+
+mod registration {
+    use super::Timestamp;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Profile {
+        pub display_name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct CreateUserRequest {
+        pub name: String,
+        pub created_at: Option<Timestamp>,
+        pub nickname: Option<String>,
+        pub profile: Option<Profile>,
+        pub retries: Option<u32>,
+    }
+}
+
+#[derive(ToProtoBuilder)]
+#[proto(type = "registration::CreateUserRequest")]
+pub struct CreateUser {
+    name: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+    nickname: Option<String>,
+    #[proto(path = "profile.display_name")]
+    display_name: String,
+    #[proto(default)]
+    retries: u32,
+}
+
+#[test]
+fn test_builder() {
+    let now = chrono::DateTime::from_timestamp(1_700_000_000, 5).unwrap();
+    let proto = CreateUserProtoBuilder::new()
+        .name(&"eve".to_string())
+        .created_at(&now)
+        .nickname(&"e".to_string())
+        .display_name(&"Eve".to_string())
+        .retries(&3)
+        .build()
+        .unwrap();
+    assert_eq!(
+        proto,
+        registration::CreateUserRequest {
+            name: "eve".to_string(),
+            created_at: Some(Timestamp {
+                seconds: 1_700_000_000,
+                nanos: 5
+            }),
+            nickname: Some("e".to_string()),
+            profile: Some(registration::Profile {
+                display_name: "Eve".to_string()
+            }),
+            retries: Some(3),
+        }
+    );
+
+    // the optional fields are left missing
+    let proto = CreateUserProtoBuilder::new()
+        .name(&"eve".to_string())
+        .created_at(&now)
+        .maybe_nickname(None)
+        .display_name(&"Eve".to_string())
+        .build()
+        .unwrap();
+    assert_eq!(proto.nickname, None);
+    assert_eq!(proto.retries, None);
+
+    let error = CreateUserProtoBuilder::default()
+        .created_at(&now)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "The required fields name, profile.display_name are not set"
+    );
+}
+
+mod alerts {
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Rule {
+        pub levels: Vec<i32>,
+        pub fallback: Option<i32>,
+        pub by_region: HashMap<String, i32>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "alerts::Rule")]
+pub struct Rule {
+    levels: Vec<Level>,
+    fallback: Option<Level>,
+    by_region: std::collections::HashMap<String, Level>,
+}
+
+#[test]
+fn test_enum_containers() {
+    let levels = vec![Level::Low, Level::High, Level::Other];
+    assert_eq!(
+        ToProtoAlias::<Vec<i32>>::to_proto_alias(&levels),
+        [1, 5, -1]
+    );
+    assert_eq!(levels[..2].to_proto_alias(), [1, 5]);
+    assert_eq!(Some(Level::High).to_proto_alias(), Some(5));
+    assert_eq!(
+        Option::<Level>::from_proto_alias(Some(Level::High).to_proto_alias()).unwrap(),
+        Some(Level::High)
+    );
+    assert_eq!(
+        ToProtoAlias::<Option<i32>>::to_proto_alias(&None::<Level>),
+        None
+    );
+
+    let proto = alerts::Rule {
+        levels: vec![5, 1],
+        fallback: Some(1),
+        by_region: [("eu".to_string(), 5), ("us".to_string(), -1)].into(),
+    };
+    let rule = proto.clone().from_proto().unwrap();
+    assert_eq!(rule.levels, [Level::High, Level::Low]);
+    assert_eq!(rule.fallback, Some(Level::Low));
+    assert_eq!(rule.by_region["us"], Level::Other);
+    assert_eq!(rule.to_proto(), proto);
+
+    let error = std::collections::HashMap::<String, Status>::from_proto_alias(
+        [("eu".to_string(), 9)].into(),
+    )
+    .unwrap_err();
+    assert!(error.to_string().starts_with("[\"eu\"]: "), "{error}");
+}
+
+#[test]
+fn test_injected_faults() {
+    use proto_conve::error::PathSegment;
+    use proto_conve::fault::{inject, Fault};
+
+    let proto = proto::User {
+        address: Some(proto::Address::default()),
+        previous: (0..10)
+            .map(|number| proto::Address {
+                street: "Main".to_string(),
+                number,
+            })
+            .collect(),
+        ..Default::default()
+    };
+    let error = inject(Fault::at("previous[7]"), || proto.clone().from_proto()).unwrap_err();
+    assert_eq!(error.to_string(), "previous[7]: Injected failure");
+
+    let fault = Fault::matching(|path| path.last() == Some(&PathSegment::Field("number")))
+        .nth(2)
+        .with_message("The number is unknown");
+    let error = inject(fault, || proto.clone().from_proto()).unwrap_err();
+    // the number of the address is the first one
+    assert_eq!(
+        error.to_string(),
+        "previous[1].number: The number is unknown"
+    );
+    assert!(proto.from_proto().is_ok());
+}
+
+mod legacy {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Account {
+        pub email: String,
+        pub legacy_email: String,
+        pub legacy_quota: Option<u32>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Org {
+        pub owner: Option<Account>,
+        pub accounts: Vec<Account>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, FromProtoLossy)]
+#[proto(type = "legacy::Account")]
+pub struct LegacyAccount {
+    email: String,
+    #[proto(deprecated = "use email instead")]
+    legacy_email: String,
+    #[proto(deprecated = "the quota is per org")]
+    legacy_quota: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(type = "legacy::Org")]
+pub struct LegacyOrg {
+    owner: LegacyAccount,
+    accounts: Vec<LegacyAccount>,
+}
+
+#[test]
+fn test_deprecated_fields() {
+    use proto_conve::deprecation::collect;
+
+    let account = |legacy_email: &str| legacy::Account {
+        email: "eve@example.com".to_string(),
+        legacy_email: legacy_email.to_string(),
+        legacy_quota: None,
+    };
+    let (converted, notices) = collect(|| account("eve@old.example.com").from_proto());
+    assert_eq!(converted.unwrap().legacy_email, "eve@old.example.com");
+    assert_eq!(notices.len(), 1);
+    assert_eq!(
+        notices[0].to_string(),
+        "legacy_email: The deprecated field, use email instead"
+    );
+    let (_, notices) = collect(|| account("").from_proto());
+    assert!(notices.is_empty());
+
+    let proto = legacy::Org {
+        owner: Some(legacy::Account {
+            legacy_quota: Some(5),
+            ..account("")
+        }),
+        accounts: (0..5).map(|_| account("bob@old.example.com")).collect(),
+    };
+    let (org, notices) = collect(|| proto.clone().from_proto());
+    assert_eq!(org.unwrap().accounts.len(), 5);
+    let paths: Vec<_> = notices.iter().map(|notice| notice.path()).collect();
+    assert_eq!(
+        paths,
+        [
+            "owner.legacy_quota",
+            "accounts[0].legacy_email",
+            "accounts[1].legacy_email",
+            "accounts[2].legacy_email",
+            "accounts[3].legacy_email",
+            "accounts[4].legacy_email",
+        ]
+    );
+
+    // the lossy conversions notice them too
+    let (_, notices) = collect(|| account("eve@old.example.com").from_proto_lossy());
+    assert_eq!(notices[0].path(), "legacy_email");
+}
+
+mod hashed {
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, PartialEq, proto_conve::ProtoHashable)]
+    pub enum Target {
+        Email(String),
+        Phone(String),
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default, proto_conve::ProtoHashable)]
+    pub struct Notify {
+        pub request_id: String,
+        pub target: Option<Target>,
+        pub fields: HashMap<String, String>,
+        pub retries: Vec<u32>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "hashed::Notify")]
+pub struct Notify {
+    request_id: String,
+    target: Option<NotifyTarget>,
+    fields: std::collections::HashMap<String, String>,
+    retries: Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(oneof = "hashed::Target")]
+pub enum NotifyTarget {
+    Email(String),
+    Phone(String),
+}
+
+#[test]
+fn test_proto_hash() {
+    use proto_conve::hash::{hash_u64, ProtoHashable, StableHasher};
+    use std::hash::Hasher;
+
+    let fields = [("name", "eve"), ("lang", "en"), ("zone", "utc")];
+    let notify = |fields: &[(&str, &str)]| Notify {
+        request_id: "r-1".to_string(),
+        target: Some(NotifyTarget::Email("eve@example.com".to_string())),
+        fields: fields
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+        retries: vec![1, 2],
+    };
+    let forward = notify(&fields);
+    let mut reversed = fields;
+    reversed.reverse();
+    assert_eq!(proto_hash_u64(&forward), proto_hash_u64(&notify(&reversed)));
+
+    // the fields in the declaration order, the variant by its index
+    let proto = forward.to_proto();
+    let mut hasher = StableHasher::new();
+    "r-1".hash_proto(&mut hasher);
+    hasher.write(&[1]);
+    0u32.hash_proto(&mut hasher);
+    "eve@example.com".hash_proto(&mut hasher);
+    proto.fields.hash_proto(&mut hasher);
+    proto.retries.hash_proto(&mut hasher);
+    assert_eq!(hasher.finish(), proto_hash_u64(&forward));
+    assert_eq!(hash_u64(&proto), proto_hash_u64(&forward));
+
+    let phone = Notify {
+        target: Some(NotifyTarget::Phone("eve@example.com".to_string())),
+        ..forward.clone()
+    };
+    assert_ne!(proto_hash_u64(&phone), proto_hash_u64(&forward));
+    let absent = Notify {
+        target: None,
+        ..forward
+    };
+    assert_ne!(proto_hash_u64(&absent), proto_hash_u64(&phone));
+}
+
+mod scalars {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Quota {
+        pub limit: Option<i32>,
+        pub owner_id: i32,
+        pub burst: i32,
+        pub priority: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Limits {
+        pub max: Option<i32>,
+        pub step: i32,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(type = "scalars::Quota")]
+pub struct Quota {
+    // the proto3 `optional` field
+    limit: Option<std::num::NonZeroU32>,
+    #[proto(presence = "implicit")]
+    owner_id: std::num::NonZeroU32,
+    #[proto(presence = "implicit")]
+    burst: Option<std::num::NonZeroI32>,
+    #[proto(presence = "implicit", default = "default_priority")]
+    priority: u32,
+}
+
+fn default_priority() -> u32 {
+    5
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "scalars::Limits")]
+pub struct Limits {
+    #[proto(presence = "explicit")]
+    max: Option<i32>,
+    #[proto(presence = "implicit")]
+    step: Option<i32>,
+}
+
+#[test]
+fn test_field_presence() {
+    let proto = scalars::Quota {
+        limit: None,
+        owner_id: 3,
+        burst: 0,
+        priority: 0,
+    };
+    let quota: Quota = proto.clone().from_proto().unwrap();
+    // the explicit `None` is preserved, the implicit zero is missing
+    assert_eq!(quota.limit, None);
+    assert_eq!(quota.owner_id.get(), 3);
+    assert_eq!(quota.burst, None);
+    assert_eq!(quota.priority, 5);
+
+    let proto = scalars::Quota {
+        limit: Some(10),
+        burst: -2,
+        priority: 1,
+        ..proto
+    };
+    let quota: Quota = proto.clone().from_proto().unwrap();
+    assert_eq!(quota.limit, std::num::NonZeroU32::new(10));
+    assert_eq!(quota.burst, std::num::NonZeroI32::new(-2));
+    assert_eq!(quota.priority, 1);
+
+    // the explicit zero is a value, which does not fit
+    let zero = scalars::Quota {
+        limit: Some(0),
+        ..proto.clone()
+    };
+    let error = FromProto::from_proto(zero).map(|_: Quota| ()).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "limit: The value 0 does not fit in to NonZeroU32"
+    );
+    // the implicit zero of the required field is missing
+    let missing = scalars::Quota {
+        owner_id: 0,
+        ..proto
+    };
+    let error = FromProto::from_proto(missing)
+        .map(|_: Quota| ())
+        .unwrap_err();
+    assert_eq!(error.to_string(), "owner_id: Required is missing");
+
+    let explicit_zero = scalars::Limits {
+        max: Some(0),
+        step: 0,
+    };
+    let limits = explicit_zero.clone().from_proto().unwrap();
+    assert_eq!(limits.max, Some(0));
+    assert_eq!(limits.step, None);
+    assert_eq!(limits.to_proto(), explicit_zero);
+    let limits = scalars::Limits::default().from_proto().unwrap();
+    assert_eq!(limits.max, None);
+    let limits = Limits {
+        max: None,
+        step: Some(4),
+    };
+    assert_eq!(limits.to_proto(), scalars::Limits { max: None, step: 4 });
+}
+
+mod logged {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Contact {
+        pub email: String,
+        pub label: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Customer {
+        pub name: String,
+        pub primary: Option<Contact>,
+        pub others: Vec<Contact>,
+        pub notes: String,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(type = "logged::Contact")]
+pub struct LoggedContact {
+    #[proto(sensitive)]
+    email: String,
+    #[proto(validate = "check_label")]
+    label: String,
+}
+
+fn check_label(label: &str) -> anyhow::Result<()> {
+    anyhow::ensure!(!label.is_empty(), "The label is empty");
+    Ok(())
+}
+
+// the contacts are redacted in the rendering of the customer too
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(type = "logged::Customer", redact)]
+pub struct LoggedCustomer {
+    name: String,
+    primary: LoggedContact,
+    others: Vec<LoggedContact>,
+}
+
+#[test]
+fn test_render_for_log() {
+    use proto_conve::redact::{attach_to_errors, ProtoRedact};
+
+    let contact = |email: &str, label: &str| logged::Contact {
+        email: email.to_owned(),
+        label: label.to_owned(),
+    };
+    assert_eq!(
+        contact("alice@example.com", "home").render_proto_for_log(),
+        r#"Contact { email: [redacted], label: "home", .. }"#
+    );
+    let proto = logged::Customer {
+        name: "Alice".to_owned(),
+        primary: Some(contact("alice@example.com", "home")),
+        others: vec![
+            contact("a@example.com", "work"),
+            contact("b@example.com", ""),
+        ],
+        notes: "not converted".to_owned(),
+    };
+    let rendered = proto.render_proto_for_log();
+    assert_eq!(
+        rendered,
+        r#"Customer { name: "Alice", primary: Some(Contact { email: [redacted], label: "home", .. }), others: [Contact { email: [redacted], label: "work", .. }, Contact { email: [redacted], label: "", .. }], .. }"#
+    );
+    assert!(!rendered.contains("example.com"));
+
+    let error = FromProto::from_proto(proto.clone())
+        .map(|_: LoggedCustomer| ())
+        .unwrap_err();
+    assert_eq!(error.to_string(), "others[1].label: The label is empty");
+    // the innermost failed message is attached
+    let error = attach_to_errors(|| proto.from_proto())
+        .map(|_: LoggedCustomer| ())
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        r#"others[1].label: The label is empty, in Contact { email: [redacted], label: "", .. }"#
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, FromProto, ToProto, proto_conve::ProtoCbor)]
+#[proto(type = "i32")]
+pub enum ParcelState {
+    Packed,
+    Shipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, proto_conve::ProtoCbor)]
+pub struct Depot {
+    code: String,
+}
+
+#[derive(Debug, Clone, PartialEq, proto_conve::ProtoCbor)]
+pub struct Parcel {
+    weight: u32,
+    state: ParcelState,
+    destination: Depot,
+    #[proto(default)]
+    origin: Depot,
+    note: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, proto_conve::ProtoCbor)]
+#[proto(rename_all = "camelCase")]
+pub struct Shipment {
+    tracking_id: String,
+    shipped_at: chrono::DateTime<chrono::Utc>,
+    parcels: Vec<Parcel>,
+    #[proto(rename = "note")]
+    comment: Option<String>,
+    #[proto(skip)]
+    cached: u32,
+}
+
+impl_traits!(cbor chrono::DateTime<chrono::Utc>);
+
+#[test]
+fn test_cbor() {
+    let shipment = Shipment {
+        tracking_id: "SH-1".to_owned(),
+        shipped_at: chrono::DateTime::from_timestamp(1_700_000_000, 250).unwrap(),
+        parcels: vec![
+            Parcel {
+                weight: 3,
+                state: ParcelState::Shipped,
+                destination: Depot {
+                    code: "PRG".to_owned(),
+                },
+                origin: Depot::default(),
+                note: None,
+            },
+            Parcel {
+                weight: 0,
+                state: ParcelState::Packed,
+                destination: Depot {
+                    code: "BTS".to_owned(),
+                },
+                origin: Depot {
+                    code: "VIE".to_owned(),
+                },
+                note: Some("fragile".to_owned()),
+            },
+        ],
+        comment: None,
+        cached: 0,
+    };
+    let bytes = shipment.to_cbor().unwrap();
+    assert_eq!(Shipment::from_cbor(&bytes).unwrap(), shipment);
+
+    // keyed by the proto field names, with the timestamp of the seconds and nanos
+    let value = shipment.to_cbor_value();
+    let proto_conve::cbor::Value::Map(entries) = &value else {
+        panic!("not a map: {value:?}");
+    };
+    let keys: Vec<_> = entries
+        .iter()
+        .map(|(key, _)| key.as_text().unwrap())
+        .collect();
+    assert_eq!(keys, ["trackingId", "shippedAt", "parcels", "note"]);
+    assert_eq!(
+        entries[1].1,
+        proto_conve::cbor::map(vec![
+            ("seconds", 1_700_000_000i64.to_cbor_value()),
+            ("nanos", 250i32.to_cbor_value()),
+        ])
+    );
+
+    // the missing scalars and enums are the proto defaults, the missing messages are required
+    let parcel = |fields: Vec<(&'static str, proto_conve::cbor::Value)>| {
+        let shipment = proto_conve::cbor::map(vec![
+            ("shippedAt", entries[1].1.clone()),
+            (
+                "parcels",
+                proto_conve::cbor::Value::Array(vec![proto_conve::cbor::map(fields)]),
+            ),
+        ]);
+        Shipment::from_cbor_value(shipment)
+    };
+    let destination = proto_conve::cbor::map(vec![("code", "PRG".to_owned().to_cbor_value())]);
+    let decoded = parcel(vec![("destination", destination)]).unwrap();
+    assert_eq!(decoded.tracking_id, "");
+    assert_eq!(decoded.parcels[0].weight, 0);
+    assert_eq!(decoded.parcels[0].state, ParcelState::Packed);
+    assert_eq!(decoded.parcels[0].origin, Depot::default());
+    assert_eq!(decoded.parcels[0].note, None);
+    let error = parcel(vec![("weight", 1u32.to_cbor_value())]).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "parcels[0].destination: Required is missing"
+    );
+    let error = parcel(vec![("weight", (-1i64).to_cbor_value())]).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "parcels[0].weight: The value -1 does not fit in to u32"
+    );
+}
+
+mod lookup {
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Outcome {
+        Found(super::proto::Address),
+        Failure(String),
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct LookupResult {
+        pub outcome: Option<Outcome>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Reply {
+        pub id: u64,
+        pub outcome: Option<Outcome>,
+    }
+}
+
+proto_conve::impl_proto_result!(lookup::LookupResult, outcome: lookup::Outcome {
+    Found(found) => Address,
+    Failure(failure) => String,
+});
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "lookup::Reply")]
+pub struct Reply {
+    id: u64,
+    outcome: Result<Address, String>,
+}
+
+#[test]
+fn test_result() {
+    let address = proto::Address {
+        street: "Long".to_string(),
+        number: 1,
+    };
+    let proto = lookup::Reply {
+        id: 1,
+        outcome: Some(lookup::Outcome::Found(address.clone())),
+    };
+    let reply = proto.clone().from_proto().unwrap();
+    assert_eq!(reply.outcome, Ok(address.clone().from_proto().unwrap()));
+    assert_eq!(reply.to_proto(), proto);
+
+    let proto = lookup::Reply {
+        id: 2,
+        outcome: Some(lookup::Outcome::Failure("not found".to_string())),
+    };
+    let reply = proto.clone().from_proto().unwrap();
+    assert_eq!(reply.outcome, Err("not found".to_string()));
+    assert_eq!(reply.to_proto(), proto);
+
+    let error = lookup::Reply {
+        id: 3,
+        outcome: None,
+    }
+    .from_proto()
+    .unwrap_err();
+    assert_eq!(error.to_string(), "outcome: Required is missing");
+
+    let results = vec![
+        lookup::LookupResult {
+            outcome: Some(lookup::Outcome::Found(address)),
+        },
+        lookup::LookupResult {
+            outcome: Some(lookup::Outcome::Failure("gone".to_string())),
+        },
+    ];
+    let converted = results.clone().from_proto().unwrap();
+    assert_eq!(converted[1], Err("gone".to_string()));
+    assert_eq!(ToProto::to_proto(&converted), results);
+}
+
+mod ledger {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Totals {
+        pub debit: i64,
+        pub credit: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Entry {
+        pub id: u64,
+        pub memo: String,
+        pub totals: Option<Totals>,
+        pub legacy_account: String,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(
+    type = "ledger::Entry",
+    deny_unknown_fields,
+    unmapped = "legacy_account"
+)]
+pub struct Entry {
+    id: u64,
+    #[proto(rename = "memo")]
+    note: String,
+    #[proto(path = "totals.debit")]
+    debit: i64,
+    #[proto(path = "totals.credit")]
+    credit: i64,
+    #[proto(skip)]
+    cached: bool,
+}
+
+#[test]
+fn test_deny_unknown_fields() {
+    let proto = ledger::Entry {
+        id: 1,
+        memo: "rent".to_string(),
+        totals: Some(ledger::Totals {
+            debit: 10,
+            credit: 0,
+        }),
+        legacy_account: "A-1".to_string(),
+    };
+    let entry = proto.from_proto().unwrap();
+    assert_eq!(
+        entry,
+        Entry {
+            id: 1,
+            note: "rent".to_string(),
+            debit: 10,
+            credit: 0,
+            cached: false,
+        }
+    );
+}
+
+mod recipients {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct PostalAddress {
+        pub street: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Recipient {
+        pub name: String,
+        pub address: Option<PostalAddress>,
+        pub billing_address: Option<PostalAddress>,
+        pub nickname: Option<String>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostalAddress {
+    street: String,
+}
+
+impl ToProto for PostalAddress {
+    type Result = recipients::PostalAddress;
+
+    fn to_proto(&self) -> Self::Result {
+        // the omitted placeholders are never converted
+        assert!(!is_placeholder(self), "the placeholder was converted");
+        recipients::PostalAddress {
+            street: self.street.clone(),
+        }
+    }
+}
+
+fn is_placeholder(address: &PostalAddress) -> bool {
+    address.street.is_empty()
+}
+
+fn is_blank(value: &str) -> bool {
+    value.trim().is_empty()
+}
+
+#[derive(Debug, Clone, PartialEq, ToProto)]
+#[proto(type = "recipients::Recipient")]
+pub struct Recipient {
+    name: String,
+    #[proto(omit_if = "is_placeholder")]
+    address: PostalAddress,
+    #[proto(omit_if = "is_placeholder")]
+    billing_address: Option<PostalAddress>,
+    #[proto(omit_if = "is_blank")]
+    nickname: Option<String>,
+}
+
+#[test]
+fn test_omit_if() {
+    let placeholder = PostalAddress {
+        street: String::new(),
+    };
+    let address = PostalAddress {
+        street: "Main 1".to_string(),
+    };
+    let recipient = Recipient {
+        name: "Ann".to_string(),
+        address: placeholder.clone(),
+        billing_address: Some(placeholder),
+        nickname: Some(" ".to_string()),
+    };
+    assert_eq!(
+        recipient.to_proto(),
+        recipients::Recipient {
+            name: "Ann".to_string(),
+            ..Default::default()
+        }
+    );
+
+    let recipient = Recipient {
+        address: address.clone(),
+        billing_address: Some(address),
+        nickname: Some("An".to_string()),
+        ..recipient
+    };
+    let proto = recipient.to_proto();
+    let street = Some(recipients::PostalAddress {
+        street: "Main 1".to_string(),
+    });
+    assert_eq!(proto.address, street);
+    assert_eq!(proto.billing_address, street);
+    assert_eq!(proto.nickname, Some("An".to_string()));
+
+    let recipient = Recipient {
+        billing_address: None,
+        nickname: None,
+        ..recipient
+    };
+    let proto = recipient.to_proto();
+    assert_eq!(proto.billing_address, None);
+    assert_eq!(proto.nickname, None);
+}
+
+mod consignments {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Consignment {
+        pub carrier: String,
+        pub weight: u32,
+    }
+}
+
+/// The conversion before the rewrite, which trimmed the carriers
+fn legacy_carrier(carrier: String) -> anyhow::Result<String> {
+    anyhow::ensure!(!carrier.is_empty(), "The carrier is empty");
+    Ok(carrier.trim().to_string())
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(type = "consignments::Consignment")]
+pub struct Consignment {
+    #[proto(dual = "legacy_carrier")]
+    carrier: String,
+    weight: u32,
+}
+
+#[test]
+fn test_dual() {
+    use std::sync::{Arc, Mutex};
+
+    use proto_conve::dual::{self, Divergence, DivergenceKind};
+
+    let reports = Arc::new(Mutex::new(Vec::<Divergence>::new()));
+    let observed = reports.clone();
+    dual::set_observer(Arc::new(move |divergence: &Divergence| {
+        observed.lock().unwrap().push(divergence.clone())
+    }));
+    let consignment = |carrier: &str| consignments::Consignment {
+        carrier: carrier.to_string(),
+        weight: 3,
+    };
+    assert_eq!(consignment("post").from_proto().unwrap().carrier, "post");
+    // the old function is primary
+    assert_eq!(consignment(" post ").from_proto().unwrap().carrier, "post");
+    let error = consignment("").from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "carrier: The carrier is empty");
+    dual::clear_observer();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0].kind(), DivergenceKind::Value);
+    assert_eq!(reports[0].field(), Some("carrier"));
+    assert_eq!(reports[0].old_result(), "Ok(\"post\")");
+    assert_eq!(reports[0].new_result(), "Ok(\" post \")");
+    assert_eq!(reports[1].kind(), DivergenceKind::OldFailed);
+    assert_eq!(
+        reports[1].old_result(),
+        "Err(carrier: The carrier is empty)"
+    );
+}
+
+mod windows {
+    #[derive(Debug, Clone, PartialEq, Default, proto_conve::ProtoDefault)]
+    pub struct Size {
+        pub width: u32,
+        pub height: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default, proto_conve::ProtoDefault)]
+    pub struct Window {
+        pub title: String,
+        pub size: Option<Size>,
+        pub tabs: Vec<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct UpdateWindow {
+        pub size: Option<Size>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, FromProto, ToProto)]
+#[proto(type = "windows::Size")]
+pub struct Size {
+    width: u32,
+    height: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "windows::UpdateWindow")]
+pub struct UpdateWindow {
+    #[proto(skip_default)]
+    size: Size,
+}
+
+#[test]
+fn test_proto_default() {
+    use proto_conve::proto_default::ProtoDefault;
+
+    let window = windows::Window::default();
+    assert!(window.is_proto_default());
+    assert!(std::ptr::eq(
+        windows::Window::proto_default(),
+        windows::Window::proto_default()
+    ));
+    assert_eq!(windows::Window::proto_default(), &window);
+    let window = windows::Window {
+        size: Some(windows::Size::default()),
+        ..Default::default()
+    };
+    assert!(!window.is_proto_default());
+
+    // the messages of `skip_default` are checked with `ProtoDefault`
+    let update = UpdateWindow {
+        size: Size::default(),
+    };
+    assert_eq!(update.to_proto().size, None);
+    let update = UpdateWindow {
+        size: Size {
+            width: 640,
+            height: 0,
+        },
+    };
+    let proto = update.to_proto();
+    assert_eq!(
+        proto.size,
+        Some(windows::Size {
+            width: 640,
+            height: 0
+        })
+    );
+    assert_eq!(proto.from_proto().unwrap(), update);
+}
+
+mod uploads {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Chunk {
+        pub offset: u64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Upload {
+        pub name: String,
+        pub chunk: Option<Chunk>,
+        pub payload: Vec<u8>,
+        pub checksum: Option<Vec<u8>>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, ToProto)]
+#[proto(type = "uploads::Chunk")]
+pub struct Chunk {
+    offset: u64,
+}
+
+#[derive(Debug, PartialEq, proto_conve::IntoProto)]
+#[proto(type = "uploads::Upload")]
+pub struct Upload {
+    #[proto(clone_cheap)]
+    name: String,
+    chunk: Chunk,
+    #[proto(move_only)]
+    payload: Vec<u8>,
+    #[proto(move_only, skip_default)]
+    checksum: Vec<u8>,
+}
+
+#[test]
+fn test_move_only() {
+    let payload = vec![7u8; 1 << 20];
+    let address = payload.as_ptr();
+    let upload = Upload {
+        name: "backup".to_string(),
+        chunk: Chunk { offset: 4096 },
+        payload,
+        checksum: Vec::new(),
+    };
+    let proto = upload.into_proto();
+    assert_eq!(proto.name, "backup");
+    assert_eq!(proto.chunk, Some(uploads::Chunk { offset: 4096 }));
+    assert_eq!(proto.checksum, None);
+    // moved, not cloned
+    assert_eq!(proto.payload.as_ptr(), address);
+    assert_eq!(proto.payload.len(), 1 << 20);
+
+    let upload = Upload {
+        name: String::new(),
+        chunk: Chunk { offset: 0 },
+        payload: Vec::new(),
+        checksum: vec![1, 2],
+    };
+    assert_eq!(upload.into_proto().checksum, Some(vec![1, 2]));
+}
+
+mod signatures {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Signer {
+        pub name: String,
+        pub weight: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Envelope {
+        pub tags: Vec<String>,
+        pub signers: Vec<Signer>,
+        pub history: Vec<String>,
+    }
+}
+
+/// The domain signers have no `Ord`
+#[derive(Debug, Clone, PartialEq, ToProto)]
+#[proto(type = "signatures::Signer")]
+pub struct Signer {
+    name: String,
+    weight: u32,
+}
+
+fn signer_weight(signer: &signatures::Signer) -> u32 {
+    signer.weight
+}
+
+#[derive(Debug, Clone, PartialEq, ToProto)]
+#[proto(type = "signatures::Envelope")]
+pub struct Envelope {
+    #[proto(sorted)]
+    tags: std::collections::HashSet<String>,
+    #[proto(sorted = "signer_weight")]
+    signers: Vec<Signer>,
+    history: Vec<String>,
+}
+
+#[test]
+fn test_sorted() {
+    let signer = |name: &str, weight| Signer {
+        name: name.to_string(),
+        weight,
+    };
+    let envelope = Envelope {
+        tags: ["b", "c", "a"].into_iter().map(String::from).collect(),
+        signers: vec![
+            signer("x", 3),
+            signer("y", 1),
+            signer("z", 3),
+            signer("w", 1),
+        ],
+        history: vec!["second".to_string(), "first".to_string()],
+    };
+    let proto = envelope.to_proto();
+    assert_eq!(proto.tags, ["a", "b", "c"]);
+    // stable for the equal weights
+    let names: Vec<_> = proto
+        .signers
+        .iter()
+        .map(|signer| signer.name.as_str())
+        .collect();
+    assert_eq!(names, ["y", "w", "x", "z"]);
+    // the value is not changed, the other fields keep their order
+    assert_eq!(envelope.signers[0], signer("x", 3));
+    assert_eq!(proto.history, ["second", "first"]);
+}
+
+mod listings {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Listing {
+        pub availability: String,
+        pub previous: Vec<String>,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, FromProto, ToProto)]
+#[proto(string, case_insensitive)]
+pub enum Availability {
+    #[proto(alias = "enabled", alias = "on")]
+    InStock,
+    #[proto(name = "SOLD_OUT", alias = "off")]
+    Gone,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "listings::Listing")]
+pub struct Listing {
+    availability: Availability,
+    previous: Vec<Availability>,
+}
+
+#[test]
+fn test_enum_strings() {
+    use proto_conve::enumeration::NameMatch;
+
+    let (value, matched) = Availability::from_proto_name("IN_STOCK").unwrap();
+    assert_eq!(
+        (value, matched),
+        (Availability::InStock, NameMatch::Canonical)
+    );
+    assert!(!matched.is_lenient());
+    let (value, matched) = Availability::from_proto_name("In_Stock").unwrap();
+    assert_eq!(
+        (value, matched),
+        (Availability::InStock, NameMatch::CaseInsensitive)
+    );
+    assert!(matched.is_lenient());
+    assert_eq!(
+        Availability::from_proto_name("on").unwrap(),
+        (Availability::InStock, NameMatch::Alias)
+    );
+    assert_eq!(
+        Availability::from_proto_name("OFF").unwrap(),
+        (Availability::Gone, NameMatch::CaseInsensitive)
+    );
+    let error = Availability::from_proto_alias("GONE".to_string()).unwrap_err();
+    assert_eq!(error.to_string(), "Unknown name \"GONE\" of Availability");
+
+    // always written in the canonical form
+    assert_eq!(Availability::Gone.proto_name(), "SOLD_OUT");
+    assert_eq!(
+        ToProtoAlias::<String>::to_proto_alias(&Availability::InStock),
+        "IN_STOCK"
+    );
+}
+
+#[test]
+fn test_enum_strings_notices() {
+    let proto = listings::Listing {
+        availability: "enabled".to_string(),
+        previous: vec!["SOLD_OUT".to_string(), "sold_out".to_string()],
+    };
+    let (listing, notices) = proto_conve::deprecation::collect(|| proto.from_proto());
+    let listing = listing.unwrap();
+    assert_eq!(listing.availability, Availability::InStock);
+    assert_eq!(listing.previous, [Availability::Gone, Availability::Gone]);
+    // the lenient matches, not the canonical one
+    let notices: Vec<_> = notices.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        notices,
+        [
+            "availability: The deprecated field, use \"IN_STOCK\", the canonical name of Availability",
+            "previous: The deprecated field, use \"SOLD_OUT\", the canonical name of Availability",
+        ]
+    );
+
+    let proto = ToProto::to_proto(&listing);
+    assert_eq!(proto.availability, "IN_STOCK");
+    assert_eq!(proto.previous, ["SOLD_OUT", "SOLD_OUT"]);
+}
+
+mod reports {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Line {
+        pub cents: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Author {
+        pub name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Report {
+        pub id: u64,
+        pub title: String,
+        pub author: Option<Author>,
+        pub lines: Vec<Line>,
+    }
+}
+
+/// The lines are expensive, the projections must not convert them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line;
+
+impl FromProto for reports::Line {
+    type Result = Line;
+
+    fn from_proto(self) -> anyhow::Result<Self::Result> {
+        panic!("the lines are converted")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(type = "reports::Author")]
+pub struct Author {
+    name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(type = "reports::Report")]
+pub struct Report {
+    id: u64,
+    title: String,
+    author: Author,
+    lines: Vec<Line>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(project_from = "reports::Report", full = "Report")]
+pub struct ReportSummary {
+    id: u64,
+    title: String,
+    #[proto(skip)]
+    read: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(project_from = "reports::Report")]
+pub struct ReportAuthor {
+    #[proto(path = "author.name")]
+    author: String,
+    #[proto(rename = "id")]
+    report_id: u64,
+}
+
+fn report() -> reports::Report {
+    reports::Report {
+        id: 7,
+        title: "Quarter".to_string(),
+        author: Some(reports::Author {
+            name: "Ann".to_string(),
+        }),
+        lines: vec![reports::Line { cents: 5 }; 3],
+    }
+}
+
+#[test]
+fn test_projection() {
+    let summary = ReportSummary::from_proto_alias(report()).unwrap();
+    assert_eq!(
+        summary,
+        ReportSummary {
+            id: 7,
+            title: "Quarter".to_string(),
+            read: false,
+        }
+    );
+    let author = ReportAuthor::from_proto_alias(report()).unwrap();
+    assert_eq!((author.author.as_str(), author.report_id), ("Ann", 7));
+
+    // the errors have the path of the proto fields
+    let proto = reports::Report {
+        author: None,
+        ..report()
+    };
+    let error = ReportAuthor::from_proto_alias(proto).unwrap_err();
+    assert_eq!(error.to_string(), "author: Required is missing");
+
+    let summaries = Vec::<ReportSummary>::from_proto_alias(vec![report(), report()]).unwrap();
+    assert_eq!(summaries.len(), 2);
+}
+
+#[test]
+fn test_projection_skips_fields() {
+    // the projection only moves its fields out, the lines are dropped
+    assert!(ReportSummary::from_proto_alias(report()).is_ok());
+    let full = std::panic::catch_unwind(|| report().from_proto());
+    let message = full.unwrap_err();
+    assert_eq!(
+        message.downcast_ref::<&str>(),
+        Some(&"the lines are converted")
+    );
+}
+
+mod vaults {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Secret {
+        pub value: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Vault {
+        pub name: String,
+        pub master: Option<Secret>,
+        pub secrets: Vec<Secret>,
+    }
+}
+
+/// The secrets are only sent when they are shared
+#[derive(Debug, Clone, PartialEq)]
+pub struct Secret {
+    value: String,
+    shared: bool,
+}
+
+impl ToProtoOption<vaults::Secret> for Secret {
+    fn to_proto(&self) -> Option<vaults::Secret> {
+        self.shared.then(|| vaults::Secret {
+            value: self.value.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, ToProto)]
+#[proto(type = "vaults::Vault")]
+pub struct Vault {
+    name: String,
+    master: Secret,
+    secrets: Vec<Secret>,
+}
+
+#[test]
+fn test_to_proto_option_fields() {
+    let secret = |value: &str, shared| Secret {
+        value: value.to_string(),
+        shared,
+    };
+    let vault = Vault {
+        name: "home".to_string(),
+        master: secret("root", false),
+        secrets: vec![secret("a", true), secret("b", false), secret("c", true)],
+    };
+    let proto = ToProto::to_proto(&vault);
+    assert_eq!(proto.master, None);
+    let values: Vec<_> = proto.secrets.iter().map(|s| s.value.as_str()).collect();
+    assert_eq!(values, ["a", "c"]);
+
+    let vault = Vault {
+        master: secret("root", true),
+        ..vault
+    };
+    let proto = ToProto::to_proto(&vault);
+    assert_eq!(proto.master.unwrap().value, "root");
+}
+
+/// The shapes of the prost-build output with `btree_map = ["."]` and `boxed = [".Formula.left",
+/// ".Formula.literal"]`, with `::std` for the `::prost::alloc` it re-exports
+mod generated {
+    #[derive(Clone, PartialEq, Default, Debug)]
+    pub struct Weight {
+        pub value: i64,
+    }
+
+    #[derive(Clone, PartialEq, Default, Debug)]
+    pub struct Formula {
+        pub name: ::std::string::String,
+        pub left: ::core::option::Option<::std::boxed::Box<Formula>>,
+        pub literal: ::core::option::Option<::std::boxed::Box<Weight>>,
+        pub bonus: ::core::option::Option<::std::boxed::Box<Weight>>,
+        pub labels: ::std::collections::BTreeMap<::std::string::String, i64>,
+        pub literals: ::std::collections::BTreeMap<::std::string::String, Weight>,
+        pub args: ::std::vec::Vec<Formula>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "generated::Weight")]
+pub struct Weight {
+    value: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "generated::Formula")]
+pub struct Formula {
+    name: String,
+    left: ::core::option::Option<Box<Formula>>,
+    literal: Weight,
+    bonus: Option<Weight>,
+    labels: std::collections::BTreeMap<String, i64>,
+    literals: std::collections::BTreeMap<String, Weight>,
+    args: Vec<Formula>,
+}
+
+#[test]
+fn test_prost_build_shapes() {
+    let leaf = Formula {
+        name: "leaf".to_string(),
+        left: None,
+        literal: Weight { value: 1 },
+        bonus: None,
+        labels: Default::default(),
+        literals: Default::default(),
+        args: vec![],
+    };
+    let expr = Formula {
+        name: "root".to_string(),
+        left: Some(Box::new(leaf.clone())),
+        literal: Weight { value: 2 },
+        bonus: Some(Weight { value: 4 }),
+        labels: [("a".to_string(), 1), ("b".to_string(), 2)].into(),
+        literals: [("x".to_string(), Weight { value: 3 })].into(),
+        args: vec![leaf],
+    };
+    let proto = ToProto::to_proto(&expr);
+    assert_eq!(
+        proto.literal.as_deref(),
+        Some(&generated::Weight { value: 2 })
+    );
+    assert_eq!(proto.left.as_ref().unwrap().name, "leaf");
+    assert_eq!(proto.bonus, Some(Box::new(generated::Weight { value: 4 })));
+    assert_eq!(proto.labels["b"], 2);
+    assert_eq!(proto.clone().from_proto().unwrap(), expr);
+
+    let proto = generated::Formula {
+        literal: None,
+        ..proto
+    };
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "literal: Required is missing");
+}
+
+mod deliveries {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Courier {
+        pub id: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Package {
+        pub id: u64,
+        pub weight: u32,
+        pub courier: Option<Courier>,
+        pub backups: Vec<Courier>,
+    }
+}
+
+/// The couriers of the deliveries by their ids
+pub struct Roster {
+    names: std::collections::HashMap<u32, &'static str>,
+}
+
+impl Roster {
+    async fn lookup(&self, id: u32) -> anyhow::Result<Driver> {
+        tokio::task::yield_now().await;
+        let name = self
+            .names
+            .get(&id)
+            .ok_or_else(|| anyhow::anyhow!("No courier {id} on the roster"))?;
+        Ok(Driver {
+            id,
+            name: name.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Driver {
+    id: u32,
+    name: String,
+}
+
+impl AsyncFromProtoWith<Roster> for deliveries::Courier {
+    type Result = Driver;
+
+    async fn from_proto_async(self, roster: &Roster) -> anyhow::Result<Driver> {
+        roster
+            .lookup(self.id)
+            .await
+            .map_err(|error| proto_conve::error::in_field(error, "id"))
+    }
+}
+
+mod drivers {
+    use super::*;
+
+    pub async fn from_proto(
+        proto: Option<deliveries::Courier>,
+        roster: &Roster,
+    ) -> anyhow::Result<Driver> {
+        let proto = proto.ok_or_else(|| anyhow::anyhow!("The parcel has no courier"))?;
+        proto.from_proto_async(roster).await
+    }
+
+    pub fn to_proto(driver: &Driver) -> Option<deliveries::Courier> {
+        Some(deliveries::Courier { id: driver.id })
+    }
+}
+
+mod backup_drivers {
+    use super::*;
+
+    pub async fn from_proto(
+        protos: Vec<deliveries::Courier>,
+        roster: &Roster,
+    ) -> anyhow::Result<Vec<Driver>> {
+        protos.from_proto_async_buffered(roster, 2).await
+    }
+
+    pub fn to_proto(drivers: &[Driver]) -> Vec<deliveries::Courier> {
+        drivers
+            .iter()
+            .map(|driver| deliveries::Courier { id: driver.id })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "deliveries::Package", context = "Roster")]
+pub struct Package {
+    id: u64,
+    weight: u32,
+    #[proto(async_with = "drivers")]
+    courier: Driver,
+    #[proto(async_with = "backup_drivers")]
+    backups: Vec<Driver>,
+}
+
+#[tokio::test]
+async fn test_async_with() {
+    let roster = Roster {
+        names: [(1, "ann"), (2, "bob"), (3, "cyd")].into(),
+    };
+    let courier = |id| deliveries::Courier { id };
+    let proto = deliveries::Package {
+        id: 7,
+        weight: 12,
+        courier: Some(courier(2)),
+        backups: vec![courier(3), courier(1)],
+    };
+    let parcel = proto.clone().from_proto_async(&roster).await.unwrap();
+    assert_eq!(parcel.courier.name, "bob");
+    let names: Vec<_> = parcel.backups.iter().map(|driver| &driver.name).collect();
+    assert_eq!(names, ["cyd", "ann"]);
+    assert_eq!(ToProto::to_proto(&parcel), proto);
+
+    let error = deliveries::Package {
+        courier: Some(courier(9)),
+        ..proto.clone()
+    }
+    .from_proto_async(&roster)
+    .await
+    .unwrap_err();
+    assert_eq!(error.to_string(), "courier.id: No courier 9 on the roster");
+    let error = deliveries::Package {
+        backups: vec![courier(1), courier(8)],
+        ..proto.clone()
+    }
+    .from_proto_async(&roster)
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "backups[1].id: No courier 8 on the roster"
+    );
+    let error = deliveries::Package {
+        courier: None,
+        ..proto
+    }
+    .from_proto_async(&roster)
+    .await
+    .unwrap_err();
+    assert_eq!(error.to_string(), "courier: The parcel has no courier");
+}
+
+mod allowances {
+    use std::collections::HashMap;
+
+    /// The enum as generated by prost, with its `TryFrom<i32>` and `From<Plan> for i32`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    #[repr(i32)]
+    pub enum Plan {
+        Basic = 1,
+        Premium = 2,
+    }
+
+    impl TryFrom<i32> for Plan {
+        type Error = i32;
+
+        fn try_from(value: i32) -> Result<Self, i32> {
+            match value {
+                1 => Ok(Plan::Basic),
+                2 => Ok(Plan::Premium),
+                value => Err(value),
+            }
+        }
+    }
+
+    impl From<Plan> for i32 {
+        fn from(plan: Plan) -> i32 {
+            plan as i32
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Allowances {
+        pub seats: HashMap<i32, u32>,
+        pub storage: HashMap<i32, u32>,
+        pub offices: HashMap<i32, String>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto, FromProtoLossy)]
+#[proto(type = "allowances::Allowances")]
+pub struct Allowances {
+    #[proto(key_enum = "allowances::Plan")]
+    seats: std::collections::HashMap<allowances::Plan, u32>,
+    #[proto(key_enum = "allowances::Plan", unknown_keys = "skip")]
+    storage: std::collections::BTreeMap<allowances::Plan, u32>,
+    #[proto(key_enum = "allowances::Plan", unknown_keys = "open")]
+    offices: std::collections::BTreeMap<proto_conve::OpenEnum<allowances::Plan>, String>,
+}
+
+#[test]
+fn test_enum_key_policies() {
+    use allowances::Plan;
+    use proto_conve::OpenEnum;
+
+    let office = |street: &str| street.to_string();
+    let proto = allowances::Allowances {
+        seats: [(1, 5), (2, 50)].into(),
+        storage: [(1, 10), (3, 30)].into(),
+        offices: [(2, office("Main")), (4, office("Side"))].into(),
+    };
+    let allowances = proto.clone().from_proto().unwrap();
+    assert_eq!(allowances.seats[&Plan::Premium], 50);
+    assert_eq!(allowances.storage, [(Plan::Basic, 10)].into());
+    assert_eq!(allowances.offices[&OpenEnum::Unknown(4)], "Side");
+    assert_eq!(allowances.offices[&OpenEnum::Known(Plan::Premium)], "Main");
+
+    // the skipped keys are lost, the open ones are written back
+    let back = allowances.to_proto();
+    assert_eq!(back.storage, [(1, 10)].into());
+    assert_eq!(
+        back,
+        allowances::Allowances {
+            storage: [(1, 10)].into(),
+            ..proto.clone()
+        }
+    );
+    assert_eq!(back.clone().from_proto().unwrap().to_proto(), back);
+
+    let (_, report) = proto.clone().from_proto_lossy().unwrap();
+    let events: Vec<_> = report
+        .iter()
+        .map(|(path, event)| (path, event.clone()))
+        .collect();
+    assert_eq!(
+        events,
+        [(
+            "storage[\"3\"]".to_string(),
+            proto_conve::lossy::LossyEvent::UnknownEnum { raw: 3 }
+        )]
+    );
+
+    let error = allowances::Allowances {
+        seats: [(1, 5), (9, 1), (7, 1)].into(),
+        ..proto
+    }
+    .from_proto()
+    .unwrap_err();
+    assert_eq!(error.to_string(), "seats: Unknown keys 7, 9 of Plan");
+    assert_eq!(
+        proto_conve::violation::ErrorCode::of(&error),
+        proto_conve::violation::ErrorCode::UnknownEnum
+    );
+}
+
+mod staff {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Office {
+        pub city: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Employee {
+        pub employee_id: u64,
+        pub display_name: String,
+        pub office: Option<Office>,
+        pub manager: Option<u64>,
+        pub reports: Vec<u64>,
+        pub category: i32,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(type = "staff::Employee")]
+pub struct Employee {
+    #[proto(rename = "employee_id")]
+    id: u64,
+    #[proto(rename = "display_name", with = "employee_names")]
+    name: String,
+    #[proto(path = "office.city")]
+    city: String,
+    #[proto(default)]
+    manager: u64,
+    reports: Vec<u64>,
+    #[proto(rename = "category")]
+    kind: i32,
+    #[proto(skip)]
+    cached_rank: u32,
+}
+
+mod employee_names {
+    pub fn from_proto(name: String) -> anyhow::Result<String> {
+        Ok(name.trim().to_string())
+    }
+}
+
+#[test]
+fn test_proto_mapping() {
+    use proto_conve::reflect::{FieldMapping, HasProtoMapping};
+
+    let mapping = Employee::proto_mapping();
+    assert_eq!(mapping.rust_type, "Employee");
+    assert_eq!(mapping.proto_type, "staff::Employee");
+    let field = |rust_name, proto_name, required, skipped| FieldMapping {
+        rust_name,
+        proto_name,
+        required,
+        skipped,
+        transform: None,
+    };
+    assert_eq!(
+        mapping.fields,
+        [
+            field("id", "employee_id", true, false),
+            FieldMapping {
+                transform: Some("employee_names::from_proto"),
+                ..field("name", "display_name", true, false)
+            },
+            field("city", "office.city", true, false),
+            field("manager", "manager", false, false),
+            field("reports", "reports", false, false),
+            field("kind", "category", true, false),
+            field("cached_rank", "cached_rank", false, true),
+        ]
+    );
+    assert_eq!(mapping.field("kind").unwrap().proto_name, "category");
+    // the same static for every call
+    assert!(std::ptr::eq(mapping, Employee::proto_mapping()));
+}
+
+mod fixtures {
+    #[derive(Debug, Clone, PartialEq, Default, proto_conve::TextProto)]
+    pub struct Stamp {
+        pub seconds: i64,
+        pub nanos: i32,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Grade {
+        Unspecified = 0,
+        Premium = 2,
+    }
+
+    impl Grade {
+        pub fn from_str_name(name: &str) -> Option<Self> {
+            match name {
+                "GRADE_UNSPECIFIED" => Some(Grade::Unspecified),
+                "GRADE_PREMIUM" => Some(Grade::Premium),
+                _ => None,
+            }
+        }
+    }
+
+    impl From<Grade> for i32 {
+        fn from(grade: Grade) -> i32 {
+            grade as i32
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, proto_conve::TextProto)]
+    pub enum Contact {
+        #[prost(string, tag = "6")]
+        Email(String),
+        #[prost(string, tag = "7")]
+        Phone(String),
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default, proto_conve::TextProto)]
+    pub struct LineItem {
+        pub sku: String,
+        pub quantity: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default, proto_conve::TextProto)]
+    pub struct Dispatch {
+        pub id: u64,
+        #[prost(enumeration = "Grade", tag = "2")]
+        pub grade: i32,
+        pub shipped: Option<Stamp>,
+        pub items: Vec<LineItem>,
+        #[prost(bytes = "vec", tag = "5")]
+        pub checksum: Vec<u8>,
+        #[prost(oneof = "Contact", tags = "6, 7")]
+        pub contact: Option<Contact>,
+    }
+}
+
+use fixtures::Stamp;
+impl_traits!(Stamp, chrono::DateTime<chrono::Utc>, verify_roundtrip);
+
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(type = "fixtures::LineItem")]
+pub struct LineItem {
+    sku: String,
+    quantity: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(oneof = "fixtures::Contact")]
+pub enum DispatchContact {
+    Email(String),
+    Phone(String),
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(type = "fixtures::Dispatch")]
+pub struct Dispatch {
+    id: u64,
+    grade: i32,
+    shipped: chrono::DateTime<chrono::Utc>,
+    items: Vec<LineItem>,
+    checksum: Vec<u8>,
+    contact: Option<DispatchContact>,
+}
+
+#[test]
+fn test_text_proto() {
+    let fixture = r#"
+        # the premium dispatch
+        id: 42
+        grade: GRADE_PREMIUM
+        shipped { seconds: 1700000000 nanos: 500 }
+        items { sku: "A-1" quantity: 2 }
+        items: [{ sku: "B-2", quantity: 1 }]
+        checksum: "\x01\x02"
+        email: "ops@example.com"
+    "#;
+    let dispatch = from_text_proto::<fixtures::Dispatch, _>(fixture).unwrap();
+    let item = |sku: &str, quantity| LineItem {
+        sku: sku.to_string(),
+        quantity,
+    };
+    assert_eq!(
+        dispatch,
+        Dispatch {
+            id: 42,
+            grade: 2,
+            shipped: chrono::DateTime::from_timestamp(1_700_000_000, 500).unwrap(),
+            items: vec![item("A-1", 2), item("B-2", 1)],
+            checksum: vec![1, 2],
+            contact: Some(DispatchContact::Email("ops@example.com".to_string())),
+        }
+    );
+
+    // the last variant of the oneof wins
+    let proto: fixtures::Dispatch =
+        proto_conve::text_format::parse("email: \"a@example.com\" phone: \"+1 555\" grade: 2")
+            .unwrap();
+    assert_eq!(
+        proto.contact,
+        Some(fixtures::Contact::Phone("+1 555".to_string()))
+    );
+    assert_eq!(proto.grade, 2);
+
+    // the missing fields fail to convert, not to parse
+    let error = from_text_proto::<fixtures::Dispatch, Dispatch>("id: 1").unwrap_err();
+    assert!(error.to_string().contains("shipped"), "{error}");
+}
+
+#[test]
+fn test_text_proto_errors() {
+    use proto_conve::text_format::{TextFormatError, TextPosition};
+
+    let error =
+        from_text_proto::<fixtures::Dispatch, Dispatch>("id: 1\nitems { sku: \"A-1\" quantity 2 }")
+            .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Expected `:` or a message, found `2` at line 2, column 29"
+    );
+    assert_eq!(
+        TextFormatError::of(&error).unwrap().position(),
+        TextPosition {
+            line: 2,
+            column: 29
+        }
+    );
+
+    let error = from_text_proto::<fixtures::Dispatch, Dispatch>(
+        "items { sku: \"A-1\" }\nitems {\n  quantity: \"two\"\n}",
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "items[1].quantity: Expected an integer, found a string at line 3, column 13"
+    );
+
+    let error = from_text_proto::<fixtures::Dispatch, Dispatch>("grade: GRADE_GOLD").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "grade: Unknown enum value `GRADE_GOLD` at line 1, column 8"
+    );
+
+    let error = from_text_proto::<fixtures::Dispatch, Dispatch>("shipped { seconds: 1 millis: 2 }")
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "shipped: Unknown field `millis` of `Stamp` at line 1, column 22"
+    );
+}
+
+mod ingest {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Source {
+        pub host: String,
+        pub port: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Event {
+        pub id: u64,
+        pub kind: String,
+        pub tags: Vec<String>,
+        pub payload: Vec<u8>,
+        pub source: Option<Source>,
+        pub received: Option<super::fixtures::Stamp>,
+    }
+}
+
+impl_traits!(arena fixtures::Stamp);
+
+#[derive(Debug, PartialEq, FromProto)]
+#[proto(type = "ingest::Source", arena)]
+pub struct EventSource<'bump> {
+    host: &'bump str,
+    port: u32,
+}
+
+#[derive(Debug, PartialEq, FromProto)]
+#[proto(type = "ingest::Event", arena)]
+pub struct ArenaEvent<'bump> {
+    id: u64,
+    #[proto(rename = "kind")]
+    name: &'bump str,
+    tags: ArenaVec<'bump, &'bump str>,
+    payload: &'bump [u8],
+    source: EventSource<'bump>,
+    received: Option<chrono::DateTime<chrono::Utc>>,
+    #[proto(skip)]
+    retries: u32,
+}
+
+#[test]
+fn test_arena() {
+    let mut bump = Bump::new();
+    let protos: Vec<_> = (0..100)
+        .map(|id| ingest::Event {
+            id,
+            kind: format!("kind-{}", id % 3),
+            tags: vec!["ingest".to_string()],
+            payload: vec![1, 2, 3],
+            source: Some(ingest::Source {
+                host: "collector".to_string(),
+                port: 4317,
+            }),
+            received: None,
+        })
+        .collect();
+    let events = protos.from_proto_in(&bump).unwrap();
+    assert_eq!(events.len(), 100);
+    assert_eq!(events[5].id, 5);
+    assert_eq!(events[5].name, "kind-2");
+    assert_eq!(events[5].tags.as_slice(), ["ingest"]);
+    assert_eq!(events[5].payload, [1, 2, 3]);
+    assert_eq!(
+        events[5].source,
+        EventSource {
+            host: "collector",
+            port: 4317
+        }
+    );
+    assert_eq!(events[5].received, None);
+    assert_eq!(events[5].retries, 0);
+    drop(events);
+    bump.reset();
+
+    let error = vec![ingest::Event::default()]
+        .from_proto_in(&bump)
+        .unwrap_err();
+    assert_eq!(error.to_string(), "[0].source: Required is missing");
+}
+
+mod migrations {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Invoice {
+        pub id: u64,
+        pub created_at_ts: Option<super::Stamp>,
+        pub created_at_millis: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Refund {
+        pub id: u64,
+        pub created_at_ts: Option<super::Stamp>,
+        pub created_at_millis: i64,
+    }
+}
+
+/// The legacy epoch millis
+impl FromProtoAlias<i64> for chrono::DateTime<chrono::Utc> {
+    fn from_proto_alias(millis: i64) -> anyhow::Result<Self> {
+        chrono::DateTime::from_timestamp_millis(millis)
+            .ok_or_else(|| anyhow::anyhow!("The millis {millis} are out of range"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "migrations::Invoice")]
+pub struct Invoice {
+    id: u64,
+    #[proto(first_of("created_at_ts", "created_at_millis"))]
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto)]
+#[proto(type = "migrations::Refund")]
+pub struct Refund {
+    id: u64,
+    #[proto(first_of("created_at_ts", "created_at_millis"), strict)]
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[test]
+fn test_first_of() {
+    let created_at = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+    let stamp = Stamp {
+        seconds: 1_700_000_000,
+        nanos: 0,
+    };
+    let legacy = migrations::Invoice {
+        id: 1,
+        created_at_ts: None,
+        created_at_millis: 1_700_000_000_000,
+    };
+    let current = migrations::Invoice {
+        id: 1,
+        created_at_ts: Some(stamp.clone()),
+        created_at_millis: 0,
+    };
+    let both = migrations::Invoice {
+        id: 1,
+        created_at_ts: Some(stamp.clone()),
+        created_at_millis: 1_600_000_000_000,
+    };
+    let invoice = Invoice { id: 1, created_at };
+    assert_eq!(legacy.clone().from_proto().unwrap(), invoice);
+    assert_eq!(current.clone().from_proto().unwrap(), invoice);
+    // the preferred field is used
+    assert_eq!(both.clone().from_proto().unwrap(), invoice);
+    // only the preferred field is set
+    assert_eq!(invoice.to_proto(), current);
+
+    // under `strict` only one of the fields can be present
+    let refund = |invoice: migrations::Invoice| migrations::Refund {
+        id: invoice.id,
+        created_at_ts: invoice.created_at_ts,
+        created_at_millis: invoice.created_at_millis,
+    };
+    let strict = Refund { id: 1, created_at };
+    assert_eq!(refund(legacy).from_proto().unwrap(), strict);
+    assert_eq!(refund(current).from_proto().unwrap(), strict);
+    let error = refund(both).from_proto().map(|_: Refund| ()).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Both `created_at_ts` and `created_at_millis` are present, only one of them can be set"
+    );
+
+    let error = migrations::Invoice {
+        id: 1,
+        ..Default::default()
+    }
+    .from_proto()
+    .map(|_: Invoice| ())
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "None of `created_at_ts`, `created_at_millis` is present"
+    );
+    assert_eq!(
+        proto_conve::violation::ErrorCode::of(&error),
+        proto_conve::violation::ErrorCode::MissingField
+    );
+
+    // the error of the present alternative is in its field
+    let error = migrations::Invoice {
+        id: 1,
+        created_at_ts: None,
+        created_at_millis: i64::MAX,
+    }
+    .from_proto()
+    .map(|_: Invoice| ())
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "created_at_millis: The millis {} are out of range",
+            i64::MAX
+        )
+    );
+}
+
+mod roles {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Role {
+        pub permissions: Vec<i32>,
+        pub scopes: Vec<i32>,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromProto, ToProto)]
+pub enum Permission {
+    Read = 0,
+    Write = 1,
+    Admin = 4,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromProto, ToProto)]
+#[proto(open)]
+pub enum Scope {
+    Own = 0,
+    Team = 1,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto, FromProtoLossy)]
+#[proto(type = "roles::Role")]
+pub struct Role {
+    permissions: proto_conve::enum_set::EnumSet<Permission>,
+    scopes: proto_conve::enum_set::EnumSet<Scope>,
+}
+
+#[test]
+fn test_enum_set() {
+    use proto_conve::lossy::LossyEvent;
+
+    let proto = roles::Role {
+        permissions: vec![4, 0, 4],
+        scopes: vec![9, 1],
+    };
+    let role = proto.clone().from_proto().unwrap();
+    assert_eq!(
+        role.permissions.iter().collect::<Vec<_>>(),
+        [Permission::Read, Permission::Admin]
+    );
+    assert!(role.scopes.contains(&Scope::Team));
+    assert_eq!(role.scopes.unknown(), [9]);
+
+    // the duplicates collapsed, the values are sorted
+    let back = role.to_proto();
+    assert_eq!(back.permissions, [0, 4]);
+    assert_eq!(back.scopes, [1, 9]);
+    assert_eq!(back.clone().from_proto().unwrap().to_proto(), back);
+
+    let error = roles::Role {
+        permissions: vec![1, 2],
+        ..proto.clone()
+    }
+    .from_proto()
+    .map(|_: Role| ())
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "permissions[1]: Unknown value 2 of {}",
+            std::any::type_name::<Permission>()
+        )
+    );
+
+    let (role, report) = roles::Role {
+        permissions: vec![1, 2, 1],
+        ..proto
+    }
+    .from_proto_lossy()
+    .unwrap();
+    assert_eq!(role.permissions.to_values(), [1]);
+    let events: Vec<_> = report
+        .iter()
+        .map(|(path, event)| (path, event.clone()))
+        .collect();
+    assert_eq!(
+        events,
+        [
+            (
+                "permissions[1]".to_string(),
+                LossyEvent::UnknownEnum { raw: 2 }
+            ),
+            (
+                "permissions[2]".to_string(),
+                LossyEvent::Duplicate { raw: 1 }
+            ),
+        ]
+    );
+}
+
+mod carts {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Quantity {
+        pub value: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct CartLine {
+        pub sku: String,
+        pub quantity: Option<Quantity>,
+        pub limit: Option<Quantity>,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, FromProto, ToProto)]
+#[proto(type = "carts::Quantity", wrapper = "value")]
+pub struct Quantity(proto_conve::Bounded<u32, 1, 999>);
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "carts::CartLine")]
+pub struct CartLine {
+    sku: String,
+    quantity: Quantity,
+    limit: Option<Quantity>,
+}
+
+#[test]
+fn test_wrapper_message() {
+    let quantity = |value| Some(carts::Quantity { value });
+    let proto = carts::CartLine {
+        sku: "tea".to_string(),
+        quantity: quantity(3),
+        limit: None,
+    };
+    let line = proto.clone().from_proto().unwrap();
+    assert_eq!(line.quantity.0.get(), 3);
+    assert_eq!(line.limit, None);
+    assert_eq!(line.to_proto(), proto);
+
+    let limited = carts::CartLine {
+        limit: quantity(10),
+        ..proto.clone()
+    };
+    let line = limited.clone().from_proto().unwrap();
+    assert_eq!(line.limit.map(|limit| limit.0.get()), Some(10));
+    assert_eq!(line.to_proto(), limited);
+
+    // the errors of the inner value have the field of the wrapper in the path
+    let error = carts::CartLine {
+        quantity: quantity(0),
+        ..proto.clone()
+    }
+    .from_proto()
+    .map(|_: CartLine| ())
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "quantity.value: The value 0 is out of range 1..=999"
+    );
+    let error = carts::CartLine {
+        quantity: None,
+        ..proto
+    }
+    .from_proto()
+    .map(|_: CartLine| ())
+    .unwrap_err();
+    assert_eq!(error.to_string(), "quantity: Required is missing");
+}
+
+mod searches {
+    pub mod search_request {
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Deadline {
+            Timeout(super::super::Duration),
+            DeadlineAt(super::super::Timestamp),
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct SearchRequest {
+        pub query: String,
+        pub deadline: Option<search_request::Deadline>,
+        pub retry_deadline: Option<search_request::Deadline>,
+    }
+}
+
+proto_conve::impl_proto_deadline!(searches::search_request::Deadline {
+    relative: Timeout(timeout),
+    absolute: DeadlineAt(deadline_at),
+});
+
+fn default_deadline() -> proto_conve::Deadline {
+    proto_conve::Deadline::After(chrono::Duration::seconds(30))
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto)]
+#[proto(type = "searches::SearchRequest")]
+pub struct SearchRequest {
+    query: String,
+    #[proto(default = "default_deadline")]
+    deadline: proto_conve::Deadline,
+    retry_deadline: Option<proto_conve::Deadline>,
+}
+
+#[test]
+fn test_deadline() {
+    use proto_conve::Deadline;
+    use searches::search_request;
+
+    let timeout = search_request::Deadline::Timeout(Duration {
+        seconds: 2,
+        nanos: 0,
+    });
+    let proto = searches::SearchRequest {
+        query: "tea".to_string(),
+        deadline: Some(timeout),
+        retry_deadline: None,
+    };
+    let search = proto.clone().from_proto().unwrap();
+    assert_eq!(
+        search.deadline,
+        Deadline::After(chrono::Duration::seconds(2))
+    );
+    assert_eq!(search.retry_deadline, None);
+    assert_eq!(search.to_proto(), proto);
+
+    let search = searches::SearchRequest {
+        deadline: None,
+        ..proto.clone()
+    }
+    .from_proto()
+    .unwrap();
+    assert_eq!(search.deadline, default_deadline());
+
+    let error = searches::SearchRequest {
+        retry_deadline: Some(search_request::Deadline::Timeout(Duration {
+            seconds: -1,
+            nanos: 0,
+        })),
+        ..proto
+    }
+    .from_proto()
+    .map(|_: SearchRequest| ())
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "retry_deadline.timeout: The timeout -1 s is negative"
+    );
+}
+
+mod preferences {
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Preferences {
+        pub defaults: HashMap<String, prost_types::Value>,
+        pub overrides: HashMap<String, prost_types::Value>,
+    }
+}
+
+#[derive(Debug, PartialEq, FromProto, ToProto)]
+#[proto(type = "preferences::Preferences")]
+pub struct Preferences {
+    defaults: std::collections::HashMap<String, serde_json::Value>,
+    #[proto(nullable_values)]
+    overrides: std::collections::HashMap<String, Option<serde_json::Value>>,
+}
+
+#[test]
+fn test_nullable_values() {
+    use prost_types::value::Kind;
+
+    let values = std::collections::HashMap::from([
+        (
+            "theme".to_owned(),
+            prost_types::Value {
+                kind: Some(Kind::StringValue("dark".to_owned())),
+            },
+        ),
+        (
+            "locale".to_owned(),
+            prost_types::Value {
+                kind: Some(Kind::NullValue(0)),
+            },
+        ),
+    ]);
+    let proto = preferences::Preferences {
+        defaults: values.clone(),
+        overrides: values,
+    };
+    let preferences = proto.clone().from_proto().unwrap();
+    assert_eq!(preferences.defaults["theme"], serde_json::json!("dark"));
+    assert_eq!(preferences.defaults["locale"], serde_json::Value::Null);
+    assert_eq!(
+        preferences.overrides["locale"],
+        Some(serde_json::Value::Null)
+    );
+    assert!(!preferences.overrides.contains_key("timezone"));
+    assert_eq!(preferences.to_proto(), proto);
+
+    // the empty value is not the explicit null
+    let mut proto = proto;
+    proto
+        .overrides
+        .insert("timezone".to_owned(), prost_types::Value { kind: None });
+    let preferences = proto.clone().from_proto().unwrap();
+    assert_eq!(preferences.overrides["timezone"], None);
+    assert_eq!(preferences.to_proto(), proto);
+}
+
+#[test]
+fn test_verify_all() {
+    // the derived conversions of these tests, with `rename`, `path`, `first_of` and the oneofs, are
+    // verified against their proto types
+    proto_conve::verify_all!();
+}
+
+mod ledgers {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct LedgerEntry {
+        pub amount: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct LedgerAudit {
+        pub note: String,
+    }
+}
+
+#[derive(Debug, PartialEq, FromProto, ToProto)]
+#[proto(type = "ledgers::LedgerEntry")]
+pub struct LedgerEntry {
+    amount: i64,
+}
+
+#[derive(Debug, PartialEq, FromProto)]
+#[proto(type = "ledgers::LedgerAudit")]
+pub struct LedgerAudit {
+    note: String,
+}
+
+#[test]
+fn test_conversion_coverage() {
+    let report = proto_conve::coverage::conversion_coverage(&[
+        "acme.ledger.v1.LedgerEntry",
+        "acme.ledger.v1.LedgerAudit",
+        "acme.ledger.v1.LedgerClosing",
+    ]);
+    assert_eq!(report.unconverted, ["acme.ledger.v1.LedgerClosing"]);
+    assert_eq!(report.without_to_proto, ["acme.ledger.v1.LedgerAudit"]);
+    assert!(report.without_from_proto.is_empty());
+}
+
+mod gauges {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Gauge {
+        pub offset: i32,
+        pub sequence: u64,
+        pub checksum: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Offset {
+        pub offset: i32,
+    }
+}
+
+#[derive(Debug, PartialEq, FromProto, ToProto)]
+#[proto(type = "gauges::Gauge")]
+pub struct Gauge {
+    #[proto(wire = "sint32")]
+    offset: i16,
+    #[proto(wire = "fixed64")]
+    sequence: u64,
+    checksum: proto_conve::wire::Sfixed64,
+}
+
+/// The domain type expecting the unsigned offsets of the `sint32`
+#[derive(Debug, PartialEq, FromProto)]
+#[proto(type = "gauges::Offset")]
+pub struct UnsignedOffset {
+    #[proto(wire = "sint32")]
+    offset: u32,
+}
+
+#[test]
+fn test_wire() {
+    let proto = gauges::Gauge {
+        offset: -120,
+        sequence: u64::MAX,
+        checksum: i64::MIN,
+    };
+    let gauge = proto.clone().from_proto().unwrap();
+    assert_eq!(gauge.offset, -120);
+    assert_eq!(gauge.sequence, u64::MAX);
+    assert_eq!(gauge.checksum.get(), i64::MIN);
+    assert_eq!(gauge.to_proto(), proto);
+
+    let error = gauges::Offset { offset: -120 }.from_proto().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "offset: The sint32 value -120 is out of the range of u32"
+    );
+
+    let mut proto = proto;
+    proto.offset = 40_000;
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "offset: The sint32 value 40000 is out of the range of i16"
+    );
+}
+
+mod deployments {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Annotation {
+        pub key: String,
+        pub value: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Deployment {
+        pub annotations: Vec<Annotation>,
+    }
+}
+
+proto_conve::impl_proto_pairs!(deployments::Annotation { key, value } => String);
+
+#[derive(Debug, PartialEq, FromProto, ToProto)]
+#[proto(type = "deployments::Deployment")]
+pub struct Deployment {
+    annotations: std::collections::HashMap<String, String>,
+}
+
+#[test]
+fn test_pairs() {
+    let annotation = |key: &str, value: &str| deployments::Annotation {
+        key: key.to_owned(),
+        value: value.to_owned(),
+    };
+    let deployment = Deployment {
+        annotations: [("owner", "search"), ("commit", "4f2a")]
+            .into_iter()
+            .map(|(key, value)| (key.to_owned(), value.to_owned()))
+            .collect(),
+    };
+    let proto = deployment.to_proto();
+    assert_eq!(
+        proto.annotations,
+        [annotation("commit", "4f2a"), annotation("owner", "search")]
+    );
+    assert_eq!(proto.from_proto().unwrap(), deployment);
+
+    let proto = deployments::Deployment {
+        annotations: vec![annotation("owner", "search"), annotation("owner", "ads")],
+    };
+    assert_eq!(
+        proto.from_proto().unwrap_err().to_string(),
+        "annotations: The key `owner` is duplicated"
+    );
+}
+
+mod tenants {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Settings {
+        pub currency: Option<String>,
+        pub locale: Option<String>,
+        pub page_size: Option<u32>,
+    }
+}
+
+fn default_page_size() -> u32 {
+    25
+}
+
+#[derive(Debug, PartialEq, FromProto)]
+#[proto(type = "tenants::Settings")]
+pub struct TenantSettings {
+    #[proto(default_from_ctx)]
+    currency: String,
+    #[proto(default_from_ctx)]
+    locale: String,
+    #[proto(default_from_ctx, default = "default_page_size")]
+    page_size: u32,
+}
+
+#[test]
+fn test_default_from_ctx() {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use proto_conve::defaults::{self, DefaultValue};
+
+    let tenant = |defaults: &[(&str, DefaultValue)]| {
+        let defaults: HashMap<String, DefaultValue> = defaults
+            .iter()
+            .map(|(path, value)| (path.to_string(), value.clone()))
+            .collect();
+        Arc::new(defaults)
+    };
+    let proto = tenants::Settings {
+        locale: Some("de-AT".to_owned()),
+        ..Default::default()
+    };
+    // the provider has the currency, the page size is the static default
+    let eur = tenant(&[("currency", DefaultValue::Text("EUR".to_owned()))]);
+    let settings = defaults::scope(eur.clone(), || proto.clone().from_proto()).unwrap();
+    assert_eq!(
+        settings,
+        TenantSettings {
+            currency: "EUR".to_owned(),
+            locale: "de-AT".to_owned(),
+            page_size: 25,
+        }
+    );
+    // the default of the context is before the static one, the present value before both
+    let paged = tenant(&[
+        ("currency", DefaultValue::Text("EUR".to_owned())),
+        ("page_size", DefaultValue::Uint(100)),
+        ("locale", DefaultValue::Text("en-GB".to_owned())),
+    ]);
+    let settings = defaults::scope(paged, || proto.clone().from_proto()).unwrap();
+    assert_eq!(settings.page_size, 100);
+    assert_eq!(settings.locale, "de-AT");
+
+    // the provider has no locale, the field is required
+    let error = defaults::scope(eur, || tenants::Settings::default().from_proto()).unwrap_err();
+    assert_eq!(error.to_string(), "locale: Required is missing");
+    let error = proto.from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "currency: Required is missing");
+}
+
+mod tickets {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Ticket {
+        pub title: String,
+        pub tags: Vec<String>,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromProto, ToProto)]
+#[proto(string, case_insensitive)]
+pub enum Topic {
+    Billing,
+    #[proto(alias = "ops")]
+    Operations,
+    #[proto(name = "SEC")]
+    Security,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ToProto, FromProtoLossy)]
+#[proto(type = "tickets::Ticket")]
+pub struct Ticket {
+    title: String,
+    tags: proto_conve::tagged_set::TaggedSet<Topic>,
+}
+
+#[test]
+fn test_tagged_set() {
+    use proto_conve::lossy::LossyEvent;
+
+    let proto = tickets::Ticket {
+        title: "Refund failed".to_owned(),
+        tags: [
+            "p1",
+            "ops",
+            "billing",
+            "team-payments",
+            "SEC",
+            "Operations",
+            "p1",
+        ]
+        .map(str::to_owned)
+        .to_vec(),
+    };
+    let ticket = proto.clone().from_proto().unwrap();
+    assert!(ticket.tags.contains(&Topic::Operations));
+    assert!(ticket.tags.contains_tag("OPS"));
+    assert!(ticket.tags.contains_tag("sec"));
+    assert_eq!(ticket.tags.free_form(), ["p1", "team-payments"]);
+    assert_eq!(
+        ticket.to_proto().tags,
+        ["BILLING", "OPERATIONS", "SEC", "p1", "team-payments"]
+    );
+    assert_eq!(ticket.to_proto().from_proto().unwrap(), ticket);
+
+    let (lossy, report) = proto.from_proto_lossy().unwrap();
+    assert_eq!(lossy, ticket);
+    let events: Vec<_> = report
+        .iter()
+        .map(|(path, event)| (path, event.clone()))
+        .collect();
+    assert_eq!(
+        events,
+        [
+            (
+                "tags[5]".to_string(),
+                LossyEvent::DuplicateTag {
+                    tag: "Operations".to_owned()
+                }
+            ),
+            (
+                "tags[6]".to_string(),
+                LossyEvent::DuplicateTag {
+                    tag: "p1".to_owned()
+                }
+            ),
+        ]
+    );
+}
+
+mod admissions {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Resources {
+        pub cpu_millis: i64,
+        pub memory_mb: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Container {
+        pub name: String,
+        pub resources: Option<Resources>,
+        pub port: Option<i64>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Pod {
+        pub namespace: String,
+        pub replicas: i64,
+        pub containers: Vec<Container>,
+        pub priority: Option<i64>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ValidateFromProto)]
+#[proto(type = "admissions::Resources")]
+pub struct PodResources {
+    cpu_millis: proto_conve::Bounded<u32, 1, 64_000>,
+    memory_mb: proto_conve::Bounded<u32, 1, 1_048_576>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ValidateFromProto)]
+#[proto(type = "admissions::Container")]
+pub struct PodContainer {
+    #[proto(validate = "proto_conve::validate::non_empty_string")]
+    name: String,
+    resources: PodResources,
+    port: Option<proto_conve::Bounded<u16, 1, 65_535>>,
+}
+
+fn has_containers(pod: &Pod) -> anyhow::Result<()> {
+    anyhow::ensure!(!pod.containers.is_empty(), "The pod has no containers");
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, FromProto, ValidateFromProto)]
+#[proto(type = "admissions::Pod", validate = "has_containers")]
+pub struct Pod {
+    namespace: String,
+    replicas: proto_conve::Bounded<u32, 0, 100>,
+    containers: Vec<PodContainer>,
+    #[proto(default = "default_pod_priority")]
+    priority: proto_conve::Bounded<u8, 0, 10>,
+}
+
+fn default_pod_priority() -> proto_conve::Bounded<u8, 0, 10> {
+    proto_conve::Bounded::new(5).unwrap()
+}
+
+fn pod_container(name: &str, port: Option<i64>) -> admissions::Container {
+    admissions::Container {
+        name: name.to_string(),
+        resources: Some(admissions::Resources {
+            cpu_millis: 500,
+            memory_mb: 256,
+        }),
+        port,
+    }
+}
+
+#[test]
+fn test_validate_from_proto() {
+    use proto_conve::violation::{ErrorCode, FieldViolation};
+
+    let mut missing = pod_container("sidecar", None);
+    missing.resources = None;
+    let proto = admissions::Pod {
+        namespace: "default".to_string(),
+        replicas: 500,
+        containers: vec![pod_container(" ", Some(8080)), missing],
+        priority: None,
+    };
+    let violations = proto.validate_proto();
+    let found: Vec<_> = violations
+        .iter()
+        .map(|violation| (violation.path.as_str(), violation.code))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("replicas", ErrorCode::OutOfRange),
+            ("containers[0].name", ErrorCode::Invalid),
+            ("containers[1].resources", ErrorCode::MissingField),
+        ]
+    );
+    assert_eq!(violations[1].message, "The value is empty");
+    // the conversion stops at the first one
+    let error = proto.clone().from_proto().unwrap_err();
+    assert_eq!(FieldViolation::of(&error), violations[0]);
+
+    let proto = admissions::Pod {
+        replicas: 3,
+        containers: vec![pod_container("web", Some(8080))],
+        ..proto
+    };
+    assert_eq!(proto.validate_proto(), []);
+    assert!(proto.from_proto().is_ok());
+
+    // the check of the struct, when the fields are valid
+    let violations = admissions::Pod::default().validate_proto();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(
+        violations[0].message,
+        "Invalid Pod: The pod has no containers"
+    );
+}
+
+#[test]
+fn test_validate_consistent() {
+    use proto_conve::__private::proptest::{self, prelude::*};
+
+    let resources = (-10i64..70_000, -10i64..2_000_000).prop_map(|(cpu_millis, memory_mb)| {
+        admissions::Resources {
+            cpu_millis,
+            memory_mb,
+        }
+    });
+    let container = (
+        "[a-z ]{0,4}",
+        proptest::option::weighted(0.9, resources),
+        proptest::option::of(-10i64..70_000),
+    )
+        .prop_map(|(name, resources, port)| admissions::Container {
+            name,
+            resources,
+            port,
+        });
+    let pod = (
+        "[a-z]{0,8}",
+        -5i64..110,
+        proptest::collection::vec(container, 0..4),
+        proptest::option::of(-2i64..12),
+    )
+        .prop_map(
+            |(namespace, replicas, containers, priority)| admissions::Pod {
+                namespace,
+                replicas,
+                containers,
+                priority,
+            },
+        );
+    proptest!(|(pod in pod)| {
+        assert_validate_consistent(pod);
+    });
+}
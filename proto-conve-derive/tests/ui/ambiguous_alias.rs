@@ -0,0 +1,14 @@
+use proto_conve::{impl_traits, FromProto};
+
+impl_traits!(prelude);
+
+#[derive(FromProto)]
+#[proto(string, case_insensitive)]
+pub enum Power {
+    #[proto(alias = "enabled")]
+    On,
+    #[proto(alias = "Enabled")]
+    Off,
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+use proto_conve::{impl_traits, FromProto};
+
+mod proto {
+    pub struct Order {
+        pub customer: u64,
+    }
+}
+
+impl_traits!(prelude);
+
+mod customers {
+    pub async fn from_proto(id: u64, _: &()) -> anyhow::Result<String> {
+        Ok(id.to_string())
+    }
+}
+
+#[derive(FromProto)]
+#[proto(type = "proto::Order")]
+pub struct Order {
+    #[proto(async_with = "customers")]
+    customer: String,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use proto_conve::{impl_traits, FromProto};
+
+mod proto {
+    pub struct Tree {
+        pub left: Option<Box<Tree>>,
+    }
+}
+
+impl_traits!(prelude);
+
+#[derive(FromProto)]
+#[proto(type = "proto::Tree")]
+pub struct Tree {
+    left: Box<Option<Tree>>,
+}
+
+fn main() {}
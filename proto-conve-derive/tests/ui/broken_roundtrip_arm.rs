@@ -0,0 +1,40 @@
+use proto_conve::impl_traits;
+
+impl_traits!(prelude);
+
+pub struct ProtoSpan {
+    pub seconds: i64,
+    pub nanos: i32,
+}
+
+pub struct ProtoSpanMillis {
+    pub millis: i64,
+}
+
+// the arm converts back in to another proto type than it converts from
+macro_rules! span_arm {
+    ($type: ident) => {
+        impl FromProto for $type {
+            type Result = chrono::Duration;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                Ok(chrono::Duration::seconds(self.seconds)
+                    + chrono::Duration::nanoseconds(self.nanos as i64))
+            }
+        }
+
+        impl ToProtoAlias<ProtoSpanMillis> for chrono::Duration {
+            fn to_proto_alias(&self) -> ProtoSpanMillis {
+                ProtoSpanMillis {
+                    millis: self.num_milliseconds(),
+                }
+            }
+        }
+
+        impl_traits!(@inverse $type, chrono::Duration);
+    };
+}
+
+span_arm!(ProtoSpan);
+
+fn main() {}
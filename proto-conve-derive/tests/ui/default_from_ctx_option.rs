@@ -0,0 +1,19 @@
+use proto_conve::{impl_traits, FromProto};
+
+mod proto {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Settings {
+        pub currency: Option<String>,
+    }
+}
+
+impl_traits!(prelude);
+
+#[derive(FromProto)]
+#[proto(type = "proto::Settings")]
+struct Settings {
+    #[proto(default_from_ctx)]
+    currency: Option<String>,
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use proto_conve::{impl_traits, FromProto};
+
+mod proto {
+    pub struct Item {
+        pub name: Option<String>,
+    }
+}
+
+impl_traits!(prelude);
+
+#[derive(FromProto)]
+#[proto(type = "proto::Item")]
+pub struct Item {
+    #[proto(default)]
+    name: Option<String>,
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+#![deny(deprecated)]
+
+use proto_conve::impl_traits;
+
+impl_traits!(prelude);
+
+pub struct Tag(String);
+
+impl ToProtoAlias<String> for Tag {
+    fn to_proto_alias(&self) -> String {
+        self.0.to_lowercase()
+    }
+}
+
+// the calls of the old names of `ToProtoAlias` warn with their replacements
+fn main() {
+    let tag = Tag("Admin".to_owned());
+    let _: String = tag.to_proto();
+    let _: Option<String> = tag.to_proto_option();
+}
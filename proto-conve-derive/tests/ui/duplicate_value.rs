@@ -0,0 +1,15 @@
+use proto_conve::{impl_traits, FromProto};
+
+impl_traits!(prelude);
+
+#[derive(FromProto)]
+pub enum Status {
+    #[proto(value = 1)]
+    Active,
+    #[proto(value = 2)]
+    Suspended,
+    #[proto(value = 1)]
+    Enabled,
+}
+
+fn main() {}
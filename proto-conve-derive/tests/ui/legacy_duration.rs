@@ -0,0 +1,13 @@
+#![deny(deprecated)]
+
+use proto_conve::impl_traits;
+
+pub struct Duration {
+    pub seconds: i64,
+    pub nanos: i32,
+}
+
+impl_traits!(prelude);
+impl_traits!(Duration, chrono::Duration, legacy);
+
+fn main() {}
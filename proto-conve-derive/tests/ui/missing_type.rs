@@ -0,0 +1,10 @@
+use proto_conve::{impl_traits, ToProto};
+
+impl_traits!(prelude);
+
+#[derive(ToProto)]
+pub struct Item {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use proto_conve::{impl_traits, ToProto};
+
+mod proto {
+    #[derive(Default)]
+    pub struct Upload {
+        pub name: String,
+        pub payload: Vec<u8>,
+    }
+}
+
+impl_traits!(prelude);
+
+#[derive(ToProto)]
+#[proto(type = "proto::Upload")]
+pub struct Upload {
+    #[proto(clone_cheap)]
+    name: String,
+    #[proto(move_only)]
+    payload: Vec<u8>,
+}
+
+fn main() {}
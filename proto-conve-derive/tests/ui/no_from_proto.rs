@@ -0,0 +1,27 @@
+use proto_conve::{impl_traits, FromProto};
+
+mod proto {
+    pub struct Price {
+        pub cents: u64,
+    }
+
+    pub struct Item {
+        pub name: String,
+        pub price: Option<Price>,
+    }
+}
+
+impl_traits!(prelude);
+
+pub struct Price {
+    cents: u64,
+}
+
+#[derive(FromProto)]
+#[proto(type = "proto::Item")]
+pub struct Item {
+    name: String,
+    price: Price,
+}
+
+fn main() {}
@@ -0,0 +1,39 @@
+use proto_conve::{impl_traits, FromProto};
+
+mod proto {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Delivered {}
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum State {
+        Delivered(Delivered),
+        Returned(Delivered),
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Handover {
+        pub state: Option<State>,
+        pub delivered_at: Option<i64>,
+        pub returned_at: Option<i64>,
+    }
+}
+
+impl_traits!(prelude);
+
+#[derive(FromProto)]
+#[proto(oneof = "proto::State")]
+enum State {
+    #[proto(sibling = "delivered_at")]
+    Delivered(i64),
+    #[proto(sibling = "returned_at")]
+    Returned(i64),
+}
+
+#[derive(FromProto)]
+#[proto(type = "proto::Handover")]
+struct Handover {
+    #[proto(siblings("returned_at", "delivered_at"))]
+    state: State,
+}
+
+fn main() {}
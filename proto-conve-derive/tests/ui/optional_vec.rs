@@ -0,0 +1,17 @@
+use proto_conve::{impl_traits, FromProto};
+
+mod proto {
+    pub struct Item {
+        pub tags: Vec<String>,
+    }
+}
+
+impl_traits!(prelude);
+
+#[derive(FromProto)]
+#[proto(type = "proto::Item")]
+pub struct Item {
+    tags: Option<Vec<String>>,
+}
+
+fn main() {}
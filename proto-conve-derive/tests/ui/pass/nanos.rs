@@ -0,0 +1,29 @@
+use proto_conve::impl_traits;
+
+pub struct Timestamp {
+    pub seconds: i64,
+    pub nanos: i32,
+}
+
+pub struct Duration {
+    pub seconds: i64,
+    pub nanos: i32,
+}
+
+pub struct UnsignedTimestamp {
+    pub seconds: i64,
+    pub nanos: u32,
+}
+
+pub struct UnsignedDuration {
+    pub seconds: i64,
+    pub nanos: u32,
+}
+
+impl_traits!(prelude);
+impl_traits!(Timestamp, chrono::DateTime<chrono::Utc>, signed_nanos);
+impl_traits!(Duration, chrono::Duration, signed_nanos);
+impl_traits!(UnsignedTimestamp, chrono::DateTime<chrono::Utc>, unsigned_nanos);
+impl_traits!(UnsignedDuration, chrono::Duration, unsigned_nanos);
+
+fn main() {}
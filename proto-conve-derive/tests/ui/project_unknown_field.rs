@@ -0,0 +1,24 @@
+use proto_conve::{impl_traits, FromProto};
+
+impl_traits!(prelude);
+
+mod proto {
+    pub struct Order {
+        pub id: u64,
+        pub note: String,
+    }
+}
+
+#[derive(FromProto)]
+#[proto(type = "proto::Order")]
+struct Order {
+    id: u64,
+}
+
+#[derive(FromProto)]
+#[proto(project_from = "proto::Order", full = "Order")]
+struct OrderNote {
+    note: String,
+}
+
+fn main() {}
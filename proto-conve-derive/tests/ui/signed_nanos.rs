@@ -0,0 +1,11 @@
+use proto_conve::impl_traits;
+
+pub struct Timestamp {
+    pub seconds: i64,
+    pub nanos: u32,
+}
+
+impl_traits!(prelude);
+impl_traits!(Timestamp, chrono::DateTime<chrono::Utc>, signed_nanos);
+
+fn main() {}
@@ -0,0 +1,18 @@
+use proto_conve::{impl_traits, FromProto};
+
+mod proto {
+    pub struct Number {
+        pub value: u32,
+    }
+}
+
+impl_traits!(prelude);
+
+#[derive(FromProto)]
+#[proto(type = "proto::Number")]
+pub union Number {
+    value: u32,
+    bits: f32,
+}
+
+fn main() {}
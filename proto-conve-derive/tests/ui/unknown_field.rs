@@ -0,0 +1,21 @@
+use proto_conve::{impl_traits, FromProto};
+
+mod proto {
+    pub struct Customer {
+        pub name: String,
+        pub email: String,
+        // added to the proto file, not mapped yet
+        pub phone: String,
+    }
+}
+
+impl_traits!(prelude);
+
+#[derive(FromProto)]
+#[proto(type = "proto::Customer", deny_unknown_fields)]
+pub struct Customer {
+    name: String,
+    email: String,
+}
+
+fn main() {}
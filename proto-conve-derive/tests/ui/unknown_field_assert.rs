@@ -0,0 +1,14 @@
+use proto_conve::assert_proto_fields;
+
+mod proto {
+    pub struct Order {
+        pub id: u64,
+        pub items: Vec<String>,
+        // added to the proto file, not mapped yet
+        pub discount: u32,
+    }
+}
+
+assert_proto_fields!(proto::Order { id, items });
+
+fn main() {}
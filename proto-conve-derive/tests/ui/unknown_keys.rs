@@ -0,0 +1,25 @@
+use proto_conve::{impl_traits, FromProto};
+
+mod proto {
+    use std::collections::HashMap;
+
+    #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Plan {
+        Basic = 1,
+    }
+
+    pub struct Seats {
+        pub seats: HashMap<i32, u32>,
+    }
+}
+
+impl_traits!(prelude);
+
+#[derive(FromProto)]
+#[proto(type = "proto::Seats")]
+pub struct Seats {
+    #[proto(key_enum = "proto::Plan", unknown_keys = "open")]
+    seats: std::collections::HashMap<proto::Plan, u32>,
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use proto_conve::{impl_traits, FromProto};
+
+mod proto {
+    pub struct Customer {
+        pub name: String,
+        pub email: String,
+    }
+}
+
+impl_traits!(prelude);
+
+#[derive(FromProto)]
+#[proto(type = "proto::Customer", deny_unknown_fields, unmapped = "email")]
+pub struct Customer {
+    name: String,
+    email: String,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use proto_conve::impl_traits;
+
+pub struct Duration {
+    pub seconds: i64,
+    pub nanos: i32,
+}
+
+impl_traits!(prelude);
+impl_traits!(Duration, chrono::Duration, unsigned_nanos);
+
+fn main() {}
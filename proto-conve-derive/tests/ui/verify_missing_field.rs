@@ -0,0 +1,30 @@
+use proto_conve::{impl_traits, FromProto};
+
+impl_traits!(prelude);
+
+mod proto {
+    #[derive(Default)]
+    pub struct Profile {
+        pub bio: String,
+    }
+
+    // `display_name` was renamed to `nickname` in the `.proto` file
+    #[derive(Default)]
+    pub struct User {
+        pub id: u64,
+        pub nickname: String,
+        pub profile: Option<Profile>,
+    }
+}
+
+#[derive(FromProto)]
+#[proto(type = "proto::User")]
+struct User {
+    id: u64,
+    #[proto(rename = "display_name")]
+    name: String,
+    #[proto(path = "profile.bio")]
+    bio: String,
+}
+
+fn main() {}
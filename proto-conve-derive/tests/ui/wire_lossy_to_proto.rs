@@ -0,0 +1,20 @@
+use proto_conve::{impl_traits, ToProto};
+
+mod proto {
+    #[derive(Default)]
+    pub struct Reading {
+        pub offset: i32,
+    }
+}
+
+impl_traits!(prelude);
+
+/// The large `u32` offsets would be written negative in to the `sint32`
+#[derive(ToProto)]
+#[proto(type = "proto::Reading")]
+pub struct Reading {
+    #[proto(wire = "sint32")]
+    offset: u32,
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+use proto_conve::{impl_traits, FromProto};
+
+mod proto {
+    pub struct Item {
+        pub name: String,
+    }
+}
+
+mod upper {
+    pub fn from_proto(value: String) -> String {
+        value.to_uppercase()
+    }
+}
+
+impl_traits!(prelude);
+
+#[derive(FromProto)]
+#[proto(type = "proto::Item")]
+pub struct Item {
+    #[proto(with = "upper")]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+// the scale of zero fails to build, not to divide by zero in the conversion
+type Broken = proto_conve::Scaled<i32, 1, 0>;
+
+fn main() {
+    let _ = Broken::from_wire(1).to_wire();
+}
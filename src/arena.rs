@@ -0,0 +1,264 @@
+//! The conversions in to the values allocated in a `bumpalo` arena, for the batches of the short
+//! lived values, enabled with the `arena` feature. `FromProtoIn` of the prelude converts the proto
+//! strings in to the `&'bump str` copied in to the arena, the `bytes` in to `&'bump [u8]` and the
+//! repeated fields in to `ArenaVec<'bump, T>`, which is `bumpalo::collections::Vec`. The scalars are
+//! converted as they are.
+//! ```ignore
+//! #[derive(FromProto)]
+//! #[proto(type = "proto::Event", arena)]
+//! struct Event<'bump> {
+//!     id: u64,
+//!     name: &'bump str,
+//!     tags: ArenaVec<'bump, &'bump str>,
+//! }
+//!
+//! let mut bump = Bump::new();
+//! let events = protos.from_proto_in(&bump)?;
+//! // serialize the events
+//! drop(events);
+//! bump.reset();
+//! ```
+//! The converted values borrow the arena, they are not freed one by one, the memory of all of them is
+//! released at once by `Bump::reset` or by dropping the arena. The structs with `#[proto(arena)]`
+//! derive `FromProtoIn` instead of `FromProto`, they have only the lifetime of the arena, and all of
+//! their fields convert with `FromProtoIn`. The other types, like the timestamps, which convert with
+//! `FromProto` without the arena, implement it with `impl_traits!(arena Timestamp)`.
+
+pub use bumpalo::Bump;
+
+/// The vector allocated in the arena
+pub type ArenaVec<'bump, T> = bumpalo::collections::Vec<'bump, T>;
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_arena {
+    (prelude) => {
+        pub use super::FromProtoIn;
+        pub use $crate::arena::{ArenaVec, Bump};
+    };
+    () => {
+        /// The conversion from the proto value in to the value allocated in the arena, see
+        /// `proto_conve::arena`
+        pub trait FromProtoIn<'bump> {
+            type Result: 'bump;
+
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_in(self, bump: &'bump $crate::arena::Bump) -> anyhow::Result<Self::Result>;
+        }
+
+        /// The field of the derived `FromProtoIn`, the markers are the ones of `FromProtoField`
+        pub trait FromProtoFieldIn<'bump, T, M> {
+            /// Convert the field, errors get the field name in their path
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_field_in(
+                self,
+                bump: &'bump $crate::arena::Bump,
+                name: &'static str,
+            ) -> anyhow::Result<T>;
+        }
+
+        impl<'bump, P: FromProtoIn<'bump>>
+            FromProtoFieldIn<'bump, P::Result, $crate::__private::field::Plain> for P
+        {
+            fn from_proto_field_in(
+                self,
+                bump: &'bump $crate::arena::Bump,
+                name: &'static str,
+            ) -> anyhow::Result<P::Result> {
+                self.from_proto_in(bump)
+                    .map_err(|error| $crate::error::in_field(error, name))
+            }
+        }
+
+        impl<'bump, P: FromProtoIn<'bump>>
+            FromProtoFieldIn<'bump, P::Result, $crate::__private::field::Required> for Option<P>
+        {
+            fn from_proto_field_in(
+                self,
+                bump: &'bump $crate::arena::Bump,
+                name: &'static str,
+            ) -> anyhow::Result<P::Result> {
+                self.required_lite(name)?
+                    .from_proto_in(bump)
+                    .map_err(|error| $crate::error::in_field(error, name))
+            }
+        }
+
+        $crate::__impl_arena!(@scalars i32, i64, u32, u64, f32, f64, bool);
+
+        /// The string is copied in to the arena
+        impl<'bump> FromProtoIn<'bump> for String {
+            type Result = &'bump str;
+
+            fn from_proto_in(self, bump: &'bump $crate::arena::Bump) -> anyhow::Result<Self::Result> {
+                Ok(bump.alloc_str(&self))
+            }
+        }
+
+        /// The proto `bytes` are copied in to the arena
+        impl<'bump> FromProtoIn<'bump> for Vec<u8> {
+            type Result = &'bump [u8];
+
+            fn from_proto_in(self, bump: &'bump $crate::arena::Bump) -> anyhow::Result<Self::Result> {
+                Ok(bump.alloc_slice_copy(&self))
+            }
+        }
+
+        impl<'bump, P: FromProtoIn<'bump>> FromProtoIn<'bump> for Vec<P> {
+            type Result = $crate::arena::ArenaVec<'bump, P::Result>;
+
+            fn from_proto_in(self, bump: &'bump $crate::arena::Bump) -> anyhow::Result<Self::Result> {
+                let mut result = $crate::arena::ArenaVec::with_capacity_in(self.len(), bump);
+                for (index, proto) in self.into_iter().enumerate() {
+                    let value = proto
+                        .from_proto_in(bump)
+                        .map_err(|error| $crate::error::at_index(error, index))?;
+                    result.push(value);
+                }
+                Ok(result)
+            }
+        }
+
+        impl<'bump, P: FromProtoIn<'bump>> FromProtoIn<'bump> for Option<P> {
+            type Result = Option<P::Result>;
+
+            fn from_proto_in(self, bump: &'bump $crate::arena::Bump) -> anyhow::Result<Self::Result> {
+                self.map(|proto| proto.from_proto_in(bump)).transpose()
+            }
+        }
+    };
+    (@scalars $($type: ty),*) => {
+        $(
+            impl<'bump> FromProtoIn<'bump> for $type {
+                type Result = $type;
+
+                fn from_proto_in(self, _: &'bump $crate::arena::Bump) -> anyhow::Result<Self::Result> {
+                    Ok(self)
+                }
+            }
+        )*
+    };
+    (@via $($type: ty),+) => {
+        $(
+            /// Converted with `FromProto`, without the arena
+            impl<'bump> FromProtoIn<'bump> for $type {
+                type Result = <$type as FromProto>::Result;
+
+                fn from_proto_in(self, _: &'bump $crate::arena::Bump) -> anyhow::Result<Self::Result> {
+                    self.from_proto()
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    use super::{ArenaVec, Bump};
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    /// The global allocator counting the frees of the test thread
+    struct CountingAllocator;
+
+    thread_local! {
+        static FREES: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            let _ = FREES.try_with(|frees| frees.set(frees.get() + 1));
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn frees() -> usize {
+        FREES.with(Cell::get)
+    }
+
+    #[derive(Debug, Clone, Default)]
+    pub struct ProtoEvent {
+        pub id: u64,
+        pub name: String,
+        pub tags: Vec<String>,
+        pub payload: Vec<u8>,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Event<'bump> {
+        id: u64,
+        name: &'bump str,
+        tags: ArenaVec<'bump, &'bump str>,
+        payload: &'bump [u8],
+    }
+
+    impl<'bump> FromProtoIn<'bump> for ProtoEvent {
+        type Result = Event<'bump>;
+
+        fn from_proto_in(self, bump: &'bump Bump) -> anyhow::Result<Self::Result> {
+            Ok(Event {
+                id: self.id.from_proto_field_in(bump, "id")?,
+                name: self.name.from_proto_field_in(bump, "name")?,
+                tags: self.tags.from_proto_field_in(bump, "tags")?,
+                payload: self.payload.from_proto_field_in(bump, "payload")?,
+            })
+        }
+    }
+
+    fn batch(size: u64) -> Vec<ProtoEvent> {
+        (0..size)
+            .map(|id| ProtoEvent {
+                id,
+                name: format!("event-{id}"),
+                tags: vec!["ingest".to_owned(), format!("shard-{}", id % 4)],
+                payload: vec![id as u8; 3],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_batch() {
+        let mut bump = Bump::new();
+        let events = batch(1000).from_proto_in(&bump).unwrap();
+        assert_eq!(events.len(), 1000);
+        assert_eq!(events[7].id, 7);
+        assert_eq!(events[7].name, "event-7");
+        assert_eq!(events[7].tags.as_slice(), ["ingest", "shard-3"]);
+        assert_eq!(events[7].payload, [7, 7, 7]);
+        assert!(bump.allocated_bytes() > 0);
+
+        // the values in the arena are not freed one by one
+        let before = frees();
+        drop(events);
+        assert_eq!(frees(), before);
+
+        // the arena is reused for the next batch
+        bump.reset();
+        let events = batch(10).from_proto_in(&bump).unwrap();
+        assert_eq!(events[9].name, "event-9");
+    }
+
+    #[test]
+    fn test_required() {
+        let bump = Bump::new();
+        let missing: Option<ProtoEvent> = None;
+        let error =
+            FromProtoFieldIn::<Event, _>::from_proto_field_in(missing, &bump, "event").unwrap_err();
+        assert_eq!(error.to_string(), "event: Required is missing");
+        let present = Some(ProtoEvent::default());
+        let event: Option<Event> = present.from_proto_in(&bump).unwrap();
+        assert_eq!(event.unwrap().name, "");
+    }
+}
@@ -0,0 +1,280 @@
+//! The conversions doing the lookups, like resolving the ids to the cached entities of a database,
+//! while the proto message is converted. `AsyncFromProtoWith<Ctx>` of the prelude converts with the
+//! context of the lookups, `&Ctx`.
+//! ```ignore
+//! #[derive(FromProto, ToProto)]
+//! #[proto(type = "proto::Order", context = "Db")]
+//! struct Order {
+//!     id: u64,
+//!     // `customers::from_proto(proto, &Db)` is `async`, `customers::to_proto(&Customer)` is not
+//!     #[proto(async_with = "customers")]
+//!     customer: Customer,
+//! }
+//!
+//! let order = proto.from_proto_async(&db).await?;
+//! ```
+//! The fields without `async_with` are converted with `FromProto` as always, every `FromProto` type is
+//! `AsyncFromProtoWith` of any context, without the lookups.
+//!
+//! The repeated messages are converted one by one with `from_proto_async_elements` of
+//! `AsyncFromProtoElements`, or with up to `limit` lookups at the same time with
+//! `from_proto_async_buffered`. Both keep the order of the elements and fail with the first error at
+//! its index, the other lookups in progress are dropped.
+
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::task::Poll;
+
+use crate::error::at_index;
+
+/// The results of the `futures` in their order, with at most `limit` of them polled at the same time.
+/// The first error is returned at the index of its future and the others are dropped. The `limit` of
+/// `0` is `1`.
+#[doc(hidden)]
+pub async fn buffered<T, F>(
+    futures: impl IntoIterator<Item = F>,
+    limit: usize,
+) -> anyhow::Result<Vec<T>>
+where
+    F: Future<Output = anyhow::Result<T>>,
+{
+    let limit = limit.max(1);
+    let mut pending = futures.into_iter().enumerate();
+    let mut running: Vec<(usize, Pin<Box<F>>)> = Vec::with_capacity(limit);
+    let mut results: Vec<Option<T>> = Vec::new();
+    poll_fn(|cx| loop {
+        while running.len() < limit {
+            let Some((index, future)) = pending.next() else {
+                break;
+            };
+            running.push((index, Box::pin(future)));
+            results.push(None);
+        }
+        if running.is_empty() {
+            return Poll::Ready(Ok(()));
+        }
+        let mut done = false;
+        let mut position = 0;
+        while position < running.len() {
+            let (index, future) = &mut running[position];
+            match future.as_mut().poll(cx) {
+                Poll::Ready(Ok(value)) => {
+                    results[*index] = Some(value);
+                    running.swap_remove(position);
+                    done = true;
+                }
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(at_index(error, *index))),
+                Poll::Pending => position += 1,
+            }
+        }
+        // the finished ones make room for the next futures
+        if !done {
+            return Poll::Pending;
+        }
+    })
+    .await?;
+    Ok(results
+        .into_iter()
+        .map(|result| result.expect("every future is finished"))
+        .collect())
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_async {
+    (prelude) => {
+        pub use super::{AsyncFromProtoElements, AsyncFromProtoWith};
+    };
+    () => {
+        /// The conversion from the proto value with the lookups in the context `Ctx`, see
+        /// `proto_conve::asynchronous`
+        #[allow(async_fn_in_trait, dead_code)]
+        pub trait AsyncFromProtoWith<Ctx: ?Sized> {
+            type Result;
+
+            #[allow(clippy::wrong_self_convention)]
+            async fn from_proto_async(self, ctx: &Ctx) -> anyhow::Result<Self::Result>;
+        }
+
+        /// Any `FromProto` type converts without the lookups
+        impl<P: FromProto, Ctx: ?Sized> AsyncFromProtoWith<Ctx> for P {
+            type Result = P::Result;
+
+            async fn from_proto_async(self, _: &Ctx) -> anyhow::Result<Self::Result> {
+                self.from_proto()
+            }
+        }
+
+        /// The conversion of the elements with their `AsyncFromProtoWith`, the errors have the
+        /// indexes of the failed elements
+        #[allow(async_fn_in_trait, dead_code)]
+        pub trait AsyncFromProtoElements<Ctx: ?Sized> {
+            type Result;
+
+            /// Convert the elements one by one
+            #[allow(clippy::wrong_self_convention)]
+            async fn from_proto_async_elements(self, ctx: &Ctx) -> anyhow::Result<Self::Result>;
+
+            /// Convert the elements with up to `limit` of them at the same time, in the same order
+            #[allow(clippy::wrong_self_convention)]
+            async fn from_proto_async_buffered(
+                self,
+                ctx: &Ctx,
+                limit: usize,
+            ) -> anyhow::Result<Self::Result>;
+        }
+
+        impl<P: AsyncFromProtoWith<Ctx>, Ctx: ?Sized> AsyncFromProtoElements<Ctx> for Vec<P> {
+            type Result = Vec<P::Result>;
+
+            async fn from_proto_async_elements(self, ctx: &Ctx) -> anyhow::Result<Self::Result> {
+                let mut result = Vec::with_capacity(self.len());
+                for (index, proto) in self.into_iter().enumerate() {
+                    let value = proto
+                        .from_proto_async(ctx)
+                        .await
+                        .map_err(|error| $crate::error::at_index(error, index))?;
+                    result.push(value);
+                }
+                Ok(result)
+            }
+
+            async fn from_proto_async_buffered(
+                self,
+                ctx: &Ctx,
+                limit: usize,
+            ) -> anyhow::Result<Self::Result> {
+                let futures = self.into_iter().map(|proto| proto.from_proto_async(ctx));
+                $crate::asynchronous::buffered(futures, limit).await
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use std::cell::Cell;
+    use std::collections::HashMap;
+
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    /// The resolver of the names by the ids, the lookups wait for `yields` polls
+    struct Directory {
+        names: HashMap<u32, &'static str>,
+        yields: usize,
+        running: Cell<usize>,
+        most_running: Cell<usize>,
+    }
+
+    impl Directory {
+        async fn lookup(&self, id: u32) -> anyhow::Result<String> {
+            self.running.set(self.running.get() + 1);
+            self.most_running
+                .set(self.most_running.get().max(self.running.get()));
+            for _ in 0..self.yields {
+                tokio::task::yield_now().await;
+            }
+            self.running.set(self.running.get() - 1);
+            let name = self
+                .names
+                .get(&id)
+                .ok_or_else(|| anyhow::anyhow!("Unknown user {id}"))?;
+            Ok(name.to_string())
+        }
+
+        /// The most lookups running at the same time
+        fn most_running(&self) -> usize {
+            self.most_running.get()
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct ProtoUser {
+        pub id: u32,
+    }
+
+    impl AsyncFromProtoWith<Directory> for ProtoUser {
+        type Result = String;
+
+        async fn from_proto_async(self, ctx: &Directory) -> anyhow::Result<Self::Result> {
+            ctx.lookup(self.id)
+                .await
+                .map_err(|error| crate::error::in_field(error, "id"))
+        }
+    }
+
+    fn directory(yields: usize) -> Directory {
+        Directory {
+            names: HashMap::from([(1, "ann"), (2, "bob"), (3, "cyd")]),
+            yields,
+            running: Cell::default(),
+            most_running: Cell::default(),
+        }
+    }
+
+    fn users(ids: &[u32]) -> Vec<ProtoUser> {
+        ids.iter().map(|&id| ProtoUser { id }).collect()
+    }
+
+    #[tokio::test]
+    async fn test_sync_blanket() {
+        let value = 7u32.from_proto_async(&()).await.unwrap();
+        assert_eq!(value, 7);
+        let values = vec![Some(1u32), None]
+            .from_proto_async_elements(&directory(0))
+            .await
+            .unwrap();
+        assert_eq!(values, [Some(1), None]);
+    }
+
+    #[tokio::test]
+    async fn test_sequential() {
+        let ctx = directory(3);
+        let names = users(&[3, 1, 2])
+            .from_proto_async_elements(&ctx)
+            .await
+            .unwrap();
+        assert_eq!(names, ["cyd", "ann", "bob"]);
+        assert_eq!(ctx.most_running(), 1);
+
+        let error = users(&[1, 9, 2])
+            .from_proto_async_elements(&ctx)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "[1].id: Unknown user 9");
+    }
+
+    #[tokio::test]
+    async fn test_buffered() {
+        let ctx = directory(3);
+        let ids = [3, 1, 2, 1, 3];
+        let names = users(&ids)
+            .from_proto_async_buffered(&ctx, 2)
+            .await
+            .unwrap();
+        assert_eq!(names, ["cyd", "ann", "bob", "ann", "cyd"]);
+        assert_eq!(ctx.most_running(), 2);
+
+        let ctx = directory(0);
+        let names = users(&ids)
+            .from_proto_async_buffered(&ctx, 0)
+            .await
+            .unwrap();
+        assert_eq!(names.len(), 5);
+        assert_eq!(ctx.most_running(), 1);
+
+        let error = users(&[1, 2, 9, 3])
+            .from_proto_async_buffered(&directory(1), 3)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "[2].id: Unknown user 9");
+        assert!(users(&[])
+            .from_proto_async_buffered(&ctx, 4)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,55 @@
+//! The repeated key and value entries used as extensible attributes, converted in to structs with a field
+//! per known key with `#[proto(attr_bag)]`.
+//! ```proto
+//! message Attribute {
+//!   string key = 1;
+//!   string value = 2;
+//! }
+//! ```
+//! The values are parsed with `FromStr` and written with `Display`, unless the field has
+//! `#[proto(with = "module")]`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Parse the value of a key, used by the derive macros
+#[doc(hidden)]
+pub fn parse<T>(value: String) -> anyhow::Result<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value.parse().map_err(|error| {
+        let message = format!("Invalid value {value:?}: {error}");
+        crate::violation::coded(crate::violation::ErrorCode::InvalidFormat, message)
+    })
+}
+
+/// Keep the value of a known key, the key can be there only once, used by the derive macros
+#[doc(hidden)]
+pub fn assign(slot: &mut Option<String>, key: &str, value: String) -> anyhow::Result<()> {
+    anyhow::ensure!(slot.is_none(), "The key {key} is duplicated");
+    *slot = Some(value);
+    Ok(())
+}
+
+/// Keep the value of an unknown key, the key can be there only once, used by the derive macros
+#[doc(hidden)]
+pub fn leftover(
+    leftovers: &mut HashMap<String, String>,
+    key: String,
+    value: String,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(!leftovers.contains_key(&key), "The key {key} is duplicated");
+    leftovers.insert(key, value);
+    Ok(())
+}
+
+/// The unknown keys and their values sorted by the key, used by the derive macros
+#[doc(hidden)]
+pub fn sorted(leftovers: &HashMap<String, String>) -> Vec<(&String, &String)> {
+    let mut entries: Vec<_> = leftovers.iter().collect();
+    entries.sort_unstable();
+    entries
+}
@@ -0,0 +1,164 @@
+//! Integers with a documented valid range, like percent `0..=100` or port `1..=65535`, checked once at
+//! the conversion instead of at every use.
+//! ```ignore
+//! type Percent = proto_conve::Bounded<u8, 0, 100>;
+//! ```
+//! They convert from any proto integer type, which fits in to `i64`, the out of range values are an
+//! error, or clamped in the lenient `ConversionMode`.
+
+use std::fmt;
+
+use crate::lossy::{ConversionReport, LossyEvent};
+use crate::mode::ConversionMode;
+use crate::violation::{coded, CodedError, ErrorCode};
+
+/// The integer `T` in the range `MIN..=MAX`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Bounded<T, const MIN: i64, const MAX: i64>(T);
+
+impl<T, const MIN: i64, const MAX: i64> Bounded<T, MIN, MAX>
+where
+    T: Copy + Into<i64>,
+{
+    /// The value, if it is in the range
+    pub fn new(value: T) -> anyhow::Result<Self> {
+        check::<MIN, MAX>(value.into())?;
+        Ok(Self(value))
+    }
+}
+
+impl<T: Copy, const MIN: i64, const MAX: i64> Bounded<T, MIN, MAX> {
+    /// The value
+    pub fn get(self) -> T {
+        self.0
+    }
+}
+
+impl<T: fmt::Display, const MIN: i64, const MAX: i64> fmt::Display for Bounded<T, MIN, MAX> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Check the value is in the range
+fn check<const MIN: i64, const MAX: i64>(value: i64) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        (MIN..=MAX).contains(&value),
+        CodedError::new(
+            ErrorCode::OutOfRange,
+            format!("The value {value} is out of range {MIN}..={MAX}"),
+        )
+    );
+    Ok(())
+}
+
+/// Convert the proto integer, used by the prelude
+#[doc(hidden)]
+pub fn from_proto<W, T, const MIN: i64, const MAX: i64>(
+    value: W,
+    mode: ConversionMode,
+) -> anyhow::Result<Bounded<T, MIN, MAX>>
+where
+    W: Copy + fmt::Display + TryInto<i64>,
+    T: TryFrom<i64>,
+{
+    let wide = match value.try_into() {
+        Ok(wide) => wide,
+        Err(_) if mode.is_lenient() => MAX,
+        Err(_) => {
+            let message = format!("The value {value} is out of range {MIN}..={MAX}");
+            return Err(coded(ErrorCode::OutOfRange, message));
+        }
+    };
+    let wide = match check::<MIN, MAX>(wide) {
+        Ok(()) => wide,
+        Err(_) if mode.is_lenient() => wide.clamp(MIN, MAX),
+        Err(error) => return Err(error),
+    };
+    match T::try_from(wide) {
+        Ok(value) => Ok(Bounded(value)),
+        Err(_) => anyhow::bail!(
+            "The value {value} does not fit in to {}",
+            std::any::type_name::<T>()
+        ),
+    }
+}
+
+/// Convert the proto integer, clamping and reporting the out of range one, used by the prelude
+#[doc(hidden)]
+pub fn from_proto_lossy<W, T, const MIN: i64, const MAX: i64>(
+    value: W,
+) -> anyhow::Result<(Bounded<T, MIN, MAX>, ConversionReport)>
+where
+    W: Copy + fmt::Display + TryInto<i64>,
+    T: TryFrom<i64>,
+{
+    if let Ok(bounded) = from_proto(value, ConversionMode::Strict) {
+        return Ok((bounded, ConversionReport::new()));
+    }
+    let bounded = from_proto(value, ConversionMode::Lenient)?;
+    let clamped = value
+        .try_into()
+        .map_or(MAX, |wide: i64| wide.clamp(MIN, MAX));
+    let event = LossyEvent::Clamped {
+        from: value.to_string(),
+        to: clamped.to_string(),
+    };
+    Ok((bounded, ConversionReport::event(event)))
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+    use crate::mode::ConversionMode;
+
+    type Percent = Bounded<u8, 0, 100>;
+    type Port = Bounded<u16, 1, 65535>;
+
+    impl_traits!(prelude);
+
+    #[test]
+    fn test_bounds() {
+        assert_eq!(Percent::from_proto_alias(0u32).unwrap().get(), 0);
+        assert_eq!(Percent::from_proto_alias(100u32).unwrap().get(), 100);
+        assert_eq!(Port::from_proto_alias(1u32).unwrap().get(), 1);
+        assert_eq!(Port::from_proto_alias(65535u32).unwrap().get(), 65535);
+
+        let error = Percent::from_proto_alias(101u32).unwrap_err();
+        assert_eq!(error.to_string(), "The value 101 is out of range 0..=100");
+        let error = Port::from_proto_alias(0u32).unwrap_err();
+        assert_eq!(error.to_string(), "The value 0 is out of range 1..=65535");
+        let error = Percent::from_proto_alias(-1i32).unwrap_err();
+        assert_eq!(error.to_string(), "The value -1 is out of range 0..=100");
+        assert!(Port::from_proto_alias(65536u64).is_err());
+    }
+
+    #[test]
+    fn test_to_proto() {
+        let percent = Percent::new(42).unwrap();
+        assert_eq!(ToProtoAlias::<u32>::to_proto_alias(&percent), 42);
+        assert!(Percent::new(200).is_err());
+    }
+
+    #[test]
+    fn test_list() {
+        let error = Vec::<Percent>::from_proto_alias(vec![10u32, 100, 130]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "[2]: The value 130 is out of range 0..=100"
+        );
+
+        let values =
+            Vec::<Percent>::from_proto_alias_mode(vec![10u32, 130], ConversionMode::Lenient);
+        assert_eq!(
+            values.unwrap(),
+            [Percent::new(10).unwrap(), Percent::new(100).unwrap()]
+        );
+        assert_eq!(
+            Option::<Percent>::from_proto_alias(None::<u32>).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,369 @@
+//! Limiting the work of the conversions of the untrusted input, like the proto messages of the public
+//! APIs. The conversions run in `limit` count the converted elements, track the depth of the nested
+//! messages and check the sizes of the strings, and the one exceeding the `ConversionBudget` fails with
+//! `BudgetExceeded` at the path of the value, like
+//! `items[1000]: The conversion exceeded the budget of 1000 elements`.
+//! ```ignore
+//! let budget = ConversionBudget::unlimited()
+//!     .max_elements(10_000)
+//!     .max_depth(32)
+//!     .max_string_bytes(64 * 1024);
+//! let order = proto_conve::budget::limit(budget, || proto.from_proto())?;
+//! ```
+//! The elements are the ones of `Vec` and the maps converted with `FromProto`, the maps converted with
+//! `FromProtoAlias` and the maps with `key_with` of the derived conversions, and the values of
+//! `ListValue` and `Struct`, counted over the whole conversion. The depth is the one of the fields
+//! converted with `FromProtoField`, like the fields of the derived conversions, and of the elements,
+//! as the paths of `fault`. The strings are the ones converted with `FromProto` and the string values
+//! of `ListValue`. Out of `limit` the budget is unlimited and nothing is counted.
+
+use std::cell::Cell;
+use std::fmt;
+
+use crate::error::PathSegment;
+
+/// The limits of the conversion, all of them are unlimited by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ConversionBudget {
+    max_elements: Option<usize>,
+    max_depth: Option<usize>,
+    max_string_bytes: Option<usize>,
+}
+
+impl ConversionBudget {
+    /// Without any limit
+    pub const fn unlimited() -> Self {
+        Self {
+            max_elements: None,
+            max_depth: None,
+            max_string_bytes: None,
+        }
+    }
+
+    /// At most `max` elements of the collections, in all of them together
+    pub const fn max_elements(mut self, max: usize) -> Self {
+        self.max_elements = Some(max);
+        self
+    }
+
+    /// At most `max` nested fields and elements, the fields of the converted message are at depth 1
+    pub const fn max_depth(mut self, max: usize) -> Self {
+        self.max_depth = Some(max);
+        self
+    }
+
+    /// At most `max` bytes in each of the strings
+    pub const fn max_string_bytes(mut self, max: usize) -> Self {
+        self.max_string_bytes = Some(max);
+        self
+    }
+}
+
+/// The limit of the `ConversionBudget`, which was exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BudgetLimit {
+    Elements,
+    Depth,
+    StringBytes,
+}
+
+/// The error of the conversion exceeding its budget, wrapped in the `ConversionError` with the path of
+/// the value, see `exceeded`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetExceeded {
+    limit: BudgetLimit,
+    max: usize,
+}
+
+impl BudgetExceeded {
+    /// The limit, which was exceeded
+    pub fn limit(&self) -> BudgetLimit {
+        self.limit
+    }
+
+    /// The value of the limit
+    pub fn max(&self) -> usize {
+        self.max
+    }
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.limit {
+            BudgetLimit::Elements => {
+                write!(
+                    f,
+                    "The conversion exceeded the budget of {} elements",
+                    self.max
+                )
+            }
+            BudgetLimit::Depth => {
+                write!(
+                    f,
+                    "The conversion exceeded the budget of depth {}",
+                    self.max
+                )
+            }
+            BudgetLimit::StringBytes => {
+                write!(f, "The string exceeded the budget of {} bytes", self.max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// The `BudgetExceeded` of the failed conversion, also in the `ConversionError` with its path
+pub fn exceeded(error: &anyhow::Error) -> Option<&BudgetExceeded> {
+    match error.downcast_ref::<crate::error::ConversionError>() {
+        Some(conversion) => conversion.reason().downcast_ref(),
+        None => error.downcast_ref(),
+    }
+}
+
+/// The budget of the conversion running in the thread, with what it has used
+#[derive(Clone, Copy)]
+struct State {
+    budget: ConversionBudget,
+    elements: usize,
+    depth: usize,
+}
+
+thread_local! {
+    /// The hooks are cheap out of `limit`
+    static ACTIVE: Cell<bool> = const { Cell::new(false) };
+    static STATE: Cell<Option<State>> = const { Cell::new(None) };
+}
+
+/// Run the conversions in `f` within the `budget`. The `limit` in it has its own budget, and the one
+/// around it continues after it.
+pub fn limit<R>(budget: ConversionBudget, f: impl FnOnce() -> R) -> R {
+    /// Restores the budget around it, also when `f` panics
+    struct Restore(Option<State>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            ACTIVE.with(|active| active.set(self.0.is_some()));
+            STATE.with(|state| state.set(self.0));
+        }
+    }
+
+    let state = State {
+        budget,
+        elements: 0,
+        depth: 0,
+    };
+    let _restore = Restore(STATE.with(|current| current.replace(Some(state))));
+    ACTIVE.with(|active| active.set(true));
+    f()
+}
+
+fn update(f: impl FnOnce(&mut State) -> anyhow::Result<()>) -> anyhow::Result<()> {
+    STATE.with(|current| {
+        let Some(mut state) = current.get() else {
+            return Ok(());
+        };
+        let result = f(&mut state);
+        current.set(Some(state));
+        result
+    })
+}
+
+fn exceed(limit: BudgetLimit, max: usize) -> anyhow::Error {
+    BudgetExceeded { limit, max }.into()
+}
+
+/// Count the element of a collection, used by the conversions of the maps
+#[doc(hidden)]
+#[inline]
+pub fn element() -> anyhow::Result<()> {
+    if !ACTIVE.with(Cell::get) {
+        return Ok(());
+    }
+    update(|state| {
+        state.elements += 1;
+        match state.budget.max_elements {
+            Some(max) if state.elements > max => Err(exceed(BudgetLimit::Elements, max)),
+            _ => Ok(()),
+        }
+    })
+}
+
+/// Check the size of the string in `bytes`
+#[doc(hidden)]
+#[inline]
+pub fn string(bytes: usize) -> anyhow::Result<()> {
+    if !ACTIVE.with(Cell::get) {
+        return Ok(());
+    }
+    match STATE
+        .with(Cell::get)
+        .and_then(|state| state.budget.max_string_bytes)
+    {
+        Some(max) if bytes > max => Err(exceed(BudgetLimit::StringBytes, max)),
+        _ => Ok(()),
+    }
+}
+
+/// Run `f` one level deeper, the `element` is counted too
+#[doc(hidden)]
+pub fn nested<T>(element: bool, f: impl FnOnce() -> anyhow::Result<T>) -> anyhow::Result<T> {
+    if element {
+        self::element()?;
+    }
+    update(|state| match state.budget.max_depth {
+        Some(max) if state.depth >= max => Err(exceed(BudgetLimit::Depth, max)),
+        _ => {
+            state.depth += 1;
+            Ok(())
+        }
+    })?;
+    let result = f();
+    update(|state| {
+        state.depth -= 1;
+        Ok(())
+    })?;
+    result
+}
+
+/// Whether the conversion runs within a budget
+#[doc(hidden)]
+#[inline]
+pub fn is_limited() -> bool {
+    ACTIVE.with(Cell::get)
+}
+
+/// Convert the `proto` at the path `segment` with `convert` within the budget, the elements and the
+/// map values are counted, see `fault::scope`
+#[doc(hidden)]
+#[inline]
+pub fn scope<P, T>(
+    segment: impl FnOnce() -> PathSegment,
+    proto: P,
+    convert: impl FnOnce(P) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    if !ACTIVE.with(Cell::get) {
+        return crate::deprecation::scope(segment, proto, convert);
+    }
+    let segment = segment();
+    let element = matches!(segment, PathSegment::Index(_) | PathSegment::Key(_));
+    nested(element, || {
+        crate::deprecation::scope(|| segment, proto, convert)
+    })
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::{exceeded, limit, BudgetLimit, ConversionBudget};
+    use crate::error::ConversionError;
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoNode {
+        name: String,
+        children: Vec<ProtoNode>,
+    }
+
+    impl_traits!(prelude);
+
+    impl FromProto for ProtoNode {
+        type Result = usize;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            let name: String = self.name.from_proto_field("name")?;
+            let children: Vec<usize> = self.children.from_proto_field("children")?;
+            Ok(name.len() + children.into_iter().sum::<usize>())
+        }
+    }
+
+    fn node(name: &str, children: Vec<ProtoNode>) -> ProtoNode {
+        ProtoNode {
+            name: name.to_owned(),
+            children,
+        }
+    }
+
+    #[test]
+    fn test_elements() {
+        let proto = node("root", (0..2000).map(|_| node("a", vec![])).collect());
+        let budget = ConversionBudget::unlimited().max_elements(1000);
+        let error = limit(budget, || proto.clone().from_proto()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "children[1000]: The conversion exceeded the budget of 1000 elements"
+        );
+        let limited = exceeded(&error).unwrap();
+        assert_eq!(limited.limit(), BudgetLimit::Elements);
+        assert_eq!(limited.max(), 1000);
+        let conversion = error.downcast_ref::<ConversionError>().unwrap();
+        assert_eq!(conversion.path_string(), "children[1000]");
+
+        // unlimited and out of the scope
+        assert_eq!(
+            limit(ConversionBudget::default(), || proto.clone().from_proto()).unwrap(),
+            2004
+        );
+        assert_eq!(proto.from_proto().unwrap(), 2004);
+
+        // the maps
+        let map = std::collections::HashMap::from([("a", 1), ("b", 2), ("c", 3)]);
+        let budget = ConversionBudget::unlimited().max_elements(2);
+        let error = limit(budget, || map.from_proto()).unwrap_err();
+        assert_eq!(exceeded(&error).unwrap().limit(), BudgetLimit::Elements);
+    }
+
+    #[test]
+    fn test_depth() {
+        let mut proto = node("leaf", vec![]);
+        for _ in 0..10 {
+            proto = node("a", vec![proto]);
+        }
+        let budget = ConversionBudget::unlimited().max_depth(8);
+        let error = limit(budget, || proto.clone().from_proto()).unwrap_err();
+        assert_eq!(exceeded(&error).unwrap().limit(), BudgetLimit::Depth);
+        assert_eq!(
+            error.to_string(),
+            "children[0].children[0].children[0].children[0].name: The conversion exceeded the \
+             budget of depth 8"
+        );
+        // the children and their elements are 2 levels each, and the name of the leaf is 1 more
+        let budget = ConversionBudget::unlimited().max_depth(21);
+        assert_eq!(limit(budget, || proto.from_proto()).unwrap(), 14);
+    }
+
+    #[test]
+    fn test_string_bytes() {
+        let proto = node(
+            "root",
+            vec![node("a", vec![]), node(&"x".repeat(100), vec![])],
+        );
+        let budget = ConversionBudget::unlimited().max_string_bytes(64);
+        let error = limit(budget, || proto.clone().from_proto()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "children[1].name: The string exceeded the budget of 64 bytes"
+        );
+        assert_eq!(exceeded(&error).unwrap().limit(), BudgetLimit::StringBytes);
+
+        let budget = ConversionBudget::unlimited().max_string_bytes(100);
+        assert_eq!(limit(budget, || proto.from_proto()).unwrap(), 105);
+    }
+
+    #[test]
+    fn test_nested_limit() {
+        let outer = ConversionBudget::unlimited().max_elements(3);
+        let proto = vec![1, 2];
+        let result = limit(outer, || {
+            proto.clone().from_proto()?;
+            // the inner budget does not count in to the outer one
+            limit(ConversionBudget::unlimited(), || vec![0; 10].from_proto())?;
+            proto.clone().from_proto()
+        });
+        let error = result.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "[1]: The conversion exceeded the budget of 3 elements"
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! The builders of the proto messages, derived with `#[derive(ToProtoBuilder)]` on the rust type of
+//! the message. The setters take the rust values and convert them the same as the `ToProto` derive,
+//! so the requests built by hand don't need the `Some(value.to_proto())` of every field.
+//! ```ignore
+//! #[derive(ToProtoBuilder)]
+//! #[proto(type = "proto::User")]
+//! struct User {
+//!     name: String,
+//!     created_at: chrono::DateTime<chrono::Utc>,
+//!     nickname: Option<String>,
+//! }
+//!
+//! let proto = UserProtoBuilder::new()
+//!     .name(&name)
+//!     .created_at(&now)
+//!     .maybe_nickname(nickname.as_ref())
+//!     .build()?;
+//! ```
+//! The builder of `User` is `UserProtoBuilder`. The fields of the `Option` types, or with a
+//! `default`, are optional, they get also the `maybe_` setter of `Option<&T>`, and they are left
+//! missing, when they are not set. `build` fails on the required fields, which are not set, naming all
+//! of them, like `The required fields name, created_at are not set`.
+
+/// Check the required fields are set, the `fields` are the proto names with whether they are set, used
+/// by the derive
+#[doc(hidden)]
+pub fn check(fields: &[(&'static str, bool)]) -> anyhow::Result<()> {
+    let missing: Vec<_> = fields
+        .iter()
+        .filter(|(_, set)| !set)
+        .map(|(name, _)| *name)
+        .collect();
+    match missing.as_slice() {
+        [] => Ok(()),
+        [name] => anyhow::bail!("The required field {name} is not set"),
+        names => anyhow::bail!("The required fields {} are not set", names.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check;
+
+    #[test]
+    fn test_check() {
+        assert!(check(&[("name", true)]).is_ok());
+        let error = check(&[("name", false), ("id", true)]).unwrap_err();
+        assert_eq!(error.to_string(), "The required field name is not set");
+        let error = check(&[("name", false), ("created_at", false)]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The required fields name, created_at are not set"
+        );
+    }
+}
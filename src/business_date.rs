@@ -0,0 +1,278 @@
+//! The business dates, with the `chrono-tz` feature, the local calendar dates of the instants in the
+//! IANA time zones, like of the reports bucketed by the day of the store.
+//! ```proto
+//! message Sale {
+//!   int64 seconds = 1;
+//!   int32 nanos = 2;
+//!   // like "Europe/Prague"
+//!   string time_zone = 3;
+//! }
+//! ```
+//! The message converts in to `chrono::NaiveDate` with `impl_proto_business_date!`, where the names
+//! are the ones of the fields. The date is the one of the instant shifted by the offset of the zone
+//! at that instant, so the days of the DST transitions are 23 or 25 hours long and the zones of the
+//! non-hour offsets, like Asia/Kathmandu of +05:45, change the date at their own midnight. The unknown
+//! zones are the error naming the zone, and the instants out of the range of chrono the error naming
+//! the instant.
+//! ```ignore
+//! impl_proto_business_date!(proto::Sale { seconds, nanos, time_zone });
+//!
+//! let day: chrono::NaiveDate = sale.from_proto()?;
+//! ```
+//! There is no conversion back, the date has no instant. The instants of the dates are chosen with
+//! the explicit `TimeOfDay` by `instant`, the times skipped by the DST move after the gap, and the
+//! repeated ones are the earliest.
+
+use chrono::{DateTime, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::chrono_compat;
+use crate::violation::{coded, ErrorCode};
+
+/// The IANA time zone of the `name`
+pub fn zone(name: &str) -> anyhow::Result<Tz> {
+    if name.is_empty() {
+        return Err(coded(ErrorCode::MissingField, "The time zone is missing"));
+    }
+    name.parse().map_err(|_| {
+        coded(
+            ErrorCode::InvalidFormat,
+            format!("The time zone `{name}` is unknown"),
+        )
+    })
+}
+
+/// The local date of the instant of the unix `seconds` and `nanos` in the `zone`
+pub fn local_date(seconds: i64, nanos: i32, zone: Tz) -> anyhow::Result<NaiveDate> {
+    let instant = u32::try_from(nanos)
+        .ok()
+        .filter(|nanos| *nanos < 1_000_000_000)
+        .and_then(|nanos| chrono_compat::from_unix(seconds, nanos))
+        .ok_or_else(|| {
+            coded(
+                ErrorCode::OutOfRange,
+                format!("The instant of {seconds} seconds and {nanos} nanos is out of range"),
+            )
+        })?;
+    Ok(instant.with_timezone(&zone).date_naive())
+}
+
+/// The local time of the instant chosen for the date, the conversion of the date back to the instant
+/// is lossy, so the time is explicit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeOfDay {
+    /// The first instant of the date, the midnight, or the end of the DST gap skipping it
+    StartOfDay,
+    /// The noon, which no DST transition skips
+    Noon,
+    /// The local time
+    At(NaiveTime),
+}
+
+/// The instant of the local `date` at the `time` in the `zone`
+pub fn instant(date: NaiveDate, zone: Tz, time: TimeOfDay) -> anyhow::Result<DateTime<Utc>> {
+    let time = match time {
+        TimeOfDay::StartOfDay => NaiveTime::MIN,
+        TimeOfDay::Noon => NaiveTime::from_hms_opt(12, 0, 0).expect("the noon is valid"),
+        TimeOfDay::At(time) => time,
+    };
+    // the times skipped by the DST move forward to the end of the gap, by the minutes
+    let mut local = NaiveDateTime::new(date, time);
+    while local.date() == date {
+        match zone.from_local_datetime(&local) {
+            LocalResult::Single(instant) | LocalResult::Ambiguous(instant, _) => {
+                return Ok(instant.with_timezone(&Utc));
+            }
+            LocalResult::None => {
+                local = match local.checked_add_signed(chrono_compat::seconds(60)) {
+                    Some(local) => local,
+                    None => break,
+                };
+            }
+        }
+    }
+    Err(coded(
+        ErrorCode::OutOfRange,
+        format!("The date {date} has no time from {time} in the time zone {zone}"),
+    ))
+}
+
+/// Implement `FromProto` of the message of the unix `seconds` and `nanos` and the IANA `time_zone`
+/// in to the local `chrono::NaiveDate`, the traits of the `impl_traits!(prelude)` have to be in the
+/// scope.
+/// ```ignore
+/// impl_proto_business_date!(proto::Sale { seconds, nanos, time_zone });
+/// ```
+/// The date has no `ToProto`, see `business_date`.
+#[macro_export]
+macro_rules! impl_proto_business_date {
+    ($proto: path { $seconds: ident, $nanos: ident, $time_zone: ident $(,)? }) => {
+        impl FromProto for $proto {
+            type Result = chrono::NaiveDate;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                let zone = $crate::business_date::zone(&self.$time_zone)
+                    .map_err(|error| $crate::error::in_field(error, stringify!($time_zone)))?;
+                $crate::business_date::local_date(self.$seconds, self.$nanos, zone)
+                    .map_err(|error| $crate::error::in_field(error, stringify!($seconds)))
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoSale {
+        seconds: i64,
+        nanos: i32,
+        time_zone: String,
+    }
+
+    impl_proto_business_date!(ProtoSale {
+        seconds,
+        nanos,
+        time_zone
+    });
+
+    fn sale(utc: &str, time_zone: &str) -> ProtoSale {
+        let instant: DateTime<Utc> = utc.parse().unwrap();
+        ProtoSale {
+            seconds: instant.timestamp(),
+            nanos: instant.timestamp_subsec_nanos() as i32,
+            time_zone: time_zone.to_owned(),
+        }
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    fn day(utc: &str, time_zone: &str) -> NaiveDate {
+        sale(utc, time_zone).from_proto().unwrap()
+    }
+
+    #[test]
+    fn test_spring_forward() {
+        // New York moves from -05:00 to -04:00 at 07:00 UTC of 2024-03-10
+        let zone = "America/New_York";
+        assert_eq!(day("2024-03-10T04:59:59.999Z", zone), date("2024-03-09"));
+        assert_eq!(day("2024-03-10T05:00:00Z", zone), date("2024-03-10"));
+        assert_eq!(day("2024-03-10T06:59:59Z", zone), date("2024-03-10"));
+        assert_eq!(day("2024-03-10T07:00:00Z", zone), date("2024-03-10"));
+        // the midnight after the transition is an hour earlier in UTC
+        assert_eq!(day("2024-03-11T03:59:59Z", zone), date("2024-03-10"));
+        assert_eq!(day("2024-03-11T04:00:00Z", zone), date("2024-03-11"));
+    }
+
+    #[test]
+    fn test_fall_back() {
+        // New York moves from -04:00 to -05:00 at 06:00 UTC of 2024-11-03
+        let zone = "America/New_York";
+        assert_eq!(day("2024-11-03T03:59:59Z", zone), date("2024-11-02"));
+        assert_eq!(day("2024-11-03T04:00:00Z", zone), date("2024-11-03"));
+        // the repeated hour of 01:00 to 02:00 local
+        assert_eq!(day("2024-11-03T05:30:00Z", zone), date("2024-11-03"));
+        assert_eq!(day("2024-11-03T06:30:00Z", zone), date("2024-11-03"));
+        assert_eq!(day("2024-11-04T04:59:59Z", zone), date("2024-11-03"));
+        assert_eq!(day("2024-11-04T05:00:00Z", zone), date("2024-11-04"));
+    }
+
+    #[test]
+    fn test_non_hour_offset() {
+        // Kathmandu is +05:45 and Chatham +13:45 in its summer
+        assert_eq!(
+            day("2024-01-01T18:14:59Z", "Asia/Kathmandu"),
+            date("2024-01-01")
+        );
+        assert_eq!(
+            day("2024-01-01T18:15:00Z", "Asia/Kathmandu"),
+            date("2024-01-02")
+        );
+        assert_eq!(
+            day("2024-01-01T10:14:59Z", "Pacific/Chatham"),
+            date("2024-01-01")
+        );
+        assert_eq!(
+            day("2024-01-01T10:15:00Z", "Pacific/Chatham"),
+            date("2024-01-02")
+        );
+        // the same instant is on the other dates in the zones
+        assert_eq!(day("2024-01-01T18:15:00Z", "UTC"), date("2024-01-01"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = sale("2024-01-01T00:00:00Z", "Mars/Olympus_Mons")
+            .from_proto()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "time_zone: The time zone `Mars/Olympus_Mons` is unknown"
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::InvalidFormat);
+        let error = sale("2024-01-01T00:00:00Z", "").from_proto().unwrap_err();
+        assert_eq!(ErrorCode::of(&error), ErrorCode::MissingField);
+
+        let mut far = sale("2024-01-01T00:00:00Z", "Europe/Prague");
+        far.seconds = i64::MAX;
+        let error = far.from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "seconds: The instant of {} seconds and 0 nanos is out of range",
+                i64::MAX
+            )
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::OutOfRange);
+        let mut negative = sale("2024-01-01T00:00:00Z", "Europe/Prague");
+        negative.nanos = -1;
+        assert!(negative.from_proto().is_err());
+    }
+
+    #[test]
+    fn test_instant() {
+        let new_york: Tz = zone("America/New_York").unwrap();
+        let at = |hour, minute| TimeOfDay::At(NaiveTime::from_hms_opt(hour, minute, 0).unwrap());
+        let utc = |value: &str| value.parse::<DateTime<Utc>>().unwrap();
+        // the skipped 02:30 moves after the gap
+        assert_eq!(
+            instant(date("2024-03-10"), new_york, at(2, 30)).unwrap(),
+            utc("2024-03-10T07:00:00Z")
+        );
+        // the repeated 01:30 is the earliest
+        assert_eq!(
+            instant(date("2024-11-03"), new_york, at(1, 30)).unwrap(),
+            utc("2024-11-03T05:30:00Z")
+        );
+        assert_eq!(
+            instant(date("2024-11-03"), new_york, TimeOfDay::StartOfDay).unwrap(),
+            utc("2024-11-03T04:00:00Z")
+        );
+        assert_eq!(
+            instant(date("2024-11-03"), new_york, TimeOfDay::Noon).unwrap(),
+            utc("2024-11-03T17:00:00Z")
+        );
+        // Santiago skips its midnight of 2024-09-08
+        let santiago = zone("America/Santiago").unwrap();
+        let start = instant(date("2024-09-08"), santiago, TimeOfDay::StartOfDay).unwrap();
+        assert_eq!(start, utc("2024-09-08T04:00:00Z"));
+        assert_eq!(
+            local_date(start.timestamp(), 0, santiago).unwrap(),
+            date("2024-09-08")
+        );
+        // Apia skipped the whole 2011-12-30
+        let apia = zone("Pacific/Apia").unwrap();
+        let error = instant(date("2011-12-30"), apia, TimeOfDay::StartOfDay).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The date 2011-12-30 has no time from 00:00:00 in the time zone Pacific/Apia"
+        );
+    }
+}
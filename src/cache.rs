@@ -0,0 +1,328 @@
+//! Caching the conversions of the sub-messages repeated across the messages, like the metadata of
+//! the sensor shared by all of its readings, so every distinct proto value is converted once and the
+//! converted values share it by their `Arc`. The fields marked with `#[proto(cached)]` are converted
+//! in to `Arc<T>`, `Option<Arc<T>>` or `Vec<Arc<T>>` through the cache of the thread.
+//! ```ignore
+//! #[derive(FromProto, ToProto)]
+//! #[proto(type = "proto::Reading")]
+//! struct Reading {
+//!     #[proto(cached)]
+//!     sensor: Arc<Sensor>,
+//!     value: f64,
+//! }
+//!
+//! let readings = batch.readings.from_proto()?;
+//! // the converted values are kept until they are cleared
+//! proto_conve::cache::clear();
+//! ```
+//! The proto types are the keys of the cache, so they have to be `Hash`, `Eq` and `Clone`. Every cache
+//! keeps at most its capacity of the values, evicting the least recently used ones, the caches of the
+//! thread have `DEFAULT_CAPACITY` of them, unless changed with `set_capacity`. The failed conversions
+//! are not cached.
+//!
+//! The cache can be also given explicitly, with `from_proto_cached(&mut cache)` of `FromProtoCached`.
+
+use std::any::{Any, TypeId};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// The number of the values kept by the caches of the thread, unless changed with `set_capacity`
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// The converted values of the proto values `P`, shared by their `Arc`s
+#[derive(Debug, Clone)]
+pub struct ConversionCache<P, T> {
+    capacity: usize,
+    values: HashMap<P, (Arc<T>, u64)>,
+    // the proto values by their last use, the first one is evicted
+    used: BTreeMap<u64, P>,
+    tick: u64,
+}
+
+impl<P: Hash + Eq + Clone, T> ConversionCache<P, T> {
+    /// The cache keeping at most `capacity` values, the zero one keeps none
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            values: HashMap::new(),
+            used: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// The converted value of `proto`, if it is cached
+    pub fn get(&mut self, proto: &P) -> Option<Arc<T>> {
+        let (value, used) = self.values.get_mut(proto)?;
+        let proto = self.used.remove(used).unwrap();
+        self.tick += 1;
+        *used = self.tick;
+        self.used.insert(self.tick, proto);
+        Some(value.clone())
+    }
+
+    /// Cache the converted `value` of `proto`, returning the cached one. The value converted before is
+    /// kept, so the values converted from the equal proto values are always shared.
+    pub fn insert(&mut self, proto: P, value: Arc<T>) -> Arc<T> {
+        if let Some(cached) = self.get(&proto) {
+            return cached;
+        }
+        if self.capacity == 0 {
+            return value;
+        }
+        if self.values.len() == self.capacity {
+            self.evict(self.capacity - 1);
+        }
+        self.tick += 1;
+        self.used.insert(self.tick, proto.clone());
+        self.values.insert(proto, (value.clone(), self.tick));
+        value
+    }
+
+    /// The shared converted value of `proto`, it is converted the first time it is seen
+    pub fn get_or_convert(
+        &mut self,
+        proto: P,
+        convert: impl FnOnce(P) -> anyhow::Result<T>,
+    ) -> anyhow::Result<Arc<T>> {
+        match self.get(&proto) {
+            Some(value) => Ok(value),
+            None => {
+                let value = Arc::new(convert(proto.clone())?);
+                Ok(self.insert(proto, value))
+            }
+        }
+    }
+
+    /// The number of the cached values
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// There are no cached values
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The number of the values kept at most
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Keep at most `capacity` values, evicting the least recently used ones over it
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict(capacity);
+    }
+
+    /// Forget the values, the converted values keep theirs
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.used.clear();
+    }
+
+    /// Evict the least recently used values, until there are `len` of them
+    fn evict(&mut self, len: usize) {
+        while self.values.len() > len {
+            let (_, proto) = self.used.pop_first().unwrap();
+            self.values.remove(&proto);
+        }
+    }
+}
+
+impl<P: Hash + Eq + Clone, T> Default for ConversionCache<P, T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+/// The caches of the thread of any types
+trait AnyCache {
+    fn as_any(&mut self) -> &mut dyn Any;
+
+    fn set_capacity(&mut self, capacity: usize);
+}
+
+impl<P: Hash + Eq + Clone + 'static, T: 'static> AnyCache for ConversionCache<P, T> {
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        ConversionCache::set_capacity(self, capacity);
+    }
+}
+
+thread_local! {
+    static CACHES: RefCell<HashMap<TypeId, Box<dyn AnyCache>>> = RefCell::new(HashMap::new());
+    static CAPACITY: Cell<usize> = const { Cell::new(DEFAULT_CAPACITY) };
+}
+
+/// Use the cache of the thread of the conversions of `P` in to `T`, it must not convert the cached
+/// fields itself
+pub fn with<P, T, R>(f: impl FnOnce(&mut ConversionCache<P, T>) -> R) -> R
+where
+    P: Hash + Eq + Clone + 'static,
+    T: 'static,
+{
+    CACHES.with(|caches| {
+        let mut caches = caches.borrow_mut();
+        let cache = caches
+            .entry(TypeId::of::<ConversionCache<P, T>>())
+            .or_insert_with(|| Box::new(ConversionCache::<P, T>::new(CAPACITY.get())));
+        f(cache.as_any().downcast_mut().unwrap())
+    })
+}
+
+/// The shared converted value of `proto` from the cache of the thread. The cache is not used while
+/// converting, so the cached fields of the nested messages are cached too.
+pub fn cached<P, T>(
+    proto: P,
+    convert: impl FnOnce(P) -> anyhow::Result<T>,
+) -> anyhow::Result<Arc<T>>
+where
+    P: Hash + Eq + Clone + 'static,
+    T: 'static,
+{
+    if let Some(value) = with(|cache: &mut ConversionCache<P, T>| cache.get(&proto)) {
+        return Ok(value);
+    }
+    let value = Arc::new(convert(proto.clone())?);
+    Ok(with(|cache| cache.insert(proto, value)))
+}
+
+/// Keep at most `capacity` values in every cache of the thread, the existing ones evict the least
+/// recently used values over it
+pub fn set_capacity(capacity: usize) {
+    CAPACITY.set(capacity);
+    CACHES.with(|caches| {
+        for cache in caches.borrow_mut().values_mut() {
+            cache.set_capacity(capacity);
+        }
+    });
+}
+
+/// Forget the values of all the caches of the thread
+pub fn clear() {
+    CACHES.with(|caches| caches.borrow_mut().clear());
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::ConversionCache;
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub struct ProtoSensor {
+        name: String,
+        unit: String,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Sensor {
+        name: String,
+        unit: String,
+    }
+
+    impl_traits!(prelude);
+
+    impl FromProto for ProtoSensor {
+        type Result = Sensor;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            if self.unit.is_empty() {
+                anyhow::bail!("The unit of {} is missing", self.name);
+            }
+            Ok(Sensor {
+                name: self.name,
+                unit: self.unit,
+            })
+        }
+    }
+
+    impl ToProto for Sensor {
+        type Result = ProtoSensor;
+
+        fn to_proto(&self) -> Self::Result {
+            ProtoSensor {
+                name: self.name.clone(),
+                unit: self.unit.clone(),
+            }
+        }
+    }
+
+    fn sensor(name: &str) -> ProtoSensor {
+        ProtoSensor {
+            name: name.to_owned(),
+            unit: "°C".to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_shared() {
+        let mut cache = ConversionCache::new(8);
+        let first = sensor("t1").from_proto_cached(&mut cache).unwrap();
+        let second = sensor("t1").from_proto_cached(&mut cache).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        let other = sensor("t2").from_proto_cached(&mut cache).unwrap();
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_eq!(cache.len(), 2);
+
+        let proto = ProtoSensor {
+            name: "t3".to_owned(),
+            unit: String::new(),
+        };
+        let error = proto.from_proto_cached(&mut cache).unwrap_err();
+        assert_eq!(error.to_string(), "The unit of t3 is missing");
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_evict() {
+        let mut cache = ConversionCache::new(2);
+        let first = sensor("t1").from_proto_cached(&mut cache).unwrap();
+        sensor("t2").from_proto_cached(&mut cache).unwrap();
+        // the first one is used again, so the second one is evicted
+        sensor("t1").from_proto_cached(&mut cache).unwrap();
+        sensor("t3").from_proto_cached(&mut cache).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&sensor("t2")).is_none());
+        let again = sensor("t1").from_proto_cached(&mut cache).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+
+        cache.set_capacity(1);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(&sensor("t1")).is_some());
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_thread() {
+        let proto = vec![sensor("t1"), sensor("t1"), sensor("t2")];
+        let sensors: Vec<Arc<Sensor>> =
+            ProtoCachedField::from_proto_cached_field(proto.clone()).unwrap();
+        assert!(Arc::ptr_eq(&sensors[0], &sensors[1]));
+        assert!(!Arc::ptr_eq(&sensors[0], &sensors[2]));
+        assert_eq!(ProtoCachedField::to_proto_cached_field(&sensors), proto);
+
+        let shared: Arc<Sensor> =
+            ProtoCachedField::from_proto_cached_field(Some(sensor("t1"))).unwrap();
+        assert!(Arc::ptr_eq(&shared, &sensors[0]));
+        let error =
+            <Arc<Sensor> as ProtoCachedField<Option<ProtoSensor>>>::from_proto_cached_field(None)
+                .unwrap_err();
+        assert_eq!(error.to_string(), "Required is missing");
+
+        super::set_capacity(0);
+        let converted: Arc<Sensor> =
+            ProtoCachedField::from_proto_cached_field(Some(sensor("t1"))).unwrap();
+        assert!(!Arc::ptr_eq(&converted, &sensors[0]));
+        super::set_capacity(super::DEFAULT_CAPACITY);
+        super::clear();
+    }
+}
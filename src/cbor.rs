@@ -0,0 +1,522 @@
+//! The CBOR encoding of the domain values with the field mapping of the proto conversions, for the
+//! devices without the protobuf codegen, enabled with the `cbor` feature. The structs deriving
+//! `ProtoCbor` are written as the maps keyed by the proto field names, with the `rename` and
+//! `rename_all` of their `#[proto(...)]` attributes, by `to_cbor` of `ToCbor` and read back by
+//! `from_cbor` of `FromCbor`, the traits of the prelude.
+//! ```ignore
+//! mod proto {
+//!     proto_conve::impl_traits!(prelude);
+//!     proto_conve::impl_traits!(cbor chrono::DateTime<chrono::Utc>);
+//! }
+//!
+//! #[derive(FromProto, ToProto, ProtoCbor)]
+//! #[proto(type = "proto::Job")]
+//! struct Job {
+//!     started: chrono::DateTime<chrono::Utc>,
+//!     #[proto(rename = "labels")]
+//!     tags: Vec<String>,
+//! }
+//!
+//! let bytes = job.to_cbor()?;
+//! let job = Job::from_cbor(&bytes)?;
+//! ```
+//! The fields are read as the proto ones: the missing scalars, strings, repeated and map fields are
+//! their defaults, the missing `Option` fields are `None`, and the missing nested structs are the
+//! `Required is missing` error, unless the field has `#[proto(default)]`. The unknown keys are ignored.
+//! The errors have the paths of the fields, like `items[3].started: ...`. The fields with `with` are
+//! converted with the module in to its proto value, which is written with `ToCbor`. The fieldless enums
+//! deriving `ProtoCbor` are their proto enum values.
+//!
+//! The chrono instants and durations are the maps of the `seconds` and `nanos` of the proto
+//! `Timestamp` and `Duration`, with `impl_traits!(cbor chrono::DateTime<chrono::Utc>)` and
+//! `impl_traits!(cbor chrono::Duration)`, converted and checked by the same code as the proto ones.
+//!
+//! It is a sidecar of the proto conversions, the `FromProto` and `ToProto` of the types do not change.
+
+use anyhow::Context;
+
+/// The CBOR value
+pub use ciborium::Value;
+
+/// Encode the CBOR value of the `T`
+pub fn to_vec<T: ?Sized>(value: &Value) -> anyhow::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(value, &mut bytes)
+        .with_context(|| format!("Failed to encode CBOR for {}", std::any::type_name::<T>()))?;
+    Ok(bytes)
+}
+
+/// Decode the CBOR value of the `T`
+pub fn from_slice<T: ?Sized>(bytes: &[u8]) -> anyhow::Result<Value> {
+    ciborium::from_reader(bytes)
+        .with_context(|| format!("Failed to parse CBOR for {}", std::any::type_name::<T>()))
+}
+
+/// The kind of the value in the errors
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Integer(_) => "an integer",
+        Value::Bytes(_) => "bytes",
+        Value::Float(_) => "a float",
+        Value::Text(_) => "a text",
+        Value::Bool(_) => "a bool",
+        Value::Null => "null",
+        Value::Tag(..) => "a tagged value",
+        Value::Array(_) => "an array",
+        Value::Map(_) => "a map",
+        _ => "an unknown value",
+    }
+}
+
+/// The value is not of the `expected` kind
+pub fn unexpected(expected: &str, value: &Value) -> anyhow::Error {
+    anyhow::anyhow!("Expected {expected}, found {}", kind(value))
+}
+
+/// The integer, which has to fit in to `T`
+pub fn integer<T: TryFrom<i128>>(value: Value) -> anyhow::Result<T> {
+    match value {
+        Value::Integer(integer) => {
+            let integer = i128::from(integer);
+            T::try_from(integer).map_err(|_| {
+                anyhow::anyhow!(
+                    "The value {integer} does not fit in to {}",
+                    std::any::type_name::<T>()
+                )
+            })
+        }
+        value => Err(unexpected("an integer", &value)),
+    }
+}
+
+/// The map of the fields, written in their order
+pub fn map(fields: Vec<(&'static str, Value)>) -> Value {
+    Value::Map(
+        fields
+            .into_iter()
+            .map(|(name, value)| (Value::Text(name.to_owned()), value))
+            .collect(),
+    )
+}
+
+/// The fields of the map read by the derived `FromCbor`
+pub struct Fields(Vec<(String, Value)>);
+
+impl Fields {
+    /// The fields of the map, the keys are the field names
+    pub fn new(value: Value) -> anyhow::Result<Self> {
+        let entries = match value {
+            Value::Map(entries) => entries,
+            value => return Err(unexpected("a map", &value)),
+        };
+        entries
+            .into_iter()
+            .map(|(key, value)| match key {
+                Value::Text(name) => Ok((name, value)),
+                key => Err(anyhow::anyhow!(
+                    "Expected a text of the field name, found {}",
+                    kind(&key)
+                )),
+            })
+            .collect::<anyhow::Result<_>>()
+            .map(Self)
+    }
+
+    /// Convert the value of the field `name`, if it is present, its errors get the field in the path
+    pub fn convert<T>(
+        &mut self,
+        name: &'static str,
+        convert: impl FnOnce(Value) -> anyhow::Result<T>,
+    ) -> anyhow::Result<Option<T>> {
+        match self.0.iter().position(|(key, _)| key == name) {
+            None => Ok(None),
+            Some(index) => {
+                let (_, value) = self.0.swap_remove(index);
+                convert(value)
+                    .map(Some)
+                    .map_err(|error| crate::error::in_field(error, name))
+            }
+        }
+    }
+}
+
+/// Generate the CBOR traits, this is part of the `impl_traits!(prelude)`, and the chrono conversions of
+/// the `cbor` arms.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_cbor {
+    (prelude) => {
+        pub use super::{FromCbor, ToCbor};
+    };
+    () => {
+        /// The CBOR value of the domain value, with the field mapping of its proto conversions
+        pub trait ToCbor {
+            /// The CBOR value
+            fn to_cbor_value(&self) -> $crate::__private::ciborium::Value;
+
+            /// Encode the value in to CBOR
+            fn to_cbor(&self) -> anyhow::Result<Vec<u8>> {
+                $crate::cbor::to_vec::<Self>(&self.to_cbor_value())
+            }
+        }
+
+        /// The domain value of the CBOR value, with the field mapping of its proto conversions
+        pub trait FromCbor: Sized {
+            /// Convert the CBOR value
+            fn from_cbor_value(value: $crate::__private::ciborium::Value) -> anyhow::Result<Self>;
+
+            /// The value of the missing field, the proto default, `None` when the field is required
+            fn from_cbor_missing() -> Option<Self> {
+                None
+            }
+
+            /// Decode the value from CBOR
+            fn from_cbor(bytes: &[u8]) -> anyhow::Result<Self> {
+                Self::from_cbor_value($crate::cbor::from_slice::<Self>(bytes)?)
+            }
+        }
+
+        $crate::__impl_cbor!(@integers i32, i64, u32, u64);
+
+        impl ToCbor for bool {
+            fn to_cbor_value(&self) -> $crate::__private::ciborium::Value {
+                $crate::__private::ciborium::Value::Bool(*self)
+            }
+        }
+
+        impl FromCbor for bool {
+            fn from_cbor_value(value: $crate::__private::ciborium::Value) -> anyhow::Result<Self> {
+                match value {
+                    $crate::__private::ciborium::Value::Bool(value) => Ok(value),
+                    value => Err($crate::cbor::unexpected("a bool", &value)),
+                }
+            }
+
+            fn from_cbor_missing() -> Option<Self> {
+                Some(false)
+            }
+        }
+
+        impl ToCbor for f64 {
+            fn to_cbor_value(&self) -> $crate::__private::ciborium::Value {
+                $crate::__private::ciborium::Value::Float(*self)
+            }
+        }
+
+        impl FromCbor for f64 {
+            fn from_cbor_value(value: $crate::__private::ciborium::Value) -> anyhow::Result<Self> {
+                match value {
+                    $crate::__private::ciborium::Value::Float(value) => Ok(value),
+                    value => Err($crate::cbor::unexpected("a float", &value)),
+                }
+            }
+
+            fn from_cbor_missing() -> Option<Self> {
+                Some(0.0)
+            }
+        }
+
+        impl ToCbor for f32 {
+            fn to_cbor_value(&self) -> $crate::__private::ciborium::Value {
+                $crate::__private::ciborium::Value::Float(*self as f64)
+            }
+        }
+
+        impl FromCbor for f32 {
+            fn from_cbor_value(value: $crate::__private::ciborium::Value) -> anyhow::Result<Self> {
+                f64::from_cbor_value(value).map(|value| value as f32)
+            }
+
+            fn from_cbor_missing() -> Option<Self> {
+                Some(0.0)
+            }
+        }
+
+        impl ToCbor for String {
+            fn to_cbor_value(&self) -> $crate::__private::ciborium::Value {
+                $crate::__private::ciborium::Value::Text(self.clone())
+            }
+        }
+
+        impl FromCbor for String {
+            fn from_cbor_value(value: $crate::__private::ciborium::Value) -> anyhow::Result<Self> {
+                match value {
+                    $crate::__private::ciborium::Value::Text(value) => Ok(value),
+                    value => Err($crate::cbor::unexpected("a text", &value)),
+                }
+            }
+
+            fn from_cbor_missing() -> Option<Self> {
+                Some(String::new())
+            }
+        }
+
+        impl ToCbor for $crate::ProtoBytes {
+            fn to_cbor_value(&self) -> $crate::__private::ciborium::Value {
+                $crate::__private::ciborium::Value::Bytes(self.to_vec())
+            }
+        }
+
+        impl FromCbor for $crate::ProtoBytes {
+            fn from_cbor_value(value: $crate::__private::ciborium::Value) -> anyhow::Result<Self> {
+                match value {
+                    $crate::__private::ciborium::Value::Bytes(value) => Ok(value.into()),
+                    value => Err($crate::cbor::unexpected("bytes", &value)),
+                }
+            }
+
+            fn from_cbor_missing() -> Option<Self> {
+                Some(Vec::new().into())
+            }
+        }
+
+        /// The absent value is `null`
+        impl<T: ToCbor> ToCbor for Option<T> {
+            fn to_cbor_value(&self) -> $crate::__private::ciborium::Value {
+                match self {
+                    Some(value) => value.to_cbor_value(),
+                    None => $crate::__private::ciborium::Value::Null,
+                }
+            }
+        }
+
+        impl<T: FromCbor> FromCbor for Option<T> {
+            fn from_cbor_value(value: $crate::__private::ciborium::Value) -> anyhow::Result<Self> {
+                match value {
+                    $crate::__private::ciborium::Value::Null => Ok(None),
+                    value => T::from_cbor_value(value).map(Some),
+                }
+            }
+
+            fn from_cbor_missing() -> Option<Self> {
+                Some(None)
+            }
+        }
+
+        impl<T: ToCbor> ToCbor for Box<T> {
+            fn to_cbor_value(&self) -> $crate::__private::ciborium::Value {
+                (**self).to_cbor_value()
+            }
+        }
+
+        impl<T: FromCbor> FromCbor for Box<T> {
+            fn from_cbor_value(value: $crate::__private::ciborium::Value) -> anyhow::Result<Self> {
+                T::from_cbor_value(value).map(Box::new)
+            }
+
+            fn from_cbor_missing() -> Option<Self> {
+                T::from_cbor_missing().map(Box::new)
+            }
+        }
+
+        impl<T: ToCbor> ToCbor for Vec<T> {
+            fn to_cbor_value(&self) -> $crate::__private::ciborium::Value {
+                $crate::__private::ciborium::Value::Array(
+                    self.iter().map(ToCbor::to_cbor_value).collect(),
+                )
+            }
+        }
+
+        /// The errors have the index of the element in the path
+        impl<T: FromCbor> FromCbor for Vec<T> {
+            fn from_cbor_value(value: $crate::__private::ciborium::Value) -> anyhow::Result<Self> {
+                match value {
+                    $crate::__private::ciborium::Value::Array(values) => values
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, value)| {
+                            T::from_cbor_value(value)
+                                .map_err(|error| $crate::error::at_index(error, index))
+                        })
+                        .collect(),
+                    value => Err($crate::cbor::unexpected("an array", &value)),
+                }
+            }
+
+            fn from_cbor_missing() -> Option<Self> {
+                Some(Vec::new())
+            }
+        }
+
+        impl<K: ToCbor, V: ToCbor, S> ToCbor for std::collections::HashMap<K, V, S> {
+            fn to_cbor_value(&self) -> $crate::__private::ciborium::Value {
+                $crate::__private::ciborium::Value::Map(
+                    self.iter()
+                        .map(|(key, value)| (key.to_cbor_value(), value.to_cbor_value()))
+                        .collect(),
+                )
+            }
+        }
+
+        /// The errors have the key of the entry in the path
+        impl<K, V, S> FromCbor for std::collections::HashMap<K, V, S>
+        where
+            K: FromCbor + Eq + std::hash::Hash + std::fmt::Display,
+            V: FromCbor,
+            S: std::hash::BuildHasher + Default,
+        {
+            fn from_cbor_value(value: $crate::__private::ciborium::Value) -> anyhow::Result<Self> {
+                match value {
+                    $crate::__private::ciborium::Value::Map(entries) => entries
+                        .into_iter()
+                        .map(|(key, value)| {
+                            let key = K::from_cbor_value(key)?;
+                            match V::from_cbor_value(value) {
+                                Ok(value) => Ok((key, value)),
+                                Err(error) => Err($crate::error::at_key(error, &key)),
+                            }
+                        })
+                        .collect(),
+                    value => Err($crate::cbor::unexpected("a map", &value)),
+                }
+            }
+
+            fn from_cbor_missing() -> Option<Self> {
+                Some(Self::default())
+            }
+        }
+    };
+    (@integers $($type: ty),*) => {
+        $(
+            impl ToCbor for $type {
+                fn to_cbor_value(&self) -> $crate::__private::ciborium::Value {
+                    $crate::__private::ciborium::Value::Integer((*self).into())
+                }
+            }
+
+            impl FromCbor for $type {
+                fn from_cbor_value(
+                    value: $crate::__private::ciborium::Value,
+                ) -> anyhow::Result<Self> {
+                    $crate::cbor::integer(value)
+                }
+
+                fn from_cbor_missing() -> Option<Self> {
+                    Some(0)
+                }
+            }
+        )*
+    };
+    (chrono::DateTime<chrono::Utc>) => {
+        // the instants are converted by the proto conversions of the `seconds` and `nanos`
+        const _: () = {
+            #[derive(Debug, Clone, Copy, PartialEq, Default)]
+            struct Timestamp {
+                seconds: i64,
+                nanos: i32,
+            }
+
+            $crate::impl_traits!(Timestamp, chrono::DateTime<chrono::Utc>);
+            $crate::__impl_cbor!(@seconds_nanos Timestamp, chrono::DateTime<chrono::Utc>);
+        };
+    };
+    (chrono::Duration) => {
+        // the durations are converted by the proto conversions of the `seconds` and `nanos`
+        const _: () = {
+            #[derive(Debug, Clone, Copy, PartialEq, Default)]
+            struct Duration {
+                seconds: i64,
+                nanos: i32,
+            }
+
+            $crate::impl_traits!(Duration, chrono::Duration);
+            $crate::__impl_cbor!(@seconds_nanos Duration, chrono::Duration);
+        };
+    };
+    (@seconds_nanos $proto: ident, $chrono: ty) => {
+        impl ToCbor for $chrono {
+            fn to_cbor_value(&self) -> $crate::__private::ciborium::Value {
+                let proto: $proto = ToProtoAlias::to_proto_alias(self);
+                $crate::cbor::map(vec![
+                    ("seconds", proto.seconds.to_cbor_value()),
+                    ("nanos", proto.nanos.to_cbor_value()),
+                ])
+            }
+        }
+
+        impl FromCbor for $chrono {
+            fn from_cbor_value(value: $crate::__private::ciborium::Value) -> anyhow::Result<Self> {
+                let mut fields = $crate::cbor::Fields::new(value)?;
+                let proto = $proto {
+                    seconds: fields.convert("seconds", i64::from_cbor_value)?.unwrap_or(0),
+                    nanos: fields.convert("nanos", i32::from_cbor_value)?.unwrap_or(0),
+                };
+                proto.from_proto()
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+    impl_traits!(cbor chrono::DateTime<chrono::Utc>);
+    impl_traits!(cbor chrono::Duration);
+
+    #[test]
+    fn test_scalars() {
+        let bytes = (-5i64).to_cbor().unwrap();
+        assert_eq!(i64::from_cbor(&bytes).unwrap(), -5);
+        let error = u32::from_cbor(&bytes).unwrap_err();
+        assert_eq!(error.to_string(), "The value -5 does not fit in to u32");
+        let error = String::from_cbor(&bytes).unwrap_err();
+        assert_eq!(error.to_string(), "Expected a text, found an integer");
+        assert!(String::from_cbor(&[0xff]).is_err());
+
+        let values = vec![Some("a".to_owned()), None];
+        let decoded: Vec<Option<String>> = FromCbor::from_cbor(&values.to_cbor().unwrap()).unwrap();
+        assert_eq!(decoded, values);
+
+        let scores = HashMap::from([("a".to_owned(), vec![1u32]), ("b".to_owned(), vec![])]);
+        let bytes = scores.to_cbor().unwrap();
+        let decoded: HashMap<String, Vec<u32>> = FromCbor::from_cbor(&bytes).unwrap();
+        assert_eq!(decoded, scores);
+        let error = HashMap::<String, Vec<bool>>::from_cbor(&bytes).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            r#"["a"][0]: Expected a bool, found an integer"#
+        );
+    }
+
+    #[test]
+    fn test_chrono() {
+        let started = chrono::DateTime::from_timestamp(1_700_000_000, 5).unwrap();
+        let bytes = started.to_cbor().unwrap();
+        assert_eq!(
+            chrono::DateTime::<chrono::Utc>::from_cbor(&bytes).unwrap(),
+            started
+        );
+        let before = chrono::DateTime::from_timestamp(-1, 500_000_000).unwrap();
+        let value = before.to_cbor_value();
+        assert_eq!(
+            value,
+            crate::cbor::map(vec![
+                ("seconds", (-1i64).to_cbor_value()),
+                ("nanos", 500_000_000i32.to_cbor_value()),
+            ])
+        );
+
+        let timeout = -chrono::Duration::milliseconds(1500);
+        let decoded = chrono::Duration::from_cbor(&timeout.to_cbor().unwrap()).unwrap();
+        assert_eq!(decoded, timeout);
+
+        // checked as the proto timestamps
+        let invalid = crate::cbor::map(vec![("nanos", 2_000_000_000i64.to_cbor_value())]);
+        let error = chrono::DateTime::<chrono::Utc>::from_cbor_value(invalid).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Failed to parse timestamp: 0 s and 2000000000 ns"
+        );
+        let invalid = crate::cbor::map(vec![("nanos", 3_000_000_000i64.to_cbor_value())]);
+        let error = chrono::DateTime::<chrono::Utc>::from_cbor_value(invalid).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "nanos: The value 3000000000 does not fit in to i32"
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! The chrono API used by the chrono arms of `impl_traits!`, so the expanded code calls only these
+//! functions and compiles the same with any chrono 0.4 of the crates invoking the macro, since 0.4.24,
+//! the minimum of the dependency. By default the functions use the current API, with
+//! `DateTime::from_timestamp` and `TimeDelta::try_seconds`, which needs chrono 0.4.35 or newer. With
+//! the `chrono-legacy` feature they use the API of the older releases, like
+//! `NaiveDateTime::from_timestamp_opt`, which is deprecated in the newer ones, so the deprecations stay
+//! in here. Both give the same results, the tests run with either of them. The legacy API is checked
+//! against the oldest chrono with `cargo update -p chrono --precise 0.4.24` and `cargo build
+//! --features chrono-legacy`, with the optional `chrono-tz` dependency taken out of the manifest, as
+//! `chrono-tz` needs chrono 0.4.25. The default features do not build with the chrono before 0.4.35.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, SecondsFormat, Utc};
+
+/// The instant of the unix `seconds` and `nanos`, the nanos of the leap second are up to `2e9`
+#[cfg(not(feature = "chrono-legacy"))]
+pub fn from_unix(seconds: i64, nanos: u32) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(seconds, nanos)
+}
+
+#[cfg(feature = "chrono-legacy")]
+#[allow(deprecated)]
+pub fn from_unix(seconds: i64, nanos: u32) -> Option<DateTime<Utc>> {
+    chrono::NaiveDateTime::from_timestamp_opt(seconds, nanos)
+        .map(|naive| chrono::TimeZone::from_utc_datetime(&Utc, &naive))
+}
+
+/// The instant of the unix `millis`
+#[cfg(not(feature = "chrono-legacy"))]
+pub fn from_unix_millis(millis: i64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(millis)
+}
+
+#[cfg(feature = "chrono-legacy")]
+#[allow(deprecated)]
+pub fn from_unix_millis(millis: i64) -> Option<DateTime<Utc>> {
+    chrono::NaiveDateTime::from_timestamp_millis(millis)
+        .map(|naive| chrono::TimeZone::from_utc_datetime(&Utc, &naive))
+}
+
+/// The unix seconds of the first instant of chrono
+#[cfg(not(feature = "chrono-legacy"))]
+pub fn min_unix_seconds() -> i64 {
+    DateTime::<Utc>::MIN_UTC.timestamp()
+}
+
+#[cfg(feature = "chrono-legacy")]
+#[allow(deprecated)]
+pub fn min_unix_seconds() -> i64 {
+    chrono::MIN_DATETIME.timestamp()
+}
+
+/// The unix seconds of the last instant of chrono
+#[cfg(not(feature = "chrono-legacy"))]
+pub fn max_unix_seconds() -> i64 {
+    DateTime::<Utc>::MAX_UTC.timestamp()
+}
+
+#[cfg(feature = "chrono-legacy")]
+#[allow(deprecated)]
+pub fn max_unix_seconds() -> i64 {
+    chrono::MAX_DATETIME.timestamp()
+}
+
+/// The unix seconds of the instant
+pub fn unix_seconds(value: &DateTime<Utc>) -> i64 {
+    value.timestamp()
+}
+
+/// The unix seconds and the nanos of the second of the instant
+pub fn unix_parts(value: &DateTime<Utc>) -> (i64, u32) {
+    (value.timestamp(), value.timestamp_subsec_nanos())
+}
+
+/// The unix millis of the instant
+pub fn unix_millis(value: &DateTime<Utc>) -> i64 {
+    value.timestamp_millis()
+}
+
+/// The RFC 3339 form of the instant in the errors, like `2024-02-29T12:00:00.5Z`
+pub fn format(value: &DateTime<Utc>) -> String {
+    value.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// The duration of the `seconds`, `None` out of `±i64::MAX` millis
+#[cfg(not(feature = "chrono-legacy"))]
+pub fn try_seconds(seconds: i64) -> Option<Duration> {
+    Duration::try_seconds(seconds)
+}
+
+#[cfg(feature = "chrono-legacy")]
+#[allow(deprecated)]
+pub fn try_seconds(seconds: i64) -> Option<Duration> {
+    // the bound of `Duration::seconds`, which panics
+    (seconds.unsigned_abs() <= (i64::MAX / 1000) as u64).then(|| Duration::seconds(seconds))
+}
+
+/// The duration of the `seconds` in the chrono range
+pub fn seconds(seconds: i64) -> Duration {
+    try_seconds(seconds).expect("the seconds in the range of the duration")
+}
+
+/// The duration of the `nanos`
+pub fn nanoseconds(nanos: i64) -> Duration {
+    Duration::nanoseconds(nanos)
+}
+
+pub fn checked_add(left: Duration, right: Duration) -> Option<Duration> {
+    left.checked_add(&right)
+}
+
+pub fn checked_sub(left: Duration, right: Duration) -> Option<Duration> {
+    left.checked_sub(&right)
+}
+
+/// The whole seconds and the nanos of the duration, both with its sign
+#[cfg(not(feature = "chrono-legacy"))]
+pub fn duration_parts(value: &Duration) -> (i64, i32) {
+    (value.num_seconds(), value.subsec_nanos())
+}
+
+#[cfg(feature = "chrono-legacy")]
+#[allow(deprecated)]
+pub fn duration_parts(value: &Duration) -> (i64, i32) {
+    let seconds = value.num_seconds();
+    let nanos = (*value - Duration::seconds(seconds)).num_nanoseconds();
+    // less than a second
+    (seconds, nanos.unwrap_or_default() as i32)
+}
+
+/// The date of the calendar, `None` for the invalid ones
+pub fn date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, month, day)
+}
+
+/// The year, the month and the day of the date
+pub fn date_parts(value: &NaiveDate) -> (i32, u32, u32) {
+    (value.year(), value.month(), value.day())
+}
+
+/// The days from the `start` to the `end`
+pub fn days_between(start: NaiveDate, end: NaiveDate) -> i64 {
+    (end - start).num_days()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instants() {
+        let value = from_unix(1_709_208_000, 500_000_000).unwrap();
+        assert_eq!(unix_parts(&value), (1_709_208_000, 500_000_000));
+        assert_eq!(unix_seconds(&value), 1_709_208_000);
+        assert_eq!(unix_millis(&value), 1_709_208_000_500);
+        assert_eq!(format(&value), "2024-02-29T12:00:00.500Z");
+        assert_eq!(from_unix_millis(1_709_208_000_500), Some(value));
+        assert_eq!(
+            from_unix_millis(-1).map(|value| unix_parts(&value)),
+            Some((-1, 999_000_000))
+        );
+        // the leap second
+        assert!(from_unix(59, 1_500_000_000).is_some());
+        assert!(from_unix(0, 2_000_000_000).is_none());
+
+        assert_eq!(min_unix_seconds(), -8_334_601_228_800);
+        assert_eq!(max_unix_seconds(), 8_210_266_876_799);
+        assert!(from_unix(min_unix_seconds(), 0).is_some());
+        assert!(from_unix(min_unix_seconds() - 1, 0).is_none());
+        assert!(from_unix(max_unix_seconds(), 999_999_999).is_some());
+        assert!(from_unix(max_unix_seconds() + 1, 0).is_none());
+        assert!(from_unix_millis(i64::MAX).is_none());
+    }
+
+    #[test]
+    fn test_durations() {
+        const MAX_SECONDS: i64 = i64::MAX / 1000;
+        assert_eq!(
+            try_seconds(MAX_SECONDS).map(|value| duration_parts(&value)),
+            Some((MAX_SECONDS, 0))
+        );
+        assert!(try_seconds(-MAX_SECONDS).is_some());
+        assert!(try_seconds(MAX_SECONDS + 1).is_none());
+        assert!(try_seconds(-MAX_SECONDS - 1).is_none());
+        assert!(try_seconds(i64::MIN).is_none());
+
+        let value = seconds(-1) - nanoseconds(500_000_000);
+        assert_eq!(duration_parts(&value), (-1, -500_000_000));
+        assert_eq!(duration_parts(&nanoseconds(-1)), (0, -1));
+        assert_eq!(duration_parts(&(seconds(2) + nanoseconds(1))), (2, 1));
+        assert_eq!(
+            checked_add(seconds(1), nanoseconds(5)),
+            Some(seconds(1) + nanoseconds(5))
+        );
+        assert_eq!(checked_sub(seconds(-MAX_SECONDS), seconds(1)), None);
+    }
+
+    #[test]
+    fn test_dates() {
+        let date = date(2024, 2, 29).unwrap();
+        assert_eq!(date_parts(&date), (2024, 2, 29));
+        assert!(super::date(2023, 2, 29).is_none());
+        assert!(super::date(2024, 0, 1).is_none());
+        assert_eq!(days_between(super::date(2024, 1, 1).unwrap(), date), 59);
+    }
+}
@@ -0,0 +1,234 @@
+//! Converting the huge inputs, like the records of the backfill files, in to the chunks of the converted
+//! values, so they can be written to the sink one chunk at a time, with the progress reported after every
+//! chunk. `from_proto_chunked` of the prelude trait `ChunkedFromProto` converts any `IntoIterator` of
+//! the proto values.
+//! ```ignore
+//! let mut chunks = records.from_proto_chunked(10_000, ErrorMode::Collect, |report| {
+//!     log::info!("{} converted, {} failed in {:?}", report.converted, report.errors, report.elapsed);
+//! });
+//! for chunk in &mut chunks {
+//!     sink.write(chunk?)?;
+//!     for error in chunks.take_errors() {
+//!         log::warn!("{error}");
+//!     }
+//! }
+//! ```
+//! The input is converted lazily, only one chunk is kept at a time. With `ErrorMode::FailFast` the first
+//! failed value is the error of its chunk, which is the last one. With `ErrorMode::Collect` the failed
+//! values are left out of their chunks and their errors are kept, until they are taken with
+//! `take_errors`. The errors have the index of the value in the whole input in their path.
+
+use std::time::Duration;
+
+use crate::clock::Started;
+use crate::error::at_index;
+
+/// How the chunks handle the values, which fail to convert
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ErrorMode {
+    /// The first error ends the conversion
+    #[default]
+    FailFast,
+    /// The failed values are left out and their errors collected
+    Collect,
+}
+
+/// The progress of the conversion, reported after every chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkReport {
+    /// The number of the values converted so far
+    pub converted: usize,
+    /// The number of the values failed so far
+    pub errors: usize,
+    /// The time since the conversion started
+    pub elapsed: Duration,
+}
+
+/// The iterator of the chunks of the values converted with `convert`, reporting to `on_chunk`
+pub struct Chunks<I, C, F> {
+    iter: I,
+    convert: C,
+    on_chunk: F,
+    chunk_size: usize,
+    mode: ErrorMode,
+    index: usize,
+    converted: usize,
+    failed: usize,
+    errors: Vec<anyhow::Error>,
+    started: Started,
+    done: bool,
+}
+
+impl<I, C, F> Chunks<I, C, F> {
+    /// The chunks of at most `chunk_size` values, the zero one is taken as one
+    pub fn new(iter: I, chunk_size: usize, mode: ErrorMode, convert: C, on_chunk: F) -> Self {
+        Self {
+            iter,
+            convert,
+            on_chunk,
+            chunk_size: chunk_size.max(1),
+            mode,
+            index: 0,
+            converted: 0,
+            failed: 0,
+            errors: Vec::new(),
+            started: Started::now(),
+            done: false,
+        }
+    }
+
+    /// The errors collected so far, which were not taken
+    pub fn errors(&self) -> &[anyhow::Error] {
+        &self.errors
+    }
+
+    /// Take the errors collected so far
+    pub fn take_errors(&mut self) -> Vec<anyhow::Error> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// The progress of the conversion so far
+    pub fn report(&self) -> ChunkReport {
+        ChunkReport {
+            converted: self.converted,
+            errors: self.failed,
+            elapsed: self.started.elapsed(),
+        }
+    }
+}
+
+impl<I, C, F, P, T> Iterator for Chunks<I, C, F>
+where
+    I: Iterator<Item = P>,
+    C: FnMut(P) -> anyhow::Result<T>,
+    F: FnMut(ChunkReport),
+{
+    type Item = anyhow::Result<Vec<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut chunk = Vec::with_capacity(self.chunk_size);
+        let mut taken = 0;
+        let mut result = Ok(());
+        while taken < self.chunk_size {
+            let Some(proto) = self.iter.next() else {
+                self.done = true;
+                break;
+            };
+            let index = self.index;
+            self.index += 1;
+            taken += 1;
+            match (self.convert)(proto) {
+                Ok(value) => {
+                    self.converted += 1;
+                    chunk.push(value);
+                }
+                Err(error) => {
+                    self.failed += 1;
+                    let error = at_index(error, index);
+                    match self.mode {
+                        ErrorMode::FailFast => {
+                            self.done = true;
+                            result = Err(error);
+                            break;
+                        }
+                        ErrorMode::Collect => self.errors.push(error),
+                    }
+                }
+            }
+        }
+        if taken == 0 {
+            return None;
+        }
+        let report = self.report();
+        (self.on_chunk)(report);
+        Some(result.map(|()| chunk))
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::{ChunkReport, ErrorMode};
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoRecord {
+        id: i64,
+    }
+
+    impl_traits!(prelude);
+
+    impl FromProto for ProtoRecord {
+        type Result = u32;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(u32::try_from(self.id)?)
+        }
+    }
+
+    fn records(ids: impl IntoIterator<Item = i64>) -> Vec<ProtoRecord> {
+        ids.into_iter().map(|id| ProtoRecord { id }).collect()
+    }
+
+    #[test]
+    fn test_chunks() {
+        let mut reports = Vec::new();
+        let chunks = records(0..25)
+            .from_proto_chunked(10, ErrorMode::FailFast, |report: ChunkReport| {
+                reports.push((report.converted, report.errors))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), [10, 10, 5]);
+        assert_eq!(chunks[2], [20, 21, 22, 23, 24]);
+        assert_eq!(reports, [(10, 0), (20, 0), (25, 0)]);
+
+        let mut count = 0;
+        assert_eq!(
+            Vec::<ProtoRecord>::new()
+                .from_proto_chunked(10, ErrorMode::FailFast, |_| count += 1)
+                .count(),
+            0
+        );
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_fail_fast() {
+        let mut reports = Vec::new();
+        let input = records((0..25).map(|id| if id == 13 { -1 } else { id }));
+        let mut chunks = input.from_proto_chunked(10, ErrorMode::FailFast, |report| {
+            reports.push((report.converted, report.errors))
+        });
+        assert_eq!(chunks.next().unwrap().unwrap().len(), 10);
+        let error = chunks.next().unwrap().unwrap_err();
+        assert!(error.to_string().starts_with("[13]: "), "{error}");
+        assert!(chunks.next().is_none());
+        drop(chunks);
+        assert_eq!(reports, [(10, 0), (13, 1)]);
+    }
+
+    #[test]
+    fn test_collect() {
+        let mut reports = Vec::new();
+        let input = records((0..25).map(|id| if id == 13 || id == 15 { -id } else { id }));
+        let mut chunks = input.from_proto_chunked(10, ErrorMode::Collect, |report| {
+            reports.push((report.converted, report.errors))
+        });
+        assert_eq!(chunks.next().unwrap().unwrap().len(), 10);
+        assert!(chunks.errors().is_empty());
+        let chunk = chunks.next().unwrap().unwrap();
+        assert_eq!(chunk, [10, 11, 12, 14, 16, 17, 18, 19]);
+        let errors = chunks.take_errors();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[1].to_string().starts_with("[15]: "), "{}", errors[1]);
+        assert_eq!(chunks.next().unwrap().unwrap().len(), 5);
+        assert!(chunks.next().is_none());
+        assert!(chunks.errors().is_empty());
+        drop(chunks);
+        assert_eq!(reports, [(10, 0), (18, 2), (23, 2)]);
+    }
+}
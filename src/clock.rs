@@ -0,0 +1,32 @@
+//! The start of the measured conversions. `std::time::Instant` panics on `wasm32-unknown-unknown`,
+//! which has no clock without JS, so there the elapsed times of the chunk reports, the metrics and the
+//! tracing spans are zero.
+
+use std::time::Duration;
+
+/// When the measured conversion started
+#[derive(Debug, Clone, Copy)]
+pub struct Started {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    instant: std::time::Instant,
+}
+
+impl Started {
+    /// Start now
+    #[inline]
+    pub fn now() -> Self {
+        Self {
+            #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+            instant: std::time::Instant::now(),
+        }
+    }
+
+    /// The time since the start, zero without the clock
+    #[inline]
+    pub fn elapsed(&self) -> Duration {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        return self.instant.elapsed();
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        Duration::ZERO
+    }
+}
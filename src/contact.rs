@@ -0,0 +1,302 @@
+//! The contact details of the customer records, with the `contact` feature. The messages of the
+//! `google.type.PostalAddress` shape convert in to `PostalAddress` with
+//! `impl_traits!(PostalAddress, contact::PostalAddress)`, and the E.164 phone numbers of the string
+//! fields in to `PhoneE164` with `FromProtoAlias<String>`.
+//! ```ignore
+//! impl_traits!(PostalAddress, contact::PostalAddress);
+//!
+//! let address = proto.address.from_proto()?;
+//! let phone: PhoneE164 = FromProtoAlias::from_proto_alias(proto.phone)?;
+//! ```
+//! The `region_code` of the addresses is the CLDR region code of two upper case letters, like `CH`,
+//! the other one is an error, and the empty `address_lines` are dropped, keeping the order of the
+//! rest. The phone numbers are `+` and 7 to 15 digits, the spaces, dots, dashes and parentheses
+//! between the digits are dropped, so `+41 (44) 668-18-00` is `+41446681800`.
+//!
+//! In to proto, the addresses and the phone numbers are written in the canonical forms, the
+//! `revision` of the address is `0`, the only one of the shape.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// The postal address of the `google.type.PostalAddress` fields, the empty strings are missing
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct PostalAddress {
+    /// The CLDR region code, like `US` or `CH`
+    pub region_code: String,
+    /// The BCP-47 language code of the address, like `de-CH`
+    pub language_code: String,
+    pub postal_code: String,
+    /// The sorting code of some countries, like the CEDEX of France
+    pub sorting_code: String,
+    /// The state, the province or the canton
+    pub administrative_area: String,
+    /// The city or the town
+    pub locality: String,
+    /// The neighbourhood or the district
+    pub sublocality: String,
+    /// The lines of the address, which are not empty, in their order
+    pub address_lines: Vec<String>,
+    pub recipients: Vec<String>,
+    pub organization: String,
+}
+
+impl PostalAddress {
+    /// Check the CLDR region code of two upper case letters
+    pub fn check_region_code(region_code: &str) -> anyhow::Result<()> {
+        let valid = region_code.len() == 2 && region_code.bytes().all(|b| b.is_ascii_uppercase());
+        anyhow::ensure!(
+            valid,
+            "The region code {region_code:?} is not two upper case letters"
+        );
+        Ok(())
+    }
+}
+
+/// The lines of the address without the empty ones
+#[doc(hidden)]
+pub fn address_lines(mut lines: Vec<String>) -> Vec<String> {
+    lines.retain(|line| !line.trim().is_empty());
+    lines
+}
+
+/// The phone number in the E.164 form, `+` and 7 to 15 digits, like `+41446681800`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PhoneE164(String);
+
+impl PhoneE164 {
+    /// The digits of the shortest numbers with the country code
+    pub const MIN_DIGITS: usize = 7;
+    /// The digits of the longest numbers, with the country code
+    pub const MAX_DIGITS: usize = 15;
+
+    /// Parse the phone number, the separators between the digits are dropped
+    pub fn parse(value: &str) -> anyhow::Result<Self> {
+        let Some(number) = value.trim().strip_prefix('+') else {
+            anyhow::bail!("The phone number {value:?} does not start with '+'");
+        };
+        let mut canonical = String::with_capacity(number.len() + 1);
+        canonical.push('+');
+        for c in number.chars() {
+            match c {
+                '0'..='9' => canonical.push(c),
+                ' ' | '.' | '-' | '(' | ')' => {}
+                _ => anyhow::bail!("The phone number {value:?} has the invalid character {c:?}"),
+            }
+        }
+        let digits = canonical.len() - 1;
+        anyhow::ensure!(
+            (Self::MIN_DIGITS..=Self::MAX_DIGITS).contains(&digits),
+            "The phone number {value:?} has {digits} digits, instead of {} to {}",
+            Self::MIN_DIGITS,
+            Self::MAX_DIGITS
+        );
+        Ok(Self(canonical))
+    }
+
+    /// The number with the leading `+`
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for PhoneE164 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for PhoneE164 {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        Self::parse(value)
+    }
+}
+
+/// Generate the conversions of the phone numbers in the traits, and of the addresses, the
+/// `contact::PostalAddress` arm of `impl_traits!`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_contact {
+    () => {
+        impl FromProtoAlias<String> for $crate::contact::PhoneE164 {
+            fn from_proto_alias(value: String) -> anyhow::Result<Self> {
+                $crate::contact::PhoneE164::parse(&value)
+            }
+        }
+
+        impl ToProtoAlias<String> for $crate::contact::PhoneE164 {
+            fn to_proto_alias(&self) -> String {
+                self.as_str().to_owned()
+            }
+        }
+
+        /// The proto3 `optional` string of the phone number
+        impl ToProtoAlias<Option<String>> for $crate::contact::PhoneE164 {
+            fn to_proto_alias(&self) -> Option<String> {
+                Some(self.as_str().to_owned())
+            }
+        }
+    };
+    ($type: ident) => {
+        impl FromProto for $type {
+            type Result = $crate::contact::PostalAddress;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                $crate::contact::PostalAddress::check_region_code(&self.region_code).map_err(
+                    |error| {
+                        $crate::trace::failed::<Self, Self::Result>($crate::error::in_field(
+                            error,
+                            "region_code",
+                        ))
+                    },
+                )?;
+                Ok($crate::contact::PostalAddress {
+                    region_code: self.region_code,
+                    language_code: self.language_code,
+                    postal_code: self.postal_code,
+                    sorting_code: self.sorting_code,
+                    administrative_area: self.administrative_area,
+                    locality: self.locality,
+                    sublocality: self.sublocality,
+                    address_lines: $crate::contact::address_lines(self.address_lines),
+                    recipients: self.recipients,
+                    organization: self.organization,
+                })
+            }
+        }
+
+        impl ToProtoAlias<$type> for $crate::contact::PostalAddress {
+            fn to_proto_alias(&self) -> $type {
+                $type {
+                    revision: 0,
+                    region_code: self.region_code.clone(),
+                    language_code: self.language_code.clone(),
+                    postal_code: self.postal_code.clone(),
+                    sorting_code: self.sorting_code.clone(),
+                    administrative_area: self.administrative_area.clone(),
+                    locality: self.locality.clone(),
+                    sublocality: self.sublocality.clone(),
+                    address_lines: $crate::contact::address_lines(self.address_lines.clone()),
+                    recipients: self.recipients.clone(),
+                    organization: self.organization.clone(),
+                }
+            }
+        }
+
+        impl ToProtoAlias<Option<$type>> for $crate::contact::PostalAddress {
+            fn to_proto_alias(&self) -> Option<$type> {
+                Some(ToProtoAlias::<$type>::to_proto_alias(self))
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::{PhoneE164, PostalAddress};
+    use crate::impl_traits;
+
+    /// The prost message of `google.type.PostalAddress`
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoPostalAddress {
+        revision: i32,
+        region_code: String,
+        language_code: String,
+        postal_code: String,
+        sorting_code: String,
+        administrative_area: String,
+        locality: String,
+        sublocality: String,
+        address_lines: Vec<String>,
+        recipients: Vec<String>,
+        organization: String,
+    }
+
+    impl_traits!(prelude);
+    impl_traits!(ProtoPostalAddress, contact::PostalAddress);
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_address() {
+        let proto = ProtoPostalAddress {
+            region_code: "CH".to_owned(),
+            postal_code: "8002".to_owned(),
+            locality: "Zürich".to_owned(),
+            address_lines: lines(&["Brandschenkestrasse 110", "", "Building C", " "]),
+            recipients: lines(&["Ann Smith"]),
+            ..Default::default()
+        };
+        let address = proto.clone().from_proto().unwrap();
+        assert_eq!(
+            address.address_lines,
+            lines(&["Brandschenkestrasse 110", "Building C"])
+        );
+        assert_eq!(address.locality, "Zürich");
+
+        let canonical = ToProtoAlias::<ProtoPostalAddress>::to_proto_alias(&address);
+        assert_eq!(
+            canonical,
+            ProtoPostalAddress {
+                address_lines: lines(&["Brandschenkestrasse 110", "Building C"]),
+                ..proto
+            }
+        );
+        assert_eq!(canonical.from_proto().unwrap(), address);
+    }
+
+    #[test]
+    fn test_region_code() {
+        for region_code in ["ch", "CHE", "", "C1"] {
+            let proto = ProtoPostalAddress {
+                region_code: region_code.to_owned(),
+                ..Default::default()
+            };
+            let error = proto.from_proto().unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!(
+                    "region_code: The region code {region_code:?} is not two upper case letters"
+                )
+            );
+        }
+        assert!(PostalAddress::check_region_code("US").is_ok());
+    }
+
+    #[test]
+    fn test_phone() {
+        let phone = PhoneE164::from_proto_alias("+41 (44) 668-18-00".to_owned()).unwrap();
+        assert_eq!(phone.as_str(), "+41446681800");
+        assert_eq!(
+            ToProtoAlias::<String>::to_proto_alias(&phone),
+            "+41446681800"
+        );
+        assert_eq!("+41446681800".parse::<PhoneE164>().unwrap(), phone);
+
+        let error = PhoneE164::from_proto_alias("041 44 668 18 00".to_owned()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The phone number \"041 44 668 18 00\" does not start with '+'"
+        );
+        let error = PhoneE164::parse("+1234567890123456").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The phone number \"+1234567890123456\" has 16 digits, instead of 7 to 15"
+        );
+        let error = PhoneE164::parse("+12345").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The phone number \"+12345\" has 5 digits, instead of 7 to 15"
+        );
+        let error = PhoneE164::parse("+41 44 CALL ME").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The phone number \"+41 44 CALL ME\" has the invalid character 'C'"
+        );
+    }
+}
@@ -0,0 +1,392 @@
+//! The paths of the errors of the hand-written conversions. The derived conversions prepend the
+//! paths of the fields on the way out of the errors, the hand-written ones descend in to the fields
+//! with the `ConvScope` guards, which keep the path in the scope of the thread, like the order, its
+//! item and the price of it.
+//! ```ignore
+//! impl FromProto for proto::Invoice {
+//!     type Result = Invoice;
+//!
+//!     fn from_proto(self) -> anyhow::Result<Self::Result> {
+//!         let _order = ConvScope::field("order");
+//!         let _items = ConvScope::field("items");
+//!         let mut lines = Vec::new();
+//!         for (index, item) in self.order.items.into_iter().enumerate() {
+//!             let _item = ConvScope::index(index);
+//!             lines.push(Line {
+//!                 price: item.price.from_proto_field("price")?,
+//!             });
+//!         }
+//!         Ok(Invoice { lines })
+//!     }
+//! }
+//! ```
+//! The errors get the path of the scopes, which are live, when the errors cross the wrapping helpers
+//! of the crate, like `FromProtoField`, the elements of `Vec` or `error::in_field`, so the error of the
+//! price, even the one of `anyhow::bail!` in its conversion, is `order.items[2].price: ...`. The
+//! segments of the scopes are added only once, the ones of the conversions of the nested messages
+//! before the ones of the conversions around them, so the paths are ordered from the outermost
+//! message, as the paths of the derived conversions. The errors returned in the scope without any of
+//! the helpers get the path with `error::scoped`.
+//!
+//! The scopes are kept in a thread local with the `std` feature, which is enabled by default, without
+//! it the scopes are empty and the errors have only the paths of the helpers.
+
+use crate::error::{ConversionError, PathSegment};
+
+/// The segment of the path, which is in the scope of the thread, until the guard is dropped
+#[must_use = "the segment is out of the scope, when the guard is dropped"]
+pub struct ConvScope {
+    /// The count of the segments with this one
+    #[cfg(feature = "std")]
+    depth: usize,
+    /// The scopes are in the thread, so the guards stay in it too
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl ConvScope {
+    /// Descend in to the field `name`
+    pub fn field(name: &'static str) -> Self {
+        Self::push(PathSegment::Field(name))
+    }
+
+    /// Descend in to the element at `index`
+    pub fn index(index: usize) -> Self {
+        Self::push(PathSegment::Index(index))
+    }
+
+    /// Descend in to the map entry with the `key`
+    pub fn key(key: &impl std::fmt::Display) -> Self {
+        Self::push(PathSegment::Key(key.to_string()))
+    }
+
+    #[cfg(feature = "std")]
+    fn push(segment: PathSegment) -> Self {
+        let depth = imp::push(segment);
+        Self {
+            depth,
+            _not_send: std::marker::PhantomData,
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn push(_segment: PathSegment) -> Self {
+        Self {
+            _not_send: std::marker::PhantomData,
+        }
+    }
+}
+
+impl Drop for ConvScope {
+    fn drop(&mut self) {
+        #[cfg(feature = "std")]
+        imp::pop(self.depth);
+    }
+}
+
+/// The path of the live scopes of the thread, from the outermost one
+pub fn current_proto_path() -> Vec<PathSegment> {
+    #[cfg(feature = "std")]
+    return imp::current();
+    #[cfg(not(feature = "std"))]
+    Vec::new()
+}
+
+/// Convert the `proto` with `convert`, the scopes in the conversion are its own, see `fault::scope`
+#[doc(hidden)]
+#[inline]
+pub fn scope<P, T>(proto: P, convert: impl FnOnce(P) -> anyhow::Result<T>) -> anyhow::Result<T> {
+    #[cfg(feature = "std")]
+    if imp::is_active() {
+        return imp::framed(proto, convert);
+    }
+    convert(proto)
+}
+
+/// Add the segments of the live scopes of the conversion to the path of the error, unless they
+/// already are
+pub(crate) fn attach(conversion: &mut ConversionError) {
+    #[cfg(feature = "std")]
+    imp::attach(conversion);
+    #[cfg(not(feature = "std"))]
+    let _ = conversion;
+}
+
+#[cfg(feature = "std")]
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use crate::error::{ConversionError, PathSegment};
+
+    #[derive(Default)]
+    struct Stack {
+        segments: Vec<PathSegment>,
+        /// The counts of the segments out of the nested conversions, from the outermost one
+        frames: Vec<usize>,
+    }
+
+    thread_local! {
+        /// The helpers are cheap without any scope
+        static ACTIVE: Cell<bool> = const { Cell::new(false) };
+        static STACK: RefCell<Stack> = RefCell::new(Stack::default());
+    }
+
+    fn update<R>(f: impl FnOnce(&mut Stack) -> R) -> R {
+        STACK.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            let result = f(&mut stack);
+            let active = !stack.segments.is_empty() || !stack.frames.is_empty();
+            ACTIVE.with(|current| current.set(active));
+            result
+        })
+    }
+
+    pub fn is_active() -> bool {
+        ACTIVE.with(Cell::get)
+    }
+
+    pub fn push(segment: PathSegment) -> usize {
+        update(|stack| {
+            stack.segments.push(segment);
+            stack.segments.len()
+        })
+    }
+
+    /// The guards dropped out of the order pop the ones pushed after them too
+    pub fn pop(depth: usize) {
+        update(|stack| stack.segments.truncate(depth - 1));
+    }
+
+    pub fn current() -> Vec<PathSegment> {
+        STACK.with(|stack| stack.borrow().segments.clone())
+    }
+
+    pub fn framed<P, T>(
+        proto: P,
+        convert: impl FnOnce(P) -> anyhow::Result<T>,
+    ) -> anyhow::Result<T> {
+        /// Pops the frame, also when `convert` panics
+        struct Frame;
+
+        impl Drop for Frame {
+            fn drop(&mut self) {
+                update(|stack| stack.frames.pop());
+            }
+        }
+
+        update(|stack| {
+            let depth = stack.segments.len();
+            stack.frames.push(depth);
+        });
+        let _frame = Frame;
+        convert(proto)
+    }
+
+    pub fn attach(conversion: &mut ConversionError) {
+        if !is_active() {
+            return;
+        }
+        STACK.with(|stack| {
+            let stack = stack.borrow();
+            let frame = stack.frames.len();
+            if conversion.scoped_frame == Some(frame) {
+                return;
+            }
+            let start = stack.frames.last().copied().unwrap_or(0);
+            let segments = stack.segments.get(start..).unwrap_or_default();
+            for segment in segments.iter().rev() {
+                conversion.path.push(segment.clone());
+            }
+            conversion.scoped_frame = Some(frame);
+        });
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+#[allow(dead_code)]
+mod tests {
+    use super::{current_proto_path, ConvScope};
+    use crate::error::PathSegment;
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoPrice {
+        cents: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoItem {
+        price: ProtoPrice,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoOrder {
+        items: Vec<ProtoItem>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoInvoice {
+        order: ProtoOrder,
+        customer: ProtoCustomer,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoCustomer {
+        address: ProtoAddress,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoAddress {
+        zip: ProtoPrice,
+    }
+
+    impl_traits!(prelude);
+
+    impl FromProto for ProtoPrice {
+        type Result = u64;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            if self.cents < 0 {
+                anyhow::bail!("The price {} is negative", self.cents);
+            }
+            Ok(self.cents as u64)
+        }
+    }
+
+    impl FromProto for ProtoItem {
+        type Result = u64;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            self.price.from_proto_field("price")
+        }
+    }
+
+    impl FromProto for ProtoCustomer {
+        type Result = u64;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            let _address = ConvScope::field("address");
+            self.address.zip.from_proto_field("zip")
+        }
+    }
+
+    /// Descends in to the order by hand
+    impl FromProto for ProtoInvoice {
+        type Result = u64;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            let total = {
+                let _order = ConvScope::field("order");
+                let _items = ConvScope::field("items");
+                let mut total = 0;
+                for (index, item) in self.order.items.into_iter().enumerate() {
+                    let _item = ConvScope::index(index);
+                    let price: u64 = item.price.from_proto_field("price")?;
+                    total += price;
+                }
+                total
+            };
+            let _billing = ConvScope::field("billing");
+            let customer: u64 = self.customer.from_proto_field("customer")?;
+            Ok(total + customer)
+        }
+    }
+
+    fn item(cents: i64) -> ProtoItem {
+        ProtoItem {
+            price: ProtoPrice { cents },
+        }
+    }
+
+    fn invoice(items: Vec<ProtoItem>, zip: i64) -> ProtoInvoice {
+        ProtoInvoice {
+            order: ProtoOrder { items },
+            customer: ProtoCustomer {
+                address: ProtoAddress {
+                    zip: ProtoPrice { cents: zip },
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn test_path() {
+        let error = invoice(vec![item(1), item(2), item(-3)], 0)
+            .from_proto()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "order.items[2].price: The price -3 is negative"
+        );
+        assert!(current_proto_path().is_empty());
+
+        // the scopes of the nested conversion come before the ones around it
+        let error = invoice(vec![item(1)], -1).from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "billing.customer.address.zip: The price -1 is negative"
+        );
+        assert!(current_proto_path().is_empty());
+
+        // the elements of `Vec`
+        let error = {
+            let _orders = ConvScope::field("orders");
+            vec![item(1), item(-1)].from_proto().unwrap_err()
+        };
+        assert_eq!(
+            error.to_string(),
+            "orders[1].price: The price -1 is negative"
+        );
+
+        // without any of the helpers
+        let error = {
+            let _orders = ConvScope::field("orders");
+            let _order = ConvScope::index(3);
+            crate::error::scoped(anyhow::anyhow!("The order is empty"))
+        };
+        assert_eq!(error.to_string(), "orders[3]: The order is empty");
+    }
+
+    #[test]
+    fn test_unwinding() {
+        assert_eq!(invoice(vec![item(1), item(2)], 3).from_proto().unwrap(), 6);
+        assert!(current_proto_path().is_empty());
+
+        let outer = ConvScope::field("outer");
+        {
+            let _inner = ConvScope::index(1);
+            assert_eq!(
+                current_proto_path(),
+                [PathSegment::Field("outer"), PathSegment::Index(1)]
+            );
+        }
+        assert_eq!(current_proto_path(), [PathSegment::Field("outer")]);
+        assert!(invoice(vec![item(-1)], 0).from_proto().is_err());
+        assert_eq!(current_proto_path(), [PathSegment::Field("outer")]);
+        drop(outer);
+        assert!(current_proto_path().is_empty());
+    }
+
+    #[test]
+    fn test_no_leakage() {
+        let error = invoice(vec![item(-1)], 0).from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "order.items[0].price: The price -1 is negative"
+        );
+        // the next conversion has none of the scopes of the failed one
+        let error = item(-2).from_proto().unwrap_err();
+        assert_eq!(error.to_string(), "price: The price -2 is negative");
+        let error = std::thread::spawn(|| {
+            let _scope = ConvScope::key(&"other");
+            item(-4).from_proto().unwrap_err()
+        })
+        .join()
+        .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "[\"other\"].price: The price -4 is negative"
+        );
+        let error = item(-5).from_proto().unwrap_err();
+        assert_eq!(error.to_string(), "price: The price -5 is negative");
+    }
+}
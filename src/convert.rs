@@ -0,0 +1,205 @@
+//! The front door of the conversions, `convert_from_proto` and `convert_to_proto` of the prelude. The
+//! conversions through them get the boundary, which the hand-written wrappers of the services used to
+//! add:
+//! - the error has the context naming the proto and the domain type, `Failed to convert
+//!   proto::Order in to Order`, around the path of the failed field
+//! - the conversion has its own `context::ConvScope` paths, see `context::scope`
+//! - the conversion is reported to the `metrics` observer, with the `metrics` feature and the observer
+//!   installed
+//! ```ignore
+//! let order = convert_from_proto(request.order)?;
+//! let reply = convert_to_proto(&order);
+//! ```
+//! The context is added only by the outermost front door of the thread, the conversions of the nested
+//! messages called through it, in the impls converting the messages around them, add none, so the
+//! error names the types once. The impls of the traits never add it.
+
+/// Add the types of the front door to the error of the conversion
+fn context<P: ?Sized, T: ?Sized>(error: anyhow::Error) -> anyhow::Error {
+    error.context(format!(
+        "Failed to convert {} in to {}",
+        std::any::type_name::<P>(),
+        std::any::type_name::<T>()
+    ))
+}
+
+/// The conversion of the proto type `P` reported to the observer
+#[inline]
+#[cfg_attr(not(feature = "metrics"), allow(clippy::extra_unused_type_parameters))]
+fn observed<P: ?Sized, T>(convert: impl FnOnce() -> anyhow::Result<T>) -> anyhow::Result<T> {
+    #[cfg(feature = "metrics")]
+    return crate::metrics::observe::<P, T>(convert);
+    #[cfg(not(feature = "metrics"))]
+    convert()
+}
+
+/// The front door of the thread, kept until it is dropped
+struct Boundary {
+    outermost: bool,
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+    static DEPTH: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+impl Boundary {
+    #[cfg(feature = "std")]
+    fn enter() -> Self {
+        let depth = DEPTH.with(|depth| depth.replace(depth.get() + 1));
+        Self {
+            outermost: depth == 0,
+        }
+    }
+
+    /// Without the thread local every front door is the outermost one
+    #[cfg(not(feature = "std"))]
+    fn enter() -> Self {
+        Self { outermost: true }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for Boundary {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// Convert the `proto` of the type `P` in to `T` in the front door, used by `convert_from_proto`
+#[doc(hidden)]
+pub fn from_proto<P, T>(
+    proto: P,
+    convert: impl FnOnce(P) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let boundary = Boundary::enter();
+    let result = observed::<P, T>(|| crate::context::scope(proto, convert));
+    match result {
+        Err(error) if boundary.outermost => Err(context::<P, T>(error)),
+        result => result,
+    }
+}
+
+/// Convert the value in to the proto type `P` in the front door, used by `convert_to_proto`
+#[doc(hidden)]
+pub fn to_proto<P>(convert: impl FnOnce() -> P) -> P {
+    match observed::<P, P>(|| Ok(convert())) {
+        Ok(proto) => proto,
+        Err(_) => unreachable!("the conversions in to proto do not fail"),
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoItem {
+        pub cents: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoOrder {
+        pub items: Vec<ProtoItem>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Order {
+        pub items: Vec<u64>,
+    }
+
+    impl FromProto for ProtoItem {
+        type Result = u64;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            anyhow::ensure!(self.cents >= 0, "The price {} is negative", self.cents);
+            Ok(self.cents as u64)
+        }
+    }
+
+    impl FromProto for ProtoOrder {
+        type Result = Order;
+
+        /// The items through the front door too
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            let mut items = Vec::new();
+            for (index, item) in self.items.into_iter().enumerate() {
+                let item = convert_from_proto(item)
+                    .map_err(|error| crate::error::at_index(error, index))
+                    .map_err(|error| crate::error::in_field(error, "items"))?;
+                items.push(item);
+            }
+            Ok(Order { items })
+        }
+    }
+
+    impl ToProto for Order {
+        type Result = ProtoOrder;
+
+        fn to_proto(&self) -> Self::Result {
+            ProtoOrder {
+                items: self
+                    .items
+                    .iter()
+                    .map(|cents| ProtoItem {
+                        cents: *cents as i64,
+                    })
+                    .collect(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_front_door() {
+        let proto = ProtoOrder {
+            items: vec![ProtoItem { cents: 5 }],
+        };
+        let order = convert_from_proto(proto.clone()).unwrap();
+        assert_eq!(order.items, vec![5]);
+        assert_eq!(convert_to_proto(&order), proto);
+    }
+
+    #[test]
+    fn test_context_once() {
+        let proto = ProtoOrder {
+            items: vec![ProtoItem { cents: 5 }, ProtoItem { cents: -1 }],
+        };
+        let error = convert_from_proto(proto).unwrap_err();
+        let proto_type = std::any::type_name::<ProtoOrder>();
+        let domain_type = std::any::type_name::<Order>();
+        assert_eq!(
+            error.to_string(),
+            format!("Failed to convert {proto_type} in to {domain_type}")
+        );
+        let message = format!("{error:#}");
+        assert_eq!(message.matches(proto_type).count(), 1);
+        assert_eq!(message.matches(domain_type).count(), 1);
+        // not by the nested front door of the items
+        assert_eq!(message.matches("Failed to convert").count(), 1);
+        assert!(message.ends_with(": items[1]: The price -1 is negative"));
+
+        // the path is kept under the context
+        let conversion = error
+            .downcast_ref::<crate::error::ConversionError>()
+            .unwrap();
+        assert_eq!(conversion.path_string(), "items[1]");
+    }
+
+    #[test]
+    fn test_direct() {
+        // the impls add no context of their own
+        let error = ProtoItem { cents: -1 }.from_proto().unwrap_err();
+        assert_eq!(format!("{error:#}"), "The price -1 is negative");
+        let error = convert_from_proto(ProtoItem { cents: -1 }).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            format!(
+                "Failed to convert {} in to u64: The price -1 is negative",
+                std::any::type_name::<ProtoItem>()
+            )
+        );
+    }
+}
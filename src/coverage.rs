@@ -0,0 +1,227 @@
+//! The coverage of the proto types by the conversions, with the `coverage` feature, for the CI gates
+//! failing on the proto messages added without their conversions. The derived `FromProto` and
+//! `ToProto` register their proto types, of `type` and the named mappings, the hand written
+//! conversions register them with `register_conversion!`, and `conversion_coverage` reports the known
+//! proto types without them.
+//! ```ignore
+//! register_conversion!(proto::Invoice => FromProto, ToProto);
+//!
+//! #[test]
+//! fn test_conversion_coverage() {
+//!     let report = proto_conve::coverage::conversion_coverage(proto::MESSAGE_NAMES);
+//!     assert!(report.is_complete(), "{report}");
+//! }
+//! ```
+//! The known proto types are the names of the messages given by the crate, like the constant written
+//! by its build script from the file descriptors. They are matched by the name of the message, the
+//! last segment of the paths, so `acme.v1.Invoice` is covered by the conversions of
+//! `proto::v1::Invoice`, the messages of the same name in the other packages are not told apart.
+//!
+//! The registrations are collected by `inventory` at the start of the process, the registered proto
+//! types are the ones of the crates linked in to the test binary. Without the feature the
+//! registrations are left out.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// The direction of the registered conversion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// `FromProto` of the proto type
+    FromProto,
+    /// `ToProto` in to the proto type
+    ToProto,
+}
+
+/// The conversion of the proto type, registered by the derive and `register_conversion!`
+#[derive(Debug)]
+pub struct Registration {
+    /// The path of the proto type
+    pub proto_type: &'static str,
+    pub direction: Direction,
+}
+
+inventory::collect!(Registration);
+
+/// All the conversions registered in the process
+pub fn registrations() -> impl Iterator<Item = &'static Registration> {
+    inventory::iter::<Registration>.into_iter()
+}
+
+/// The name of the message of the proto type, the last segment of the rust path or of the full name
+/// of the proto package
+fn message_name(proto_type: &str) -> String {
+    let proto_type: String = proto_type.split_whitespace().collect();
+    let proto_type = proto_type.split('<').next().unwrap_or_default();
+    proto_type
+        .rsplit(['.', ':'])
+        .next()
+        .unwrap_or_default()
+        .to_owned()
+}
+
+/// The known proto types without the conversions, in the order they were given
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// Without any conversion
+    pub unconverted: Vec<String>,
+    /// With `ToProto`, but without `FromProto`
+    pub without_from_proto: Vec<String>,
+    /// With `FromProto`, but without `ToProto`
+    pub without_to_proto: Vec<String>,
+}
+
+impl CoverageReport {
+    /// All the known proto types have both of the conversions
+    pub fn is_complete(&self) -> bool {
+        self.unconverted.is_empty()
+            && self.without_from_proto.is_empty()
+            && self.without_to_proto.is_empty()
+    }
+}
+
+/// The lines of the missing conversions, or that the coverage is complete
+impl fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_complete() {
+            return write!(f, "all the proto types have their conversions");
+        }
+        let mut first = true;
+        for (description, names) in [
+            ("without the conversions", &self.unconverted),
+            ("without FromProto", &self.without_from_proto),
+            ("without ToProto", &self.without_to_proto),
+        ] {
+            if names.is_empty() {
+                continue;
+            }
+            if !first {
+                writeln!(f)?;
+            }
+            first = false;
+            write!(f, "proto types {description}: {}", names.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// The report of the `known_proto_types` without the registered conversions
+pub fn conversion_coverage(known_proto_types: &[&str]) -> CoverageReport {
+    coverage_of(registrations(), known_proto_types)
+}
+
+fn coverage_of<'a>(
+    registrations: impl IntoIterator<Item = &'a Registration>,
+    known_proto_types: &[&str],
+) -> CoverageReport {
+    let mut from_proto = BTreeSet::new();
+    let mut to_proto = BTreeSet::new();
+    for registration in registrations {
+        let name = message_name(registration.proto_type);
+        match registration.direction {
+            Direction::FromProto => from_proto.insert(name),
+            Direction::ToProto => to_proto.insert(name),
+        };
+    }
+    let mut report = CoverageReport::default();
+    for known in known_proto_types {
+        let name = message_name(known);
+        match (from_proto.contains(&name), to_proto.contains(&name)) {
+            (true, true) => {}
+            (false, false) => report.unconverted.push(known.to_string()),
+            (false, true) => report.without_from_proto.push(known.to_string()),
+            (true, false) => report.without_to_proto.push(known.to_string()),
+        }
+    }
+    report
+}
+
+/// Register the conversions of the proto type for `coverage::conversion_coverage`, of the directions
+/// `FromProto` and `ToProto`, the derived ones are registered by the derive.
+/// ```ignore
+/// register_conversion!(proto::Invoice => FromProto, ToProto);
+/// register_conversion!(proto::AuditEvent => ToProto);
+/// ```
+#[cfg(feature = "coverage")]
+#[macro_export]
+macro_rules! register_conversion {
+    ($proto: path => $($direction: ident),+ $(,)?) => {
+        $(
+            $crate::__private::inventory::submit! {
+                $crate::coverage::Registration {
+                    proto_type: stringify!($proto),
+                    direction: $crate::coverage::Direction::$direction,
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+
+    mod proto {
+        pub struct Invoice;
+        pub struct Refund;
+        pub struct Customer;
+    }
+
+    register_conversion!(proto::Invoice => FromProto, ToProto);
+    register_conversion!(proto::Refund => FromProto, ToProto);
+    register_conversion!(proto::Customer => FromProto, ToProto);
+
+    #[test]
+    fn test_conversion_coverage() {
+        let known = [
+            "acme.v1.Invoice",
+            "acme.v1.Refund",
+            "acme.v1.Payout",
+            "acme.v1.Customer",
+            "acme.v1.Dispute",
+        ];
+        let report = conversion_coverage(&known);
+        assert_eq!(report.unconverted, ["acme.v1.Payout", "acme.v1.Dispute"]);
+        assert!(report.without_from_proto.is_empty() && report.without_to_proto.is_empty());
+        assert_eq!(
+            report.to_string(),
+            "proto types without the conversions: acme.v1.Payout, acme.v1.Dispute"
+        );
+        let report = conversion_coverage(&known[..2]);
+        assert!(report.is_complete());
+        assert_eq!(
+            report.to_string(),
+            "all the proto types have their conversions"
+        );
+    }
+
+    #[test]
+    fn test_one_direction() {
+        let registrations = [
+            Registration {
+                proto_type: "proto :: v1 :: Invoice",
+                direction: Direction::FromProto,
+            },
+            Registration {
+                proto_type: "proto::Refund",
+                direction: Direction::ToProto,
+            },
+        ];
+        let report = coverage_of(&registrations, &["Invoice", "acme.Refund", "acme.Payout"]);
+        assert_eq!(
+            report,
+            CoverageReport {
+                unconverted: vec!["acme.Payout".to_owned()],
+                without_from_proto: vec!["acme.Refund".to_owned()],
+                without_to_proto: vec!["Invoice".to_owned()],
+            }
+        );
+        assert_eq!(
+            report.to_string(),
+            "proto types without the conversions: acme.Payout\n\
+             proto types without FromProto: acme.Refund\n\
+             proto types without ToProto: Invoice"
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! The dates without the time zone, like `google.type.Date`,
+//! ```proto
+//! message Date {
+//!   int32 year = 1;
+//!   int32 month = 2;
+//!   int32 day = 3;
+//! }
+//! message DateRange {
+//!   Date start = 1;
+//!   Date end = 2;
+//! }
+//! ```
+//! converted in to `chrono::NaiveDate` with `impl_traits!(Date, chrono::NaiveDate)`, and the ranges of
+//! them in to `std::ops::RangeInclusive<chrono::NaiveDate>` with `impl_traits!(DateRange,
+//! std::ops::RangeInclusive<chrono::NaiveDate> { start, end })`, where `start` and `end` are the names
+//! of the fields. The partial dates, of the zero year, month or day, are invalid. The range includes
+//! the end, so the range of the same start and end is the single day, the start after the end is an
+//! error. Both dates are required.
+//!
+//! The ranges longer than a number of days are rejected with `from_proto_max_days(days)` of
+//! `FromProtoDateRange`, like the stays longer than the booking allows.
+
+use std::fmt;
+
+/// The error of the invalid date, used by the prelude
+#[doc(hidden)]
+pub fn invalid(year: i32, month: i32, day: i32) -> anyhow::Error {
+    anyhow::anyhow!("The date {year:04}-{month:02}-{day:02} is invalid")
+}
+
+/// The error of the start after the end, used by the prelude
+#[doc(hidden)]
+pub fn inverted(start: impl fmt::Display, end: impl fmt::Display) -> anyhow::Error {
+    anyhow::anyhow!("The range {start} to {end} is inverted, the start is after the end")
+}
+
+/// Check the range of the `days`, including the end, is not over the `max` ones, used by the prelude
+#[doc(hidden)]
+pub fn check_days(
+    start: impl fmt::Display,
+    end: impl fmt::Display,
+    days: i64,
+    max: u32,
+) -> anyhow::Result<()> {
+    if days > i64::from(max) {
+        anyhow::bail!(
+            "The range {start} to {end} is {days} days long, over the maximum {max} days"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use std::ops::RangeInclusive;
+
+    use chrono::NaiveDate;
+
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Date {
+        year: i32,
+        month: i32,
+        day: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct DateRange {
+        start: Option<Date>,
+        end: Option<Date>,
+    }
+
+    impl_traits!(prelude);
+    impl_traits!(Date, chrono::NaiveDate);
+    impl_traits!(DateRange, std::ops::RangeInclusive<chrono::NaiveDate> { start, end });
+
+    fn date(year: i32, month: i32, day: i32) -> Option<Date> {
+        Some(Date { year, month, day })
+    }
+
+    fn naive(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_date() {
+        let proto = date(2024, 2, 29).unwrap();
+        assert_eq!(proto.clone().from_proto().unwrap(), naive(2024, 2, 29));
+        assert_eq!(
+            ToProtoAlias::<Date>::to_proto_alias(&naive(2024, 2, 29)),
+            proto
+        );
+
+        let error = date(2023, 2, 29).unwrap().from_proto().unwrap_err();
+        assert_eq!(error.to_string(), "The date 2023-02-29 is invalid");
+        let error = date(2023, 0, 0).unwrap().from_proto().unwrap_err();
+        assert_eq!(error.to_string(), "The date 2023-00-00 is invalid");
+    }
+
+    #[test]
+    fn test_range() {
+        let proto = DateRange {
+            start: date(2024, 5, 1),
+            end: date(2024, 5, 3),
+        };
+        let range: RangeInclusive<NaiveDate> = proto.clone().from_proto().unwrap();
+        assert_eq!(range, naive(2024, 5, 1)..=naive(2024, 5, 3));
+        assert!(range.contains(&naive(2024, 5, 3)));
+        assert_eq!(ToProtoAlias::<DateRange>::to_proto_alias(&range), proto);
+        assert_eq!(
+            ToProtoAlias::<Option<DateRange>>::to_proto_alias(&range),
+            Some(proto)
+        );
+    }
+
+    #[test]
+    fn test_single_day() {
+        let proto = DateRange {
+            start: date(2024, 5, 1),
+            end: date(2024, 5, 1),
+        };
+        let range = proto.clone().from_proto_max_days(1).unwrap();
+        assert_eq!(range, naive(2024, 5, 1)..=naive(2024, 5, 1));
+        assert_eq!(
+            range
+                .start()
+                .iter_days()
+                .take_while(|day| range.contains(day))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_invalid_range() {
+        let error = DateRange {
+            start: date(2024, 5, 3),
+            end: date(2024, 5, 1),
+        }
+        .from_proto()
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The range 2024-05-03 to 2024-05-01 is inverted, the start is after the end"
+        );
+
+        let error = DateRange {
+            start: date(2024, 5, 1),
+            end: date(2024, 13, 1),
+        }
+        .from_proto()
+        .unwrap_err();
+        assert_eq!(error.to_string(), "end: The date 2024-13-01 is invalid");
+
+        let error = DateRange {
+            start: None,
+            end: date(2024, 5, 1),
+        }
+        .from_proto()
+        .unwrap_err();
+        assert_eq!(error.to_string(), "start: Required is missing");
+    }
+
+    #[test]
+    fn test_max_days() {
+        let proto = DateRange {
+            start: date(2024, 5, 1),
+            end: date(2024, 5, 30),
+        };
+        assert!(proto.clone().from_proto_max_days(30).is_ok());
+        let error = proto.from_proto_max_days(29).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The range 2024-05-01 to 2024-05-30 is 30 days long, over the maximum 29 days"
+        );
+    }
+}
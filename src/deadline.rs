@@ -0,0 +1,354 @@
+//! The deadlines of the requests, which the callers give either as the relative timeout or as the
+//! absolute time, in a oneof of `google.protobuf.Duration` and `google.protobuf.Timestamp`.
+//! ```proto
+//! message SearchRequest {
+//!   oneof deadline {
+//!     google.protobuf.Duration timeout = 1;
+//!     google.protobuf.Timestamp deadline_at = 2;
+//!   }
+//! }
+//! ```
+//! The oneof converts in to `Deadline` with `impl_proto_deadline!`, where `relative` and `absolute`
+//! are the variants of the prost oneof enum with their proto field names. The payloads convert with
+//! the arms of `impl_traits!` of the proto `Duration` and `Timestamp` types, and the negative timeouts
+//! are an error. The relative deadline is written back as the relative one. The well known types of
+//! `prost_types` convert through the local shims of `impl_traits!(remote ...)`, named after the field
+//! names.
+//! ```
+//! use chrono::{DateTime, Utc};
+//! use proto_conve::{impl_proto_deadline, impl_traits, Deadline};
+//!
+//! impl_traits!(prelude);
+//! impl_traits!(remote prost_types::Duration as Duration, chrono::Duration);
+//! impl_traits!(remote prost_types::Timestamp as Timestamp, chrono::DateTime<chrono::Utc>);
+//!
+//! pub mod search_request {
+//!     #[derive(Clone, PartialEq, Debug)]
+//!     pub enum Deadline {
+//!         Timeout(prost_types::Duration),
+//!         DeadlineAt(prost_types::Timestamp),
+//!     }
+//! }
+//!
+//! impl_proto_deadline!(search_request::Deadline {
+//!     relative: Timeout(timeout: Duration),
+//!     absolute: DeadlineAt(deadline_at: Timestamp),
+//! });
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let now = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap();
+//! let timeout = prost_types::Duration {
+//!     seconds: 1,
+//!     nanos: 500_000_000,
+//! };
+//! let deadline = search_request::Deadline::Timeout(timeout).from_proto()?;
+//! assert_eq!(deadline.resolve(now), now + chrono::Duration::milliseconds(1500));
+//!
+//! // the negative nanos of `google.protobuf.Duration` have the sign of the seconds
+//! let negative = prost_types::Duration {
+//!     seconds: -1,
+//!     nanos: -500_000_000,
+//! };
+//! let error = search_request::Deadline::Timeout(negative).from_proto().unwrap_err();
+//! assert_eq!(error.to_string(), "timeout: The timeout -1.5 s is negative");
+//! # Ok(())
+//! # }
+//! ```
+//! The missing oneof is `None`, the derived conversions of the `Deadline` fields make it the missing
+//! field error, or their `#[proto(default = "...")]` deadline.
+
+use chrono::{DateTime, Utc};
+
+use crate::violation::{coded, ErrorCode};
+
+/// The deadline relative to the time of the request, or the absolute one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Deadline {
+    /// The timeout after the time of the request
+    After(chrono::Duration),
+    /// The absolute time
+    At(DateTime<Utc>),
+}
+
+impl Deadline {
+    /// The deadline after the `timeout`, which is not negative
+    pub fn after(timeout: chrono::Duration) -> anyhow::Result<Self> {
+        if timeout < chrono::Duration::zero() {
+            return Err(coded(
+                ErrorCode::OutOfRange,
+                format!(
+                    "The timeout {} s is negative",
+                    timeout.num_milliseconds() as f64 / 1000.0
+                ),
+            ));
+        }
+        Ok(Deadline::After(timeout))
+    }
+
+    /// The absolute time of the deadline, the relative ones after `now`, clamped to the range of
+    /// `DateTime`
+    pub fn resolve(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        match *self {
+            Deadline::After(timeout) => {
+                now.checked_add_signed(timeout)
+                    .unwrap_or(if timeout < chrono::Duration::zero() {
+                        DateTime::<Utc>::MIN_UTC
+                    } else {
+                        DateTime::<Utc>::MAX_UTC
+                    })
+            }
+            Deadline::At(at) => at,
+        }
+    }
+}
+
+/// Implement `FromProto` of the prost oneof enum of the timeout and the absolute deadline in to
+/// `Deadline`, and `ToProtoAlias` of it back, the traits of the `impl_traits!(prelude)` have to be in
+/// the scope.
+/// ```ignore
+/// impl_proto_deadline!(search_request::Deadline {
+///     relative: Timeout(timeout),
+///     absolute: DeadlineAt(deadline_at),
+/// });
+/// ```
+/// The payloads convert in to `chrono::Duration` and `DateTime<Utc>`, see `deadline`. The remote
+/// payloads convert through their shims of `impl_traits!(remote ...)`, named after the field names,
+/// like `Timeout(timeout: Duration)`.
+#[macro_export]
+macro_rules! impl_proto_deadline {
+    // the payload is converted as it is, or through its remote shim
+    (@from $value: expr, $name: expr, []) => {
+        FromProtoField::from_proto_field($value, $name)
+    };
+    (@from $value: expr, $name: expr, [$shim: ident]) => {
+        FromProtoField::from_proto_field($shim::from($value), $name)
+    };
+    (@to $value: expr, []) => {
+        ToProtoField::to_proto_field($value)
+    };
+    (@to $value: expr, [$shim: ident]) => {
+        $shim::to_remote($value)
+    };
+    (
+        $oneof: path {
+            relative: $relative_variant: ident ($relative_name: ident $(: $relative_shim: ident)?),
+            absolute: $absolute_variant: ident ($absolute_name: ident $(: $absolute_shim: ident)?) $(,)?
+        }
+    ) => {
+        /// The negative timeouts are an error
+        impl FromProto for $oneof {
+            type Result = $crate::Deadline;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                use $oneof as Oneof;
+                match self {
+                    Oneof::$relative_variant(value) => {
+                        let name = stringify!($relative_name);
+                        let timeout: chrono::Duration = $crate::impl_proto_deadline!(
+                            @from value,
+                            name,
+                            [$($relative_shim)?]
+                        )?;
+                        $crate::Deadline::after(timeout)
+                            .map_err(|error| $crate::error::in_field(error, name))
+                    }
+                    Oneof::$absolute_variant(value) => Ok($crate::Deadline::At(
+                        $crate::impl_proto_deadline!(
+                            @from value,
+                            stringify!($absolute_name),
+                            [$($absolute_shim)?]
+                        )?,
+                    )),
+                }
+            }
+        }
+
+        impl ToProtoAlias<$oneof> for $crate::Deadline {
+            fn to_proto_alias(&self) -> $oneof {
+                use $oneof as Oneof;
+                match self {
+                    $crate::Deadline::After(timeout) => Oneof::$relative_variant(
+                        $crate::impl_proto_deadline!(@to timeout, [$($relative_shim)?]),
+                    ),
+                    $crate::Deadline::At(at) => Oneof::$absolute_variant(
+                        $crate::impl_proto_deadline!(@to at, [$($absolute_shim)?]),
+                    ),
+                }
+            }
+        }
+
+        /// The oneof fields of the messages
+        impl ToProtoAlias<Option<$oneof>> for $crate::Deadline {
+            fn to_proto_alias(&self) -> Option<$oneof> {
+                Some(ToProtoAlias::<$oneof>::to_proto_alias(self))
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoDuration {
+        seconds: i64,
+        nanos: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoTimestamp {
+        seconds: i64,
+        nanos: i32,
+    }
+
+    pub mod proto_search_request {
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Deadline {
+            Timeout(super::ProtoDuration),
+            DeadlineAt(super::ProtoTimestamp),
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoSearchRequest {
+        query: String,
+        deadline: Option<proto_search_request::Deadline>,
+    }
+
+    impl_traits!(prelude);
+    impl_traits!(ProtoDuration, chrono::Duration);
+    impl_traits!(ProtoTimestamp, chrono::DateTime<chrono::Utc>);
+    impl_proto_deadline!(proto_search_request::Deadline {
+        relative: Timeout(timeout),
+        absolute: DeadlineAt(deadline_at),
+    });
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct SearchRequest {
+        query: String,
+        deadline: Deadline,
+    }
+
+    const DEFAULT_TIMEOUT: chrono::Duration = chrono::Duration::seconds(30);
+
+    impl FromProto for ProtoSearchRequest {
+        type Result = SearchRequest;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(SearchRequest {
+                query: self.query,
+                deadline: FromProtoField::<Option<Deadline>, _>::from_proto_field(
+                    self.deadline,
+                    "deadline",
+                )?
+                .unwrap_or(Deadline::After(DEFAULT_TIMEOUT)),
+            })
+        }
+    }
+
+    fn request(deadline: proto_search_request::Deadline) -> ProtoSearchRequest {
+        ProtoSearchRequest {
+            query: "tea".to_owned(),
+            deadline: Some(deadline),
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_relative() {
+        let timeout = proto_search_request::Deadline::Timeout(ProtoDuration {
+            seconds: 5,
+            nanos: 500_000_000,
+        });
+        let search = request(timeout.clone()).from_proto().unwrap();
+        assert_eq!(
+            search.deadline,
+            Deadline::After(chrono::Duration::milliseconds(5_500))
+        );
+        assert_eq!(
+            search.deadline.resolve(now()),
+            now() + chrono::Duration::milliseconds(5_500)
+        );
+        // the relative deadline stays relative
+        assert_eq!(
+            ToProtoAlias::<Option<proto_search_request::Deadline>>::to_proto_alias(
+                &search.deadline
+            ),
+            Some(timeout)
+        );
+    }
+
+    #[test]
+    fn test_absolute() {
+        let at = proto_search_request::Deadline::DeadlineAt(ProtoTimestamp {
+            seconds: now().timestamp() + 60,
+            nanos: 0,
+        });
+        let search = request(at.clone()).from_proto().unwrap();
+        let expected = now() + chrono::Duration::minutes(1);
+        assert_eq!(search.deadline, Deadline::At(expected));
+        // the same for any time of the request
+        assert_eq!(search.deadline.resolve(now()), expected);
+        assert_eq!(search.deadline.resolve(DateTime::<Utc>::MIN_UTC), expected);
+        assert_eq!(
+            ToProtoAlias::<proto_search_request::Deadline>::to_proto_alias(&search.deadline),
+            at
+        );
+    }
+
+    #[test]
+    fn test_missing() {
+        let search = ProtoSearchRequest {
+            query: "tea".to_owned(),
+            deadline: None,
+        }
+        .from_proto()
+        .unwrap();
+        assert_eq!(search.deadline, Deadline::After(DEFAULT_TIMEOUT));
+        assert_eq!(
+            None::<proto_search_request::Deadline>.from_proto().unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_negative_timeout() {
+        let timeout = proto_search_request::Deadline::Timeout(ProtoDuration {
+            seconds: -2,
+            nanos: -500_000_000,
+        });
+        let error = request(timeout).from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "deadline.timeout: The timeout -2.5 s is negative"
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::OutOfRange);
+        assert!(Deadline::after(chrono::Duration::zero()).is_ok());
+    }
+
+    #[test]
+    fn test_resolve_clamped() {
+        let near_max = DateTime::<Utc>::MAX_UTC - chrono::Duration::seconds(1);
+        let deadline = Deadline::after(chrono::Duration::days(365)).unwrap();
+        assert_eq!(deadline.resolve(near_max), DateTime::<Utc>::MAX_UTC);
+        let deadline = Deadline::after(chrono::Duration::milliseconds(500)).unwrap();
+        assert_eq!(
+            deadline.resolve(near_max),
+            near_max + chrono::Duration::milliseconds(500)
+        );
+        // the negative ones constructed by hand are clamped to the minimum
+        let before = Deadline::After(chrono::Duration::days(-1));
+        assert_eq!(
+            before.resolve(DateTime::<Utc>::MIN_UTC),
+            DateTime::<Utc>::MIN_UTC
+        );
+    }
+}
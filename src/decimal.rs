@@ -0,0 +1,153 @@
+//! 64 bit integers carried in the proto `string` fields, as they are in the schemas used from
+//! JavaScript, which can not represent all of them as numbers.
+//! ```ignore
+//! struct Payment {
+//!     id: proto_conve::Decimal<u64>,
+//!     amount: proto_conve::Decimal<i64>,
+//! }
+//! ```
+//! The strings are the digits in decimal, the signed ones can start with `-`. The empty strings, the
+//! leading `+`, the whitespace and the values, which do not fit, are an error.
+
+use std::fmt;
+use std::num::{NonZeroI64, NonZeroU64, ParseIntError};
+use std::str::FromStr;
+
+/// The integer `T` written in decimal in to the proto string
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Decimal<T>(T);
+
+impl<T> Decimal<T> {
+    /// The value to be written in decimal
+    pub const fn new(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Copy> Decimal<T> {
+    /// The value
+    pub fn get(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Decimal<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Decimal<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The integers carried in the proto strings
+pub trait DecimalInt: FromStr<Err = ParseIntError> + fmt::Display + Copy + sealed::Sealed {
+    /// The name of the type in the errors
+    const NAME: &'static str;
+}
+
+macro_rules! impl_decimal_int {
+    ($($type: ty => $name: literal),*) => {
+        $(
+            impl sealed::Sealed for $type {}
+
+            impl DecimalInt for $type {
+                const NAME: &'static str = $name;
+            }
+        )*
+    };
+}
+
+impl_decimal_int!(
+    u64 => "u64",
+    i64 => "i64",
+    NonZeroU64 => "non zero u64",
+    NonZeroI64 => "non zero i64"
+);
+
+/// Parse the proto string, used by the prelude
+#[doc(hidden)]
+pub fn from_proto<T: DecimalInt>(value: &str) -> anyhow::Result<Decimal<T>> {
+    // `FromStr` of the integers accepts the leading `+`
+    if value.starts_with('+') {
+        anyhow::bail!(
+            "The value {value:?} is not a valid {}: the sign `+` is not allowed",
+            T::NAME
+        );
+    }
+    match value.parse() {
+        Ok(value) => Ok(Decimal(value)),
+        Err(error) => anyhow::bail!("The value {value:?} is not a valid {}: {error}", T::NAME),
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    fn to_proto<T: DecimalInt>(value: T) -> String {
+        ToProtoAlias::<String>::to_proto_alias(&Decimal::new(value))
+    }
+
+    #[test]
+    fn test_unsigned() {
+        let max = Decimal::<u64>::from_proto_alias(u64::MAX.to_string()).unwrap();
+        assert_eq!(max.get(), u64::MAX);
+        assert_eq!(to_proto(u64::MAX), "18446744073709551615");
+
+        let error = Decimal::<u64>::from_proto_alias("184467440737095516150".to_owned());
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "The value \"184467440737095516150\" is not a valid u64: number too large to fit in \
+             target type"
+        );
+        let error = Decimal::<u64>::from_proto_alias(String::new()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The value \"\" is not a valid u64: cannot parse integer from empty string"
+        );
+        let error = Decimal::<u64>::from_proto_alias("+1".to_owned()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The value \"+1\" is not a valid u64: the sign `+` is not allowed"
+        );
+        assert!(Decimal::<u64>::from_proto_alias(" 1".to_owned()).is_err());
+        assert!(Decimal::<u64>::from_proto_alias("1 ".to_owned()).is_err());
+        assert!(Decimal::<u64>::from_proto_alias("-1".to_owned()).is_err());
+    }
+
+    #[test]
+    fn test_signed() {
+        let min = Decimal::<i64>::from_proto_alias(i64::MIN.to_string()).unwrap();
+        assert_eq!(min.get(), i64::MIN);
+        assert_eq!(to_proto(i64::MIN), "-9223372036854775808");
+
+        // the negative zero is the zero, written without the sign
+        let zero = Decimal::<i64>::from_proto_alias("-0".to_owned()).unwrap();
+        assert_eq!(zero.get(), 0);
+        assert_eq!(ToProtoAlias::<String>::to_proto_alias(&zero), "0");
+        assert!(Decimal::<i64>::from_proto_alias("9223372036854775808".to_owned()).is_err());
+    }
+
+    #[test]
+    fn test_non_zero() {
+        let error = Decimal::<NonZeroU64>::from_proto_alias("0".to_owned()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The value \"0\" is not a valid non zero u64: number would be zero for non-zero type"
+        );
+        let value = Decimal::<NonZeroI64>::from_proto_alias("-5".to_owned()).unwrap();
+        assert_eq!(to_proto(value.get()), "-5");
+    }
+}
@@ -0,0 +1,305 @@
+//! The defaults of the missing proto fields, which are not the constants of the code, but the
+//! configuration of the conversion, like the default currency or the page size of the tenant. The
+//! `DefaultsProvider` of the conversion context, given with `defaults::scope`, has the values of the
+//! field paths, the fields marked with `#[proto(default_from_ctx)]` take them, when they are missing.
+//! ```ignore
+//! #[derive(FromProto)]
+//! #[proto(type = "proto::Invoice")]
+//! struct Invoice {
+//!     #[proto(default_from_ctx)]
+//!     currency: Currency,
+//!     #[proto(default_from_ctx, default = "default_page_size")]
+//!     page_size: u32,
+//! }
+//!
+//! let invoice = defaults::scope(tenant.clone(), || proto.from_proto())?;
+//! ```
+//! The value of the missing field is, in the order of the precedence
+//! - the proto value, when the field is present,
+//! - the value of the provider, it is the proto value of the field, converted the same as the sent
+//!   one, so the `String` default of the `Currency` is parsed as the proto string,
+//! - the static `default` of the field, when it has one,
+//! - the missing field error, the field is required, when neither has the value.
+//!
+//! The field path asked from the provider is the path of the live `context::ConvScope` scopes with the
+//! proto name of the field, like `order.currency`, the derived conversions of the nested messages do
+//! not add their fields, so it is the name of the field without the scopes. The hand written
+//! conversions take the defaults with `ctx_default` and `or_ctx_default`. Out of a scope the provider
+//! has nothing.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::error::{in_field, missing_field, path_string, PathSegment};
+use crate::violation::{coded, ErrorCode};
+
+/// The default value of the field given by the `DefaultsProvider`
+#[derive(Debug, Clone, PartialEq)]
+pub enum DefaultValue {
+    Bool(bool),
+    Int(i64),
+    Uint(u64),
+    Float(f64),
+    Text(String),
+}
+
+/// The name of the kind of the value in the errors
+impl fmt::Display for DefaultValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DefaultValue::Bool(value) => write!(f, "the bool {value}"),
+            DefaultValue::Int(value) => write!(f, "the integer {value}"),
+            DefaultValue::Uint(value) => write!(f, "the unsigned integer {value}"),
+            DefaultValue::Float(value) => write!(f, "the float {value}"),
+            DefaultValue::Text(value) => write!(f, "the string {value:?}"),
+        }
+    }
+}
+
+/// The defaults of the missing fields in the conversion context
+pub trait DefaultsProvider {
+    /// The default of the field at the `field_path`, like `order.currency`, `None` leaves the field
+    /// to its static default or the missing field error
+    fn default_for(&self, field_path: &str) -> Option<DefaultValue>;
+}
+
+/// The defaults of the fixed paths
+impl DefaultsProvider for HashMap<String, DefaultValue> {
+    fn default_for(&self, field_path: &str) -> Option<DefaultValue> {
+        self.get(field_path).cloned()
+    }
+}
+
+thread_local! {
+    static PROVIDER: RefCell<Option<Arc<dyn DefaultsProvider>>> = const { RefCell::new(None) };
+}
+
+/// Restores the provider of the outer scope, also when the conversion panics
+struct Restore(Option<Arc<dyn DefaultsProvider>>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let outer = self.0.take();
+        PROVIDER.with(|provider| *provider.borrow_mut() = outer);
+    }
+}
+
+/// Run the conversions in `f` with the defaults of the `provider`
+pub fn scope<R>(provider: Arc<dyn DefaultsProvider>, f: impl FnOnce() -> R) -> R {
+    let outer = PROVIDER.with(|current| current.borrow_mut().replace(provider));
+    let _restore = Restore(outer);
+    f()
+}
+
+/// The proto values given by the `DefaultValue`
+pub trait FromDefaultValue: Sized {
+    /// The proto value, `None` of the other kinds of the values
+    fn from_default_value(value: &DefaultValue) -> Option<Self>;
+}
+
+impl FromDefaultValue for bool {
+    fn from_default_value(value: &DefaultValue) -> Option<Self> {
+        match value {
+            DefaultValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl FromDefaultValue for String {
+    fn from_default_value(value: &DefaultValue) -> Option<Self> {
+        match value {
+            DefaultValue::Text(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! integer_defaults {
+    ($($type: ty),*) => {$(
+        /// The integers of either sign in the range of the type
+        impl FromDefaultValue for $type {
+            fn from_default_value(value: &DefaultValue) -> Option<Self> {
+                match value {
+                    DefaultValue::Int(value) => <$type>::try_from(*value).ok(),
+                    DefaultValue::Uint(value) => <$type>::try_from(*value).ok(),
+                    _ => None,
+                }
+            }
+        }
+    )*};
+}
+
+integer_defaults!(i32, i64, u32, u64);
+
+impl FromDefaultValue for f64 {
+    fn from_default_value(value: &DefaultValue) -> Option<Self> {
+        match value {
+            DefaultValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl FromDefaultValue for f32 {
+    fn from_default_value(value: &DefaultValue) -> Option<Self> {
+        match value {
+            DefaultValue::Float(value) => Some(*value as f32),
+            _ => None,
+        }
+    }
+}
+
+/// The proto value of the missing field `name` given by the provider of the scope, the value of the
+/// other kind, or out of the range of the proto type, is `ErrorCode::InvalidFormat` in the field
+pub fn ctx_default<P: FromDefaultValue>(name: &'static str) -> anyhow::Result<Option<P>> {
+    let Some(provider) = PROVIDER.with(|provider| provider.borrow().clone()) else {
+        return Ok(None);
+    };
+    let mut path = crate::context::current_proto_path();
+    path.push(PathSegment::Field(name));
+    let field_path = path_string(path.iter());
+    let Some(value) = provider.default_for(&field_path) else {
+        return Ok(None);
+    };
+    match P::from_default_value(&value) {
+        Some(proto) => Ok(Some(proto)),
+        None => Err(in_field(
+            coded(
+                ErrorCode::InvalidFormat,
+                format!(
+                    "The default of `{field_path}` is {value}, which is not {}",
+                    std::any::type_name::<P>()
+                ),
+            ),
+            name,
+        )),
+    }
+}
+
+/// The proto value of the field `name`, or its default of the provider, the missing field error
+/// without either
+pub fn or_ctx_default<P: FromDefaultValue>(
+    proto: Option<P>,
+    name: &'static str,
+) -> anyhow::Result<P> {
+    match proto {
+        Some(proto) => Ok(proto),
+        None => ctx_default(name)?.ok_or_else(|| missing_field(name)),
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::context::ConvScope;
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    /// The tenant with the default currency, but without the default locale
+    struct Tenant;
+
+    impl DefaultsProvider for Tenant {
+        fn default_for(&self, field_path: &str) -> Option<DefaultValue> {
+            match field_path {
+                "currency" | "order.currency" => Some(DefaultValue::Text("EUR".to_owned())),
+                "page_size" => Some(DefaultValue::Int(-1)),
+                _ => None,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoInvoice {
+        currency: Option<String>,
+        locale: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Invoice {
+        currency: String,
+        locale: String,
+    }
+
+    impl FromProto for ProtoInvoice {
+        type Result = Invoice;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(Invoice {
+                currency: or_ctx_default(self.currency, "currency")?,
+                locale: or_ctx_default(self.locale, "locale")?,
+            })
+        }
+    }
+
+    fn invoice(currency: Option<&str>, locale: Option<&str>) -> ProtoInvoice {
+        ProtoInvoice {
+            currency: currency.map(str::to_owned),
+            locale: locale.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn test_provider() {
+        let tenant: Arc<dyn DefaultsProvider> = Arc::new(Tenant);
+        let converted = scope(tenant.clone(), || invoice(None, Some("de-AT")).from_proto());
+        assert_eq!(
+            converted.unwrap(),
+            Invoice {
+                currency: "EUR".to_owned(),
+                locale: "de-AT".to_owned(),
+            }
+        );
+        // the present value is before the default
+        let converted = scope(tenant.clone(), || {
+            invoice(Some("CHF"), Some("de-CH")).from_proto()
+        });
+        assert_eq!(converted.unwrap().currency, "CHF");
+
+        // the provider has no locale
+        let error = scope(tenant, || invoice(None, None).from_proto()).unwrap_err();
+        assert_eq!(error.to_string(), "locale: Required is missing");
+        assert_eq!(ErrorCode::of(&error), ErrorCode::MissingField);
+    }
+
+    #[test]
+    fn test_paths() {
+        let tenant: Arc<dyn DefaultsProvider> = Arc::new(Tenant);
+        let currency = scope(tenant.clone(), || {
+            let _order = ConvScope::field("order");
+            ctx_default::<String>("currency")
+        });
+        assert_eq!(currency.unwrap().as_deref(), Some("EUR"));
+        let currency = scope(tenant.clone(), || {
+            let _refund = ConvScope::field("refund");
+            ctx_default::<String>("currency")
+        });
+        assert_eq!(currency.unwrap(), None);
+
+        // the default of the other kind
+        let error = scope(tenant.clone(), || ctx_default::<String>("page_size")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "page_size: The default of `page_size` is the integer -1, which is not alloc::string::String"
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::InvalidFormat);
+        assert!(scope(tenant, || ctx_default::<u32>("page_size")).is_err());
+
+        // out of a scope
+        assert_eq!(ctx_default::<String>("currency").unwrap(), None);
+        let error = invoice(None, Some("de-AT")).from_proto().unwrap_err();
+        assert_eq!(error.to_string(), "currency: Required is missing");
+    }
+
+    #[test]
+    fn test_fixed_paths() {
+        let defaults: HashMap<String, DefaultValue> =
+            [("limit".to_owned(), DefaultValue::Uint(50))].into();
+        let limit = scope(Arc::new(defaults), || or_ctx_default::<i32>(None, "limit"));
+        assert_eq!(limit.unwrap(), 50);
+    }
+}
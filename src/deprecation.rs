@@ -0,0 +1,268 @@
+//! Finding the clients, which still send the deprecated proto fields. The fields marked with
+//! `#[proto(deprecated = "use new_field instead")]`, and the proto values wrapped in `DeprecatedField`
+//! by the hand-written conversions, record a `DeprecationNotice`, when they are present, not the proto
+//! default, and convert as before. The notices of the conversions run in `collect` are returned with
+//! the paths of the fields, like `items[3].legacy_id`.
+//! ```ignore
+//! let (user, notices) = proto_conve::deprecation::collect(|| proto.from_proto());
+//! for notice in &notices {
+//!     log::warn!("{client} sent {notice}");
+//! }
+//!
+//! // hand-written
+//! let legacy_id = DeprecatedField::new(self.legacy_id, "use id instead").from_proto()?;
+//! ```
+//! With the `metrics` feature, the conversions with `from_proto_observed` report their notices to
+//! `ConversionObserver::on_deprecated`. The paths are tracked through the fields converted with
+//! `FromProtoField`, like the fields of the derived conversions, and the elements of `Vec`, as the
+//! paths of `fault`, and only while the notices are collected, otherwise nothing is recorded.
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+
+use crate::error::{path_string, PathSegment};
+
+/// The deprecated value present in the converted proto value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeprecationNotice {
+    /// From the outermost segment
+    path: Vec<PathSegment>,
+    message: &'static str,
+}
+
+impl DeprecationNotice {
+    /// The path of the deprecated value, like `items[3].legacy_id`, the path of the collected value
+    /// itself is empty
+    pub fn path(&self) -> String {
+        path_string(self.path.iter())
+    }
+
+    /// The segments of the path, from the outermost one
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.path
+    }
+
+    /// The message of the deprecation, like `use new_field instead`
+    pub fn message(&self) -> &'static str {
+        self.message
+    }
+}
+
+impl fmt::Display for DeprecationNotice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "The deprecated value, {}", self.message)
+        } else {
+            write!(f, "{}: The deprecated field, {}", self.path(), self.message)
+        }
+    }
+}
+
+/// The proto value of a deprecated field, its conversion records the notice, when it is present
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeprecatedField<P> {
+    proto: P,
+    message: &'static str,
+}
+
+impl<P: Default + PartialEq> DeprecatedField<P> {
+    /// The proto value deprecated with the `message`
+    pub fn new(proto: P, message: &'static str) -> Self {
+        Self { proto, message }
+    }
+
+    /// Record the notice, when the proto value is present, and take it for the other conversions than
+    /// `FromProto`, like `FromProtoAlias`
+    pub fn record(self) -> P {
+        deprecated(&[], self.proto, self.message)
+    }
+}
+
+/// The notices collected in the thread, with the path of the conversion
+#[derive(Default)]
+struct Collector {
+    path: Vec<PathSegment>,
+    notices: Vec<DeprecationNotice>,
+}
+
+thread_local! {
+    /// The path is tracked only while collecting, so the hooks are cheap without it
+    static COLLECTING: Cell<bool> = const { Cell::new(false) };
+    static COLLECTOR: RefCell<Option<Collector>> = const { RefCell::new(None) };
+}
+
+/// Run `f` collecting the deprecation notices of the conversions in the thread. The collection around
+/// it gets them too, with the path of the value `f` converts.
+pub fn collect<R>(f: impl FnOnce() -> R) -> (R, Vec<DeprecationNotice>) {
+    /// Restores the collection around it, also when `f` panics
+    struct Restore(Option<Collector>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            COLLECTING.with(|collecting| collecting.set(previous.is_some()));
+            COLLECTOR.with(|current| current.replace(previous));
+        }
+    }
+
+    let restore = Restore(COLLECTOR.with(|current| current.replace(Some(Collector::default()))));
+    COLLECTING.with(|collecting| collecting.set(true));
+    let result = f();
+    let notices = COLLECTOR.with(|current| {
+        current
+            .borrow_mut()
+            .as_mut()
+            .map(|collector| std::mem::take(&mut collector.notices))
+            .unwrap_or_default()
+    });
+    drop(restore);
+    for notice in &notices {
+        record(&notice.path, notice.message);
+    }
+    (result, notices)
+}
+
+/// Record the notice with the `message` at the `path`, under the path of the conversion
+fn record(path: &[PathSegment], message: &'static str) {
+    if !COLLECTING.with(Cell::get) {
+        return;
+    }
+    COLLECTOR.with(|current| {
+        if let Some(collector) = current.borrow_mut().as_mut() {
+            let path = collector.path.iter().chain(path).cloned().collect();
+            collector.notices.push(DeprecationNotice { path, message });
+        }
+    });
+}
+
+/// Record the notice of the `proto` value at the `path`, unless it is the proto default, used by the
+/// derive macros
+#[doc(hidden)]
+pub fn deprecated<P: Default + PartialEq>(
+    path: &[PathSegment],
+    proto: P,
+    message: &'static str,
+) -> P {
+    if COLLECTING.with(Cell::get) && proto != P::default() {
+        record(path, message);
+    }
+    proto
+}
+
+/// Record the notice with the `message` at the path of the conversion, like of the lenient enum
+/// strings of the derive
+#[doc(hidden)]
+pub fn notice(message: &'static str) {
+    record(&[], message);
+}
+
+/// Convert the `proto` at the path `segment` with `convert`, tracking the path while collecting
+#[doc(hidden)]
+#[inline]
+pub fn scope<P, T>(
+    segment: impl FnOnce() -> PathSegment,
+    proto: P,
+    convert: impl FnOnce(P) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    if !COLLECTING.with(Cell::get) {
+        return crate::context::scope(proto, convert);
+    }
+    let push = |collector: &mut Collector| collector.path.push(segment());
+    COLLECTOR.with(|current| current.borrow_mut().as_mut().map(push));
+    let result = crate::context::scope(proto, convert);
+    COLLECTOR.with(|current| {
+        if let Some(collector) = current.borrow_mut().as_mut() {
+            collector.path.pop();
+        }
+    });
+    result
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::{collect, DeprecatedField};
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoItem {
+        sku: String,
+        legacy_sku: String,
+    }
+
+    impl_traits!(prelude);
+
+    impl FromProto for ProtoItem {
+        type Result = String;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            let field = DeprecatedField::new(self.legacy_sku, "use sku instead");
+            let legacy: String = FromProtoField::from_proto_field(field, "legacy_sku")?;
+            Ok(match self.sku.is_empty() {
+                true => legacy,
+                false => self.sku,
+            })
+        }
+    }
+
+    fn legacy(sku: &str) -> ProtoItem {
+        ProtoItem {
+            sku: String::new(),
+            legacy_sku: sku.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_present() {
+        let (sku, notices) = collect(|| legacy("A-1").from_proto());
+        assert_eq!(sku.unwrap(), "A-1");
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].path(), "legacy_sku");
+        assert_eq!(notices[0].message(), "use sku instead");
+        assert_eq!(
+            notices[0].to_string(),
+            "legacy_sku: The deprecated field, use sku instead"
+        );
+
+        let current = ProtoItem {
+            sku: "A-2".to_owned(),
+            legacy_sku: String::new(),
+        };
+        let (sku, notices) = collect(|| current.from_proto());
+        assert_eq!(sku.unwrap(), "A-2");
+        assert!(notices.is_empty());
+
+        let field = DeprecatedField::new(7, "use the string instead");
+        let (value, notices) = collect(|| field.record());
+        assert_eq!(value, 7);
+        assert_eq!(
+            notices[0].to_string(),
+            "The deprecated value, use the string instead"
+        );
+
+        // nothing is recorded without collecting
+        assert_eq!(legacy("A-3").from_proto().unwrap(), "A-3");
+    }
+
+    #[test]
+    fn test_containers() {
+        let items: Vec<_> = (0..5).map(|n| legacy(&n.to_string())).collect();
+        let (skus, notices) = collect(|| items.clone().from_proto());
+        assert_eq!(skus.unwrap().len(), 5);
+        let paths: Vec<_> = notices.iter().map(|notice| notice.path()).collect();
+        assert_eq!(
+            paths,
+            [
+                "[0].legacy_sku",
+                "[1].legacy_sku",
+                "[2].legacy_sku",
+                "[3].legacy_sku",
+                "[4].legacy_sku"
+            ]
+        );
+
+        // the outer collection gets the notices of the nested one
+        let (inner, outer) = collect(|| collect(|| items.from_proto()).1);
+        assert_eq!(inner, outer);
+    }
+}
@@ -0,0 +1,277 @@
+//! The dry run of the conversions, which checks the proto message would convert with `FromProto`,
+//! collecting the violations of all of its fields, without building the domain value. It is for the
+//! callers rejecting the invalid messages, like the admission webhooks, which need only the
+//! violations.
+//! ```ignore
+//! #[derive(FromProto, ValidateFromProto)]
+//! #[proto(type = "proto::Order")]
+//! struct Order {
+//!     id: OrderId,
+//!     customer: Customer,
+//!     lines: Vec<Line>,
+//! }
+//!
+//! let violations = proto.validate_proto();
+//! // [{"path": "id", ..}, {"path": "customer.email", ..}, {"path": "lines[2].quantity", ..}]
+//! ```
+//! The derived `ValidateFromProto` of the proto type checks every field with the same conversion as
+//! the derived `FromProto`, one violation per failed field, with its path from the message. The fields
+//! of the nested messages, of their lists and the optional ones, which derive `ValidateFromProto` too,
+//! are checked field by field, so their violations are all reported and their domain values are not
+//! built. The other fields are converted from their clones, giving the first error of the field.
+//! With the `validate` function of the struct, the checked value is converted, when its fields have
+//! no violation, as the function needs the domain value.
+//!
+//! A message without any violation converts with `FromProto`, and the failed conversion has the
+//! violation of its error among the violations, `assert_validate_consistent` of the `test-util`
+//! prelude asserts it for the property tests of the messages.
+
+use std::marker::PhantomData;
+
+use crate::error::PathSegment;
+use crate::violation::FieldViolation;
+
+/// The proto field of the type `P` checked as the field of the rust type `T`, the derived
+/// `ValidateFromProto` dispatches on it as `(&&Check::<T, _>::new(&field)).validate_field(..)`
+#[doc(hidden)]
+pub struct Check<'a, T, P>(pub &'a P, PhantomData<T>);
+
+impl<'a, T, P> Check<'a, T, P> {
+    pub fn new(proto: &'a P) -> Self {
+        Self(proto, PhantomData)
+    }
+}
+
+/// Add the violation of the failed conversion of the field
+#[doc(hidden)]
+pub fn check<T>(violations: &mut Vec<FieldViolation>, convert: impl FnOnce() -> anyhow::Result<T>) {
+    if let Err(error) = convert() {
+        violations.push(FieldViolation::of(&error));
+    }
+}
+
+/// Prepend the segment of the outer message to the paths of the nested violations
+#[doc(hidden)]
+pub fn nested(
+    violations: Vec<FieldViolation>,
+    segment: PathSegment,
+) -> impl Iterator<Item = FieldViolation> {
+    violations.into_iter().map(move |mut violation| {
+        let inner = std::mem::take(&mut violation.path);
+        violation.path = segment.to_string();
+        if !inner.is_empty() && !inner.starts_with('[') {
+            violation.path.push('.');
+        }
+        violation.path.push_str(&inner);
+        violation
+    })
+}
+
+/// `ValidateFromProto` and the checks of the fields of the derived one, this is part of the
+/// `impl_traits!(prelude)`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_dry_run {
+    (prelude) => {
+        pub use super::{__validate_field, ValidateFromProto};
+    };
+    () => {
+        /// Check the proto message would convert with `FromProto`, without converting it, see
+        /// `proto_conve::dry_run`
+        pub trait ValidateFromProto: FromProto {
+            /// The violations of all of the fields, none when `from_proto` succeeds
+            fn validate_proto(&self) -> Vec<$crate::violation::FieldViolation>;
+        }
+
+        /// The checks of the fields of the derived `ValidateFromProto`, from the most specific one
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        pub mod __validate_field {
+            use $crate::dry_run::{nested, Check};
+            use $crate::error::{missing_field, PathSegment};
+            use $crate::violation::FieldViolation;
+
+            use super::{FromProto, FromProtoField, ValidateFromProto};
+
+            /// The required nested message, checked field by field
+            pub trait ViaRequired {
+                fn validate_field(&self, name: &'static str, violations: &mut Vec<FieldViolation>);
+            }
+
+            impl<T, P: ValidateFromProto + FromProto<Result = T>> ViaRequired
+                for &Check<'_, T, Option<P>>
+            {
+                fn validate_field(&self, name: &'static str, violations: &mut Vec<FieldViolation>) {
+                    match self.0 {
+                        Some(proto) => violations
+                            .extend(nested(proto.validate_proto(), PathSegment::Field(name))),
+                        None => violations.push(FieldViolation::of(&missing_field(name))),
+                    }
+                }
+            }
+
+            /// The optional nested message, checked field by field, when it is present
+            pub trait ViaOptional {
+                fn validate_field(&self, name: &'static str, violations: &mut Vec<FieldViolation>);
+            }
+
+            impl<T, P: ValidateFromProto + FromProto<Result = T>> ViaOptional
+                for &Check<'_, Option<T>, Option<P>>
+            {
+                fn validate_field(&self, name: &'static str, violations: &mut Vec<FieldViolation>) {
+                    if let Some(proto) = self.0 {
+                        violations.extend(nested(proto.validate_proto(), PathSegment::Field(name)));
+                    }
+                }
+            }
+
+            /// The repeated nested messages, checked field by field
+            pub trait ViaRepeated {
+                fn validate_field(&self, name: &'static str, violations: &mut Vec<FieldViolation>);
+            }
+
+            impl<T, P: ValidateFromProto + FromProto<Result = T>> ViaRepeated
+                for &Check<'_, Vec<T>, Vec<P>>
+            {
+                fn validate_field(&self, name: &'static str, violations: &mut Vec<FieldViolation>) {
+                    for (index, proto) in self.0.iter().enumerate() {
+                        let element = nested(proto.validate_proto(), PathSegment::Index(index));
+                        violations.extend(nested(element.collect(), PathSegment::Field(name)));
+                    }
+                }
+            }
+
+            /// Any other field, converted from its clone
+            pub trait ViaConvert<M> {
+                fn validate_field(&self, name: &'static str, violations: &mut Vec<FieldViolation>);
+            }
+
+            impl<T, M, P: Clone + FromProtoField<T, M>> ViaConvert<M> for Check<'_, T, P> {
+                fn validate_field(&self, name: &'static str, violations: &mut Vec<FieldViolation>) {
+                    if let Err(error) = self.0.clone().from_proto_field(name) {
+                        violations.push(FieldViolation::of(&error));
+                    }
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+    use crate::violation::ErrorCode;
+
+    impl_traits!(prelude);
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoLine {
+        quantity: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoOrder {
+        id: i64,
+        line: Option<ProtoLine>,
+        lines: Vec<ProtoLine>,
+    }
+
+    type Count = crate::Bounded<u32, 1, 100>;
+
+    #[derive(Debug, PartialEq)]
+    pub struct Line {
+        quantity: Count,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Order {
+        id: Count,
+        line: Line,
+        lines: Vec<Line>,
+    }
+
+    impl FromProto for ProtoLine {
+        type Result = Line;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(Line {
+                quantity: self.quantity.from_proto_field("quantity")?,
+            })
+        }
+    }
+
+    impl FromProto for ProtoOrder {
+        type Result = Order;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(Order {
+                id: self.id.from_proto_field("id")?,
+                line: self.line.from_proto_field("line")?,
+                lines: self.lines.from_proto_field("lines")?,
+            })
+        }
+    }
+
+    /// As derived
+    impl ValidateFromProto for ProtoLine {
+        #[allow(clippy::needless_borrow)]
+        fn validate_proto(&self) -> Vec<FieldViolation> {
+            #[allow(unused_imports)]
+            use __validate_field::{ViaConvert, ViaOptional, ViaRepeated, ViaRequired};
+
+            let mut violations = Vec::new();
+            (&&Check::<Count, _>::new(&self.quantity)).validate_field("quantity", &mut violations);
+            violations
+        }
+    }
+
+    impl ValidateFromProto for ProtoOrder {
+        #[allow(clippy::needless_borrow)]
+        fn validate_proto(&self) -> Vec<FieldViolation> {
+            #[allow(unused_imports)]
+            use __validate_field::{ViaConvert, ViaOptional, ViaRepeated, ViaRequired};
+
+            let mut violations = Vec::new();
+            (&&Check::<Count, _>::new(&self.id)).validate_field("id", &mut violations);
+            (&&Check::<Line, _>::new(&self.line)).validate_field("line", &mut violations);
+            (&&Check::<Vec<Line>, _>::new(&self.lines)).validate_field("lines", &mut violations);
+            violations
+        }
+    }
+
+    #[test]
+    fn test_violations() {
+        let proto = ProtoOrder {
+            id: -1,
+            line: Some(ProtoLine { quantity: -2 }),
+            lines: vec![ProtoLine { quantity: 1 }, ProtoLine { quantity: -3 }],
+        };
+        let violations = proto.validate_proto();
+        let paths: Vec<_> = violations.iter().map(|violation| &violation.path).collect();
+        assert_eq!(paths, ["id", "line.quantity", "lines[1].quantity"]);
+        assert!(violations
+            .iter()
+            .all(|violation| violation.code == ErrorCode::OutOfRange));
+        // the conversion fails at the first one
+        let error = proto.from_proto().unwrap_err();
+        assert_eq!(FieldViolation::of(&error), violations[0]);
+
+        let violations = ProtoOrder::default().validate_proto();
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[1].path, "line");
+        assert_eq!(violations[1].code, ErrorCode::MissingField);
+    }
+
+    #[test]
+    fn test_valid() {
+        let proto = ProtoOrder {
+            id: 1,
+            line: Some(ProtoLine { quantity: 2 }),
+            lines: vec![ProtoLine { quantity: 3 }],
+        };
+        assert_eq!(proto.validate_proto(), []);
+        assert!(proto.from_proto().is_ok());
+    }
+}
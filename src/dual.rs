@@ -0,0 +1,298 @@
+//! The audit of the rewritten conversions, the old and the new conversion of the same proto value run
+//! side by side, the results are compared and the divergences are reported, while the result of the
+//! primary one, the old one by default, is used.
+//! ```ignore
+//! let user = dual_from_proto(proto, legacy::user_from_proto, FromProto::from_proto, &|divergence| {
+//!     tracing::warn!(%divergence, "the conversions of the user diverged");
+//! })?;
+//! ```
+//! The values are compared with `PartialEq`, the errors with their messages, with the paths, so an
+//! error of one conversion and a value of the other one diverge too. The divergences carry the values
+//! and the errors rendered with `Debug` and `{:#}`.
+//!
+//! The fields of the derived conversions are converted in the dual mode with
+//! `#[proto(dual = "path::to::old_fn")]`, the old function is primary, the new conversion is the one
+//! of the traits, and the divergences are reported to the observer installed with `set_observer`.
+//! Once there are none, the attribute is removed.
+
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// The conversion, whose result is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Primary {
+    /// The existing conversion
+    #[default]
+    Old,
+    /// The rewritten conversion
+    New,
+}
+
+/// How the results of the conversions diverged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DivergenceKind {
+    /// Both converted, in to the values, which are not equal
+    Value,
+    /// Only the old conversion failed
+    OldFailed,
+    /// Only the new conversion failed
+    NewFailed,
+    /// Both failed, with different errors
+    Error,
+}
+
+/// The results of the old and the new conversion, which diverged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    type_name: &'static str,
+    field: Option<&'static str>,
+    kind: DivergenceKind,
+    old: String,
+    new: String,
+}
+
+impl Divergence {
+    /// The proto type converted
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The proto field of the derived conversion, which diverged
+    pub fn field(&self) -> Option<&'static str> {
+        self.field
+    }
+
+    pub fn kind(&self) -> DivergenceKind {
+        self.kind
+    }
+
+    /// The rendered result of the old conversion
+    pub fn old_result(&self) -> &str {
+        &self.old
+    }
+
+    /// The rendered result of the new conversion
+    pub fn new_result(&self) -> &str {
+        &self.new
+    }
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The conversions of {}", self.type_name)?;
+        if let Some(field) = self.field {
+            write!(f, " in the field {field}")?;
+        }
+        write!(f, " diverged, old: {}, new: {}", self.old, self.new)
+    }
+}
+
+/// Notified about the divergences of the dual conversions
+pub trait DivergenceObserver {
+    fn on_divergence(&self, divergence: &Divergence);
+}
+
+impl<F: Fn(&Divergence)> DivergenceObserver for F {
+    fn on_divergence(&self, divergence: &Divergence) {
+        self(divergence)
+    }
+}
+
+/// The result rendered in to the divergence
+fn render<T: fmt::Debug>(result: &anyhow::Result<T>) -> String {
+    match result {
+        Ok(value) => format!("Ok({value:?})"),
+        Err(error) => format!("Err({error:#})"),
+    }
+}
+
+/// Compare the results, with the errors rendered first, as the primary one is returned
+fn compare<T: PartialEq + fmt::Debug>(
+    old: &anyhow::Result<T>,
+    new: &anyhow::Result<T>,
+) -> Option<DivergenceKind> {
+    match (old, new) {
+        (Ok(old), Ok(new)) if old == new => None,
+        (Ok(_), Ok(_)) => Some(DivergenceKind::Value),
+        (Err(_), Ok(_)) => Some(DivergenceKind::OldFailed),
+        (Ok(_), Err(_)) => Some(DivergenceKind::NewFailed),
+        (Err(old), Err(new)) if format!("{old:#}") == format!("{new:#}") => None,
+        (Err(_), Err(_)) => Some(DivergenceKind::Error),
+    }
+}
+
+/// The harness of the dual conversions, with the primary conversion and the observer of the
+/// divergences
+pub struct DualConvert<'a> {
+    primary: Primary,
+    observer: &'a dyn DivergenceObserver,
+    field: Option<&'static str>,
+}
+
+impl<'a> DualConvert<'a> {
+    /// Report the divergences to the `observer`, the old conversion is primary
+    pub fn new(observer: &'a dyn DivergenceObserver) -> Self {
+        Self {
+            primary: Primary::Old,
+            observer,
+            field: None,
+        }
+    }
+
+    /// Use the result of the `primary` conversion
+    pub fn primary(mut self, primary: Primary) -> Self {
+        self.primary = primary;
+        self
+    }
+
+    /// The proto field converted, reported in the divergences
+    pub fn field(mut self, field: &'static str) -> Self {
+        self.field = Some(field);
+        self
+    }
+
+    /// Convert the clone of the `proto` with the `old` and the `proto` with the `new` conversion,
+    /// report their divergence, and return the result of the primary one
+    pub fn from_proto<P: Clone, T: PartialEq + fmt::Debug>(
+        &self,
+        proto: P,
+        old: impl FnOnce(P) -> anyhow::Result<T>,
+        new: impl FnOnce(P) -> anyhow::Result<T>,
+    ) -> anyhow::Result<T> {
+        let old = old(proto.clone());
+        let new = new(proto);
+        if let Some(kind) = compare(&old, &new) {
+            self.observer.on_divergence(&Divergence {
+                type_name: std::any::type_name::<P>(),
+                field: self.field,
+                kind,
+                old: render(&old),
+                new: render(&new),
+            });
+        }
+        match self.primary {
+            Primary::Old => old,
+            Primary::New => new,
+        }
+    }
+}
+
+/// Convert the `proto` with the `old` and the `new` conversion, report their divergence to the
+/// `observer`, and return the result of the old one, see `DualConvert`
+pub fn dual_from_proto<P: Clone, T: PartialEq + fmt::Debug>(
+    proto: P,
+    old: impl FnOnce(P) -> anyhow::Result<T>,
+    new: impl FnOnce(P) -> anyhow::Result<T>,
+    observer: &dyn DivergenceObserver,
+) -> anyhow::Result<T> {
+    DualConvert::new(observer).from_proto(proto, old, new)
+}
+
+static OBSERVER: RwLock<Option<Arc<dyn DivergenceObserver + Send + Sync>>> = RwLock::new(None);
+
+/// Install the `observer` of the divergences of the derived fields, returning the one installed before
+pub fn set_observer(
+    observer: Arc<dyn DivergenceObserver + Send + Sync>,
+) -> Option<Arc<dyn DivergenceObserver + Send + Sync>> {
+    let mut current = OBSERVER.write().unwrap_or_else(|error| error.into_inner());
+    current.replace(observer)
+}
+
+/// Remove the observer of the divergences, returning it
+pub fn clear_observer() -> Option<Arc<dyn DivergenceObserver + Send + Sync>> {
+    let mut current = OBSERVER.write().unwrap_or_else(|error| error.into_inner());
+    current.take()
+}
+
+/// Convert the proto `field` of `#[proto(dual = "...")]`, the divergences are reported to the
+/// installed observer, without it only the old conversion runs
+#[doc(hidden)]
+pub fn dual_field<P: Clone, T: PartialEq + fmt::Debug>(
+    proto: P,
+    field: &'static str,
+    old: impl FnOnce(P) -> anyhow::Result<T>,
+    new: impl FnOnce(P) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    // not locked during the conversions, which can be dual too
+    let observer = OBSERVER
+        .read()
+        .unwrap_or_else(|error| error.into_inner())
+        .clone();
+    match observer {
+        Some(observer) => DualConvert::new(&*observer)
+            .field(field)
+            .from_proto(proto, old, new),
+        None => old(proto),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::{dual_from_proto, Divergence, DivergenceKind, DualConvert, Primary};
+
+    fn parse_old(value: String) -> anyhow::Result<u32> {
+        Ok(value.trim().parse()?)
+    }
+
+    fn parse_new(value: String) -> anyhow::Result<u32> {
+        Ok(value.parse()?)
+    }
+
+    #[test]
+    fn test_identical() {
+        let reports = RefCell::new(Vec::<Divergence>::new());
+        let observer = |divergence: &Divergence| reports.borrow_mut().push(divergence.clone());
+        let value = dual_from_proto("42".to_owned(), parse_old, parse_new, &observer).unwrap();
+        assert_eq!(value, 42);
+        // the same errors
+        assert!(dual_from_proto("x".to_owned(), parse_old, parse_new, &observer).is_err());
+        assert!(reports.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_value() {
+        let reports = RefCell::new(Vec::<Divergence>::new());
+        let observer = |divergence: &Divergence| reports.borrow_mut().push(divergence.clone());
+        let value = dual_from_proto(7u32, |value| Ok(value * 100), Ok, &observer);
+        assert_eq!(value.unwrap(), 700);
+
+        let reports = reports.into_inner();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].kind(), DivergenceKind::Value);
+        assert_eq!(reports[0].old_result(), "Ok(700)");
+        assert_eq!(reports[0].new_result(), "Ok(7)");
+        assert_eq!(
+            reports[0].to_string(),
+            "The conversions of u32 diverged, old: Ok(700), new: Ok(7)"
+        );
+    }
+
+    #[test]
+    fn test_error() {
+        let reports = RefCell::new(Vec::<Divergence>::new());
+        let observer = |divergence: &Divergence| reports.borrow_mut().push(divergence.clone());
+        let value = DualConvert::new(&observer)
+            .field("count")
+            .from_proto(" 42".to_owned(), parse_old, parse_new)
+            .unwrap();
+        assert_eq!(value, 42);
+        // the new one is primary
+        let error = DualConvert::new(&observer)
+            .primary(Primary::New)
+            .from_proto(" 42".to_owned(), parse_old, parse_new)
+            .unwrap_err();
+        assert_eq!(error.to_string(), "invalid digit found in string");
+
+        let reports = reports.into_inner();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].kind(), DivergenceKind::NewFailed);
+        assert_eq!(reports[0].field(), Some("count"));
+        // the type names are not stable
+        assert!(reports[0].to_string().ends_with(
+            "String in the field count diverged, old: Ok(42), new: Err(invalid digit found in string)"
+        ));
+        assert_eq!(reports[1].field(), None);
+    }
+}
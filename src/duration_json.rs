@@ -0,0 +1,195 @@
+//! The durations of the proto JSON mapping, like `"3.000000001s"` or `"-0.5s"`, carried in the proto
+//! `string` fields of the configs. They are converted in to `chrono::Duration` with
+//! `impl_traits!(String, chrono::Duration)`.
+//!
+//! The strings are an optional `-`, the seconds, an optional fraction of up to 9 digits and the `s`,
+//! anything else is an error. The durations are in the range of the spec, `±315576000000.999999999s`,
+//! the seconds and the nanos together, when they are read and written. The durations are written in
+//! the minimal form, without the trailing zeros of the fraction, the zero is `"0s"`.
+
+/// The seconds of 10000 years, the range of the proto durations
+pub const MAX_SECONDS: i64 = 315_576_000_000;
+
+/// The nanos of the longest proto duration, `315576000000.999999999s`
+const MAX_NANOS: i128 = MAX_SECONDS as i128 * 1_000_000_000 + 999_999_999;
+
+/// Whether the seconds and the nanos of the same sign are in the range of the spec
+pub fn in_range(seconds: i64, nanos: i32) -> bool {
+    (seconds as i128 * 1_000_000_000 + nanos as i128).abs() <= MAX_NANOS
+}
+
+/// The seconds and the nanos of the same sign clamped in to the range of the spec
+pub fn clamp(seconds: i64, nanos: i32) -> (i64, i32) {
+    match seconds as i128 * 1_000_000_000 + nanos as i128 {
+        nanos if nanos > MAX_NANOS => (MAX_SECONDS, 999_999_999),
+        nanos if nanos < -MAX_NANOS => (-MAX_SECONDS, -999_999_999),
+        _ => (seconds, nanos),
+    }
+}
+
+/// Parse the duration string in to the seconds and the nanos, which have the same sign
+pub fn parse(value: &str) -> anyhow::Result<(i64, i32)> {
+    let invalid = || anyhow::anyhow!("The duration {value:?} is not a valid proto JSON duration");
+    let (negative, unsigned) = match value.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, value),
+    };
+    let unsigned = unsigned.strip_suffix('s').ok_or_else(invalid)?;
+    let (seconds, fraction) = match unsigned.split_once('.') {
+        Some((seconds, fraction)) => (seconds, Some(fraction)),
+        None => (unsigned, None),
+    };
+    let digits = |digits: &str| !digits.is_empty() && digits.bytes().all(|c| c.is_ascii_digit());
+    if !digits(seconds) || !fraction.is_none_or(|fraction| digits(fraction) && fraction.len() <= 9)
+    {
+        return Err(invalid());
+    }
+    let out_of_range = || {
+        anyhow::anyhow!("The duration {value:?} is out of the range of ±{MAX_SECONDS}.999999999 s")
+    };
+    let seconds: i64 = seconds.parse().map_err(|_| out_of_range())?;
+    let nanos: i32 = match fraction {
+        Some(fraction) => format!("{fraction:0<9}").parse().map_err(|_| invalid())?,
+        None => 0,
+    };
+    let (seconds, nanos) = if negative {
+        (-seconds, -nanos)
+    } else {
+        (seconds, nanos)
+    };
+    if !in_range(seconds, nanos) {
+        return Err(out_of_range());
+    }
+    Ok((seconds, nanos))
+}
+
+/// Write the seconds and the nanos of the same sign in the minimal form
+pub fn format(seconds: i64, nanos: i32) -> String {
+    let sign = if seconds < 0 || nanos < 0 { "-" } else { "" };
+    let seconds = seconds.unsigned_abs();
+    match nanos.unsigned_abs() {
+        0 => format!("{sign}{seconds}s"),
+        nanos => {
+            let fraction = format!("{nanos:09}");
+            format!("{sign}{seconds}.{}s", fraction.trim_end_matches('0'))
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+    impl_traits!(String, chrono::Duration);
+
+    fn roundtrip(value: &str) -> chrono::Duration {
+        let duration = chrono::Duration::from_proto_alias(value.to_owned()).unwrap();
+        assert_eq!(ToProtoAlias::<String>::to_proto_alias(&duration), value);
+        duration
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        assert_eq!(roundtrip("-0.5s"), chrono::Duration::milliseconds(-500));
+        assert_eq!(
+            roundtrip("315576000000s"),
+            chrono::Duration::seconds(315_576_000_000)
+        );
+        assert_eq!(
+            roundtrip("-315576000000s"),
+            chrono::Duration::seconds(-315_576_000_000)
+        );
+        assert_eq!(
+            roundtrip("1.000000001s"),
+            chrono::Duration::nanoseconds(1_000_000_001)
+        );
+        assert_eq!(roundtrip("0s"), chrono::Duration::zero());
+        assert_eq!(roundtrip("3.05s"), chrono::Duration::milliseconds(3050));
+    }
+
+    #[test]
+    fn test_minimal_form() {
+        let duration = chrono::Duration::from_proto_alias("3.500s".to_owned()).unwrap();
+        assert_eq!(ToProtoAlias::<String>::to_proto_alias(&duration), "3.5s");
+        let zero = chrono::Duration::from_proto_alias("-0.000s".to_owned()).unwrap();
+        assert_eq!(ToProtoAlias::<String>::to_proto_alias(&zero), "0s");
+    }
+
+    #[test]
+    fn test_invalid() {
+        for value in [
+            "1.5",
+            "1s ",
+            " 1s",
+            "1.0000000001s",
+            "",
+            "s",
+            "-s",
+            ".5s",
+            "1.s",
+            "+1s",
+            "--1s",
+            "1e3s",
+            "1.5S",
+        ] {
+            let error = chrono::Duration::from_proto_alias(value.to_owned()).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("The duration {value:?} is not a valid proto JSON duration")
+            );
+        }
+        for value in ["315576000001s", "-315576000001s", "99999999999999999999s"] {
+            let error = chrono::Duration::from_proto_alias(value.to_owned()).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("The duration {value:?} is out of the range of ±315576000000.999999999 s")
+            );
+        }
+    }
+
+    #[test]
+    fn test_range() {
+        let max =
+            chrono::Duration::seconds(315_576_000_000) + chrono::Duration::nanoseconds(999_999_999);
+        assert_eq!(roundtrip("315576000000.999999999s"), max);
+        assert_eq!(roundtrip("-315576000000.999999999s"), -max);
+        assert_eq!(
+            roundtrip("315576000000.5s"),
+            chrono::Duration::seconds(315_576_000_000) + chrono::Duration::milliseconds(500)
+        );
+        assert!(!super::in_range(315_576_000_001, 0));
+        assert!(!super::in_range(-315_576_000_001, 0));
+        assert_eq!(
+            super::clamp(315_576_000_001, 5),
+            (315_576_000_000, 999_999_999)
+        );
+        assert_eq!(
+            super::clamp(-315_576_000_001, -5),
+            (-315_576_000_000, -999_999_999)
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "out of the range of the proto JSON durations")]
+    fn test_to_proto_out_of_range() {
+        let duration = chrono::Duration::seconds(315_576_000_001);
+        ToProtoAlias::<String>::to_proto_alias(&duration);
+    }
+
+    #[test]
+    #[cfg(not(debug_assertions))]
+    fn test_to_proto_clamped() {
+        let duration = chrono::Duration::seconds(315_576_000_001);
+        assert_eq!(
+            ToProtoAlias::<String>::to_proto_alias(&duration),
+            "315576000000.999999999s"
+        );
+        assert_eq!(
+            ToProtoAlias::<String>::to_proto_alias(&-duration),
+            "-315576000000.999999999s"
+        );
+    }
+}
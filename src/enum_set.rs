@@ -0,0 +1,413 @@
+//! The repeated enum fields, like `repeated Permission permissions`, converted in to the set of the
+//! variants, which is one `u64` of the bits at their proto values.
+//! ```ignore
+//! #[derive(Debug, Clone, Copy, PartialEq, Eq, FromProto, ToProto)]
+//! enum Permission {
+//!     Read = 0,
+//!     Write = 1,
+//!     Admin = 2,
+//! }
+//!
+//! #[derive(FromProto, ToProto)]
+//! #[proto(type = "proto::Role")]
+//! struct Role {
+//!     // repeated Permission permissions = 1;
+//!     permissions: EnumSet<Permission>,
+//! }
+//! ```
+//! The derived enum conversions with the `i32` values implement `EnumSetValue`, their values have to be
+//! in `0..64`, which is checked when the `EnumSet` is used. The duplicate values collapse in to one,
+//! `FromProtoLossy` reports them as `LossyEvent::Duplicate`. The unknown values of the `#[proto(open)]`
+//! enums are kept in the set and converted back, the ones of the other enums are an error, or dropped in
+//! the lenient `ConversionMode` and reported as `LossyEvent::UnknownEnum`. The set converts back in to
+//! the values sorted in the ascending order, the other producers may send them in any order.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use crate::enumeration::unknown_value;
+use crate::error::at_index;
+use crate::lossy::{ConversionReport, LossyEvent};
+use crate::mode::ConversionMode;
+
+/// The fieldless enum of the `i32` proto values, implemented by the derived enum conversions
+pub trait EnumSetValue: Sized {
+    /// The proto values of all the variants, each of them in `0..64`
+    const VALUES: &'static [i32];
+    /// The unknown values are kept, as the enum is `#[proto(open)]`
+    const OPEN: bool = false;
+
+    /// The proto value of the variant
+    fn to_value(&self) -> i32;
+
+    /// The variant of the proto value, `None` for the unknown values
+    fn from_value(value: i32) -> Option<Self>;
+}
+
+/// The set of the variants of the enum `E`
+pub struct EnumSet<E> {
+    bits: u64,
+    /// The unknown values of the open enums, sorted and without the duplicates
+    unknown: Vec<i32>,
+    values: PhantomData<E>,
+}
+
+impl<E: EnumSetValue> EnumSet<E> {
+    /// The values of the enum fit in the bits
+    const SMALL: () = {
+        let mut index = 0;
+        while index < E::VALUES.len() {
+            let value = E::VALUES[index];
+            assert!(
+                0 <= value && value < 64,
+                "the values of the `EnumSet` enums are in `0..64`"
+            );
+            index += 1;
+        }
+    };
+
+    /// The set without any variant
+    pub fn new() -> Self {
+        Self::with_bits(0)
+    }
+
+    fn with_bits(bits: u64) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::SMALL;
+        Self {
+            bits,
+            unknown: Vec::new(),
+            values: PhantomData,
+        }
+    }
+
+    fn bit(value: &E) -> u64 {
+        1 << value.to_value()
+    }
+
+    /// The set of the proto values, the unknown ones are kept for the open enums, otherwise they are
+    /// an error, or dropped in the lenient `mode`
+    pub fn from_values(values: &[i32], mode: ConversionMode) -> anyhow::Result<Self> {
+        Self::collect(values, mode, None)
+    }
+
+    fn collect(
+        values: &[i32],
+        mode: ConversionMode,
+        mut report: Option<&mut ConversionReport>,
+    ) -> anyhow::Result<Self> {
+        let mut set = Self::new();
+        for (index, &value) in values.iter().enumerate() {
+            let mut event = None;
+            match E::from_value(value) {
+                Some(known) => {
+                    if !set.insert(known) {
+                        event = Some(LossyEvent::Duplicate { raw: value.into() })
+                    }
+                }
+                None if E::OPEN => match set.unknown.binary_search(&value) {
+                    Ok(_) => event = Some(LossyEvent::Duplicate { raw: value.into() }),
+                    Err(position) => set.unknown.insert(position, value),
+                },
+                None if mode.is_lenient() => {
+                    event = Some(LossyEvent::UnknownEnum { raw: value.into() })
+                }
+                None => {
+                    return Err(at_index(
+                        unknown_value(value, std::any::type_name::<E>()),
+                        index,
+                    ))
+                }
+            }
+            if let (Some(report), Some(event)) = (report.as_deref_mut(), event) {
+                report.append(ConversionReport::event(event).at_index(index));
+            }
+        }
+        Ok(set)
+    }
+
+    /// The proto values of the set, in the ascending order
+    pub fn to_values(&self) -> Vec<i32> {
+        let mut values: Vec<i32> = self.iter().map(|value| value.to_value()).collect();
+        values.extend_from_slice(&self.unknown);
+        values.sort_unstable();
+        values
+    }
+
+    /// Whether the variant is in the set
+    pub fn contains(&self, value: &E) -> bool {
+        self.bits & Self::bit(value) != 0
+    }
+
+    /// Add the variant, `true` when it was not in the set
+    pub fn insert(&mut self, value: E) -> bool {
+        let inserted = !self.contains(&value);
+        self.bits |= Self::bit(&value);
+        inserted
+    }
+
+    /// Remove the variant, `true` when it was in the set
+    pub fn remove(&mut self, value: &E) -> bool {
+        let removed = self.contains(value);
+        self.bits &= !Self::bit(value);
+        removed
+    }
+
+    /// The unknown values of the open enum, in the ascending order
+    pub fn unknown(&self) -> &[i32] {
+        &self.unknown
+    }
+
+    /// The number of the variants and the unknown values
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize + self.unknown.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0 && self.unknown.is_empty()
+    }
+
+    /// The variants and the unknown values of either of the sets
+    pub fn union(&self, other: &Self) -> Self {
+        let mut set = Self::with_bits(self.bits | other.bits);
+        set.unknown = self.unknown.clone();
+        for value in &other.unknown {
+            if let Err(position) = set.unknown.binary_search(value) {
+                set.unknown.insert(position, *value);
+            }
+        }
+        set
+    }
+
+    /// The variants and the unknown values of both of the sets
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut set = Self::with_bits(self.bits & other.bits);
+        set.unknown = self
+            .unknown
+            .iter()
+            .filter(|value| other.unknown.binary_search(value).is_ok())
+            .copied()
+            .collect();
+        set
+    }
+
+    /// The known variants of the set, in the order of their proto values
+    pub fn iter(&self) -> impl Iterator<Item = E> + '_ {
+        (0..64)
+            .filter(|bit| self.bits & 1 << bit != 0)
+            .filter_map(E::from_value)
+    }
+}
+
+/// Convert the repeated enum values, used by the prelude
+#[doc(hidden)]
+pub fn from_proto_values<E: EnumSetValue>(
+    values: Vec<i32>,
+    mode: ConversionMode,
+) -> anyhow::Result<EnumSet<E>> {
+    EnumSet::from_values(&values, mode)
+}
+
+/// Convert the repeated enum values with the report of the duplicate and the dropped unknown values,
+/// used by the prelude
+#[doc(hidden)]
+pub fn from_proto_lossy<E: EnumSetValue>(
+    values: Vec<i32>,
+) -> anyhow::Result<(EnumSet<E>, ConversionReport)> {
+    let mut report = ConversionReport::new();
+    let set = EnumSet::collect(&values, ConversionMode::Lenient, Some(&mut report))?;
+    Ok((set, report))
+}
+
+impl<E> Clone for EnumSet<E> {
+    fn clone(&self) -> Self {
+        Self {
+            bits: self.bits,
+            unknown: self.unknown.clone(),
+            values: PhantomData,
+        }
+    }
+}
+
+impl<E> PartialEq for EnumSet<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits && self.unknown == other.unknown
+    }
+}
+
+impl<E> Eq for EnumSet<E> {}
+
+impl<E> Hash for EnumSet<E> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits.hash(state);
+        self.unknown.hash(state);
+    }
+}
+
+impl<E: EnumSetValue> Default for EnumSet<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: EnumSetValue + fmt::Debug> fmt::Debug for EnumSet<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(self.iter())
+            .entries(&self.unknown)
+            .finish()
+    }
+}
+
+impl<E: EnumSetValue> FromIterator<E> for EnumSet<E> {
+    fn from_iter<I: IntoIterator<Item = E>>(values: I) -> Self {
+        let mut set = Self::new();
+        set.extend(values);
+        set
+    }
+}
+
+impl<E: EnumSetValue> Extend<E> for EnumSet<E> {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, values: I) {
+        for value in values {
+            self.insert(value);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+    use crate::violation::ErrorCode;
+
+    impl_traits!(prelude);
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Permission {
+        Read = 0,
+        Write = 1,
+        Admin = 5,
+    }
+
+    impl EnumSetValue for Permission {
+        const VALUES: &'static [i32] = &[0, 1, 5];
+
+        fn to_value(&self) -> i32 {
+            *self as i32
+        }
+
+        fn from_value(value: i32) -> Option<Self> {
+            match value {
+                0 => Some(Permission::Read),
+                1 => Some(Permission::Write),
+                5 => Some(Permission::Admin),
+                _ => None,
+            }
+        }
+    }
+
+    /// The same values of the `#[proto(open)]` enum
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct OpenPermission(Permission);
+
+    impl EnumSetValue for OpenPermission {
+        const VALUES: &'static [i32] = Permission::VALUES;
+        const OPEN: bool = true;
+
+        fn to_value(&self) -> i32 {
+            self.0.to_value()
+        }
+
+        fn from_value(value: i32) -> Option<Self> {
+            Permission::from_value(value).map(OpenPermission)
+        }
+    }
+
+    type Permissions = EnumSet<Permission>;
+
+    #[test]
+    fn test_round_trip() {
+        let permissions = Permissions::from_proto_alias(vec![5, 0]).unwrap();
+        assert_eq!(
+            permissions.iter().collect::<Vec<_>>(),
+            vec![Permission::Read, Permission::Admin]
+        );
+        assert!(permissions.contains(&Permission::Admin));
+        assert!(!permissions.contains(&Permission::Write));
+        assert_eq!(permissions.len(), 2);
+        assert_eq!(
+            ToProtoAlias::<Vec<i32>>::to_proto_alias(&permissions),
+            vec![0, 5]
+        );
+        assert!(Permissions::from_proto_alias(vec![]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let permissions = Permissions::from_proto_alias(vec![1, 5, 1, 1]).unwrap();
+        assert_eq!(permissions.to_values(), vec![1, 5]);
+
+        let (permissions, report) = Permissions::from_proto_alias_lossy(vec![1, 5, 1]).unwrap();
+        assert_eq!(permissions.to_values(), vec![1, 5]);
+        assert_eq!(
+            report.events("[2]").collect::<Vec<_>>(),
+            vec![&LossyEvent::Duplicate { raw: 1 }]
+        );
+        assert_eq!(report.len(), 1);
+    }
+
+    #[test]
+    fn test_unknown() {
+        let error = Permissions::from_proto_alias(vec![0, 3]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "[1]: Unknown value 3 of {}",
+                std::any::type_name::<Permission>()
+            )
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::UnknownEnum);
+
+        let permissions =
+            Permissions::from_proto_alias_mode(vec![0, 3, 70], ConversionMode::Lenient).unwrap();
+        assert_eq!(permissions.to_values(), vec![0]);
+        let (_, report) = Permissions::from_proto_alias_lossy(vec![3, 0]).unwrap();
+        assert_eq!(
+            report.events("[0]").collect::<Vec<_>>(),
+            vec![&LossyEvent::UnknownEnum { raw: 3 }]
+        );
+
+        // the open enums keep the unknown values, also the ones out of the bits
+        let open = EnumSet::<OpenPermission>::from_proto_alias(vec![70, 1, -2, 70]).unwrap();
+        assert_eq!(open.unknown(), [-2, 70]);
+        assert_eq!(open.len(), 3);
+        assert_eq!(open.to_values(), vec![-2, 1, 70]);
+    }
+
+    #[test]
+    fn test_set_ops() {
+        let read_write: Permissions = [Permission::Write, Permission::Read].into_iter().collect();
+        let admin_write: Permissions = [Permission::Admin, Permission::Write].into_iter().collect();
+        assert_eq!(
+            read_write.union(&admin_write).iter().collect::<Vec<_>>(),
+            vec![Permission::Read, Permission::Write, Permission::Admin]
+        );
+        assert_eq!(read_write.intersection(&admin_write).to_values(), vec![1]);
+
+        let mut permissions = read_write.clone();
+        assert!(!permissions.insert(Permission::Read));
+        assert!(permissions.remove(&Permission::Read));
+        assert!(!permissions.remove(&Permission::Read));
+        assert_eq!(format!("{permissions:?}"), "{Write}");
+
+        let first =
+            EnumSet::<OpenPermission>::from_values(&[7, 9, 0], ConversionMode::Strict).unwrap();
+        let second =
+            EnumSet::<OpenPermission>::from_values(&[9, 8], ConversionMode::Strict).unwrap();
+        assert_eq!(first.union(&second).unknown(), [7, 8, 9]);
+        assert_eq!(first.intersection(&second).to_values(), vec![9]);
+    }
+}
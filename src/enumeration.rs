@@ -0,0 +1,256 @@
+//! The raw `i32` enum fields of prost, converted without the generated getters. The getter, like
+//! `fn status(&self) -> Status`, converts the unknown values in to the default variant, so they can
+//! not be told apart from the unspecified ones. `ProtoEnumField` converts them with the `TryFrom<i32>`
+//! of the prost enum, either failing on the unknown values, or keeping them in `OpenEnum`.
+//! ```ignore
+//! let status: Status = ProtoEnumField::new(proto.status).known()?;
+//! let status: OpenEnum<Status> = ProtoEnumField::new(proto.status).open();
+//! ```
+//! The derive converts the fields with `#[proto(enumeration = "path::Status")]` this way, the fields
+//! of `OpenEnum<Status>` keep the unknown values, the others fail on them.
+//!
+//! The derived enums with `#[proto(string)]` convert from and to the proto strings too, the canonical
+//! name of a variant is the screaming snake case of its name, as `IN_REVIEW`, or its
+//! `#[proto(name = "...")]`. The strings of the older clients are accepted with the aliases of the
+//! variants, `#[proto(alias = "enabled", alias = "on")]`, and with `#[proto(string,
+//! case_insensitive)]` in any ASCII case. `ToProtoAlias<String>` always writes the canonical name.
+//! ```ignore
+//! #[derive(FromProto, ToProto)]
+//! #[proto(string, case_insensitive)]
+//! enum Status {
+//!     #[proto(alias = "enabled", alias = "on")]
+//!     Active,
+//!     Inactive,
+//! }
+//!
+//! let (status, matched) = Status::from_proto_name("Enabled")?;
+//! if matched.is_lenient() { ... }
+//! ```
+//! The lenient matches, which are not the canonical name, record a `DeprecationNotice`, so the
+//! clients sending them are found with `deprecation::collect`, as the ones of the deprecated fields.
+//! The same alias of two variants does not compile.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::violation::{coded, ErrorCode};
+use crate::OpenEnum;
+
+/// The raw value of the proto enum field of the prost enum `E`
+pub struct ProtoEnumField<E> {
+    raw: i32,
+    enumeration: PhantomData<E>,
+}
+
+impl<E> Clone for ProtoEnumField<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for ProtoEnumField<E> {}
+
+impl<E> fmt::Debug for ProtoEnumField<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ProtoEnumField").field(&self.raw).finish()
+    }
+}
+
+impl<E: TryFrom<i32>> ProtoEnumField<E> {
+    /// The field of the `raw` value
+    pub fn new(raw: i32) -> Self {
+        Self {
+            raw,
+            enumeration: PhantomData,
+        }
+    }
+
+    /// The raw value
+    pub fn raw(self) -> i32 {
+        self.raw
+    }
+
+    /// The variant of the value, the unknown values are an error
+    pub fn known(self) -> anyhow::Result<E> {
+        E::try_from(self.raw).map_err(|_| unknown_value(self.raw, enum_name::<E>()))
+    }
+
+    /// The variant of the value, the unknown values are kept
+    pub fn open(self) -> OpenEnum<E> {
+        match E::try_from(self.raw) {
+            Ok(known) => OpenEnum::Known(known),
+            Err(_) => OpenEnum::Unknown(self.raw),
+        }
+    }
+}
+
+/// The name of the enum without its module path
+pub(crate) fn enum_name<E>() -> &'static str {
+    let name = std::any::type_name::<E>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Convert the raw value of the field, used by the derive
+#[doc(hidden)]
+pub fn from_proto<E: TryFrom<i32>>(raw: i32) -> anyhow::Result<E> {
+    ProtoEnumField::new(raw).known()
+}
+
+/// Convert the raw value of the open field, used by the derive
+#[doc(hidden)]
+pub fn from_proto_open<E: TryFrom<i32>>(raw: i32) -> anyhow::Result<OpenEnum<E>> {
+    Ok(ProtoEnumField::new(raw).open())
+}
+
+/// The raw value of the field, used by the derive
+#[doc(hidden)]
+pub fn to_proto<E: Copy + Into<i32>>(value: &E) -> i32 {
+    (*value).into()
+}
+
+/// The raw value of the open field, used by the derive
+#[doc(hidden)]
+pub fn to_proto_open<E: Copy + Into<i32>>(value: &OpenEnum<E>) -> i32 {
+    match value {
+        OpenEnum::Known(known) => (*known).into(),
+        OpenEnum::Unknown(raw) => *raw,
+    }
+}
+
+/// How the proto string matched the variant of the enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NameMatch {
+    /// The canonical name of the variant
+    Canonical,
+    /// One of the aliases of the variant
+    Alias,
+    /// The name or an alias in another case
+    CaseInsensitive,
+}
+
+impl NameMatch {
+    /// The string is not the canonical name, it is reported as deprecated
+    pub fn is_lenient(self) -> bool {
+        self != NameMatch::Canonical
+    }
+}
+
+/// The fieldless enum of the proto strings, implemented by the derived `FromProto` of the
+/// `#[proto(string)]` enums
+pub trait ProtoNamed: Sized {
+    /// The canonical names of the variants, in their order
+    const NAMES: &'static [&'static str];
+
+    /// The index of the canonical name of the variant in `NAMES`
+    fn name_index(&self) -> usize;
+
+    /// The variant of the proto string and how it matched, with the aliases and the case policy of
+    /// the enum, the lenient matches record the deprecation notice
+    fn match_name(value: &str) -> anyhow::Result<(Self, NameMatch)>;
+
+    /// The canonical name of the variant
+    fn canonical_name(&self) -> &'static str {
+        Self::NAMES[self.name_index()]
+    }
+}
+
+/// The names of a variant, the canonical one, the aliases and the message of the notice of the lenient
+/// matches
+#[doc(hidden)]
+pub type VariantNames = (&'static str, &'static [&'static str], &'static str);
+
+/// The index of the variant of the proto string in the `names`, used by the derive
+#[doc(hidden)]
+pub fn from_proto_name(
+    value: &str,
+    enum_name: &str,
+    names: &[VariantNames],
+    case_insensitive: bool,
+) -> anyhow::Result<(usize, NameMatch)> {
+    if let Some(index) = names.iter().position(|(name, _, _)| *name == value) {
+        return Ok((index, NameMatch::Canonical));
+    }
+    let lenient = |index: usize, matched| {
+        crate::deprecation::notice(names[index].2);
+        Ok((index, matched))
+    };
+    let alias = names
+        .iter()
+        .position(|(_, aliases, _)| aliases.contains(&value));
+    if let Some(index) = alias {
+        return lenient(index, NameMatch::Alias);
+    }
+    if case_insensitive {
+        let position = names.iter().position(|(name, aliases, _)| {
+            name.eq_ignore_ascii_case(value)
+                || aliases
+                    .iter()
+                    .any(|alias| alias.eq_ignore_ascii_case(value))
+        });
+        if let Some(index) = position {
+            return lenient(index, NameMatch::CaseInsensitive);
+        }
+    }
+    Err(coded(
+        ErrorCode::UnknownEnum,
+        format_args!("Unknown name {value:?} of {enum_name}"),
+    ))
+}
+
+/// The error of the unknown enum `value`, used by the derive macros
+#[doc(hidden)]
+pub fn unknown_value(value: impl fmt::Display, enum_name: &str) -> anyhow::Error {
+    coded(
+        ErrorCode::UnknownEnum,
+        format_args!("Unknown value {value} of {enum_name}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The enum as generated by prost
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(i32)]
+    enum Status {
+        Unspecified = 0,
+        Active = 1,
+    }
+
+    impl TryFrom<i32> for Status {
+        type Error = i32;
+
+        fn try_from(value: i32) -> Result<Self, i32> {
+            match value {
+                0 => Ok(Status::Unspecified),
+                1 => Ok(Status::Active),
+                value => Err(value),
+            }
+        }
+    }
+
+    impl From<Status> for i32 {
+        fn from(value: Status) -> i32 {
+            value as i32
+        }
+    }
+
+    #[test]
+    fn test_known() {
+        let field = ProtoEnumField::<Status>::new(1);
+        assert_eq!(field.known().unwrap(), Status::Active);
+        assert_eq!(field.open(), OpenEnum::Known(Status::Active));
+        assert_eq!(to_proto(&Status::Active), 1);
+    }
+
+    #[test]
+    fn test_unknown() {
+        let field = ProtoEnumField::<Status>::new(7);
+        let error = field.known().unwrap_err();
+        assert_eq!(error.to_string(), "Unknown value 7 of Status");
+        assert_eq!(field.open(), OpenEnum::Unknown(7));
+        assert_eq!(to_proto_open(&field.open()), 7);
+        assert_eq!(field.raw(), 7);
+    }
+}
@@ -0,0 +1,406 @@
+//! The envelopes of the messages tagged by their kind, like
+//! ```proto
+//! message Envelope {
+//!   string kind = 1;
+//!   bytes payload = 2;
+//! }
+//! ```
+//! which carry one of several messages, without `google.protobuf.Any` and `prost-types`. The kinds are
+//! registered in the `EnvelopeRegistry` with the decoding of their payloads and the conversions of
+//! the decoded messages in to the one domain type, usually the enum of the events.
+//! ```ignore
+//! let mut registry = EnvelopeRegistry::new();
+//! // `RegisterEnvelope` is a part of the prelude, with the `prost` feature
+//! registry.register_message::<proto::Created>("created", Event::Created);
+//! registry.insert("legacy.text", decode_text, |text| Ok(Event::Note(text)));
+//!
+//! let event = registry.from_proto_envelope(&envelope.kind, &envelope.payload)?;
+//! ```
+//! The unknown kinds, the payloads failing to decode and the failed conversions are the distinct
+//! `EnvelopeError`s with the kind. The domain values are packed back with `pack`, or
+//! `to_proto_envelope` of `ToProtoEnvelope` of the prelude, in to the kind and the encoded payload.
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// The decoding and the conversion of the payload of one kind
+type Unpack<T> = Box<dyn Fn(&str, &[u8]) -> Result<T, EnvelopeError> + Send + Sync>;
+
+/// The conversion of the envelope failed
+#[derive(Debug)]
+pub enum EnvelopeError {
+    /// No kind of the name is registered
+    UnknownKind {
+        /// The kind of the envelope
+        kind: String,
+    },
+    /// The payload is not the encoded message of the kind
+    Decode {
+        /// The kind of the envelope
+        kind: String,
+        /// The error of the decoding
+        error: anyhow::Error,
+    },
+    /// The decoded message failed to convert
+    Conversion {
+        /// The kind of the envelope
+        kind: String,
+        /// The error of the conversion, with the path of the failed field
+        error: anyhow::Error,
+    },
+}
+
+impl EnvelopeError {
+    /// The kind of the envelope
+    pub fn kind(&self) -> &str {
+        match self {
+            EnvelopeError::UnknownKind { kind }
+            | EnvelopeError::Decode { kind, .. }
+            | EnvelopeError::Conversion { kind, .. } => kind,
+        }
+    }
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvelopeError::UnknownKind { kind } => {
+                write!(f, "The envelope kind {kind:?} is not registered")
+            }
+            EnvelopeError::Decode { kind, error } => {
+                write!(
+                    f,
+                    "The payload of the envelope kind {kind:?} failed to decode: {error:#}"
+                )
+            }
+            EnvelopeError::Conversion { kind, error } => {
+                write!(
+                    f,
+                    "The payload of the envelope kind {kind:?} failed to convert: {error:#}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for EnvelopeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EnvelopeError::UnknownKind { .. } => None,
+            EnvelopeError::Decode { error, .. } | EnvelopeError::Conversion { error, .. } => {
+                Some(error.as_ref())
+            }
+        }
+    }
+}
+
+/// The conversions of the envelopes in to `T` by their kinds
+pub struct EnvelopeRegistry<T> {
+    kinds: HashMap<String, Unpack<T>>,
+}
+
+impl<T> Default for EnvelopeRegistry<T> {
+    fn default() -> Self {
+        Self {
+            kinds: HashMap::new(),
+        }
+    }
+}
+
+impl<T: 'static> EnvelopeRegistry<T> {
+    /// The registry without any kinds
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the kind, its payloads are decoded with `decode` and the messages converted with
+    /// `convert`, replacing the previous registration of the kind
+    pub fn insert<P: 'static>(
+        &mut self,
+        kind: impl Into<String>,
+        decode: impl Fn(&[u8]) -> anyhow::Result<P> + Send + Sync + 'static,
+        convert: impl Fn(P) -> anyhow::Result<T> + Send + Sync + 'static,
+    ) {
+        let unpack = move |kind: &str, payload: &[u8]| {
+            let message = decode(payload).map_err(|error| EnvelopeError::Decode {
+                kind: kind.to_owned(),
+                error,
+            })?;
+            convert(message).map_err(|error| EnvelopeError::Conversion {
+                kind: kind.to_owned(),
+                error,
+            })
+        };
+        self.kinds.insert(kind.into(), Box::new(unpack));
+    }
+
+    /// Register the kind of the prost message `P`, converted with `convert`, see `RegisterEnvelope` of
+    /// the prelude for the `FromProto` ones
+    #[cfg(feature = "prost")]
+    pub fn insert_prost<P: prost::Message + Default + 'static>(
+        &mut self,
+        kind: impl Into<String>,
+        convert: impl Fn(P) -> anyhow::Result<T> + Send + Sync + 'static,
+    ) {
+        self.insert(kind, |payload| Ok(P::decode(payload)?), convert);
+    }
+
+    /// The kind is registered
+    pub fn contains(&self, kind: &str) -> bool {
+        self.kinds.contains_key(kind)
+    }
+
+    /// The registered kinds, in no particular order
+    pub fn kinds(&self) -> impl Iterator<Item = &str> {
+        self.kinds.keys().map(String::as_str)
+    }
+
+    /// Decode the payload of the kind and convert it, the errors are `EnvelopeError`
+    pub fn from_proto_envelope(&self, kind: &str, payload: &[u8]) -> anyhow::Result<T> {
+        let unpack = self
+            .kinds
+            .get(kind)
+            .ok_or_else(|| EnvelopeError::UnknownKind {
+                kind: kind.to_owned(),
+            })?;
+        Ok(unpack(kind, payload)?)
+    }
+}
+
+impl<T> fmt::Debug for EnvelopeRegistry<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.kinds.keys()).finish()
+    }
+}
+
+/// The kind and the encoded payload of the envelope of the prost message
+#[cfg(feature = "prost")]
+pub fn pack<M: prost::Message>(kind: impl Into<String>, message: &M) -> (String, Vec<u8>) {
+    (kind.into(), message.encode_to_vec())
+}
+
+/// Generate the envelopes of the `FromProto` and `ToProto` prost messages, this is part of the
+/// `impl_traits!(prelude)`.
+#[cfg(feature = "prost")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_envelope {
+    (prelude) => {
+        pub use super::{RegisterEnvelope, ToProtoEnvelope};
+    };
+    () => {
+        /// Register the kinds of the prost messages converted with `FromProto`, see
+        /// `proto_conve::envelope`
+        #[allow(dead_code)]
+        pub trait RegisterEnvelope<T> {
+            /// Register the kind of the message `P`, its domain value is wrapped in to `T` with `wrap`,
+            /// like the variant of the enum of the events
+            fn register_message<P>(
+                &mut self,
+                kind: impl Into<String>,
+                wrap: impl Fn(P::Result) -> T + Send + Sync + 'static,
+            ) where
+                P: FromProto + $crate::__private::prost::Message + Default + 'static;
+        }
+
+        impl<T: 'static> RegisterEnvelope<T> for $crate::envelope::EnvelopeRegistry<T> {
+            fn register_message<P>(
+                &mut self,
+                kind: impl Into<String>,
+                wrap: impl Fn(P::Result) -> T + Send + Sync + 'static,
+            ) where
+                P: FromProto + $crate::__private::prost::Message + Default + 'static,
+            {
+                self.insert_prost(kind, move |proto: P| proto.from_proto().map(&wrap));
+            }
+        }
+
+        /// Pack the value converted in to the prost message in to the envelope
+        #[allow(dead_code)]
+        pub trait ToProtoEnvelope {
+            /// The kind and the encoded payload of the value
+            fn to_proto_envelope(&self, kind: impl Into<String>) -> (String, Vec<u8>);
+        }
+
+        impl<T> ToProtoEnvelope for T
+        where
+            T: ToProto,
+            T::Result: $crate::__private::prost::Message,
+        {
+            fn to_proto_envelope(&self, kind: impl Into<String>) -> (String, Vec<u8>) {
+                $crate::envelope::pack(kind, &self.to_proto())
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::violation::ErrorCode;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Event {
+        Note(String),
+        Tick(u32),
+    }
+
+    fn decode_text(payload: &[u8]) -> anyhow::Result<String> {
+        Ok(std::str::from_utf8(payload)?.to_owned())
+    }
+
+    fn decode_tick(payload: &[u8]) -> anyhow::Result<u32> {
+        let bytes: [u8; 4] = payload.try_into()?;
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    fn registry() -> EnvelopeRegistry<Event> {
+        let mut registry = EnvelopeRegistry::new();
+        registry.insert("note", decode_text, |text| Ok(Event::Note(text)));
+        registry.insert("tick", decode_tick, |tick| {
+            anyhow::ensure!(tick > 0, "The tick is zero");
+            Ok(Event::Tick(tick))
+        });
+        registry
+    }
+
+    fn envelope_error(error: anyhow::Error) -> EnvelopeError {
+        error.downcast().unwrap()
+    }
+
+    #[test]
+    fn test_kinds() {
+        let registry = registry();
+        let event = registry.from_proto_envelope("note", b"hello").unwrap();
+        assert_eq!(event, Event::Note("hello".to_owned()));
+        let event = registry
+            .from_proto_envelope("tick", &7u32.to_be_bytes())
+            .unwrap();
+        assert_eq!(event, Event::Tick(7));
+
+        let mut kinds: Vec<_> = registry.kinds().collect();
+        kinds.sort();
+        assert_eq!(kinds, ["note", "tick"]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let registry = registry();
+        let error = registry.from_proto_envelope("alarm", b"").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The envelope kind \"alarm\" is not registered"
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::UnknownEnum);
+        assert!(
+            matches!(envelope_error(error), EnvelopeError::UnknownKind { kind } if kind == "alarm")
+        );
+
+        let error = registry
+            .from_proto_envelope("note", &[0xff, 0xfe])
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The payload of the envelope kind \"note\" failed to decode: invalid utf-8 sequence of 1 \
+             bytes from index 0"
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::InvalidFormat);
+        assert_eq!(envelope_error(error).kind(), "note");
+
+        let error = registry
+            .from_proto_envelope("tick", &0u32.to_be_bytes())
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The payload of the envelope kind \"tick\" failed to convert: The tick is zero"
+        );
+        assert!(matches!(
+            envelope_error(error),
+            EnvelopeError::Conversion { .. }
+        ));
+    }
+
+    #[cfg(feature = "prost")]
+    mod prost_messages {
+        use super::super::*;
+        use crate::impl_traits;
+
+        impl_traits!(prelude);
+
+        #[derive(Clone, PartialEq, prost::Message)]
+        pub struct ProtoCreated {
+            #[prost(string, tag = "1")]
+            name: String,
+        }
+
+        #[derive(Clone, PartialEq, prost::Message)]
+        pub struct ProtoDeleted {
+            #[prost(uint64, tag = "1")]
+            id: u64,
+        }
+
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Event {
+            Created(String),
+            Deleted(u64),
+        }
+
+        impl FromProto for ProtoCreated {
+            type Result = String;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                anyhow::ensure!(!self.name.is_empty(), "The name is empty");
+                Ok(self.name)
+            }
+        }
+
+        impl FromProto for ProtoDeleted {
+            type Result = u64;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                Ok(self.id)
+            }
+        }
+
+        impl ToProto for Event {
+            type Result = ProtoCreated;
+
+            fn to_proto(&self) -> Self::Result {
+                match self {
+                    Event::Created(name) => ProtoCreated { name: name.clone() },
+                    Event::Deleted(_) => ProtoCreated::default(),
+                }
+            }
+        }
+
+        #[test]
+        fn test_prost_kinds() {
+            let mut registry = EnvelopeRegistry::new();
+            registry.register_message::<ProtoCreated>("created", Event::Created);
+            registry.register_message::<ProtoDeleted>("deleted", Event::Deleted);
+
+            let (kind, payload) = Event::Created("job".to_owned()).to_proto_envelope("created");
+            let event = registry.from_proto_envelope(&kind, &payload).unwrap();
+            assert_eq!(event, Event::Created("job".to_owned()));
+            let (kind, payload) = pack("deleted", &ProtoDeleted { id: 4 });
+            let event = registry.from_proto_envelope(&kind, &payload).unwrap();
+            assert_eq!(event, Event::Deleted(4));
+
+            // the truncated length of the string
+            let error = registry
+                .from_proto_envelope("created", &[0x0a, 0x05])
+                .unwrap_err();
+            assert!(matches!(
+                error.downcast_ref::<EnvelopeError>(),
+                Some(EnvelopeError::Decode { .. })
+            ));
+            let (kind, payload) = Event::Created(String::new()).to_proto_envelope("created");
+            let error = registry.from_proto_envelope(&kind, &payload).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "The payload of the envelope kind \"created\" failed to convert: The name is empty"
+            );
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Converting the proto messages behind the trait objects, like in the plugins registered at runtime.
+//! `FromProto` has the associated types and consumes the value, so it is not object safe, instead the
+//! conversions are registered in the `ProtoRegistry` by the proto type names and they convert the
+//! boxed values.
+//! ```ignore
+//! let mut registry = ProtoRegistry::new();
+//! // `RegisterFromProto` is a part of the prelude
+//! registry.register::<proto::Event>("my.proto.Event");
+//!
+//! let event = registry.convert("my.proto.Event", Box::new(proto))?;
+//! let event: Box<Event> = event.downcast().unwrap();
+//! ```
+//! The unknown type names, the values of other types and the failed conversions are distinct
+//! `ErasedError`s.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The boxed proto message or domain value
+pub type ErasedValue = Box<dyn Any + Send>;
+
+/// The conversion from the boxed proto message in to the boxed domain value
+pub trait ErasedFromProto: Send + Sync {
+    /// The name of the proto type, it converts from
+    fn proto_type_name(&self) -> &'static str;
+
+    /// The name of the domain type, it converts in to
+    fn domain_type_name(&self) -> &'static str;
+
+    /// Convert the boxed proto message
+    fn convert(&self, name: &str, proto: ErasedValue) -> Result<ErasedValue, ErasedError>;
+}
+
+/// The `ErasedFromProto` of the conversion function, the prelude registers `FromProto::from_proto`
+pub struct Converter<P, T> {
+    convert: fn(P) -> anyhow::Result<T>,
+}
+
+impl<P, T> Converter<P, T> {
+    /// The converter calling `convert`
+    pub fn new(convert: fn(P) -> anyhow::Result<T>) -> Self {
+        Self { convert }
+    }
+}
+
+impl<P: Any + Send, T: Any + Send> ErasedFromProto for Converter<P, T> {
+    fn proto_type_name(&self) -> &'static str {
+        std::any::type_name::<P>()
+    }
+
+    fn domain_type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn convert(&self, name: &str, proto: ErasedValue) -> Result<ErasedValue, ErasedError> {
+        let proto = proto.downcast::<P>().map_err(|_| ErasedError::Downcast {
+            name: name.to_owned(),
+            expected: self.proto_type_name(),
+        })?;
+        match (self.convert)(*proto) {
+            Ok(value) => Ok(Box::new(value)),
+            Err(error) => Err(ErasedError::Conversion {
+                name: name.to_owned(),
+                error,
+            }),
+        }
+    }
+}
+
+/// The conversion through the erased interface failed
+#[derive(Debug)]
+pub enum ErasedError {
+    /// No conversion is registered for the type name
+    UnknownType {
+        /// The proto type name
+        name: String,
+    },
+    /// The boxed value is not of the registered proto type
+    Downcast {
+        /// The proto type name
+        name: String,
+        /// The registered proto type
+        expected: &'static str,
+    },
+    /// The proto message failed to convert
+    Conversion {
+        /// The proto type name
+        name: String,
+        /// The error of the conversion
+        error: anyhow::Error,
+    },
+}
+
+impl fmt::Display for ErasedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErasedError::UnknownType { name } => {
+                write!(f, "No conversion is registered for the proto type {name}")
+            }
+            ErasedError::Downcast { name, expected } => write!(
+                f,
+                "The value converted as the proto type {name} is not {expected}"
+            ),
+            ErasedError::Conversion { name, error } => {
+                write!(f, "The proto type {name} failed to convert: {error:#}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ErasedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ErasedError::Conversion { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// The conversions by the proto type names
+#[derive(Default)]
+pub struct ProtoRegistry {
+    converters: HashMap<String, Box<dyn ErasedFromProto>>,
+}
+
+impl ProtoRegistry {
+    /// The registry without any conversions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the conversion of the proto type name, replacing the previous one
+    pub fn insert(&mut self, name: impl Into<String>, converter: Box<dyn ErasedFromProto>) {
+        self.converters.insert(name.into(), converter);
+    }
+
+    /// Register the conversion function of the proto type name
+    pub fn insert_fn<P: Any + Send, T: Any + Send>(
+        &mut self,
+        name: impl Into<String>,
+        convert: fn(P) -> anyhow::Result<T>,
+    ) {
+        self.insert(name, Box::new(Converter::new(convert)));
+    }
+
+    /// The conversion of the proto type name
+    pub fn get(&self, name: &str) -> Option<&dyn ErasedFromProto> {
+        self.converters.get(name).map(|converter| &**converter)
+    }
+
+    /// There is a conversion of the proto type name
+    pub fn contains(&self, name: &str) -> bool {
+        self.converters.contains_key(name)
+    }
+
+    /// The registered proto type names, in no particular order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.converters.keys().map(String::as_str)
+    }
+
+    /// Convert the boxed proto message of the type name in to the boxed domain value
+    pub fn convert(&self, name: &str, proto: ErasedValue) -> Result<ErasedValue, ErasedError> {
+        match self.converters.get(name) {
+            Some(converter) => converter.convert(name, proto),
+            None => Err(ErasedError::UnknownType {
+                name: name.to_owned(),
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for ProtoRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.converters.iter().map(|(name, converter)| {
+                (
+                    name,
+                    (converter.proto_type_name(), converter.domain_type_name()),
+                )
+            }))
+            .finish()
+    }
+}
+
+/// Generate the registering of the conversions, this is part of the `impl_traits!(prelude)`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_erased {
+    (prelude) => {
+        pub use super::RegisterFromProto;
+    };
+    () => {
+        /// Register the `FromProto` conversions in the `proto_conve::erased::ProtoRegistry`
+        #[allow(dead_code)]
+        pub trait RegisterFromProto {
+            /// Register `FromProto` of the proto type `P` by its type name
+            fn register<P>(&mut self, name: impl Into<String>)
+            where
+                P: FromProto + Send + 'static,
+                P::Result: Send + 'static;
+        }
+
+        impl RegisterFromProto for $crate::erased::ProtoRegistry {
+            fn register<P>(&mut self, name: impl Into<String>)
+            where
+                P: FromProto + Send + 'static,
+                P::Result: Send + 'static,
+            {
+                self.insert_fn(name, <P as FromProto>::from_proto);
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoEvent {
+        name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Event {
+        name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoCount {
+        value: i64,
+    }
+
+    impl_traits!(prelude);
+
+    impl FromProto for ProtoEvent {
+        type Result = Event;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            anyhow::ensure!(!self.name.is_empty(), "The name is empty");
+            Ok(Event { name: self.name })
+        }
+    }
+
+    impl FromProto for ProtoCount {
+        type Result = u32;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(u32::try_from(self.value)?)
+        }
+    }
+
+    fn registry() -> ProtoRegistry {
+        let mut registry = ProtoRegistry::new();
+        registry.register::<ProtoEvent>("my.proto.Event");
+        registry.register::<ProtoCount>("my.proto.Count");
+        registry
+    }
+
+    #[test]
+    fn test_convert() {
+        let registry = registry();
+        let proto = ProtoEvent {
+            name: "start".to_owned(),
+        };
+        let event = registry.convert("my.proto.Event", Box::new(proto)).unwrap();
+        assert_eq!(
+            *event.downcast::<Event>().unwrap(),
+            Event {
+                name: "start".to_owned()
+            }
+        );
+        let count = registry
+            .convert("my.proto.Count", Box::new(ProtoCount { value: 3 }))
+            .unwrap();
+        assert_eq!(*count.downcast::<u32>().unwrap(), 3);
+
+        let mut names: Vec<_> = registry.names().collect();
+        names.sort();
+        assert_eq!(names, ["my.proto.Count", "my.proto.Event"]);
+        let converter = registry.get("my.proto.Count").unwrap();
+        assert_eq!(converter.domain_type_name(), "u32");
+    }
+
+    #[test]
+    fn test_errors() {
+        let registry = registry();
+        let error = registry
+            .convert("my.proto.Event", Box::new(ProtoCount { value: 3 }))
+            .unwrap_err();
+        assert!(matches!(error, ErasedError::Downcast { .. }));
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "The value converted as the proto type my.proto.Event is not {}",
+                std::any::type_name::<ProtoEvent>()
+            )
+        );
+
+        let error = registry
+            .convert("my.proto.Other", Box::new(ProtoCount { value: 3 }))
+            .unwrap_err();
+        assert!(matches!(error, ErasedError::UnknownType { .. }));
+        assert_eq!(
+            error.to_string(),
+            "No conversion is registered for the proto type my.proto.Other"
+        );
+
+        let error = registry
+            .convert("my.proto.Count", Box::new(ProtoCount { value: -1 }))
+            .unwrap_err();
+        assert!(matches!(error, ErasedError::Conversion { .. }));
+        assert_eq!(
+            error.to_string(),
+            "The proto type my.proto.Count failed to convert: out of range integral type conversion \
+             attempted"
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Conversion errors carrying the path to the field, which failed to convert.
+
+use std::fmt;
+
+/// One step of the path to the failed field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    /// Named field of a message
+    Field(&'static str),
+    /// Element of a repeated field
+    Index(usize),
+    /// Entry of a map field
+    Key(String),
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathSegment::Field(name) => write!(f, "{name}"),
+            PathSegment::Index(index) => write!(f, "[{index}]"),
+            PathSegment::Key(key) => write!(f, "[{key:?}]"),
+        }
+    }
+}
+
+/// Render the segments from the outermost one with dots between the fields
+pub(crate) fn path_string<'a>(segments: impl Iterator<Item = &'a PathSegment>) -> String {
+    let mut result = String::new();
+    for segment in segments {
+        if !result.is_empty() && matches!(segment, PathSegment::Field(_)) {
+            result.push('.');
+        }
+        result.push_str(&segment.to_string());
+    }
+    result
+}
+
+/// The conversion failed at some field, the error keeps the path to it from the outermost message.
+#[derive(Debug)]
+pub struct ConversionError {
+    /// The segments are stored from the innermost one, so wrapping the error in an outer field is cheap
+    pub(crate) path: Vec<PathSegment>,
+    /// The reason of the failure
+    reason: anyhow::Error,
+    /// The redacted rendering of the innermost proto message, which failed to convert, attached in
+    /// `redact::attach_to_errors`
+    rendered: Option<String>,
+    /// The count of the nested conversions of the thread, when the segments of the `context::ConvScope`
+    /// were added, so they are added only once
+    #[cfg(feature = "std")]
+    pub(crate) scoped_frame: Option<usize>,
+}
+
+impl ConversionError {
+    /// Create the error without any path yet
+    pub fn new(reason: anyhow::Error) -> Self {
+        Self {
+            path: Vec::new(),
+            reason,
+            rendered: None,
+            #[cfg(feature = "std")]
+            scoped_frame: None,
+        }
+    }
+
+    /// The path to the failed field, from the outermost message
+    pub fn path(&self) -> impl Iterator<Item = &PathSegment> {
+        self.path.iter().rev()
+    }
+
+    /// Render the path with dots between the fields, e.g. `items[3].price`
+    pub fn path_string(&self) -> String {
+        path_string(self.path())
+    }
+
+    /// The reason of the failure
+    pub fn reason(&self) -> &anyhow::Error {
+        &self.reason
+    }
+
+    /// The redacted rendering of the innermost proto message, which failed to convert, if it was attached
+    pub fn rendered(&self) -> Option<&str> {
+        self.rendered.as_deref()
+    }
+
+    /// Prepend the segment of the outer message to the path
+    pub fn push(mut self, segment: PathSegment) -> Self {
+        self.path.push(segment);
+        self
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path_string())?;
+        }
+        write!(f, "{:#}", self.reason)?;
+        match &self.rendered {
+            Some(rendered) => write!(f, ", in {rendered}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// Add the segment to the path of the error, if the error is not a `ConversionError` yet, it is wrapped in
+/// to one. The `ConversionError` is updated in place, creating a new `anyhow::Error` on every level of
+/// a deep message would capture the backtrace again, which is as deep as the message. The segments of
+/// the live `context::ConvScope` are added around the segment.
+pub fn with_segment(mut error: anyhow::Error, segment: PathSegment) -> anyhow::Error {
+    match error.downcast_mut::<ConversionError>() {
+        Some(conversion) => {
+            conversion.path.push(segment);
+            crate::context::attach(conversion);
+            error
+        }
+        None => {
+            let mut conversion = ConversionError::new(error).push(segment);
+            crate::context::attach(&mut conversion);
+            conversion.into()
+        }
+    }
+}
+
+/// Add the segments of the live `context::ConvScope` to the path of the error, which did not cross any
+/// of the wrapping helpers in them
+pub fn scoped(mut error: anyhow::Error) -> anyhow::Error {
+    match error.downcast_mut::<ConversionError>() {
+        Some(conversion) => {
+            crate::context::attach(conversion);
+            error
+        }
+        None => {
+            let mut conversion = ConversionError::new(error);
+            crate::context::attach(&mut conversion);
+            conversion.into()
+        }
+    }
+}
+
+/// Attach the redacted `rendered` proto message to the error, unless the error of a message nested in it
+/// has its own already
+pub(crate) fn with_rendered(mut error: anyhow::Error, rendered: String) -> anyhow::Error {
+    match error.downcast_mut::<ConversionError>() {
+        Some(conversion) => {
+            conversion.rendered.get_or_insert(rendered);
+            error
+        }
+        None => {
+            let mut conversion = ConversionError::new(error);
+            conversion.rendered = Some(rendered);
+            conversion.into()
+        }
+    }
+}
+
+/// The error happened while converting the field `name`
+pub fn in_field(error: anyhow::Error, name: &'static str) -> anyhow::Error {
+    with_segment(error, PathSegment::Field(name))
+}
+
+/// The error happened while converting a field nested in the `fields`, given from the outermost
+pub fn in_fields(error: anyhow::Error, fields: &[&'static str]) -> anyhow::Error {
+    fields
+        .iter()
+        .rev()
+        .fold(error, |error, name| in_field(error, name))
+}
+
+/// Run the conversion of a value nested in the `fields`, its errors get the fields in their path
+pub fn nested<T>(
+    fields: &[&'static str],
+    convert: impl FnOnce() -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    convert().map_err(|error| in_fields(error, fields))
+}
+
+/// The error happened while converting the element at `index`
+pub fn at_index(error: anyhow::Error, index: usize) -> anyhow::Error {
+    with_segment(error, PathSegment::Index(index))
+}
+
+/// The error happened while converting the map entry with the `key`
+pub fn at_key(error: anyhow::Error, key: &impl fmt::Display) -> anyhow::Error {
+    with_segment(error, PathSegment::Key(key.to_string()))
+}
+
+/// The required field `name` is missing
+pub fn missing_field(name: &'static str) -> anyhow::Error {
+    MissingField::new(name).into()
+}
+
+/// The required field is missing, the error without any allocation of the hot paths checking the
+/// fields with `required_lite` of the prelude. It becomes the `ConversionError` with the field in the
+/// path, when it is converted in to `anyhow::Error` at the boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingField {
+    name: &'static str,
+}
+
+impl MissingField {
+    /// The required field `name` is missing
+    pub const fn new(name: &'static str) -> Self {
+        Self { name }
+    }
+
+    /// The name of the missing field
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl fmt::Display for MissingField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {Missing}", self.name)
+    }
+}
+
+/// It is not `std::error::Error`, so it is not converted by the blanket `From` of `anyhow::Error`,
+/// missing the path
+impl From<MissingField> for anyhow::Error {
+    fn from(missing: MissingField) -> Self {
+        let mut conversion = ConversionError::new(anyhow::Error::new(Missing))
+            .push(PathSegment::Field(missing.name));
+        crate::context::attach(&mut conversion);
+        conversion.into()
+    }
+}
+
+/// The reason of the errors of the missing fields
+#[derive(Debug)]
+pub(crate) struct Missing;
+
+impl fmt::Display for Missing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Required is missing")
+    }
+}
+
+impl std::error::Error for Missing {}
+
+/// The elements of the sparse repeated field at the `indices` are missing
+pub fn missing_elements(indices: &[usize]) -> anyhow::Error {
+    anyhow::anyhow!("The elements at the indices {indices:?} are missing")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path() {
+        let error = missing_field("value");
+        let error = in_field(error, "left");
+        let error = at_index(error, 3);
+        let error = in_field(error, "items");
+        assert_eq!(
+            error.to_string(),
+            "items[3].left.value: Required is missing"
+        );
+
+        let error = error.downcast::<ConversionError>().unwrap();
+        assert_eq!(
+            error.path().cloned().collect::<Vec<_>>(),
+            vec![
+                PathSegment::Field("items"),
+                PathSegment::Index(3),
+                PathSegment::Field("left"),
+                PathSegment::Field("value"),
+            ]
+        );
+    }
+}
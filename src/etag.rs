@@ -0,0 +1,276 @@
+//! The ETags of the optimistic concurrency, with the `prost` feature, computed from the encoded proto
+//! form of the values, so the services serializing the same entity agree on its tag. `proto_etag` of
+//! the prelude converts the value with `ToProto`, encodes the prost message, hashes the bytes with
+//! SHA-256 of the `sha2` feature and renders the first 16 bytes of the digest as the lower case hex,
+//! `verify_proto_etag` checks the tag of the value, like of `If-Match`.
+//! ```ignore
+//! let tag = proto_etag(&user);
+//! anyhow::ensure!(verify_proto_etag(&stored, &request.etag), "The user was changed");
+//! ```
+//! The other digests implement `EtagDigest` and are used with `proto_etag_with` and
+//! `verify_proto_etag_with`, all the services computing the tags of an entity have to use the same one.
+//!
+//! The tag is the one of the encoding, prost encodes the fields in the order of their numbers and the
+//! repeated fields in their order, so it is the same across the processes and the releases, as long as
+//! the proto messages are the same. The `HashMap` fields are encoded in the order of the iteration,
+//! which is random, so the proto maps have to be `BTreeMap` with `btree_map(["."])` of prost-build, the
+//! domain maps can be any. The fields without presence are not encoded with their defaults, so the
+//! absent and the default values have the same tag, the `optional` fields have the tags differing by
+//! the presence.
+
+/// The digest of the encoded proto messages, the first `TAG_BYTES` of it are the tag
+pub trait EtagDigest {
+    /// The digest of the `bytes`
+    fn digest(bytes: &[u8]) -> Vec<u8>;
+}
+
+/// The SHA-256 digest, the one of `proto_etag`
+#[cfg(feature = "sha2")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha256;
+
+#[cfg(feature = "sha2")]
+impl EtagDigest for Sha256 {
+    fn digest(bytes: &[u8]) -> Vec<u8> {
+        use sha2::Digest;
+
+        sha2::Sha256::digest(bytes).to_vec()
+    }
+}
+
+/// The bytes of the digest rendered in to the tag
+pub const TAG_BYTES: usize = 16;
+
+/// The tag of the prost `message`, the hex of the first `TAG_BYTES` of its digest
+pub fn etag<D: EtagDigest, M: prost::Message>(message: &M) -> String {
+    use std::fmt::Write;
+
+    let digest = D::digest(&message.encode_to_vec());
+    let mut tag = String::with_capacity(2 * TAG_BYTES);
+    for byte in digest.iter().take(TAG_BYTES) {
+        write!(tag, "{byte:02x}").expect("writing in to a string does not fail");
+    }
+    tag
+}
+
+/// The `tag` is the one of the prost `message`, the tag can be quoted as in the HTTP headers and in
+/// any case
+pub fn verify<D: EtagDigest, M: prost::Message>(message: &M, tag: &str) -> bool {
+    let tag = tag
+        .strip_prefix('"')
+        .and_then(|tag| tag.strip_suffix('"'))
+        .unwrap_or(tag);
+    etag::<D, M>(message).eq_ignore_ascii_case(tag)
+}
+
+/// Generate the ETags of the `ToProto` prost messages, this is part of the `impl_traits!(prelude)`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_etag {
+    (prelude) => {
+        pub use super::{proto_etag_with, verify_proto_etag_with};
+        $crate::__impl_etag_sha2!(prelude);
+    };
+    () => {
+        /// The ETag of the proto form of the `value` with the digest `D`, see `proto_conve::etag`
+        #[allow(dead_code)]
+        pub fn proto_etag_with<D, T>(value: &T) -> String
+        where
+            D: $crate::etag::EtagDigest,
+            T: ToProto + ?Sized,
+            T::Result: $crate::__private::prost::Message,
+        {
+            $crate::etag::etag::<D, _>(&value.to_proto())
+        }
+
+        /// The `tag` is the ETag of the proto form of the `value` with the digest `D`
+        #[allow(dead_code)]
+        pub fn verify_proto_etag_with<D, T>(value: &T, tag: &str) -> bool
+        where
+            D: $crate::etag::EtagDigest,
+            T: ToProto + ?Sized,
+            T::Result: $crate::__private::prost::Message,
+        {
+            $crate::etag::verify::<D, _>(&value.to_proto(), tag)
+        }
+
+        $crate::__impl_etag_sha2!();
+    };
+}
+
+/// Generate the SHA-256 ETags, this is part of the `impl_traits!(prelude)`.
+#[cfg(feature = "sha2")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_etag_sha2 {
+    (prelude) => {
+        pub use super::{proto_etag, verify_proto_etag};
+    };
+    () => {
+        /// The SHA-256 ETag of the proto form of the `value`, see `proto_conve::etag`
+        #[allow(dead_code)]
+        pub fn proto_etag<T>(value: &T) -> String
+        where
+            T: ToProto + ?Sized,
+            T::Result: $crate::__private::prost::Message,
+        {
+            proto_etag_with::<$crate::etag::Sha256, T>(value)
+        }
+
+        /// The `tag` is the SHA-256 ETag of the proto form of the `value`
+        #[allow(dead_code)]
+        pub fn verify_proto_etag<T>(value: &T, tag: &str) -> bool
+        where
+            T: ToProto + ?Sized,
+            T::Result: $crate::__private::prost::Message,
+        {
+            verify_proto_etag_with::<$crate::etag::Sha256, T>(value, tag)
+        }
+    };
+}
+
+/// Without the `sha2` feature the ETags are computed only with the given digests.
+#[cfg(not(feature = "sha2"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_etag_sha2 {
+    ($($prelude: ident)?) => {};
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+    use std::hash::Hasher;
+
+    use super::*;
+    use crate::hash::StableHasher;
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ProtoAccount {
+        #[prost(string, tag = "1")]
+        name: String,
+        #[prost(uint32, tag = "2")]
+        credit: u32,
+        #[prost(btree_map = "string, string", tag = "3")]
+        labels: BTreeMap<String, String>,
+        #[prost(string, optional, tag = "4")]
+        nickname: Option<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Account {
+        name: String,
+        credit: Option<u32>,
+        labels: HashMap<String, String>,
+        nickname: Option<String>,
+    }
+
+    impl ToProto for Account {
+        type Result = ProtoAccount;
+
+        fn to_proto(&self) -> Self::Result {
+            ProtoAccount {
+                name: self.name.clone(),
+                credit: self.credit.unwrap_or_default(),
+                labels: self
+                    .labels
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect(),
+                nickname: self.nickname.clone(),
+            }
+        }
+    }
+
+    /// The digest of the tests without the `sha2` feature
+    struct Stable;
+
+    impl EtagDigest for Stable {
+        fn digest(bytes: &[u8]) -> Vec<u8> {
+            let mut hasher = StableHasher::new();
+            hasher.write(bytes);
+            let first = hasher.finish();
+            hasher.write(&[0]);
+            [first.to_be_bytes(), hasher.finish().to_be_bytes()].concat()
+        }
+    }
+
+    fn account(labels: &[(&str, &str)]) -> Account {
+        Account {
+            name: "ann".to_owned(),
+            credit: Some(20),
+            labels: labels
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            nickname: None,
+        }
+    }
+
+    #[test]
+    fn test_map_order() {
+        let labels = [("team", "search"), ("tier", "gold"), ("region", "eu")];
+        let tag = proto_etag_with::<Stable, _>(&account(&labels));
+        assert_eq!(tag.len(), 2 * TAG_BYTES);
+        let mut reversed = labels;
+        reversed.reverse();
+        // the maps of the same entries with the other insertion order
+        for _ in 0..16 {
+            assert_eq!(proto_etag_with::<Stable, _>(&account(&reversed)), tag);
+        }
+        assert_ne!(proto_etag_with::<Stable, _>(&account(&labels[..2])), tag);
+    }
+
+    #[test]
+    fn test_default_and_absent() {
+        let mut absent = account(&[]);
+        absent.credit = None;
+        let mut zero = absent.clone();
+        zero.credit = Some(0);
+        // the field without presence is the same
+        assert_eq!(
+            proto_etag_with::<Stable, _>(&absent),
+            proto_etag_with::<Stable, _>(&zero)
+        );
+        // the optional field differs by the presence
+        let mut empty = absent.clone();
+        empty.nickname = Some(String::new());
+        assert_ne!(
+            proto_etag_with::<Stable, _>(&absent),
+            proto_etag_with::<Stable, _>(&empty)
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let account = account(&[("team", "search")]);
+        let tag = proto_etag_with::<Stable, _>(&account);
+        assert!(verify_proto_etag_with::<Stable, _>(&account, &tag));
+        assert!(verify_proto_etag_with::<Stable, _>(
+            &account,
+            &format!("\"{}\"", tag.to_uppercase())
+        ));
+        let mut changed = account;
+        changed.credit = Some(21);
+        assert!(!verify_proto_etag_with::<Stable, _>(&changed, &tag));
+        assert!(!verify_proto_etag_with::<Stable, _>(&changed, ""));
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn test_sha256() {
+        // the same in every process, the SHA-256 of the encoding
+        let account = account(&[("tier", "gold"), ("team", "search")]);
+        let tag = proto_etag(&account);
+        assert_eq!(tag, TAG);
+        assert!(verify_proto_etag(&account, TAG));
+        assert_eq!(proto_etag(&account), proto_etag_with::<Sha256, _>(&account));
+    }
+
+    #[cfg(feature = "sha2")]
+    const TAG: &str = "3a2ebe84d1d893276b7d69b5e231a0ae";
+}
@@ -0,0 +1,64 @@
+//! Finding the proto fields added to the messages, which the conversions do not map yet, at compile
+//! time. The derived `FromProto` of the structs with `#[proto(deny_unknown_fields)]` builds the proto
+//! message with all of its fields without `..`, in the code, which is never run, so the field added to
+//! the prost generated struct fails to compile with `missing field`, instead of being dropped. The
+//! proto fields left out on purpose are listed with `#[proto(unmapped = "legacy_id, internal_note")]`.
+//! ```ignore
+//! #[derive(FromProto)]
+//! #[proto(type = "proto::Customer", deny_unknown_fields, unmapped = "legacy_id")]
+//! struct Customer {
+//!     name: String,
+//!     email: String,
+//! }
+//!
+//! // hand-written
+//! impl FromProto for proto::Order {
+//!     ...
+//! }
+//! assert_proto_fields!(proto::Order { id, items, legacy_note });
+//! ```
+//! The hand-written conversions list all the proto fields of the message with `assert_proto_fields!`,
+//! the mapped and the unmapped ones. Without them the conversions stay lenient.
+
+/// Check at compile time, that the proto message has only the listed fields, the added ones fail to
+/// compile
+/// ```ignore
+/// assert_proto_fields!(proto::Order { id, items, legacy_note });
+/// ```
+#[macro_export]
+macro_rules! assert_proto_fields {
+    ($($type: ident)::+ { $($field: ident),* $(,)? }) => {
+        const _: fn() -> $($type)::+ = || $($type)::+ {
+            $($field: $crate::exhaustive::unmapped(),)*
+        };
+    };
+}
+
+/// The value of the proto field in the checks, which are never run
+#[doc(hidden)]
+pub fn unmapped<T>() -> T {
+    unreachable!("the proto fields are only checked at compile time")
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    pub struct ProtoOrder {
+        id: u64,
+        items: Vec<String>,
+        legacy_note: String,
+    }
+
+    mod proto {
+        pub struct Item {
+            pub sku: String,
+        }
+    }
+
+    assert_proto_fields!(ProtoOrder {
+        id,
+        items,
+        legacy_note
+    });
+    assert_proto_fields!(proto::Item { sku });
+}
@@ -0,0 +1,373 @@
+//! Injecting the failures in to the conversions, for testing how the callers handle the errors, with the
+//! `test-util` feature. `FaultyProto` wraps a proto value, so its conversion fails, optionally after
+//! converting the value, and the faults injected with `inject` fail the conversions of the fields and
+//! the elements, which match them, in the messages converted in the thread.
+//! ```ignore
+//! let error = proto_conve::fault::inject(Fault::at("items[7]"), || order.from_proto()).unwrap_err();
+//! assert_eq!(error.to_string(), "items[7]: Injected failure");
+//!
+//! let fault = Fault::matching(|path| path.last() == Some(&PathSegment::Field("price")))
+//!     .nth(2)
+//!     .with_message("Price service is down");
+//! let result = proto_conve::fault::inject(fault, || orders.from_proto());
+//! ```
+//! The fields are seen by the faults, when they are converted with `FromProtoField`, like the fields
+//! of the derived conversions, and the elements, when the `Vec` is converted with `FromProto`. The path
+//! is the one of the errors, from the value the conversion was called on, so the injected error has
+//! the same path, as the real error of the field would have. Without the feature, the hooks are empty.
+
+use crate::error::PathSegment;
+
+#[cfg(any(test, feature = "test-util"))]
+use std::cell::{Cell, RefCell};
+
+/// Convert the `proto` at the path `segment` with `convert`, it is the injected error instead, when the
+/// fault matches it. The segment is made and the path is tracked only while a fault is injected, or the
+/// deprecation notices are collected, see `deprecation::scope`, or the conversion runs within a
+/// budget, see `budget::scope`. The nested conversions in the `context::ConvScope` have their own
+/// frames of the scopes, see `context::scope`.
+#[doc(hidden)]
+#[inline]
+pub fn scope<P, T>(
+    segment: impl FnOnce() -> PathSegment,
+    proto: P,
+    convert: impl FnOnce(P) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    #[cfg(any(test, feature = "test-util"))]
+    if ACTIVE.with(Cell::get) {
+        return injected_scope(segment(), proto, convert);
+    }
+    crate::budget::scope(segment, proto, convert)
+}
+
+/// The message of the injected errors without their own one
+#[cfg(any(test, feature = "test-util"))]
+const DEFAULT_MESSAGE: &str = "Injected failure";
+
+/// The proto value, which fails to convert
+#[cfg(any(test, feature = "test-util"))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultyProto<P> {
+    proto: P,
+    message: Option<String>,
+    after_converting: bool,
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl<P> FaultyProto<P> {
+    /// The value converting as the `proto` does
+    pub fn passing(proto: P) -> Self {
+        Self {
+            proto,
+            message: None,
+            after_converting: false,
+        }
+    }
+
+    /// The value failing with the `message`, without converting the `proto`
+    pub fn failing(proto: P, message: impl Into<String>) -> Self {
+        Self {
+            proto,
+            message: Some(message.into()),
+            after_converting: false,
+        }
+    }
+
+    /// The values of the `protos`, the one at `index` fails with the `message`
+    pub fn fail_at(
+        protos: impl IntoIterator<Item = P>,
+        index: usize,
+        message: impl Into<String>,
+    ) -> Vec<Self> {
+        let message = message.into();
+        protos
+            .into_iter()
+            .enumerate()
+            .map(|(at, proto)| match at == index {
+                true => Self::failing(proto, message.clone()),
+                false => Self::passing(proto),
+            })
+            .collect()
+    }
+
+    /// Convert the `proto` before failing, so the side effects of its conversion happen, its own
+    /// error is returned, when it fails
+    pub fn after_converting(mut self) -> Self {
+        self.after_converting = true;
+        self
+    }
+
+    /// The wrapped proto value
+    pub fn into_inner(self) -> P {
+        self.proto
+    }
+
+    /// Convert the proto value with `convert`, failing as configured
+    #[doc(hidden)]
+    pub fn convert<T>(self, convert: impl FnOnce(P) -> anyhow::Result<T>) -> anyhow::Result<T> {
+        let Some(message) = self.message else {
+            return convert(self.proto);
+        };
+        if self.after_converting {
+            convert(self.proto)?;
+        }
+        Err(anyhow::anyhow!(message))
+    }
+}
+
+/// The paths of the conversions, which the fault fails
+#[cfg(any(test, feature = "test-util"))]
+type Predicate = Box<dyn Fn(&[PathSegment]) -> bool>;
+
+/// Which conversions the injected fault fails
+#[cfg(any(test, feature = "test-util"))]
+pub struct Fault {
+    matches: Predicate,
+    nth: Option<usize>,
+    message: String,
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl Fault {
+    /// Fail the conversions at the paths, which the `predicate` matches, the path starts at the value
+    /// the conversion was called on
+    pub fn matching(predicate: impl Fn(&[PathSegment]) -> bool + 'static) -> Self {
+        Self {
+            matches: Box::new(predicate),
+            nth: None,
+            message: DEFAULT_MESSAGE.to_string(),
+        }
+    }
+
+    /// Fail the conversion at the `path`, written as in the errors, like `items[7].price`
+    pub fn at(path: impl Into<String>) -> Self {
+        let path = path.into();
+        Self::matching(move |segments| crate::error::path_string(segments.iter()) == path)
+    }
+
+    /// Fail every conversion of a field or an element
+    pub fn any() -> Self {
+        Self::matching(|_| true)
+    }
+
+    /// Fail only the matching conversion `n`, counted from zero in the order they are converted
+    pub fn nth(mut self, n: usize) -> Self {
+        self.nth = Some(n);
+        self
+    }
+
+    /// The message of the injected errors
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = message.into();
+        self
+    }
+}
+
+/// The fault injected in the thread, with the path of the conversion
+#[cfg(any(test, feature = "test-util"))]
+struct Injector {
+    fault: Fault,
+    path: Vec<PathSegment>,
+    matched: usize,
+    injected: usize,
+}
+
+#[cfg(any(test, feature = "test-util"))]
+thread_local! {
+    /// The path is tracked only with a fault injected, so the hooks are cheap without it
+    static ACTIVE: Cell<bool> = const { Cell::new(false) };
+    static INJECTOR: RefCell<Option<Injector>> = const { RefCell::new(None) };
+}
+
+/// Run `f` with the `fault` injected in the conversions of the thread, the fault injected before is
+/// restored after it
+#[cfg(any(test, feature = "test-util"))]
+pub fn inject<R>(fault: Fault, f: impl FnOnce() -> R) -> R {
+    inject_counted(fault, f).0
+}
+
+/// Run `f` with the `fault` injected, as `inject` does, with the number of the injected failures
+#[cfg(any(test, feature = "test-util"))]
+pub fn inject_counted<R>(fault: Fault, f: impl FnOnce() -> R) -> (R, usize) {
+    /// Restores the fault injected before, also when `f` panics
+    struct Restore(Option<Injector>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            ACTIVE.with(|active| active.set(previous.is_some()));
+            INJECTOR.with(|current| current.replace(previous));
+        }
+    }
+
+    let injector = Injector {
+        fault,
+        path: Vec::new(),
+        matched: 0,
+        injected: 0,
+    };
+    let restore = Restore(INJECTOR.with(|current| current.replace(Some(injector))));
+    ACTIVE.with(|active| active.set(true));
+    let result = f();
+    let injected = INJECTOR.with(|current| {
+        current
+            .borrow()
+            .as_ref()
+            .map_or(0, |injector| injector.injected)
+    });
+    drop(restore);
+    (result, injected)
+}
+
+#[cfg(any(test, feature = "test-util"))]
+fn injected_scope<P, T>(
+    segment: PathSegment,
+    proto: P,
+    convert: impl FnOnce(P) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let message = INJECTOR.with(|current| {
+        let mut current = current.borrow_mut();
+        let injector = current.as_mut()?;
+        injector.path.push(segment.clone());
+        if !(injector.fault.matches)(&injector.path) {
+            return None;
+        }
+        let n = injector.matched;
+        injector.matched += 1;
+        if injector.fault.nth.is_some_and(|nth| nth != n) {
+            return None;
+        }
+        injector.injected += 1;
+        Some(injector.fault.message.clone())
+    });
+    let result = match message {
+        Some(message) => Err(anyhow::anyhow!(message)),
+        None => crate::budget::scope(|| segment, proto, convert),
+    };
+    INJECTOR.with(|current| {
+        if let Some(injector) = current.borrow_mut().as_mut() {
+            injector.path.pop();
+        }
+    });
+    result
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::{inject, inject_counted, Fault, FaultyProto};
+    use crate::error::PathSegment;
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoItem {
+        price: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoOrder {
+        items: Vec<ProtoItem>,
+        backorder: Vec<ProtoItem>,
+    }
+
+    impl_traits!(prelude);
+
+    impl FromProto for ProtoItem {
+        type Result = u32;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            FromProtoField::from_proto_field(self.price, "price")
+                .and_then(|price: i64| Ok(u32::try_from(price)?))
+        }
+    }
+
+    impl FromProto for ProtoOrder {
+        type Result = (Vec<u32>, Vec<u32>);
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok((
+                FromProtoField::from_proto_field(self.items, "items")?,
+                FromProtoField::from_proto_field(self.backorder, "backorder")?,
+            ))
+        }
+    }
+
+    fn items(count: i64) -> Vec<ProtoItem> {
+        (0..count).map(|price| ProtoItem { price }).collect()
+    }
+
+    fn order() -> ProtoOrder {
+        ProtoOrder {
+            items: items(10),
+            backorder: items(3),
+        }
+    }
+
+    #[test]
+    fn test_faulty_proto() {
+        let protos = FaultyProto::fail_at(items(10), 7, "The item is broken");
+        let error = protos.clone().from_proto().unwrap_err();
+        assert_eq!(error.to_string(), "[7]: The item is broken");
+        assert_eq!(
+            FaultyProto::passing(ProtoItem { price: 4 })
+                .from_proto()
+                .unwrap(),
+            4
+        );
+
+        // its own error is returned, when the value fails to convert
+        let proto = FaultyProto::failing(ProtoItem { price: -1 }, "Injected").after_converting();
+        assert_ne!(proto.from_proto().unwrap_err().to_string(), "Injected");
+        let proto = FaultyProto::failing(ProtoItem { price: 1 }, "Injected").after_converting();
+        assert_eq!(proto.from_proto().unwrap_err().to_string(), "Injected");
+    }
+
+    #[test]
+    fn test_inject_at_index() {
+        let error = inject(Fault::at("items[7]"), || order().from_proto()).unwrap_err();
+        assert_eq!(error.to_string(), "items[7]: Injected failure");
+
+        let fault = Fault::matching(|path| matches!(path.last(), Some(PathSegment::Index(_))))
+            .nth(11)
+            .with_message("Lost connection");
+        let (result, injected) = inject_counted(fault, || order().from_proto());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "backorder[1]: Lost connection"
+        );
+        assert_eq!(injected, 1);
+
+        // the fault is gone after it
+        assert!(order().from_proto().is_ok());
+    }
+
+    #[test]
+    fn test_inject_path_predicate() {
+        let fault = Fault::matching(|path| path.last() == Some(&PathSegment::Field("price")))
+            .nth(3)
+            .with_message("Price service is down");
+        let error = inject(fault, || order().from_proto()).unwrap_err();
+        assert_eq!(error.to_string(), "items[3].price: Price service is down");
+
+        let fault = Fault::matching(|path| path.first() == Some(&PathSegment::Field("backorder")));
+        let error = inject(fault, || order().from_proto()).unwrap_err();
+        assert_eq!(error.to_string(), "backorder: Injected failure");
+
+        let (result, injected) = inject_counted(Fault::at("items[12]"), || order().from_proto());
+        assert!(result.is_ok());
+        assert_eq!(injected, 0);
+    }
+
+    #[test]
+    fn test_nested_inject() {
+        let result = inject(Fault::at("backorder"), || {
+            let inner = inject(Fault::at("items"), || order().from_proto()).unwrap_err();
+            assert_eq!(inner.to_string(), "items: Injected failure");
+            order().from_proto()
+        });
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "backorder: Injected failure"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! The value sent in one of several proto fields, like while a field is migrated from the legacy
+//! epoch millis `int64` to a `Timestamp` message, and the clients set either of them. The alternatives
+//! are given in the order of their preference, the first one, which is present and converts, is used.
+//! ```ignore
+//! let created_at = FirstOf::new(&["created_at_ts", "created_at_millis"])
+//!     .or(proto.created_at_ts.map(|value| value.from_proto_field("created_at_ts")))
+//!     .or((proto.created_at_millis != 0)
+//!         .then(|| proto.created_at_millis.from_proto_field("created_at_millis")))
+//!     .finish()?;
+//! ```
+//! The alternatives are converted with their fields in the paths of the errors, as `FromProtoField`
+//! does. When none of them is present, it is the
+//! `MissingField` error listing all of the fields, and when the present ones fail to convert, it is the
+//! error of the first one. `FirstOf::strict` rejects the values sent in more than one of the fields, as
+//! they are ambiguous, otherwise the later ones are ignored.
+//!
+//! The derived conversions use it for the fields with `#[proto(first_of("created_at_ts",
+//! "created_at_millis"))]`, adding `strict` for the ambiguity check. The alternative is present, when
+//! it is not the proto default, and converts with `FromProtoField`, so the `Option` messages with
+//! `FromProto`, and the scalars with `FromProtoAlias`. The rust value is converted in to the first
+//! field, the others are left with their defaults.
+
+use crate::violation::{coded, ErrorCode};
+
+/// The first of the alternative proto fields, which is present and converts
+#[derive(Debug)]
+pub struct FirstOf<T> {
+    names: &'static [&'static str],
+    strict: bool,
+    /// The number of the alternatives given so far
+    given: usize,
+    /// The first present alternative
+    present: Option<&'static str>,
+    /// The two present alternatives of the strict conversion
+    ambiguous: Option<(&'static str, &'static str)>,
+    result: Option<anyhow::Result<T>>,
+}
+
+impl<T> FirstOf<T> {
+    /// The alternatives of the proto fields `names`, given with `or` in the same order
+    pub fn new(names: &'static [&'static str]) -> Self {
+        Self {
+            names,
+            strict: false,
+            given: 0,
+            present: None,
+            ambiguous: None,
+            result: None,
+        }
+    }
+
+    /// More than one present alternative is an error
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// The converted value of the next alternative, `None` when it is not present
+    ///
+    /// # Panics
+    ///
+    /// When there are more alternatives than the names
+    pub fn or(mut self, value: Option<anyhow::Result<T>>) -> Self {
+        let name = self.names[self.given];
+        self.given += 1;
+        let Some(value) = value else {
+            return self;
+        };
+        match self.present {
+            None => self.present = Some(name),
+            Some(first) if self.strict && self.ambiguous.is_none() => {
+                self.ambiguous = Some((first, name))
+            }
+            Some(_) => {}
+        }
+        // the first conversion, which succeeds, or the first error
+        if !matches!(self.result, Some(Ok(_))) {
+            match (value, &self.result) {
+                (Ok(value), _) => self.result = Some(Ok(value)),
+                (Err(error), None) => self.result = Some(Err(error)),
+                (Err(_), Some(_)) => {}
+            }
+        }
+        self
+    }
+
+    /// The value of the first alternative, which is present and converts
+    pub fn finish(self) -> anyhow::Result<T> {
+        if let Some((first, second)) = self.ambiguous {
+            return Err(coded(
+                ErrorCode::Invalid,
+                format!("Both `{first}` and `{second}` are present, only one of them can be set"),
+            ));
+        }
+        match self.result {
+            Some(result) => result,
+            None => Err(coded(
+                ErrorCode::MissingField,
+                format!("None of `{}` is present", self.names.join("`, `")),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FirstOf;
+    use crate::error::in_field;
+    use crate::violation::ErrorCode;
+
+    const NAMES: &[&str] = &["created_at_ts", "created_at_millis"];
+
+    fn parse(value: &str, name: &'static str) -> anyhow::Result<u64> {
+        value
+            .parse()
+            .map_err(|error| in_field(anyhow::Error::new(error), name))
+    }
+
+    fn first_of(first: Option<&str>, second: Option<&str>) -> FirstOf<u64> {
+        FirstOf::new(NAMES)
+            .or(first.map(|value| parse(value, NAMES[0])))
+            .or(second.map(|value| parse(value, NAMES[1])))
+    }
+
+    #[test]
+    fn test_first_of() {
+        assert_eq!(first_of(None, Some("2")).finish().unwrap(), 2);
+        assert_eq!(first_of(Some("1"), None).finish().unwrap(), 1);
+        assert_eq!(first_of(Some("1"), Some("2")).finish().unwrap(), 1);
+        // the later alternative, when the first one fails
+        assert_eq!(first_of(Some("one"), Some("2")).finish().unwrap(), 2);
+
+        let error = first_of(Some("one"), Some("two")).finish().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "created_at_ts: invalid digit found in string"
+        );
+        let error = first_of(None, None).finish().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "None of `created_at_ts`, `created_at_millis` is present"
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::MissingField);
+    }
+
+    #[test]
+    fn test_strict() {
+        let strict = |first: Option<&str>, second: Option<&str>| {
+            FirstOf::new(NAMES)
+                .strict()
+                .or(first.map(|value| parse(value, NAMES[0])))
+                .or(second.map(|value| parse(value, NAMES[1])))
+                .finish()
+        };
+        assert_eq!(strict(Some("1"), None).unwrap(), 1);
+        assert_eq!(strict(None, Some("2")).unwrap(), 2);
+        let error = strict(Some("1"), Some("2")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Both `created_at_ts` and `created_at_millis` are present, only one of them can be set"
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::Invalid);
+    }
+}
@@ -0,0 +1,434 @@
+//! The sets of flags of a fieldless enum, which the protos send either positionally as
+//! `repeated bool flags` or packed as `uint64 mask`, both converting in to the same `FlagSet`.
+//! ```ignore
+//! #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+//! enum Feature {
+//!     DarkMode,
+//!     Beta,
+//!     Offline,
+//! }
+//!
+//! impl proto_conve::flags::Flag for Feature {
+//!     const ALL: &'static [Self] = &[Feature::DarkMode, Feature::Beta, Feature::Offline];
+//! }
+//!
+//! #[derive(FromProto, ToProto)]
+//! #[proto(type = "proto::Account")]
+//! struct Account {
+//!     // repeated bool features = 1;
+//!     features: FlagSet<Feature>,
+//!     // uint64 permissions = 2;
+//!     permissions: FlagSet<Permission>,
+//! }
+//! ```
+//! The flag is at its position in `Flag::ALL`, which is the index in the repeated bools and the bit of
+//! the mask, so at most 64 flags. The flags of the newer producers, the set positions after the known
+//! ones or the unknown bits, are an error, or ignored in the lenient `ConversionMode`. The unset ones
+//! are always ignored. The explicit policy is the one of `FlagSet::from_bools` and
+//! `FlagSet::from_mask`, and `FlagSet::to_bools` and `FlagSet::to_mask` give the either form.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use crate::mode::ConversionMode;
+
+/// The fieldless enum of the flags
+pub trait Flag: Copy + Eq + 'static {
+    /// All the flags, in the order of their positions and bits, at most 64 of them
+    const ALL: &'static [Self];
+
+    /// The position of the flag in `ALL`
+    fn index(self) -> u32 {
+        Self::ALL
+            .iter()
+            .position(|flag| *flag == self)
+            .expect("the flag is in `Flag::ALL`") as u32
+    }
+}
+
+/// What the conversion does with the set flags, which are not known
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownFlags {
+    /// The unknown flags are an error
+    #[default]
+    Reject,
+    /// The unknown flags are dropped
+    Ignore,
+}
+
+impl From<ConversionMode> for UnknownFlags {
+    fn from(mode: ConversionMode) -> Self {
+        if mode.is_lenient() {
+            Self::Ignore
+        } else {
+            Self::Reject
+        }
+    }
+}
+
+/// The set of the flags `E`
+pub struct FlagSet<E> {
+    bits: u64,
+    flags: PhantomData<E>,
+}
+
+impl<E: Flag> FlagSet<E> {
+    /// The bits of all the known flags
+    const KNOWN: u64 = {
+        assert!(E::ALL.len() <= 64, "at most 64 flags in `Flag::ALL`");
+        match E::ALL.len() {
+            64 => u64::MAX,
+            count => (1 << count) - 1,
+        }
+    };
+
+    /// The set without any flag
+    pub const fn empty() -> Self {
+        Self {
+            bits: 0,
+            flags: PhantomData,
+        }
+    }
+
+    /// The set of all the known flags
+    pub fn all() -> Self {
+        Self::with_bits(Self::KNOWN)
+    }
+
+    fn with_bits(bits: u64) -> Self {
+        Self {
+            bits,
+            flags: PhantomData,
+        }
+    }
+
+    fn bit(flag: E) -> u64 {
+        1 << flag.index()
+    }
+
+    /// The set of the repeated bools, the flag at every `true` position
+    pub fn from_bools(values: &[bool], policy: UnknownFlags) -> anyhow::Result<Self> {
+        let count = E::ALL.len();
+        let extra = values.iter().skip(count).filter(|value| **value).count();
+        anyhow::ensure!(
+            extra == 0 || policy == UnknownFlags::Ignore,
+            "The {extra} flags after the {count} known ones of {} are set",
+            std::any::type_name::<E>()
+        );
+        let bits = values
+            .iter()
+            .take(count)
+            .enumerate()
+            .filter(|(_, value)| **value)
+            .fold(0, |bits, (index, _)| bits | 1 << index);
+        Ok(Self::with_bits(bits))
+    }
+
+    /// The set of the mask, the flag at every set bit
+    pub fn from_mask(mask: u64, policy: UnknownFlags) -> anyhow::Result<Self> {
+        let unknown = mask & !Self::KNOWN;
+        anyhow::ensure!(
+            unknown == 0 || policy == UnknownFlags::Ignore,
+            "The unknown bits {unknown:#x} of {} are set",
+            std::any::type_name::<E>()
+        );
+        Ok(Self::with_bits(mask & Self::KNOWN))
+    }
+
+    /// The repeated bools, one for every known flag
+    pub fn to_bools(&self) -> Vec<bool> {
+        E::ALL.iter().map(|flag| self.contains(*flag)).collect()
+    }
+
+    /// The mask of the set flags
+    pub fn to_mask(&self) -> u64 {
+        self.bits
+    }
+
+    /// Whether the flag is set
+    pub fn contains(&self, flag: E) -> bool {
+        self.bits & Self::bit(flag) != 0
+    }
+
+    /// Set the flag, `true` when it was not set
+    pub fn insert(&mut self, flag: E) -> bool {
+        let inserted = !self.contains(flag);
+        self.bits |= Self::bit(flag);
+        inserted
+    }
+
+    /// Unset the flag, `true` when it was set
+    pub fn remove(&mut self, flag: E) -> bool {
+        let removed = self.contains(flag);
+        self.bits &= !Self::bit(flag);
+        removed
+    }
+
+    /// Set or unset the flag
+    pub fn set(&mut self, flag: E, value: bool) {
+        if value {
+            self.insert(flag);
+        } else {
+            self.remove(flag);
+        }
+    }
+
+    /// The number of the set flags
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Whether all the flags of `other` are set
+    pub fn is_superset(&self, other: &Self) -> bool {
+        self.bits & other.bits == other.bits
+    }
+
+    /// The flags set in either of the sets
+    pub fn union(&self, other: &Self) -> Self {
+        Self::with_bits(self.bits | other.bits)
+    }
+
+    /// The flags set in both of the sets
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self::with_bits(self.bits & other.bits)
+    }
+
+    /// The flags set in this set, but not in `other`
+    pub fn difference(&self, other: &Self) -> Self {
+        Self::with_bits(self.bits & !other.bits)
+    }
+
+    /// The set flags, in the order of `Flag::ALL`
+    pub fn iter(&self) -> impl Iterator<Item = E> + '_ {
+        E::ALL.iter().copied().filter(|flag| self.contains(*flag))
+    }
+}
+
+/// Convert the repeated bools, used by the prelude
+#[doc(hidden)]
+pub fn from_proto_bools<E: Flag>(
+    values: Vec<bool>,
+    mode: ConversionMode,
+) -> anyhow::Result<FlagSet<E>> {
+    FlagSet::from_bools(&values, mode.into())
+}
+
+/// Convert the mask, used by the prelude
+#[doc(hidden)]
+pub fn from_proto_mask<E: Flag>(mask: u64, mode: ConversionMode) -> anyhow::Result<FlagSet<E>> {
+    FlagSet::from_mask(mask, mode.into())
+}
+
+impl<E> Clone for FlagSet<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for FlagSet<E> {}
+
+impl<E> PartialEq for FlagSet<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits
+    }
+}
+
+impl<E> Eq for FlagSet<E> {}
+
+impl<E> Hash for FlagSet<E> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits.hash(state);
+    }
+}
+
+impl<E: Flag> Default for FlagSet<E> {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<E: Flag + fmt::Debug> fmt::Debug for FlagSet<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<E: Flag> FromIterator<E> for FlagSet<E> {
+    fn from_iter<I: IntoIterator<Item = E>>(flags: I) -> Self {
+        let mut set = Self::empty();
+        set.extend(flags);
+        set
+    }
+}
+
+impl<E: Flag> Extend<E> for FlagSet<E> {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, flags: I) {
+        for flag in flags {
+            self.insert(flag);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Feature {
+        DarkMode,
+        Beta,
+        Offline,
+    }
+
+    impl Flag for Feature {
+        const ALL: &'static [Self] = &[Feature::DarkMode, Feature::Beta, Feature::Offline];
+    }
+
+    type Features = FlagSet<Feature>;
+
+    #[test]
+    fn test_bools() {
+        let features = Features::from_proto_alias(vec![true, false, true]).unwrap();
+        assert_eq!(
+            features.iter().collect::<Vec<_>>(),
+            vec![Feature::DarkMode, Feature::Offline]
+        );
+        assert_eq!(
+            ToProtoAlias::<Vec<bool>>::to_proto_alias(&features),
+            vec![true, false, true]
+        );
+
+        // the shorter list of the older producers and the unset extra positions
+        let features = Features::from_proto_alias(vec![false, true]).unwrap();
+        assert_eq!(features.to_bools(), vec![false, true, false]);
+        let features = Features::from_proto_alias(vec![true, false, false, false]).unwrap();
+        assert_eq!(features.to_bools(), vec![true, false, false]);
+        assert!(Features::from_proto_alias(vec![]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_extra_bools() {
+        let values = vec![true, false, true, true, false, true];
+        let error = Features::from_proto_alias(values.clone()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "The 2 flags after the 3 known ones of {} are set",
+                std::any::type_name::<Feature>()
+            )
+        );
+
+        let features = Features::from_proto_alias_mode(values.clone(), ConversionMode::Lenient);
+        assert_eq!(features.unwrap().to_bools(), vec![true, false, true]);
+        let features = Features::from_bools(&values, UnknownFlags::Ignore).unwrap();
+        assert_eq!(features.len(), 2);
+        assert!(Features::from_bools(&values, UnknownFlags::Reject).is_err());
+    }
+
+    #[test]
+    fn test_mask() {
+        let features = Features::from_proto_alias(0b110u64).unwrap();
+        assert_eq!(
+            features.iter().collect::<Vec<_>>(),
+            vec![Feature::Beta, Feature::Offline]
+        );
+        assert_eq!(ToProtoAlias::<u64>::to_proto_alias(&features), 0b110);
+        assert_eq!(Features::from_proto_alias(0u64).unwrap(), Features::empty());
+        assert_eq!(
+            Features::from_proto_alias(0b111u64).unwrap(),
+            Features::all()
+        );
+
+        let error = Features::from_proto_alias(0b11_0001u64).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "The unknown bits 0x30 of {} are set",
+                std::any::type_name::<Feature>()
+            )
+        );
+        let features = Features::from_proto_alias_mode(0b11_0001u64, ConversionMode::Lenient);
+        assert_eq!(features.unwrap().to_mask(), 0b1);
+        let features = Features::from_mask(u64::MAX, UnknownFlags::Ignore).unwrap();
+        assert_eq!(features, Features::all());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for mask in 0..0b1000u64 {
+            let features = Features::from_mask(mask, UnknownFlags::Reject).unwrap();
+            let bools = features.to_bools();
+            assert_eq!(
+                Features::from_bools(&bools, UnknownFlags::Reject).unwrap(),
+                features
+            );
+            assert_eq!(features.to_mask(), mask);
+        }
+    }
+
+    #[test]
+    fn test_operations() {
+        let mut features = Features::empty();
+        assert!(features.insert(Feature::Beta));
+        assert!(!features.insert(Feature::Beta));
+        features.set(Feature::Offline, true);
+        assert!(features.contains(Feature::Beta));
+        assert!(!features.contains(Feature::DarkMode));
+        assert_eq!(features.len(), 2);
+        assert!(features.remove(Feature::Beta));
+        assert!(!features.remove(Feature::Beta));
+        features.set(Feature::Offline, false);
+        assert!(features.is_empty());
+
+        let left: Features = [Feature::DarkMode, Feature::Beta].into_iter().collect();
+        let right: Features = [Feature::Beta, Feature::Offline].into_iter().collect();
+        assert_eq!(left.union(&right), Features::all());
+        assert_eq!(
+            left.intersection(&right),
+            [Feature::Beta].into_iter().collect()
+        );
+        assert_eq!(
+            left.difference(&right),
+            [Feature::DarkMode].into_iter().collect()
+        );
+        assert!(Features::all().is_superset(&left));
+        assert!(!left.is_superset(&right));
+        assert_eq!(format!("{left:?}"), "{DarkMode, Beta}");
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Bit(u32);
+
+    impl Flag for Bit {
+        const ALL: &'static [Self] = &{
+            let mut all = [Bit(0); 64];
+            let mut index = 0;
+            while index < 64 {
+                all[index] = Bit(index as u32);
+                index += 1;
+            }
+            all
+        };
+
+        fn index(self) -> u32 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_all_bits() {
+        let bits = FlagSet::<Bit>::from_mask(u64::MAX, UnknownFlags::Reject).unwrap();
+        assert_eq!(bits.len(), 64);
+        assert!(bits.contains(Bit(63)));
+        assert_eq!(bits.to_bools().len(), 64);
+    }
+}
@@ -0,0 +1,531 @@
+//! The graphs of the shared nodes, like the `Arc<Node>` of the DAGs, converted in to the proto side
+//! table of the nodes referenced by their indices, so the shared nodes are written once, instead of
+//! once per path to them, and the cycles are an error, instead of the conversion never ending.
+//! ```proto
+//! message Plan {
+//!   // the nodes, the root is the first one
+//!   repeated Task tasks = 1;
+//!   uint32 root = 2;
+//! }
+//!
+//! message Task {
+//!   string name = 1;
+//!   // the indices of the nodes in `tasks`
+//!   repeated uint32 deps = 2;
+//! }
+//! ```
+//! The nodes implement `GraphNode`, converting their fields and referencing the other nodes with
+//! `SharedToProto::reference`, which writes the node, when it is the first reference of it, and
+//! `SharedFromProto::reference`, which converts the node once and shares it. `impl_proto_graph!`
+//! implements `FromProto` of the message of the table in to the shared root and `TryToProto` back.
+//! ```ignore
+//! impl GraphNode for Task {
+//!     type Proto = proto::Task;
+//!     type Shared = Arc<Task>;
+//!
+//!     fn to_proto_node(&self, graph: &mut SharedToProto<Self>) -> anyhow::Result<proto::Task> {
+//!         Ok(proto::Task {
+//!             name: self.name.clone(),
+//!             deps: self.deps.iter().map(|dep| graph.reference(dep)).collect::<anyhow::Result<_>>()?,
+//!         })
+//!     }
+//!
+//!     fn from_proto_node(proto: proto::Task, graph: &mut SharedFromProto<Self>) -> anyhow::Result<Self> {
+//!         Ok(Task {
+//!             name: proto.name,
+//!             deps: proto.deps.into_iter().map(|dep| graph.reference(dep)).collect::<anyhow::Result<_>>()?,
+//!         })
+//!     }
+//! }
+//!
+//! impl_proto_graph!(proto::Plan { tasks, root } => Arc<Task>);
+//! ```
+//! The nodes are identified by their pointers, the ones of the cycles made with the interior
+//! mutability are an error with the path of the references to the node closing the cycle, like
+//! `tasks[0].tasks[1]: The node 0 closes a cycle at the depth 2`. The proto references out of the
+//! table or making a cycle are an error too, and the nodes, which are not referenced, are dropped. The
+//! depth of the references is bounded by `max_depth`, `DEFAULT_MAX_DEPTH` by default, the deeper ones
+//! fail with `ErrorCode::BudgetExceeded`.
+
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::error::{at_index, in_field};
+use crate::violation::{coded, ErrorCode};
+
+/// The depth of the references of the graphs, when it is not given
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// The shared pointers of the nodes, identified by their addresses
+pub trait SharedPtr: Clone + Deref + Sized
+where
+    Self::Target: Sized,
+{
+    /// The pointer of the new node
+    fn new(value: Self::Target) -> Self;
+
+    /// The address of the node
+    fn as_ptr(this: &Self) -> *const Self::Target;
+}
+
+impl<T> SharedPtr for Arc<T> {
+    fn new(value: T) -> Self {
+        Arc::new(value)
+    }
+
+    fn as_ptr(this: &Self) -> *const T {
+        Arc::as_ptr(this)
+    }
+}
+
+impl<T> SharedPtr for Rc<T> {
+    fn new(value: T) -> Self {
+        Rc::new(value)
+    }
+
+    fn as_ptr(this: &Self) -> *const T {
+        Rc::as_ptr(this)
+    }
+}
+
+/// The node of the graph, converted with the references to the other nodes
+pub trait GraphNode: Sized {
+    /// The proto message of the node in the table
+    type Proto;
+    /// The pointer sharing the nodes, like `Arc<Self>`
+    type Shared: SharedPtr<Target = Self>;
+
+    /// The proto node, referencing the other nodes with `graph`
+    fn to_proto_node(&self, graph: &mut SharedToProto<Self>) -> anyhow::Result<Self::Proto>;
+
+    /// The node of the proto one, referencing the other nodes with `graph`
+    fn from_proto_node(
+        proto: Self::Proto,
+        graph: &mut SharedFromProto<Self>,
+    ) -> anyhow::Result<Self>;
+}
+
+/// The table of the proto nodes written so far
+pub struct SharedToProto<N: GraphNode> {
+    name: &'static str,
+    max_depth: usize,
+    nodes: Vec<Option<N::Proto>>,
+    indices: HashMap<*const N, u32>,
+    /// The indices of the nodes on the path of the references
+    path: Vec<u32>,
+}
+
+impl<N: GraphNode> SharedToProto<N> {
+    /// The empty table of the proto field `name`
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            max_depth: DEFAULT_MAX_DEPTH,
+            nodes: Vec::new(),
+            indices: HashMap::new(),
+            path: Vec::new(),
+        }
+    }
+
+    /// The references deeper than `max_depth` are an error
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// The index of the `node`, it is written in to the table with the nodes it references, when it
+    /// is the first reference of it
+    pub fn reference(&mut self, node: &N::Shared) -> anyhow::Result<u32> {
+        let ptr = SharedPtr::as_ptr(node);
+        if let Some(&index) = self.indices.get(&ptr) {
+            if self.path.contains(&index) {
+                return Err(coded(
+                    ErrorCode::Invalid,
+                    format!(
+                        "The node {index} closes a cycle at the depth {}",
+                        self.path.len()
+                    ),
+                ));
+            }
+            return Ok(index);
+        }
+        if self.path.len() >= self.max_depth {
+            return Err(deeper(self.max_depth));
+        }
+        let index = u32::try_from(self.nodes.len())
+            .map_err(|_| coded(ErrorCode::OutOfRange, "The graph has too many nodes"))?;
+        self.nodes.push(None);
+        self.indices.insert(ptr, index);
+        self.path.push(index);
+        let result = node.to_proto_node(self);
+        self.path.pop();
+        let proto = result.map_err(|error| in_field(at_index(error, index as usize), self.name))?;
+        self.nodes[index as usize] = Some(proto);
+        Ok(index)
+    }
+
+    /// The table of the nodes in the order of their first references
+    pub fn finish(self) -> Vec<N::Proto> {
+        self.nodes
+            .into_iter()
+            .map(|node| node.expect("the nodes are written, when their references return"))
+            .collect()
+    }
+}
+
+/// The proto nodes of the table, converted when they are referenced
+pub struct SharedFromProto<N: GraphNode> {
+    name: &'static str,
+    max_depth: usize,
+    protos: Vec<Option<N::Proto>>,
+    nodes: Vec<Option<N::Shared>>,
+    path: Vec<u32>,
+}
+
+impl<N: GraphNode> SharedFromProto<N> {
+    /// The table of the proto field `name`
+    pub fn new(name: &'static str, protos: Vec<N::Proto>) -> Self {
+        Self {
+            name,
+            max_depth: DEFAULT_MAX_DEPTH,
+            nodes: protos.iter().map(|_| None).collect(),
+            protos: protos.into_iter().map(Some).collect(),
+            path: Vec::new(),
+        }
+    }
+
+    /// The references deeper than `max_depth` are an error
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// The node at the `index` of the table, shared by all of its references
+    pub fn reference(&mut self, index: u32) -> anyhow::Result<N::Shared> {
+        let slot = index as usize;
+        let Some(node) = self.nodes.get(slot) else {
+            return Err(coded(
+                ErrorCode::OutOfRange,
+                format!(
+                    "The node {index} is not in the {} nodes of `{}`",
+                    self.nodes.len(),
+                    self.name
+                ),
+            ));
+        };
+        if let Some(node) = node {
+            return Ok(node.clone());
+        }
+        if self.path.contains(&index) {
+            return Err(coded(
+                ErrorCode::Invalid,
+                format!(
+                    "The node {index} closes a cycle at the depth {}",
+                    self.path.len()
+                ),
+            ));
+        }
+        if self.path.len() >= self.max_depth {
+            return Err(deeper(self.max_depth));
+        }
+        let proto = self.protos[slot]
+            .take()
+            .expect("the proto node is taken, when it is converted");
+        self.path.push(index);
+        let result = N::from_proto_node(proto, self);
+        self.path.pop();
+        let node: N::Shared =
+            SharedPtr::new(result.map_err(|error| in_field(at_index(error, slot), self.name))?);
+        self.nodes[slot] = Some(node.clone());
+        Ok(node)
+    }
+}
+
+fn deeper(max_depth: usize) -> anyhow::Error {
+    coded(
+        ErrorCode::BudgetExceeded,
+        format!("The graph is deeper than {max_depth} nodes"),
+    )
+}
+
+/// The table of the nodes of the graph from the `root` in the proto field `name`, and the index of the
+/// root
+pub fn to_proto_graph<P>(
+    root: &P,
+    name: &'static str,
+) -> anyhow::Result<(Vec<<P::Target as GraphNode>::Proto>, u32)>
+where
+    P: SharedPtr,
+    P::Target: GraphNode<Shared = P>,
+{
+    let mut graph = SharedToProto::<P::Target>::new(name);
+    let root = graph.reference(root)?;
+    Ok((graph.finish(), root))
+}
+
+/// The graph of the table of the nodes in the proto field `name` from the node at the index `root`
+pub fn from_proto_graph<N: GraphNode>(
+    nodes: Vec<N::Proto>,
+    root: u32,
+    name: &'static str,
+) -> anyhow::Result<N::Shared> {
+    SharedFromProto::<N>::new(name, nodes).reference(root)
+}
+
+/// Implement `FromProto` of the message of the table of the nodes and the index of the root in to the
+/// shared root node, and `TryToProto` of it back, the traits of the `impl_traits!(prelude)` have to be
+/// in the scope.
+/// ```ignore
+/// impl_proto_graph!(proto::Plan { tasks, root } => Arc<Task>);
+/// ```
+/// The nodes implement `GraphNode`, see `graph`.
+#[macro_export]
+macro_rules! impl_proto_graph {
+    ($proto: path { $nodes: ident, $root: ident $(,)? } => $shared: ty) => {
+        impl FromProto for $proto {
+            type Result = $shared;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                $crate::graph::from_proto_graph::<<$shared as ::core::ops::Deref>::Target>(
+                    self.$nodes,
+                    self.$root,
+                    stringify!($nodes),
+                )
+            }
+        }
+
+        /// The cycles are an error
+        impl TryToProto for $shared {
+            type Result = $proto;
+
+            fn try_to_proto(&self) -> anyhow::Result<Self::Result> {
+                let mut proto = <$proto as ::core::default::Default>::default();
+                (proto.$nodes, proto.$root) =
+                    $crate::graph::to_proto_graph(self, stringify!($nodes))?;
+                Ok(proto)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoTask {
+        name: String,
+        deps: Vec<u32>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoPlan {
+        tasks: Vec<ProtoTask>,
+        root: u32,
+    }
+
+    #[derive(Debug)]
+    pub struct Task {
+        name: String,
+        deps: Vec<Arc<Task>>,
+    }
+
+    impl GraphNode for Task {
+        type Proto = ProtoTask;
+        type Shared = Arc<Task>;
+
+        fn to_proto_node(&self, graph: &mut SharedToProto<Self>) -> anyhow::Result<ProtoTask> {
+            Ok(ProtoTask {
+                name: self.name.clone(),
+                deps: self
+                    .deps
+                    .iter()
+                    .map(|dep| graph.reference(dep))
+                    .collect::<anyhow::Result<_>>()?,
+            })
+        }
+
+        fn from_proto_node(
+            proto: ProtoTask,
+            graph: &mut SharedFromProto<Self>,
+        ) -> anyhow::Result<Self> {
+            anyhow::ensure!(!proto.name.is_empty(), "The name is empty");
+            Ok(Task {
+                name: proto.name,
+                deps: proto
+                    .deps
+                    .into_iter()
+                    .map(|dep| graph.reference(dep))
+                    .collect::<anyhow::Result<_>>()?,
+            })
+        }
+    }
+
+    impl_proto_graph!(ProtoPlan { tasks, root } => Arc<Task>);
+
+    fn task(name: &str, deps: Vec<Arc<Task>>) -> Arc<Task> {
+        Arc::new(Task {
+            name: name.to_owned(),
+            deps,
+        })
+    }
+
+    fn proto_task(name: &str, deps: Vec<u32>) -> ProtoTask {
+        ProtoTask {
+            name: name.to_owned(),
+            deps,
+        }
+    }
+
+    #[test]
+    fn test_diamond() {
+        let deploy = task("deploy", vec![]);
+        let test = task("test", vec![deploy.clone()]);
+        let lint = task("lint", vec![deploy.clone()]);
+        let release = task("release", vec![test, lint]);
+
+        let plan = release.try_to_proto().unwrap();
+        // the shared node is written once
+        assert_eq!(
+            plan,
+            ProtoPlan {
+                tasks: vec![
+                    proto_task("release", vec![1, 3]),
+                    proto_task("test", vec![2]),
+                    proto_task("deploy", vec![]),
+                    proto_task("lint", vec![2]),
+                ],
+                root: 0,
+            }
+        );
+
+        let release = plan.clone().from_proto().unwrap();
+        assert_eq!(release.name, "release");
+        let (test, lint) = (&release.deps[0], &release.deps[1]);
+        assert_eq!((test.name.as_str(), lint.name.as_str()), ("test", "lint"));
+        assert!(Arc::ptr_eq(&test.deps[0], &lint.deps[0]));
+        assert_eq!(release.try_to_proto().unwrap(), plan);
+    }
+
+    #[derive(Debug)]
+    pub struct Step {
+        name: String,
+        next: RefCell<Vec<Rc<Step>>>,
+    }
+
+    impl GraphNode for Step {
+        type Proto = ProtoTask;
+        type Shared = Rc<Step>;
+
+        fn to_proto_node(&self, graph: &mut SharedToProto<Self>) -> anyhow::Result<ProtoTask> {
+            Ok(ProtoTask {
+                name: self.name.clone(),
+                deps: self
+                    .next
+                    .borrow()
+                    .iter()
+                    .map(|next| graph.reference(next))
+                    .collect::<anyhow::Result<_>>()?,
+            })
+        }
+
+        fn from_proto_node(
+            proto: ProtoTask,
+            graph: &mut SharedFromProto<Self>,
+        ) -> anyhow::Result<Self> {
+            Ok(Step {
+                name: proto.name,
+                next: RefCell::new(
+                    proto
+                        .deps
+                        .into_iter()
+                        .map(|next| graph.reference(next))
+                        .collect::<anyhow::Result<_>>()?,
+                ),
+            })
+        }
+    }
+
+    fn step(name: &str) -> Rc<Step> {
+        Rc::new(Step {
+            name: name.to_owned(),
+            next: RefCell::new(Vec::new()),
+        })
+    }
+
+    #[test]
+    fn test_cycle() {
+        let first = step("first");
+        let second = step("second");
+        let third = step("third");
+        first.next.borrow_mut().push(second.clone());
+        second.next.borrow_mut().push(third.clone());
+        third.next.borrow_mut().push(second.clone());
+
+        let error = to_proto_graph(&first, "steps").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "steps[0].steps[1].steps[2]: The node 1 closes a cycle at the depth 3"
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::Invalid);
+        // the cycle of the test is broken, so the nodes are dropped
+        third.next.borrow_mut().clear();
+
+        // the cycles of the proto references
+        let nodes = vec![proto_task("first", vec![1]), proto_task("second", vec![0])];
+        let error = from_proto_graph::<Step>(nodes, 0, "steps").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "steps[0].steps[1]: The node 0 closes a cycle at the depth 2"
+        );
+    }
+
+    #[test]
+    fn test_invalid_references() {
+        let plan = ProtoPlan {
+            tasks: vec![proto_task("release", vec![1]), proto_task("test", vec![5])],
+            root: 0,
+        };
+        let error = plan.from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "tasks[0].tasks[1]: The node 5 is not in the 2 nodes of `tasks`"
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::OutOfRange);
+
+        let plan = ProtoPlan {
+            tasks: vec![proto_task("release", vec![1]), proto_task("", vec![])],
+            root: 0,
+        };
+        let error = plan.from_proto().unwrap_err();
+        assert_eq!(error.to_string(), "tasks[0].tasks[1]: The name is empty");
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut chain = task("0", vec![]);
+        for index in 1..5 {
+            chain = task(&index.to_string(), vec![chain]);
+        }
+        let mut graph = SharedToProto::<Task>::new("tasks").max_depth(3);
+        let error = graph.reference(&chain).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "tasks[0].tasks[1].tasks[2]: The graph is deeper than 3 nodes"
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::BudgetExceeded);
+
+        let plan = chain.try_to_proto().unwrap();
+        assert_eq!(plan.tasks.len(), 5);
+        let mut graph = SharedFromProto::<Task>::new("tasks", plan.tasks).max_depth(3);
+        assert!(graph.reference(0).is_err());
+    }
+}
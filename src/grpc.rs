@@ -0,0 +1,378 @@
+//! Helpers to convert tonic requests and responses, enabled with the `tonic` feature.
+//! The conversion traits themselves are generated by `impl_traits!(prelude)`.
+//!
+//! With the `futures` feature too, the streams of the streaming RPCs are converted with
+//! `from_proto_stream` and `to_proto_stream` of the prelude.
+//! ```ignore
+//! async fn chat(&self, request: Request<Streaming<ProtoMessage>>) -> Result<Response<ChatStream>, Status> {
+//!     // Stream<Item = Result<Message, Status>>
+//!     let messages = from_proto_stream(request.into_inner());
+//!     let replies = self.replies(messages);
+//!     Ok(Response::new(Box::pin(to_proto_stream(replies))))
+//! }
+//! ```
+//! The messages are converted one by one, as they are polled, and the stream ends after its first
+//! error, the transport one or the conversion one as `invalid_argument` with the path of the field.
+
+#[cfg(feature = "futures")]
+use std::pin::Pin;
+#[cfg(feature = "futures")]
+use std::task::{Context, Poll};
+
+use tonic::Status;
+
+/// Map the conversion error in to `invalid_argument` status, the whole context chain is kept in the
+/// message, so the client can see which field failed to convert.
+pub fn invalid_argument(error: anyhow::Error) -> Status {
+    Status::invalid_argument(format!("{error:#}"))
+}
+
+/// The stream of the converted messages of the `stream`, ending after the first error, see
+/// `from_proto_stream` and `to_proto_stream` of the prelude. The boxed `Box::pin(stream)` converts the
+/// streams, which are not `Unpin`.
+#[cfg(feature = "futures")]
+pub struct ConvertStream<S, F> {
+    stream: S,
+    convert: F,
+    done: bool,
+}
+
+#[cfg(feature = "futures")]
+impl<S, F> ConvertStream<S, F> {
+    /// Convert the messages of the `stream` with `convert`
+    pub fn new(stream: S, convert: F) -> Self {
+        Self {
+            stream,
+            convert,
+            done: false,
+        }
+    }
+
+    /// The stream of the proto messages
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S, F, T, U> futures_core::Stream for ConvertStream<S, F>
+where
+    S: futures_core::Stream<Item = Result<T, Status>> + Unpin,
+    F: FnMut(T) -> Result<U, Status> + Unpin,
+{
+    type Item = Result<U, Status>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        let item = match Pin::new(&mut this.stream).poll_next(cx) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(None) => None,
+            Poll::Ready(Some(Ok(message))) => Some((this.convert)(message)),
+            Poll::Ready(Some(Err(status))) => Some(Err(status)),
+        };
+        this.done = !matches!(item, Some(Ok(_)));
+        Poll::Ready(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.done {
+            true => (0, Some(0)),
+            // it may end early with an error
+            false => (0, self.stream.size_hint().1),
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S, F> futures_core::FusedStream for ConvertStream<S, F>
+where
+    Self: futures_core::Stream,
+{
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+/// Generate the tonic conversion traits, this is part of the `impl_traits!(prelude)`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_tonic {
+    (prelude) => {
+        pub use super::{FromProtoRequest, IntoProtoResponse};
+    };
+    () => {
+        /// Convert the tonic request in to the domain value, keeping the metadata and extensions of the
+        /// request.
+        pub trait FromProtoRequest {
+            /// The domain value type
+            type Result;
+
+            /// Convert the request message, conversion errors are returned as `invalid_argument` status.
+            #[allow(clippy::wrong_self_convention, clippy::result_large_err)]
+            fn from_proto_request(
+                self,
+            ) -> Result<
+                (
+                    Self::Result,
+                    $crate::__private::tonic::metadata::MetadataMap,
+                    $crate::__private::tonic::Extensions,
+                ),
+                $crate::__private::tonic::Status,
+            >;
+        }
+
+        impl<P: FromProto> FromProtoRequest for $crate::__private::tonic::Request<P> {
+            type Result = P::Result;
+
+            #[allow(clippy::result_large_err)]
+            fn from_proto_request(
+                self,
+            ) -> Result<
+                (
+                    Self::Result,
+                    $crate::__private::tonic::metadata::MetadataMap,
+                    $crate::__private::tonic::Extensions,
+                ),
+                $crate::__private::tonic::Status,
+            > {
+                let (metadata, extensions, message) = self.into_parts();
+                let value = message
+                    .from_proto()
+                    .map_err($crate::grpc::invalid_argument)?;
+                Ok((value, metadata, extensions))
+            }
+        }
+
+        /// Convert the domain value in to the tonic response.
+        pub trait IntoProtoResponse {
+            /// The proto message type
+            type Result;
+
+            /// Convert the value in to proto message and wrap it in to the response
+            fn into_proto_response(self) -> $crate::__private::tonic::Response<Self::Result>;
+        }
+
+        impl<T: ToProto> IntoProtoResponse for T {
+            type Result = T::Result;
+
+            fn into_proto_response(self) -> $crate::__private::tonic::Response<Self::Result> {
+                $crate::__private::tonic::Response::new(self.to_proto())
+            }
+        }
+    };
+}
+
+/// Generate the conversions of the tonic streams, this is part of the `impl_traits!(prelude)` with the
+/// `futures` feature.
+#[cfg(feature = "futures")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_tonic_stream {
+    (prelude) => {
+        pub use super::{from_proto_stream, to_proto_stream};
+    };
+    () => {
+        /// Convert the stream of the proto messages, like the `tonic::Streaming` of the requests, in to
+        /// the stream of the domain values. The conversion error is `invalid_argument` status, which
+        /// ends the stream, as the transport errors do.
+        #[allow(clippy::type_complexity, clippy::result_large_err)]
+        pub fn from_proto_stream<S, P>(
+            stream: S,
+        ) -> $crate::grpc::ConvertStream<
+            S,
+            fn(P) -> Result<P::Result, $crate::__private::tonic::Status>,
+        >
+        where
+            S: $crate::__private::futures_core::Stream<
+                    Item = Result<P, $crate::__private::tonic::Status>,
+                > + Unpin,
+            P: FromProto,
+        {
+            $crate::grpc::ConvertStream::new(stream, |proto| {
+                proto.from_proto().map_err($crate::grpc::invalid_argument)
+            })
+        }
+
+        /// Convert the stream of the domain values in to the stream of the proto messages of the
+        /// responses, it ends after the first error.
+        #[allow(clippy::type_complexity, clippy::result_large_err)]
+        pub fn to_proto_stream<S, T>(
+            stream: S,
+        ) -> $crate::grpc::ConvertStream<
+            S,
+            fn(T) -> Result<T::Result, $crate::__private::tonic::Status>,
+        >
+        where
+            S: $crate::__private::futures_core::Stream<
+                    Item = Result<T, $crate::__private::tonic::Status>,
+                > + Unpin,
+            T: ToProto,
+        {
+            $crate::grpc::ConvertStream::new(stream, |value| Ok(ToProto::to_proto(&value)))
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code, deprecated)]
+mod tests {
+    use crate::impl_traits;
+
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct ProtoDuration {
+        seconds: i64,
+        nanos: u32,
+    }
+
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct ProtoDateTimeUtc {
+        seconds: i64,
+        nanos: u32,
+    }
+
+    impl_traits!(prelude);
+    // the tonic responses need `ToProto`
+    impl_traits!(ProtoDuration, chrono::Duration, legacy);
+    impl_traits!(ProtoDateTimeUtc, chrono::DateTime<chrono::Utc>);
+
+    #[test]
+    fn test_request() {
+        let mut request = tonic::Request::new(ProtoDuration {
+            seconds: 3,
+            nanos: 4,
+        });
+        request
+            .metadata_mut()
+            .insert("x-request-id", "abc".parse().unwrap());
+
+        let (value, metadata, _) = request.from_proto_request().unwrap();
+        assert_eq!(
+            value,
+            chrono::Duration::seconds(3) + chrono::Duration::nanoseconds(4)
+        );
+        assert_eq!(metadata.get("x-request-id").unwrap(), "abc");
+    }
+
+    #[test]
+    fn test_request_invalid() {
+        let request = tonic::Request::new(ProtoDateTimeUtc {
+            seconds: 0,
+            nanos: 2_000_000_000,
+        });
+
+        let status = request.from_proto_request().unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("Failed to parse timestamp"));
+    }
+
+    #[test]
+    fn test_response() {
+        let response = chrono::Duration::seconds(-5).into_proto_response();
+        assert_eq!(
+            response.into_inner(),
+            ProtoDuration {
+                seconds: -5,
+                nanos: 0
+            }
+        );
+    }
+
+    #[cfg(feature = "futures")]
+    #[allow(clippy::result_large_err)]
+    fn timestamp(seconds: i64, nanos: u32) -> Result<ProtoDateTimeUtc, tonic::Status> {
+        Ok(ProtoDateTimeUtc { seconds, nanos })
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn test_from_proto_stream() {
+        use futures::executor::block_on_stream;
+
+        let protos = futures::stream::iter(vec![timestamp(1, 0), timestamp(2, 0)]);
+        let values: Vec<_> = block_on_stream(from_proto_stream(protos))
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            values,
+            [
+                chrono::DateTime::from_timestamp(1, 0).unwrap(),
+                chrono::DateTime::from_timestamp(2, 0).unwrap()
+            ]
+        );
+
+        // the conversion error ends the stream
+        let protos = vec![
+            timestamp(1, 0),
+            timestamp(0, 2_000_000_000),
+            timestamp(3, 0),
+        ];
+        let results: Vec<_> =
+            block_on_stream(from_proto_stream(futures::stream::iter(protos))).collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        let status = results[1].as_ref().unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("Failed to parse timestamp"));
+
+        // and the transport error
+        let protos = vec![
+            timestamp(1, 0),
+            Err(tonic::Status::unavailable("gone")),
+            timestamp(3, 0),
+        ];
+        let results: Vec<_> =
+            block_on_stream(from_proto_stream(futures::stream::iter(protos))).collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[1].as_ref().unwrap_err().code(),
+            tonic::Code::Unavailable
+        );
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    #[allow(clippy::result_large_err)]
+    fn test_from_proto_stream_lazy() {
+        use std::cell::Cell;
+
+        let pulled = Cell::new(0);
+        let protos = (0..10).map(|seconds| {
+            pulled.set(pulled.get() + 1);
+            timestamp(seconds, 0)
+        });
+        let mut values =
+            futures::executor::block_on_stream(from_proto_stream(futures::stream::iter(protos)));
+        assert!(values.next().unwrap().is_ok());
+        assert!(values.next().unwrap().is_ok());
+        // a message is pulled, when the converted one is polled
+        assert_eq!(pulled.get(), 2);
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn test_to_proto_stream() {
+        let values = vec![
+            Ok(chrono::Duration::seconds(1)),
+            Err(tonic::Status::internal("failed")),
+            Ok(chrono::Duration::seconds(3)),
+        ];
+        let results: Vec<_> =
+            futures::executor::block_on_stream(to_proto_stream(futures::stream::iter(values)))
+                .collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[0].as_ref().unwrap(),
+            &ProtoDuration {
+                seconds: 1,
+                nanos: 0
+            }
+        );
+        assert_eq!(
+            results[1].as_ref().unwrap_err().code(),
+            tonic::Code::Internal
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//! The hashes of the canonical proto form of the values, like for the idempotency keys. `proto_hash` of
+//! the prelude converts the value with `ToProto` and feeds the proto value to the hasher with
+//! `ProtoHashable`, `proto_hash_u64` hashes it with the `StableHasher`, which is the same across the
+//! runs, the platforms and the releases.
+//! ```ignore
+//! #[derive(ProtoHashable)]
+//! pub struct CreateOrder { ... }
+//!
+//! let key = proto_hash_u64(&order);
+//! ```
+//! The proto messages derive `ProtoHashable`, the prost ones with the `type_attribute` of
+//! `#[derive(::proto_conve::ProtoHashable)]`, and the hand-written ones implement it. The traversal is
+//! canonical:
+//! - the fields are hashed in their declaration order, without their names, and the oneof enums as the
+//!   index of their variant in the declaration order and its fields
+//! - the integers and the `bool` are hashed as their little endian bytes, `bool` as one byte, the
+//!   floats as the bits of the little endian bytes, with `-0.0` as `0.0` and one NaN, as they are equal
+//!   on the wire
+//! - the strings, bytes and repeated fields are their length as `u64` and their elements
+//! - the maps are their length and the entries sorted by the keys, so the order of a `HashMap` does not
+//!   matter
+//! - the absent optional values are the byte `0`, the present ones the byte `1` and their value, so the
+//!   absent differs from the default
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hasher;
+
+/// The proto value fed to a hasher in the canonical traversal
+pub trait ProtoHashable {
+    /// Feed the value to the `hasher`
+    fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H);
+}
+
+macro_rules! hash_integers {
+    ($($type: ty),*) => {
+        $(
+            impl ProtoHashable for $type {
+                fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H) {
+                    hasher.write(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+hash_integers!(u8, i32, i64, u32, u64);
+
+impl ProtoHashable for bool {
+    fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H) {
+        hasher.write(&[u8::from(*self)]);
+    }
+}
+
+impl ProtoHashable for f32 {
+    fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H) {
+        let value = match self {
+            value if value.is_nan() => f32::NAN,
+            value if *value == 0.0 => 0.0,
+            value => *value,
+        };
+        hasher.write(&value.to_bits().to_le_bytes());
+    }
+}
+
+impl ProtoHashable for f64 {
+    fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H) {
+        let value = match self {
+            value if value.is_nan() => f64::NAN,
+            value if *value == 0.0 => 0.0,
+            value => *value,
+        };
+        hasher.write(&value.to_bits().to_le_bytes());
+    }
+}
+
+/// The length of the strings, bytes, repeated fields and maps
+fn hash_len<H: Hasher + ?Sized>(len: usize, hasher: &mut H) {
+    (len as u64).hash_proto(hasher);
+}
+
+impl ProtoHashable for str {
+    fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H) {
+        hash_len(self.len(), hasher);
+        hasher.write(self.as_bytes());
+    }
+}
+
+impl ProtoHashable for String {
+    fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H) {
+        self.as_str().hash_proto(hasher);
+    }
+}
+
+impl<T: ProtoHashable> ProtoHashable for [T] {
+    fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H) {
+        hash_len(self.len(), hasher);
+        for value in self {
+            value.hash_proto(hasher);
+        }
+    }
+}
+
+impl<T: ProtoHashable> ProtoHashable for Vec<T> {
+    fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H) {
+        self.as_slice().hash_proto(hasher);
+    }
+}
+
+impl ProtoHashable for crate::ProtoBytes {
+    fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H) {
+        let bytes: &[u8] = self.as_ref();
+        bytes.hash_proto(hasher);
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl ProtoHashable for bytes::Bytes {
+    fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H) {
+        let bytes: &[u8] = self.as_ref();
+        bytes.hash_proto(hasher);
+    }
+}
+
+impl<T: ProtoHashable> ProtoHashable for Option<T> {
+    fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H) {
+        match self {
+            None => hasher.write(&[0]),
+            Some(value) => {
+                hasher.write(&[1]);
+                value.hash_proto(hasher);
+            }
+        }
+    }
+}
+
+impl<T: ProtoHashable + ?Sized> ProtoHashable for Box<T> {
+    fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H) {
+        (**self).hash_proto(hasher);
+    }
+}
+
+impl<T: ProtoHashable + ?Sized> ProtoHashable for &T {
+    fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H) {
+        (**self).hash_proto(hasher);
+    }
+}
+
+/// The entries sorted by the keys
+fn hash_entries<'a, K, V, H>(entries: impl ExactSizeIterator<Item = (&'a K, &'a V)>, hasher: &mut H)
+where
+    K: ProtoHashable + Ord + 'a,
+    V: ProtoHashable + 'a,
+    H: Hasher + ?Sized,
+{
+    hash_len(entries.len(), hasher);
+    let mut entries: Vec<_> = entries.collect();
+    entries.sort_unstable_by_key(|&(key, _)| key);
+    for (key, value) in entries {
+        key.hash_proto(hasher);
+        value.hash_proto(hasher);
+    }
+}
+
+impl<K: ProtoHashable + Ord, V: ProtoHashable, S> ProtoHashable for HashMap<K, V, S> {
+    fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H) {
+        hash_entries(self.iter(), hasher);
+    }
+}
+
+impl<K: ProtoHashable + Ord, V: ProtoHashable> ProtoHashable for BTreeMap<K, V> {
+    fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H) {
+        hash_entries(self.iter(), hasher);
+    }
+}
+
+/// The 64 bit FNV-1a hasher, its hashes are the same across the runs, the platforms and the releases,
+/// unlike the ones of `std::collections::hash_map::DefaultHasher`. It is not resistant to the
+/// collisions made on purpose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StableHasher(u64);
+
+impl StableHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    /// The hasher of nothing hashed yet
+    pub fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(Self::PRIME);
+        }
+    }
+}
+
+/// The stable hash of the proto `value`
+pub fn hash_u64<P: ProtoHashable + ?Sized>(value: &P) -> u64 {
+    let mut hasher = StableHasher::new();
+    value.hash_proto(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use std::collections::HashMap;
+    use std::hash::Hasher;
+
+    use super::{hash_u64, ProtoHashable, StableHasher};
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoLabels {
+        name: String,
+        labels: HashMap<String, i64>,
+        weight: Option<f64>,
+    }
+
+    impl ProtoHashable for ProtoLabels {
+        fn hash_proto<H: Hasher + ?Sized>(&self, hasher: &mut H) {
+            self.name.hash_proto(hasher);
+            self.labels.hash_proto(hasher);
+            self.weight.hash_proto(hasher);
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Labels {
+        name: String,
+        labels: Vec<(String, i64)>,
+        weight: Option<f64>,
+    }
+
+    impl_traits!(prelude);
+
+    impl ToProto for Labels {
+        type Result = ProtoLabels;
+
+        fn to_proto(&self) -> Self::Result {
+            ProtoLabels {
+                name: self.name.clone(),
+                labels: self.labels.iter().cloned().collect(),
+                weight: self.weight,
+            }
+        }
+    }
+
+    fn labels(labels: &[(&str, i64)]) -> Labels {
+        Labels {
+            name: "job".to_owned(),
+            labels: labels
+                .iter()
+                .map(|(key, value)| (key.to_string(), *value))
+                .collect(),
+            weight: None,
+        }
+    }
+
+    #[test]
+    fn test_map_order() {
+        let entries: Vec<_> = (0..64).map(|n| (format!("key{n}"), n)).collect();
+        let forward: HashMap<_, _> = entries.iter().cloned().collect();
+        let mut backward = HashMap::with_capacity(1024);
+        backward.extend(entries.iter().rev().cloned());
+        assert_eq!(hash_u64(&forward), hash_u64(&backward));
+
+        let a = labels(&[("a", 1), ("b", 2)]);
+        let b = labels(&[("b", 2), ("a", 1)]);
+        assert_eq!(proto_hash_u64(&a), proto_hash_u64(&b));
+        assert_ne!(
+            proto_hash_u64(&a),
+            proto_hash_u64(&labels(&[("a", 2), ("b", 1)]))
+        );
+    }
+
+    #[test]
+    fn test_stable() {
+        // the hashes must not change across the releases, they are stored as the idempotency keys
+        assert_eq!(StableHasher::new().finish(), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash_u64("a"), 0x529a_4ddc_8ff5_6bbf);
+        assert_eq!(proto_hash_u64(&labels(&[("a", 1)])), 0x98b0_a1e5_ac3a_c59b);
+
+        let mut hasher = StableHasher::new();
+        proto_hash(&labels(&[]), &mut hasher);
+        assert_eq!(hasher.finish(), proto_hash_u64(&labels(&[])));
+    }
+
+    #[test]
+    fn test_absent_and_default() {
+        let absent = labels(&[]);
+        let default = Labels {
+            weight: Some(0.0),
+            ..absent.clone()
+        };
+        assert_ne!(proto_hash_u64(&absent), proto_hash_u64(&default));
+        // equal on the wire
+        let negative = Labels {
+            weight: Some(-0.0),
+            ..absent.clone()
+        };
+        assert_eq!(proto_hash_u64(&default), proto_hash_u64(&negative));
+        assert_ne!(hash_u64(&(None::<u32>)), hash_u64(&Some(0u32)));
+        assert_ne!(hash_u64(&["ab", "c"][..]), hash_u64(&["a", "bc"][..]));
+    }
+}
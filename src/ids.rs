@@ -0,0 +1,321 @@
+//! The typed ids, the newtypes of the uuids, the strings or the integers, like `OrderId(Uuid)`,
+//! `Slug(String)` or `ShardId(u16)`, converted from their proto fields with `impl_proto_id!`, given
+//! the wire form of their proto field.
+//! ```ignore
+//! impl_proto_id!(OrderId(uuid::Uuid) as string_uuid);
+//! impl_proto_id!(Slug(String) as string_raw);
+//! impl_proto_id!(ShardId(u16) as uint32);
+//! impl_proto_id!(AccountId(u64) as uint64);
+//!
+//! let order: OrderId = FromProtoAlias::from_proto_alias(proto.order_id)?;
+//! ```
+//! The wire forms are
+//! - `string_uuid`, the `string` of the uuid, which is not nil, written hyphenated in lower case, with
+//!   the `uuid` feature,
+//! - `string_raw`, the `string`, which is not empty, written as it is,
+//! - `uint32` and `uint64`, the integers, which fit in to the integer of the id.
+//!
+//! The ids convert with `FromProtoAlias` and `ToProtoAlias` of the wire form, also in the `Option` and
+//! `Vec` of them, so they are the fields of the derived conversions too. The invalid ones fail with
+//! `InvalidId` naming the id type, like `Invalid OrderId: the uuid is nil`. The macro implements
+//! `Display` and `FromStr` of the ids too, with the same form and validation as the proto ones, so the
+//! ids in the logs, the urls and the proto fields are the same.
+
+use std::fmt;
+
+/// The proto value, which is not a valid id
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidId {
+    id: &'static str,
+    reason: String,
+}
+
+impl InvalidId {
+    #[doc(hidden)]
+    pub fn new(id: &'static str, reason: String) -> Self {
+        Self { id, reason }
+    }
+
+    /// The name of the id type
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+
+    /// What is wrong with the value
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl fmt::Display for InvalidId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid {}: {}", self.id, self.reason)
+    }
+}
+
+impl std::error::Error for InvalidId {}
+
+/// The wire form of the ids with their representation `R`, the reasons of the errors are wrapped in to
+/// `InvalidId` by the conversions of `impl_proto_id!`
+#[doc(hidden)]
+pub trait IdWire<R> {
+    /// The proto field
+    type Proto;
+
+    fn from_proto(proto: Self::Proto) -> Result<R, String>;
+
+    fn to_proto(value: &R) -> Self::Proto;
+
+    /// Parse the value of `FromStr`
+    fn parse(value: &str) -> Result<R, String>;
+}
+
+/// The `string` of the uuid, which is not nil
+#[cfg(feature = "uuid")]
+#[doc(hidden)]
+pub struct StringUuid;
+
+#[cfg(feature = "uuid")]
+impl IdWire<::uuid::Uuid> for StringUuid {
+    type Proto = String;
+
+    fn from_proto(proto: String) -> Result<::uuid::Uuid, String> {
+        Self::parse(&proto)
+    }
+
+    fn to_proto(value: &::uuid::Uuid) -> String {
+        value.hyphenated().to_string()
+    }
+
+    fn parse(value: &str) -> Result<::uuid::Uuid, String> {
+        let uuid = ::uuid::Uuid::parse_str(value)
+            .map_err(|error| format!("{value:?} is not a uuid: {error}"))?;
+        if uuid.is_nil() {
+            return Err("the uuid is nil".to_owned());
+        }
+        Ok(uuid)
+    }
+}
+
+/// The `string`, which is not empty
+#[doc(hidden)]
+pub struct StringRaw;
+
+impl IdWire<String> for StringRaw {
+    type Proto = String;
+
+    fn from_proto(proto: String) -> Result<String, String> {
+        if proto.is_empty() {
+            return Err("the value is empty".to_owned());
+        }
+        Ok(proto)
+    }
+
+    fn to_proto(value: &String) -> String {
+        value.clone()
+    }
+
+    fn parse(value: &str) -> Result<String, String> {
+        Self::from_proto(value.to_owned())
+    }
+}
+
+macro_rules! uint_wire {
+    ($wire: ident, $proto: ty) => {
+        #[doc = concat!("The `", stringify!($proto), "`, which fits in to the integer of the id")]
+        #[doc(hidden)]
+        pub struct $wire;
+
+        impl<R: TryFrom<$proto> + Into<$proto> + Copy> IdWire<R> for $wire {
+            type Proto = $proto;
+
+            fn from_proto(proto: $proto) -> Result<R, String> {
+                R::try_from(proto).map_err(|_| {
+                    format!(
+                        "{proto} is out of the range of {}",
+                        std::any::type_name::<R>()
+                    )
+                })
+            }
+
+            fn to_proto(value: &R) -> $proto {
+                (*value).into()
+            }
+
+            fn parse(value: &str) -> Result<R, String> {
+                let proto = value
+                    .parse::<$proto>()
+                    .map_err(|error| format!("{value:?} is not a number: {error}"))?;
+                Self::from_proto(proto)
+            }
+        }
+    };
+}
+
+uint_wire!(Uint32, u32);
+uint_wire!(Uint64, u64);
+
+/// Implement the conversions of the typed id `$type`, the newtype of `$repr`, from and in to its proto
+/// field of the wire form, with `Display` and `FromStr` of the same form, the traits of the
+/// `impl_traits!(prelude)` have to be in the scope.
+/// ```ignore
+/// impl_proto_id!(OrderId(uuid::Uuid) as string_uuid);
+/// impl_proto_id!(Slug(String) as string_raw);
+/// impl_proto_id!(ShardId(u16) as uint32);
+/// impl_proto_id!(AccountId(u64) as uint64);
+/// ```
+/// The wire forms are `string_uuid`, `string_raw`, `uint32` and `uint64`, see `ids`.
+#[macro_export]
+macro_rules! impl_proto_id {
+    ($type: ident ($repr: ty) as string_uuid) => {
+        $crate::impl_proto_id!(@impl $type, $repr, $crate::ids::StringUuid, String);
+    };
+    ($type: ident ($repr: ty) as string_raw) => {
+        $crate::impl_proto_id!(@impl $type, $repr, $crate::ids::StringRaw, String);
+    };
+    ($type: ident ($repr: ty) as uint32) => {
+        $crate::impl_proto_id!(@impl $type, $repr, $crate::ids::Uint32, u32);
+    };
+    ($type: ident ($repr: ty) as uint64) => {
+        $crate::impl_proto_id!(@impl $type, $repr, $crate::ids::Uint64, u64);
+    };
+    (@impl $type: ident, $repr: ty, $wire: ty, $proto: ty) => {
+        impl FromProtoAlias<$proto> for $type {
+            fn from_proto_alias(value: $proto) -> anyhow::Result<Self> {
+                <$wire as $crate::ids::IdWire<$repr>>::from_proto(value)
+                    .map($type)
+                    .map_err(|reason| $crate::ids::InvalidId::new(stringify!($type), reason).into())
+            }
+        }
+
+        impl ToProtoAlias<$proto> for $type {
+            fn to_proto_alias(&self) -> $proto {
+                <$wire as $crate::ids::IdWire<$repr>>::to_proto(&self.0)
+            }
+        }
+
+        /// The proto3 `optional` field of the id
+        impl ToProtoAlias<Option<$proto>> for $type {
+            fn to_proto_alias(&self) -> Option<$proto> {
+                Some(<$wire as $crate::ids::IdWire<$repr>>::to_proto(&self.0))
+            }
+        }
+
+        impl ::std::fmt::Display for $type {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                ::std::fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl ::std::str::FromStr for $type {
+            type Err = $crate::ids::InvalidId;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                <$wire as $crate::ids::IdWire<$repr>>::parse(value)
+                    .map($type)
+                    .map_err(|reason| $crate::ids::InvalidId::new(stringify!($type), reason))
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::InvalidId;
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Slug(String);
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct ShardId(u16);
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct AccountId(u64);
+
+    impl_traits!(prelude);
+    impl_proto_id!(Slug(String) as string_raw);
+    impl_proto_id!(ShardId(u16) as uint32);
+    impl_proto_id!(AccountId(u64) as uint64);
+
+    #[test]
+    fn test_string_raw() {
+        let slug = Slug::from_proto_alias("spring-sale".to_owned()).unwrap();
+        assert_eq!(slug, Slug("spring-sale".to_owned()));
+        assert_eq!(ToProtoAlias::<String>::to_proto_alias(&slug), "spring-sale");
+        assert_eq!(slug.to_string(), "spring-sale");
+        assert_eq!("spring-sale".parse::<Slug>().unwrap(), slug);
+
+        let error = Slug::from_proto_alias(String::new()).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid Slug: the value is empty");
+        let invalid = error.downcast_ref::<InvalidId>().unwrap();
+        assert_eq!(invalid.id(), "Slug");
+        assert_eq!(invalid.reason(), "the value is empty");
+        assert!("".parse::<Slug>().is_err());
+    }
+
+    #[test]
+    fn test_uint32() {
+        let shard = ShardId::from_proto_alias(7u32).unwrap();
+        assert_eq!(shard, ShardId(7));
+        assert_eq!(ToProtoAlias::<u32>::to_proto_alias(&shard), 7);
+        assert_eq!(ToProtoAlias::<Option<u32>>::to_proto_alias(&shard), Some(7));
+        assert_eq!(shard.to_string(), "7");
+        assert_eq!("7".parse::<ShardId>().unwrap(), shard);
+
+        let error = ShardId::from_proto_alias(70_000u32).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid ShardId: 70000 is out of the range of u16"
+        );
+        let error = "70000".parse::<ShardId>().unwrap_err();
+        assert_eq!(error.reason(), "70000 is out of the range of u16");
+        let error = "-1".parse::<ShardId>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid ShardId: \"-1\" is not a number: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn test_uint64() {
+        let account = AccountId::from_proto_alias(u64::MAX).unwrap();
+        assert_eq!(ToProtoAlias::<u64>::to_proto_alias(&account), u64::MAX);
+        assert_eq!(account.to_string().parse::<AccountId>().unwrap(), account);
+
+        // element wise, with the index in the path
+        let accounts: Vec<AccountId> = FromProtoAlias::from_proto_alias(vec![1u64, 2]).unwrap();
+        assert_eq!(accounts, [AccountId(1), AccountId(2)]);
+        let error =
+            <Vec<ShardId> as FromProtoAlias<_>>::from_proto_alias(vec![1u32, 1 << 20]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "[1]: Invalid ShardId: 1048576 is out of the range of u16"
+        );
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn test_string_uuid() {
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        pub struct OrderId(uuid::Uuid);
+
+        impl_proto_id!(OrderId(uuid::Uuid) as string_uuid);
+
+        let order =
+            OrderId::from_proto_alias("67E55044-10B1-426F-9247-BB680E5FE0C8".to_owned()).unwrap();
+        assert_eq!(
+            ToProtoAlias::<String>::to_proto_alias(&order),
+            "67e55044-10b1-426f-9247-bb680e5fe0c8"
+        );
+        assert_eq!(order.to_string(), "67e55044-10b1-426f-9247-bb680e5fe0c8");
+        assert_eq!(order.to_string().parse::<OrderId>().unwrap(), order);
+
+        let error = OrderId::from_proto_alias(uuid::Uuid::nil().to_string()).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid OrderId: the uuid is nil");
+        let error = "not-a-uuid".parse::<OrderId>().unwrap_err();
+        assert_eq!(error.id(), "OrderId");
+        assert!(error.reason().starts_with("\"not-a-uuid\" is not a uuid"));
+    }
+}
@@ -0,0 +1,212 @@
+//! Interning the strings repeated across the messages of the batches, like the labels or the host
+//! names, so every distinct string is allocated once. The fields marked with `#[proto(intern)]` are
+//! converted in to `Arc<str>`, `Option<Arc<str>>` or `Vec<Arc<str>>` through the interner of the
+//! thread.
+//! ```ignore
+//! #[derive(FromProto, ToProto)]
+//! #[proto(type = "proto::Sample")]
+//! struct Sample {
+//!     #[proto(intern)]
+//!     host: Arc<str>,
+//!     #[proto(intern)]
+//!     labels: Vec<Arc<str>>,
+//!     value: f64,
+//! }
+//!
+//! let samples = batch.samples.from_proto()?;
+//! // the strings are kept until they are cleared
+//! proto_conve::intern::clear();
+//! ```
+//! The interner of the thread keeps the strings, until it is cleared, or replaced with
+//! `intern::replace`, like with a new one for every batch. The `Arc<str>` fields without `intern` are
+//! allocated for every value.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// The distinct strings, shared by their `Arc`s
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl Interner {
+    /// The interner without any strings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The interner with the space for `capacity` distinct strings
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            strings: HashSet::with_capacity(capacity),
+        }
+    }
+
+    /// The shared string equal to `value`, it is allocated the first time it is seen
+    pub fn intern(&mut self, value: &str) -> Arc<str> {
+        match self.strings.get(value) {
+            Some(interned) => interned.clone(),
+            None => {
+                let interned: Arc<str> = Arc::from(value);
+                self.strings.insert(interned.clone());
+                interned
+            }
+        }
+    }
+
+    /// The number of the distinct strings
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// There are no strings
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// The number of the distinct strings, which fit without allocating
+    pub fn capacity(&self) -> usize {
+        self.strings.capacity()
+    }
+
+    /// Forget the strings, the converted values keep theirs
+    pub fn clear(&mut self) {
+        self.strings.clear();
+    }
+
+    /// Free the space, keeping at least `capacity` of it
+    pub fn shrink_to(&mut self, capacity: usize) {
+        self.strings.shrink_to(capacity);
+    }
+}
+
+thread_local! {
+    static INTERNER: RefCell<Interner> = RefCell::new(Interner::new());
+}
+
+/// Use the interner of the thread
+pub fn with<R>(f: impl FnOnce(&mut Interner) -> R) -> R {
+    INTERNER.with(|interner| f(&mut interner.borrow_mut()))
+}
+
+/// The shared string equal to `value` from the interner of the thread
+pub fn intern(value: &str) -> Arc<str> {
+    with(|interner| interner.intern(value))
+}
+
+/// Forget the strings of the interner of the thread
+pub fn clear() {
+    with(Interner::clear);
+}
+
+/// Use the `interner` in the thread, returning the one used before
+pub fn replace(interner: Interner) -> Interner {
+    with(|current| std::mem::replace(current, interner))
+}
+
+/// The proto strings, which are interned
+pub trait InternProto: Sized {
+    /// The interned value
+    type Value;
+
+    /// Intern the strings of the proto value
+    fn intern(self, interner: &mut Interner) -> Self::Value;
+
+    /// The proto value of the interned value
+    fn to_proto(value: &Self::Value) -> Self;
+}
+
+impl InternProto for String {
+    type Value = Arc<str>;
+
+    fn intern(self, interner: &mut Interner) -> Self::Value {
+        interner.intern(&self)
+    }
+
+    fn to_proto(value: &Self::Value) -> Self {
+        value.to_string()
+    }
+}
+
+impl<P: InternProto> InternProto for Option<P> {
+    type Value = Option<P::Value>;
+
+    fn intern(self, interner: &mut Interner) -> Self::Value {
+        self.map(|value| value.intern(interner))
+    }
+
+    fn to_proto(value: &Self::Value) -> Self {
+        value.as_ref().map(P::to_proto)
+    }
+}
+
+impl<P: InternProto> InternProto for Vec<P> {
+    type Value = Vec<P::Value>;
+
+    fn intern(self, interner: &mut Interner) -> Self::Value {
+        self.into_iter()
+            .map(|value| value.intern(interner))
+            .collect()
+    }
+
+    fn to_proto(value: &Self::Value) -> Self {
+        value.iter().map(P::to_proto).collect()
+    }
+}
+
+/// Intern the proto field with the interner of the thread, used by `#[proto(intern)]`
+#[doc(hidden)]
+pub fn from_proto<P: InternProto>(proto: P) -> anyhow::Result<P::Value> {
+    Ok(with(|interner| proto.intern(interner)))
+}
+
+/// The proto field of the interned field, used by `#[proto(intern)]`
+#[doc(hidden)]
+pub fn to_proto<P: InternProto>(value: &P::Value) -> P {
+    P::to_proto(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared() {
+        let protos = vec!["web-1".to_owned(); 10_000];
+        let hosts = from_proto(protos).unwrap();
+        assert_eq!(hosts.len(), 10_000);
+        assert!(hosts.iter().all(|host| Arc::ptr_eq(host, &hosts[0])));
+        assert_eq!(with(|interner| interner.len()), 1);
+        // the one allocation is shared by the values and the interner
+        assert_eq!(Arc::strong_count(&hosts[0]), 10_001);
+
+        let host = from_proto(Some("web-1".to_owned())).unwrap().unwrap();
+        assert!(Arc::ptr_eq(&host, &hosts[0]));
+        assert_eq!(
+            to_proto::<Vec<String>>(&hosts[..2].to_vec()),
+            ["web-1", "web-1"]
+        );
+        clear();
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut interner = Interner::with_capacity(64);
+        assert!(interner.capacity() >= 64);
+        let first = interner.intern("a");
+        interner.intern("b");
+        assert_eq!(interner.len(), 2);
+        interner.clear();
+        interner.shrink_to(0);
+        assert!(interner.is_empty());
+        // the cleared strings are kept by the values, but allocated again
+        assert!(!Arc::ptr_eq(&interner.intern("a"), &first));
+        assert_eq!(&*first, "a");
+
+        let previous = replace(interner);
+        assert_eq!(intern("a"), first);
+        assert_eq!(replace(previous).len(), 1);
+    }
+}
@@ -0,0 +1,124 @@
+//! The JS times of the browser SDKs built for `wasm32-unknown-unknown`, with the `wasm` feature. The
+//! epoch millis `f64` of `Date.now()` and `Date.prototype.getTime()`, and the `js_sys::Date` values,
+//! convert from and in to `chrono::DateTime<chrono::Utc>` with the arm of `impl_traits!`.
+//! ```ignore
+//! mod proto {
+//!     proto_conve::impl_traits!(prelude);
+//!     proto_conve::impl_traits!(js chrono::DateTime<chrono::Utc>);
+//! }
+//!
+//! let created = chrono::DateTime::<chrono::Utc>::from_proto_alias(js_sys::Date::now())?;
+//! let millis: f64 = created.to_proto_alias();
+//! let date: js_sys::Date = created.to_proto_alias();
+//! ```
+//! The millis, which are NaN, infinite or further from the epoch than the `8.64e15` of the JS dates, are
+//! rejected, and so are the ones past the years `±262143` of chrono. The fraction of the millisecond is truncated toward zero, as `new Date(millis)` does, so
+//! `-1.5` is the instant of `-1`. In to JS the nanos under the millisecond are dropped, rounding down as
+//! `timestamp_millis` does, and the instants out of the range of the JS dates are the invalid dates.
+
+/// The furthest JS date from the epoch in millis, a hundred million days
+pub const MAX_MILLIS: f64 = 8.64e15;
+
+/// The whole millis of the JS time, it is an error, when it is not a valid JS date
+pub fn millis(value: f64) -> anyhow::Result<i64> {
+    if !value.is_finite() {
+        anyhow::bail!("The JS time {value} is not a finite number of millis");
+    }
+    if value.abs() > MAX_MILLIS {
+        anyhow::bail!("The JS time {value} ms is out of the range of the JS dates");
+    }
+    Ok(value.trunc() as i64)
+}
+
+/// The JS date of the epoch `millis`
+pub fn date(millis: i64) -> js_sys::Date {
+    let date = js_sys::Date::new_0();
+    date.set_time(millis as f64);
+    date
+}
+
+/// Generate the conversions of the JS times, the `js` arm of `impl_traits!`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_wasm {
+    (chrono::DateTime<chrono::Utc>) => {
+        impl FromProtoAlias<f64> for chrono::DateTime<chrono::Utc> {
+            fn from_proto_alias(value: f64) -> anyhow::Result<Self> {
+                let millis = $crate::js_time::millis(value)?;
+                match $crate::chrono_compat::from_unix_millis(millis) {
+                    Some(value) => Ok(value),
+                    None => anyhow::bail!("Failed to parse timestamp: {millis} ms"),
+                }
+            }
+        }
+
+        impl ToProtoAlias<f64> for chrono::DateTime<chrono::Utc> {
+            fn to_proto_alias(&self) -> f64 {
+                $crate::chrono_compat::unix_millis(self) as f64
+            }
+        }
+
+        impl FromProtoAlias<$crate::__private::js_sys::Date> for chrono::DateTime<chrono::Utc> {
+            fn from_proto_alias(value: $crate::__private::js_sys::Date) -> anyhow::Result<Self> {
+                FromProtoAlias::<f64>::from_proto_alias(value.get_time())
+            }
+        }
+
+        impl ToProtoAlias<$crate::__private::js_sys::Date> for chrono::DateTime<chrono::Utc> {
+            fn to_proto_alias(&self) -> $crate::__private::js_sys::Date {
+                $crate::js_time::date($crate::chrono_compat::unix_millis(self))
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+    impl_traits!(js chrono::DateTime<chrono::Utc>);
+
+    type DateTime = chrono::DateTime<chrono::Utc>;
+
+    #[test]
+    fn test_millis() {
+        let value = DateTime::from_proto_alias(1_700_000_000_123.0).unwrap();
+        assert_eq!(value.timestamp_millis(), 1_700_000_000_123);
+        let millis: f64 = value.to_proto_alias();
+        assert_eq!(millis, 1_700_000_000_123.0);
+
+        // truncated toward zero as by `new Date(millis)`
+        let value = DateTime::from_proto_alias(2.9).unwrap();
+        assert_eq!(value.timestamp_millis(), 2);
+        let value = DateTime::from_proto_alias(-1.5).unwrap();
+        assert_eq!(value.timestamp_millis(), -1);
+        let value = DateTime::from_timestamp_nanos(-1_500_000);
+        assert_eq!(ToProtoAlias::<f64>::to_proto_alias(&value), -2.0);
+
+        let value = DateTime::from_proto_alias(-8e15).unwrap();
+        assert_eq!(value.to_rfc3339(), "-251540-02-03T09:46:40+00:00");
+    }
+
+    #[test]
+    fn test_invalid() {
+        let error = DateTime::from_proto_alias(f64::NAN).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The JS time NaN is not a finite number of millis"
+        );
+        assert!(DateTime::from_proto_alias(f64::INFINITY).is_err());
+        let error = DateTime::from_proto_alias(-8.64e15 - 1.0).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The JS time -8640000000000001 ms is out of the range of the JS dates"
+        );
+        // the latest JS dates are after the chrono ones
+        let error = DateTime::from_proto_alias(super::MAX_MILLIS).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Failed to parse timestamp: 8640000000000000 ms"
+        );
+    }
+}
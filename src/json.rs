@@ -0,0 +1,127 @@
+//! Helpers to go between the proto3 JSON mapping and the domain values, enabled with the `json` feature.
+//! The proto types have to implement serde traits with the proto3 JSON representation, which is what
+//! `pbjson` generates (and `pbjson-types` provides for the well known types).
+
+use anyhow::Context;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Deserialize the proto value from its proto3 JSON representation
+pub fn from_str<P: DeserializeOwned>(s: &str) -> anyhow::Result<P> {
+    serde_json::from_str(s).with_context(|| {
+        format!(
+            "Failed to parse proto JSON for {}",
+            std::any::type_name::<P>()
+        )
+    })
+}
+
+/// Serialize the proto value in to its proto3 JSON representation
+pub fn to_string<P: Serialize>(value: &P) -> anyhow::Result<String> {
+    serde_json::to_string(value).with_context(|| {
+        format!(
+            "Failed to serialize proto JSON for {}",
+            std::any::type_name::<P>()
+        )
+    })
+}
+
+/// Generate the JSON conversions, this is part of the `impl_traits!(prelude)`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_json {
+    (prelude) => {
+        pub use super::{from_proto_json_str, ToProtoJson};
+    };
+    () => {
+        /// Parse the proto3 JSON representation of the proto type `P` and convert it in to the domain value
+        pub fn from_proto_json_str<P, T>(s: &str) -> anyhow::Result<T>
+        where
+            P: FromProto<Result = T> + $crate::__private::serde::de::DeserializeOwned,
+        {
+            $crate::json::from_str::<P>(s)?.from_proto()
+        }
+
+        /// Convert the value in to proto value and serialize it in to proto3 JSON
+        pub trait ToProtoJson {
+            /// Convert the value and serialize the proto value in to proto3 JSON string
+            fn to_proto_json_string(&self) -> anyhow::Result<String>;
+        }
+
+        impl<T: ToProto> ToProtoJson for T
+        where
+            T::Result: $crate::__private::serde::Serialize,
+        {
+            fn to_proto_json_string(&self) -> anyhow::Result<String> {
+                $crate::json::to_string(&self.to_proto())
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::impl_traits;
+    use pbjson_types::{Duration, Timestamp};
+
+    impl_traits!(prelude);
+    impl_traits!(Duration, chrono::Duration);
+    impl_traits!(Timestamp, chrono::DateTime<chrono::Utc>);
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct ProtoJob {
+        started: Timestamp,
+        timeout: Duration,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Job {
+        started: chrono::DateTime<chrono::Utc>,
+        timeout: chrono::Duration,
+    }
+
+    impl FromProto for ProtoJob {
+        type Result = Job;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(Job {
+                started: self.started.from_proto()?,
+                timeout: self.timeout.from_proto()?,
+            })
+        }
+    }
+
+    impl ToProto for Job {
+        type Result = ProtoJob;
+
+        fn to_proto(&self) -> Self::Result {
+            ProtoJob {
+                started: self.started.to_proto_alias(),
+                timeout: self.timeout.to_proto_alias(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let json = r#"{"started":"2023-09-01T12:30:00.250Z","timeout":"1.5s"}"#;
+        let job = from_proto_json_str::<ProtoJob, _>(json).unwrap();
+        assert_eq!(
+            job,
+            Job {
+                started: "2023-09-01T12:30:00.250Z".parse().unwrap(),
+                timeout: chrono::Duration::milliseconds(1500),
+            }
+        );
+
+        let json = job.to_proto_json_string().unwrap();
+        assert_eq!(from_proto_json_str::<ProtoJob, _>(&json).unwrap(), job);
+    }
+
+    #[test]
+    fn test_json_invalid() {
+        let error = from_proto_json_str::<Timestamp, _>(r#""yesterday""#).unwrap_err();
+        assert!(error.to_string().contains("Failed to parse proto JSON"));
+    }
+}
@@ -0,0 +1,282 @@
+//! The conversions of the map keys, used by the derive with `#[proto(key = "uuid")]` for the
+//! `map<string, V>` fields keyed by `uuid::Uuid` with the `uuid` feature, `#[proto(key = "enum")]` for
+//! the `map<int32, V>` fields keyed by the enums, or `#[proto(key_with = "module")]` for any other keys.
+//!
+//! The keys, which fail to convert, are an error with the raw key in the path, like
+//! `owners["not-a-uuid"]`. The raw keys converting in to the same key are an error too, instead of one
+//! of the values overwriting the other.
+//!
+//! The `map<int32, V>` fields keyed by the values of a prost enum convert with `#[proto(key_enum =
+//! "path::Status")]` in to the maps keyed by `Status` or `OpenEnum<Status>`, see `EnumKey`. The keys
+//! unknown to the enum, like the ones of the newer peers, are handled by the `UnknownKeys` policy of
+//! `#[proto(unknown_keys = "...")]`:
+//! ```ignore
+//! #[derive(FromProto, ToProto, FromProtoLossy)]
+//! #[proto(type = "proto::Quotas")]
+//! struct Quotas {
+//!     // `error`, the error lists all the unknown keys
+//!     #[proto(key_enum = "proto::Tier")]
+//!     limits: HashMap<proto::Tier, u32>,
+//!     // `skip`, the entries are left out, `FromProtoLossy` reports them as `LossyEvent::UnknownEnum`
+//!     #[proto(key_enum = "proto::Tier", unknown_keys = "skip")]
+//!     usage: HashMap<proto::Tier, u32>,
+//!     // `open`, the only policy of the `OpenEnum` keys, which keep the unknown ones
+//!     #[proto(key_enum = "proto::Tier", unknown_keys = "open")]
+//!     bonus: BTreeMap<OpenEnum<proto::Tier>, u32>,
+//! }
+//! ```
+//! The values are converted with their traits, their errors have the raw key in the path. The keys
+//! are converted back in to their raw values.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+use crate::enumeration::enum_name;
+use crate::violation::{coded, ErrorCode};
+use crate::OpenEnum;
+
+/// The map of the converted keys
+#[doc(hidden)]
+pub trait KeyMap<K, V> {
+    /// Insert the entry, unless there is one of the key already
+    fn insert_new(&mut self, key: K, value: V) -> bool;
+}
+
+impl<K: Eq + Hash, V, S: BuildHasher> KeyMap<K, V> for HashMap<K, V, S> {
+    fn insert_new(&mut self, key: K, value: V) -> bool {
+        match self.entry(key) {
+            std::collections::hash_map::Entry::Occupied(_) => false,
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(value);
+                true
+            }
+        }
+    }
+}
+
+impl<K: Ord, V> KeyMap<K, V> for BTreeMap<K, V> {
+    fn insert_new(&mut self, key: K, value: V) -> bool {
+        match self.entry(key) {
+            std::collections::btree_map::Entry::Occupied(_) => false,
+            std::collections::btree_map::Entry::Vacant(entry) => {
+                entry.insert(value);
+                true
+            }
+        }
+    }
+}
+
+/// Insert the entry of the `converted` key, it is an error with the `raw` key, when another raw key
+/// converted in to the same one, used by the derive
+#[doc(hidden)]
+pub fn insert<M, K, V>(
+    map: &mut M,
+    raw: &impl fmt::Display,
+    converted: K,
+    value: V,
+) -> anyhow::Result<()>
+where
+    M: KeyMap<K, V>,
+{
+    if map.insert_new(converted, value) {
+        Ok(())
+    } else {
+        Err(crate::error::at_key(
+            anyhow::anyhow!("The key converts in to the same key as another one"),
+            raw,
+        ))
+    }
+}
+
+/// What the maps keyed by the prost enum do with the raw keys, which are not the values of the enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum UnknownKeys {
+    /// The conversion fails, the error lists all the unknown keys
+    #[default]
+    Error,
+    /// The entries of the unknown keys are left out
+    Skip,
+    /// The unknown keys are kept, the policy of the `OpenEnum` keys
+    Open,
+}
+
+/// The key of the maps keyed by the prost enum `E`, the enum itself or `OpenEnum<E>`
+pub trait EnumKey<E>: Sized {
+    /// The key of the raw value, `None` if it is unknown
+    fn from_raw(raw: i32) -> Option<Self>;
+
+    /// The raw value of the key
+    fn to_raw(&self) -> i32;
+}
+
+impl<E: TryFrom<i32> + Copy + Into<i32>> EnumKey<E> for E {
+    fn from_raw(raw: i32) -> Option<Self> {
+        E::try_from(raw).ok()
+    }
+
+    fn to_raw(&self) -> i32 {
+        (*self).into()
+    }
+}
+
+impl<E: TryFrom<i32> + Copy + Into<i32>> EnumKey<E> for OpenEnum<E> {
+    fn from_raw(raw: i32) -> Option<Self> {
+        Some(E::try_from(raw).map_or(OpenEnum::Unknown(raw), OpenEnum::Known))
+    }
+
+    fn to_raw(&self) -> i32 {
+        match self {
+            OpenEnum::Known(known) => (*known).into(),
+            OpenEnum::Unknown(raw) => *raw,
+        }
+    }
+}
+
+/// Convert the map keyed by the raw values of the prost enum `E`, the values are converted with
+/// `convert` getting the raw key. The skipped unknown keys are returned with the map in their order,
+/// used by the derive.
+#[doc(hidden)]
+pub fn from_proto_enum_keys<E, K, P, V, M>(
+    raw: impl IntoIterator<Item = (i32, P)>,
+    unknown: UnknownKeys,
+    mut convert: impl FnMut(i32, P) -> anyhow::Result<V>,
+) -> anyhow::Result<(M, Vec<i32>)>
+where
+    K: EnumKey<E>,
+    M: KeyMap<K, V> + Default,
+{
+    let mut map = M::default();
+    let mut unknown_keys = Vec::new();
+    for (key, value) in raw {
+        let Some(converted) = K::from_raw(key) else {
+            unknown_keys.push(key);
+            continue;
+        };
+        // the values of the known keys are not converted, once the map fails
+        if unknown_keys.is_empty() || unknown == UnknownKeys::Skip {
+            let value = convert(key, value).map_err(|error| crate::error::at_key(error, &key))?;
+            insert(&mut map, &key, converted, value)?;
+        }
+    }
+    unknown_keys.sort_unstable();
+    match unknown {
+        UnknownKeys::Skip => Ok((map, unknown_keys)),
+        _ if unknown_keys.is_empty() => Ok((map, unknown_keys)),
+        _ => {
+            let keys: Vec<_> = unknown_keys.iter().map(i32::to_string).collect();
+            Err(coded(
+                ErrorCode::UnknownEnum,
+                format_args!("Unknown keys {} of {}", keys.join(", "), enum_name::<E>()),
+            ))
+        }
+    }
+}
+
+/// The `uuid::Uuid` keys of the `map<string, V>` fields, the keys are written hyphenated in lower case
+#[cfg(feature = "uuid")]
+pub mod uuid {
+    /// Parse the key, any of the formats of `Uuid::parse_str` is accepted
+    pub fn from_proto(key: String) -> anyhow::Result<::uuid::Uuid> {
+        ::uuid::Uuid::parse_str(&key)
+            .map_err(|error| anyhow::anyhow!("The key is not a UUID: {error}"))
+    }
+
+    /// Write the key hyphenated
+    pub fn to_proto(key: &::uuid::Uuid) -> String {
+        key.hyphenated().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert() {
+        let mut map = HashMap::new();
+        insert(&mut map, &"1", 1u32, "a").unwrap();
+        insert(&mut map, &"2", 2u32, "b").unwrap();
+        let error = insert(&mut map, &"01", 1u32, "c").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "[\"01\"]: The key converts in to the same key as another one"
+        );
+        assert_eq!(map[&1], "a");
+
+        let mut map = BTreeMap::new();
+        insert(&mut map, &"a", 'a', 1).unwrap();
+        assert!(insert(&mut map, &"A", 'a', 2).is_err());
+    }
+
+    /// The enum as generated by prost
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    enum Tier {
+        Free = 1,
+        Pro = 2,
+    }
+
+    impl TryFrom<i32> for Tier {
+        type Error = i32;
+
+        fn try_from(value: i32) -> Result<Self, i32> {
+            match value {
+                1 => Ok(Tier::Free),
+                2 => Ok(Tier::Pro),
+                value => Err(value),
+            }
+        }
+    }
+
+    impl From<Tier> for i32 {
+        fn from(tier: Tier) -> i32 {
+            tier as i32
+        }
+    }
+
+    fn convert<K: EnumKey<Tier> + Ord>(
+        raw: &[(i32, u32)],
+        unknown: UnknownKeys,
+    ) -> anyhow::Result<(BTreeMap<K, u32>, Vec<i32>)> {
+        from_proto_enum_keys::<Tier, K, _, _, _>(raw.iter().copied(), unknown, |_, value| {
+            anyhow::ensure!(value < 100, "The limit {value} is too high");
+            Ok(value)
+        })
+    }
+
+    #[test]
+    fn test_enum_keys() {
+        let (map, skipped) = convert::<Tier>(&[(2, 20), (1, 10)], UnknownKeys::Error).unwrap();
+        assert_eq!(map, BTreeMap::from([(Tier::Free, 10), (Tier::Pro, 20)]));
+        assert!(skipped.is_empty());
+        assert_eq!(Tier::Pro.to_raw(), 2);
+
+        let error = convert::<Tier>(&[(9, 1), (1, 10), (7, 1)], UnknownKeys::Error).unwrap_err();
+        assert_eq!(error.to_string(), "Unknown keys 7, 9 of Tier");
+        assert_eq!(ErrorCode::of(&error), ErrorCode::UnknownEnum);
+
+        let raw = [(9, 1), (1, 10), (7, 1)];
+        let (map, skipped) = convert::<Tier>(&raw, UnknownKeys::Skip).unwrap();
+        assert_eq!(map, BTreeMap::from([(Tier::Free, 10)]));
+        assert_eq!(skipped, [7, 9]);
+
+        let (map, _) = convert::<OpenEnum<Tier>>(&raw, UnknownKeys::Open).unwrap();
+        assert_eq!(
+            map.keys().map(EnumKey::to_raw).collect::<Vec<_>>(),
+            [1, 7, 9]
+        );
+        assert_eq!(map[&OpenEnum::Unknown(9)], 1);
+
+        let error = convert::<Tier>(&[(1, 100)], UnknownKeys::Skip).unwrap_err();
+        assert_eq!(error.to_string(), "[\"1\"]: The limit 100 is too high");
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn test_uuid() {
+        let key = uuid::from_proto("67E55044-10B1-426F-9247-BB680E5FE0C8".to_owned()).unwrap();
+        assert_eq!(uuid::to_proto(&key), "67e55044-10b1-426f-9247-bb680e5fe0c8");
+        let error = uuid::from_proto("not-a-uuid".to_owned()).unwrap_err();
+        assert!(error.to_string().starts_with("The key is not a UUID: "));
+    }
+}
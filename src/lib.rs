@@ -1,6 +1,887 @@
 //! These are convenient traits to convert the proto values in to the appropriate rust values
 //! To be able to implement them for custom types in the project, they have to be defined in your project,
 //! so use the macro
+//!
+//! The traits are imported with the `prelude` module generated by the macro, `use
+//! path::to::prelude::*;` brings all of them in to scope with the wrappers and the derive macros of this
+//! crate, see `prelude`. The methods of `ToProtoAlias` are `to_proto_alias` and
+//! `to_proto_alias_option`, apart from the ones of `ToProto`, the old `to_proto` and `to_proto_option`
+//! of them are kept deprecated, defaulting to the renamed ones, which default to them, so the impls of
+//! the old names still compile. The calls of `to_proto` of the types having both traits name `ToProto`.
+//! The calls of the API before the renames, and the arms of `impl_traits!` of then, keep compiling with
+//! only the deprecation warnings naming the replacements, as checked by the `compat` test target.
+//! The `Vec`, slices, `Option` and `HashMap` values of the `ToProtoAlias` and `FromProtoAlias` types
+//! convert element wise, like the enums in to the repeated `i32` fields. `ToProto` of the references
+//! is the one of the values they borrow, so the borrowed shapes, like `Option<&T>`, `&Option<T>`,
+//! `&[&T]` and `Vec<&T>`, convert in to `Option<T::Result>` and `Vec<T::Result>` as the owned ones.
+//!
+//! The front door of the conversions is `convert_from_proto` and `convert_to_proto` of the prelude,
+//! the errors name the proto and the domain type once at the top, and the conversions are observed
+//! with the `metrics` feature, see `convert`.
+//!
+//! The errors of the conversions have their stable `ErrorCode`, and `to_field_violations` gives their
+//! paths, codes and messages for the structured errors of the APIs, serializable with the `serde`
+//! feature, see `violation`. The derived `ValidateFromProto` of the prelude gives the violations of
+//! all of the fields of the message, without converting it, see `dry_run`.
+//!
+//! With the `derive` feature, the conversions for structs can be derived with `#[derive(FromProto, ToProto)]`.
+//! The builders of the proto messages, setting the fields from the rust values, are derived with
+//! `#[derive(ToProtoBuilder)]`, see `builder`.
+//!
+//! With the `test-util` feature, the prelude has the round trip assertions `assert_roundtrip_from` and
+//! `assert_roundtrip_to`, or `assert_roundtrip_from_alias` and `assert_roundtrip_to_alias` of the
+//! `ToProtoAlias` conversions, and `test_util` has the proptest strategies of the seconds and nanos messages
+//! and `proto_diff` of the values, with the `prost` feature also `proto_diff_encoded` of the messages.
+//! The failures are injected in to the conversions with `FaultyProto` and `fault::inject`, see `fault`.
+//! The synthetic inputs of the benchmarks, `benches/conversions.rs` run with `cargo bench --features
+//! derive,test-util`, are made with `test_util::synthetic`, also for the benchmarks of the other crates.
+//!
+//! With the `serde` feature, the prelude has the `proto_serde` module and `ViaProto` wrapper
+//! serializing the domain values through their proto values, see `proto_serde`.
+//!
+//! The proto `bytes` fields are converted as one buffer with `ProtoBytes`, and with the `bytes`
+//! feature also from and in to `bytes::Bytes`, see `proto_bytes`.
+//!
+//! The flags of a fieldless enum, the proto `repeated bool` or `uint64` mask, convert in to
+//! `flags::FlagSet`, see `flags`. The repeated enum fields convert in to `enum_set::EnumSet` of the
+//! variants, see `enum_set`, and the repeated string tags in to `tagged_set::TaggedSet` of the known
+//! variants of the string enum and the free-form tags, see `tagged_set`.
+//!
+//! With the `prost` feature, the files of the length delimited proto records are read in to the
+//! domain values with `read_proto_records` of the prelude and written with `write_proto_records`, see
+//! `records`. The ETags of the optimistic concurrency are computed from the encoded proto form of the
+//! values with `proto_etag` of the prelude, SHA-256 with the `sha2` feature, see `etag`.
+//!
+//! With the `prost-types` feature, the `google.protobuf.ListValue` fields convert in to the `Vec` of
+//! `serde_json::Value` or of the values of one kind, and `NullValue` in to `()`, see `list_value`.
+//!
+//! With the `tonic` feature, the requests and the responses of tonic are converted with
+//! `FromProtoRequest` and `IntoProtoResponse` of the prelude, with the `futures` feature too, the
+//! streams of the streaming RPCs with `from_proto_stream` and `to_proto_stream`, see `grpc`.
+//!
+//! With the `tracing` feature, the failed conversions are traced as `warn` events, see `trace`.
+//!
+//! With the `wasm` feature, the JS times of the browser SDKs convert in to `chrono::DateTime`, see
+//! `js_time`. On `wasm32-unknown-unknown` the elapsed times of the reports are zero, see `clock`.
+//!
+//! The chrono arms of `impl_traits!` call chrono through `chrono_compat`, by default with the API of
+//! chrono 0.4.35 and newer, the default features do not build with an older one. The crates pinning
+//! an older chrono 0.4, since 0.4.24, enable the `chrono-legacy` feature, which uses the API
+//! deprecated since, with the same results. The `chrono-tz` feature needs chrono 0.4.25.
+//!
+//! The numbers sent as the strings with the thousands separators or the decimal commas, like
+//! `"1 234,5"`, are read with `number::LenientNumber` in the `NumberPolicy` of `number::scope`, and
+//! written in the canonical form, see `number`.
+//!
+//! With the `reflect` feature, the structs deriving `FromProto` describe their proto type and the
+//! mappings of their fields with `reflect::HasProtoMapping`, for the tooling, see `reflect`. With the
+//! `coverage` feature, the conversions register their proto types and `coverage::conversion_coverage`
+//! reports the proto messages without them, for the CI gates, see `coverage`.
+//!
+//! With the `text-format` feature, the `.textproto` fixtures are read in to the proto messages deriving
+//! `TextProto` and converted in to their domain types with `from_text_proto` of the prelude, the
+//! errors have the lines and the columns, see `text_format`.
+//!
+//! With the `unicode` feature, the proto strings are normalized in to the Unicode NFC with
+//! `text::NfcString`, see `text`.
+//!
+//! With the `metrics` feature, the conversions with `from_proto_observed` are counted by the
+//! `ConversionObserver` installed with `metrics::set_observer`, with their deprecation notices, see
+//! `metrics`.
+//!
+//! The clients of older schema versions are converted with `FromProtoVersioned` and
+//! `version::SchemaVersion`.
+//!
+//! The masked fields of the update requests are merged in to the existing values with
+//! `MergeFromProto` and `mask::FieldMaskSet`.
+//!
+//! The data migrations convert as much as possible with `FromProtoLossy`, reporting what was lossy or
+//! defaulted in `lossy::ConversionReport`.
+//!
+//! The proto integers convert in to the `NonZero` integers with `FromProtoAlias`, the zero is an
+//! error, so the `Option<i32>` of the proto3 `optional` fields converts in to `Option<NonZeroU32>`.
+//! The derived fields without presence, the plain proto3 scalars with zero as missing, are marked with
+//! `#[proto(presence = "implicit")]`.
+//!
+//! The conversions, which can not fail, like of the identity scalars, implement `FromProtoInfallible`
+//! converting without the `Result`, and so do the `Vec` and `Option` of them with
+//! `FromProtoInfallibleElements`.
+//!
+//! The iterators of the referenced values, like the values of a map, convert in to the proto lists
+//! with `to_proto_from_iter` and `ToProtoIterator::to_proto_vec`, or with `try_to_proto_from_iter`
+//! for the `TryToProto` values.
+//!
+//! The huge inputs, like the records of the backfill files, are converted in to the chunks of the
+//! converted values with `ChunkedFromProto::from_proto_chunked`, reporting the progress after every
+//! chunk, see `chunked`.
+//!
+//! The physical quantities carried in the scaled proto integers, like `int32 temperature_milli_c`,
+//! convert in to `f64` with `Scaled`, rounding them back in to proto, see `scaled`. The money and the
+//! quantities sent in the proto `double` convert in to the integer amounts with `FloatToScaledInt`,
+//! checking the float errors.
+//!
+//! The proto strings convert in to `Arc<str>`, `Rc<str>`, `Box<str>` and `Cow<'static, str>` with
+//! `FromProtoAlias<String>`, which is also the `ToProtoAlias<String>` of them and of `&str`.
+//!
+//! The strings repeated in the batches are shared by interning them, see `intern`, and the repeated
+//! sub-messages by caching their conversions, see `cache`.
+//!
+//! The hot paths rejecting the malformed messages check the required fields with `required_lite` of
+//! the prelude, its `error::MissingField` does not allocate, until it is converted in to
+//! `anyhow::Error`, which the derived conversions do only when the error is returned.
+//!
+//! The personal data is encrypted at the conversion boundary with the `ProtoTransform` of the
+//! `#[proto(transform)]` fields, see `transform`.
+//!
+//! The missing `#[proto(default_from_ctx)]` fields take the defaults of the tenant configuration from
+//! the `DefaultsProvider` of `defaults::scope`, before their static defaults, see `defaults`.
+//!
+//! The raw `i32` enum fields of prost are converted with `ProtoEnumField`, instead of the getters
+//! turning the unknown values in to the default variant, see `enumeration`.
+//!
+//! The keys of the derived map fields are converted with `#[proto(key = "enum")]`, with the `uuid`
+//! feature also `#[proto(key = "uuid")]`, or `#[proto(key_with = "module")]`, and the keys of the
+//! prost enums with `#[proto(key_enum = "...")]` in the policy of their unknown keys, see `key`.
+//!
+//! The string settings of `map<string, string>` convert in to the typed config structs with
+//! `impl_proto_settings!`, see `settings`.
+//!
+//! The fields of the patch requests convert in to `Patch`, which is left as it is, cleared or set,
+//! from the proto3 `optional` fields, or from the nullable wrapper messages with `impl_proto_patch!`,
+//! see `patch`.
+//!
+//! The list responses with the repeated items, `next_page_token` and `total_size` convert in to
+//! `Page` with `impl_proto_page!`, see `page`.
+//!
+//! The string fields of the types parsed with `FromStr` and written with `Display`, like the ids or the
+//! versions, convert with `impl_proto_via_str!`, see `via_str`.
+//!
+//! The typed ids, the newtypes of the uuids, the strings and the integers, convert from and in to the
+//! proto fields of their wire forms with `impl_proto_id!`, validating them and naming the id type in
+//! the errors, see `ids`. The wrapper messages of a single field, like `message UserId { string value =
+//! 1; }`, convert in to the rust type of the field with `impl_proto_wrapper!`, see `wrapper`.
+//!
+//! The proto values get another conversion than the one of their type, like of the proto types of
+//! another library in to the rust types of yet another one, through the `Proto` wrapper, see `proto`.
+//!
+//! The plugins convert the boxed proto messages by their type names with `erased::ProtoRegistry`,
+//! where the prelude trait `RegisterFromProto` registers the `FromProto` conversions, see `erased`.
+//! The own envelopes of the kind and the encoded payload, instead of `google.protobuf.Any`, convert in
+//! to the domain values of the kinds registered in `envelope::EnvelopeRegistry`, see `envelope`.
+//!
+//! The conversions looking up the values, like the entities by their ids, convert with the context of
+//! the lookups with `AsyncFromProtoWith` of the prelude and the fields of `#[proto(async_with = "...")]`,
+//! see `asynchronous`.
+//!
+//! With the `arena` feature, the batches of the short lived values are converted in to a `bumpalo`
+//! arena with `FromProtoIn` of the prelude, the strings and the repeated fields are allocated in it
+//! and released all at once, see `arena`.
+//!
+//! The conversions are checked to be inverse with `check_proto_roundtrip` and
+//! `check_domain_roundtrip` of the `roundtrip` module of the prelude, see `roundtrip`.
+//!
+//! The clients still sending the deprecated fields are found with the notices of the fields with
+//! `#[proto(deprecated = "...")]` and of `DeprecatedField`, collected with `deprecation::collect`,
+//! see `deprecation`.
+//!
+//! The idempotency keys are hashed from the canonical proto form of the values with `proto_hash` and
+//! `proto_hash_u64` of the prelude, the proto messages derive `hash::ProtoHashable`, see `hash`.
+//!
+//! The proto values are checked to be the default without constructing the default, with
+//! `proto_default::ProtoDefault` derived by the proto messages, which has their default instance in a
+//! static, see `proto_default`.
+//!
+//! The devices without the protobuf codegen encode the values in to CBOR with the field mapping of the
+//! proto conversions, with `#[derive(ProtoCbor)]` and the `cbor` feature, see `cbor`.
+//!
+//! The proto messages are rendered for the logs with the fields marked `#[proto(sensitive)]` redacted
+//! by `redact::ProtoRedact::render_proto_for_log`, and attached to the conversion errors in
+//! `redact::attach_to_errors`, see `redact`.
+//!
+//! The result messages with the oneof of the value or the error convert in to `Result` with
+//! `impl_proto_result!`, see `result`. The oneofs of the relative timeout or the absolute deadline
+//! convert in to `Deadline` with `impl_proto_deadline!`, resolved to the absolute time of the request,
+//! see `deadline`. The statuses of the errors of the other services convert in to
+//! `remote_error::RemoteError` with their details decoded by the type urls, see `remote_error`.
+//! The measurements of the value and the unit convert in to `unit::Quantity` normalized to the
+//! canonical unit with `impl_proto_quantity!`, see `unit`. The graphs of the shared `Arc` nodes convert
+//! in to the side table of the nodes written once with `impl_proto_graph!`, the cycles are an error,
+//! see `graph`. The instants in the IANA time zones convert in to their local business dates with
+//! `impl_proto_business_date!` of the `chrono-tz` feature, see `business_date`. The integer wire types
+//! of the same prost integers, like `sint32` and `uint32`, are named by `wire::Sint32` and the others,
+//! and checked against the signedness of the domain integers, see `wire`. The date times of the legacy
+//! schemas split in to the strings of the date, the time and the zone are reassembled with
+//! `impl_proto_split_datetime!`, see `split_datetime`. The maps sent as the repeated key and value
+//! pairs convert with `impl_proto_pairs!`, written sorted by the key, so the same map is the same
+//! bytes, see `pairs`.
+//!
+//! The proto fields added to the messages, which are not mapped yet, fail to compile with
+//! `#[proto(deny_unknown_fields)]` of the derived conversions and `assert_proto_fields!` of the
+//! hand-written ones, see `exhaustive`. The proto fields renamed or removed, which the derived
+//! conversions still map, fail to compile at the derived fields with the `verify` feature, see
+//! `verify`.
+//!
+//! The conversions of the untrusted input are limited in the count of the elements, the depth and the
+//! sizes of the strings with `budget::limit`, the exceeding ones fail with `budget::BudgetExceeded`
+//! at the path of the value, see `budget`.
+//!
+//! The optional proto fields are left out by a predicate deciding before the conversion, with
+//! `ToProto::to_proto_option_if`, `OmitIf` and `#[proto(omit_if = "...")]` of the derived fields, see
+//! `omit`.
+//!
+//! With the `contact` feature, the `google.type.PostalAddress` messages convert in to
+//! `contact::PostalAddress`, and the E.164 phone strings in to `contact::PhoneE164`, both validated
+//! and written back in the canonical forms, see `contact`.
+//!
+//! The hand-written conversions descend in to the fields and the elements with the guards of
+//! `context::ConvScope`, their errors get the path of the scopes, when they cross the wrapping helpers
+//! of the crate, see `context`.
+//!
+//! The rewritten conversions are audited against the old ones with `dual::dual_from_proto` and
+//! `#[proto(dual = "...")]` of the derived fields, both run, the divergences of their results are
+//! reported and the result of the primary one is used, see `dual`.
+//!
+//! The value sent in either of the proto fields of a migration, like the legacy epoch millis and the
+//! new `Timestamp`, is converted from the first one, which is present and converts, with
+//! `first_of::FirstOf` and `#[proto(first_of("created_at_ts", "created_at_millis"))]` of the derived
+//! fields, see `first_of`.
+//!
+//! The order of the repeated fields is kept by the conversions of `Vec` in both directions, the
+//! `BTreeSet` converts in to the proto list in the order of the set, and the `HashSet` and the maps in
+//! no defined order. The proto lists are sorted by the keys of the proto elements with
+//! `ToProtoSorted::to_proto_sorted_by_key` of the prelude and `#[proto(sorted)]` of the derived fields,
+//! without changing the value.
+//!
+//! The converted values are checked with `FromProto::from_proto_validated`, for the types implementing
+//! `validate::ProtoValidate`.
+
+// The derive macros refer to this crate as `::proto_conve`, also in its own tests.
+extern crate self as proto_conve;
+
+#[cfg(feature = "arena")]
+pub mod arena;
+pub mod asynchronous;
+pub mod bag;
+pub mod bounded;
+pub mod budget;
+pub mod builder;
+#[cfg(feature = "chrono-tz")]
+pub mod business_date;
+pub mod cache;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+#[doc(hidden)]
+pub mod chrono_compat;
+pub mod chunked;
+pub mod clock;
+#[cfg(feature = "contact")]
+pub mod contact;
+pub mod context;
+pub mod convert;
+#[cfg(feature = "coverage")]
+pub mod coverage;
+pub mod date;
+pub mod deadline;
+pub mod decimal;
+pub mod defaults;
+pub mod deprecation;
+pub mod dry_run;
+pub mod dual;
+pub mod duration_json;
+pub mod enum_set;
+pub mod enumeration;
+pub mod envelope;
+pub mod erased;
+pub mod error;
+#[cfg(feature = "prost")]
+pub mod etag;
+pub mod exhaustive;
+pub mod fault;
+pub mod first_of;
+pub mod flags;
+pub mod graph;
+#[cfg(feature = "tonic")]
+pub mod grpc;
+pub mod hash;
+pub mod ids;
+pub mod intern;
+#[cfg(feature = "wasm")]
+pub mod js_time;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod key;
+#[cfg(feature = "prost-types")]
+pub mod list_value;
+pub mod lossy;
+pub mod mask;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod mode;
+pub mod number;
+pub mod omit;
+pub mod open;
+pub mod page;
+pub mod pairs;
+pub mod patch;
+pub mod pivot;
+pub mod prelude;
+pub mod proto;
+pub mod proto_bytes;
+pub mod proto_default;
+#[cfg(feature = "serde")]
+pub mod proto_serde;
+pub mod range;
+#[cfg(feature = "prost")]
+pub mod records;
+pub mod redact;
+#[cfg(feature = "reflect")]
+pub mod reflect;
+pub mod remote_error;
+pub mod result;
+pub mod roundtrip;
+pub mod scaled;
+pub mod schedule;
+pub mod settings;
+pub mod split_datetime;
+pub mod tagged_set;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+pub mod text;
+#[cfg(feature = "text-format")]
+pub mod text_format;
+pub mod timestamp;
+pub mod trace;
+pub mod transform;
+pub mod unit;
+pub mod validate;
+pub mod verify;
+pub mod version;
+pub mod via_str;
+pub mod violation;
+pub mod wire;
+pub mod wrapper;
+
+pub use bounded::Bounded;
+pub use deadline::Deadline;
+pub use decimal::Decimal;
+pub use enumeration::ProtoEnumField;
+pub use omit::OmitIf;
+pub use open::OpenEnum;
+pub use page::Page;
+pub use patch::Patch;
+pub use proto::Proto;
+pub use proto_bytes::ProtoBytes;
+#[cfg(all(feature = "derive", feature = "cbor"))]
+pub use proto_conve_derive::ProtoCbor;
+#[cfg(all(feature = "derive", feature = "text-format"))]
+pub use proto_conve_derive::TextProto;
+#[cfg(feature = "derive")]
+pub use proto_conve_derive::{
+    FromProto, FromProtoLossy, FromProtoVersioned, IntoProto, MergeFromProto, ProtoDefault,
+    ProtoHashable, ToProto, ToProtoBuilder, ValidateFromProto,
+};
+pub use scaled::{FloatToScaledInt, Scaled};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use anyhow;
+    #[cfg(feature = "chrono-tz")]
+    pub use chrono_tz;
+    #[cfg(feature = "indexmap")]
+    pub use indexmap;
+    #[cfg(feature = "coverage")]
+    pub use inventory;
+    #[cfg(feature = "wasm")]
+    pub use js_sys;
+    #[cfg(any(test, feature = "test-util"))]
+    pub use proptest;
+
+    /// Markers selecting how the field is converted in `FromProtoField` and `ToProtoField`.
+    pub mod field {
+        /// The field is converted as it is
+        pub struct Plain;
+        /// The proto field is optional, but the value is required
+        pub struct Required;
+        /// The field is converted with `FromProtoAlias` or `ToProtoAlias`
+        pub struct Alias;
+        /// The value converts in to the optional proto field with `ToProtoOption`, as it can be absent
+        pub struct Optional;
+        /// The proto message field is boxed, as prost-build boxes the recursive ones, but the
+        /// domain one is not
+        pub struct Boxed;
+        /// The boxed proto message field is optional, but the value is required
+        pub struct BoxedRequired;
+    }
+
+    /// The types of the `nanos` fields, asserted by the `signed_nanos` and `unsigned_nanos` arms
+    pub mod nanos {
+        #[diagnostic::on_unimplemented(
+            message = "the `nanos` of the proto message are `{Self}`, not the `int32` of `signed_nanos`",
+            label = "the `nanos` are not `i32`",
+            note = "the `uint32` nanos are converted with the `unsigned_nanos` arm"
+        )]
+        pub trait Signed {}
+
+        impl Signed for i32 {}
+
+        #[diagnostic::on_unimplemented(
+            message = "the `nanos` of the proto message are `{Self}`, not the `uint32` of `unsigned_nanos`",
+            label = "the `nanos` are not `u32`",
+            note = "the `int32` nanos of the well known types are converted with the `signed_nanos` arm"
+        )]
+        pub trait Unsigned {}
+
+        impl Unsigned for u32 {}
+
+        pub fn signed<N: Signed>(_: &N) {}
+
+        pub fn unsigned<N: Unsigned>(_: &N) {}
+
+        /// How the `nanos` of the proto durations carry the sign of the negative durations
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Encoding {
+            /// The signed nanos are added to the seconds, in to proto they have the sign of the
+            /// seconds, as in `google.protobuf.Duration`
+            Signed,
+            /// The sign of the negative seconds applies to the unsigned nanos, so the negative
+            /// durations shorter than a second have no encoding, they are written as their magnitude
+            Magnitude,
+            /// The unsigned nanos are added to the seconds, which are rounded down in to proto, of
+            /// the `floor_nanos` arm
+            Floor,
+        }
+
+        /// The integer types of the `nanos` of the durations
+        pub trait Nanos: Copy {
+            /// The encoding of the nanos of the type, unless the arm floors them
+            const ENCODING: Encoding;
+
+            /// The canonical nanos, in the range of the type
+            fn from_canonical(nanos: i64) -> Self;
+        }
+
+        macro_rules! nanos {
+            ($($type: ty: $encoding: ident),*) => {$(
+                impl Nanos for $type {
+                    const ENCODING: Encoding = Encoding::$encoding;
+
+                    fn from_canonical(nanos: i64) -> Self {
+                        nanos as $type
+                    }
+                }
+            )*};
+        }
+
+        nanos!(i32: Signed, i64: Signed, u32: Magnitude, u64: Magnitude);
+
+        /// The encoding of the `nanos`, `Floor` of the `floor` arms
+        pub fn encoding<N: Nanos>(_: &N, floor: bool) -> Encoding {
+            if floor {
+                Encoding::Floor
+            } else {
+                N::ENCODING
+            }
+        }
+
+        /// The seconds and the nanos added to them of the proto duration, the seconds of the
+        /// floored nanos are rounded up, so they are in the chrono range with the duration
+        pub fn offset(seconds: i64, nanos: i64, encoding: Encoding) -> (i64, i64) {
+            match encoding {
+                Encoding::Magnitude if seconds < 0 => (seconds, -nanos),
+                Encoding::Floor if seconds < 0 && nanos > 0 => (seconds + 1, nanos - 1_000_000_000),
+                _ => (seconds, nanos),
+            }
+        }
+
+        /// The proto duration of the nanos in its canonical encoding for the type of its `nanos`,
+        /// `None` when the seconds do not fit
+        pub fn canonical<P, N: Nanos>(
+            nanos: i128,
+            floor: bool,
+            proto: impl FnOnce(i64, N) -> P,
+        ) -> Option<P> {
+            let encoding = if floor { Encoding::Floor } else { N::ENCODING };
+            let (seconds, nanos) = super::canonical_duration(nanos, encoding)?;
+            Some(proto(seconds, N::from_canonical(nanos)))
+        }
+    }
+
+    /// The value, unless it is the default, `-0.0` equals to `0.0` and is the default too, as it is
+    /// for the proto3 fields without presence
+    pub fn non_default<P: crate::proto_default::ProtoDefault>(value: P) -> Option<P> {
+        if value.is_proto_default() {
+            None
+        } else {
+            Some(value)
+        }
+    }
+
+    /// The proto list sorted stably, of `#[proto(sorted)]`
+    pub fn sorted<P: Ord>(mut values: Vec<P>) -> Vec<P> {
+        values.sort();
+        values
+    }
+
+    /// The proto list sorted stably by the `key`, of `#[proto(sorted = "...")]`
+    pub fn sorted_by_key<P, K: Ord>(mut values: Vec<P>, key: impl FnMut(&P) -> K) -> Vec<P> {
+        values.sort_by_key(key);
+        values
+    }
+
+    /// The integer types of the `days` of the durations split in to days
+    pub trait Days: Copy {
+        const MIN: i64;
+        const MAX: i64;
+
+        /// The days in the range of the type
+        fn from_days(days: i64) -> Self;
+    }
+
+    impl Days for i32 {
+        const MIN: i64 = i32::MIN as i64;
+        const MAX: i64 = i32::MAX as i64;
+
+        fn from_days(days: i64) -> Self {
+            days as i32
+        }
+    }
+
+    impl Days for i64 {
+        const MIN: i64 = i64::MIN;
+        const MAX: i64 = i64::MAX;
+
+        fn from_days(days: i64) -> Self {
+            days
+        }
+    }
+
+    /// The seconds and signed nanos split in to the days, the seconds of the day in `0..86400` and the
+    /// nanos in `0..1e9`, only the days are negative. `None` for the days out of the range of their
+    /// type.
+    pub fn split_days<D: Days>(seconds: i64, nanos: i32) -> Option<(D, i32, i32)> {
+        let (seconds, nanos) = if nanos < 0 {
+            (seconds - 1, nanos + 1_000_000_000)
+        } else {
+            (seconds, nanos)
+        };
+        let days = seconds.div_euclid(86_400);
+        (D::MIN..=D::MAX)
+            .contains(&days)
+            .then(|| (D::from_days(days), seconds.rem_euclid(86_400) as i32, nanos))
+    }
+
+    /// The days of the seconds out of the range of their type saturated, the nearest duration of the
+    /// days type
+    pub fn saturated_days<D: Days>(seconds: i64) -> (D, i32, i32) {
+        if seconds < 0 {
+            (D::from_days(D::MIN), 0, 0)
+        } else {
+            (D::from_days(D::MAX), 86_399, 999_999_999)
+        }
+    }
+
+    /// The nanos of the proto duration of the `encoding`
+    pub fn duration_nanos(seconds: i64, nanos: i64, encoding: nanos::Encoding) -> i128 {
+        let (seconds, nanos) = (seconds as i128 * 1_000_000_000, nanos as i128);
+        match encoding {
+            nanos::Encoding::Magnitude if seconds < 0 => seconds - nanos,
+            _ => seconds + nanos,
+        }
+    }
+
+    /// The canonical seconds and nanos of the duration in nanos. The signed nanos have the sign of
+    /// the seconds and the magnitude ones are in `0..1e9`, both with the seconds rounded towards zero,
+    /// the floored ones are in `0..1e9` with the seconds rounded down. `None` when the seconds do not
+    /// fit.
+    pub fn canonical_duration(nanos: i128, encoding: nanos::Encoding) -> Option<(i64, i64)> {
+        let (seconds, nanos) = match encoding {
+            nanos::Encoding::Signed => (nanos / 1_000_000_000, nanos % 1_000_000_000),
+            nanos::Encoding::Magnitude => (nanos / 1_000_000_000, (nanos % 1_000_000_000).abs()),
+            nanos::Encoding::Floor => (
+                nanos.div_euclid(1_000_000_000),
+                nanos.rem_euclid(1_000_000_000),
+            ),
+        };
+        Some((i64::try_from(seconds).ok()?, nanos as i64))
+    }
+
+    /// Evaluated by the `legacy` arms of `impl_traits!`, so their invocations warn
+    #[deprecated(
+        note = "the `legacy` arms of `impl_traits!` implement `ToProto` of the chrono type, use the arm \
+                without `legacy`, which implements `ToProtoAlias` of the proto type"
+    )]
+    pub const fn legacy_to_proto() {}
+
+    /// Check the variants of the derived enum have distinct values, it is evaluated in a const, so the
+    /// duplicate values fail to compile with the message naming both variants
+    pub const fn check_enum_values(name: &str, variants: &[&str], values: &[i64]) {
+        let mut second = 1;
+        while second < values.len() {
+            let mut first = 0;
+            while first < second {
+                if values[first] == values[second] {
+                    let message = ConstMessage::new()
+                        .push("The variants `")
+                        .push(variants[first])
+                        .push("` and `")
+                        .push(variants[second])
+                        .push("` of `")
+                        .push(name)
+                        .push("` have the same proto value");
+                    panic!("{}", message.as_str());
+                }
+                first += 1;
+            }
+            second += 1;
+        }
+    }
+
+    /// Check the `siblings` of the oneof field are the `sibling` fields of the variants of the enum,
+    /// in their order, it is evaluated in a const as `check_enum_values`
+    pub const fn check_siblings(name: &str, variants: &[&str], fields: &[&str]) {
+        let mut same = variants.len() == fields.len();
+        let mut index = 0;
+        while same && index < variants.len() {
+            let (variant, field) = (variants[index].as_bytes(), fields[index].as_bytes());
+            same = variant.len() == field.len();
+            let mut byte = 0;
+            while same && byte < variant.len() {
+                same = variant[byte] == field[byte];
+                byte += 1;
+            }
+            index += 1;
+        }
+        if !same {
+            let message = ConstMessage::new()
+                .push(
+                    "The `siblings` of the field are not the `sibling` fields of the variants of `",
+                )
+                .push(name)
+                .push("`, in their order");
+            panic!("{}", message.as_str());
+        }
+    }
+
+    /// The message of the const panics, the parts not fitting in to it are left out
+    struct ConstMessage {
+        bytes: [u8; 256],
+        len: usize,
+    }
+
+    impl ConstMessage {
+        const fn new() -> Self {
+            Self {
+                bytes: [0; 256],
+                len: 0,
+            }
+        }
+
+        const fn push(mut self, part: &str) -> Self {
+            let part = part.as_bytes();
+            if self.len + part.len() <= self.bytes.len() {
+                let mut index = 0;
+                while index < part.len() {
+                    self.bytes[self.len + index] = part[index];
+                    index += 1;
+                }
+                self.len += part.len();
+            }
+            self
+        }
+
+        const fn as_str(&self) -> &str {
+            match core::str::from_utf8(self.bytes.split_at(self.len).0) {
+                Ok(message) => message,
+                Err(_) => "",
+            }
+        }
+    }
+
+    #[cfg(feature = "bytes")]
+    pub use bytes;
+    #[cfg(feature = "cbor")]
+    pub use ciborium;
+    #[cfg(feature = "futures")]
+    pub use futures_core;
+    #[cfg(feature = "prost")]
+    pub use prost;
+    #[cfg(feature = "prost-types")]
+    pub use prost_types;
+    #[cfg(any(feature = "json", feature = "serde"))]
+    pub use serde;
+    #[cfg(feature = "prost-types")]
+    pub use serde_json;
+    #[cfg(feature = "tonic")]
+    pub use tonic;
+}
+
+/// Without the `tonic` feature there are no tonic conversions.
+#[cfg(not(feature = "tonic"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_tonic {
+    ($($prelude: ident)?) => {};
+}
+
+/// Without the `tonic` and `futures` features there are no tonic stream conversions.
+#[cfg(not(all(feature = "tonic", feature = "futures")))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_tonic_stream {
+    ($($prelude: ident)?) => {};
+}
+
+/// Without the `test-util` feature there are no round trip assertions.
+#[cfg(not(any(test, feature = "test-util")))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_test_util {
+    ($($prelude: ident)?) => {};
+}
+
+/// Without the `test-util` feature there are no generators for the tests of `verify_roundtrip`.
+#[cfg(not(any(test, feature = "test-util")))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __verify_roundtrip {
+    ($($item: tt)*) => {
+        #[cfg(test)]
+        ::core::compile_error!(
+            "`verify_roundtrip` generates the property tests with the generators of the `test-util` \
+             feature, enable it in the dev-dependencies"
+        );
+    };
+}
+
+/// Without the `serde` feature there is no serde representation through proto.
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_serde {
+    ($($prelude: ident)?) => {};
+}
+
+/// Without the `prost` feature the envelopes are registered only with their decoders.
+#[cfg(not(feature = "prost"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_envelope {
+    ($($prelude: ident)?) => {};
+}
+
+/// Without the `prost` feature there are no ETags of the encoded messages.
+#[cfg(not(feature = "prost"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_etag {
+    ($($prelude: ident)?) => {};
+}
+
+/// Without the `reflect` feature the derived types have no `HasProtoMapping`.
+#[cfg(not(feature = "reflect"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __reflect {
+    ($($item: tt)*) => {};
+}
+
+/// Without the `coverage` feature the conversions are not registered.
+#[cfg(not(feature = "coverage"))]
+#[macro_export]
+macro_rules! register_conversion {
+    ($proto: path => $($direction: ident),+ $(,)?) => {};
+}
+
+/// Without the `arena` feature there are no conversions in to the arenas.
+#[cfg(not(feature = "arena"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_arena {
+    ($($prelude: ident)?) => {};
+    (@via $($type: tt)+) => {
+        compile_error!("The arena conversions need the `arena` feature of proto-conve");
+    };
+}
+
+/// Without the `text-format` feature there is no protobuf text format.
+#[cfg(not(feature = "text-format"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_text_format {
+    ($($prelude: ident)?) => {};
+}
+
+/// Without the `prost` feature there are no record files.
+#[cfg(not(feature = "prost"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_records {
+    ($($prelude: ident)?) => {};
+}
+
+/// Without the `bytes` feature there are no `bytes::Bytes` conversions.
+#[cfg(not(feature = "bytes"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_bytes {
+    () => {};
+}
+
+/// Without the `metrics` feature there are no observed conversions.
+#[cfg(not(feature = "metrics"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_metrics {
+    ($($prelude: ident)?) => {};
+}
+
+/// Without the `json` feature there are no JSON conversions.
+#[cfg(not(feature = "json"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_json {
+    ($($prelude: ident)?) => {};
+}
+
+/// Without the `cbor` feature there is no CBOR encoding.
+#[cfg(not(feature = "cbor"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_cbor {
+    ($($prelude: ident)?) => {};
+    ($($type: tt)+) => {
+        compile_error!("The CBOR conversions need the `cbor` feature of proto-conve");
+    };
+}
+
+/// Without the `prost-types` feature there are no `ListValue` and `NullValue` conversions.
+#[cfg(not(feature = "prost-types"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_prost_types {
+    () => {};
+}
+
+/// Without the `contact` feature there are no conversions of the addresses and the phone numbers.
+#[cfg(not(feature = "contact"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_contact {
+    () => {};
+    ($type: ident) => {
+        compile_error!("The address conversions need the `contact` feature of proto-conve");
+    };
+}
+
+/// Without the `wasm` feature there are no JS time conversions.
+#[cfg(not(feature = "wasm"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_wasm {
+    ($($type: tt)*) => {
+        compile_error!("The JS time conversions need the `wasm` feature of proto-conve");
+    };
+}
 
 /// Implement FromProto and ToProto to some specific chrono types when the proto type has a specific format.
 /// ```proto
@@ -15,17 +896,148 @@
 ///   uint32 nanos = 2;
 /// }
 /// ```
+/// The `nanos` can be also `int32`, as it is in the well known types. The arms asserting the type of
+/// the `nanos` at the build, `impl_traits!(Timestamp, chrono::DateTime<chrono::Utc>, signed_nanos)` for
+/// the `int32` ones and `impl_traits!(Duration, chrono::Duration, unsigned_nanos)` for the `uint32`
+/// ones, are otherwise the same, so the arm copied to the message of the other schema style is a build
+/// error instead of the negative nanos written in to the `uint32` field.
+///
+/// The chrono types convert in to the proto types with `ToProtoAlias<Duration>` and
+/// `ToProtoAlias<Option<Duration>>`, the optional values with `ToProtoAlias<Option<Duration>>` of
+/// `Option<chrono::Duration>`, so several proto types convert from the same chrono type in one crate,
+/// like the own `Duration` and the one of another API:
+/// ```ignore
+/// impl_traits!(Duration, chrono::Duration);
+/// impl_traits!(Interval, chrono::Duration);
+///
+/// let interval: Interval = ToProtoAlias::to_proto_alias(&duration);
+/// ```
+/// The derived fields are converted with them as they are.
+///
+/// The arms with the `verify_roundtrip` flag, `impl_traits!(Duration, chrono::Duration,
+/// verify_roundtrip)` and `impl_traits!(Timestamp, chrono::DateTime<chrono::Utc>, verify_roundtrip)`,
+/// check at the build that the chrono type converts back in to the same proto type, and generate the
+/// `#[cfg(test)]` property test named by the proto type, converting the values of the generators of
+/// `test_util` from proto and back. The durations come back in their canonical encoding of
+/// `CanonicalProto`, so the negative nanos of the signed ones may be normalized. The tests need the
+/// `test-util` feature, like in the dev-dependencies.
+///
+/// Migrating from `ToProto`, which these arms implemented before, the calls of `duration.to_proto()`
+/// name the proto type as `ToProtoAlias::<Duration>::to_proto_alias(&duration)`, and the round trips are
+/// asserted with `assert_roundtrip_from_alias` and `assert_roundtrip_to_alias::<Duration, _>`. The
+/// conversions needing `ToProto` of the chrono type, like of the `Vec` of them, `proto_serde` or
+/// `IntoProtoResponse`, keep it with the deprecated `impl_traits!(Duration, chrono::Duration, legacy)`,
+/// `impl_traits!(Timedelta, chrono::Duration { days, seconds, nanos }, legacy)` and
+/// `impl_traits!(Timestamp, chrono::DateTime<chrono::Utc>, legacy)`, which implement it instead of
+/// `ToProtoAlias`, and warn with `deprecated`. There can be only one of them for a chrono type.
+///
+/// The expanded code calls chrono only through `chrono_compat`, so it compiles with the chrono of
+/// the crate whichever of its 0.4 releases it is, the ones before 0.4.35 with the `chrono-legacy`
+/// feature.
+///
+/// The durations split in to days are converted with `impl_traits!(Timedelta, chrono::Duration { days,
+/// seconds, nanos })`, for the `int32` or `int64` fields of the same names. The fields can have mixed signs, in
+/// to proto the seconds are in `0..86400`, the nanos in `0..1e9` and only the days are negative. The
+/// `int64` days have the range of chrono. The durations of more than `i32::MAX` days, or less than
+/// `i32::MIN`, do not fit the `int32` ones, converting them panics with the debug assertions and
+/// saturates without them, so they are checked before, like with `#[proto(validate_on_to_proto)]`.
+///
+/// The instants out of the range of the proto `Timestamp`, `0001-01-01T00:00:00Z` to
+/// `9999-12-31T23:59:59.999999999Z`, are rejected with `impl_traits!(Timestamp,
+/// chrono::DateTime<chrono::Utc>, spec_range)`, then `chrono::DateTime` has `TryToProto` instead of
+/// `ToProtoAlias`. The derived fields of them are converted with `#[proto(to_with = "path::to::fn")]`.
+///
+/// The legacy sentinel instants, like the `0` of no deadline, are `None` of the
+/// `Option<chrono::DateTime<chrono::Utc>>` fields converted with the module generated by
+/// `impl_traits!(sentinel_none deadline: Timestamp, seconds = [0, i64::MAX])`, for the messages of the
+/// `DateTime` arm, or `impl_traits!(sentinel_none expires: i64, millis = [0])`, for the epoch millis.
+/// `None` is converted in to the first sentinel, the instants equal to a sentinel are read back as
+/// `None`.
+///
+/// The messages wrapping a single repeated field, like `message Items { repeated Item items = 1; }`,
+/// convert in to the `Vec` of the domain elements with `impl_traits!(Items, wrapper Vec<Item>)`, or
+/// `impl_traits!(Items, wrapper values: Vec<Item>)` for the field of another name. The optional wrapper
+/// fields are required, unless the derived field has `#[proto(default)]`, converting the missing one in
+/// to the empty `Vec`.
+///
+/// The messages of the `min` and `max` bounds convert in to the ranges of the numeric scalars with
+/// `impl_traits!(Limits, std::ops::Range<i64>)` or `impl_traits!(Thresholds,
+/// std::ops::RangeInclusive<f64>)`, the inverted bounds are an error, or swapped in the lenient mode,
+/// see `range`.
+///
+/// The dates of the year, month and day convert in to `chrono::NaiveDate` with `impl_traits!(Date,
+/// chrono::NaiveDate)`, and the messages of the start and end dates in to the inclusive ranges with
+/// `impl_traits!(DateRange, std::ops::RangeInclusive<chrono::NaiveDate> { start, end })`, the longer
+/// ones than a number of days are rejected with `from_proto_max_days(days)` of `FromProtoDateRange`,
+/// see `date`.
+///
+/// The messages of the start instant, the interval and the count of the occurrences convert in to
+/// `schedule::Schedule` with `impl_traits!(Schedule, schedule::Schedule { start, interval, count })`,
+/// the count `0` is the schedule without the end, see `schedule`.
+///
+/// The proto durations longer than a bound are rejected with `from_proto_bounded(max)` of
+/// `FromProtoBounded`, and the instants out of the allowed range with `from_proto_not_before(min)`
+/// and `from_proto_not_after(max)` of `FromProtoInstant`, before they are added or compared.
+///
+/// The duration strings of the proto JSON mapping, like `"3.5s"`, are converted with
+/// `impl_traits!(String, chrono::Duration)`, see `duration_json`.
+///
+/// The `int32` nanos of the durations are added to the `seconds`, as in `google.protobuf.Duration`, so
+/// `{ seconds: -1, nanos: -500000000 }` is `-1.5s`, the same as `{ seconds: -2, nanos: 500000000 }`
+/// and `{ seconds: 0, nanos: -1500000000 }`, and `{ seconds: -1, nanos: 1000000000 }` is zero. In to
+/// proto, the durations are in the canonical form, the `nanos` are in `-1e9..1e9` with the sign of the
+/// `seconds`, `-0.5s` is `{ seconds: 0, nanos: -500000000 }`.
+/// The sign of the negative `seconds` applies to the `uint32` nanos, as it always did, so `{ seconds:
+/// -1, nanos: 500000000 }` is `-1.5s`. They cannot encode the negative durations shorter than a
+/// second, those are written as their magnitude. The `uint32` nanos of
+/// `impl_traits!(Duration, chrono::Duration, floor_nanos)` are added to the `seconds` instead, which are
+/// rounded down in to proto, so `-0.5s` is `{ seconds: -1, nanos: 500000000 }`. The stored durations of
+/// the other arm read differently with it, the negative ones are not migrated by switching the arm.
+/// The proto durations are checked with `is_canonical` and rewritten in to the canonical form with
+/// `canonicalize` of `CanonicalProto`, without converting them.
+///
+/// With the `wasm` feature, the epoch millis `f64` and `js_sys::Date` of the JS times convert in to
+/// `chrono::DateTime<chrono::Utc>` with `impl_traits!(js chrono::DateTime<chrono::Utc>)`, see
+/// `js_time`.
+///
+/// With the `contact` feature, the messages of the `google.type.PostalAddress` shape convert in to
+/// `contact::PostalAddress` with `impl_traits!(PostalAddress, contact::PostalAddress)`, see `contact`.
+///
+/// With the `cbor` feature, the chrono instants and durations are written to CBOR as the `seconds` and
+/// `nanos` of the proto messages with `impl_traits!(cbor chrono::DateTime<chrono::Utc>)` and
+/// `impl_traits!(cbor chrono::Duration)`, see `cbor`.
+///
+/// The types implementing `FromProtoInfallible` get the `FromProto`, which never fails, with
+/// `impl_traits!(infallible ProtoUserId)`, it is a part of `impl_traits!(identity Type)`.
+///
+/// The enums converting from `i32` with `FromProtoAlias` are opened with `impl_traits!(open Status)`, so
+/// `OpenEnum<Status>` keeps the unknown values.
+///
+/// The proto types of the crates, which neither own the traits nor the chrono types, can not have the
+/// conversions of the traits of another crate, so they convert through the local shim with
+/// `impl_traits!(remote shared::Timestamp as Timestamp, chrono::DateTime<chrono::Utc>)`, also of
+/// `chrono::Duration` and `chrono::NaiveDate`. It is the transparent newtype `Timestamp(pub
+/// shared::Timestamp)`, with `From` both ways, and the conversions of the chrono type, and
+/// `Timestamp::from_remote(proto)` and `Timestamp::to_remote(&value)` convert the remote proto type
+/// without wrapping it.
 #[macro_export]
 macro_rules! impl_traits {
     (prelude) => {
         /// Unwrap the value from the optional proto value, if the value is missing, an error is returned and
         /// convert it in to result if appropriate.
-        trait Required {
+        #[allow(dead_code)]
+        pub trait Required {
             /// The result of this type
             type Result;
             /// Convert the value from proto buf option in to Result, if the value is missing (None) the
             /// error is returned
             fn required(self) -> anyhow::Result<Self::Result>;
+            /// Unwrap the value of the required field `name`, the error of the missing one does not
+            /// allocate, until it is converted in to `anyhow::Error`
+            fn required_lite(
+                self,
+                name: &'static str,
+            ) -> Result<Self::Result, $crate::error::MissingField>;
         }
 
         /// Implementation of the required for all the options.
@@ -36,9 +1048,15 @@ macro_rules! impl_traits {
                 if let Some(value) = self {
                     Ok(value)
                 } else {
-                    anyhow::bail!("Required is missing");
+                    Err($crate::trace::failed::<Self, T>(anyhow::anyhow!(
+                        "Required is missing"
+                    )))
                 }
             }
+
+            fn required_lite(self, name: &'static str) -> Result<T, $crate::error::MissingField> {
+                self.ok_or($crate::error::MissingField::new(name))
+            }
         }
 
         /// Convert the value in to appropriate proto value
@@ -53,139 +1071,3168 @@ macro_rules! impl_traits {
             fn to_proto_option(&self) -> Option<Self::Result> {
                 Some(self.to_proto())
             }
+
+            /// Convert the value in to proto value wrapped in to option, it is `None` without the
+            /// conversion, when `omit` is true for the value, see `omit`
+            fn to_proto_option_if(&self, omit: impl Fn(&Self) -> bool) -> Option<Self::Result> {
+                if omit(self) {
+                    None
+                } else {
+                    Some(self.to_proto())
+                }
+            }
         }
 
         /// Convert the value in to proto value
+        #[diagnostic::on_unimplemented(
+            message = "`{Self}` can not be converted in to the proto value `{T}`",
+            label = "no proto conversion",
+            note = "implement `ToProto` for the type, or convert the field with `#[proto(with = \"module\")]`"
+        )]
         pub trait ToProtoAlias<T> {
-            fn to_proto(&self) -> T;
+            /// Convert the value in to the proto value `T`, it is named apart from `ToProto::to_proto`,
+            /// so the calls are not ambiguous for the types having both. The impls implement it, or the
+            /// deprecated `to_proto` of before the rename, they default to each other, so one of them
+            /// has to be implemented.
+            #[allow(deprecated)]
+            fn to_proto_alias(&self) -> T {
+                self.to_proto()
+            }
+
+            /// Convert the value in to the proto value `T` and wrap it in to option
+            #[allow(deprecated)]
+            fn to_proto_alias_option(&self) -> Option<T> {
+                self.to_proto_option()
+            }
 
+            /// Convert the value in to the proto value `T` wrapped in to option, it is `None` without
+            /// the conversion, when `omit` is true for the value, see `omit`
+            fn to_proto_alias_option_if(&self, omit: impl Fn(&Self) -> bool) -> Option<T> {
+                if omit(self) {
+                    None
+                } else {
+                    Some(self.to_proto_alias())
+                }
+            }
+
+            /// The name of `to_proto_alias` before the rename, the impls of it still compile
+            #[deprecated(note = "renamed to `ToProtoAlias::to_proto_alias`")]
+            fn to_proto(&self) -> T {
+                self.to_proto_alias()
+            }
+
+            /// The name of `to_proto_alias_option` before the rename, the impls of it still compile
+            #[deprecated(note = "renamed to `ToProtoAlias::to_proto_alias_option`")]
             fn to_proto_option(&self) -> Option<T> {
-                Some(self.to_proto())
+                Some(self.to_proto_alias())
             }
         }
 
-        /// Convert the value in to proto value wrapped in an option
-        pub trait ToProtoOption<T> {
-            fn to_proto(&self) -> Option<T>;
+        /// Convert the value in to proto value consuming it, all the `ToProto` types have it
+        pub trait IntoProto {
+            /// The proto value type
+            type Result;
+
+            /// Convert the value in to proto value
+            fn into_proto(self) -> Self::Result;
         }
 
-        /// Convert the proto value in to the appropriate value
-        pub trait FromProto {
-            type Result;
-            fn from_proto(self) -> anyhow::Result<Self::Result>;
+        impl<T: ToProto> IntoProto for T {
+            type Result = T::Result;
+
+            fn into_proto(self) -> Self::Result {
+                self.to_proto()
+            }
         }
 
-        impl<T: FromProto> FromProto for Option<T> {
-            type Result = Option<T::Result>;
+        /// Convert the `proto` through the front door, the error names the proto and the domain type, see
+        /// `proto_conve::convert`
+        #[allow(dead_code)]
+        pub fn convert_from_proto<P: FromProto>(proto: P) -> anyhow::Result<P::Result> {
+            $crate::convert::from_proto(proto, P::from_proto)
+        }
 
-            fn from_proto(self) -> anyhow::Result<Self::Result> {
-                self.map(|v| v.from_proto()).transpose()
+        /// Convert the `value` in to proto through the front door, see `proto_conve::convert`
+        #[allow(dead_code)]
+        pub fn convert_to_proto<T: ToProto + ?Sized>(value: &T) -> T::Result {
+            $crate::convert::to_proto(|| value.to_proto())
+        }
+
+        /// Convert the value in to another domain type of the same proto type, through the proto value
+        #[allow(dead_code)]
+        pub fn pivot_convert<A, P, B>(value: &A) -> anyhow::Result<B>
+        where
+            A: ToProto<Result = P>,
+            P: FromProto<Result = B>,
+        {
+            value
+                .to_proto()
+                .from_proto()
+                .map_err($crate::pivot::context::<A, P, B>)
+        }
+
+        /// Convert the value in to another domain type of the same proto type, consuming it
+        #[allow(dead_code)]
+        pub fn pivot_convert_owned<A, P, B>(value: A) -> anyhow::Result<B>
+        where
+            A: IntoProto<Result = P>,
+            P: FromProto<Result = B>,
+        {
+            value
+                .into_proto()
+                .from_proto()
+                .map_err($crate::pivot::context::<A, P, B>)
+        }
+
+        /// Convert the value in to another domain type of the same proto type with `pivot_convert`
+        pub trait ViaProtoConvert: ToProto {
+            /// Convert the value through its proto value
+            fn via_proto<B>(&self) -> anyhow::Result<B>
+            where
+                Self: Sized,
+                Self::Result: FromProto<Result = B>;
+        }
+
+        impl<A: ToProto> ViaProtoConvert for A {
+            fn via_proto<B>(&self) -> anyhow::Result<B>
+            where
+                A::Result: FromProto<Result = B>,
+            {
+                pivot_convert(self)
             }
         }
 
-        impl ToProtoAlias<bool> for bool {
-            fn to_proto(&self) -> bool {
-                *self
+        /// Convert the value in to proto value, which is `None` for the default proto value, like zero or
+        /// the empty string and list, so only the meaningful fields are set in the patch messages
+        pub trait ToProtoNonDefault<T> {
+            /// Convert the value in to proto value, unless it is the default
+            fn to_proto_non_default(&self) -> Option<T>;
+        }
+
+        impl<T: ToProto> ToProtoNonDefault<T::Result> for T
+        where
+            T::Result: $crate::proto_default::ProtoDefault,
+        {
+            fn to_proto_non_default(&self) -> Option<T::Result> {
+                $crate::__private::non_default(self.to_proto())
             }
         }
-        /// Convert list of proto elements in a vector
-        impl<T: FromProto> FromProto for Vec<T> {
-            type Result = Vec<T::Result>;
 
-            fn from_proto(self) -> anyhow::Result<Self::Result> {
-                let mut result = Vec::new();
-                for item in self.into_iter() {
-                    result.push(item.from_proto()?);
-                }
-                Ok(result)
+        /// Convert the value in to the proto list sorted by the key of the proto elements, like for the
+        /// deterministic diffs and signatures, the value is not changed and its elements need no `Ord`
+        pub trait ToProtoSorted<P> {
+            /// Convert the value in to the proto list, sorted stably by the `key` of the elements, so the
+            /// ones with the equal keys keep their order
+            fn to_proto_sorted_by_key<K: Ord>(&self, key: impl FnMut(&P) -> K) -> Vec<P>;
+        }
+
+        impl<P, T: ToProto<Result = Vec<P>> + ?Sized> ToProtoSorted<P> for T {
+            fn to_proto_sorted_by_key<K: Ord>(&self, key: impl FnMut(&P) -> K) -> Vec<P> {
+                $crate::__private::sorted_by_key(self.to_proto(), key)
             }
         }
 
-        /// Convert list of elements in vector to proto list
-        impl<T: ToProto> ToProto for Vec<T> {
-            type Result = Vec<T::Result>;
+        /// Convert the proto value in to this type, the counterpart of `ToProtoAlias` for types converting
+        /// from proto types, which can not have `FromProto` for them, like the `i32` of proto enums.
+        #[diagnostic::on_unimplemented(
+            message = "`{Self}` can not be converted from the proto value `{T}`",
+            label = "no proto conversion",
+            note = "implement `FromProto` for the proto type, or convert the field with `#[proto(with = \"module\")]`"
+        )]
+        pub trait FromProtoAlias<T>: Sized {
+            fn from_proto_alias(value: T) -> anyhow::Result<Self>;
 
-            fn to_proto(&self) -> Self::Result {
-                self.iter().map(|v| v.to_proto()).collect()
+            /// Convert the proto value strictly or leniently, the same as `from_proto_alias`, unless
+            /// the type handles the malformed values differently
+            fn from_proto_alias_mode(
+                value: T,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self> {
+                let _ = mode;
+                Self::from_proto_alias(value)
+            }
+
+            /// Convert the proto value as much as possible with the report of what was lossy, the
+            /// same as `from_proto_alias`, unless the type handles the malformed values differently
+            fn from_proto_alias_lossy(
+                value: T,
+            ) -> anyhow::Result<(Self, $crate::lossy::ConversionReport)> {
+                Ok((Self::from_proto_alias(value)?, $crate::lossy::ConversionReport::new()))
             }
         }
 
-        impl<T: ToProto> ToProto for Option<T> {
-            type Result = Option<T::Result>;
+        impl<P, T: FromProtoAlias<P>> FromProtoAlias<Vec<P>> for Vec<T> {
+            fn from_proto_alias(value: Vec<P>) -> anyhow::Result<Self> {
+                Self::from_proto_alias_mode(value, $crate::mode::ConversionMode::Strict)
+            }
 
-            fn to_proto(&self) -> Self::Result {
-                self.as_ref().map(|v| v.to_proto())
+            fn from_proto_alias_mode(
+                value: Vec<P>,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self> {
+                let _batch = $crate::trace::Batch::new::<Self>(value.len());
+                value
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, value)| {
+                        T::from_proto_alias_mode(value, mode).map_err(|error| {
+                            let error = $crate::error::at_index(error, index);
+                            $crate::trace::failed::<Vec<P>, Self>(error)
+                        })
+                    })
+                    .collect()
+            }
+
+            fn from_proto_alias_lossy(
+                value: Vec<P>,
+            ) -> anyhow::Result<(Self, $crate::lossy::ConversionReport)> {
+                let _batch = $crate::trace::Batch::new::<Self>(value.len());
+                let mut result = Vec::with_capacity(value.len());
+                let mut report = $crate::lossy::ConversionReport::new();
+                for (index, value) in value.into_iter().enumerate() {
+                    let (value, nested) = T::from_proto_alias_lossy(value).map_err(|error| {
+                        let error = $crate::error::at_index(error, index);
+                        $crate::trace::failed::<Vec<P>, Self>(error)
+                    })?;
+                    report.append(nested.at_index(index));
+                    result.push(value);
+                }
+                Ok((result, report))
             }
         }
-    };
-    ($type: ident, chrono::Duration) => {
-        impl FromProto for $type {
-            type Result = chrono::Duration;
 
-            fn from_proto(self) -> anyhow::Result<Self::Result> {
-                if self.seconds >= 0 {
-                    Ok((chrono::Duration::seconds(self.seconds)
-                        + chrono::Duration::nanoseconds(self.nanos as i64))
-                        )
-                } else {
-                    Ok((chrono::Duration::seconds(self.seconds)
-                        - chrono::Duration::nanoseconds(self.nanos as i64))
-                       )
+        impl<P, T: FromProtoAlias<P>> FromProtoAlias<Option<P>> for Option<T> {
+            fn from_proto_alias(value: Option<P>) -> anyhow::Result<Self> {
+                value.map(T::from_proto_alias).transpose()
+            }
+
+            fn from_proto_alias_mode(
+                value: Option<P>,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self> {
+                value
+                    .map(|value| T::from_proto_alias_mode(value, mode))
+                    .transpose()
+            }
+
+            fn from_proto_alias_lossy(
+                value: Option<P>,
+            ) -> anyhow::Result<(Self, $crate::lossy::ConversionReport)> {
+                match value {
+                    Some(value) => T::from_proto_alias_lossy(value)
+                        .map(|(value, report)| (Some(value), report)),
+                    None => Ok((None, $crate::lossy::ConversionReport::new())),
                 }
             }
         }
-        impl ToProto for chrono::Duration {
-            type Result = $type;
 
-            fn to_proto(&self) -> Self::Result {
-                let (seconds, nanos) = if self >= &chrono::Duration::zero() {
-                    let time = self.to_std().unwrap();
-                    (time.as_secs() as i64, time.subsec_nanos())
-                } else {
-                    let time = (-*self).to_std().unwrap();
-                    (-(time.as_secs() as i64), time.subsec_nanos())
-                };
-                $type { seconds, nanos }
+        /// The elements are converted with their `ToProtoAlias`, like the enums in to the repeated
+        /// `i32` fields
+        impl<P, T: ToProtoAlias<P>> ToProtoAlias<Vec<P>> for Vec<T> {
+            fn to_proto_alias(&self) -> Vec<P> {
+                self.as_slice().to_proto_alias()
             }
         }
-    };
-    ($type: ident, chrono::DateTime<chrono::Utc>) => {
-        impl ToProto for chrono::DateTime<chrono::Utc> {
-            type Result = $type;
-            fn to_proto(&self) -> Self::Result {
-                let value = self.naive_utc();
-                $type {
-                    seconds: value.timestamp(),
-                    nanos: value.timestamp_subsec_nanos(),
-                }
+
+        impl<P, T: ToProtoAlias<P>> ToProtoAlias<Vec<P>> for [T] {
+            fn to_proto_alias(&self) -> Vec<P> {
+                self.iter().map(ToProtoAlias::to_proto_alias).collect()
             }
         }
 
-        impl FromProto for $type {
-            type Result = chrono::DateTime<chrono::Utc>;
+        impl<P, T: ToProtoAlias<P>> ToProtoAlias<Option<P>> for Option<T> {
+            fn to_proto_alias(&self) -> Option<P> {
+                self.as_ref().map(ToProtoAlias::to_proto_alias)
+            }
+        }
 
-            fn from_proto(self) -> anyhow::Result<Self::Result> {
-                use chrono::NaiveDateTime;
-                match NaiveDateTime::from_timestamp_opt(self.seconds, self.nanos) {
-                    None => {
-                        anyhow::bail!(
-                            "Failed to parse timestamp: {} s and {} ns",
-                            self.seconds,
-                            self.nanos
-                        );
+        /// The values of the map are converted with their `FromProtoAlias`, the keys are kept as they
+        /// are
+        impl<K, P, T> FromProtoAlias<::std::collections::HashMap<K, P>>
+            for ::std::collections::HashMap<K, T>
+        where
+            K: Eq + ::std::hash::Hash + ::std::fmt::Display,
+            T: FromProtoAlias<P>,
+        {
+            fn from_proto_alias(value: ::std::collections::HashMap<K, P>) -> anyhow::Result<Self> {
+                let mut result = ::std::collections::HashMap::with_capacity(value.len());
+                for (key, value) in value {
+                    if let Err(error) = $crate::budget::element() {
+                        return Err($crate::error::at_key(error, &key));
                     }
-                    Some(value) => Ok(chrono::DateTime::from_naive_utc_and_offset(value, chrono::Utc)),
+                    match T::from_proto_alias(value) {
+                        Ok(value) => result.insert(key, value),
+                        Err(error) => return Err($crate::error::at_key(error, &key)),
+                    };
                 }
+                Ok(result)
             }
         }
-    };
-}
 
+        /// The same as of `HashMap`, for the maps of prost-build with `btree_map`
+        impl<K, P, T> FromProtoAlias<::std::collections::BTreeMap<K, P>>
+            for ::std::collections::BTreeMap<K, T>
+        where
+            K: Ord + ::std::fmt::Display,
+            T: FromProtoAlias<P>,
+        {
+            fn from_proto_alias(value: ::std::collections::BTreeMap<K, P>) -> anyhow::Result<Self> {
+                let mut result = ::std::collections::BTreeMap::new();
+                for (key, value) in value {
+                    if let Err(error) = $crate::budget::element() {
+                        return Err($crate::error::at_key(error, &key));
+                    }
+                    match T::from_proto_alias(value) {
+                        Ok(value) => result.insert(key, value),
+                        Err(error) => return Err($crate::error::at_key(error, &key)),
+                    };
+                }
+                Ok(result)
+            }
+        }
 
+        /// The values of the map are converted with their `ToProtoAlias`, the keys are kept as they are
+        impl<K, P, T> ToProtoAlias<::std::collections::HashMap<K, P>> for ::std::collections::HashMap<K, T>
+        where
+            K: Eq + ::std::hash::Hash + Clone,
+            T: ToProtoAlias<P>,
+        {
+            fn to_proto_alias(&self) -> ::std::collections::HashMap<K, P> {
+                self.iter()
+                    .map(|(key, value)| (key.clone(), value.to_proto_alias()))
+                    .collect()
+            }
+        }
+
+        /// The same as of `HashMap`, for the maps of prost-build with `btree_map`
+        impl<K, P, T> ToProtoAlias<::std::collections::BTreeMap<K, P>> for ::std::collections::BTreeMap<K, T>
+        where
+            K: Ord + Clone,
+            T: ToProtoAlias<P>,
+        {
+            fn to_proto_alias(&self) -> ::std::collections::BTreeMap<K, P> {
+                self.iter()
+                    .map(|(key, value)| (key.clone(), value.to_proto_alias()))
+                    .collect()
+            }
+        }
+
+        /// The string is checked to be non empty
+        impl<const TRIM: bool> FromProtoAlias<String> for $crate::text::NonEmptyString<TRIM> {
+            fn from_proto_alias(value: String) -> anyhow::Result<Self> {
+                Self::new(value)
+            }
+        }
+
+        impl<const TRIM: bool> ToProtoAlias<String> for $crate::text::NonEmptyString<TRIM> {
+            fn to_proto_alias(&self) -> String {
+                self.as_str().to_owned()
+            }
+        }
+
+        /// The number is read with the policy of `number::scope`
+        impl<T: $crate::number::LenientNumeric> FromProtoAlias<String> for $crate::number::LenientNumber<T> {
+            fn from_proto_alias(value: String) -> anyhow::Result<Self> {
+                Self::parse(&value)
+            }
+        }
+
+        impl<T: $crate::number::LenientNumeric> ToProtoAlias<String> for $crate::number::LenientNumber<T> {
+            fn to_proto_alias(&self) -> String {
+                self.to_string()
+            }
+        }
+
+        impl FromProtoAlias<String> for $crate::text::TrimmedString {
+            fn from_proto_alias(value: String) -> anyhow::Result<Self> {
+                Ok(Self::new(value))
+            }
+        }
+
+        impl ToProtoAlias<String> for $crate::text::TrimmedString {
+            fn to_proto_alias(&self) -> String {
+                self.as_str().to_owned()
+            }
+        }
+
+        impl<N: $crate::text::Normalize> FromProtoAlias<String> for $crate::text::Normalized<N> {
+            fn from_proto_alias(value: String) -> anyhow::Result<Self> {
+                Ok(Self::new(value))
+            }
+        }
+
+        impl<N> ToProtoAlias<String> for $crate::text::Normalized<N> {
+            fn to_proto_alias(&self) -> String {
+                self.as_str().to_owned()
+            }
+        }
+
+        /// The bytes are checked to be valid UTF-8
+        impl FromProtoAlias<Vec<u8>> for $crate::text::StrictUtf8Bytes {
+            fn from_proto_alias(value: Vec<u8>) -> anyhow::Result<Self> {
+                Self::new(value)
+            }
+        }
+
+        impl ToProtoAlias<Vec<u8>> for $crate::text::StrictUtf8Bytes {
+            fn to_proto_alias(&self) -> Vec<u8> {
+                self.as_str().as_bytes().to_vec()
+            }
+        }
+
+        /// The string is checked to be at most `MAX` characters long
+        impl<const MAX: usize> FromProtoAlias<String> for $crate::text::BoundedString<MAX> {
+            fn from_proto_alias(value: String) -> anyhow::Result<Self> {
+                Self::new(value)
+            }
+        }
+
+        impl<const MAX: usize> ToProtoAlias<String> for $crate::text::BoundedString<MAX> {
+            fn to_proto_alias(&self) -> String {
+                self.as_str().to_owned()
+            }
+        }
+
+        /// The integer is checked to be in the range
+        impl<W, T, const MIN: i64, const MAX: i64> FromProtoAlias<W> for $crate::Bounded<T, MIN, MAX>
+        where
+            W: Copy + std::fmt::Display + TryInto<i64>,
+            T: TryFrom<i64>,
+        {
+            fn from_proto_alias(value: W) -> anyhow::Result<Self> {
+                $crate::bounded::from_proto(value, $crate::mode::ConversionMode::Strict)
+            }
+
+            fn from_proto_alias_mode(
+                value: W,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self> {
+                $crate::bounded::from_proto(value, mode)
+            }
+
+            fn from_proto_alias_lossy(
+                value: W,
+            ) -> anyhow::Result<(Self, $crate::lossy::ConversionReport)> {
+                $crate::bounded::from_proto_lossy(value)
+            }
+        }
+
+        impl<W, T, const MIN: i64, const MAX: i64> ToProtoAlias<W> for $crate::Bounded<T, MIN, MAX>
+        where
+            T: Copy + Into<W>,
+        {
+            fn to_proto_alias(&self) -> W {
+                self.get().into()
+            }
+        }
+
+        impl<E: $crate::flags::Flag> FromProtoAlias<Vec<bool>> for $crate::flags::FlagSet<E> {
+            fn from_proto_alias(value: Vec<bool>) -> anyhow::Result<Self> {
+                $crate::flags::from_proto_bools(value, $crate::mode::ConversionMode::Strict)
+            }
+
+            fn from_proto_alias_mode(
+                value: Vec<bool>,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self> {
+                $crate::flags::from_proto_bools(value, mode)
+            }
+        }
+
+        impl<E: $crate::flags::Flag> FromProtoAlias<u64> for $crate::flags::FlagSet<E> {
+            fn from_proto_alias(value: u64) -> anyhow::Result<Self> {
+                $crate::flags::from_proto_mask(value, $crate::mode::ConversionMode::Strict)
+            }
+
+            fn from_proto_alias_mode(
+                value: u64,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self> {
+                $crate::flags::from_proto_mask(value, mode)
+            }
+        }
+
+        impl<E: $crate::flags::Flag> ToProtoAlias<Vec<bool>> for $crate::flags::FlagSet<E> {
+            fn to_proto_alias(&self) -> Vec<bool> {
+                self.to_bools()
+            }
+        }
+
+        impl<E: $crate::flags::Flag> ToProtoAlias<u64> for $crate::flags::FlagSet<E> {
+            fn to_proto_alias(&self) -> u64 {
+                self.to_mask()
+            }
+        }
+
+        impl<E: $crate::enum_set::EnumSetValue> FromProtoAlias<Vec<i32>> for $crate::enum_set::EnumSet<E> {
+            fn from_proto_alias(value: Vec<i32>) -> anyhow::Result<Self> {
+                $crate::enum_set::from_proto_values(value, $crate::mode::ConversionMode::Strict)
+            }
+
+            fn from_proto_alias_mode(
+                value: Vec<i32>,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self> {
+                $crate::enum_set::from_proto_values(value, mode)
+            }
+
+            fn from_proto_alias_lossy(
+                value: Vec<i32>,
+            ) -> anyhow::Result<(Self, $crate::lossy::ConversionReport)> {
+                $crate::enum_set::from_proto_lossy(value)
+            }
+        }
+
+        impl<E: $crate::enum_set::EnumSetValue> ToProtoAlias<Vec<i32>> for $crate::enum_set::EnumSet<E> {
+            fn to_proto_alias(&self) -> Vec<i32> {
+                self.to_values()
+            }
+        }
+
+        /// The string is allocated for every value, the `#[proto(intern)]` fields share them
+        impl FromProtoAlias<String> for ::std::sync::Arc<str> {
+            fn from_proto_alias(value: String) -> anyhow::Result<Self> {
+                Ok(value.into())
+            }
+        }
+
+        impl FromProtoAlias<String> for ::std::rc::Rc<str> {
+            fn from_proto_alias(value: String) -> anyhow::Result<Self> {
+                Ok(value.into())
+            }
+        }
+
+        /// The buffer of the string is kept, unless it has a spare capacity
+        impl FromProtoAlias<String> for Box<str> {
+            fn from_proto_alias(value: String) -> anyhow::Result<Self> {
+                Ok(value.into_boxed_str())
+            }
+        }
+
+        impl FromProtoAlias<String> for ::std::borrow::Cow<'static, str> {
+            fn from_proto_alias(value: String) -> anyhow::Result<Self> {
+                Ok(::std::borrow::Cow::Owned(value))
+            }
+        }
+
+        /// The borrowed strings are copied in to the proto `String` with a single allocation
+        impl ToProtoAlias<String> for &str {
+            fn to_proto_alias(&self) -> String {
+                String::from(*self)
+            }
+        }
+
+        impl ToProtoAlias<String> for ::std::sync::Arc<str> {
+            fn to_proto_alias(&self) -> String {
+                String::from(&**self)
+            }
+        }
+
+        impl ToProtoAlias<String> for ::std::rc::Rc<str> {
+            fn to_proto_alias(&self) -> String {
+                String::from(&**self)
+            }
+        }
+
+        impl ToProtoAlias<String> for Box<str> {
+            fn to_proto_alias(&self) -> String {
+                String::from(&**self)
+            }
+        }
+
+        impl ToProtoAlias<String> for ::std::borrow::Cow<'_, str> {
+            fn to_proto_alias(&self) -> String {
+                String::from(&**self)
+            }
+        }
+
+        /// The proto integer is scaled in to the value
+        impl<W, const NUM: i64, const DEN: i64, R> FromProtoAlias<W> for $crate::Scaled<W, NUM, DEN, R>
+        where
+            W: $crate::scaled::ScaledInt,
+            R: $crate::scaled::Rounding,
+        {
+            fn from_proto_alias(value: W) -> anyhow::Result<Self> {
+                Ok(Self::from_wire(value))
+            }
+        }
+
+        /// The values, which do not fit in to the proto integer after the scaling, are an error
+        impl<W, const NUM: i64, const DEN: i64, R> TryToProto for $crate::Scaled<W, NUM, DEN, R>
+        where
+            W: $crate::scaled::ScaledInt,
+            R: $crate::scaled::Rounding,
+        {
+            type Result = W;
+
+            fn try_to_proto(&self) -> anyhow::Result<Self::Result> {
+                self.to_wire()
+            }
+        }
+
+        /// The proto `double` is scaled in to the integer amount, within the tolerance of an integer
+        impl<T, const SCALE: i64, const TOLERANCE_PPM: u32> FromProtoAlias<f64>
+            for $crate::FloatToScaledInt<T, SCALE, TOLERANCE_PPM>
+        where
+            T: $crate::scaled::ScaledInt,
+        {
+            fn from_proto_alias(value: f64) -> anyhow::Result<Self> {
+                Self::from_wire(value)
+            }
+        }
+
+        impl<T, const SCALE: i64, const TOLERANCE_PPM: u32> ToProtoAlias<f64>
+            for $crate::FloatToScaledInt<T, SCALE, TOLERANCE_PPM>
+        where
+            T: $crate::scaled::ScaledInt,
+        {
+            fn to_proto_alias(&self) -> f64 {
+                (*self).to_wire()
+            }
+        }
+
+        /// The string is parsed as a decimal integer
+        impl<T: $crate::decimal::DecimalInt> FromProtoAlias<String> for $crate::Decimal<T> {
+            fn from_proto_alias(value: String) -> anyhow::Result<Self> {
+                $crate::decimal::from_proto(&value)
+            }
+        }
+
+        impl<T: $crate::decimal::DecimalInt> ToProtoAlias<String> for $crate::Decimal<T> {
+            fn to_proto_alias(&self) -> String {
+                self.to_string()
+            }
+        }
+
+        /// Convert the value in to proto value wrapped in an option. The values are also converted in
+        /// the derived `Option` fields, and in the repeated ones without the absent values, see
+        /// `ToProtoOptionElements`. It is not in the `prelude` module, as its `to_proto` is named as
+        /// the one of `ToProto`.
+        pub trait ToProtoOption<T> {
+            fn to_proto(&self) -> Option<T>;
+        }
+
+        /// Convert the value in to proto value, when the conversion can fail, like validating the
+        /// value before it is sent. Every `ToProto` type converts without failing.
+        pub trait TryToProto {
+            /// The proto value type
+            type Result;
+
+            /// Convert the value in to proto value
+            fn try_to_proto(&self) -> anyhow::Result<Self::Result>;
+        }
+
+        impl<T: ToProto> TryToProto for T {
+            type Result = T::Result;
+
+            fn try_to_proto(&self) -> anyhow::Result<Self::Result> {
+                Ok(self.to_proto())
+            }
+        }
+
+        /// Convert the proto value in to the appropriate value
+        pub trait FromProto {
+            type Result;
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto(self) -> anyhow::Result<Self::Result>;
+
+            /// Convert the proto value and validate the result with its `ProtoValidate`
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_validated(self) -> anyhow::Result<Self::Result>
+            where
+                Self: Sized,
+                Self::Result: $crate::validate::ProtoValidate,
+            {
+                let result = self.from_proto()?;
+                $crate::validate::ProtoValidate::validate(&result)?;
+                Ok(result)
+            }
+        }
+
+        /// Convert the proto value, when the conversion can not fail, like for the identity scalars.
+        /// The types get `FromProto` returning the result in `Ok` with `impl_traits!(infallible Type)`,
+        /// it is not a blanket impl, as it would conflict with the one of `Box`.
+        pub trait FromProtoInfallible {
+            type Result;
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_infallible(self) -> Self::Result;
+        }
+
+        /// Convert the containers of the infallible values without the `Result`, they are still
+        /// `FromProto` through their fallible impls
+        pub trait FromProtoInfallibleElements {
+            type Result;
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_infallible(self) -> Self::Result;
+        }
+
+        impl<T: FromProtoInfallible> FromProtoInfallibleElements for Vec<T> {
+            type Result = Vec<T::Result>;
+
+            fn from_proto_infallible(self) -> Self::Result {
+                self.into_iter().map(T::from_proto_infallible).collect()
+            }
+        }
+
+        impl<T: FromProtoInfallible> FromProtoInfallibleElements for Option<T> {
+            type Result = Option<T::Result>;
+
+            fn from_proto_infallible(self) -> Self::Result {
+                self.map(T::from_proto_infallible)
+            }
+        }
+
+        /// Convert the proto value strictly or leniently. Implement it with the default method for the
+        /// types, which have no malformed values, the containers pass the mode to their elements.
+        pub trait FromProtoMode: FromProto + Sized {
+            /// Convert the proto value, the same as `from_proto`, unless the type handles the malformed
+            /// values differently
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_mode(
+                self,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self::Result> {
+                let _ = mode;
+                self.from_proto()
+            }
+        }
+
+        /// Convert the proto duration, rejecting the longer one than the bound, before it is added
+        /// to an instant. The `chrono::Duration` arms implement it.
+        pub trait FromProtoBounded: FromProto + Sized {
+            /// Convert the proto value, which is not over the `max`
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_bounded(self, max: Self::Result) -> anyhow::Result<Self::Result>;
+        }
+
+        /// Convert the proto instant in the allowed range, the `chrono::DateTime` arms implement it
+        pub trait FromProtoInstant: FromProto + Sized {
+            /// Convert the proto value, which is not before the `min`
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_not_before(self, min: Self::Result) -> anyhow::Result<Self::Result>;
+
+            /// Convert the proto value, which is not after the `max`
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_not_after(self, max: Self::Result) -> anyhow::Result<Self::Result>;
+        }
+
+        /// The proto values with more encodings of the same value, like the durations of the
+        /// `chrono::Duration` arms
+        pub trait CanonicalProto {
+            /// Whether the value is in the canonical encoding, the one written in to proto
+            fn is_canonical(&self) -> bool;
+
+            /// Rewrite the value in to the canonical encoding, the value out of the range of the
+            /// encoding is kept as it is
+            fn canonicalize(&mut self);
+        }
+
+        /// Convert the proto date range, rejecting the longer one than the number of days, the
+        /// `std::ops::RangeInclusive<chrono::NaiveDate>` arm implements it
+        pub trait FromProtoDateRange: FromProto + Sized {
+            /// Convert the proto value, which is not longer than the `days`, including the end
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_max_days(self, days: u32) -> anyhow::Result<Self::Result>;
+        }
+
+        impl<T: FromProtoMode> FromProtoMode for Option<T> {
+            fn from_proto_mode(
+                self,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self::Result> {
+                self.map(|v| v.from_proto_mode(mode)).transpose()
+            }
+        }
+
+        impl<T: FromProtoMode> FromProtoMode for Vec<T> {
+            fn from_proto_mode(
+                self,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self::Result> {
+                let mut result = Vec::with_capacity(self.len());
+                for (index, item) in self.into_iter().enumerate() {
+                    result.push(item.from_proto_mode(mode).map_err(|error| {
+                        let error = $crate::error::at_index(error, index);
+                        $crate::trace::failed::<Self, Self::Result>(error)
+                    })?);
+                }
+                Ok(result)
+            }
+        }
+
+        impl<T: FromProtoMode> FromProtoMode for Box<T> {
+            fn from_proto_mode(
+                self,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self::Result> {
+                (*self).from_proto_mode(mode).map(Box::new)
+            }
+        }
+
+        impl<K, V> FromProtoMode for std::collections::HashMap<K, V>
+        where
+            K: Eq + std::hash::Hash + std::fmt::Display,
+            V: FromProtoMode,
+        {
+            fn from_proto_mode(
+                self,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self::Result> {
+                let mut result = std::collections::HashMap::with_capacity(self.len());
+                for (key, value) in self {
+                    let value = value.from_proto_mode(mode).map_err(|error| {
+                        let error = $crate::error::at_key(error, &key);
+                        $crate::trace::failed::<Self, Self::Result>(error)
+                    })?;
+                    result.insert(key, value);
+                }
+                Ok(result)
+            }
+        }
+
+        impl<K, V> FromProtoMode for std::collections::BTreeMap<K, V>
+        where
+            K: Ord + std::fmt::Display,
+            V: FromProtoMode,
+        {
+            fn from_proto_mode(
+                self,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self::Result> {
+                let mut result = std::collections::BTreeMap::new();
+                for (key, value) in self {
+                    let value = value.from_proto_mode(mode).map_err(|error| {
+                        let error = $crate::error::at_key(error, &key);
+                        $crate::trace::failed::<Self, Self::Result>(error)
+                    })?;
+                    result.insert(key, value);
+                }
+                Ok(result)
+            }
+        }
+
+        impl FromProtoMode for bool {}
+
+        /// Convert the proto value in the schema version of the client. Implement it with the default
+        /// method for the types, which are the same in all the versions, the containers pass the
+        /// version to their elements.
+        pub trait FromProtoVersioned: FromProto + Sized {
+            /// Convert the proto value, the same as `from_proto`, unless the meaning of the value
+            /// changed between the versions
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_versioned(
+                self,
+                version: $crate::version::SchemaVersion,
+            ) -> anyhow::Result<Self::Result> {
+                let _ = version;
+                self.from_proto()
+            }
+        }
+
+        impl<T: FromProtoVersioned> FromProtoVersioned for Option<T> {
+            fn from_proto_versioned(
+                self,
+                version: $crate::version::SchemaVersion,
+            ) -> anyhow::Result<Self::Result> {
+                self.map(|v| v.from_proto_versioned(version)).transpose()
+            }
+        }
+
+        impl<T: FromProtoVersioned> FromProtoVersioned for Vec<T> {
+            fn from_proto_versioned(
+                self,
+                version: $crate::version::SchemaVersion,
+            ) -> anyhow::Result<Self::Result> {
+                let mut result = Vec::with_capacity(self.len());
+                for (index, item) in self.into_iter().enumerate() {
+                    result.push(item.from_proto_versioned(version).map_err(|error| {
+                        let error = $crate::error::at_index(error, index);
+                        $crate::trace::failed::<Self, Self::Result>(error)
+                    })?);
+                }
+                Ok(result)
+            }
+        }
+
+        impl<T: FromProtoVersioned> FromProtoVersioned for Box<T> {
+            fn from_proto_versioned(
+                self,
+                version: $crate::version::SchemaVersion,
+            ) -> anyhow::Result<Self::Result> {
+                (*self).from_proto_versioned(version).map(Box::new)
+            }
+        }
+
+        impl<K, V> FromProtoVersioned for std::collections::HashMap<K, V>
+        where
+            K: Eq + std::hash::Hash + std::fmt::Display,
+            V: FromProtoVersioned,
+        {
+            fn from_proto_versioned(
+                self,
+                version: $crate::version::SchemaVersion,
+            ) -> anyhow::Result<Self::Result> {
+                let mut result = std::collections::HashMap::with_capacity(self.len());
+                for (key, value) in self {
+                    let value = value.from_proto_versioned(version).map_err(|error| {
+                        let error = $crate::error::at_key(error, &key);
+                        $crate::trace::failed::<Self, Self::Result>(error)
+                    })?;
+                    result.insert(key, value);
+                }
+                Ok(result)
+            }
+        }
+
+        impl<K, V> FromProtoVersioned for std::collections::BTreeMap<K, V>
+        where
+            K: Ord + std::fmt::Display,
+            V: FromProtoVersioned,
+        {
+            fn from_proto_versioned(
+                self,
+                version: $crate::version::SchemaVersion,
+            ) -> anyhow::Result<Self::Result> {
+                let mut result = std::collections::BTreeMap::new();
+                for (key, value) in self {
+                    let value = value.from_proto_versioned(version).map_err(|error| {
+                        let error = $crate::error::at_key(error, &key);
+                        $crate::trace::failed::<Self, Self::Result>(error)
+                    })?;
+                    result.insert(key, value);
+                }
+                Ok(result)
+            }
+        }
+
+        impl FromProtoVersioned for bool {}
+
+        /// Convert the proto value as much as possible, the malformed values are converted leniently
+        /// and the report has what was lossy or defaulted. Implement it with the default method for
+        /// the types, which have no malformed values, the containers aggregate the reports of their
+        /// elements.
+        pub trait FromProtoLossy: FromProto + Sized {
+            /// Convert the proto value, the same as `from_proto` with an empty report, unless the type
+            /// handles the malformed values differently
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_lossy(
+                self,
+            ) -> anyhow::Result<(Self::Result, $crate::lossy::ConversionReport)> {
+                Ok((self.from_proto()?, $crate::lossy::ConversionReport::new()))
+            }
+        }
+
+        impl<T: FromProtoLossy> FromProtoLossy for Option<T> {
+            fn from_proto_lossy(
+                self,
+            ) -> anyhow::Result<(Self::Result, $crate::lossy::ConversionReport)> {
+                match self {
+                    Some(value) => value
+                        .from_proto_lossy()
+                        .map(|(value, report)| (Some(value), report)),
+                    None => Ok((None, $crate::lossy::ConversionReport::new())),
+                }
+            }
+        }
+
+        impl<T: FromProtoLossy> FromProtoLossy for Vec<T> {
+            fn from_proto_lossy(
+                self,
+            ) -> anyhow::Result<(Self::Result, $crate::lossy::ConversionReport)> {
+                let _batch = $crate::trace::Batch::new::<Self::Result>(self.len());
+                let mut result = Vec::with_capacity(self.len());
+                let mut report = $crate::lossy::ConversionReport::new();
+                for (index, item) in self.into_iter().enumerate() {
+                    let (value, nested) = item.from_proto_lossy().map_err(|error| {
+                        let error = $crate::error::at_index(error, index);
+                        $crate::trace::failed::<Self, Self::Result>(error)
+                    })?;
+                    report.append(nested.at_index(index));
+                    result.push(value);
+                }
+                Ok((result, report))
+            }
+        }
+
+        impl<T: FromProtoLossy> FromProtoLossy for Box<T> {
+            fn from_proto_lossy(
+                self,
+            ) -> anyhow::Result<(Self::Result, $crate::lossy::ConversionReport)> {
+                (*self)
+                    .from_proto_lossy()
+                    .map(|(value, report)| (Box::new(value), report))
+            }
+        }
+
+        impl<K, V> FromProtoLossy for std::collections::HashMap<K, V>
+        where
+            K: Eq + std::hash::Hash + std::fmt::Display,
+            V: FromProtoLossy,
+        {
+            fn from_proto_lossy(
+                self,
+            ) -> anyhow::Result<(Self::Result, $crate::lossy::ConversionReport)> {
+                let mut result = std::collections::HashMap::with_capacity(self.len());
+                let mut report = $crate::lossy::ConversionReport::new();
+                for (key, value) in self {
+                    let (value, nested) = value.from_proto_lossy().map_err(|error| {
+                        let error = $crate::error::at_key(error, &key);
+                        $crate::trace::failed::<Self, Self::Result>(error)
+                    })?;
+                    report.append(nested.at_key(&key));
+                    result.insert(key, value);
+                }
+                Ok((result, report))
+            }
+        }
+
+        impl<K, V> FromProtoLossy for std::collections::BTreeMap<K, V>
+        where
+            K: Ord + std::fmt::Display,
+            V: FromProtoLossy,
+        {
+            fn from_proto_lossy(
+                self,
+            ) -> anyhow::Result<(Self::Result, $crate::lossy::ConversionReport)> {
+                let mut result = std::collections::BTreeMap::new();
+                let mut report = $crate::lossy::ConversionReport::new();
+                for (key, value) in self {
+                    let (value, nested) = value.from_proto_lossy().map_err(|error| {
+                        let error = $crate::error::at_key(error, &key);
+                        $crate::trace::failed::<Self, Self::Result>(error)
+                    })?;
+                    report.append(nested.at_key(&key));
+                    result.insert(key, value);
+                }
+                Ok((result, report))
+            }
+        }
+
+        impl FromProtoLossy for bool {}
+
+        impl<T: FromProto> FromProto for Option<T> {
+            type Result = Option<T::Result>;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                self.map(|v| v.from_proto()).transpose()
+            }
+        }
+
+        impl ToProtoAlias<bool> for bool {
+            fn to_proto_alias(&self) -> bool {
+                *self
+            }
+        }
+        /// Convert list of proto elements in a vector, the order of the elements is kept
+        impl<T: FromProto> FromProto for Vec<T> {
+            type Result = Vec<T::Result>;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                let _batch = $crate::trace::Batch::new::<Self::Result>(self.len());
+                // the result is allocated once, instead of growing it, see `benches/conversions.rs`
+                let mut result = Vec::with_capacity(self.len());
+                for (index, item) in self.into_iter().enumerate() {
+                    let segment = || $crate::error::PathSegment::Index(index);
+                    let converted = $crate::fault::scope(segment, item, T::from_proto);
+                    result.push(converted.map_err(|error| {
+                        let error = $crate::error::at_index(error, index);
+                        $crate::trace::failed::<Self, Self::Result>(error)
+                    })?);
+                }
+                Ok(result)
+            }
+        }
+
+        /// The deprecated proto value records the notice, when it is present
+        impl<P: FromProto + Default + PartialEq> FromProto for $crate::deprecation::DeprecatedField<P> {
+            type Result = P::Result;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                self.record().from_proto()
+            }
+        }
+
+        /// Convert the sparse repeated values, like of the sparse columns. The missing elements are
+        /// kept with `from_proto` in to `Vec<Option<T>>`, or they are an error with `from_proto_dense`.
+        pub trait FromProtoSparse {
+            type Item;
+            /// Convert the elements, when none of them is missing, the error lists the indices of the
+            /// missing ones
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_dense(self) -> anyhow::Result<Vec<Self::Item>>;
+        }
+
+        impl<P: FromProto> FromProtoSparse for Vec<Option<P>> {
+            type Item = P::Result;
+
+            fn from_proto_dense(self) -> anyhow::Result<Vec<Self::Item>> {
+                let holes: Vec<usize> = self
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, item)| item.is_none().then_some(index))
+                    .collect();
+                if !holes.is_empty() {
+                    let error = $crate::error::missing_elements(&holes);
+                    return Err($crate::trace::failed::<Self, Vec<Self::Item>>(error));
+                }
+                self.into_iter().flatten().collect::<Vec<P>>().from_proto()
+            }
+        }
+
+        /// Convert list of elements in vector to proto list, the order of the elements is kept
+        impl<T: ToProto> ToProto for Vec<T> {
+            type Result = Vec<T::Result>;
+
+            fn to_proto(&self) -> Self::Result {
+                to_proto_from_iter(self)
+            }
+        }
+
+        /// Feed the canonical proto form of the `value` to the `hasher`, see `proto_conve::hash`
+        #[allow(dead_code)]
+        pub fn proto_hash<T: ToProto>(value: &T, hasher: &mut impl std::hash::Hasher)
+        where
+            T::Result: $crate::hash::ProtoHashable,
+        {
+            $crate::hash::ProtoHashable::hash_proto(&value.to_proto(), hasher)
+        }
+
+        /// The hash of the canonical proto form of the `value` with the `StableHasher`, which is the
+        /// same across the runs, like for the idempotency keys
+        #[allow(dead_code)]
+        pub fn proto_hash_u64<T: ToProto>(value: &T) -> u64
+        where
+            T::Result: $crate::hash::ProtoHashable,
+        {
+            $crate::hash::hash_u64(&value.to_proto())
+        }
+
+        /// Convert the referenced elements, like the values of a map, in to the proto list, it is
+        /// preallocated by the `size_hint` of the iterator
+        #[allow(dead_code)]
+        pub fn to_proto_from_iter<'a, T, I>(iter: I) -> Vec<T::Result>
+        where
+            T: ToProto + 'a,
+            I: IntoIterator<Item = &'a T>,
+        {
+            let iter = iter.into_iter();
+            let mut result = Vec::with_capacity(iter.size_hint().0);
+            result.extend(iter.map(T::to_proto));
+            result
+        }
+
+        /// Convert the referenced elements, which can fail, in to the proto list, the error has the
+        /// index of the first failed element
+        #[allow(dead_code)]
+        pub fn try_to_proto_from_iter<'a, T, I>(iter: I) -> anyhow::Result<Vec<T::Result>>
+        where
+            T: TryToProto + 'a,
+            I: IntoIterator<Item = &'a T>,
+        {
+            let iter = iter.into_iter();
+            let mut result = Vec::with_capacity(iter.size_hint().0);
+            for (index, item) in iter.enumerate() {
+                let item = item
+                    .try_to_proto()
+                    .map_err(|error| $crate::error::at_index(error, index))?;
+                result.push(item);
+            }
+            Ok(result)
+        }
+
+        /// Convert the iterators of the referenced elements in to the proto lists, the same as
+        /// `to_proto_from_iter` and `try_to_proto_from_iter`
+        pub trait ToProtoIterator<'a, T: 'a>: Iterator<Item = &'a T> + Sized {
+            /// Convert the elements in to the proto list
+            fn to_proto_vec(self) -> Vec<<T as ToProto>::Result>
+            where
+                T: ToProto,
+            {
+                to_proto_from_iter(self)
+            }
+
+            /// Convert the elements in to the proto list, the error has the index of the failed one
+            fn try_to_proto_vec(self) -> anyhow::Result<Vec<<T as TryToProto>::Result>>
+            where
+                T: TryToProto,
+            {
+                try_to_proto_from_iter(self)
+            }
+        }
+
+        impl<'a, T: 'a, I: Iterator<Item = &'a T>> ToProtoIterator<'a, T> for I {}
+
+        impl<T: ToProto> ToProto for Option<T> {
+            type Result = Option<T::Result>;
+
+            fn to_proto(&self) -> Self::Result {
+                self.as_ref().map(|v| v.to_proto())
+            }
+        }
+
+        /// The value is `None`, when the predicate is true for it, otherwise it is converted
+        impl<T: ToProto + ?Sized, F: Fn(&T) -> bool> ToProto for $crate::OmitIf<'_, T, F> {
+            type Result = Option<T::Result>;
+
+            fn to_proto(&self) -> Self::Result {
+                match self.is_omitted() {
+                    true => None,
+                    false => Some(self.value().to_proto()),
+                }
+            }
+        }
+
+        /// The proto3 `optional` field of the patch, the missing value is left as it is
+        impl<P, T> FromProtoAlias<Option<P>> for $crate::patch::Patch<T>
+        where
+            P: FromProto<Result = T>,
+        {
+            fn from_proto_alias(value: Option<P>) -> anyhow::Result<Self> {
+                match value {
+                    None => Ok($crate::patch::Patch::Unchanged),
+                    Some(value) => value.from_proto().map($crate::patch::Patch::Set),
+                }
+            }
+        }
+
+        /// The proto3 `optional` field of the patch, there is no clear in it, so `Clear` is written as
+        /// the default proto value
+        impl<T: ToProto> ToProto for $crate::patch::Patch<T>
+        where
+            T::Result: Default,
+        {
+            type Result = Option<T::Result>;
+
+            fn to_proto(&self) -> Self::Result {
+                match self {
+                    $crate::patch::Patch::Unchanged => None,
+                    $crate::patch::Patch::Clear => Some(Default::default()),
+                    $crate::patch::Patch::Set(value) => Some(value.to_proto()),
+                }
+            }
+        }
+
+        /// Convert the proto value through the cache, the equal proto values share the converted one
+        pub trait FromProtoCached: FromProto + ::std::hash::Hash + Eq + Clone {
+            /// Convert the proto value, or take the one converted before from the `cache`
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_cached(
+                self,
+                cache: &mut $crate::cache::ConversionCache<Self, Self::Result>,
+            ) -> anyhow::Result<::std::sync::Arc<Self::Result>> {
+                cache.get_or_convert(self, Self::from_proto)
+            }
+        }
+
+        impl<P: FromProto + ::std::hash::Hash + Eq + Clone> FromProtoCached for P {}
+
+        /// The fields converted through the cache of the thread, used by `#[proto(cached)]`
+        #[doc(hidden)]
+        pub trait ProtoCachedField<P>: Sized {
+            fn from_proto_cached_field(proto: P) -> anyhow::Result<Self>;
+
+            fn to_proto_cached_field(&self) -> P;
+        }
+
+        impl<P, T> ProtoCachedField<Option<P>> for ::std::sync::Arc<T>
+        where
+            P: FromProto<Result = T> + ::std::hash::Hash + Eq + Clone + 'static,
+            T: ToProto<Result = P> + 'static,
+        {
+            fn from_proto_cached_field(proto: Option<P>) -> anyhow::Result<Self> {
+                $crate::cache::cached(proto.required()?, P::from_proto)
+            }
+
+            fn to_proto_cached_field(&self) -> Option<P> {
+                Some((**self).to_proto())
+            }
+        }
+
+        impl<P, T> ProtoCachedField<Option<P>> for Option<::std::sync::Arc<T>>
+        where
+            P: FromProto<Result = T> + ::std::hash::Hash + Eq + Clone + 'static,
+            T: ToProto<Result = P> + 'static,
+        {
+            fn from_proto_cached_field(proto: Option<P>) -> anyhow::Result<Self> {
+                proto
+                    .map(|proto| $crate::cache::cached(proto, P::from_proto))
+                    .transpose()
+            }
+
+            fn to_proto_cached_field(&self) -> Option<P> {
+                self.as_deref().map(ToProto::to_proto)
+            }
+        }
+
+        impl<P, T> ProtoCachedField<Vec<P>> for Vec<::std::sync::Arc<T>>
+        where
+            P: FromProto<Result = T> + ::std::hash::Hash + Eq + Clone + 'static,
+            T: ToProto<Result = P> + 'static,
+        {
+            fn from_proto_cached_field(proto: Vec<P>) -> anyhow::Result<Self> {
+                proto
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, proto)| {
+                        $crate::cache::cached(proto, P::from_proto)
+                            .map_err(|error| $crate::error::at_index(error, index))
+                    })
+                    .collect()
+            }
+
+            fn to_proto_cached_field(&self) -> Vec<P> {
+                self.iter().map(|value| (**value).to_proto()).collect()
+            }
+        }
+
+        /// The wrapped list is converted through the wrapped elements
+        impl<P> FromProto for $crate::Proto<Vec<P>>
+        where
+            $crate::Proto<P>: FromProto,
+        {
+            type Result = Vec<<$crate::Proto<P> as FromProto>::Result>;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                self.0
+                    .into_iter()
+                    .map($crate::Proto)
+                    .collect::<Vec<_>>()
+                    .from_proto()
+            }
+        }
+
+        impl<P> FromProto for $crate::Proto<Option<P>>
+        where
+            $crate::Proto<P>: FromProto,
+        {
+            type Result = Option<<$crate::Proto<P> as FromProto>::Result>;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                self.0.map($crate::Proto).from_proto()
+            }
+        }
+
+        impl<'a, T> ToProto for $crate::Proto<&'a Vec<T>>
+        where
+            $crate::Proto<&'a T>: ToProto,
+        {
+            type Result = Vec<<$crate::Proto<&'a T> as ToProto>::Result>;
+
+            fn to_proto(&self) -> Self::Result {
+                self.0
+                    .iter()
+                    .map(|value| $crate::Proto(value).to_proto())
+                    .collect()
+            }
+        }
+
+        impl<'a, T> ToProto for $crate::Proto<&'a Option<T>>
+        where
+            $crate::Proto<&'a T>: ToProto,
+        {
+            type Result = Option<<$crate::Proto<&'a T> as ToProto>::Result>;
+
+            fn to_proto(&self) -> Self::Result {
+                self.0.as_ref().map(|value| $crate::Proto(value).to_proto())
+            }
+        }
+
+        /// Convert the proto value through the `FromProto` of its `Proto` wrapper, instead of its own
+        pub trait FromProtoWrapped: Sized {
+            type Result;
+
+            /// Convert the value as `Proto(self).from_proto()`
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_wrapped(self) -> anyhow::Result<Self::Result>;
+        }
+
+        impl<P> FromProtoWrapped for P
+        where
+            $crate::Proto<P>: FromProto,
+        {
+            type Result = <$crate::Proto<P> as FromProto>::Result;
+
+            fn from_proto_wrapped(self) -> anyhow::Result<Self::Result> {
+                $crate::Proto(self).from_proto()
+            }
+        }
+
+        /// Convert the proto values in to the chunks of the converted values, see `chunked`
+        pub trait ChunkedFromProto: IntoIterator + Sized
+        where
+            Self::Item: FromProto,
+        {
+            /// The chunks of at most `chunk_size` values, `on_chunk` gets the progress after every
+            /// chunk
+            #[allow(clippy::wrong_self_convention, clippy::type_complexity)]
+            fn from_proto_chunked<F: FnMut($crate::chunked::ChunkReport)>(
+                self,
+                chunk_size: usize,
+                mode: $crate::chunked::ErrorMode,
+                on_chunk: F,
+            ) -> $crate::chunked::Chunks<
+                Self::IntoIter,
+                fn(Self::Item) -> anyhow::Result<<Self::Item as FromProto>::Result>,
+                F,
+            > {
+                $crate::chunked::Chunks::new(
+                    self.into_iter(),
+                    chunk_size,
+                    mode,
+                    FromProto::from_proto,
+                    on_chunk,
+                )
+            }
+        }
+
+        impl<I: IntoIterator> ChunkedFromProto for I where I::Item: FromProto {}
+
+        /// Convert the optional value in to a proto value, which is there even for `None`, like for the
+        /// proto3 message fields, which are not optional
+        pub trait ToProtoOrDefault<T: ToProto> {
+            /// Convert the value, `None` is the default proto value
+            fn to_proto_or_default(&self) -> T::Result
+            where
+                T::Result: Default;
+
+            /// Convert the value, `None` is the `fallback`
+            fn to_proto_or(&self, fallback: T::Result) -> T::Result;
+        }
+
+        impl<T: ToProto> ToProtoOrDefault<T> for Option<T> {
+            fn to_proto_or_default(&self) -> T::Result
+            where
+                T::Result: Default,
+            {
+                self.as_ref().map(T::to_proto).unwrap_or_default()
+            }
+
+            fn to_proto_or(&self, fallback: T::Result) -> T::Result {
+                self.as_ref().map(T::to_proto).unwrap_or(fallback)
+            }
+        }
+
+        /// Convert the optional value, which converts in to an optional proto value too, like the nested
+        /// `Option<Option<T>>`, in to a single optional proto value
+        pub trait ToProtoOptionFlat<P> {
+            /// Convert the value, it is `None` when any of the options is `None`
+            fn to_proto_option_flat(&self) -> Option<P>;
+        }
+
+        impl<P, T: ToProto<Result = Option<P>>> ToProtoOptionFlat<P> for Option<T> {
+            fn to_proto_option_flat(&self) -> Option<P> {
+                self.as_ref().and_then(T::to_proto)
+            }
+        }
+
+        /// The missing value converts in to `None`, as the absent one
+        impl<P, T: ToProtoOption<P>> ToProtoOption<P> for Option<T> {
+            fn to_proto(&self) -> Option<P> {
+                self.as_ref().and_then(T::to_proto)
+            }
+        }
+
+        /// Convert the elements, which can be absent in proto, like the redacted ones, with their
+        /// `ToProtoOption`
+        pub trait ToProtoOptionElements<P> {
+            /// The proto values of the present elements, the absent ones are left out
+            fn to_proto_present(&self) -> Vec<P>;
+
+            /// The proto values of all the elements, an absent one is an error with its index
+            fn try_to_proto_all(&self) -> anyhow::Result<Vec<P>>;
+        }
+
+        impl<P, T: ToProtoOption<P>> ToProtoOptionElements<P> for [T] {
+            fn to_proto_present(&self) -> Vec<P> {
+                self.iter().filter_map(T::to_proto).collect()
+            }
+
+            fn try_to_proto_all(&self) -> anyhow::Result<Vec<P>> {
+                self.iter()
+                    .enumerate()
+                    .map(|(index, value)| {
+                        value.to_proto().ok_or_else(|| {
+                            $crate::error::at_index(
+                                anyhow::anyhow!("The value has no proto value"),
+                                index,
+                            )
+                        })
+                    })
+                    .collect()
+            }
+        }
+
+        /// Convert the boxed proto value, prost boxes the recursive message fields
+        impl<T: FromProto> FromProto for Box<T> {
+            type Result = Box<T::Result>;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                (*self).from_proto().map(Box::new)
+            }
+        }
+
+        /// Convert the boxed value in to boxed proto value
+        impl<T: ToProto> ToProto for Box<T> {
+            type Result = Box<T::Result>;
+
+            fn to_proto(&self) -> Self::Result {
+                Box::new((**self).to_proto())
+            }
+        }
+
+        /// The borrowed value converts as the value it borrows, so the `Option<&T>` of the borrowed
+        /// sub-fields and the `Vec<&T>` of the picked elements convert without the adapters
+        impl<T: ToProto + ?Sized> ToProto for &T {
+            type Result = T::Result;
+
+            fn to_proto(&self) -> Self::Result {
+                (**self).to_proto()
+            }
+        }
+
+        impl<T: ToProto + ?Sized> ToProto for &mut T {
+            type Result = T::Result;
+
+            fn to_proto(&self) -> Self::Result {
+                (**self).to_proto()
+            }
+        }
+
+        /// Convert the slice to proto list, the order of the elements is kept
+        impl<T: ToProto> ToProto for [T] {
+            type Result = Vec<T::Result>;
+
+            fn to_proto(&self) -> Self::Result {
+                to_proto_from_iter(self)
+            }
+        }
+
+        /// Convert the proto map, the keys are kept as they are
+        impl<K, V> FromProto for ::std::collections::HashMap<K, V>
+        where
+            K: Eq + ::std::hash::Hash + ::std::fmt::Display,
+            V: FromProto,
+        {
+            type Result = ::std::collections::HashMap<K, V::Result>;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                let mut result = ::std::collections::HashMap::with_capacity(self.len());
+                for (key, value) in self.into_iter() {
+                    let value = match $crate::budget::element().and_then(|()| value.from_proto()) {
+                        Ok(value) => value,
+                        Err(error) => {
+                            return Err($crate::trace::failed::<Self, Self::Result>(
+                                $crate::error::at_key(error, &key),
+                            ))
+                        }
+                    };
+                    result.insert(key, value);
+                }
+                Ok(result)
+            }
+        }
+
+        /// The same as of `HashMap`, for the maps of prost-build with `btree_map`
+        impl<K, V> FromProto for ::std::collections::BTreeMap<K, V>
+        where
+            K: Ord + ::std::fmt::Display,
+            V: FromProto,
+        {
+            type Result = ::std::collections::BTreeMap<K, V::Result>;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                let mut result = ::std::collections::BTreeMap::new();
+                for (key, value) in self.into_iter() {
+                    let value = match $crate::budget::element().and_then(|()| value.from_proto()) {
+                        Ok(value) => value,
+                        Err(error) => {
+                            return Err($crate::trace::failed::<Self, Self::Result>(
+                                $crate::error::at_key(error, &key),
+                            ))
+                        }
+                    };
+                    result.insert(key, value);
+                }
+                Ok(result)
+            }
+        }
+
+        /// Convert the map in to proto map, the keys are kept as they are
+        impl<K, V> ToProto for ::std::collections::HashMap<K, V>
+        where
+            K: Eq + ::std::hash::Hash + Clone,
+            V: ToProto,
+        {
+            type Result = ::std::collections::HashMap<K, V::Result>;
+
+            fn to_proto(&self) -> Self::Result {
+                self.iter()
+                    .map(|(key, value)| (key.clone(), value.to_proto()))
+                    .collect()
+            }
+        }
+
+        /// The same as of `HashMap`, for the maps of prost-build with `btree_map`
+        impl<K, V> ToProto for ::std::collections::BTreeMap<K, V>
+        where
+            K: Ord + Clone,
+            V: ToProto,
+        {
+            type Result = ::std::collections::BTreeMap<K, V::Result>;
+
+            fn to_proto(&self) -> Self::Result {
+                self.iter()
+                    .map(|(key, value)| (key.clone(), value.to_proto()))
+                    .collect()
+            }
+        }
+
+        /// Convert the set in to proto list, the order of the elements is not defined, it is sorted with
+        /// `ToProtoSorted`
+        impl<T: ToProto> ToProto for ::std::collections::HashSet<T> {
+            type Result = Vec<T::Result>;
+
+            fn to_proto(&self) -> Self::Result {
+                to_proto_from_iter(self)
+            }
+        }
+
+        /// Convert the set in to proto list, ordered as the set
+        impl<T: ToProto> ToProto for ::std::collections::BTreeSet<T> {
+            type Result = Vec<T::Result>;
+
+            fn to_proto(&self) -> Self::Result {
+                to_proto_from_iter(self)
+            }
+        }
+
+        impl FromProtoInfallible for bool {
+            type Result = bool;
+
+            fn from_proto_infallible(self) -> Self::Result {
+                self
+            }
+        }
+
+        $crate::impl_traits!(infallible bool);
+
+        $crate::impl_traits!(identity i32, i64, u32, u64, f32, f64);
+        $crate::impl_traits!(identity $crate::ProtoBytes);
+
+        impl FromProtoInfallible for String {
+            type Result = String;
+
+            fn from_proto_infallible(self) -> Self::Result {
+                self
+            }
+        }
+
+        /// The identity, it fails only for the strings exceeding the size limit of `budget::limit`
+        impl FromProto for String {
+            type Result = String;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                $crate::budget::string(self.len())?;
+                Ok(self)
+            }
+        }
+
+        impl ToProto for String {
+            type Result = String;
+
+            fn to_proto(&self) -> Self::Result {
+                self.clone()
+            }
+        }
+
+        impl FromProtoMode for String {}
+
+        impl FromProtoVersioned for String {}
+
+        impl FromProtoLossy for String {}
+
+        /// The buffer is moved, not converted per byte
+        impl FromProtoAlias<Vec<u8>> for $crate::ProtoBytes {
+            fn from_proto_alias(value: Vec<u8>) -> anyhow::Result<Self> {
+                Ok(value.into())
+            }
+        }
+
+        impl ToProtoAlias<Vec<u8>> for $crate::ProtoBytes {
+            fn to_proto_alias(&self) -> Vec<u8> {
+                self.to_vec()
+            }
+        }
+
+        impl FromProtoAlias<Vec<u8>> for Box<[u8]> {
+            fn from_proto_alias(value: Vec<u8>) -> anyhow::Result<Self> {
+                Ok(value.into_boxed_slice())
+            }
+        }
+
+        impl ToProtoAlias<Vec<u8>> for Box<[u8]> {
+            fn to_proto_alias(&self) -> Vec<u8> {
+                self.to_vec()
+            }
+        }
+        $crate::impl_traits!(narrow i64 => i32, i16, i8);
+        $crate::impl_traits!(narrow i32 => i16, i8);
+        $crate::impl_traits!(narrow u64 => u32, u16, u8);
+        $crate::impl_traits!(narrow u32 => u16, u8);
+        $crate::impl_traits!(widen i32 => i16, i8);
+        $crate::impl_traits!(widen u32 => u16, u8);
+        $crate::impl_traits!(nonzero i32 => NonZeroI32, NonZeroU32);
+        $crate::impl_traits!(nonzero u32 => NonZeroU32);
+        $crate::impl_traits!(nonzero i64 => NonZeroI64, NonZeroU64);
+        $crate::impl_traits!(nonzero u64 => NonZeroU64);
+        $crate::impl_traits!(widen i32 => ::core::num::NonZeroI32);
+        $crate::impl_traits!(widen u32 => ::core::num::NonZeroU32);
+        $crate::impl_traits!(widen i64 => ::core::num::NonZeroI64);
+        $crate::impl_traits!(widen u64 => ::core::num::NonZeroU64);
+
+        /// Merge the masked fields of the proto value in to this value, the other fields are kept, like
+        /// for the update requests
+        pub trait MergeFromProto<P> {
+            /// Convert and assign the fields in the mask, the unknown paths are an error
+            fn merge_from_proto(
+                &mut self,
+                proto: P,
+                mask: &$crate::mask::FieldMaskSet,
+            ) -> anyhow::Result<()>;
+        }
+
+        /// The missing nested message is merged as the default one
+        impl<P: Default, T: MergeFromProto<P>> MergeFromProto<Option<P>> for T {
+            fn merge_from_proto(
+                &mut self,
+                proto: Option<P>,
+                mask: &$crate::mask::FieldMaskSet,
+            ) -> anyhow::Result<()> {
+                self.merge_from_proto(proto.unwrap_or_default(), mask)
+            }
+        }
+
+        /// Convert the proto field of a message in to the domain field, used by the derive macros. The
+        /// marker `M` selects, if the proto field is converted as it is, it is an optional field
+        /// which is required, or it is converted with `FromProtoAlias`.
+        #[diagnostic::on_unimplemented(
+            message = "the proto field of type `{Self}` can not be converted in to `{T}`",
+            label = "this field has no proto conversion",
+            note = "implement `FromProto` for the proto type, or convert the field with `#[proto(with = \"module\")]`"
+        )]
+        pub trait FromProtoField<T, M> {
+            /// Convert the field, errors get the field name in their path
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_field(self, name: &'static str) -> anyhow::Result<T>;
+        }
+
+        impl<P: FromProto> FromProtoField<P::Result, $crate::__private::field::Plain> for P {
+            fn from_proto_field(self, name: &'static str) -> anyhow::Result<P::Result> {
+                let segment = || $crate::error::PathSegment::Field(name);
+                $crate::fault::scope(segment, self, P::from_proto)
+                    .map_err(|error| $crate::error::in_field(error, name))
+            }
+        }
+
+        impl<P: FromProto> FromProtoField<P::Result, $crate::__private::field::Required>
+            for Option<P>
+        {
+            fn from_proto_field(self, name: &'static str) -> anyhow::Result<P::Result> {
+                match self.required_lite(name) {
+                    Ok(value) => {
+                        let segment = || $crate::error::PathSegment::Field(name);
+                        $crate::fault::scope(segment, value, P::from_proto)
+                            .map_err(|error| $crate::error::in_field(error, name))
+                    }
+                    // the conversions return `anyhow::Error`, so the missing field is converted once,
+                    // here where it is returned, the present fields do not allocate
+                    Err(missing) => Err($crate::trace::failed::<Self, P::Result>(missing.into())),
+                }
+            }
+        }
+
+        impl<P, T: FromProtoAlias<P>> FromProtoField<T, $crate::__private::field::Alias> for P {
+            fn from_proto_field(self, name: &'static str) -> anyhow::Result<T> {
+                let segment = || $crate::error::PathSegment::Field(name);
+                $crate::fault::scope(segment, self, T::from_proto_alias)
+                    .map_err(|error| $crate::error::in_field(error, name))
+            }
+        }
+
+        impl<P: FromProto> FromProtoField<P::Result, $crate::__private::field::Boxed> for Box<P> {
+            fn from_proto_field(self, name: &'static str) -> anyhow::Result<P::Result> {
+                FromProtoField::<_, $crate::__private::field::Plain>::from_proto_field(*self, name)
+            }
+        }
+
+        impl<P: FromProto> FromProtoField<Option<P::Result>, $crate::__private::field::Boxed>
+            for Option<Box<P>>
+        {
+            fn from_proto_field(self, name: &'static str) -> anyhow::Result<Option<P::Result>> {
+                self.map(|value| *value).from_proto_field(name)
+            }
+        }
+
+        impl<P: FromProto> FromProtoField<P::Result, $crate::__private::field::BoxedRequired>
+            for Option<Box<P>>
+        {
+            fn from_proto_field(self, name: &'static str) -> anyhow::Result<P::Result> {
+                FromProtoField::<_, $crate::__private::field::Required>::from_proto_field(
+                    self.map(|value| *value),
+                    name,
+                )
+            }
+        }
+
+        /// Convert the proto field of a message in the schema version, used by the derived
+        /// `FromProtoVersioned`, the same as `FromProtoField` with the version passed to the messages
+        #[diagnostic::on_unimplemented(
+            message = "the proto field of type `{Self}` can not be converted in to `{T}` with a version",
+            label = "this field has no versioned proto conversion",
+            note = "derive or implement `FromProtoVersioned` for the proto type"
+        )]
+        pub trait FromProtoVersionedField<T, M> {
+            /// Convert the field, errors get the field name in their path
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_versioned_field(
+                self,
+                name: &'static str,
+                version: $crate::version::SchemaVersion,
+            ) -> anyhow::Result<T>;
+        }
+
+        impl<P: FromProtoVersioned> FromProtoVersionedField<P::Result, $crate::__private::field::Plain>
+            for P
+        {
+            fn from_proto_versioned_field(
+                self,
+                name: &'static str,
+                version: $crate::version::SchemaVersion,
+            ) -> anyhow::Result<P::Result> {
+                self.from_proto_versioned(version)
+                    .map_err(|error| $crate::error::in_field(error, name))
+            }
+        }
+
+        impl<P: FromProtoVersioned>
+            FromProtoVersionedField<P::Result, $crate::__private::field::Required> for Option<P>
+        {
+            fn from_proto_versioned_field(
+                self,
+                name: &'static str,
+                version: $crate::version::SchemaVersion,
+            ) -> anyhow::Result<P::Result> {
+                match self.required_lite(name) {
+                    Ok(value) => value
+                        .from_proto_versioned(version)
+                        .map_err(|error| $crate::error::in_field(error, name)),
+                    // the conversions return `anyhow::Error`, so the missing field is converted once,
+                    // here where it is returned, the present fields do not allocate
+                    Err(missing) => Err($crate::trace::failed::<Self, P::Result>(missing.into())),
+                }
+            }
+        }
+
+        impl<P, T: FromProtoAlias<P>> FromProtoVersionedField<T, $crate::__private::field::Alias>
+            for P
+        {
+            fn from_proto_versioned_field(
+                self,
+                name: &'static str,
+                version: $crate::version::SchemaVersion,
+            ) -> anyhow::Result<T> {
+                let _ = version;
+                T::from_proto_alias(self).map_err(|error| $crate::error::in_field(error, name))
+            }
+        }
+
+        impl<P: FromProtoVersioned>
+            FromProtoVersionedField<P::Result, $crate::__private::field::Boxed> for Box<P>
+        {
+            fn from_proto_versioned_field(
+                self,
+                name: &'static str,
+                version: $crate::version::SchemaVersion,
+            ) -> anyhow::Result<P::Result> {
+                FromProtoVersionedField::<_, $crate::__private::field::Plain>::from_proto_versioned_field(
+                    *self, name, version,
+                )
+            }
+        }
+
+        impl<P: FromProtoVersioned>
+            FromProtoVersionedField<Option<P::Result>, $crate::__private::field::Boxed>
+            for Option<Box<P>>
+        {
+            fn from_proto_versioned_field(
+                self,
+                name: &'static str,
+                version: $crate::version::SchemaVersion,
+            ) -> anyhow::Result<Option<P::Result>> {
+                self.map(|value| *value)
+                    .from_proto_versioned_field(name, version)
+            }
+        }
+
+        impl<P: FromProtoVersioned>
+            FromProtoVersionedField<P::Result, $crate::__private::field::BoxedRequired>
+            for Option<Box<P>>
+        {
+            fn from_proto_versioned_field(
+                self,
+                name: &'static str,
+                version: $crate::version::SchemaVersion,
+            ) -> anyhow::Result<P::Result> {
+                FromProtoVersionedField::<_, $crate::__private::field::Required>::from_proto_versioned_field(
+                    self.map(|value| *value),
+                    name,
+                    version,
+                )
+            }
+        }
+
+        /// Convert the proto field of a message as much as possible, used by the derived `FromProtoLossy`,
+        /// the same as `FromProtoField` with the missing required fields defaulted
+        #[diagnostic::on_unimplemented(
+            message = "the proto field of type `{Self}` can not be converted in to `{T}` lossily",
+            label = "this field has no lossy proto conversion",
+            note = "derive or implement `FromProtoLossy` for the proto type, the missing required fields are defaulted, so their types implement `Default`"
+        )]
+        pub trait FromProtoLossyField<T, M> {
+            /// Convert the field, errors and the events of the report get the field name in their path
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_lossy_field(
+                self,
+                name: &'static str,
+            ) -> anyhow::Result<(T, $crate::lossy::ConversionReport)>;
+        }
+
+        impl<P: FromProtoLossy> FromProtoLossyField<P::Result, $crate::__private::field::Plain> for P {
+            fn from_proto_lossy_field(
+                self,
+                name: &'static str,
+            ) -> anyhow::Result<(P::Result, $crate::lossy::ConversionReport)> {
+                match self.from_proto_lossy() {
+                    Ok((value, report)) => Ok((value, report.in_field(name))),
+                    Err(error) => Err($crate::error::in_field(error, name)),
+                }
+            }
+        }
+
+        impl<P> FromProtoLossyField<P::Result, $crate::__private::field::Required> for Option<P>
+        where
+            P: FromProtoLossy,
+            P::Result: Default,
+        {
+            fn from_proto_lossy_field(
+                self,
+                name: &'static str,
+            ) -> anyhow::Result<(P::Result, $crate::lossy::ConversionReport)> {
+                match self {
+                    Some(value) => value.from_proto_lossy_field(name),
+                    None => {
+                        let report = $crate::lossy::ConversionReport::event(
+                            $crate::lossy::LossyEvent::MissingDefaulted,
+                        );
+                        Ok((Default::default(), report.in_field(name)))
+                    }
+                }
+            }
+        }
+
+        impl<P, T: FromProtoAlias<P>> FromProtoLossyField<T, $crate::__private::field::Alias> for P {
+            fn from_proto_lossy_field(
+                self,
+                name: &'static str,
+            ) -> anyhow::Result<(T, $crate::lossy::ConversionReport)> {
+                match T::from_proto_alias_lossy(self) {
+                    Ok((value, report)) => Ok((value, report.in_field(name))),
+                    Err(error) => Err($crate::error::in_field(error, name)),
+                }
+            }
+        }
+
+        impl<P: FromProtoLossy> FromProtoLossyField<P::Result, $crate::__private::field::Boxed>
+            for Box<P>
+        {
+            fn from_proto_lossy_field(
+                self,
+                name: &'static str,
+            ) -> anyhow::Result<(P::Result, $crate::lossy::ConversionReport)> {
+                FromProtoLossyField::<_, $crate::__private::field::Plain>::from_proto_lossy_field(
+                    *self, name,
+                )
+            }
+        }
+
+        impl<P: FromProtoLossy>
+            FromProtoLossyField<Option<P::Result>, $crate::__private::field::Boxed>
+            for Option<Box<P>>
+        {
+            fn from_proto_lossy_field(
+                self,
+                name: &'static str,
+            ) -> anyhow::Result<(Option<P::Result>, $crate::lossy::ConversionReport)> {
+                self.map(|value| *value).from_proto_lossy_field(name)
+            }
+        }
+
+        impl<P> FromProtoLossyField<P::Result, $crate::__private::field::BoxedRequired>
+            for Option<Box<P>>
+        where
+            P: FromProtoLossy,
+            P::Result: Default,
+        {
+            fn from_proto_lossy_field(
+                self,
+                name: &'static str,
+            ) -> anyhow::Result<(P::Result, $crate::lossy::ConversionReport)> {
+                FromProtoLossyField::<_, $crate::__private::field::Required>::from_proto_lossy_field(
+                    self.map(|value| *value),
+                    name,
+                )
+            }
+        }
+
+        /// Convert the domain field in to the proto field of a message, used by the derive macros. The
+        /// marker `M` selects, if the value is converted with `ToProto`, wrapped in to option for
+        /// message fields, or converted with `ToProtoAlias`.
+        #[diagnostic::on_unimplemented(
+            message = "`{Self}` can not be converted in to the proto field of type `{P}`",
+            label = "this field has no proto conversion",
+            note = "implement `ToProto` for the type, or convert the field with `#[proto(with = \"module\")]`"
+        )]
+        pub trait ToProtoField<P, M> {
+            /// Convert the field
+            fn to_proto_field(&self) -> P;
+        }
+
+        impl<T: ToProto> ToProtoField<T::Result, $crate::__private::field::Plain> for T {
+            fn to_proto_field(&self) -> T::Result {
+                self.to_proto()
+            }
+        }
+
+        impl<T: ToProto> ToProtoField<Option<T::Result>, $crate::__private::field::Required> for T {
+            fn to_proto_field(&self) -> Option<T::Result> {
+                Some(self.to_proto())
+            }
+        }
+
+        impl<P, T: ToProtoAlias<P>> ToProtoField<P, $crate::__private::field::Alias> for T {
+            fn to_proto_field(&self) -> P {
+                ToProtoAlias::to_proto_alias(self)
+            }
+        }
+
+        impl<T: ToProto> ToProtoField<Box<T::Result>, $crate::__private::field::Boxed> for T {
+            fn to_proto_field(&self) -> Box<T::Result> {
+                Box::new(self.to_proto())
+            }
+        }
+
+        impl<T: ToProto> ToProtoField<Option<Box<T::Result>>, $crate::__private::field::Boxed>
+            for Option<T>
+        {
+            fn to_proto_field(&self) -> Option<Box<T::Result>> {
+                self.as_ref().map(|value| Box::new(value.to_proto()))
+            }
+        }
+
+        impl<T: ToProto> ToProtoField<Option<Box<T::Result>>, $crate::__private::field::BoxedRequired>
+            for T
+        {
+            fn to_proto_field(&self) -> Option<Box<T::Result>> {
+                Some(Box::new(self.to_proto()))
+            }
+        }
+
+        impl<P, T: ToProtoOption<P>> ToProtoField<Option<P>, $crate::__private::field::Optional> for T {
+            fn to_proto_field(&self) -> Option<P> {
+                ToProtoOption::to_proto(self)
+            }
+        }
+
+        /// The absent elements are left out of the repeated field
+        impl<P, T: ToProtoOption<P>> ToProtoField<Vec<P>, $crate::__private::field::Optional> for Vec<T> {
+            fn to_proto_field(&self) -> Vec<P> {
+                self.to_proto_present()
+            }
+        }
+
+        $crate::__impl_tonic!();
+        $crate::__impl_tonic_stream!();
+        $crate::__impl_bytes!();
+        $crate::__impl_json!();
+        $crate::__impl_cbor!();
+        $crate::__impl_contact!();
+        $crate::__impl_prost_types!();
+        $crate::__impl_serde!();
+        $crate::__impl_roundtrip!();
+        $crate::__impl_async!();
+        $crate::__impl_arena!();
+        $crate::__impl_erased!();
+        $crate::__impl_envelope!();
+        $crate::__impl_wire!();
+        $crate::__impl_tagged_set!();
+        $crate::__impl_dry_run!();
+        $crate::__impl_etag!();
+        $crate::__impl_metrics!();
+        $crate::__impl_records!();
+        $crate::__impl_text_format!();
+        $crate::__impl_test_util!();
+
+        /// The traits of the conversions with the wrappers and the derive macros of `proto_conve`, so
+        /// `use path::to::prelude::*;` brings all of them in to scope, see `proto_conve::prelude`
+        #[allow(unused_imports)]
+        pub mod prelude {
+            pub use super::{
+                convert_from_proto, convert_to_proto, pivot_convert, pivot_convert_owned, proto_hash, proto_hash_u64, to_proto_from_iter,
+                try_to_proto_from_iter,
+                CanonicalProto, ChunkedFromProto, FromProto, FromProtoAlias, FromProtoBounded, FromProtoCached,
+                FromProtoDateRange, FromProtoInfallible, FromProtoInfallibleElements,
+                FromProtoInstant, FromProtoLossy, FromProtoMode, FromProtoSparse,
+                FromProtoVersioned, FromProtoWrapped, IntoProto, MergeFromProto, Required, ToProto,
+                ToProtoAlias, ToProtoIterator, ToProtoNonDefault, ToProtoOptionElements, ToProtoOptionFlat,
+                ToProtoOrDefault, ToProtoSorted, TryToProto, ViaProtoConvert,
+            };
+            pub use $crate::prelude::*;
+
+            $crate::__impl_tonic!(prelude);
+            $crate::__impl_tonic_stream!(prelude);
+            $crate::__impl_json!(prelude);
+            $crate::__impl_cbor!(prelude);
+            $crate::__impl_serde!(prelude);
+            $crate::__impl_roundtrip!(prelude);
+            $crate::__impl_async!(prelude);
+            $crate::__impl_arena!(prelude);
+            $crate::__impl_erased!(prelude);
+            $crate::__impl_envelope!(prelude);
+            $crate::__impl_wire!(prelude);
+            $crate::__impl_tagged_set!(prelude);
+            $crate::__impl_dry_run!(prelude);
+            $crate::__impl_etag!(prelude);
+            $crate::__impl_metrics!(prelude);
+            $crate::__impl_records!(prelude);
+            $crate::__impl_text_format!(prelude);
+            $crate::__impl_test_util!(prelude);
+        }
+    };
+    (identity $($type: ty),*) => {
+        $(
+            impl FromProtoInfallible for $type {
+                type Result = $type;
+
+                fn from_proto_infallible(self) -> Self::Result {
+                    self
+                }
+            }
+
+            $crate::impl_traits!(infallible $type);
+
+            impl ToProto for $type {
+                type Result = $type;
+
+                fn to_proto(&self) -> Self::Result {
+                    self.clone()
+                }
+            }
+
+            impl FromProtoMode for $type {}
+
+            impl FromProtoVersioned for $type {}
+
+            impl FromProtoLossy for $type {}
+        )*
+    };
+    (infallible $($type: ty),*) => {
+        $(
+            impl FromProto for $type {
+                type Result = <$type as FromProtoInfallible>::Result;
+
+                fn from_proto(self) -> anyhow::Result<Self::Result> {
+                    Ok(self.from_proto_infallible())
+                }
+            }
+        )*
+    };
+    (narrow $from: ty => $($type: ty),*) => {
+        $(
+            /// The wider proto integer does not have to fit, which is an error, or saturates in the
+            /// lenient mode
+            impl FromProtoAlias<$from> for $type {
+                fn from_proto_alias(value: $from) -> anyhow::Result<Self> {
+                    Self::from_proto_alias_mode(value, $crate::mode::ConversionMode::Strict)
+                }
+
+                fn from_proto_alias_mode(
+                    value: $from,
+                    mode: $crate::mode::ConversionMode,
+                ) -> anyhow::Result<Self> {
+                    match <$type>::try_from(value) {
+                        Ok(value) => Ok(value),
+                        Err(_) if mode.is_lenient() => {
+                            Ok(value.clamp(<$type>::MIN as $from, <$type>::MAX as $from) as $type)
+                        }
+                        Err(_) => anyhow::bail!(
+                            "The value {} does not fit in to {}",
+                            value,
+                            stringify!($type)
+                        ),
+                    }
+                }
+
+                fn from_proto_alias_lossy(
+                    value: $from,
+                ) -> anyhow::Result<(Self, $crate::lossy::ConversionReport)> {
+                    let mut report = $crate::lossy::ConversionReport::new();
+                    let value = report.clamp(value, <$type>::MIN as $from, <$type>::MAX as $from);
+                    Ok((value as $type, report))
+                }
+            }
+        )*
+    };
+    (open $($type: ty),*) => {
+        $(
+            /// The unknown values are kept, so the conversion never fails
+            impl FromProtoAlias<i32> for $crate::OpenEnum<$type> {
+                fn from_proto_alias(value: i32) -> anyhow::Result<Self> {
+                    Ok(match <$type as FromProtoAlias<i32>>::from_proto_alias(value) {
+                        Ok(known) => $crate::OpenEnum::Known(known),
+                        Err(_) => $crate::OpenEnum::Unknown(value),
+                    })
+                }
+            }
+
+            /// The unknown values are written back as they were received
+            impl ToProtoAlias<i32> for $crate::OpenEnum<$type> {
+                fn to_proto_alias(&self) -> i32 {
+                    match self {
+                        $crate::OpenEnum::Known(known) => ToProtoAlias::<i32>::to_proto_alias(known),
+                        $crate::OpenEnum::Unknown(value) => *value,
+                    }
+                }
+            }
+        )*
+    };
+    (widen $proto: ty => $($type: ty),*) => {
+        $(
+            impl ToProtoAlias<$proto> for $type {
+                fn to_proto_alias(&self) -> $proto {
+                    <$proto>::from(*self)
+                }
+            }
+        )*
+    };
+    (nonzero $proto: ty => $($type: ident),*) => {
+        $(
+            /// The zero and the values out of the range are an error, like of the ids
+            impl FromProtoAlias<$proto> for ::core::num::$type {
+                #[allow(clippy::unnecessary_fallible_conversions)]
+                fn from_proto_alias(value: $proto) -> anyhow::Result<Self> {
+                    match value.try_into().ok().and_then(::core::num::$type::new) {
+                        Some(value) => Ok(value),
+                        None => anyhow::bail!(
+                            "The value {} does not fit in to {}",
+                            value,
+                            stringify!($type)
+                        ),
+                    }
+                }
+            }
+        )*
+    };
+    ($type: ident, wrapper Vec<$item: ty>) => {
+        $crate::impl_traits!($type, wrapper items: Vec<$item>);
+    };
+    ($type: ident, wrapper $field: ident: Vec<$item: ty>) => {
+        /// The message wrapping the repeated field converts in to the elements
+        impl FromProto for $type {
+            type Result = Vec<$item>;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                self.$field
+                    .from_proto()
+                    .map_err(|error| $crate::error::in_field(error, stringify!($field)))
+            }
+        }
+
+        impl ToProtoAlias<$type> for Vec<$item> {
+            fn to_proto_alias(&self) -> $type {
+                $type {
+                    $field: self.iter().map(ToProtoField::to_proto_field).collect(),
+                }
+            }
+        }
+
+        /// The optional wrapper message of the fields
+        impl ToProtoAlias<Option<$type>> for Vec<$item> {
+            fn to_proto_alias(&self) -> Option<$type> {
+                Some(ToProtoAlias::<$type>::to_proto_alias(self))
+            }
+        }
+    };
+    ($type: ident, std::ops::Range<$item: ty>) => {
+        $crate::impl_traits!(@range $type, std::ops::Range<$item>, |min, max| min..max);
+        impl ToProto for std::ops::Range<$item> {
+            type Result = $type;
+
+            fn to_proto(&self) -> Self::Result {
+                $type {
+                    min: self.start,
+                    max: self.end,
+                }
+            }
+        }
+    };
+    ($type: ident, std::ops::RangeInclusive<chrono::NaiveDate> { $start: ident, $end: ident }) => {
+        /// Both dates are required, the start is not after the end
+        impl FromProto for $type {
+            type Result = std::ops::RangeInclusive<chrono::NaiveDate>;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                let failed = $crate::trace::failed::<Self, Self::Result>;
+                let start: chrono::NaiveDate = self
+                    .$start
+                    .from_proto_field(stringify!($start))
+                    .map_err(failed)?;
+                let end: chrono::NaiveDate =
+                    self.$end.from_proto_field(stringify!($end)).map_err(failed)?;
+                if start > end {
+                    return Err(failed($crate::date::inverted(start, end)));
+                }
+                Ok(start..=end)
+            }
+        }
+
+        impl FromProtoDateRange for $type {
+            fn from_proto_max_days(self, days: u32) -> anyhow::Result<Self::Result> {
+                let range = self.from_proto()?;
+                let (start, end) = (*range.start(), *range.end());
+                $crate::date::check_days(start, end, $crate::chrono_compat::days_between(start, end) + 1, days)
+                    .map_err($crate::trace::failed::<Self, Self::Result>)?;
+                Ok(range)
+            }
+        }
+
+        impl FromProtoVersioned for $type {}
+
+        impl FromProtoLossy for $type {}
+
+        impl ToProtoAlias<$type> for std::ops::RangeInclusive<chrono::NaiveDate> {
+            fn to_proto_alias(&self) -> $type {
+                $type {
+                    $start: ToProtoAlias::to_proto_alias(self.start()),
+                    $end: ToProtoAlias::to_proto_alias(self.end()),
+                }
+            }
+        }
+
+        impl ToProtoAlias<Option<$type>> for std::ops::RangeInclusive<chrono::NaiveDate> {
+            fn to_proto_alias(&self) -> Option<$type> {
+                Some(ToProtoAlias::<$type>::to_proto_alias(self))
+            }
+        }
+    };
+    ($type: ident, schedule::Schedule { $start: ident, $interval: ident, $count: ident }) => {
+        /// Both the start and the interval are required, the last occurrence is in the chrono range
+        impl FromProto for $type {
+            type Result = $crate::schedule::Schedule;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                let failed = $crate::trace::failed::<Self, Self::Result>;
+                let start: chrono::DateTime<chrono::Utc> = self
+                    .$start
+                    .from_proto_field(stringify!($start))
+                    .map_err(failed)?;
+                let interval: chrono::Duration = self
+                    .$interval
+                    .from_proto_field(stringify!($interval))
+                    .map_err(failed)?;
+                let repeat = $crate::schedule::Repeat::from_count(self.$count);
+                $crate::schedule::Schedule::new(start, interval, repeat).map_err(failed)
+            }
+        }
+
+        impl FromProtoVersioned for $type {}
+
+        impl FromProtoLossy for $type {}
+
+        impl ToProtoAlias<$type> for $crate::schedule::Schedule {
+            fn to_proto_alias(&self) -> $type {
+                $type {
+                    $start: ToProtoAlias::to_proto_alias(&self.start()),
+                    $interval: ToProtoAlias::to_proto_alias(&self.interval()),
+                    $count: self.repeat().count(),
+                }
+            }
+        }
+
+        impl ToProtoAlias<Option<$type>> for $crate::schedule::Schedule {
+            fn to_proto_alias(&self) -> Option<$type> {
+                Some(ToProtoAlias::<$type>::to_proto_alias(self))
+            }
+        }
+    };
+    ($type: ident, std::ops::RangeInclusive<$item: ty>) => {
+        $crate::impl_traits!(@range $type, std::ops::RangeInclusive<$item>, |min, max| min..=max);
+        impl ToProto for std::ops::RangeInclusive<$item> {
+            type Result = $type;
+
+            fn to_proto(&self) -> Self::Result {
+                $type {
+                    min: *self.start(),
+                    max: *self.end(),
+                }
+            }
+        }
+    };
+    (@range $type: ident, $range: ty, $new: expr) => {
+        impl FromProto for $type {
+            type Result = $range;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                self.from_proto_mode($crate::mode::ConversionMode::Strict)
+            }
+        }
+
+        /// The lenient mode swaps the inverted bounds
+        impl FromProtoMode for $type {
+            fn from_proto_mode(
+                self,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self::Result> {
+                let (min, max) = $crate::range::bounds(self.min, self.max, mode)
+                    .map_err($crate::trace::failed::<Self, Self::Result>)?;
+                Ok(($new)(min, max))
+            }
+        }
+
+        impl FromProtoVersioned for $type {}
+
+        impl FromProtoLossy for $type {}
+    };
+    (js chrono::DateTime<chrono::Utc>) => {
+        $crate::__impl_wasm!(chrono::DateTime<chrono::Utc>);
+    };
+    (arena $($type: ty),+) => {
+        $crate::__impl_arena!(@via $($type),+);
+    };
+    (cbor chrono::DateTime<chrono::Utc>) => {
+        $crate::__impl_cbor!(chrono::DateTime<chrono::Utc>);
+    };
+    (cbor chrono::Duration) => {
+        $crate::__impl_cbor!(chrono::Duration);
+    };
+    (remote $($remote: ident)::+ as $local: ident, chrono::DateTime<chrono::Utc>) => {
+        $crate::impl_traits!(
+            @remote $($remote)::+ as $local,
+            [chrono::DateTime<chrono::Utc>] { seconds: i64, nanos: i32 }
+        );
+    };
+    (remote $($remote: ident)::+ as $local: ident, chrono::Duration) => {
+        $crate::impl_traits!(
+            @remote $($remote)::+ as $local,
+            [chrono::Duration] { seconds: i64, nanos: i32 }
+        );
+    };
+    (remote $($remote: ident)::+ as $local: ident, chrono::NaiveDate) => {
+        $crate::impl_traits!(
+            @remote $($remote)::+ as $local,
+            [chrono::NaiveDate] { year: i32, month: i32, day: i32 }
+        );
+    };
+    // the remote proto type is copied in to the local parts, which have the conversions of the
+    // chrono type
+    (@remote $($remote: ident)::+ as $local: ident, [$($chrono: tt)+] { $($field: ident: $field_type: ty),+ }) => {
+        #[doc = concat!("The local shim of the remote proto type `", stringify!($($remote)::+), "`")]
+        #[repr(transparent)]
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct $local(pub $($remote)::+);
+
+        impl From<$($remote)::+> for $local {
+            fn from(value: $($remote)::+) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$local> for $($remote)::+ {
+            fn from(value: $local) -> Self {
+                value.0
+            }
+        }
+
+        const _: () = {
+            pub struct Parts {
+                $($field: $field_type,)+
+            }
+
+            $crate::impl_traits!(Parts, $($chrono)+);
+
+            impl FromProto for $local {
+                type Result = $($chrono)+;
+
+                fn from_proto(self) -> anyhow::Result<Self::Result> {
+                    Parts {
+                        $($field: self.0.$field as _,)+
+                    }
+                    .from_proto()
+                }
+            }
+
+            impl ToProtoAlias<$local> for $($chrono)+ {
+                fn to_proto_alias(&self) -> $local {
+                    let parts: Parts = ToProtoAlias::to_proto_alias(self);
+                    $local($($remote)::+ {
+                        $($field: parts.$field as _,)+
+                    })
+                }
+            }
+
+            impl ToProtoAlias<Option<$local>> for $($chrono)+ {
+                fn to_proto_alias(&self) -> Option<$local> {
+                    Some(ToProtoAlias::<$local>::to_proto_alias(self))
+                }
+            }
+
+            impl $local {
+                /// Convert the remote proto value
+                pub fn from_remote(value: $($remote)::+) -> anyhow::Result<$($chrono)+> {
+                    $local(value).from_proto()
+                }
+
+                /// Convert the value in to the remote proto value
+                pub fn to_remote(value: &$($chrono)+) -> $($remote)::+ {
+                    ToProtoAlias::<$local>::to_proto_alias(value).0
+                }
+            }
+        };
+    };
+    (String, chrono::Duration) => {
+        /// The duration string of the proto JSON mapping, like `"-0.5s"`
+        impl FromProtoAlias<String> for chrono::Duration {
+            fn from_proto_alias(value: String) -> anyhow::Result<Self> {
+                let (seconds, nanos) = $crate::duration_json::parse(&value)?;
+                Ok($crate::chrono_compat::seconds(seconds) + $crate::chrono_compat::nanoseconds(nanos as i64))
+            }
+        }
+
+        /// The durations out of the range of the spec are a bug of the caller, `ToProtoAlias` cannot
+        /// fail, so they panic with the debug assertions and are clamped in to the range without them
+        impl ToProtoAlias<String> for chrono::Duration {
+            fn to_proto_alias(&self) -> String {
+                let (seconds, nanos) = $crate::chrono_compat::duration_parts(self);
+                debug_assert!(
+                    $crate::duration_json::in_range(seconds, nanos),
+                    "The duration of {seconds} s is out of the range of the proto JSON durations"
+                );
+                let (seconds, nanos) = $crate::duration_json::clamp(seconds, nanos);
+                $crate::duration_json::format(seconds, nanos)
+            }
+        }
+    };
+    ($type: ident, chrono::Duration) => {
+        $crate::impl_traits!(@duration $type);
+        $crate::impl_traits!(@to_proto_alias $type, chrono::Duration, @duration_proto);
+    };
+    ($type: ident, chrono::Duration, legacy) => {
+        $crate::impl_traits!(@duration $type);
+        $crate::impl_traits!(@legacy_to_proto $type, chrono::Duration, @duration_proto);
+    };
+    ($type: ident, chrono::Duration, verify_roundtrip) => {
+        $crate::impl_traits!($type, chrono::Duration);
+        $crate::impl_traits!(@inverse $type, chrono::Duration);
+        $crate::__verify_roundtrip!(@duration $type);
+    };
+    ($type: ident, chrono::Duration { days, seconds, nanos }) => {
+        $crate::impl_traits!(@days $type);
+        $crate::impl_traits!(@to_proto_alias $type, chrono::Duration, @days_proto);
+    };
+    ($type: ident, chrono::Duration { days, seconds, nanos }, legacy) => {
+        $crate::impl_traits!(@days $type);
+        $crate::impl_traits!(@legacy_to_proto $type, chrono::Duration, @days_proto);
+    };
+    ($type: ident, chrono::DateTime<chrono::Utc>) => {
+        $crate::impl_traits!(
+            @date_time $type,
+            $crate::chrono_compat::min_unix_seconds(),
+            $crate::chrono_compat::max_unix_seconds()
+        );
+        $crate::impl_traits!(
+            @to_proto_alias $type,
+            chrono::DateTime<chrono::Utc>,
+            @date_time_proto
+        );
+    };
+    ($type: ident, chrono::DateTime<chrono::Utc>, legacy) => {
+        $crate::impl_traits!(
+            @date_time $type,
+            $crate::chrono_compat::min_unix_seconds(),
+            $crate::chrono_compat::max_unix_seconds()
+        );
+        $crate::impl_traits!(
+            @legacy_to_proto $type,
+            chrono::DateTime<chrono::Utc>,
+            @date_time_proto
+        );
+    };
+    ($type: ident, chrono::DateTime<chrono::Utc>, verify_roundtrip) => {
+        $crate::impl_traits!($type, chrono::DateTime<chrono::Utc>);
+        $crate::impl_traits!(@inverse $type, chrono::DateTime<chrono::Utc>);
+        $crate::__verify_roundtrip!(@date_time $type);
+    };
+    // both directions of the arm are between the same types, checked at the build
+    (@inverse $type: ident, $rust: ty) => {
+        const _: fn() = || {
+            fn inverse<P: FromProto<Result = T>, T: ToProtoAlias<P>>() {}
+            inverse::<$type, $rust>();
+        };
+    };
+    ($type: ident, chrono::Duration, signed_nanos) => {
+        $crate::impl_traits!(@nanos $type, signed);
+        $crate::impl_traits!($type, chrono::Duration);
+    };
+    ($type: ident, chrono::Duration, unsigned_nanos) => {
+        $crate::impl_traits!(@nanos $type, unsigned);
+        $crate::impl_traits!($type, chrono::Duration);
+    };
+    ($type: ident, chrono::Duration, floor_nanos) => {
+        $crate::impl_traits!(@nanos $type, unsigned);
+        $crate::impl_traits!(@duration $type, true);
+        $crate::impl_traits!(@to_proto_alias $type, chrono::Duration, @floor_duration_proto);
+    };
+    ($type: ident, chrono::DateTime<chrono::Utc>, signed_nanos) => {
+        $crate::impl_traits!(@nanos $type, signed);
+        $crate::impl_traits!($type, chrono::DateTime<chrono::Utc>);
+    };
+    ($type: ident, chrono::DateTime<chrono::Utc>, unsigned_nanos) => {
+        $crate::impl_traits!(@nanos $type, unsigned);
+        $crate::impl_traits!($type, chrono::DateTime<chrono::Utc>);
+    };
+    // the type of the `nanos` field is asserted at the build
+    (@nanos $type: ident, $sign: ident) => {
+        const _: fn(&$type) = |proto| $crate::__private::nanos::$sign(&proto.nanos);
+    };
+    ($type: ident, contact::PostalAddress) => {
+        $crate::__impl_contact!($type);
+    };
+    ($type: ident, chrono::NaiveDate) => {
+        /// The partial dates, of the zero year, month or day, are invalid
+        impl FromProto for $type {
+            type Result = chrono::NaiveDate;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                let (year, month, day) = (self.year as i32, self.month as i32, self.day as i32);
+                let date = u32::try_from(month).ok().zip(u32::try_from(day).ok());
+                match date.and_then(|(month, day)| $crate::chrono_compat::date(year, month, day)) {
+                    Some(date) => Ok(date),
+                    None => Err($crate::trace::failed::<Self, Self::Result>(
+                        $crate::date::invalid(year, month, day),
+                    )),
+                }
+            }
+        }
+
+        impl FromProtoVersioned for $type {}
+
+        impl FromProtoLossy for $type {}
+
+        $crate::impl_traits!(@to_proto_alias $type, chrono::NaiveDate, @naive_date_proto);
+    };
+    ($type: ident, chrono::DateTime<chrono::Utc>, spec_range) => {
+        /// The instants out of the range of the proto `Timestamp` are an error, so there is no
+        /// `ToProto`
+        impl TryToProto for chrono::DateTime<chrono::Utc> {
+            type Result = $type;
+
+            fn try_to_proto(&self) -> anyhow::Result<Self::Result> {
+                let range = $crate::timestamp::MIN_SECONDS..=$crate::timestamp::MAX_SECONDS;
+                let (seconds, nanos) = $crate::chrono_compat::unix_parts(self);
+                if !range.contains(&seconds) {
+                    return Err($crate::timestamp::out_of_range(
+                        $crate::chrono_compat::format(self),
+                    ));
+                }
+                Ok($type {
+                    seconds,
+                    nanos: nanos as _,
+                })
+            }
+        }
+
+        $crate::impl_traits!(
+            @date_time $type,
+            $crate::timestamp::MIN_SECONDS,
+            $crate::timestamp::MAX_SECONDS
+        );
+    };
+    (sentinel_none $name: ident: i64, millis = [$($sentinel: expr),+ $(,)?]) => {
+        /// The epoch millis of the optional instant, the sentinel values are `None`, converted with
+        /// `#[proto(with = "path::to::module")]`
+        #[allow(dead_code)]
+        pub mod $name {
+            const SENTINELS: &[i64] = &[$($sentinel),+];
+
+            /// The sentinel values are `None`
+            pub fn from_proto(
+                proto: i64,
+            ) -> $crate::__private::anyhow::Result<Option<chrono::DateTime<chrono::Utc>>> {
+                if SENTINELS.contains(&proto) {
+                    return Ok(None);
+                }
+                match $crate::chrono_compat::from_unix_millis(proto) {
+                    Some(value) => Ok(Some(value)),
+                    None => {
+                        $crate::__private::anyhow::bail!("Failed to parse timestamp: {proto} ms")
+                    }
+                }
+            }
+
+            /// `None` is the first sentinel value
+            pub fn to_proto(value: &Option<chrono::DateTime<chrono::Utc>>) -> i64 {
+                match value {
+                    Some(value) => $crate::chrono_compat::unix_millis(value),
+                    None => SENTINELS[0],
+                }
+            }
+        }
+    };
+    (sentinel_none $name: ident: $type: ident, seconds = [$($sentinel: expr),+ $(,)?]) => {
+        /// The optional instant, the messages of the sentinel seconds without nanos are `None`,
+        /// converted with `#[proto(with = "path::to::module")]`
+        #[allow(dead_code)]
+        pub mod $name {
+            use super::{$type, FromProto};
+
+            const SENTINELS: &[i64] = &[$($sentinel),+];
+
+            /// The sentinel messages and the missing message are `None`
+            pub fn from_proto(
+                proto: Option<$type>,
+            ) -> $crate::__private::anyhow::Result<Option<chrono::DateTime<chrono::Utc>>> {
+                match proto {
+                    Some(proto) if proto.nanos == 0 && SENTINELS.contains(&proto.seconds) => {
+                        Ok(None)
+                    }
+                    proto => proto.from_proto(),
+                }
+            }
+
+            /// `None` is the message of the first sentinel seconds
+            pub fn to_proto(value: &Option<chrono::DateTime<chrono::Utc>>) -> Option<$type> {
+                match value {
+                    Some(value) => Some($crate::impl_traits!(@date_time_proto $type, value)),
+                    None => Some($type {
+                        seconds: SENTINELS[0],
+                        nanos: 0,
+                    }),
+                }
+            }
+        }
+    };
+    // the conversions from proto of the durations in seconds and nanos, `@bounded` included
+    (@duration $type: ident) => {
+        $crate::impl_traits!(@duration $type, false);
+    };
+    // the `floor` nanos are added to the seconds rounded down, whatever the type of them
+    (@duration $type: ident, $floor: expr) => {
+        $crate::impl_traits!(@bounded $type);
+
+        impl CanonicalProto for $type {
+            fn is_canonical(&self) -> bool {
+                let parts = (self.seconds as i64, self.nanos as i64);
+                let encoding = $crate::__private::nanos::encoding(&self.nanos, $floor);
+                let nanos = $crate::__private::duration_nanos(parts.0, parts.1, encoding);
+                $crate::__private::canonical_duration(nanos, encoding) == Some(parts)
+            }
+
+            fn canonicalize(&mut self) {
+                let encoding = $crate::__private::nanos::encoding(&self.nanos, $floor);
+                let nanos =
+                    $crate::__private::duration_nanos(self.seconds as i64, self.nanos as i64, encoding);
+                if let Some((seconds, nanos)) = $crate::__private::canonical_duration(nanos, encoding) {
+                    self.seconds = seconds as _;
+                    self.nanos = nanos as _;
+                }
+            }
+        }
+
+        impl FromProto for $type {
+            type Result = chrono::Duration;
+
+            /// The durations out of the chrono range, `±i64::MAX` millis, are an error
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                let out_of_range = || {
+                    $crate::trace::failed::<Self, Self::Result>($crate::violation::coded(
+                        $crate::violation::ErrorCode::OutOfRange,
+                        format_args!(
+                            "The duration {} s and {} ns is out of range",
+                            self.seconds,
+                            self.nanos
+                        ),
+                    ))
+                };
+                let encoding = $crate::__private::nanos::encoding(&self.nanos, $floor);
+                let (seconds, nanos) =
+                    $crate::__private::nanos::offset(self.seconds, self.nanos as i64, encoding);
+                let seconds = $crate::chrono_compat::try_seconds(seconds).ok_or_else(out_of_range)?;
+                let nanos = $crate::chrono_compat::nanoseconds(nanos);
+                $crate::chrono_compat::checked_add(seconds, nanos).ok_or_else(out_of_range)
+            }
+        }
+
+        impl FromProtoVersioned for $type {}
+
+        /// The strict mode rejects the nanos out of `0..1e9`, or the signed ones out of `-1e9..1e9`, and
+        /// the seconds out of the chrono range, the lenient one clamps them
+        impl FromProtoMode for $type {
+            fn from_proto_mode(
+                self,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self::Result> {
+                const MAX_SECONDS: i64 = i64::MAX / 1000;
+                let failed = $crate::trace::failed::<Self, Self::Result>;
+                let encoding = $crate::__private::nanos::encoding(&self.nanos, $floor);
+                let signed = encoding == $crate::__private::nanos::Encoding::Signed;
+                let nanos = $crate::mode::duration_nanos(self.nanos as i64, signed, mode).map_err(failed)?;
+                let (seconds, nanos) = $crate::__private::nanos::offset(self.seconds, nanos, encoding);
+                let seconds = $crate::mode::seconds(seconds, -MAX_SECONDS, MAX_SECONDS, mode)
+                    .map_err(failed)?;
+                let seconds = $crate::chrono_compat::seconds(seconds);
+                match $crate::chrono_compat::checked_add(seconds, $crate::chrono_compat::nanoseconds(nanos)) {
+                    Some(duration) => Ok(duration),
+                    None if mode.is_lenient() => Ok(seconds),
+                    None => Err(failed($crate::violation::coded(
+                        $crate::violation::ErrorCode::OutOfRange,
+                        format_args!(
+                            "The duration {} s and {} ns is out of range",
+                            self.seconds,
+                            self.nanos
+                        ),
+                    ))),
+                }
+            }
+        }
+
+        /// The seconds out of the chrono range and the nanos out of `0..1e9`, or the signed ones out of
+        /// `-1e9..1e9`, are clamped, the nanos which do not fit any more are truncated
+        impl FromProtoLossy for $type {
+            fn from_proto_lossy(
+                self,
+            ) -> anyhow::Result<(Self::Result, $crate::lossy::ConversionReport)> {
+                const MAX_SECONDS: i64 = i64::MAX / 1000;
+                let mut report = $crate::lossy::ConversionReport::new();
+                let encoding = $crate::__private::nanos::encoding(&self.nanos, $floor);
+                let min = match encoding {
+                    $crate::__private::nanos::Encoding::Signed => -999_999_999,
+                    _ => 0,
+                };
+                let nanos = report.clamp(self.nanos as i64, min, 999_999_999);
+                let (seconds, nanos) = $crate::__private::nanos::offset(self.seconds, nanos, encoding);
+                let seconds = $crate::chrono_compat::seconds(report.clamp(seconds, -MAX_SECONDS, MAX_SECONDS));
+                let nanos = $crate::chrono_compat::nanoseconds(nanos);
+                let duration = $crate::chrono_compat::checked_add(seconds, nanos).unwrap_or_else(|| {
+                    report.record($crate::lossy::LossyEvent::TruncatedPrecision);
+                    seconds
+                });
+                Ok((duration, report))
+            }
+        }
+
+    };
+    // the conversions from proto of the durations, `@bounded` included
+    (@days $type: ident) => {
+        $crate::impl_traits!(@bounded $type);
+
+        impl FromProto for $type {
+            type Result = chrono::Duration;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                self.from_proto_mode($crate::mode::ConversionMode::Strict)
+            }
+        }
+
+        impl FromProtoVersioned for $type {}
+
+        /// The strict mode rejects the days and seconds out of the chrono range, the lenient one clamps
+        /// them
+        impl FromProtoMode for $type {
+            fn from_proto_mode(
+                self,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self::Result> {
+                const MAX_SECONDS: i64 = i64::MAX / 1000;
+                let failed = $crate::trace::failed::<Self, Self::Result>;
+                let seconds = $crate::mode::days(self.days as i64, self.seconds as i64, mode)
+                    .map_err(failed)?;
+                // the negative seconds before the positive nanos are rounded up, for the minimum
+                let (seconds, nanos) = $crate::__private::nanos::offset(
+                    seconds,
+                    self.nanos as i64,
+                    $crate::__private::nanos::Encoding::Floor,
+                );
+                let seconds = $crate::mode::seconds(seconds, -MAX_SECONDS, MAX_SECONDS, mode)
+                    .map_err(failed)?;
+                let seconds = $crate::chrono_compat::seconds(seconds);
+                match $crate::chrono_compat::checked_add(seconds, $crate::chrono_compat::nanoseconds(nanos)) {
+                    Some(duration) => Ok(duration),
+                    None if mode.is_lenient() => Ok(seconds),
+                    None => Err(failed($crate::violation::coded(
+                        $crate::violation::ErrorCode::OutOfRange,
+                        format_args!(
+                            "The duration {} days, {} s and {} ns is out of range",
+                            self.days,
+                            self.seconds,
+                            self.nanos
+                        ),
+                    ))),
+                }
+            }
+        }
+
+        /// The days and seconds out of the chrono range are clamped, the nanos which do not fit any more
+        /// are truncated
+        impl FromProtoLossy for $type {
+            fn from_proto_lossy(
+                self,
+            ) -> anyhow::Result<(Self::Result, $crate::lossy::ConversionReport)> {
+                const MAX_SECONDS: i64 = i64::MAX / 1000;
+                let mut report = $crate::lossy::ConversionReport::new();
+                let lenient = $crate::mode::ConversionMode::Lenient;
+                let seconds = $crate::mode::days(self.days as i64, self.seconds as i64, lenient)?;
+                let (seconds, nanos) = $crate::__private::nanos::offset(
+                    seconds,
+                    self.nanos as i64,
+                    $crate::__private::nanos::Encoding::Floor,
+                );
+                let seconds = $crate::chrono_compat::seconds(report.clamp(
+                    seconds,
+                    -MAX_SECONDS,
+                    MAX_SECONDS,
+                ));
+                let nanos = $crate::chrono_compat::nanoseconds(nanos);
+                let duration = $crate::chrono_compat::checked_add(seconds, nanos).unwrap_or_else(|| {
+                    report.record($crate::lossy::LossyEvent::TruncatedPrecision);
+                    seconds
+                });
+                Ok((duration, report))
+            }
+        }
+
+    };
+    // the proto value of the chrono value for `@to_proto_alias` and `@legacy_to_proto`
+    (@duration_proto $type: ident, $value: expr) => {{
+        let (seconds, nanos) = $crate::chrono_compat::duration_parts($value);
+        let nanos = seconds as i128 * 1_000_000_000 + nanos as i128;
+        // the chrono durations are in the range of the proto seconds
+        $crate::__private::nanos::canonical(nanos, false, |seconds, nanos| $type { seconds, nanos }).unwrap()
+    }};
+    (@floor_duration_proto $type: ident, $value: expr) => {{
+        let (seconds, nanos) = $crate::chrono_compat::duration_parts($value);
+        let nanos = seconds as i128 * 1_000_000_000 + nanos as i128;
+        $crate::__private::nanos::canonical(nanos, true, |seconds, nanos| $type { seconds, nanos }).unwrap()
+    }};
+    (@days_proto $type: ident, $value: expr) => {{
+        let (seconds, nanos) = $crate::chrono_compat::duration_parts($value);
+        let days = $crate::__private::split_days(seconds, nanos);
+        // `ToProtoAlias` cannot fail, the durations of more days than the `int32` days have are a bug
+        // of the caller, saturated in the release builds
+        debug_assert!(
+            days.is_some(),
+            "The duration of {seconds} s is out of the range of the proto days"
+        );
+        let (days, seconds, nanos) =
+            days.unwrap_or_else(|| $crate::__private::saturated_days(seconds));
+        $type {
+            days,
+            seconds: seconds as _,
+            nanos: nanos as _,
+        }
+    }};
+    (@date_time_proto $type: ident, $value: expr) => {{
+        let (seconds, nanos) = $crate::chrono_compat::unix_parts($value);
+        $type {
+            seconds,
+            nanos: nanos as _,
+        }
+    }};
+    (@naive_date_proto $type: ident, $value: expr) => {{
+        let (year, month, day) = $crate::chrono_compat::date_parts($value);
+        $type {
+            year: year as _,
+            month: month as _,
+            day: day as _,
+        }
+    }};
+    // `ToProtoAlias` of the chrono type, so it converts in to several proto types, also for the
+    // optional message fields
+    (@to_proto_alias $type: ident, $chrono: ty, @$proto: ident) => {
+        impl ToProtoAlias<$type> for $chrono {
+            fn to_proto_alias(&self) -> $type {
+                $crate::impl_traits!(@$proto $type, self)
+            }
+        }
+
+        impl ToProtoAlias<Option<$type>> for $chrono {
+            fn to_proto_alias(&self) -> Option<$type> {
+                Some(ToProtoAlias::<$type>::to_proto_alias(self))
+            }
+        }
+    };
+    // `ToProto` of the chrono type, there can be only one proto type converting from it
+    (@legacy_to_proto $type: ident, $chrono: ty, @$proto: ident) => {
+        const _: () = $crate::__private::legacy_to_proto();
+
+        impl ToProto for $chrono {
+            type Result = $type;
+
+            fn to_proto(&self) -> Self::Result {
+                $crate::impl_traits!(@$proto $type, self)
+            }
+        }
+    };
+    // the conversions from proto of the instants with the seconds in `min..=max`
+    (@bounded $type: ident) => {
+        impl FromProtoBounded for $type {
+            fn from_proto_bounded(self, max: chrono::Duration) -> anyhow::Result<Self::Result> {
+                let duration = self.from_proto()?;
+                if duration > max {
+                    let format = |value: chrono::Duration| {
+                        let (seconds, nanos) = $crate::chrono_compat::duration_parts(&value);
+                        $crate::duration_json::format(seconds, nanos)
+                    };
+                    return Err($crate::trace::failed::<Self, Self::Result>(anyhow::anyhow!(
+                        "The duration {} is over the maximum {}",
+                        format(duration),
+                        format(max)
+                    )));
+                }
+                Ok(duration)
+            }
+        }
+    };
+    (@date_time $type: ident, $min: expr, $max: expr) => {
+        impl FromProtoInstant for $type {
+            fn from_proto_not_before(
+                self,
+                min: chrono::DateTime<chrono::Utc>,
+            ) -> anyhow::Result<Self::Result> {
+                let value = self.from_proto()?;
+                if value < min {
+                    let format = |value: chrono::DateTime<chrono::Utc>| {
+                        $crate::chrono_compat::format(&value)
+                    };
+                    return Err($crate::trace::failed::<Self, Self::Result>(anyhow::anyhow!(
+                        "The timestamp {} is before the minimum {}",
+                        format(value),
+                        format(min)
+                    )));
+                }
+                Ok(value)
+            }
+
+            fn from_proto_not_after(
+                self,
+                max: chrono::DateTime<chrono::Utc>,
+            ) -> anyhow::Result<Self::Result> {
+                let value = self.from_proto()?;
+                if value > max {
+                    let format = |value: chrono::DateTime<chrono::Utc>| {
+                        $crate::chrono_compat::format(&value)
+                    };
+                    return Err($crate::trace::failed::<Self, Self::Result>(anyhow::anyhow!(
+                        "The timestamp {} is after the maximum {}",
+                        format(value),
+                        format(max)
+                    )));
+                }
+                Ok(value)
+            }
+        }
+
+        impl FromProto for $type {
+            type Result = chrono::DateTime<chrono::Utc>;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                let failed = $crate::trace::failed::<Self, Self::Result>;
+                match $crate::chrono_compat::from_unix(self.seconds, self.nanos as u32) {
+                    None => Err(failed(anyhow::anyhow!(
+                        "Failed to parse timestamp: {} s and {} ns",
+                        self.seconds,
+                        self.nanos
+                    ))),
+                    Some(value) if !($min..=$max).contains(&$crate::chrono_compat::unix_seconds(&value)) => {
+                        Err(failed($crate::timestamp::out_of_range(
+                            $crate::chrono_compat::format(&value),
+                        )))
+                    }
+                    Some(value) => Ok(value),
+                }
+            }
+        }
+
+        impl FromProtoVersioned for $type {}
+
+        /// The strict mode rejects the nanos out of `0..1e9` and the seconds out of the range, the
+        /// lenient one clamps them
+        impl FromProtoMode for $type {
+            fn from_proto_mode(
+                self,
+                mode: $crate::mode::ConversionMode,
+            ) -> anyhow::Result<Self::Result> {
+                let failed = $crate::trace::failed::<Self, Self::Result>;
+                let seconds = $crate::mode::seconds(
+                    self.seconds,
+                    $crate::chrono_compat::min_unix_seconds(),
+                    $crate::chrono_compat::max_unix_seconds(),
+                    mode,
+                )
+                .map_err(failed)?;
+                let seconds = if mode.is_lenient() {
+                    seconds.clamp($min, $max)
+                } else {
+                    seconds
+                };
+                let nanos = $crate::mode::nanos(self.nanos as i64, mode).map_err(failed)?;
+                match $crate::chrono_compat::from_unix(seconds, nanos) {
+                    None => Err(failed(anyhow::anyhow!(
+                        "Failed to parse timestamp: {} s and {} ns",
+                        self.seconds,
+                        self.nanos
+                    ))),
+                    Some(value) if !($min..=$max).contains(&$crate::chrono_compat::unix_seconds(&value)) => {
+                        Err(failed($crate::timestamp::out_of_range(
+                            $crate::chrono_compat::format(&value),
+                        )))
+                    }
+                    Some(value) => Ok(value),
+                }
+            }
+        }
+        /// The seconds out of the range and the nanos out of `0..1e9` are clamped
+        impl FromProtoLossy for $type {
+            fn from_proto_lossy(
+                self,
+            ) -> anyhow::Result<(Self::Result, $crate::lossy::ConversionReport)> {
+                let mut report = $crate::lossy::ConversionReport::new();
+                let seconds = report.clamp(self.seconds, $min, $max);
+                let nanos = report.clamp(self.nanos as i64, 0, 999_999_999);
+                match $crate::chrono_compat::from_unix(seconds, nanos as u32) {
+                    None => Err($crate::trace::failed::<Self, Self::Result>(anyhow::anyhow!(
+                        "Failed to parse timestamp: {} s and {} ns",
+                        self.seconds,
+                        self.nanos
+                    ))),
+                    Some(value) => Ok((value, report)),
+                }
+            }
+        }
+    };
+}
 
 #[cfg(test)]
+#[allow(dead_code)]
 mod tests {
-    use super::*;
     /// Duration specified in seconds and nanoseconds
     #[derive(Debug, PartialEq, Clone)]
     pub struct ProtoDuration {
@@ -200,39 +4247,1090 @@ mod tests {
     }
 
     impl_traits!(prelude);
-    impl_traits! (ProtoDuration, chrono::Duration);
-    impl_traits! (ProtoDateTimeUtc, chrono::DateTime<chrono::Utc>);
+    impl_traits!(ProtoDuration, chrono::Duration, verify_roundtrip);
+    impl_traits!(
+        ProtoDateTimeUtc,
+        chrono::DateTime<chrono::Utc>,
+        verify_roundtrip
+    );
 
     #[test]
     fn test_duration() {
-        let pd = ProtoDuration {
+        assert_roundtrip_from_alias(ProtoDuration {
             seconds: 1,
             nanos: 2,
-        };
-
-        let d = pd.clone().from_proto().unwrap();
-        let pd2 = d.to_proto();
-        assert_eq!(pd, pd2);
-
-        let pd = ProtoDuration {
+        });
+        assert_roundtrip_from_alias(ProtoDuration {
             seconds: -1,
             nanos: 2,
-        };
+        });
+        assert_roundtrip_to_alias::<ProtoDuration, _>(chrono::Duration::milliseconds(-1500));
+        // the sign of the negative seconds applies to the unsigned nanos, as before the canonical form
+        assert_eq!(
+            ProtoDuration {
+                seconds: -1,
+                nanos: 2
+            }
+            .from_proto()
+            .unwrap(),
+            -chrono::Duration::nanoseconds(1_000_000_002)
+        );
+        // the negative durations shorter than a second have no encoding, they lose the sign
+        assert_eq!(
+            ToProtoAlias::<ProtoDuration>::to_proto_alias(&chrono::Duration::milliseconds(-500)),
+            ProtoDuration {
+                seconds: 0,
+                nanos: 500_000_000
+            }
+        );
+    }
+
+    #[test]
+    fn test_duration_overflow() {
+        let duration = |seconds, nanos| ProtoDuration { seconds, nanos };
+        let error = duration(i64::MAX, 0).from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("The duration {} s and 0 ns is out of range", i64::MAX)
+        );
+        assert!(duration(i64::MIN, 0).from_proto().is_err());
 
-        let d = pd.clone().from_proto().unwrap();
-        let pd2 = d.to_proto();
-        assert_eq!(pd, pd2);
+        // the max of chrono is `i64::MAX` millis
+        let max = chrono::Duration::MAX;
+        let proto: ProtoDuration = max.to_proto_alias();
+        assert_eq!(proto, duration(i64::MAX / 1000, 807_000_000));
+        assert_eq!(proto.from_proto().unwrap(), max);
+        assert_eq!(
+            ToProtoAlias::<ProtoDuration>::to_proto_alias(&-max),
+            duration(-(i64::MAX / 1000), 807_000_000)
+        );
+        assert_eq!(
+            duration(-(i64::MAX / 1000), 807_000_000)
+                .from_proto()
+                .unwrap(),
+            -max
+        );
+
+        // one nanosecond past it
+        let error = duration(i64::MAX / 1000, 807_000_001)
+            .from_proto()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The duration 9223372036854775 s and 807000001 ns is out of range"
+        );
+        assert!(duration(-(i64::MAX / 1000), 807_000_001)
+            .from_proto()
+            .is_err());
+        assert!(duration(i64::MAX / 1000 + 1, 0).from_proto().is_err());
+    }
+
+    /// The duration with the `uint32` nanos added to the seconds rounded down
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct ProtoFloorDuration {
+        seconds: i64,
+        nanos: u32,
+    }
+
+    impl_traits!(ProtoFloorDuration, chrono::Duration, floor_nanos);
+
+    #[test]
+    fn test_duration_floor_nanos() {
+        let duration = |seconds, nanos| ProtoFloorDuration { seconds, nanos };
+        assert_roundtrip_to_alias::<ProtoFloorDuration, _>(chrono::Duration::milliseconds(-500));
+        assert_eq!(
+            ToProtoAlias::<ProtoFloorDuration>::to_proto_alias(&chrono::Duration::milliseconds(
+                -500
+            )),
+            duration(-1, 500_000_000)
+        );
+        assert_eq!(
+            duration(-1, 2).from_proto().unwrap(),
+            -chrono::Duration::nanoseconds(999_999_998)
+        );
+        assert_eq!(
+            duration(-1, 1_000_000_000).from_proto().unwrap(),
+            chrono::Duration::zero()
+        );
+
+        // the seconds are rounded down
+        let max = chrono::Duration::MAX;
+        let min = duration(-(i64::MAX / 1000) - 1, 193_000_000);
+        assert_eq!(
+            ToProtoAlias::<ProtoFloorDuration>::to_proto_alias(&-max),
+            min
+        );
+        assert_eq!(min.from_proto().unwrap(), -max);
+        assert!(duration(-(i64::MAX / 1000) - 1, 192_999_999)
+            .from_proto()
+            .is_err());
     }
 
     #[test]
     fn test_date_time() {
-        let pd = ProtoDateTimeUtc {
+        assert_roundtrip_from_alias(ProtoDateTimeUtc {
             seconds: -1,
             nanos: 2,
+        });
+        assert_roundtrip_to_alias::<ProtoDateTimeUtc, _>(chrono::DateTime::<chrono::Utc>::MAX_UTC);
+    }
+
+    /// The duration of another API, converting from the same `chrono::Duration` as `ProtoDuration`
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct ProtoInterval {
+        seconds: i64,
+        nanos: i32,
+    }
+
+    impl_traits!(ProtoInterval, chrono::Duration, verify_roundtrip);
+
+    #[test]
+    fn test_duration_proto_types() {
+        let duration = chrono::Duration::milliseconds(-1500);
+        let interval: ProtoInterval = ToProtoAlias::to_proto_alias(&duration);
+        assert_eq!(
+            interval,
+            ProtoInterval {
+                seconds: -1,
+                nanos: -500_000_000
+            }
+        );
+        assert_eq!(interval.from_proto().unwrap(), duration);
+        let proto: ProtoDuration = ToProtoAlias::to_proto_alias(&duration);
+        assert_eq!(proto.from_proto().unwrap(), duration);
+
+        assert_eq!(
+            ToProtoAlias::<Option<ProtoInterval>>::to_proto_alias(&Some(duration)),
+            Some(ToProtoAlias::to_proto_alias(&duration))
+        );
+        assert_eq!(
+            ToProtoAlias::<Option<ProtoInterval>>::to_proto_alias(&None::<chrono::Duration>),
+            None
+        );
+    }
+
+    /// The canonical encodings of the durations and the other encodings of them
+    fn assert_canonical<P>(encodings: Vec<(chrono::Duration, P, Vec<P>)>)
+    where
+        P: FromProto<Result = chrono::Duration>
+            + CanonicalProto
+            + Clone
+            + PartialEq
+            + std::fmt::Debug,
+        chrono::Duration: ToProtoAlias<P>,
+    {
+        for (duration, canonical, others) in encodings {
+            assert_eq!(ToProtoAlias::<P>::to_proto_alias(&duration), canonical);
+            assert!(canonical.is_canonical());
+            assert_eq!(canonical.clone().from_proto().unwrap(), duration);
+            for other in others {
+                assert!(!other.is_canonical(), "{other:?}");
+                assert_eq!(other.clone().from_proto().unwrap(), duration);
+                let mut other = other;
+                other.canonicalize();
+                assert_eq!(other, canonical);
+            }
+        }
+    }
+
+    #[test]
+    fn test_duration_canonical() {
+        let interval = |seconds, nanos| ProtoInterval { seconds, nanos };
+        assert_canonical(vec![
+            (
+                chrono::Duration::zero(),
+                interval(0, 0),
+                vec![
+                    interval(1, -1_000_000_000),
+                    interval(-1, 1_000_000_000),
+                    interval(2, -2_000_000_000),
+                    interval(-2, 2_000_000_000),
+                ],
+            ),
+            (
+                chrono::Duration::milliseconds(-1500),
+                interval(-1, -500_000_000),
+                vec![
+                    interval(-2, 500_000_000),
+                    interval(0, -1_500_000_000),
+                    interval(-3, 1_500_000_000),
+                ],
+            ),
+            (
+                chrono::Duration::milliseconds(-500),
+                interval(0, -500_000_000),
+                vec![interval(-1, 500_000_000), interval(1, -1_500_000_000)],
+            ),
+            (
+                chrono::Duration::milliseconds(1500),
+                interval(1, 500_000_000),
+                vec![interval(0, 1_500_000_000), interval(2, -500_000_000)],
+            ),
+            // the signed nanos are added to the seconds
+            (
+                chrono::Duration::seconds(-2),
+                interval(-2, 0),
+                vec![interval(-1, -1_000_000_000), interval(-3, 1_000_000_000)],
+            ),
+        ]);
+
+        // the sign of the negative seconds applies to the unsigned nanos
+        let duration = |seconds, nanos| ProtoDuration { seconds, nanos };
+        assert_canonical(vec![
+            (chrono::Duration::zero(), duration(0, 0), vec![]),
+            (
+                chrono::Duration::milliseconds(-1500),
+                duration(-1, 500_000_000),
+                vec![],
+            ),
+            (
+                chrono::Duration::seconds(-2),
+                duration(-2, 0),
+                vec![duration(-1, 1_000_000_000)],
+            ),
+            (
+                chrono::Duration::milliseconds(1500),
+                duration(1, 500_000_000),
+                vec![duration(0, 1_500_000_000)],
+            ),
+        ]);
+
+        // the floored nanos are added to the seconds
+        let floor = |seconds, nanos| ProtoFloorDuration { seconds, nanos };
+        assert_canonical(vec![
+            (
+                chrono::Duration::zero(),
+                floor(0, 0),
+                vec![floor(-1, 1_000_000_000), floor(-2, 2_000_000_000)],
+            ),
+            (
+                chrono::Duration::milliseconds(-500),
+                floor(-1, 500_000_000),
+                vec![floor(-2, 1_500_000_000)],
+            ),
+            (
+                chrono::Duration::milliseconds(-1500),
+                floor(-2, 500_000_000),
+                vec![floor(-3, 1_500_000_000)],
+            ),
+        ]);
+
+        // the seconds out of the range are kept
+        let mut proto = interval(i64::MAX, 1_500_000_000);
+        assert!(!proto.is_canonical());
+        proto.canonicalize();
+        assert_eq!(proto, interval(i64::MAX, 1_500_000_000));
+    }
+
+    #[test]
+    fn test_duration_well_known() {
+        // the canonical negative durations of `google.protobuf.Duration`
+        let well_known = |seconds, nanos| prost_types::Duration { seconds, nanos };
+        let interval = |proto: prost_types::Duration| ProtoInterval {
+            seconds: proto.seconds,
+            nanos: proto.nanos,
+        };
+        for (proto, millis) in [
+            (well_known(-1, -500_000_000), -1500),
+            (well_known(0, -500_000_000), -500),
+            (well_known(-2, 0), -2000),
+            (well_known(1, 500_000_000), 1500),
+        ] {
+            let duration = chrono::Duration::milliseconds(millis);
+            assert!(interval(proto).is_canonical());
+            assert_eq!(interval(proto).from_proto().unwrap(), duration);
+            let back: ProtoInterval = duration.to_proto_alias();
+            assert_eq!(back, interval(proto));
+        }
+    }
+
+    #[test]
+    fn test_duration_modes() {
+        use crate::mode::ConversionMode;
+
+        // the canonical durations with the negative nanos convert in every mode
+        for millis in [-1500, -500, -1, 0, 500, 1500] {
+            let duration = chrono::Duration::milliseconds(millis);
+            let proto: ProtoInterval = duration.to_proto_alias();
+            assert_eq!(proto.clone().from_proto().unwrap(), duration);
+            for mode in [ConversionMode::Strict, ConversionMode::Lenient] {
+                assert_eq!(proto.clone().from_proto_mode(mode).unwrap(), duration);
+            }
+            let (lossy, report) = proto.from_proto_lossy().unwrap();
+            assert_eq!(lossy, duration);
+            assert!(report.is_empty(), "{report:?}");
+        }
+
+        let proto = ProtoInterval {
+            seconds: -1,
+            nanos: -1_000_000_000,
+        };
+        let error = proto
+            .clone()
+            .from_proto_mode(ConversionMode::Strict)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid nanos -1000000000, they have to be in -1e9..1e9 exclusive"
+        );
+        assert_eq!(
+            proto
+                .clone()
+                .from_proto_mode(ConversionMode::Lenient)
+                .unwrap(),
+            -chrono::Duration::nanoseconds(1_999_999_999)
+        );
+        let (lossy, report) = proto.from_proto_lossy().unwrap();
+        assert_eq!(lossy, -chrono::Duration::nanoseconds(1_999_999_999));
+        assert!(!report.is_empty());
+    }
+
+    /// The proto types of a shared generated crate
+    mod shared {
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct Timestamp {
+            pub seconds: i64,
+            pub nanos: i32,
+        }
+
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct Duration {
+            pub seconds: i64,
+            pub nanos: i32,
+        }
+
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct Date {
+            pub year: i32,
+            pub month: i32,
+            pub day: i32,
+        }
+    }
+
+    impl_traits!(remote shared::Timestamp as SharedTimestamp, chrono::DateTime<chrono::Utc>);
+    impl_traits!(remote shared::Duration as SharedDuration, chrono::Duration);
+    impl_traits!(remote shared::Date as SharedDate, chrono::NaiveDate);
+
+    #[test]
+    fn test_remote() {
+        let proto = shared::Timestamp {
+            seconds: 1_700_000_000,
+            nanos: 5,
+        };
+        let value = SharedTimestamp::from_remote(proto.clone()).unwrap();
+        assert_eq!(value.timestamp(), 1_700_000_000);
+        assert_eq!(value.timestamp_subsec_nanos(), 5);
+        assert_eq!(SharedTimestamp::to_remote(&value), proto);
+        // through the shim
+        let shim = SharedTimestamp::from(proto.clone());
+        assert_eq!(shim.clone().from_proto().unwrap(), value);
+        let back: SharedTimestamp = ToProtoAlias::to_proto_alias(&value);
+        assert_eq!(shared::Timestamp::from(back), proto);
+        let error = SharedTimestamp::from_remote(shared::Timestamp {
+            seconds: 0,
+            nanos: -1,
+        })
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Failed to parse timestamp: 0 s and -1 ns"
+        );
+
+        let duration = chrono::Duration::milliseconds(-1500);
+        let proto = SharedDuration::to_remote(&duration);
+        assert_eq!(
+            proto,
+            shared::Duration {
+                seconds: -1,
+                nanos: -500_000_000
+            }
+        );
+        assert_eq!(SharedDuration::from_remote(proto).unwrap(), duration);
+        let optional: Option<SharedDuration> = ToProtoAlias::to_proto_alias(&duration);
+        assert_eq!(optional.unwrap().0.seconds, -1);
+
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let proto = SharedDate::to_remote(&date);
+        assert_eq!((proto.year, proto.month, proto.day), (2024, 2, 29));
+        assert_eq!(SharedDate::from_remote(proto).unwrap(), date);
+        assert!(SharedDate::from_remote(shared::Date {
+            year: 2023,
+            month: 2,
+            day: 29
+        })
+        .is_err());
+    }
+
+    impl_traits!(remote prost_types::Duration as WellKnownDuration, chrono::Duration);
+
+    #[test]
+    fn test_remote_well_known() {
+        let well_known = |seconds, nanos| prost_types::Duration { seconds, nanos };
+        for (proto, millis) in [
+            (well_known(-1, -500_000_000), -1500),
+            (well_known(0, -500_000_000), -500),
+            (well_known(1, 500_000_000), 1500),
+        ] {
+            let duration = chrono::Duration::milliseconds(millis);
+            assert_eq!(WellKnownDuration::from_remote(proto).unwrap(), duration);
+            assert_eq!(WellKnownDuration::to_remote(&duration), proto);
+        }
+        // the non-canonical ones are added up too
+        assert_eq!(
+            WellKnownDuration::from_remote(well_known(-2, 500_000_000)).unwrap(),
+            chrono::Duration::milliseconds(-1500)
+        );
+    }
+
+    #[allow(deprecated)]
+    mod legacy {
+        #[derive(Debug, PartialEq, Clone)]
+        pub struct ProtoTimeout {
+            seconds: i64,
+            nanos: i32,
+        }
+
+        impl_traits!(prelude);
+        impl_traits!(ProtoTimeout, chrono::Duration, legacy);
+
+        #[test]
+        fn test_legacy() {
+            let timeout = chrono::Duration::seconds(3).to_proto();
+            assert_eq!(
+                timeout,
+                ProtoTimeout {
+                    seconds: 3,
+                    nanos: 0
+                }
+            );
+            assert_roundtrip_from(timeout);
+            assert_roundtrip_to(vec![chrono::Duration::milliseconds(-1500)]);
+        }
+    }
+
+    mod days {
+        /// Duration split in to days, seconds and nanoseconds
+        #[derive(Debug, PartialEq, Clone)]
+        pub struct ProtoTimedelta {
+            days: i32,
+            seconds: i32,
+            nanos: i32,
+        }
+
+        impl_traits!(prelude);
+        impl_traits!(
+            ProtoTimedelta,
+            chrono::Duration {
+                days,
+                seconds,
+                nanos
+            }
+        );
+
+        #[test]
+        fn test_days() {
+            let timedelta = |days, seconds, nanos| ProtoTimedelta {
+                days,
+                seconds,
+                nanos,
+            };
+            let to_proto = ToProtoAlias::<ProtoTimedelta>::to_proto_alias;
+            assert_roundtrip_from_alias(timedelta(2, 3_600, 5));
+            assert_roundtrip_from_alias(timedelta(-2, 3_600, 5));
+            let days = chrono::Duration::days(3) + chrono::Duration::nanoseconds(7);
+            assert_roundtrip_to_alias::<ProtoTimedelta, _>(days);
+            assert_roundtrip_to_alias::<ProtoTimedelta, _>(-days);
+            assert_eq!(
+                to_proto(&-chrono::Duration::nanoseconds(1)),
+                timedelta(-1, 86_399, 999_999_999)
+            );
+            assert_eq!(
+                timedelta(1, -3_600, -5).from_proto().unwrap(),
+                chrono::Duration::seconds(82_800) - chrono::Duration::nanoseconds(5)
+            );
+            assert_eq!(
+                to_proto(&timedelta(1, -3_600, -5).from_proto().unwrap()),
+                timedelta(0, 82_799, 999_999_995)
+            );
+        }
+
+        #[test]
+        fn test_days_boundary() {
+            let max = chrono::Duration::days(i32::MAX as i64)
+                + chrono::Duration::seconds(86_399)
+                + chrono::Duration::nanoseconds(999_999_999);
+            let min = chrono::Duration::days(i32::MIN as i64);
+            assert_roundtrip_to_alias::<ProtoTimedelta, _>(max);
+            assert_roundtrip_to_alias::<ProtoTimedelta, _>(min);
+            assert_roundtrip_from_alias(ProtoTimedelta {
+                days: i32::MAX,
+                seconds: 86_399,
+                nanos: 999_999_999,
+            });
+            assert_roundtrip_from_alias(ProtoTimedelta {
+                days: i32::MIN,
+                seconds: 0,
+                nanos: 0,
+            });
+        }
+
+        #[test]
+        #[cfg(debug_assertions)]
+        #[should_panic(expected = "out of the range of the proto days")]
+        fn test_days_out_of_range() {
+            let min = chrono::Duration::days(i32::MIN as i64) - chrono::Duration::nanoseconds(1);
+            ToProtoAlias::<ProtoTimedelta>::to_proto_alias(&min);
+        }
+
+        #[test]
+        #[cfg(not(debug_assertions))]
+        fn test_days_saturated() {
+            let max = ToProtoAlias::<ProtoTimedelta>::to_proto_alias(&chrono::Duration::MAX);
+            assert_eq!(
+                max.from_proto().unwrap(),
+                chrono::Duration::days(i32::MAX as i64)
+                    + chrono::Duration::seconds(86_399)
+                    + chrono::Duration::nanoseconds(999_999_999)
+            );
+        }
+
+        /// The duration split in to the `int64` days
+        #[derive(Debug, PartialEq, Clone)]
+        pub struct ProtoLongTimedelta {
+            days: i64,
+            seconds: i32,
+            nanos: i32,
+        }
+
+        impl_traits!(
+            ProtoLongTimedelta,
+            chrono::Duration {
+                days,
+                seconds,
+                nanos
+            }
+        );
+
+        #[test]
+        fn test_days_long() {
+            let days = chrono::Duration::days(i32::MAX as i64 + 10) + chrono::Duration::seconds(5);
+            let proto = ToProtoAlias::<ProtoLongTimedelta>::to_proto_alias(&days);
+            assert_eq!(
+                proto,
+                ProtoLongTimedelta {
+                    days: i32::MAX as i64 + 10,
+                    seconds: 5,
+                    nanos: 0
+                }
+            );
+            assert_roundtrip_to_alias::<ProtoLongTimedelta, _>(days);
+            assert_roundtrip_to_alias::<ProtoLongTimedelta, _>(-days);
+            assert_roundtrip_to_alias::<ProtoLongTimedelta, _>(chrono::Duration::MAX);
+            assert_roundtrip_to_alias::<ProtoLongTimedelta, _>(chrono::Duration::MIN);
+        }
+    }
+
+    /// The tens, converted in to the proto value rounded down
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct Tens(u32);
+
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct ProtoTens(u32);
+
+    impl FromProto for ProtoTens {
+        type Result = Tens;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(Tens(self.0))
+        }
+    }
+
+    impl ToProto for Tens {
+        type Result = ProtoTens;
+
+        fn to_proto(&self) -> Self::Result {
+            ProtoTens(self.0 / 10 * 10)
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The value changed in the round trip\n  value: Tens(15)\n   back: Tens(10)"
+    )]
+    fn test_roundtrip_mismatch() {
+        assert_roundtrip_from(ProtoTens(20));
+        assert_roundtrip_to(Tens(15));
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_duration_parts((seconds, nanos) in crate::test_util::duration_parts()) {
+            assert_roundtrip_from_alias(ProtoDuration { seconds, nanos });
+        }
+
+        #[test]
+        fn test_date_time_parts((seconds, nanos) in crate::test_util::date_time_parts()) {
+            assert_roundtrip_from_alias(ProtoDateTimeUtc { seconds, nanos });
+        }
+    }
+
+    #[test]
+    fn test_to_proto_or_default() {
+        assert_eq!(None::<u32>.to_proto_or_default(), 0);
+        assert_eq!(Some("a".to_string()).to_proto_or_default(), "a");
+        assert_eq!(None::<u64>.to_proto_or(7), 7);
+
+        assert_eq!(None::<Tens>.to_proto_or(ProtoTens(5)), ProtoTens(5));
+        assert_eq!(Some(Tens(15)).to_proto_or(ProtoTens(5)), ProtoTens(10));
+    }
+
+    #[test]
+    fn test_to_proto_option_flat() {
+        assert_eq!(Some(Some(3u32)).to_proto(), Some(Some(3)));
+        assert_eq!(Some(Some(3u32)).to_proto_option_flat(), Some(3));
+        assert_eq!(Some(None::<u32>).to_proto_option_flat(), None);
+        assert_eq!(None::<Option<u32>>.to_proto_option_flat(), None);
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Secret(Option<u32>);
+
+    impl ToProtoOption<u64> for Secret {
+        fn to_proto(&self) -> Option<u64> {
+            self.0.map(u64::from)
+        }
+    }
+
+    #[test]
+    fn test_to_proto_option_elements() {
+        let values = [Secret(Some(1)), Secret(None), Secret(Some(3))];
+        assert_eq!(values.to_proto_present(), vec![1, 3]);
+        let error = values.try_to_proto_all().unwrap_err();
+        assert_eq!(error.to_string(), "[1]: The value has no proto value");
+        let values = [Secret(Some(1)), Secret(Some(2))];
+        assert_eq!(values.try_to_proto_all().unwrap(), vec![1, 2]);
+        assert_eq!(ToProtoField::to_proto_field(&values.to_vec()), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_to_proto_option_nested() {
+        assert_eq!(ToProtoOption::to_proto(&Some(Secret(Some(2)))), Some(2));
+        assert_eq!(ToProtoOption::to_proto(&Some(Secret(None))), None);
+        assert_eq!(ToProtoOption::to_proto(&None::<Secret>), None);
+        assert_eq!(
+            ToProtoOption::to_proto(&Some(Some(Secret(Some(4))))),
+            Some(4)
+        );
+
+        let field: Option<u64> = ToProtoField::to_proto_field(&Secret(Some(5)));
+        assert_eq!(field, Some(5));
+        let field: Option<u64> = ToProtoField::to_proto_field(&Some(Secret(None)));
+        assert_eq!(field, None);
+    }
+
+    #[test]
+    fn test_non_default() {
+        assert_eq!(0i32.to_proto_non_default(), None);
+        assert_eq!(7u64.to_proto_non_default(), Some(7));
+        assert_eq!(String::new().to_proto_non_default(), None);
+        assert_eq!(
+            "a".to_string().to_proto_non_default(),
+            Some("a".to_string())
+        );
+        assert_eq!(Vec::<u32>::new().to_proto_non_default(), None);
+        assert_eq!(vec![0u32].to_proto_non_default(), Some(vec![0]));
+
+        assert_eq!((-0.0f64).to_proto_non_default(), None);
+        assert_eq!(0.5f32.to_proto_non_default(), Some(0.5));
+        assert!(f64::NAN.to_proto_non_default().unwrap().is_nan());
+    }
+
+    #[test]
+    fn test_order() {
+        let values = vec![3u32, 1, 2];
+        assert_eq!(ToProto::to_proto(&values), [3, 1, 2]);
+        assert_eq!(values.clone().from_proto().unwrap(), [3, 1, 2]);
+        let set: std::collections::BTreeSet<u32> = values.iter().copied().collect();
+        assert_eq!(ToProto::to_proto(&set), [1, 2, 3]);
+
+        /// Has no `Ord`, the proto elements are sorted
+        struct Tagged(&'static str, u32);
+
+        impl ToProto for Tagged {
+            type Result = (u32, &'static str);
+
+            fn to_proto(&self) -> Self::Result {
+                (self.1, self.0)
+            }
+        }
+
+        let tagged = vec![
+            Tagged("a", 2),
+            Tagged("b", 1),
+            Tagged("c", 2),
+            Tagged("d", 1),
+        ];
+        // stable for the equal keys
+        assert_eq!(
+            tagged.to_proto_sorted_by_key(|(weight, _)| *weight),
+            [(1, "b"), (1, "d"), (2, "a"), (2, "c")]
+        );
+        assert_eq!(tagged[0].0, "a");
+        let set: std::collections::HashSet<u32> = values.iter().copied().collect();
+        assert_eq!(set.to_proto_sorted_by_key(|value| *value), [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_to_proto_option_if() {
+        /// Counts its conversions
+        struct Counted(u32, std::cell::Cell<usize>);
+
+        impl ToProto for Counted {
+            type Result = u32;
+
+            fn to_proto(&self) -> Self::Result {
+                self.1.set(self.1.get() + 1);
+                self.0
+            }
+        }
+
+        let value = Counted(0, Default::default());
+        assert_eq!(value.to_proto_option_if(|value| value.0 == 0), None);
+        assert_eq!(value.1.get(), 0);
+        let value = Counted(7, Default::default());
+        assert_eq!(value.to_proto_option_if(|value| value.0 == 0), Some(7));
+        assert_eq!(value.1.get(), 1);
+
+        assert_eq!(
+            ToProto::to_proto(&crate::OmitIf::new(&value, |value: &Counted| value.0 == 7)),
+            None
+        );
+        assert_eq!(
+            ToProto::to_proto(&crate::OmitIf::new(&value, |value: &Counted| value.0 == 0)),
+            Some(7)
+        );
+        assert_eq!(value.1.get(), 2);
+
+        // the nested option is omitted by the value in it, and it is `None` without it
+        let omit = |value: &Option<Counted>| value.as_ref().is_some_and(|value| value.0 == 0);
+        let values = [None, Some(Counted(0, Default::default())), Some(value)];
+        let proto: Vec<_> = values
+            .iter()
+            .map(|value| value.to_proto_option_if(omit).flatten())
+            .collect();
+        assert_eq!(proto, [None, None, Some(7)]);
+        assert_eq!(values[1].as_ref().unwrap().1.get(), 0);
+
+        let omit = |value: &&str| value.is_empty();
+        assert_eq!("a".to_proto_alias_option_if(omit), Some("a".to_owned()));
+        assert_eq!("".to_proto_alias_option_if(omit), None::<String>);
+    }
+
+    #[test]
+    fn test_nonzero() {
+        use std::num::{NonZeroI64, NonZeroU32};
+
+        let id = NonZeroU32::from_proto_alias(7i32).unwrap();
+        assert_eq!(id.get(), 7);
+        let error = NonZeroU32::from_proto_alias(0i32).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The value 0 does not fit in to NonZeroU32"
+        );
+        assert!(NonZeroU32::from_proto_alias(-1i32).is_err());
+        assert_eq!(ToProtoAlias::<u32>::to_proto_alias(&id), 7);
+
+        // the proto3 `optional` fields keep the presence
+        let ids = Option::<NonZeroU32>::from_proto_alias(Some(3i32)).unwrap();
+        assert_eq!(ids, NonZeroU32::new(3));
+        assert_eq!(
+            Option::<NonZeroU32>::from_proto_alias(None::<i32>).unwrap(),
+            None
+        );
+        assert!(Option::<NonZeroU32>::from_proto_alias(Some(0i32)).is_err());
+        let offset = NonZeroI64::new(-5);
+        assert_eq!(
+            ToProtoAlias::<Option<i64>>::to_proto_alias(&offset),
+            Some(-5)
+        );
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoUserId {
+        value: u64,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct UserId(u64);
+
+    impl FromProtoInfallible for ProtoUserId {
+        type Result = UserId;
+
+        fn from_proto_infallible(self) -> Self::Result {
+            UserId(self.value)
+        }
+    }
+
+    impl_traits!(infallible ProtoUserId);
+
+    // the infallible conversions can be used in the `From` impls
+    impl From<ProtoUserId> for UserId {
+        fn from(proto: ProtoUserId) -> Self {
+            proto.from_proto_infallible()
+        }
+    }
+
+    #[test]
+    fn test_infallible() {
+        let values: Vec<i64> = vec![1i64, -2, 3].from_proto_infallible();
+        assert_eq!(values, [1, -2, 3]);
+        assert_eq!(vec![1i64].from_proto().unwrap(), [1]);
+        assert_eq!(Some(true).from_proto_infallible(), Some(true));
+
+        assert_eq!(UserId::from(ProtoUserId { value: 7 }), UserId(7));
+        let ids = vec![ProtoUserId { value: 1 }].from_proto().unwrap();
+        assert_eq!(ids, [UserId(1)]);
+    }
+
+    impl ToProto for UserId {
+        type Result = ProtoUserId;
+
+        fn to_proto(&self) -> Self::Result {
+            ProtoUserId { value: self.0 }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoUserIds {
+        items: Vec<ProtoUserId>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoDeadlines {
+        values: Vec<ProtoDateTimeUtc>,
+    }
+
+    impl_traits!(ProtoUserIds, wrapper Vec<UserId>);
+    impl_traits!(ProtoDeadlines, wrapper values: Vec<chrono::DateTime<chrono::Utc>>);
+
+    #[test]
+    fn test_wrapper() {
+        let proto = ProtoUserIds {
+            items: vec![ProtoUserId { value: 1 }, ProtoUserId { value: 2 }],
         };
+        let ids: Vec<UserId> = proto.clone().from_proto().unwrap();
+        assert_eq!(ids, [UserId(1), UserId(2)]);
+        assert_eq!(ToProtoAlias::<ProtoUserIds>::to_proto_alias(&ids), proto);
+        assert_eq!(
+            ToProtoAlias::<Option<ProtoUserIds>>::to_proto_alias(&ids),
+            Some(proto)
+        );
+
+        let proto = ProtoDeadlines {
+            values: vec![ProtoDateTimeUtc {
+                seconds: i64::MAX,
+                nanos: 0,
+            }],
+        };
+        let error = proto.from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "values[0]: Failed to parse timestamp: {} s and 0 ns",
+                i64::MAX
+            )
+        );
+    }
+
+    fn sparse() -> Vec<Option<ProtoUserId>> {
+        let id = |value| Some(ProtoUserId { value });
+        vec![id(0), None, id(2), id(3), None]
+    }
+
+    #[test]
+    fn test_sparse_preserved() {
+        let ids = sparse().from_proto().unwrap();
+        assert_eq!(
+            ids,
+            [
+                Some(UserId(0)),
+                None,
+                Some(UserId(2)),
+                Some(UserId(3)),
+                None
+            ]
+        );
+        assert_eq!(ids.to_proto(), sparse());
+    }
+
+    #[test]
+    fn test_sparse_dense() {
+        let error = sparse().from_proto_dense().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The elements at the indices [1, 4] are missing"
+        );
+        let dense: Vec<_> = sparse().into_iter().flatten().map(Some).collect();
+        assert_eq!(
+            dense.from_proto_dense().unwrap(),
+            [UserId(0), UserId(2), UserId(3)]
+        );
+    }
+
+    impl_traits!(sentinel_none deadline: ProtoDateTimeUtc, seconds = [0, i64::MAX]);
+    impl_traits!(sentinel_none expires: i64, millis = [i64::MAX, 0]);
+
+    #[test]
+    fn test_sentinel_seconds() {
+        let proto = |seconds, nanos| Some(ProtoDateTimeUtc { seconds, nanos });
+        assert_eq!(deadline::from_proto(proto(0, 0)).unwrap(), None);
+        assert_eq!(deadline::from_proto(proto(i64::MAX, 0)).unwrap(), None);
+        assert_eq!(deadline::from_proto(None).unwrap(), None);
+        // only the sentinel itself is `None`
+        let close = deadline::from_proto(proto(0, 1)).unwrap();
+        assert_eq!(close, chrono::DateTime::from_timestamp(0, 1));
+        assert!(deadline::from_proto(proto(i64::MAX, 1)).is_err());
+
+        assert_eq!(deadline::to_proto(&None), proto(0, 0));
+        assert_eq!(deadline::to_proto(&close), proto(0, 1));
+    }
+
+    #[test]
+    fn test_sentinel_millis() {
+        assert_eq!(expires::from_proto(i64::MAX).unwrap(), None);
+        assert_eq!(expires::from_proto(0).unwrap(), None);
+        let value = expires::from_proto(1).unwrap();
+        assert_eq!(value, chrono::DateTime::from_timestamp_millis(1));
+        assert_eq!(expires::to_proto(&value), 1);
+        assert_eq!(expires::to_proto(&None), i64::MAX);
+        assert!(expires::from_proto(i64::MAX - 1).is_err());
+    }
+
+    #[test]
+    fn test_bounded() {
+        let day = chrono::Duration::days(1);
+        let proto = |seconds| ProtoDuration { seconds, nanos: 0 };
+        let within = proto(3600).from_proto_bounded(day).unwrap();
+        assert_eq!(within, chrono::Duration::hours(1));
+        assert_eq!(proto(86_400).from_proto_bounded(day).unwrap(), day);
+        let error = ProtoDuration {
+            seconds: 86_400,
+            nanos: 1,
+        }
+        .from_proto_bounded(day)
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The duration 86400.000000001s is over the maximum 86400s"
+        );
+    }
+
+    #[test]
+    fn test_not_before() {
+        let min = chrono::DateTime::from_timestamp(1_577_836_800, 0).unwrap();
+        let proto = |seconds| ProtoDateTimeUtc { seconds, nanos: 0 };
+        assert_eq!(
+            proto(1_577_836_800).from_proto_not_before(min).unwrap(),
+            min
+        );
+        let error = proto(1_577_836_799).from_proto_not_before(min).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The timestamp 2019-12-31T23:59:59Z is before the minimum 2020-01-01T00:00:00Z"
+        );
+        let error = proto(1_577_836_801).from_proto_not_after(min).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The timestamp 2020-01-01T00:00:01Z is after the maximum 2020-01-01T00:00:00Z"
+        );
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Limit(u32);
+
+    /// The limits over the maximum are not sent
+    impl TryToProto for Limit {
+        type Result = i64;
+
+        fn try_to_proto(&self) -> anyhow::Result<Self::Result> {
+            anyhow::ensure!(self.0 <= 100, "The limit {} is over 100", self.0);
+            Ok(self.0.into())
+        }
+    }
+
+    #[test]
+    fn test_to_proto_from_iter() {
+        let users: std::collections::HashMap<&str, UserId> =
+            [("ann", UserId(1)), ("bob", UserId(0)), ("eve", UserId(3))].into();
+        let mut ids = users.values().filter(|id| id.0 > 0).to_proto_vec();
+        ids.sort_by_key(|id| id.value);
+        assert_eq!(ids, [ProtoUserId { value: 1 }, ProtoUserId { value: 3 }]);
+
+        let ids = to_proto_from_iter(users.values());
+        assert_eq!(ids.capacity(), 3);
+
+        let limits = [Limit(10), Limit(20)];
+        assert_eq!(limits.iter().try_to_proto_vec().unwrap(), [10, 20]);
+    }
 
-        let d = pd.clone().from_proto().unwrap();
-        let pd2 = d.to_proto();
-        assert_eq!(pd, pd2);
+    #[test]
+    fn test_try_to_proto_from_iter() {
+        let limits = [Limit(10), Limit(20), Limit(200), Limit(300)];
+        let error = try_to_proto_from_iter(&limits).unwrap_err();
+        assert_eq!(error.to_string(), "[2]: The limit 200 is over 100");
+        let error = limits.iter().skip(3).try_to_proto_vec().unwrap_err();
+        assert_eq!(error.to_string(), "[0]: The limit 300 is over 100");
+    }
+
+    #[test]
+    fn test_borrowed_strings() {
+        let value = ToProtoAlias::<String>::to_proto_alias(&"eve");
+        assert_eq!(value, "eve");
+        // the exact buffer is allocated once
+        assert_eq!(value.capacity(), 3);
+        let cow: std::borrow::Cow<'_, str> = "bob".into();
+        assert_eq!(ToProtoAlias::<String>::to_proto_alias(&cow), "bob");
+
+        let boxed = Box::<str>::from_proto_alias("ann".to_owned()).unwrap();
+        assert_eq!(&*boxed, "ann");
+        let shared = std::rc::Rc::<str>::from_proto_alias("joe".to_owned()).unwrap();
+        assert_eq!(ToProtoAlias::<String>::to_proto_alias(&shared), "joe");
+    }
+
+    #[test]
+    fn test_borrowed_values() {
+        let ids = [UserId(1), UserId(2)];
+        let proto = |value| ProtoUserId { value };
+
+        let first: Option<&UserId> = ids.first();
+        assert_eq!(first.to_proto(), Some(proto(1)));
+        let owned = Some(UserId(3));
+        let borrowed: &Option<UserId> = &owned;
+        assert_eq!(borrowed.to_proto(), Some(proto(3)));
+        let mut last = UserId(2);
+        let edited: Option<&mut UserId> = Some(&mut last);
+        assert_eq!(edited.to_proto(), Some(proto(2)));
+
+        let picked: Vec<&UserId> = ids.iter().rev().collect();
+        assert_eq!(picked.to_proto(), [proto(2), proto(1)]);
+        assert_eq!(picked.as_slice().to_proto(), [proto(2), proto(1)]);
+        assert_eq!(ids[..1].to_proto(), [proto(1)]);
+
+        // without the `as_ref`, the `map` and the `iter` of each level
+        let nested: Option<Vec<&UserId>> = Some(ids.iter().collect());
+        let nested: &Option<Vec<&UserId>> = &nested;
+        let result: Option<Vec<ProtoUserId>> = nested.to_proto();
+        assert_eq!(result, Some(vec![proto(1), proto(2)]));
+        let missing: &Option<Vec<&UserId>> = &None;
+        assert_eq!(missing.to_proto(), None);
+    }
+
+    /// The derived impls of `HasProtoMapping` are left out without the `reflect` feature
+    #[cfg(not(feature = "reflect"))]
+    #[test]
+    fn test_reflect_disabled() {
+        crate::__reflect! {
+            compile_error!("the impls of the `reflect` feature are compiled without it");
+        }
     }
-}
\ No newline at end of file
+}
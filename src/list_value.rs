@@ -0,0 +1,482 @@
+//! The `google.protobuf.ListValue` and `NullValue` fields of `prost-types`, converted with the
+//! `prost-types` feature. The lists of any values convert in to `Vec<serde_json::Value>` with
+//! `FromProto`, and the lists of the values of one kind in to `Vec<f64>`, `Vec<String>` or `Vec<bool>`
+//! with `FromProtoAlias<ListValue>`, the other kinds are an error naming the element, like `The element
+//! 2 was a string, expected number`. The lists of the other types are converted through the
+//! `ListElement` implemented for them.
+//!
+//! The `NullValue`, used as a marker by some APIs, converts in to `()`, and so `Option<NullValue>` in to
+//! `Option<()>`. The raw `i32` of the prost enum field is `ProtoEnumField<NullValue>`, see
+//! `enumeration`.
+//!
+//! The `Value`, like the values of the `map<string, google.protobuf.Value>` fields of the JSON
+//! transcoded maps, converts in to `serde_json::Value` with `FromProtoAlias`, where the explicit null
+//! and the empty value collapse in to `serde_json::Value::Null`. The maps distinguishing the keys with
+//! the explicit null convert with `nullable_values` in to `HashMap<String, Option<serde_json::Value>>`,
+//! the null is `Some(serde_json::Value::Null)`, the empty value is `None` and the absent keys are
+//! missing, the derived conversions use it for the map fields with `#[proto(nullable_values)]`.
+
+use prost_types::value::Kind;
+use prost_types::{ListValue, Struct, Value};
+
+/// The values of a kind of the proto `Value`, which are the elements of the homogeneous lists
+pub trait ListElement: Sized {
+    /// The name of the kind, like `number`
+    const KIND: &'static str;
+
+    /// The element of the `kind`, or the `kind` back, when it is another one
+    fn from_kind(kind: Kind) -> Result<Self, Kind>;
+
+    /// The proto value of the element
+    fn to_value(&self) -> Value;
+}
+
+impl ListElement for f64 {
+    const KIND: &'static str = "number";
+
+    fn from_kind(kind: Kind) -> Result<Self, Kind> {
+        match kind {
+            Kind::NumberValue(value) => Ok(value),
+            kind => Err(kind),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        value(Kind::NumberValue(*self))
+    }
+}
+
+impl ListElement for String {
+    const KIND: &'static str = "string";
+
+    fn from_kind(kind: Kind) -> Result<Self, Kind> {
+        match kind {
+            Kind::StringValue(value) => Ok(value),
+            kind => Err(kind),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        value(Kind::StringValue(self.clone()))
+    }
+}
+
+impl ListElement for bool {
+    const KIND: &'static str = "bool";
+
+    fn from_kind(kind: Kind) -> Result<Self, Kind> {
+        match kind {
+            Kind::BoolValue(value) => Ok(value),
+            kind => Err(kind),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        value(Kind::BoolValue(*self))
+    }
+}
+
+/// The name of the kind of the value, as it is in the errors
+pub fn kind_name(kind: Option<&Kind>) -> &'static str {
+    match kind {
+        None => "empty",
+        Some(Kind::NullValue(_)) => "null",
+        Some(Kind::NumberValue(_)) => "a number",
+        Some(Kind::StringValue(_)) => "a string",
+        Some(Kind::BoolValue(_)) => "a bool",
+        Some(Kind::StructValue(_)) => "a struct",
+        Some(Kind::ListValue(_)) => "a list",
+    }
+}
+
+/// The elements of the list, which are all of the kind of `T`
+pub fn from_list<T: ListElement>(list: ListValue) -> anyhow::Result<Vec<T>> {
+    list.values
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| {
+            if crate::budget::is_limited() {
+                charge(&value).map_err(|error| crate::error::at_index(error, index))?;
+            }
+            match value.kind {
+                Some(kind) => T::from_kind(kind).map_err(|kind| mismatch::<T>(index, Some(&kind))),
+                // the empty value is null for the lists of any values
+                None => T::from_kind(Kind::NullValue(0)).map_err(|_| mismatch::<T>(index, None)),
+            }
+        })
+        .collect()
+}
+
+/// Count the `value` with its nested values in to the budget of the conversion, see `budget`
+fn charge(value: &Value) -> anyhow::Result<()> {
+    crate::budget::nested(true, || match &value.kind {
+        Some(Kind::StringValue(value)) => crate::budget::string(value.len()),
+        Some(Kind::StructValue(value)) => value.fields.iter().try_for_each(|(key, value)| {
+            charge(value).map_err(|error| crate::error::at_key(error, key))
+        }),
+        Some(Kind::ListValue(list)) => {
+            list.values
+                .iter()
+                .enumerate()
+                .try_for_each(|(index, value)| {
+                    charge(value).map_err(|error| crate::error::at_index(error, index))
+                })
+        }
+        _ => Ok(()),
+    })
+}
+
+/// Any value, the null and the empty value are `serde_json::Value::Null`
+pub fn from_value(value: Value) -> anyhow::Result<serde_json::Value> {
+    if crate::budget::is_limited() {
+        charge(&value)?;
+    }
+    Ok(kind_to_json(value.kind))
+}
+
+/// The list of the elements
+pub fn to_list<T: ListElement>(values: &[T]) -> ListValue {
+    ListValue {
+        values: values.iter().map(T::to_value).collect(),
+    }
+}
+
+fn mismatch<T: ListElement>(index: usize, kind: Option<&Kind>) -> anyhow::Error {
+    anyhow::anyhow!(
+        "The element {index} was {}, expected {}",
+        kind_name(kind),
+        T::KIND
+    )
+}
+
+/// Any value, the numbers, which are not finite, are `null`, as they are in `serde_json`
+impl ListElement for serde_json::Value {
+    const KIND: &'static str = "any";
+
+    fn from_kind(kind: Kind) -> Result<Self, Kind> {
+        Ok(kind_to_json(Some(kind)))
+    }
+
+    fn to_value(&self) -> Value {
+        json_to_value(self)
+    }
+}
+
+fn kind_to_json(kind: Option<Kind>) -> serde_json::Value {
+    match kind {
+        None | Some(Kind::NullValue(_)) => serde_json::Value::Null,
+        Some(Kind::NumberValue(number)) => number.into(),
+        Some(Kind::StringValue(value)) => serde_json::Value::String(value),
+        Some(Kind::BoolValue(value)) => serde_json::Value::Bool(value),
+        Some(Kind::StructValue(value)) => serde_json::Value::Object(
+            value
+                .fields
+                .into_iter()
+                .map(|(key, value)| (key, kind_to_json(value.kind)))
+                .collect(),
+        ),
+        Some(Kind::ListValue(list)) => serde_json::Value::Array(
+            list.values
+                .into_iter()
+                .map(|value| kind_to_json(value.kind))
+                .collect(),
+        ),
+    }
+}
+
+fn json_to_value(json: &serde_json::Value) -> Value {
+    value(match json {
+        serde_json::Value::Null => Kind::NullValue(prost_types::NullValue::NullValue.into()),
+        // the big integers are rounded, as the proto numbers are `double`
+        serde_json::Value::Number(number) => Kind::NumberValue(number.as_f64().unwrap_or_default()),
+        serde_json::Value::String(value) => Kind::StringValue(value.clone()),
+        serde_json::Value::Bool(value) => Kind::BoolValue(*value),
+        serde_json::Value::Array(values) => Kind::ListValue(to_list(values)),
+        serde_json::Value::Object(fields) => Kind::StructValue(Struct {
+            fields: fields
+                .iter()
+                .map(|(key, value)| (key.clone(), json_to_value(value)))
+                .collect(),
+        }),
+    })
+}
+
+/// The values of the maps, which are converted with their explicit nulls
+pub mod nullable_values {
+    use prost_types::value::Kind;
+    use prost_types::Value;
+
+    /// The values of the map, the explicit null is `Some(serde_json::Value::Null)` and the empty value
+    /// is `None`
+    pub fn from_proto<M>(map: impl IntoIterator<Item = (String, Value)>) -> anyhow::Result<M>
+    where
+        M: FromIterator<(String, Option<serde_json::Value>)>,
+    {
+        map.into_iter()
+            .map(|(key, value)| {
+                if crate::budget::is_limited() {
+                    crate::budget::element()
+                        .and_then(|()| super::charge(&value))
+                        .map_err(|error| crate::error::at_key(error, &key))?;
+                }
+                let value = match value.kind {
+                    None => None,
+                    Some(Kind::NullValue(_)) => Some(serde_json::Value::Null),
+                    kind => Some(super::kind_to_json(kind)),
+                };
+                Ok((key, value))
+            })
+            .collect()
+    }
+
+    /// The proto values of the map, `None` is the empty value
+    pub fn to_proto<'a, M>(
+        map: impl IntoIterator<Item = (&'a String, &'a Option<serde_json::Value>)>,
+    ) -> M
+    where
+        M: FromIterator<(String, Value)>,
+    {
+        map.into_iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    Some(value) => super::json_to_value(value),
+                    None => Value { kind: None },
+                };
+                (key.clone(), value)
+            })
+            .collect()
+    }
+}
+
+fn value(kind: Kind) -> Value {
+    Value { kind: Some(kind) }
+}
+
+/// Generate the `ListValue` and `NullValue` conversions, this is part of the `impl_traits!(prelude)`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_prost_types {
+    () => {
+        /// The list of any values
+        impl FromProto for $crate::__private::prost_types::ListValue {
+            type Result = Vec<$crate::__private::serde_json::Value>;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                $crate::list_value::from_list(self)
+            }
+        }
+
+        /// The list of the values of the kind of `T`
+        impl<T: $crate::list_value::ListElement>
+            FromProtoAlias<$crate::__private::prost_types::ListValue> for Vec<T>
+        {
+            fn from_proto_alias(
+                value: $crate::__private::prost_types::ListValue,
+            ) -> anyhow::Result<Self> {
+                $crate::list_value::from_list(value)
+            }
+        }
+
+        impl<T: $crate::list_value::ListElement>
+            ToProtoAlias<$crate::__private::prost_types::ListValue> for Vec<T>
+        {
+            fn to_proto_alias(&self) -> $crate::__private::prost_types::ListValue {
+                $crate::list_value::to_list(self)
+            }
+        }
+
+        /// Any value, the null and the empty value are `serde_json::Value::Null`
+        impl FromProtoAlias<$crate::__private::prost_types::Value>
+            for $crate::__private::serde_json::Value
+        {
+            fn from_proto_alias(
+                value: $crate::__private::prost_types::Value,
+            ) -> anyhow::Result<Self> {
+                $crate::list_value::from_value(value)
+            }
+        }
+
+        impl ToProtoAlias<$crate::__private::prost_types::Value>
+            for $crate::__private::serde_json::Value
+        {
+            fn to_proto_alias(&self) -> $crate::__private::prost_types::Value {
+                $crate::list_value::ListElement::to_value(self)
+            }
+        }
+
+        /// The marker of the null value
+        impl FromProto for $crate::__private::prost_types::NullValue {
+            type Result = ();
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                Ok(())
+            }
+        }
+
+        impl ToProtoAlias<$crate::__private::prost_types::NullValue> for () {
+            fn to_proto_alias(&self) -> $crate::__private::prost_types::NullValue {
+                $crate::__private::prost_types::NullValue::NullValue
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use prost_types::value::Kind;
+    use prost_types::{ListValue, NullValue, Value};
+
+    use super::nullable_values;
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    fn list(kinds: Vec<Kind>) -> ListValue {
+        ListValue {
+            values: kinds
+                .into_iter()
+                .map(|kind| Value { kind: Some(kind) })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_numbers() {
+        let proto = list(vec![Kind::NumberValue(1.5), Kind::NumberValue(-2.0)]);
+        let numbers: Vec<f64> = FromProtoAlias::from_proto_alias(proto.clone()).unwrap();
+        assert_eq!(numbers, [1.5, -2.0]);
+        assert_eq!(ToProtoAlias::<ListValue>::to_proto_alias(&numbers), proto);
+    }
+
+    #[test]
+    fn test_mixed() {
+        let proto = list(vec![
+            Kind::NumberValue(1.0),
+            Kind::NumberValue(2.0),
+            Kind::StringValue("3".to_owned()),
+        ]);
+        let error = <Vec<f64> as FromProtoAlias<_>>::from_proto_alias(proto.clone()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The element 2 was a string, expected number"
+        );
+        let error = <Vec<bool> as FromProtoAlias<_>>::from_proto_alias(ListValue {
+            values: vec![Value::default()],
+        })
+        .unwrap_err();
+        assert_eq!(error.to_string(), "The element 0 was empty, expected bool");
+
+        // any values are converted
+        let values = proto.clone().from_proto().unwrap();
+        assert_eq!(
+            values,
+            [
+                serde_json::json!(1.0),
+                serde_json::json!(2.0),
+                serde_json::json!("3")
+            ]
+        );
+        assert_eq!(ToProtoAlias::<ListValue>::to_proto_alias(&values), proto);
+
+        let values = list(vec![Kind::NumberValue(f64::NAN)])
+            .from_proto()
+            .unwrap();
+        assert_eq!(values, [serde_json::Value::Null]);
+    }
+
+    #[test]
+    fn test_null() {
+        assert_eq!(NullValue::NullValue.from_proto().unwrap(), ());
+        assert_eq!(Some(NullValue::NullValue).from_proto().unwrap(), Some(()));
+        assert_eq!(None::<NullValue>.from_proto().unwrap(), None);
+        assert_eq!(
+            ToProtoAlias::<NullValue>::to_proto_alias(&()),
+            NullValue::NullValue
+        );
+        assert_eq!(
+            ToProtoAlias::<Option<NullValue>>::to_proto_alias(&None::<()>),
+            None
+        );
+    }
+
+    #[test]
+    fn test_budget() {
+        use crate::budget::{limit, ConversionBudget};
+
+        let nested = list(vec![Kind::StringValue("a".repeat(10))]);
+        let proto = list(vec![Kind::NumberValue(1.0), Kind::ListValue(nested)]);
+        let budget = ConversionBudget::unlimited().max_string_bytes(8);
+        let error = limit(budget, || proto.clone().from_proto()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "[1][0]: The string exceeded the budget of 8 bytes"
+        );
+        let budget = ConversionBudget::unlimited().max_elements(2);
+        let error = limit(budget, || proto.clone().from_proto()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "[1][0]: The conversion exceeded the budget of 2 elements"
+        );
+        let budget = ConversionBudget::unlimited().max_depth(1);
+        assert!(limit(budget, || proto.clone().from_proto()).is_err());
+        let budget = ConversionBudget::unlimited().max_depth(2);
+        assert_eq!(limit(budget, || proto.from_proto()).unwrap().len(), 2);
+    }
+
+    fn settings() -> HashMap<String, Value> {
+        HashMap::from([
+            (
+                "theme".to_owned(),
+                Value {
+                    kind: Some(Kind::StringValue("dark".to_owned())),
+                },
+            ),
+            (
+                "locale".to_owned(),
+                Value {
+                    kind: Some(Kind::NullValue(0)),
+                },
+            ),
+            ("timezone".to_owned(), Value { kind: None }),
+        ])
+    }
+
+    #[test]
+    fn test_map_values() {
+        // the null and the empty value collapse
+        let values: HashMap<String, serde_json::Value> =
+            FromProtoAlias::from_proto_alias(settings()).unwrap();
+        assert_eq!(values.len(), 3);
+        assert_eq!(values["theme"], serde_json::json!("dark"));
+        assert_eq!(values["locale"], serde_json::Value::Null);
+        assert_eq!(values["timezone"], serde_json::Value::Null);
+        assert!(!values.contains_key("currency"));
+        let proto: HashMap<String, Value> = ToProtoAlias::to_proto_alias(&values);
+        assert_eq!(proto["timezone"], proto["locale"]);
+        assert_eq!(proto["theme"], settings()["theme"]);
+    }
+
+    #[test]
+    fn test_nullable_values() {
+        let values: HashMap<String, Option<serde_json::Value>> =
+            nullable_values::from_proto(settings()).unwrap();
+        assert_eq!(values.len(), 3);
+        assert_eq!(values["theme"], Some(serde_json::json!("dark")));
+        assert_eq!(values["locale"], Some(serde_json::Value::Null));
+        assert_eq!(values["timezone"], None);
+        assert!(!values.contains_key("currency"));
+        // the explicit null is kept through the round trip
+        let proto: HashMap<String, Value> = nullable_values::to_proto(&values);
+        assert_eq!(proto, settings());
+
+        let sorted: BTreeMap<String, Option<serde_json::Value>> =
+            nullable_values::from_proto(settings()).unwrap();
+        assert_eq!(
+            sorted.keys().collect::<Vec<_>>(),
+            ["locale", "theme", "timezone"]
+        );
+    }
+}
@@ -0,0 +1,355 @@
+//! Converting as much as possible, like for the data migrations. `FromProtoLossy::from_proto_lossy`
+//! converts the malformed values leniently, defaults the missing ones and reports them per field.
+//! ```ignore
+//! let (user, report) = proto.from_proto_lossy()?;
+//! if !report.is_empty() {
+//!     log::warn!("{report}");
+//! }
+//! ```
+//! The fields marked with `#[proto(required_nonzero)]` report their proto default values as
+//! `LossyEvent::SuspiciousDefault`, to catch the schema drift in the staging before the data is
+//! corrupted.
+
+use std::fmt;
+
+use crate::error::{path_string, PathSegment};
+
+/// What was lossy in the conversion of a value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LossyEvent {
+    /// The value was missing and the default was used
+    MissingDefaulted,
+    /// The value was out of range and it was clamped
+    Clamped {
+        /// The proto value
+        from: String,
+        /// The converted value
+        to: String,
+    },
+    /// The enum value was unknown, the variant for the unknown values or of the default value was
+    /// used, or the map entry of the unknown enum key was skipped
+    UnknownEnum {
+        /// The proto enum value
+        raw: i64,
+    },
+    /// The value did not fit with its whole precision
+    TruncatedPrecision,
+    /// The value of the `#[proto(required_nonzero)]` field is the proto default, its field may have
+    /// been renumbered or retyped in the proto schema
+    SuspiciousDefault,
+    /// The repeated value was given more than once, the duplicates collapsed in to one
+    Duplicate {
+        /// The proto value
+        raw: i64,
+    },
+    /// The string tag was given more than once, also as the other name of the same variant, the
+    /// duplicates collapsed in to one
+    DuplicateTag {
+        /// The proto string
+        tag: String,
+    },
+}
+
+impl fmt::Display for LossyEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LossyEvent::MissingDefaulted => write!(f, "The missing value was defaulted"),
+            LossyEvent::Clamped { from, to } => write!(f, "The value {from} was clamped to {to}"),
+            LossyEvent::UnknownEnum { raw } => {
+                write!(f, "The enum value {raw} is unknown")
+            }
+            LossyEvent::TruncatedPrecision => write!(f, "The precision was truncated"),
+            LossyEvent::SuspiciousDefault => {
+                write!(
+                    f,
+                    "The value is the proto default, the schema may have drifted"
+                )
+            }
+            LossyEvent::Duplicate { raw } => write!(f, "The duplicate value {raw} was collapsed"),
+            LossyEvent::DuplicateTag { tag } => {
+                write!(f, "The duplicate tag {tag:?} was collapsed")
+            }
+        }
+    }
+}
+
+/// The event at the path of the value
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    /// The segments are stored from the innermost one, as in `ConversionError`
+    path: Vec<PathSegment>,
+    event: LossyEvent,
+}
+
+impl Entry {
+    fn path(&self) -> String {
+        path_string(self.path.iter().rev())
+    }
+}
+
+/// The lossy events of a conversion with the paths of their fields, like `items[1].created`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ConversionReport {
+    entries: Vec<Entry>,
+}
+
+impl ConversionReport {
+    /// The report without any events
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The report of the single event of the value itself
+    pub fn event(event: LossyEvent) -> Self {
+        let mut report = Self::new();
+        report.record(event);
+        report
+    }
+
+    /// The conversion was not lossy
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The number of the events
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// The events with the paths of their values, the path of the value itself is empty
+    pub fn iter(&self) -> impl Iterator<Item = (String, &LossyEvent)> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.path(), &entry.event))
+    }
+
+    /// The events of the value at the path
+    pub fn events<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a LossyEvent> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.path() == path)
+            .map(|entry| &entry.event)
+    }
+
+    /// There is an event of the value at the path
+    pub fn contains(&self, path: &str) -> bool {
+        self.events(path).next().is_some()
+    }
+
+    /// Record the event of the value itself
+    pub fn record(&mut self, event: LossyEvent) {
+        self.entries.push(Entry {
+            path: Vec::new(),
+            event,
+        });
+    }
+
+    /// Record the clamped value, unless it is the same
+    pub fn clamp<T: Copy + Ord + fmt::Display>(&mut self, value: T, min: T, max: T) -> T {
+        let clamped = value.clamp(min, max);
+        if clamped != value {
+            self.record(LossyEvent::Clamped {
+                from: value.to_string(),
+                to: clamped.to_string(),
+            });
+        }
+        clamped
+    }
+
+    /// Add the events of a nested value
+    pub fn append(&mut self, other: ConversionReport) {
+        self.entries.extend(other.entries);
+    }
+
+    /// Prepend the segment of the outer message to the paths of the events
+    pub fn push(mut self, segment: PathSegment) -> Self {
+        for entry in &mut self.entries {
+            entry.path.push(segment.clone());
+        }
+        self
+    }
+
+    /// The events happened in the field `name`
+    pub fn in_field(self, name: &'static str) -> Self {
+        self.push(PathSegment::Field(name))
+    }
+
+    /// The events happened in the element at `index`
+    pub fn at_index(self, index: usize) -> Self {
+        self.push(PathSegment::Index(index))
+    }
+
+    /// The events happened in the map entry with the `key`
+    pub fn at_key(self, key: &impl fmt::Display) -> Self {
+        self.push(PathSegment::Key(key.to_string()))
+    }
+}
+
+impl fmt::Display for ConversionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.entries.len() {
+            0 => return write!(f, "The conversion was not lossy"),
+            1 => write!(f, "1 lossy conversion")?,
+            count => write!(f, "{count} lossy conversions")?,
+        }
+        for entry in &self.entries {
+            if entry.path.is_empty() {
+                write!(f, "\n  {}", entry.event)?;
+            } else {
+                write!(f, "\n  {}: {}", entry.path(), entry.event)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The report of a value nested at the `path`, given from the outermost segment
+fn nested(report: ConversionReport, path: &[PathSegment]) -> ConversionReport {
+    path.iter()
+        .rev()
+        .cloned()
+        .fold(report, ConversionReport::push)
+}
+
+/// Take the converted value and add its events with the `path`, used by the derive macros
+#[doc(hidden)]
+pub fn merge<T>(
+    converted: anyhow::Result<(T, ConversionReport)>,
+    report: &mut ConversionReport,
+    path: &[PathSegment],
+) -> anyhow::Result<T> {
+    let (value, events) = converted?;
+    report.append(nested(events, path));
+    Ok(value)
+}
+
+/// Record the missing value at the `path`, which was defaulted, used by the derive macros
+#[doc(hidden)]
+pub fn defaulted<T>(report: &mut ConversionReport, path: &[PathSegment], value: T) -> T {
+    let event = ConversionReport::event(LossyEvent::MissingDefaulted);
+    report.append(nested(event, path));
+    value
+}
+
+/// Record the unknown enum key `raw` of the map at the `path`, whose entry was skipped, used by the
+/// derive macros
+#[doc(hidden)]
+pub fn skipped_key(report: &mut ConversionReport, path: &[PathSegment], raw: i32) {
+    let event = ConversionReport::event(LossyEvent::UnknownEnum { raw: raw.into() });
+    report.append(nested(event.at_key(&raw), path));
+}
+
+/// Record the proto default value at the `path`, used by the derive macros
+#[doc(hidden)]
+pub fn suspicious_default<P: crate::proto_default::ProtoDefault>(
+    report: &mut ConversionReport,
+    path: &[PathSegment],
+    value: P,
+) -> P {
+    if value.is_proto_default() {
+        let event = ConversionReport::event(LossyEvent::SuspiciousDefault);
+        report.append(nested(event, path));
+    }
+    value
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct ProtoDuration {
+        seconds: i64,
+        nanos: i32,
+    }
+
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct ProtoDateTimeUtc {
+        seconds: i64,
+        nanos: u32,
+    }
+
+    impl_traits!(prelude);
+    impl_traits!(ProtoDuration, chrono::Duration);
+    impl_traits!(ProtoDateTimeUtc, chrono::DateTime<chrono::Utc>);
+
+    #[test]
+    fn test_report() {
+        let mut report = ConversionReport::new();
+        report.record(LossyEvent::TruncatedPrecision);
+        let mut outer = ConversionReport::event(LossyEvent::UnknownEnum { raw: 7 });
+        outer.append(report.at_index(1).in_field("items"));
+        assert_eq!(outer.len(), 2);
+        assert!(outer.contains("items[1]"));
+        assert_eq!(
+            outer.events("").collect::<Vec<_>>(),
+            vec![&LossyEvent::UnknownEnum { raw: 7 }]
+        );
+        assert_eq!(
+            outer.to_string(),
+            "2 lossy conversions\n  The enum value 7 is unknown\n  items[1]: The \
+             precision was truncated"
+        );
+        assert_eq!(
+            ConversionReport::new().to_string(),
+            "The conversion was not lossy"
+        );
+    }
+
+    #[test]
+    fn test_containers() {
+        let protos = vec![
+            Some(ProtoDateTimeUtc {
+                seconds: 1,
+                nanos: 0,
+            }),
+            None,
+            Some(ProtoDateTimeUtc {
+                seconds: i64::MAX,
+                nanos: 0,
+            }),
+        ];
+        let (times, report) = protos.from_proto_lossy().unwrap();
+        assert_eq!(times.len(), 3);
+        assert_eq!(
+            times[2].unwrap().timestamp(),
+            chrono::DateTime::<chrono::Utc>::MAX_UTC.timestamp()
+        );
+        assert_eq!(
+            report.iter().map(|(path, _)| path).collect::<Vec<_>>(),
+            ["[2]"]
+        );
+
+        let durations = std::collections::HashMap::from([(
+            "timeout",
+            ProtoDuration {
+                seconds: 1,
+                nanos: -1_000_000_005,
+            },
+        )]);
+        let (durations, report) = durations.from_proto_lossy().unwrap();
+        assert_eq!(durations["timeout"], chrono::Duration::nanoseconds(1));
+        assert_eq!(
+            report.events("[\"timeout\"]").collect::<Vec<_>>(),
+            vec![&LossyEvent::Clamped {
+                from: "-1000000005".to_owned(),
+                to: "-999999999".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_narrowing() {
+        let (value, report) = i8::from_proto_alias_lossy(300i32).unwrap();
+        assert_eq!(value, 127);
+        assert_eq!(
+            report.to_string(),
+            "1 lossy conversion\n  The value 300 was clamped to 127"
+        );
+        let (value, report) = i8::from_proto_alias_lossy(3i32).unwrap();
+        assert_eq!(value, 3);
+        assert!(report.is_empty());
+    }
+}
@@ -0,0 +1,94 @@
+//! The field masks of the update requests, selecting the fields merged by `MergeFromProto`.
+//! ```ignore
+//! let mask = FieldMaskSet::new(request.update_mask.paths);
+//! user.merge_from_proto(request.user.required()?, &mask)?;
+//! ```
+
+use std::collections::BTreeSet;
+
+/// The paths of a field mask, like `name` or `address.city`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FieldMaskSet {
+    paths: BTreeSet<String>,
+}
+
+impl FieldMaskSet {
+    /// The mask of the paths
+    pub fn new<S: Into<String>>(paths: impl IntoIterator<Item = S>) -> Self {
+        paths.into_iter().collect()
+    }
+
+    /// There are no paths
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// The whole field is in the mask
+    pub fn contains(&self, field: &str) -> bool {
+        self.paths.contains(field)
+    }
+
+    /// The mask of the nested message paths under the field, `None` if there are none
+    pub fn nested(&self, field: &str) -> Option<FieldMaskSet> {
+        let paths: BTreeSet<String> = self
+            .paths
+            .iter()
+            .filter_map(|path| path.strip_prefix(field)?.strip_prefix('.'))
+            .map(str::to_owned)
+            .collect();
+        (!paths.is_empty()).then_some(FieldMaskSet { paths })
+    }
+
+    /// Check all the paths are the `fields`, or are nested in the `messages`, the error lists the
+    /// unknown ones
+    pub fn check(&self, fields: &[&str], messages: &[&str]) -> anyhow::Result<()> {
+        let unknown: Vec<&str> = self
+            .paths
+            .iter()
+            .map(String::as_str)
+            .filter(|path| match path.split_once('.') {
+                None => !fields.contains(path),
+                Some((field, _)) => !messages.contains(&field),
+            })
+            .collect();
+        anyhow::ensure!(
+            unknown.is_empty(),
+            "Unknown field mask paths: {}",
+            unknown.join(", ")
+        );
+        Ok(())
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for FieldMaskSet {
+    fn from_iter<I: IntoIterator<Item = S>>(paths: I) -> Self {
+        Self {
+            paths: paths.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask() {
+        let mask = FieldMaskSet::new(["name", "address.city", "address.zip"]);
+        assert!(mask.contains("name"));
+        assert!(!mask.contains("address"));
+        assert_eq!(
+            mask.nested("address"),
+            Some(FieldMaskSet::new(["city", "zip"]))
+        );
+        assert_eq!(mask.nested("name"), None);
+        assert_eq!(mask.nested("addr"), None);
+
+        mask.check(&["name", "address"], &["address"]).unwrap();
+        let error = mask.check(&["name"], &[]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown field mask paths: address.city, address.zip"
+        );
+    }
+}
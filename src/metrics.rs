@@ -0,0 +1,286 @@
+//! Counting the conversions and their failures per proto type with the `metrics` feature, without the
+//! `tracing`. The conversions through `FromProtoObserved::from_proto_observed` of the prelude report
+//! to the `ConversionObserver` installed with `set_observer`, the other conversions are not observed.
+//! ```ignore
+//! let counts = Arc::new(CountingObserver::new());
+//! proto_conve::metrics::set_observer(counts.clone());
+//!
+//! let user = proto.from_proto_observed()?;
+//! assert_eq!(counts.successes(std::any::type_name::<proto::User>()), 1);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::time::Duration;
+
+use crate::clock::Started;
+use crate::deprecation::DeprecationNotice;
+use crate::error::{ConversionError, Missing};
+use crate::version::UnsupportedVersion;
+
+/// Why the conversion failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FailureKind {
+    /// A required field was missing
+    Missing,
+    /// The version of the client is not supported
+    UnsupportedVersion,
+    /// A value was invalid
+    Invalid,
+}
+
+impl FailureKind {
+    /// The kind of the conversion error
+    pub fn of(error: &anyhow::Error) -> Self {
+        let reason = match error.downcast_ref::<ConversionError>() {
+            Some(conversion) => conversion.reason(),
+            None => error,
+        };
+        if reason.downcast_ref::<UnsupportedVersion>().is_some() {
+            FailureKind::UnsupportedVersion
+        // the message of `Required`
+        } else if reason.downcast_ref::<Missing>().is_some()
+            || reason.to_string() == "Required is missing"
+        {
+            FailureKind::Missing
+        } else {
+            FailureKind::Invalid
+        }
+    }
+}
+
+/// Notified about the observed conversions, it has to be cheap, it is called for every one of them
+pub trait ConversionObserver: Send + Sync {
+    /// The proto type `type_name` was converted in the `elapsed` time
+    fn on_success(&self, type_name: &'static str, elapsed: Duration);
+
+    /// The proto type `type_name` failed to convert
+    fn on_failure(&self, type_name: &'static str, reason_kind: FailureKind);
+
+    /// The converted proto type `type_name` had the deprecated value of the `notice` present, see
+    /// `deprecation`
+    fn on_deprecated(&self, type_name: &'static str, notice: &DeprecationNotice) {
+        let _ = (type_name, notice);
+    }
+}
+
+static OBSERVER: RwLock<Option<Arc<dyn ConversionObserver>>> = RwLock::new(None);
+
+/// Install the `observer` of the conversions, returning the one installed before
+pub fn set_observer(observer: Arc<dyn ConversionObserver>) -> Option<Arc<dyn ConversionObserver>> {
+    let mut current = OBSERVER.write().unwrap_or_else(|error| error.into_inner());
+    current.replace(observer)
+}
+
+/// Remove the observer of the conversions, returning it
+pub fn clear_observer() -> Option<Arc<dyn ConversionObserver>> {
+    let mut current = OBSERVER.write().unwrap_or_else(|error| error.into_inner());
+    current.take()
+}
+
+/// Run the conversion of the proto type `P` and report it, with its deprecation notices, used by the
+/// prelude. The notices are collected only with an observer installed.
+#[doc(hidden)]
+pub fn observe<P: ?Sized, T>(convert: impl FnOnce() -> anyhow::Result<T>) -> anyhow::Result<T> {
+    let start = Started::now();
+    // not locked during the conversion, which can observe the nested ones
+    let observer = OBSERVER
+        .read()
+        .unwrap_or_else(|error| error.into_inner())
+        .clone();
+    let Some(observer) = observer else {
+        return convert();
+    };
+    let (result, notices) = crate::deprecation::collect(convert);
+    let type_name = std::any::type_name::<P>();
+    for notice in &notices {
+        observer.on_deprecated(type_name, notice);
+    }
+    match &result {
+        Ok(_) => observer.on_success(type_name, start.elapsed()),
+        Err(error) => observer.on_failure(type_name, FailureKind::of(error)),
+    }
+    result
+}
+
+/// The number of the conversions of a proto type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Counts {
+    /// The successful conversions
+    pub successes: u64,
+    /// The failed conversions
+    pub failures: u64,
+    /// The time of the successful conversions
+    pub elapsed: Duration,
+}
+
+/// Counts the conversions per proto type, for the tests and the simple counters
+#[derive(Debug, Default)]
+pub struct CountingObserver {
+    counts: Mutex<HashMap<&'static str, Counts>>,
+    failures: Mutex<HashMap<FailureKind, u64>>,
+    deprecations: Mutex<HashMap<String, u64>>,
+}
+
+/// The counts are kept, even when a thread panicked holding them
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+impl CountingObserver {
+    /// The observer without any conversions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The counts of the proto type `type_name`
+    pub fn counts(&self, type_name: &str) -> Counts {
+        let counts = lock(&self.counts);
+        counts.get(type_name).copied().unwrap_or_default()
+    }
+
+    /// The successful conversions of the proto type `type_name`
+    pub fn successes(&self, type_name: &str) -> u64 {
+        self.counts(type_name).successes
+    }
+
+    /// The failed conversions of the proto type `type_name`
+    pub fn failures(&self, type_name: &str) -> u64 {
+        self.counts(type_name).failures
+    }
+
+    /// The failed conversions of all the proto types for the `kind`
+    pub fn failures_of(&self, kind: FailureKind) -> u64 {
+        let failures = lock(&self.failures);
+        failures.get(&kind).copied().unwrap_or_default()
+    }
+
+    /// The present deprecated values at the `path` of all the proto types, like `items[3].legacy_id`
+    pub fn deprecations(&self, path: &str) -> u64 {
+        let deprecations = lock(&self.deprecations);
+        deprecations.get(path).copied().unwrap_or_default()
+    }
+}
+
+impl ConversionObserver for CountingObserver {
+    fn on_success(&self, type_name: &'static str, elapsed: Duration) {
+        let mut counts = lock(&self.counts);
+        let counts = counts.entry(type_name).or_default();
+        counts.successes += 1;
+        counts.elapsed += elapsed;
+    }
+
+    fn on_failure(&self, type_name: &'static str, reason_kind: FailureKind) {
+        let mut counts = lock(&self.counts);
+        counts.entry(type_name).or_default().failures += 1;
+        let mut failures = lock(&self.failures);
+        *failures.entry(reason_kind).or_default() += 1;
+    }
+
+    fn on_deprecated(&self, _type_name: &'static str, notice: &DeprecationNotice) {
+        let mut deprecations = lock(&self.deprecations);
+        *deprecations.entry(notice.path()).or_default() += 1;
+    }
+}
+
+/// Generate the observed conversions, this is part of the `impl_traits!(prelude)`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_metrics {
+    (prelude) => {
+        pub use super::FromProtoObserved;
+    };
+    () => {
+        /// Convert the proto value and report it to the observer of `proto_conve::metrics`
+        pub trait FromProtoObserved: FromProto + Sized {
+            /// Convert the proto value the same as `from_proto`, reporting the time or the failure
+            #[allow(clippy::wrong_self_convention)]
+            fn from_proto_observed(self) -> anyhow::Result<Self::Result> {
+                $crate::metrics::observe::<Self, _>(|| self.from_proto())
+            }
+        }
+
+        impl<T: FromProto> FromProtoObserved for T {}
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoUser {
+        age: Option<i64>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct User {
+        age: u8,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoGroup {
+        legacy_name: String,
+    }
+
+    impl_traits!(prelude);
+
+    impl FromProto for ProtoGroup {
+        type Result = String;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            let name = crate::deprecation::DeprecatedField::new(self.legacy_name, "use the id");
+            FromProtoField::from_proto_field(name, "legacy_name")
+        }
+    }
+
+    impl FromProto for ProtoUser {
+        type Result = User;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            let age = self
+                .age
+                .required()
+                .map_err(|error| crate::error::in_field(error, "age"))?;
+            Ok(User {
+                age: u8::try_from(age)?,
+            })
+        }
+    }
+
+    #[test]
+    fn test_counting() {
+        let counts = Arc::new(CountingObserver::new());
+        set_observer(counts.clone());
+
+        let batch = [Some(30), None, Some(300), Some(40)];
+        let users: Vec<_> = batch
+            .iter()
+            .map(|&age| ProtoUser { age }.from_proto_observed())
+            .collect();
+        assert_eq!(users.iter().filter(|user| user.is_ok()).count(), 2);
+        // the plain conversions are not observed
+        ProtoUser { age: Some(1) }.from_proto().unwrap();
+        let all: Vec<_> = batch.iter().map(|&age| ProtoUser { age }).collect();
+        assert!(all.from_proto_observed().is_err());
+        let groups = ["a", "", "b"].map(|name| ProtoGroup {
+            legacy_name: name.to_owned(),
+        });
+        assert_eq!(groups.to_vec().from_proto_observed().unwrap().len(), 3);
+        assert!(clear_observer().is_some());
+        ProtoUser { age: None }.from_proto_observed().unwrap_err();
+
+        let user = std::any::type_name::<ProtoUser>();
+        assert_eq!(counts.successes(user), 2);
+        assert_eq!(counts.failures(user), 2);
+        assert_eq!(counts.failures(std::any::type_name::<Vec<ProtoUser>>()), 1);
+        assert_eq!(counts.failures_of(FailureKind::Missing), 2);
+        assert_eq!(counts.failures_of(FailureKind::Invalid), 1);
+        assert_eq!(counts.counts("other"), Counts::default());
+        assert_eq!(counts.deprecations("[0].legacy_name"), 1);
+        assert_eq!(counts.deprecations("[1].legacy_name"), 0);
+        assert_eq!(counts.deprecations("[2].legacy_name"), 1);
+    }
+}
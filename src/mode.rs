@@ -0,0 +1,228 @@
+//! How strictly the proto values are converted. The API edge wants the malformed values rejected, while
+//! a backfill of old data wants them clamped or defaulted, so the same proto can be converted both ways
+//! with `FromProtoMode::from_proto_mode`.
+
+use crate::violation::{coded, ErrorCode};
+
+/// How the malformed proto values are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ConversionMode {
+    /// The out of range values, unknown enum values and invalid nanos are an error
+    #[default]
+    Strict,
+    /// The out of range values are clamped and unknown enum values are defaulted
+    Lenient,
+}
+
+impl ConversionMode {
+    /// The mode is lenient
+    pub fn is_lenient(self) -> bool {
+        self == ConversionMode::Lenient
+    }
+}
+
+/// The nanos of a second, clamped in lenient mode, used by the chrono conversions
+#[doc(hidden)]
+pub fn nanos(nanos: i64, mode: ConversionMode) -> anyhow::Result<u32> {
+    match mode {
+        _ if (0..1_000_000_000).contains(&nanos) => Ok(nanos as u32),
+        ConversionMode::Strict => Err(coded(
+            ErrorCode::OutOfRange,
+            format_args!("Invalid nanos {nanos}, they have to be in 0..1e9"),
+        )),
+        ConversionMode::Lenient => Ok(nanos.clamp(0, 999_999_999) as u32),
+    }
+}
+
+/// The nanos of a duration, the `signed` ones are in `-1e9..1e9`, clamped in lenient mode, used by the
+/// chrono conversions
+#[doc(hidden)]
+pub fn duration_nanos(nanos: i64, signed: bool, mode: ConversionMode) -> anyhow::Result<i64> {
+    if !signed {
+        return self::nanos(nanos, mode).map(i64::from);
+    }
+    match mode {
+        _ if (-999_999_999..1_000_000_000).contains(&nanos) => Ok(nanos),
+        ConversionMode::Strict => Err(coded(
+            ErrorCode::OutOfRange,
+            format_args!("Invalid nanos {nanos}, they have to be in -1e9..1e9 exclusive"),
+        )),
+        ConversionMode::Lenient => Ok(nanos.clamp(-999_999_999, 999_999_999)),
+    }
+}
+
+/// The seconds in the range `min..=max`, clamped in lenient mode, used by the chrono conversions
+#[doc(hidden)]
+pub fn seconds(seconds: i64, min: i64, max: i64, mode: ConversionMode) -> anyhow::Result<i64> {
+    match mode {
+        _ if (min..=max).contains(&seconds) => Ok(seconds),
+        ConversionMode::Strict => Err(coded(
+            ErrorCode::OutOfRange,
+            format_args!("The {seconds} s are out of range"),
+        )),
+        ConversionMode::Lenient => Ok(seconds.clamp(min, max)),
+    }
+}
+
+/// The days and seconds joined in to seconds, saturated in lenient mode, used by the chrono conversions
+#[doc(hidden)]
+pub fn days(days: i64, seconds: i64, mode: ConversionMode) -> anyhow::Result<i64> {
+    match days
+        .checked_mul(86_400)
+        .and_then(|days| days.checked_add(seconds))
+    {
+        Some(seconds) => Ok(seconds),
+        None if mode.is_lenient() => Ok(days.saturating_mul(86_400).saturating_add(seconds)),
+        None => Err(coded(
+            ErrorCode::OutOfRange,
+            format_args!("The {days} days and {seconds} s are out of range"),
+        )),
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct ProtoDuration {
+        seconds: i64,
+        nanos: i32,
+    }
+
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct ProtoDateTimeUtc {
+        seconds: i64,
+        nanos: u32,
+    }
+
+    impl_traits!(prelude);
+    impl_traits!(ProtoDuration, chrono::Duration);
+    impl_traits!(ProtoDateTimeUtc, chrono::DateTime<chrono::Utc>);
+
+    #[test]
+    fn test_duration_nanos() {
+        let proto = ProtoDuration {
+            seconds: 1,
+            nanos: 2_000_000_000,
+        };
+        let error = proto
+            .clone()
+            .from_proto_mode(ConversionMode::Strict)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid nanos 2000000000, they have to be in -1e9..1e9 exclusive"
+        );
+        let duration = proto.from_proto_mode(ConversionMode::Lenient).unwrap();
+        assert_eq!(duration, chrono::Duration::nanoseconds(1_999_999_999));
+    }
+
+    #[test]
+    fn test_date_time_range() {
+        let proto = ProtoDateTimeUtc {
+            seconds: i64::MAX,
+            nanos: 0,
+        };
+        assert!(proto
+            .clone()
+            .from_proto_mode(ConversionMode::Strict)
+            .is_err());
+        let time = proto.from_proto_mode(ConversionMode::Lenient).unwrap();
+        assert_eq!(
+            time.timestamp(),
+            chrono::DateTime::<chrono::Utc>::MAX_UTC.timestamp()
+        );
+    }
+
+    mod days {
+        use super::ConversionMode;
+        use crate::impl_traits;
+
+        #[derive(Debug, PartialEq, Clone)]
+        pub struct ProtoTimedelta {
+            days: i64,
+            seconds: i64,
+            nanos: i32,
+        }
+
+        impl_traits!(prelude);
+        impl_traits!(
+            ProtoTimedelta,
+            chrono::Duration {
+                days,
+                seconds,
+                nanos
+            }
+        );
+
+        #[test]
+        fn test_days_overflow() {
+            let proto = ProtoTimedelta {
+                days: i64::MAX / 86_400,
+                seconds: i64::MAX,
+                nanos: 0,
+            };
+            let error = proto
+                .clone()
+                .from_proto_mode(ConversionMode::Strict)
+                .unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!(
+                    "The {} days and {} s are out of range",
+                    i64::MAX / 86_400,
+                    i64::MAX
+                )
+            );
+            let duration = proto.from_proto_mode(ConversionMode::Lenient).unwrap();
+            assert_eq!(duration, chrono::Duration::seconds(i64::MAX / 1000));
+        }
+    }
+
+    #[test]
+    fn test_narrowing() {
+        assert_eq!(
+            i8::from_proto_alias_mode(300i32, ConversionMode::Lenient).unwrap(),
+            127
+        );
+        assert_eq!(
+            u16::from_proto_alias_mode(7u64, ConversionMode::Strict).unwrap(),
+            7
+        );
+        let error = i8::from_proto_alias_mode(300i32, ConversionMode::Strict).unwrap_err();
+        assert_eq!(error.to_string(), "The value 300 does not fit in to i8");
+    }
+
+    #[test]
+    fn test_containers() {
+        let protos = vec![
+            ProtoDuration {
+                seconds: 1,
+                nanos: 0,
+            },
+            ProtoDuration {
+                seconds: 1,
+                nanos: -1_000_000_000,
+            },
+        ];
+        let error = protos
+            .clone()
+            .from_proto_mode(ConversionMode::Strict)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "[1]: Invalid nanos -1000000000, they have to be in -1e9..1e9 exclusive"
+        );
+        let durations = Some(protos)
+            .from_proto_mode(ConversionMode::Lenient)
+            .unwrap()
+            .unwrap();
+        assert_eq!(durations[1], chrono::Duration::nanoseconds(1));
+
+        let values = Vec::<u8>::from_proto_alias_mode(vec![1u32, 256], ConversionMode::Lenient);
+        assert_eq!(values.unwrap(), [1, 255]);
+    }
+}
@@ -0,0 +1,387 @@
+//! The numbers sent as strings, like by the bridges of the CSV files, which write them as they are in
+//! the spreadsheets, `"1,234.5"` or `"1 234,5"`. `LenientNumber<f64>` and `LenientNumber<i64>`, also
+//! of `f32`, `i32`, `u64` and `u32`, convert from the proto `String` with the `NumberPolicy` of the
+//! conversion context, given with `number::scope`.
+//! ```ignore
+//! #[derive(FromProto, ToProto)]
+//! #[proto(type = "proto::Row")]
+//! struct Row {
+//!     amount: LenientNumber<f64>,
+//! }
+//!
+//! let row = number::scope(NumberPolicy::CommaDecimal, || proto.from_proto())?;
+//! ```
+//! Out of a scope the policy is `NumberPolicy::Canonical`, only the numbers as Rust writes them are
+//! read, like `-1234.5`, `1e-3` for the floats, without the `+`, the whitespace or the separators.
+//! Into proto the numbers are always written in the canonical form.
+//!
+//! The lenient policies name the decimal mark, the other one of `,` and `.` is the grouping separator,
+//! as are the spaces, the no break spaces and `'`. So the ambiguous `"1,234"` is `1234` with
+//! `DotDecimal` and `1.234` with `CommaDecimal`, where it is not an integer. The separators are only in
+//! the integer part, one kind of them per number, and the digits are grouped by three, the first group
+//! can be shorter, so `"12,34"` is not read as `1234`. The whitespace around the number and the leading
+//! `+` are ignored.
+//!
+//! The malformed numbers are `ErrorCode::InvalidFormat`, the integers out of their range and the floats
+//! overflowing to the infinity are `ErrorCode::OutOfRange`. The non-finite floats are written as `NaN`
+//! and `inf`, which are not read back.
+
+use std::cell::Cell;
+use std::fmt;
+
+use crate::violation::{coded, ErrorCode};
+
+/// How the numbers of the strings are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NumberPolicy {
+    /// Only the canonical form, as the numbers are written
+    #[default]
+    Canonical,
+    /// The decimal mark is `.`, `,` groups the digits, like `1,234.5`
+    DotDecimal,
+    /// The decimal mark is `,`, `.` groups the digits, like `1.234,5`
+    CommaDecimal,
+}
+
+impl NumberPolicy {
+    /// The decimal mark of the lenient policies
+    fn decimal_mark(self) -> Option<char> {
+        match self {
+            NumberPolicy::Canonical => None,
+            NumberPolicy::DotDecimal => Some('.'),
+            NumberPolicy::CommaDecimal => Some(','),
+        }
+    }
+}
+
+thread_local! {
+    static POLICY: Cell<NumberPolicy> = const { Cell::new(NumberPolicy::Canonical) };
+}
+
+/// Run the conversions in `f` reading the numbers with the `policy`
+pub fn scope<R>(policy: NumberPolicy, f: impl FnOnce() -> R) -> R {
+    /// Restores the policy of the outer scope, also when `f` panics
+    struct Restore(NumberPolicy);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            POLICY.with(|policy| policy.set(self.0));
+        }
+    }
+
+    let _restore = Restore(POLICY.with(|current| current.replace(policy)));
+    f()
+}
+
+/// The policy of the scope
+pub fn current_policy() -> NumberPolicy {
+    POLICY.with(Cell::get)
+}
+
+/// The number types of `LenientNumber`
+pub trait LenientNumeric: Copy + fmt::Display + private::Sealed {
+    /// The number has the fraction and the exponent
+    #[doc(hidden)]
+    const FRACTION: bool;
+
+    /// Parse the canonical number, `None` out of the range
+    #[doc(hidden)]
+    fn parse_canonical(value: &str) -> Option<Self>;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_integer {
+    ($($type: ty),*) => {$(
+        impl private::Sealed for $type {}
+
+        impl LenientNumeric for $type {
+            const FRACTION: bool = false;
+
+            fn parse_canonical(value: &str) -> Option<Self> {
+                value.parse().ok()
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_float {
+    ($($type: ty),*) => {$(
+        impl private::Sealed for $type {}
+
+        impl LenientNumeric for $type {
+            const FRACTION: bool = true;
+
+            fn parse_canonical(value: &str) -> Option<Self> {
+                value.parse().ok().filter(|value: &$type| value.is_finite())
+            }
+        }
+    )*};
+}
+
+impl_integer!(i64, i32, u64, u32);
+impl_float!(f64, f32);
+
+/// The number of the proto string, read with the `NumberPolicy` of the scope
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct LenientNumber<T>(pub T);
+
+impl<T: LenientNumeric> LenientNumber<T> {
+    /// The number of the string, read with the policy of the scope
+    pub fn parse(value: &str) -> anyhow::Result<Self> {
+        Self::parse_with(value, current_policy())
+    }
+
+    /// The number of the string, read with the `policy`
+    pub fn parse_with(value: &str, policy: NumberPolicy) -> anyhow::Result<Self> {
+        let canonical = match policy.decimal_mark() {
+            None if is_canonical(value, T::FRACTION) => value.to_owned(),
+            None => {
+                let message = format!("Invalid number {value:?}, it is not in the canonical form");
+                return Err(coded(ErrorCode::InvalidFormat, message));
+            }
+            Some(mark) => match canonicalize(value, mark, T::FRACTION) {
+                Ok(canonical) => canonical,
+                Err(reason) => {
+                    let message = format!("Invalid number {value:?}, {reason}");
+                    return Err(coded(ErrorCode::InvalidFormat, message));
+                }
+            },
+        };
+        match T::parse_canonical(&canonical) {
+            Some(number) => Ok(Self(number)),
+            None => {
+                let message = format!(
+                    "The number {value:?} is out of the range of {}",
+                    std::any::type_name::<T>()
+                );
+                Err(coded(ErrorCode::OutOfRange, message))
+            }
+        }
+    }
+
+    /// The number
+    pub fn get(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for LenientNumber<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+/// The canonical form
+impl<T: fmt::Display> fmt::Display for LenientNumber<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The non empty ASCII digits
+fn is_digits(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+/// The number is written as Rust writes it, with an optional exponent for the floats
+fn is_canonical(value: &str, fraction: bool) -> bool {
+    let value = value.strip_prefix('-').unwrap_or(value);
+    if !fraction {
+        return is_digits(value);
+    }
+    let (mantissa, exponent) = match value.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (value, None),
+    };
+    let exponent = exponent.map(|exponent| exponent.strip_prefix('-').unwrap_or(exponent));
+    let (integer, decimals) = match mantissa.split_once('.') {
+        Some((integer, decimals)) => (integer, Some(decimals)),
+        None => (mantissa, None),
+    };
+    is_digits(integer) && decimals.is_none_or(is_digits) && exponent.is_none_or(is_digits)
+}
+
+/// The separators grouping the digits, besides the other decimal mark
+const GROUPING: [char; 4] = [' ', '\u{a0}', '\u{202f}', '\''];
+
+/// The canonical form of the number of the lenient policy with the decimal `mark`
+fn canonicalize(value: &str, mark: char, fraction: bool) -> Result<String, &'static str> {
+    let value = value.trim();
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(value) => ("-", value),
+        None => ("", value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (mantissa, exponent) = match value.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) if fraction => (mantissa, Some(exponent)),
+        _ => (value, None),
+    };
+    let (integer, decimals) = match mantissa.split_once(mark) {
+        Some(_) if !fraction => return Err("the integer has a fraction"),
+        Some((_, decimals)) if decimals.contains(mark) => {
+            return Err("it has more than one decimal mark")
+        }
+        Some((integer, decimals)) => (integer, Some(decimals)),
+        None => (mantissa, None),
+    };
+    let other = if mark == '.' { ',' } else { '.' };
+    let mut separators = integer.chars().filter(|c| !c.is_ascii_digit());
+    let mut canonical = sign.to_owned();
+    match separators.next() {
+        None => canonical.push_str(integer),
+        Some(separator) if separator == other || GROUPING.contains(&separator) => {
+            if separators.any(|c| c != separator) {
+                return Err("it has more than one kind of the grouping separators");
+            }
+            let mut groups = integer.split(separator);
+            let first = groups.next().unwrap_or_default();
+            let grouped = (1..=3).contains(&first.len())
+                && is_digits(first)
+                && groups.all(|group| group.len() == 3 && is_digits(group));
+            if !grouped {
+                return Err("the digits have to be grouped by three");
+            }
+            canonical.extend(integer.chars().filter(char::is_ascii_digit));
+        }
+        Some(_) => return Err("it is not a number"),
+    }
+    if let Some(decimals) = decimals {
+        canonical.push('.');
+        canonical.push_str(decimals);
+    }
+    if let Some(exponent) = exponent {
+        canonical.push('e');
+        canonical.push_str(exponent);
+    }
+    if is_canonical(&canonical, fraction) {
+        Ok(canonical)
+    } else {
+        Err("it is not a number")
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    fn float(value: &str, policy: NumberPolicy) -> anyhow::Result<f64> {
+        LenientNumber::<f64>::parse_with(value, policy).map(LenientNumber::get)
+    }
+
+    fn integer(value: &str, policy: NumberPolicy) -> anyhow::Result<i64> {
+        LenientNumber::<i64>::parse_with(value, policy).map(LenientNumber::get)
+    }
+
+    #[test]
+    fn test_canonical() {
+        for policy in [
+            NumberPolicy::Canonical,
+            NumberPolicy::DotDecimal,
+            NumberPolicy::CommaDecimal,
+        ] {
+            assert_eq!(integer("-1234", policy).unwrap(), -1234);
+            assert_eq!(float("1234", policy).unwrap(), 1234.0);
+            assert_eq!(float("-2e3", policy).unwrap(), -2000.0);
+        }
+        let canonical = NumberPolicy::Canonical;
+        assert_eq!(float("1234.5", canonical).unwrap(), 1234.5);
+        assert_eq!(float("1.5E-3", canonical).unwrap(), 0.0015);
+        for value in ["1,234.5", " 1", "+1", ".5", "1.", "inf", "NaN", ""] {
+            let error = float(value, canonical).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("Invalid number {value:?}, it is not in the canonical form")
+            );
+            assert_eq!(ErrorCode::of(&error), ErrorCode::InvalidFormat);
+        }
+        assert!(integer("1e3", canonical).is_err());
+    }
+
+    #[test]
+    fn test_grouped() {
+        let dot = NumberPolicy::DotDecimal;
+        assert_eq!(float("1,234.5", dot).unwrap(), 1234.5);
+        assert_eq!(float(" +1 234 567.25 ", dot).unwrap(), 1_234_567.25);
+        assert_eq!(float("1'234", dot).unwrap(), 1234.0);
+        assert_eq!(integer("-12,345,678", dot).unwrap(), -12_345_678);
+        assert_eq!(integer("9\u{a0}999", dot).unwrap(), 9999);
+    }
+
+    #[test]
+    fn test_comma_decimal() {
+        let comma = NumberPolicy::CommaDecimal;
+        assert_eq!(float("1 234,5", comma).unwrap(), 1234.5);
+        assert_eq!(float("1.234.567,125", comma).unwrap(), 1_234_567.125);
+        assert_eq!(float("0,5e2", comma).unwrap(), 50.0);
+        assert_eq!(integer("1.234", comma).unwrap(), 1234);
+    }
+
+    #[test]
+    fn test_ambiguous() {
+        assert_eq!(float("1,234", NumberPolicy::DotDecimal).unwrap(), 1234.0);
+        assert_eq!(float("1,234", NumberPolicy::CommaDecimal).unwrap(), 1.234);
+        assert_eq!(integer("1,234", NumberPolicy::DotDecimal).unwrap(), 1234);
+        let error = integer("1,234", NumberPolicy::CommaDecimal).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid number \"1,234\", the integer has a fraction"
+        );
+    }
+
+    #[test]
+    fn test_garbage() {
+        let dot = NumberPolicy::DotDecimal;
+        let cases = [
+            ("12,34", "the digits have to be grouped by three"),
+            ("1,2345", "the digits have to be grouped by three"),
+            (",123", "the digits have to be grouped by three"),
+            (
+                "1,234 567",
+                "it has more than one kind of the grouping separators",
+            ),
+            ("1.2.3", "it has more than one decimal mark"),
+            ("12a", "it is not a number"),
+            ("1.5x", "it is not a number"),
+            ("--1", "it is not a number"),
+            ("", "it is not a number"),
+        ];
+        for (value, reason) in cases {
+            let error = float(value, dot).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("Invalid number {value:?}, {reason}")
+            );
+        }
+        let error = integer("9,223,372,036,854,775,808", dot).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The number \"9,223,372,036,854,775,808\" is out of the range of i64"
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::OutOfRange);
+        assert!(float("1e999", dot).is_err());
+    }
+
+    #[test]
+    fn test_conversions() {
+        let proto = "1.234,5".to_owned();
+        let error = LenientNumber::<f64>::from_proto_alias(proto.clone()).unwrap_err();
+        assert_eq!(ErrorCode::of(&error), ErrorCode::InvalidFormat);
+        let number = scope(NumberPolicy::CommaDecimal, || {
+            LenientNumber::<f64>::from_proto_alias(proto)
+        })
+        .unwrap();
+        assert_eq!(number, LenientNumber(1234.5));
+        assert_eq!(current_policy(), NumberPolicy::Canonical);
+
+        let back: String = number.to_proto_alias();
+        assert_eq!(back, "1234.5");
+        let back: String = LenientNumber(-7i64).to_proto_alias();
+        assert_eq!(back, "-7");
+    }
+}
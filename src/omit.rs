@@ -0,0 +1,59 @@
+//! Leaving the optional proto fields out by a predicate on the value, like the placeholder values,
+//! which the clients should see as missing. The predicate decides before the conversion, so the
+//! omitted value is never converted, which matters for the values, which would not convert well, or
+//! are expensive to convert.
+//! ```ignore
+//! fn is_placeholder(address: &Address) -> bool {
+//!     address.street.is_empty()
+//! }
+//!
+//! let proto = proto::Customer {
+//!     address: address.to_proto_option_if(is_placeholder),
+//!     ..
+//! };
+//! ```
+//! The `OmitIf` wrapper of the value converts with `ToProto` in to the optional proto value, like in the
+//! generic code taking the `ToProto` values, and the derived fields use the predicate with
+//! `#[proto(omit_if = "is_placeholder")]`, for the `Option` fields the predicate gets the value in it.
+//! ```ignore
+//! #[derive(ToProto)]
+//! #[proto(type = "proto::Customer")]
+//! struct Customer {
+//!     name: String,
+//!     #[proto(omit_if = "is_placeholder")]
+//!     address: Address,
+//!     #[proto(omit_if = "is_placeholder")]
+//!     billing_address: Option<Address>,
+//! }
+//! ```
+
+use std::fmt;
+
+/// The value, which converts in to `None`, when the predicate `omit` is true for it
+pub struct OmitIf<'a, T: ?Sized, F> {
+    value: &'a T,
+    omit: F,
+}
+
+impl<'a, T: ?Sized, F: Fn(&T) -> bool> OmitIf<'a, T, F> {
+    /// The `value` omitted, when `omit` is true for it
+    pub fn new(value: &'a T, omit: F) -> Self {
+        Self { value, omit }
+    }
+
+    /// The wrapped value
+    pub fn value(&self) -> &'a T {
+        self.value
+    }
+
+    /// Whether the value is omitted
+    pub fn is_omitted(&self) -> bool {
+        (self.omit)(self.value)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug, F> fmt::Debug for OmitIf<'_, T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OmitIf").field(&self.value).finish()
+    }
+}
@@ -0,0 +1,99 @@
+//! Proto enum values, which are kept even when they are unknown. A newer client can send a value this
+//! build does not know yet, the raw value is preserved, so it can be forwarded as it is.
+
+/// The proto enum value, either one of the known values of `E`, or the raw unknown value. The known
+/// values are ordered before the unknown ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum OpenEnum<E> {
+    /// The value converted in to the enum
+    Known(E),
+    /// The value not known to the enum
+    Unknown(i32),
+}
+
+impl<E> OpenEnum<E> {
+    /// The known value
+    pub fn known(&self) -> Option<&E> {
+        match self {
+            OpenEnum::Known(value) => Some(value),
+            OpenEnum::Unknown(_) => None,
+        }
+    }
+
+    /// The value is not known to the enum
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, OpenEnum::Unknown(_))
+    }
+}
+
+impl<E> From<E> for OpenEnum<E> {
+    fn from(value: E) -> Self {
+        OpenEnum::Known(value)
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    pub enum Color {
+        Red,
+        Green,
+    }
+
+    impl_traits!(prelude);
+    impl_traits!(open Color);
+
+    impl FromProtoAlias<i32> for Color {
+        fn from_proto_alias(value: i32) -> anyhow::Result<Self> {
+            match value {
+                1 => Ok(Color::Red),
+                2 => Ok(Color::Green),
+                value => anyhow::bail!("Unknown value {value} of Color"),
+            }
+        }
+    }
+
+    impl ToProtoAlias<i32> for Color {
+        fn to_proto_alias(&self) -> i32 {
+            match self {
+                Color::Red => 1,
+                Color::Green => 2,
+            }
+        }
+    }
+
+    #[test]
+    fn test_open() {
+        let color = OpenEnum::<Color>::from_proto_alias(2).unwrap();
+        assert_eq!(color, OpenEnum::Known(Color::Green));
+        assert_eq!(ToProtoAlias::<i32>::to_proto_alias(&color), 2);
+
+        let color = OpenEnum::<Color>::from_proto_alias(9).unwrap();
+        assert_eq!(color, OpenEnum::Unknown(9));
+        assert_eq!(ToProtoAlias::<i32>::to_proto_alias(&color), 9);
+    }
+
+    #[test]
+    fn test_order() {
+        let mut colors = vec![
+            OpenEnum::Unknown(4),
+            OpenEnum::Known(Color::Green),
+            OpenEnum::Unknown(3),
+            OpenEnum::Known(Color::Red),
+        ];
+        colors.sort();
+        assert_eq!(
+            colors,
+            [
+                OpenEnum::Known(Color::Red),
+                OpenEnum::Known(Color::Green),
+                OpenEnum::Unknown(3),
+                OpenEnum::Unknown(4),
+            ]
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! The pages of the list responses, which all have the same shape.
+//! ```proto
+//! message ListUsersResponse {
+//!   repeated User items = 1;
+//!   string next_page_token = 2;
+//!   int32 total_size = 3;
+//! }
+//! ```
+//! They convert in to `Page<User>` with `impl_proto_page!(ListUsersResponse, items: Vec<User>)`, where
+//! `items` is the repeated field and `User` the domain type of its elements. The empty token is the
+//! last page, the negative total size is an error.
+
+/// The page of the items of a list response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    /// The items of the page
+    pub items: Vec<T>,
+    /// The token of the next page, `None` on the last page
+    pub next_token: Option<String>,
+    /// The number of the items of all the pages, `None` is written as `0`
+    pub total: Option<u64>,
+}
+
+impl<T> Default for Page<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            next_token: None,
+            total: None,
+        }
+    }
+}
+
+impl<T> Page<T> {
+    /// There is no next page
+    pub fn is_last(&self) -> bool {
+        self.next_token.is_none()
+    }
+}
+
+/// The token of the next page, used by `impl_proto_page!`
+#[doc(hidden)]
+pub fn next_token(token: String) -> Option<String> {
+    Some(token).filter(|token| !token.is_empty())
+}
+
+/// The total size of the pages, used by `impl_proto_page!`
+#[doc(hidden)]
+pub fn total(total: i32) -> anyhow::Result<Option<u64>> {
+    match u64::try_from(total) {
+        Ok(total) => Ok(Some(total)),
+        Err(_) => Err(crate::error::in_field(
+            anyhow::anyhow!("The total size {total} is negative"),
+            "total_size",
+        )),
+    }
+}
+
+/// The proto total size of the pages, used by `impl_proto_page!`
+#[doc(hidden)]
+pub fn total_size(total: Option<u64>) -> i32 {
+    total.map_or(0, |total| i32::try_from(total).unwrap_or(i32::MAX))
+}
+
+/// Implement `FromProto` of the list response in to `Page` and `ToProto` of the `Page` back, the
+/// traits of the `impl_traits!(prelude)` have to be in the scope.
+/// ```ignore
+/// impl_proto_page!(ListUsersResponse, items: Vec<User>);
+/// ```
+/// The total size over `i32::MAX` is written as `i32::MAX`.
+#[macro_export]
+macro_rules! impl_proto_page {
+    ($type: ident, $field: ident: Vec<$item: ty>) => {
+        /// The list response converts in to the page of the domain items
+        impl FromProto for $type {
+            type Result = $crate::page::Page<$item>;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                Ok($crate::page::Page {
+                    items: self
+                        .$field
+                        .from_proto()
+                        .map_err(|error| $crate::error::in_field(error, stringify!($field)))?,
+                    next_token: $crate::page::next_token(self.next_page_token),
+                    total: $crate::page::total(self.total_size)?,
+                })
+            }
+        }
+
+        impl ToProtoAlias<$type> for $crate::page::Page<$item> {
+            fn to_proto_alias(&self) -> $type {
+                $type {
+                    $field: ToProto::to_proto(&self.items),
+                    next_page_token: self.next_token.clone().unwrap_or_default(),
+                    total_size: $crate::page::total_size(self.total),
+                }
+            }
+        }
+
+        /// The optional list response of the fields
+        impl ToProtoAlias<Option<$type>> for $crate::page::Page<$item> {
+            fn to_proto_alias(&self) -> Option<$type> {
+                Some(ToProtoAlias::<$type>::to_proto_alias(self))
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::Page;
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoUser {
+        age: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct User {
+        age: u8,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoListUsersResponse {
+        items: Vec<ProtoUser>,
+        next_page_token: String,
+        total_size: i32,
+    }
+
+    impl_traits!(prelude);
+    impl_proto_page!(ProtoListUsersResponse, items: Vec<User>);
+
+    impl FromProto for ProtoUser {
+        type Result = User;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            let age = u8::try_from(self.age)
+                .map_err(|_| anyhow::anyhow!("The age {} is invalid", self.age))?;
+            Ok(User { age })
+        }
+    }
+
+    impl ToProto for User {
+        type Result = ProtoUser;
+
+        fn to_proto(&self) -> Self::Result {
+            ProtoUser {
+                age: self.age.into(),
+            }
+        }
+    }
+
+    fn response(ages: &[i64], token: &str, total: i32) -> ProtoListUsersResponse {
+        ProtoListUsersResponse {
+            items: ages.iter().map(|&age| ProtoUser { age }).collect(),
+            next_page_token: token.to_owned(),
+            total_size: total,
+        }
+    }
+
+    #[test]
+    fn test_page() {
+        let proto = response(&[30, 40], "abc", 7);
+        let page = proto.clone().from_proto().unwrap();
+        assert_eq!(
+            page,
+            Page {
+                items: vec![User { age: 30 }, User { age: 40 }],
+                next_token: Some("abc".to_owned()),
+                total: Some(7),
+            }
+        );
+        assert!(!page.is_last());
+        assert_eq!(
+            ToProtoAlias::<ProtoListUsersResponse>::to_proto_alias(&page),
+            proto
+        );
+    }
+
+    #[test]
+    fn test_last_page() {
+        let page = response(&[], "", 0).from_proto().unwrap();
+        assert!(page.is_last());
+        assert!(page.items.is_empty());
+        let page = Page::<User>::default();
+        assert_eq!(
+            ToProtoAlias::<Option<ProtoListUsersResponse>>::to_proto_alias(&page),
+            Some(response(&[], "", 0))
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let error = response(&[30], "", -1).from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "total_size: The total size -1 is negative"
+        );
+        let error = response(&[30, 300], "", 2).from_proto().unwrap_err();
+        assert_eq!(error.to_string(), "items[1]: The age 300 is invalid");
+    }
+}
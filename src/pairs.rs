@@ -0,0 +1,288 @@
+//! The maps sent as the repeated key and value pairs, instead of the proto maps, like
+//! ```proto
+//! message Label {
+//!   string key = 1;
+//!   LabelValue value = 2;
+//! }
+//! message Resource {
+//!   repeated Label labels = 1;
+//! }
+//! ```
+//! converted in to `HashMap<String, T>` and `BTreeMap<String, T>` with `impl_proto_pairs!`, where `key`
+//! and `value` are the names of the fields of the pair and `T` is the domain value, converted the same
+//! as the field of the derive.
+//! ```ignore
+//! impl_proto_pairs!(proto::Label { key, value } => LabelValue);
+//! ```
+//! Into proto the pairs are canonical, sorted by the key in the byte order of the UTF-8, which is the
+//! order of `str`, so the same map is always the same proto message and the same bytes, whatever the
+//! order of the iteration of the `HashMap`, for the byte level diffs and the caches of the encoded
+//! messages. The `IndexMap` of the `indexmap` feature keeps the order of the insertion instead, in both
+//! directions.
+//!
+//! From proto the keys duplicated in the pairs are `ErrorCode::Invalid`, listing all the duplicated keys,
+//! the values which do not convert are the errors of their key.
+
+use std::collections::BTreeSet;
+
+use crate::error::at_key;
+use crate::violation::{coded, ErrorCode};
+
+/// The map of the `pairs` of the key and the converted value, the duplicated keys are an error, used by
+/// `impl_proto_pairs!`
+#[doc(hidden)]
+pub fn collect<M, P, T>(
+    pairs: Vec<P>,
+    mut convert: impl FnMut(P) -> (String, anyhow::Result<T>),
+) -> anyhow::Result<M>
+where
+    M: Default + Extend<(String, T)>,
+{
+    let mut seen = BTreeSet::new();
+    let mut duplicated = BTreeSet::new();
+    let mut map = M::default();
+    for pair in pairs {
+        let (key, value) = convert(pair);
+        if let Err(error) = crate::budget::element() {
+            return Err(at_key(error, &key));
+        }
+        let value = value.map_err(|error| at_key(error, &key))?;
+        if seen.contains(&key) {
+            duplicated.insert(key);
+            continue;
+        }
+        seen.insert(key.clone());
+        map.extend([(key, value)]);
+    }
+    if duplicated.is_empty() {
+        return Ok(map);
+    }
+    let keys = duplicated
+        .iter()
+        .map(|key| format!("`{key}`"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Err(coded(
+        ErrorCode::Invalid,
+        if duplicated.len() == 1 {
+            format!("The key {keys} is duplicated")
+        } else {
+            format!("The keys {keys} are duplicated")
+        },
+    ))
+}
+
+/// The entries sorted by the key in the byte order, used by `impl_proto_pairs!`
+#[doc(hidden)]
+pub fn sorted<'a, T>(
+    entries: impl IntoIterator<Item = (&'a String, &'a T)>,
+) -> Vec<(&'a String, &'a T)> {
+    let mut entries: Vec<_> = entries.into_iter().collect();
+    entries.sort_unstable_by(|(left, _), (right, _)| left.as_bytes().cmp(right.as_bytes()));
+    entries
+}
+
+/// Implement `FromProtoAlias` and `ToProtoAlias` of the repeated pairs of the key and the value for
+/// `HashMap<String, T>` and `BTreeMap<String, T>`, and `IndexMap<String, T>` with the `indexmap`
+/// feature, the traits of the `impl_traits!(prelude)` have to be in the scope.
+/// ```ignore
+/// impl_proto_pairs!(proto::Label { key, value } => LabelValue);
+/// ```
+/// The pairs are written sorted by the key, see `pairs`.
+#[macro_export]
+macro_rules! impl_proto_pairs {
+    ($pair: path { $key: ident, $value: ident $(,)? } => $rust: ty) => {
+        $crate::impl_proto_pairs!(@map $pair { $key, $value }, ::std::collections::HashMap<String, $rust>, sorted);
+        $crate::impl_proto_pairs!(@map $pair { $key, $value }, ::std::collections::BTreeMap<String, $rust>, sorted);
+        $crate::__impl_pairs_indexmap!($pair { $key, $value } => $rust);
+    };
+    (@map $pair: path { $key: ident, $value: ident }, $map: ty, $order: ident) => {
+        /// The duplicated keys are an error
+        impl FromProtoAlias<Vec<$pair>> for $map {
+            fn from_proto_alias(value: Vec<$pair>) -> anyhow::Result<Self> {
+                $crate::pairs::collect(value, |pair| {
+                    (
+                        pair.$key,
+                        FromProtoField::from_proto_field(pair.$value, stringify!($value)),
+                    )
+                })
+            }
+        }
+
+        impl ToProtoAlias<Vec<$pair>> for $map {
+            fn to_proto_alias(&self) -> Vec<$pair> {
+                $crate::impl_proto_pairs!(@$order self)
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let mut pair = <$pair as ::core::default::Default>::default();
+                        pair.$key = key.clone();
+                        pair.$value = ToProtoField::to_proto_field(value);
+                        pair
+                    })
+                    .collect()
+            }
+        }
+    };
+    (@sorted $map: ident) => {
+        $crate::pairs::sorted($map)
+    };
+    (@inserted $map: ident) => {
+        $map
+    };
+}
+
+/// Implement the pairs of `IndexMap`, in the order of the insertion.
+#[cfg(feature = "indexmap")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_pairs_indexmap {
+    ($pair: path { $key: ident, $value: ident } => $rust: ty) => {
+        $crate::impl_proto_pairs!(
+            @map $pair { $key, $value },
+            $crate::__private::indexmap::IndexMap<String, $rust>,
+            inserted
+        );
+    };
+}
+
+/// Without the `indexmap` feature the pairs convert only in to the sorted maps.
+#[cfg(not(feature = "indexmap"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_pairs_indexmap {
+    ($($pairs: tt)*) => {};
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use super::*;
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    #[derive(Clone, PartialEq)]
+    #[cfg_attr(feature = "prost", derive(prost::Message))]
+    #[cfg_attr(not(feature = "prost"), derive(Debug, Default))]
+    pub struct ProtoLabel {
+        #[cfg_attr(feature = "prost", prost(string, tag = "1"))]
+        key: String,
+        #[cfg_attr(feature = "prost", prost(uint32, tag = "2"))]
+        value: u32,
+    }
+
+    #[derive(Clone, PartialEq)]
+    #[cfg_attr(feature = "prost", derive(prost::Message))]
+    #[cfg_attr(not(feature = "prost"), derive(Debug, Default))]
+    pub struct ProtoResource {
+        #[cfg_attr(feature = "prost", prost(message, repeated, tag = "1"))]
+        labels: Vec<ProtoLabel>,
+    }
+
+    impl_proto_pairs!(ProtoLabel { key, value } => u32);
+
+    fn label(key: &str, value: u32) -> ProtoLabel {
+        ProtoLabel {
+            key: key.to_owned(),
+            value,
+        }
+    }
+
+    const ENTRIES: [(&str, u32); 6] = [
+        ("zone", 1),
+        ("Zone", 2),
+        ("app", 3),
+        ("ápp", 4),
+        ("app.kubernetes.io/name", 5),
+        ("", 6),
+    ];
+
+    fn labels(entries: impl IntoIterator<Item = (&'static str, u32)>) -> Vec<ProtoLabel> {
+        let map: HashMap<String, u32> = entries
+            .into_iter()
+            .map(|(key, value)| (key.to_owned(), value))
+            .collect();
+        map.to_proto_alias()
+    }
+
+    #[test]
+    fn test_sorted() {
+        // the byte order of the UTF-8, the upper case before the lower case and the accents last
+        assert_eq!(
+            labels(ENTRIES),
+            [
+                label("", 6),
+                label("Zone", 2),
+                label("app", 3),
+                label("app.kubernetes.io/name", 5),
+                label("zone", 1),
+                label("ápp", 4),
+            ]
+        );
+        let map: BTreeMap<String, u32> = ENTRIES
+            .iter()
+            .map(|(key, value)| (key.to_string(), *value))
+            .collect();
+        assert_eq!(
+            ToProtoAlias::<Vec<ProtoLabel>>::to_proto_alias(&map),
+            labels(ENTRIES)
+        );
+    }
+
+    #[cfg(feature = "prost")]
+    #[test]
+    fn test_deterministic_bytes() {
+        use prost::Message;
+
+        let encode = |entries: Vec<(&'static str, u32)>| {
+            ProtoResource {
+                labels: labels(entries),
+            }
+            .encode_to_vec()
+        };
+        let bytes = encode(ENTRIES.to_vec());
+        // the maps of the other insertion orders and hash seeds
+        for shift in 0..ENTRIES.len() {
+            let mut entries = ENTRIES.to_vec();
+            entries.rotate_left(shift);
+            assert_eq!(encode(entries.clone()), bytes);
+            entries.reverse();
+            assert_eq!(encode(entries), bytes);
+        }
+    }
+
+    #[test]
+    fn test_duplicated_keys() {
+        let pairs = vec![
+            label("team", 1),
+            label("tier", 2),
+            label("team", 3),
+            label("app", 4),
+            label("tier", 5),
+            label("team", 6),
+        ];
+        let error = HashMap::<String, u32>::from_proto_alias(pairs).unwrap_err();
+        assert_eq!(error.to_string(), "The keys `team`, `tier` are duplicated");
+        assert_eq!(ErrorCode::of(&error), ErrorCode::Invalid);
+        let error = BTreeMap::<String, u32>::from_proto_alias(vec![label("a", 1), label("a", 1)])
+            .unwrap_err();
+        assert_eq!(error.to_string(), "The key `a` is duplicated");
+
+        let map = HashMap::<String, u32>::from_proto_alias(labels(ENTRIES)).unwrap();
+        assert_eq!(map.len(), ENTRIES.len());
+        assert_eq!(map["ápp"], 4);
+    }
+
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn test_insertion_order() {
+        use indexmap::IndexMap;
+
+        let pairs = vec![label("zone", 1), label("app", 2), label("Zone", 3)];
+        let map = IndexMap::<String, u32>::from_proto_alias(pairs.clone()).unwrap();
+        assert_eq!(map.keys().collect::<Vec<_>>(), ["zone", "app", "Zone"]);
+        assert_eq!(ToProtoAlias::<Vec<ProtoLabel>>::to_proto_alias(&map), pairs);
+    }
+}
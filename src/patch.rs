@@ -0,0 +1,234 @@
+//! The fields of the patch requests, which are either left as they are, cleared or set, converted in to
+//! `Patch`. The proto3 `optional` fields, `Option<T>` of prost, convert in to `Unchanged` when they are
+//! missing and `Set` when they are there, there is no clear. The nullable wrapper messages,
+//! ```proto
+//! message NullableString {
+//!   bool null = 1;
+//!   string value = 2;
+//! }
+//! ```
+//! in the optional message fields convert in to all three with
+//! `impl_proto_patch!(NullableString, null, value: String)`, the missing message is `Unchanged`, the
+//! message of the `null` flag is `Clear` and the other ones are `Set`.
+//! ```ignore
+//! let patch: Patch<String> = FromProtoAlias::from_proto_alias(request.nickname)?;
+//! patch.apply(&mut user.nickname);
+//! ```
+
+/// The change of a field by a patch request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Patch<T> {
+    /// The field is left as it is
+    #[default]
+    Unchanged,
+    /// The field is cleared
+    Clear,
+    /// The field is set to the value
+    Set(T),
+}
+
+impl<T> Patch<T> {
+    /// The field is left as it is
+    pub fn is_unchanged(&self) -> bool {
+        matches!(self, Patch::Unchanged)
+    }
+
+    /// Convert the value to be set
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Patch<U> {
+        match self {
+            Patch::Unchanged => Patch::Unchanged,
+            Patch::Clear => Patch::Clear,
+            Patch::Set(value) => Patch::Set(f(value)),
+        }
+    }
+
+    /// Apply the change to the optional `target`, clearing sets it to `None`
+    pub fn apply(self, target: &mut Option<T>) {
+        match self {
+            Patch::Unchanged => {}
+            Patch::Clear => *target = None,
+            Patch::Set(value) => *target = Some(value),
+        }
+    }
+
+    /// Apply the change to the `target`, clearing sets it to the default
+    pub fn apply_or_default(self, target: &mut T)
+    where
+        T: Default,
+    {
+        match self {
+            Patch::Unchanged => {}
+            Patch::Clear => *target = T::default(),
+            Patch::Set(value) => *target = value,
+        }
+    }
+}
+
+/// Implement the `FromProtoAlias` of the optional nullable wrapper message in to `Patch` and
+/// `ToProtoAlias` of the `Patch` back, the traits of the `impl_traits!(prelude)` have to be in the
+/// scope.
+/// ```ignore
+/// impl_proto_patch!(NullableString, null, value: String);
+/// ```
+/// The `null` flag of the message is the clear, the `value` field converts in to the value to be set.
+/// The value of the cleared message is written as the default.
+#[macro_export]
+macro_rules! impl_proto_patch {
+    ($type: ident, $flag: ident, $field: ident: $item: ty) => {
+        /// The wrapper message is the clear with the flag, the value is set without it
+        impl FromProtoAlias<Option<$type>> for $crate::patch::Patch<$item> {
+            fn from_proto_alias(value: Option<$type>) -> anyhow::Result<Self> {
+                match value {
+                    None => Ok($crate::patch::Patch::Unchanged),
+                    Some(value) if value.$flag => Ok($crate::patch::Patch::Clear),
+                    Some(value) => {
+                        let value: $item =
+                            FromProtoField::from_proto_field(value.$field, stringify!($field))?;
+                        Ok($crate::patch::Patch::Set(value))
+                    }
+                }
+            }
+        }
+
+        impl ToProtoAlias<Option<$type>> for $crate::patch::Patch<$item> {
+            fn to_proto_alias(&self) -> Option<$type> {
+                match self {
+                    $crate::patch::Patch::Unchanged => None,
+                    $crate::patch::Patch::Clear => Some($type {
+                        $flag: true,
+                        $field: Default::default(),
+                    }),
+                    $crate::patch::Patch::Set(value) => Some($type {
+                        $flag: false,
+                        $field: ToProtoField::to_proto_field(value),
+                    }),
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::Patch;
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoNullableString {
+        null: bool,
+        value: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoLimit {
+        value: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoNullableLimit {
+        null: bool,
+        limit: Option<ProtoLimit>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Limit(u8);
+
+    impl_traits!(prelude);
+    impl_proto_patch!(ProtoNullableString, null, value: String);
+    impl_proto_patch!(ProtoNullableLimit, null, limit: Limit);
+
+    impl FromProto for ProtoLimit {
+        type Result = Limit;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            let value = u8::try_from(self.value)
+                .map_err(|_| anyhow::anyhow!("The limit {} is invalid", self.value))?;
+            Ok(Limit(value))
+        }
+    }
+
+    impl ToProto for Limit {
+        type Result = ProtoLimit;
+
+        fn to_proto(&self) -> Self::Result {
+            ProtoLimit {
+                value: self.0.into(),
+            }
+        }
+    }
+
+    fn from_optional(proto: Option<i64>) -> Patch<i64> {
+        FromProtoAlias::from_proto_alias(proto).unwrap()
+    }
+
+    #[test]
+    fn test_optional() {
+        assert_eq!(from_optional(None), Patch::Unchanged);
+        assert_eq!(from_optional(Some(0)), Patch::Set(0));
+        assert_eq!(Patch::<i64>::Unchanged.to_proto(), None);
+        assert_eq!(Patch::Set(7i64).to_proto(), Some(7));
+        // there is no clear, it is the default value
+        assert_eq!(Patch::<i64>::Clear.to_proto(), Some(0));
+
+        let patch: anyhow::Result<Patch<Limit>> =
+            FromProtoAlias::from_proto_alias(Some(ProtoLimit { value: 300 }));
+        assert_eq!(patch.unwrap_err().to_string(), "The limit 300 is invalid");
+    }
+
+    fn nullable(null: bool, value: &str) -> Option<ProtoNullableString> {
+        Some(ProtoNullableString {
+            null,
+            value: value.to_owned(),
+        })
+    }
+
+    #[test]
+    fn test_nullable() {
+        let from_proto =
+            |proto| -> Patch<String> { FromProtoAlias::from_proto_alias(proto).unwrap() };
+        let to_proto = ToProtoAlias::<Option<ProtoNullableString>>::to_proto_alias;
+        assert_eq!(from_proto(None), Patch::Unchanged);
+        assert_eq!(from_proto(nullable(true, "ignored")), Patch::Clear);
+        assert_eq!(from_proto(nullable(false, "")), Patch::Set(String::new()));
+        assert_eq!(to_proto(&Patch::Unchanged), None);
+        assert_eq!(to_proto(&Patch::Clear), nullable(true, ""));
+        assert_eq!(
+            to_proto(&Patch::Set("eve".to_owned())),
+            nullable(false, "eve")
+        );
+
+        let proto = |limit| Some(ProtoNullableLimit { null: false, limit });
+        let patch: Patch<Limit> =
+            FromProtoAlias::from_proto_alias(proto(Some(ProtoLimit { value: 5 }))).unwrap();
+        assert_eq!(patch, Patch::Set(Limit(5)));
+        let error = <Patch<Limit> as FromProtoAlias<_>>::from_proto_alias(proto(None)).unwrap_err();
+        assert_eq!(error.to_string(), "limit: Required is missing");
+        assert_eq!(
+            ToProtoAlias::<Option<ProtoNullableLimit>>::to_proto_alias(&Patch::Clear),
+            Some(ProtoNullableLimit {
+                null: true,
+                limit: None
+            })
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut nickname = Some("eve".to_owned());
+        Patch::Unchanged.apply(&mut nickname);
+        assert_eq!(nickname.as_deref(), Some("eve"));
+        Patch::Set("bob".to_owned()).apply(&mut nickname);
+        assert_eq!(nickname.as_deref(), Some("bob"));
+        Patch::Clear.apply(&mut nickname);
+        assert_eq!(nickname, None);
+
+        let mut retries = 3u32;
+        Patch::Set(5).apply_or_default(&mut retries);
+        assert_eq!(retries, 5);
+        Patch::Clear.apply_or_default(&mut retries);
+        assert_eq!(retries, 0);
+        assert!(Patch::<u32>::default().is_unchanged());
+        assert_eq!(Patch::Set(2).map(|value| value * 2), Patch::Set(4));
+    }
+}
@@ -0,0 +1,119 @@
+//! Conversions between two domain types of the same proto type, going through the proto value. The
+//! prelude has `pivot_convert`, `pivot_convert_owned` and the `via_proto` method of all the `ToProto`
+//! types.
+//! ```ignore
+//! let internal: InternalUser = wire_user.via_proto()?;
+//! ```
+
+/// Add the types of the pivot to the error of converting the proto value
+#[doc(hidden)]
+pub fn context<A: ?Sized, P: ?Sized, B: ?Sized>(error: anyhow::Error) -> anyhow::Error {
+    error.context(format!(
+        "While pivoting {} -> {} -> {}",
+        std::any::type_name::<A>(),
+        std::any::type_name::<P>(),
+        std::any::type_name::<B>()
+    ))
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoUser {
+        pub name: String,
+        pub age: Option<u32>,
+    }
+
+    /// The user as it is sent to the clients
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct WireUser {
+        pub name: String,
+        pub age: Option<u32>,
+    }
+
+    /// The user with the required age
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct User {
+        pub name: String,
+        pub age: u32,
+    }
+
+    impl ToProto for WireUser {
+        type Result = ProtoUser;
+
+        fn to_proto(&self) -> Self::Result {
+            ProtoUser {
+                name: self.name.clone(),
+                age: self.age,
+            }
+        }
+    }
+
+    impl FromProto for ProtoUser {
+        type Result = User;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(User {
+                name: self.name,
+                age: self.age.from_proto_field("age")?,
+            })
+        }
+    }
+
+    fn wire(name: &str, age: Option<u32>) -> WireUser {
+        WireUser {
+            name: name.to_string(),
+            age,
+        }
+    }
+
+    #[test]
+    fn test_pivot() {
+        let user: User = pivot_convert(&wire("Ann", Some(30))).unwrap();
+        assert_eq!(
+            user,
+            User {
+                name: "Ann".to_string(),
+                age: 30
+            }
+        );
+        assert_eq!(wire("Ann", Some(30)).via_proto().unwrap(), user);
+        assert_eq!(pivot_convert_owned(wire("Ann", Some(30))).unwrap(), user);
+
+        let error = wire("Bob", None).via_proto::<User>().unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "While pivoting proto_conve::pivot::tests::WireUser -> proto_conve::pivot::tests::ProtoUser \
+             -> proto_conve::pivot::tests::User: age: Required is missing"
+        );
+    }
+
+    #[test]
+    fn test_pivot_containers() {
+        let users = vec![wire("Ann", Some(30)), wire("Bob", Some(40))];
+        let converted: Vec<User> = users.via_proto().unwrap();
+        assert_eq!(converted[1].age, 40);
+
+        let users = vec![wire("Ann", Some(30)), wire("Bob", None)];
+        let error = users.via_proto::<Vec<User>>().unwrap_err();
+        assert_eq!(
+            error.root_cause().to_string(),
+            "[1].age: Required is missing"
+        );
+        assert!(error
+            .to_string()
+            .starts_with("While pivoting alloc::vec::Vec<"));
+
+        let user: Option<User> = pivot_convert(&Some(wire("Ann", Some(30)))).unwrap();
+        assert_eq!(user.unwrap().name, "Ann");
+        assert_eq!(
+            pivot_convert::<Option<WireUser>, _, _>(&None).unwrap(),
+            None
+        );
+    }
+}
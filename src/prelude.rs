@@ -0,0 +1,39 @@
+//! The wrappers, the common types and the derive macros of the crate, to be imported all at once. The
+//! traits of the conversions are defined by `impl_traits!(prelude)` in your crate, its `prelude`
+//! module has them with all of this, so a single import brings every conversion in to scope.
+//! ```ignore
+//! mod proto {
+//!     proto_conve::impl_traits!(prelude);
+//! }
+//!
+//! use crate::proto::prelude::*;
+//! ```
+//! The methods of the traits in it have distinct names, so they are not ambiguous for the types having
+//! several of the traits, like `to_proto` of `ToProto` and `to_proto_alias` of `ToProtoAlias`.
+
+pub use crate::chunked::ErrorMode;
+#[cfg(feature = "contact")]
+pub use crate::contact::{PhoneE164, PostalAddress};
+pub use crate::deprecation::DeprecatedField;
+pub use crate::hash::ProtoHashable;
+pub use crate::lossy::ConversionReport;
+pub use crate::mask::FieldMaskSet;
+pub use crate::mode::ConversionMode;
+pub use crate::proto_default::ProtoDefault;
+pub use crate::redact::{ProtoRedact, Redacted, RedactedDebug};
+pub use crate::version::SchemaVersion;
+pub use crate::{
+    Bounded, Deadline, Decimal, FloatToScaledInt, OmitIf, OpenEnum, Page, Patch, Proto, ProtoBytes,
+    ProtoEnumField, Scaled,
+};
+
+// the derive macros have the names of their traits, which are in the `prelude` of `impl_traits!`
+#[cfg(all(feature = "derive", feature = "cbor"))]
+pub use crate::ProtoCbor;
+#[cfg(all(feature = "derive", feature = "text-format"))]
+pub use crate::TextProto;
+#[cfg(feature = "derive")]
+pub use crate::{
+    FromProto, FromProtoLossy, FromProtoVersioned, IntoProto, MergeFromProto, ProtoDefault,
+    ProtoHashable, ToProto, ToProtoBuilder,
+};
@@ -0,0 +1,174 @@
+//! The wrapper of the proto values, which get another conversion than the one of their type. The
+//! traits of `impl_traits!(prelude)` are defined in the crate using the macro, so they can be
+//! implemented there for the foreign types too, but only once for every type, and a `Vec` or `Option`
+//! of them is already covered by the blanket impls. The conversions of the `Proto` wrapper are another
+//! ones, like of a proto type of another library, which has its `FromProto` already, in to the rust
+//! type of yet another one.
+//! ```ignore
+//! impl FromProto for Proto<money::Money> {
+//!     type Result = ledger::Amount;
+//!
+//!     fn from_proto(self) -> anyhow::Result<Self::Result> {
+//!         ledger::Amount::from_units(self.units, self.nanos)
+//!     }
+//! }
+//!
+//! impl ToProto for Proto<&ledger::Amount> {
+//!     type Result = money::Money;
+//!
+//!     fn to_proto(&self) -> Self::Result {
+//!         self.split_units()
+//!     }
+//! }
+//!
+//! let amounts: Vec<ledger::Amount> = proto.amounts.from_proto_wrapped()?;
+//! let proto: Vec<money::Money> = Proto(&amounts).to_proto();
+//! ```
+//! The values are converted in to proto by reference, so the `ToProto` is implemented for the wrapped
+//! reference. The wrapped `Vec` and `Option` are converted element wise, through the conversions of the
+//! wrapped elements, `from_proto_wrapped` of `FromProtoWrapped` converts them without the wrapping.
+//!
+//! The crates using the traits of another crate, which has the prelude, can not implement them for the
+//! foreign types, and neither for `Proto` of them, as it is not their type either. They need their own
+//! wrapper type.
+
+use std::ops::{Deref, DerefMut};
+
+/// The proto value, which has the conversions of the wrapper instead of its own
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Proto<T>(pub T);
+
+impl<T> Proto<T> {
+    /// The wrapped value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Proto<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Deref for Proto<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Proto<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> AsRef<T> for Proto<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::Proto;
+    use crate::impl_traits;
+
+    /// The proto types of a library
+    mod money {
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct Money {
+            pub units: i64,
+            pub nanos: i32,
+        }
+    }
+
+    /// The rust types of another library
+    mod ledger {
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        pub struct Amount {
+            pub nanos: i128,
+        }
+    }
+
+    impl_traits!(prelude);
+
+    /// The conversion of the type itself, in to the units
+    impl FromProto for money::Money {
+        type Result = i64;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(self.units)
+        }
+    }
+
+    impl FromProto for Proto<money::Money> {
+        type Result = ledger::Amount;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            if self.nanos.unsigned_abs() >= 1_000_000_000 {
+                anyhow::bail!("The nanos {} are out of range", self.nanos);
+            }
+            let nanos = i128::from(self.units) * 1_000_000_000 + i128::from(self.nanos);
+            Ok(ledger::Amount { nanos })
+        }
+    }
+
+    impl ToProto for Proto<&ledger::Amount> {
+        type Result = money::Money;
+
+        fn to_proto(&self) -> Self::Result {
+            money::Money {
+                units: (self.nanos / 1_000_000_000) as i64,
+                nanos: (self.nanos % 1_000_000_000) as i32,
+            }
+        }
+    }
+
+    fn money(units: i64, nanos: i32) -> money::Money {
+        money::Money { units, nanos }
+    }
+
+    #[test]
+    fn test_wrapper() {
+        let amount = Proto(money(3, 500)).from_proto().unwrap();
+        assert_eq!(amount.nanos, 3_000_000_500);
+        assert_eq!(Proto(&amount).to_proto(), money(3, 500));
+        // the type keeps its own conversion
+        assert_eq!(money(3, 500).from_proto().unwrap(), 3);
+        assert_eq!(money(3, 500).from_proto_wrapped().unwrap(), amount);
+
+        let mut wrapped = Proto::from(money(1, 0));
+        wrapped.units += 1;
+        assert_eq!(wrapped.units, 2);
+        assert_eq!(wrapped.into_inner(), money(2, 0));
+    }
+
+    #[test]
+    fn test_containers() {
+        let protos = vec![money(1, 0), money(-2, -5)];
+        let amounts = protos.clone().from_proto_wrapped().unwrap();
+        assert_eq!(amounts[1].nanos, -2_000_000_005);
+        assert_eq!(Proto(&amounts).to_proto(), protos);
+        // the wrapped elements compose with the impls of the containers too
+        let wrapped: Vec<_> = protos.into_iter().map(Proto).collect();
+        assert_eq!(wrapped.from_proto().unwrap(), amounts);
+
+        let amount = Some(money(0, 7)).from_proto_wrapped().unwrap();
+        assert_eq!(amount, Some(ledger::Amount { nanos: 7 }));
+        assert_eq!(Proto(&amount).to_proto(), Some(money(0, 7)));
+        assert_eq!(None::<money::Money>.from_proto_wrapped().unwrap(), None);
+
+        let error = vec![money(1, 0), money(1, 1_000_000_000)]
+            .from_proto_wrapped()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "[1]: The nanos 1000000000 are out of range"
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! The proto `bytes` fields, which are `Vec<u8>` in prost. They are converted as one buffer, which is
+//! moved and never iterated per byte, so they are not the repeated `u8` values.
+//! ```ignore
+//! struct Attachment {
+//!     name: String,
+//!     content: proto_conve::ProtoBytes,
+//! }
+//! ```
+//! The derive macros convert the `Vec<u8>` fields the same way. `ProtoBytes` converts from and in to
+//! `Vec<u8>` and `Box<[u8]>`, and with the `bytes` feature from and in to `bytes::Bytes`, for the
+//! fields generated as `bytes::Bytes`.
+
+use std::ops::Deref;
+
+/// The buffer of the proto `bytes` field
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct ProtoBytes(Vec<u8>);
+
+impl ProtoBytes {
+    /// The bytes of the field
+    pub const fn new(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    /// The buffer, without copying it
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl Deref for ProtoBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for ProtoBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for ProtoBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<ProtoBytes> for Vec<u8> {
+    fn from(bytes: ProtoBytes) -> Self {
+        bytes.0
+    }
+}
+
+impl From<Box<[u8]>> for ProtoBytes {
+    fn from(bytes: Box<[u8]>) -> Self {
+        Self(bytes.into_vec())
+    }
+}
+
+impl From<ProtoBytes> for Box<[u8]> {
+    fn from(bytes: ProtoBytes) -> Self {
+        bytes.0.into_boxed_slice()
+    }
+}
+
+/// The buffer is copied only when it is shared
+#[cfg(feature = "bytes")]
+impl From<::bytes::Bytes> for ProtoBytes {
+    fn from(bytes: ::bytes::Bytes) -> Self {
+        Self(bytes.into())
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl From<ProtoBytes> for ::bytes::Bytes {
+    fn from(bytes: ProtoBytes) -> Self {
+        bytes.0.into()
+    }
+}
+
+/// Convert the proto field in to the `Vec<u8>` field, used by the derive macros
+#[doc(hidden)]
+pub fn from_proto<P: Into<ProtoBytes>>(proto: P) -> Vec<u8> {
+    proto.into().into_inner()
+}
+
+/// Convert the `Vec<u8>` field in to the proto field, used by the derive macros
+#[doc(hidden)]
+pub fn to_proto<P: From<ProtoBytes>>(bytes: &[u8]) -> P {
+    P::from(ProtoBytes(bytes.to_vec()))
+}
+
+/// Generate the conversions of the `bytes::Bytes` fields, this is part of the `impl_traits!(prelude)`.
+#[cfg(feature = "bytes")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_bytes {
+    () => {
+        impl FromProtoAlias<$crate::__private::bytes::Bytes> for $crate::ProtoBytes {
+            fn from_proto_alias(value: $crate::__private::bytes::Bytes) -> anyhow::Result<Self> {
+                Ok(value.into())
+            }
+        }
+
+        impl ToProtoAlias<$crate::__private::bytes::Bytes> for $crate::ProtoBytes {
+            fn to_proto_alias(&self) -> $crate::__private::bytes::Bytes {
+                self.clone().into()
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    #[test]
+    fn test_moved() {
+        let proto = vec![7u8; 4096];
+        let pointer = proto.as_ptr();
+        let bytes = ProtoBytes::from(proto).from_proto().unwrap();
+        assert_eq!(bytes.as_ptr(), pointer);
+        assert_eq!(bytes.len(), 4096);
+
+        let bytes = ProtoBytes::from_proto_alias(bytes.into_inner()).unwrap();
+        assert_eq!(bytes.as_ptr(), pointer);
+        let vec = from_proto(bytes);
+        assert_eq!(vec.as_ptr(), pointer);
+    }
+
+    #[test]
+    fn test_boxed() {
+        let boxed: Box<[u8]> = Box::from(&b"proto"[..]);
+        let pointer = boxed.as_ptr();
+        let bytes = ProtoBytes::from(boxed);
+        assert_eq!(bytes.as_ptr(), pointer);
+        assert_eq!(&*bytes, b"proto");
+        let proto: Vec<u8> = ToProtoAlias::to_proto_alias(&bytes);
+        assert_eq!(proto, b"proto");
+        assert_eq!(Box::<[u8]>::from(bytes).as_ref(), b"proto");
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_bytes() {
+        let proto = ::bytes::Bytes::from(vec![3u8; 16]);
+        let pointer = proto.as_ptr();
+        let bytes = ProtoBytes::from_proto_alias(proto).unwrap();
+        assert_eq!(bytes.as_ptr(), pointer);
+        let proto: ::bytes::Bytes = ToProtoAlias::to_proto_alias(&bytes);
+        assert_eq!(&proto[..], &[3u8; 16]);
+    }
+}
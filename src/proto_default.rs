@@ -0,0 +1,247 @@
+//! The default proto values, checked without constructing them. `ProtoDefault::proto_default` is the
+//! shared instance of the type, in a static, and `ProtoDefault::is_proto_default` checks the value
+//! field wise, so asking whether a message with the strings and the lists is the default allocates
+//! nothing, unlike comparing it with a fresh `Default::default()`.
+//! ```ignore
+//! #[derive(ProtoDefault)]
+//! pub struct Settings { ... }
+//!
+//! if settings.is_proto_default() { ... }
+//! ```
+//! The proto messages derive `ProtoDefault`, the prost ones with the `type_attribute` of
+//! `#[derive(::proto_conve::ProtoDefault)]`, and the hand-written ones implement it with
+//! `impl_proto_default!(Settings { name, labels })`, listing all of their fields. The defaults are the
+//! ones of the wire:
+//! - zero of the integers and the floats, also `-0.0`, but not NaN, and `false`
+//! - the empty strings, bytes, repeated fields and maps
+//! - the absent optional values, `Some` of the default value is not the default, it is present
+//! - the messages with all of their fields at the default
+//!
+//! The `skip_default` fields of the derived conversions, `ToProtoNonDefault` and the drift detection of
+//! `#[proto(required_nonzero)]` check the proto values with it.
+
+use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{OnceLock, RwLock};
+
+/// The proto value with the shared default instance
+pub trait ProtoDefault: 'static {
+    /// The default value, the same instance for all the calls
+    fn proto_default() -> &'static Self;
+
+    /// The value is the default, checked without constructing the default
+    fn is_proto_default(&self) -> bool;
+}
+
+macro_rules! default_scalars {
+    ($($type: ty: $zero: expr),*) => {
+        $(
+            impl ProtoDefault for $type {
+                fn proto_default() -> &'static Self {
+                    &$zero
+                }
+
+                fn is_proto_default(&self) -> bool {
+                    *self == $zero
+                }
+            }
+        )*
+    };
+}
+
+// `-0.0 == 0.0`, and NaN is not equal to it
+default_scalars!(i32: 0, i64: 0, u32: 0, u64: 0, f32: 0.0, f64: 0.0);
+
+impl ProtoDefault for bool {
+    fn proto_default() -> &'static Self {
+        &false
+    }
+
+    fn is_proto_default(&self) -> bool {
+        !*self
+    }
+}
+
+impl ProtoDefault for String {
+    fn proto_default() -> &'static Self {
+        const { &String::new() }
+    }
+
+    fn is_proto_default(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<T: 'static> ProtoDefault for Vec<T> {
+    fn proto_default() -> &'static Self {
+        const { &Vec::new() }
+    }
+
+    fn is_proto_default(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl ProtoDefault for bytes::Bytes {
+    fn proto_default() -> &'static Self {
+        // the shared bytes are atomic, so they are not constant
+        static DEFAULT: OnceLock<bytes::Bytes> = OnceLock::new();
+        DEFAULT.get_or_init(bytes::Bytes::new)
+    }
+
+    fn is_proto_default(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<T: 'static> ProtoDefault for Option<T> {
+    fn proto_default() -> &'static Self {
+        &None
+    }
+
+    fn is_proto_default(&self) -> bool {
+        self.is_none()
+    }
+}
+
+impl<K: 'static, V: 'static> ProtoDefault for BTreeMap<K, V> {
+    fn proto_default() -> &'static Self {
+        const { &BTreeMap::new() }
+    }
+
+    fn is_proto_default(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<K, V, S> ProtoDefault for HashMap<K, V, S>
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    S: Default + Send + Sync + 'static,
+{
+    fn proto_default() -> &'static Self {
+        leaked_default()
+    }
+
+    fn is_proto_default(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+/// The default of the generic type, which can not be constant, like `HashMap` with its hasher, made
+/// once per type and kept for the whole run
+fn leaked_default<T: Default + Send + Sync + 'static>() -> &'static T {
+    type Defaults = RwLock<HashMap<TypeId, &'static (dyn Any + Send + Sync)>>;
+    static DEFAULTS: OnceLock<Defaults> = OnceLock::new();
+
+    let defaults = DEFAULTS.get_or_init(Default::default);
+    let found = defaults
+        .read()
+        .unwrap_or_else(|error| error.into_inner())
+        .get(&TypeId::of::<T>())
+        .copied();
+    let default = match found {
+        Some(default) => default,
+        None => *defaults
+            .write()
+            .unwrap_or_else(|error| error.into_inner())
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::leak(Box::<T>::default())),
+    };
+    default.downcast_ref().expect("the default of the type")
+}
+
+/// Implement `ProtoDefault` of the hand-written proto message `$type` with all of its `$field`s, the
+/// message implements `Default`.
+/// ```ignore
+/// impl_proto_default!(Settings { name, labels, limit });
+/// ```
+#[macro_export]
+macro_rules! impl_proto_default {
+    ($type: ident { $($field: ident),* $(,)? }) => {
+        impl $crate::proto_default::ProtoDefault for $type {
+            fn proto_default() -> &'static Self {
+                static DEFAULT: ::std::sync::OnceLock<$type> = ::std::sync::OnceLock::new();
+                DEFAULT.get_or_init(::core::default::Default::default)
+            }
+
+            fn is_proto_default(&self) -> bool {
+                // all the fields are listed
+                let $type { $($field),* } = self;
+                true $(&& $crate::proto_default::ProtoDefault::is_proto_default($field))*
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use super::ProtoDefault;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoSettings {
+        name: String,
+        labels: HashMap<String, String>,
+        limits: Vec<u32>,
+        ratio: f64,
+        parent: Option<Box<ProtoSettings>>,
+    }
+
+    impl_proto_default!(ProtoSettings {
+        name,
+        labels,
+        limits,
+        ratio,
+        parent
+    });
+
+    #[test]
+    fn test_is_proto_default() {
+        let settings = ProtoSettings::default();
+        assert!(settings.is_proto_default());
+        assert_eq!(ProtoSettings::proto_default(), &settings);
+        assert!(ProtoSettings {
+            ratio: -0.0,
+            ..Default::default()
+        }
+        .is_proto_default());
+
+        assert!(!ProtoSettings {
+            limits: vec![0],
+            ..Default::default()
+        }
+        .is_proto_default());
+        // present, even though it is the default
+        assert!(!ProtoSettings {
+            parent: Some(Box::default()),
+            ..Default::default()
+        }
+        .is_proto_default());
+        assert!(!ProtoSettings {
+            ratio: f64::NAN,
+            ..Default::default()
+        }
+        .is_proto_default());
+    }
+
+    #[test]
+    fn test_static() {
+        assert!(std::ptr::eq(
+            ProtoSettings::proto_default(),
+            ProtoSettings::proto_default()
+        ));
+        assert!(std::ptr::eq(
+            HashMap::<u32, String>::proto_default(),
+            HashMap::<u32, String>::proto_default()
+        ));
+        assert!(HashMap::<u32, String>::proto_default().is_empty());
+        assert!(HashMap::<String, u32>::proto_default().is_empty());
+        assert!(BTreeMap::<u32, String>::proto_default().is_proto_default());
+        assert_eq!(<Option<String>>::proto_default(), &None);
+        assert_eq!(f32::proto_default(), &0.0);
+    }
+}
@@ -0,0 +1,164 @@
+//! The serde representation of the domain values through their proto values, enabled with the `serde`
+//! feature. The proto types have to implement the serde traits, like the ones generated by `pbjson`.
+//!
+//! The prelude gets the `proto_serde` module for the fields and the `ViaProto` wrapper for the whole
+//! values. They are in the module of `impl_traits!(prelude)`, as the traits are.
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Cached {
+//!     #[serde(with = "crate::proto::proto_serde")]
+//!     started: chrono::DateTime<chrono::Utc>,
+//! }
+//!
+//! let json = serde_json::to_string(&proto::ViaProto(user))?;
+//! ```
+//! The values are serialized as their proto value, and the deserialized proto value is converted with
+//! `FromProto`, its errors are the custom serde errors.
+
+/// Generate the serde bridge, this is part of the `impl_traits!(prelude)`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_serde {
+    (prelude) => {
+        pub use super::{proto_serde, ViaProto};
+    };
+    () => {
+        /// Serialize and deserialize the field through its proto value, with
+        /// `#[serde(with = "path::to::proto_serde")]`
+        #[allow(dead_code)]
+        pub mod proto_serde {
+            use super::{FromProto, ToProto};
+            use $crate::__private::serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+            /// Serialize the proto value of the value
+            pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                T: ToProto,
+                T::Result: Serialize,
+                S: Serializer,
+            {
+                value.to_proto().serialize(serializer)
+            }
+
+            /// Deserialize the proto value and convert it in to the value
+            pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+            where
+                T: ToProto,
+                T::Result: FromProto<Result = T> + Deserialize<'de>,
+                D: Deserializer<'de>,
+            {
+                T::Result::deserialize(deserializer)?
+                    .from_proto()
+                    .map_err(|error| de::Error::custom(format!("{error:#}")))
+            }
+        }
+
+        /// The value serialized and deserialized through its proto value
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct ViaProto<T>(pub T);
+
+        impl<T> $crate::__private::serde::Serialize for ViaProto<T>
+        where
+            T: ToProto,
+            T::Result: $crate::__private::serde::Serialize,
+        {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: $crate::__private::serde::Serializer,
+            {
+                proto_serde::serialize(&self.0, serializer)
+            }
+        }
+
+        impl<'de, T> $crate::__private::serde::Deserialize<'de> for ViaProto<T>
+        where
+            T: ToProto,
+            T::Result: FromProto<Result = T> + $crate::__private::serde::Deserialize<'de>,
+        {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: $crate::__private::serde::Deserializer<'de>,
+            {
+                proto_serde::deserialize(deserializer).map(ViaProto)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code, deprecated)]
+mod tests {
+    use crate::impl_traits;
+    use pbjson_types::Timestamp;
+
+    impl_traits!(prelude);
+    // `proto_serde` needs `ToProto`
+    impl_traits!(Timestamp, chrono::DateTime<chrono::Utc>, legacy);
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Cached {
+        #[serde(with = "proto_serde")]
+        started: chrono::DateTime<chrono::Utc>,
+        name: String,
+    }
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct ProtoWindow {
+        start: Option<Timestamp>,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Window {
+        start: chrono::DateTime<chrono::Utc>,
+    }
+
+    impl FromProto for ProtoWindow {
+        type Result = Window;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(Window {
+                start: self.start.from_proto_field("start")?,
+            })
+        }
+    }
+
+    impl ToProto for Window {
+        type Result = ProtoWindow;
+
+        fn to_proto(&self) -> Self::Result {
+            ProtoWindow {
+                start: self.start.to_proto_field(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_field() {
+        let cached = Cached {
+            started: "2023-09-01T12:30:00.250Z".parse().unwrap(),
+            name: "job".to_string(),
+        };
+        let json = serde_json::to_string(&cached).unwrap();
+        assert_eq!(
+            json,
+            r#"{"started":"2023-09-01T12:30:00.250+00:00","name":"job"}"#
+        );
+        assert_eq!(serde_json::from_str::<Cached>(&json).unwrap(), cached);
+    }
+
+    #[test]
+    fn test_via_proto() {
+        let window = ViaProto(Window {
+            start: "2023-09-01T12:30:00Z".parse().unwrap(),
+        });
+        let json = serde_json::to_string(&window).unwrap();
+        assert_eq!(json, r#"{"start":"2023-09-01T12:30:00+00:00"}"#);
+        assert_eq!(
+            serde_json::from_str::<ViaProto<Window>>(&json).unwrap(),
+            window
+        );
+
+        let error = serde_json::from_str::<ViaProto<Window>>(r#"{"start":null}"#).unwrap_err();
+        assert_eq!(error.to_string(), "start: Required is missing");
+    }
+}
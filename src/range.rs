@@ -0,0 +1,110 @@
+//! The messages of the `min` and `max` bounds, like `message Limits { int64 min = 1; int64 max = 2; }`,
+//! converted in to `std::ops::Range` with `impl_traits!(Limits, std::ops::Range<i64>)`, or in to
+//! `std::ops::RangeInclusive`. The `max` is the end of the range, so it is excluded from the `Range`
+//! and included in the `RangeInclusive`.
+//!
+//! The inverted bounds, `min > max`, are an error, in the lenient `ConversionMode` they are swapped.
+//! The empty `Range`, `min == max`, is valid. The float bounds, which are not a number, are always an
+//! error.
+
+use std::fmt;
+
+use crate::mode::ConversionMode;
+
+/// The bounds in order, used by the prelude
+#[doc(hidden)]
+pub fn bounds<T>(min: T, max: T, mode: ConversionMode) -> anyhow::Result<(T, T)>
+where
+    T: PartialOrd + fmt::Display,
+{
+    match min.partial_cmp(&max) {
+        Some(std::cmp::Ordering::Greater) if mode.is_lenient() => Ok((max, min)),
+        Some(std::cmp::Ordering::Greater) => {
+            anyhow::bail!("The range {min} to {max} is inverted, the min is greater than the max")
+        }
+        Some(_) => Ok((min, max)),
+        None => anyhow::bail!("The range {min} to {max} has a bound, which is not a number"),
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use std::ops::{Range, RangeInclusive};
+
+    use crate::impl_traits;
+    use crate::mode::ConversionMode;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoLimits {
+        min: i64,
+        max: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoThresholds {
+        min: f64,
+        max: f64,
+    }
+
+    impl_traits!(prelude);
+    impl_traits!(ProtoLimits, std::ops::Range<i64>);
+    impl_traits!(ProtoThresholds, std::ops::RangeInclusive<f64>);
+
+    #[test]
+    fn test_valid() {
+        let proto = ProtoLimits { min: -5, max: 10 };
+        let range: Range<i64> = proto.clone().from_proto().unwrap();
+        assert_eq!(range, -5..10);
+        assert_eq!(range.to_proto(), proto);
+
+        let empty = ProtoLimits { min: 3, max: 3 }.from_proto().unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.to_proto(), ProtoLimits { min: 3, max: 3 });
+    }
+
+    #[test]
+    fn test_inclusive() {
+        let proto = ProtoThresholds { min: 0.5, max: 0.9 };
+        let range: RangeInclusive<f64> = proto.clone().from_proto().unwrap();
+        assert!(range.contains(&0.9));
+        assert_eq!(range.to_proto(), proto);
+        let point = ProtoThresholds { min: 0.5, max: 0.5 }.from_proto().unwrap();
+        assert!(point.contains(&0.5));
+
+        let range = ProtoLimits { min: 1, max: 5 }.from_proto().unwrap();
+        assert!(!range.contains(&5));
+    }
+
+    #[test]
+    fn test_inverted() {
+        let proto = ProtoLimits { min: 10, max: -5 };
+        let error = proto.clone().from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The range 10 to -5 is inverted, the min is greater than the max"
+        );
+        let range = proto.from_proto_mode(ConversionMode::Lenient).unwrap();
+        assert_eq!(range, -5..10);
+
+        let proto = ProtoThresholds { min: 0.9, max: 0.5 };
+        assert!(proto.clone().from_proto().is_err());
+        assert_eq!(
+            proto.from_proto_mode(ConversionMode::Lenient).unwrap(),
+            0.5..=0.9
+        );
+    }
+
+    #[test]
+    fn test_not_a_number() {
+        let proto = ProtoThresholds {
+            min: f64::NAN,
+            max: 0.5,
+        };
+        let error = proto.from_proto_mode(ConversionMode::Lenient).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The range NaN to 0.5 has a bound, which is not a number"
+        );
+    }
+}
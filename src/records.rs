@@ -0,0 +1,446 @@
+//! The files of the length delimited proto records, with the `prost` feature, like the ones of the
+//! batch pipelines. Every record is the varint length of the message and the encoded message, as
+//! `prost::Message::encode_length_delimited` writes it. `read_proto_records` of the prelude reads the
+//! records of the proto type and converts them in to its domain type, `write_proto_records` converts
+//! the domain values in to proto and writes them.
+//! ```ignore
+//! let mut writer = ProtoRecordWriter::new(BufWriter::new(File::create(path)?));
+//! write_proto_records(&mut writer, &users)?;
+//! writer.flush()?;
+//!
+//! for user in read_proto_records::<proto::User, _>(BufReader::new(File::open(path)?)) {
+//!     let user = user?;
+//! }
+//! ```
+//! The errors are `RecordError` with the index of the record, from zero, and the `RecordErrorKind` of
+//! the failure. The records, which fail to decode or to convert, are skipped with their errors, the
+//! next record is read after them. The errors of the reader and the truncated last record end the
+//! records, there is no next one to be found.
+//!
+//! The zero length records are the messages with all the fields at the default, they are read as the
+//! default of the proto type, which is converted as any other message. The reader takes the bytes
+//! one at a time for the lengths, so wrap the files in `BufReader`.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+
+/// Why the record failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecordErrorKind {
+    /// Reading or writing failed
+    Io,
+    /// The file ended in the record
+    Truncated,
+    /// The record is not the encoded proto message
+    Decode,
+    /// The proto message failed to convert
+    Conversion,
+}
+
+/// The failure of the record at the index
+#[derive(Debug)]
+pub struct RecordError {
+    record: usize,
+    kind: RecordErrorKind,
+    reason: anyhow::Error,
+}
+
+impl RecordError {
+    fn new(record: usize, kind: RecordErrorKind, reason: impl Into<anyhow::Error>) -> Self {
+        Self {
+            record,
+            kind,
+            reason: reason.into(),
+        }
+    }
+
+    /// The index of the record in the file, from zero
+    pub fn record(&self) -> usize {
+        self.record
+    }
+
+    pub fn kind(&self) -> RecordErrorKind {
+        self.kind
+    }
+
+    /// The reason of the failure, for the conversions the `ConversionError` with the path
+    pub fn reason(&self) -> &anyhow::Error {
+        &self.reason
+    }
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self.kind {
+            RecordErrorKind::Io => "Failed to read or write",
+            RecordErrorKind::Truncated => "Truncated",
+            RecordErrorKind::Decode => "Failed to decode",
+            RecordErrorKind::Conversion => "Failed to convert",
+        };
+        write!(f, "{action} the record {}: {:#}", self.record, self.reason)
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+/// The length of the record, `None` at the end of the file before it
+fn read_length(reader: &mut impl Read) -> Result<Option<u64>, (RecordErrorKind, anyhow::Error)> {
+    let mut length = 0u64;
+    for index in 0..10 {
+        let mut byte = [0u8];
+        loop {
+            match reader.read(&mut byte) {
+                Ok(0) if index == 0 => return Ok(None),
+                Ok(0) => {
+                    let error = anyhow::anyhow!("The file ends in the length of the record");
+                    return Err((RecordErrorKind::Truncated, error));
+                }
+                Ok(_) => break,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err((RecordErrorKind::Io, error.into())),
+            }
+        }
+        length |= u64::from(byte[0] & 0x7f) << (7 * index);
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(length));
+        }
+    }
+    let error = anyhow::anyhow!("The length of the record is longer than 10 bytes");
+    Err((RecordErrorKind::Decode, error))
+}
+
+/// The iterator of the domain values of the length delimited records of the proto type `P`
+pub struct ProtoRecordReader<R, P, T> {
+    reader: R,
+    convert: fn(P) -> anyhow::Result<T>,
+    record: usize,
+    buffer: Vec<u8>,
+    done: bool,
+    _proto: PhantomData<fn() -> P>,
+}
+
+impl<R: Read, P, T> ProtoRecordReader<R, P, T> {
+    /// Read the records of the `reader` and convert them with `convert`, see `read_proto_records` of
+    /// the prelude
+    pub fn new(reader: R, convert: fn(P) -> anyhow::Result<T>) -> Self {
+        Self {
+            reader,
+            convert,
+            record: 0,
+            buffer: Vec::new(),
+            done: false,
+            _proto: PhantomData,
+        }
+    }
+
+    /// The number of the records read so far, also the failed ones
+    pub fn records(&self) -> usize {
+        self.record
+    }
+
+    /// The reader, positioned after the last record read
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read, P: prost::Message + Default, T> ProtoRecordReader<R, P, T> {
+    fn read(&mut self) -> Result<Option<P>, (RecordErrorKind, anyhow::Error)> {
+        // the records after the broken length can not be found
+        let length = read_length(&mut self.reader).inspect_err(|_| self.done = true)?;
+        let Some(length) = length else {
+            return Ok(None);
+        };
+        self.buffer.clear();
+        // not allocated up front, the length of a corrupted file can be anything
+        let read = (&mut self.reader)
+            .take(length)
+            .read_to_end(&mut self.buffer)
+            .map_err(|error| (RecordErrorKind::Io, error.into()))?;
+        if (read as u64) < length {
+            let error = anyhow::anyhow!("The file ends after {read} of the {length} bytes");
+            return Err((RecordErrorKind::Truncated, error));
+        }
+        P::decode(self.buffer.as_slice())
+            .map(Some)
+            .map_err(|error| (RecordErrorKind::Decode, error.into()))
+    }
+}
+
+impl<R: Read, P: prost::Message + Default, T> Iterator for ProtoRecordReader<R, P, T> {
+    type Item = anyhow::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let record = self.record;
+        let proto = match self.read() {
+            Ok(Some(proto)) => proto,
+            Ok(None) => {
+                self.done = true;
+                return None;
+            }
+            Err((kind, error)) => {
+                // the next record can not be found after them
+                self.done |= matches!(kind, RecordErrorKind::Io | RecordErrorKind::Truncated);
+                self.record += 1;
+                return Some(Err(RecordError::new(record, kind, error).into()));
+            }
+        };
+        self.record += 1;
+        let result = (self.convert)(proto)
+            .map_err(|error| RecordError::new(record, RecordErrorKind::Conversion, error).into());
+        Some(result)
+    }
+}
+
+/// The writer of the length delimited records
+pub struct ProtoRecordWriter<W> {
+    writer: W,
+    record: usize,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> ProtoRecordWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            record: 0,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Write the `message` as the next record, the default one is the zero length record
+    pub fn write_record<M: prost::Message>(&mut self, message: &M) -> anyhow::Result<()> {
+        self.buffer.clear();
+        message
+            .encode_length_delimited(&mut self.buffer)
+            .expect("the vector grows");
+        self.writer
+            .write_all(&self.buffer)
+            .map_err(|error| RecordError::new(self.record, RecordErrorKind::Io, error))?;
+        self.record += 1;
+        Ok(())
+    }
+
+    /// Write the `values` converted with `to_proto`, returning the number of them, see
+    /// `write_proto_records` of the prelude
+    pub fn write_records<'a, T: 'a, M: prost::Message>(
+        &mut self,
+        values: impl IntoIterator<Item = &'a T>,
+        to_proto: impl Fn(&T) -> M,
+    ) -> anyhow::Result<usize> {
+        let mut written = 0;
+        for value in values {
+            self.write_record(&to_proto(value))?;
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    /// The number of the records written so far
+    pub fn records(&self) -> usize {
+        self.record
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// The writer, the records are not flushed
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// The record files of the prelude
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_records {
+    (prelude) => {
+        pub use super::{read_proto_records, write_proto_records};
+        pub use $crate::records::{ProtoRecordReader, ProtoRecordWriter};
+    };
+    () => {
+        /// Read the length delimited records of `P` converted in to its domain type, see
+        /// `proto_conve::records`
+        #[allow(dead_code)]
+        pub fn read_proto_records<P, R>(
+            reader: R,
+        ) -> $crate::records::ProtoRecordReader<R, P, P::Result>
+        where
+            P: FromProto + $crate::__private::prost::Message + Default,
+            R: std::io::Read,
+        {
+            $crate::records::ProtoRecordReader::new(reader, P::from_proto)
+        }
+
+        /// Write the `values` converted in to proto as the length delimited records, returning the
+        /// number of them
+        #[allow(dead_code)]
+        pub fn write_proto_records<'a, T, W>(
+            writer: &mut $crate::records::ProtoRecordWriter<W>,
+            values: impl IntoIterator<Item = &'a T>,
+        ) -> anyhow::Result<usize>
+        where
+            T: ToProto + 'a,
+            T::Result: $crate::__private::prost::Message,
+            W: std::io::Write,
+        {
+            writer.write_records(values, T::to_proto)
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::{ProtoRecordWriter, RecordError, RecordErrorKind};
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ProtoEvent {
+        #[prost(string, tag = "1")]
+        name: String,
+        #[prost(int64, tag = "2")]
+        count: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Event {
+        name: String,
+        count: u64,
+    }
+
+    impl FromProto for ProtoEvent {
+        type Result = Event;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            let count = self
+                .count
+                .try_into()
+                .map_err(|_| crate::error::in_field(anyhow::anyhow!("negative"), "count"))?;
+            Ok(Event {
+                name: self.name,
+                count,
+            })
+        }
+    }
+
+    impl ToProto for Event {
+        type Result = ProtoEvent;
+
+        fn to_proto(&self) -> Self::Result {
+            ProtoEvent {
+                name: self.name.clone(),
+                count: self.count as i64,
+            }
+        }
+    }
+
+    fn events() -> Vec<Event> {
+        (0..5)
+            .map(|index| Event {
+                name: format!("event {index}"),
+                count: index * 1000,
+            })
+            .collect()
+    }
+
+    fn record_error(error: anyhow::Error) -> RecordError {
+        error.downcast().unwrap()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut writer = ProtoRecordWriter::new(Vec::new());
+        assert_eq!(write_proto_records(&mut writer, &events()).unwrap(), 5);
+        assert_eq!(writer.records(), 5);
+        let file = writer.into_inner();
+
+        let mut reader = read_proto_records::<ProtoEvent, _>(file.as_slice());
+        let read: Vec<_> = (&mut reader).map(Result::unwrap).collect();
+        assert_eq!(read, events());
+        assert_eq!(reader.records(), 5);
+    }
+
+    #[test]
+    fn test_zero_length() {
+        let mut writer = ProtoRecordWriter::new(Vec::new());
+        writer.write_record(&ProtoEvent::default()).unwrap();
+        let file = writer.into_inner();
+        assert_eq!(file, [0]);
+
+        let read: Vec<_> = read_proto_records::<ProtoEvent, _>(file.as_slice()).collect();
+        assert_eq!(read.len(), 1);
+        assert_eq!(read[0].as_ref().unwrap().name, "");
+    }
+
+    #[test]
+    fn test_truncated() {
+        let mut writer = ProtoRecordWriter::new(Vec::new());
+        write_proto_records(&mut writer, &events()).unwrap();
+        let mut file = writer.into_inner();
+        file.truncate(file.len() - 3);
+
+        let read: Vec<_> = read_proto_records::<ProtoEvent, _>(file.as_slice()).collect();
+        assert_eq!(read.len(), 5);
+        assert!(read[..4].iter().all(Result::is_ok));
+        let error = record_error(read.into_iter().last().unwrap().unwrap_err());
+        assert_eq!(error.record(), 4);
+        assert_eq!(error.kind(), RecordErrorKind::Truncated);
+        assert_eq!(
+            error.to_string(),
+            "Truncated the record 4: The file ends after 9 of the 12 bytes"
+        );
+
+        // in the length
+        let read: Vec<_> = read_proto_records::<ProtoEvent, _>(&[0x80][..]).collect();
+        assert_eq!(read.len(), 1);
+        let error = record_error(read.into_iter().next().unwrap().unwrap_err());
+        assert_eq!(
+            error.to_string(),
+            "Truncated the record 0: The file ends in the length of the record"
+        );
+
+        let read: Vec<_> = read_proto_records::<ProtoEvent, _>(&[0xff; 11][..]).collect();
+        assert_eq!(read.len(), 1);
+        let error = record_error(read.into_iter().next().unwrap().unwrap_err());
+        assert_eq!(error.kind(), RecordErrorKind::Decode);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut writer = ProtoRecordWriter::new(Vec::new());
+        writer
+            .write_record(&ProtoEvent {
+                name: "late".to_owned(),
+                count: -1,
+            })
+            .unwrap();
+        // the string field with the invalid UTF-8
+        writer.write_record(&vec![0x0au8, 1, 0xff]).unwrap();
+        write_proto_records(&mut writer, &events()[..1]).unwrap();
+        let file = writer.into_inner();
+
+        let read: Vec<_> = read_proto_records::<ProtoEvent, _>(file.as_slice()).collect();
+        assert_eq!(read.len(), 3);
+        let mut read = read.into_iter();
+
+        let error = record_error(read.next().unwrap().unwrap_err());
+        assert_eq!(error.kind(), RecordErrorKind::Conversion);
+        assert_eq!(
+            error.to_string(),
+            "Failed to convert the record 0: count: negative"
+        );
+        let error = record_error(read.next().unwrap().unwrap_err());
+        assert_eq!(error.kind(), RecordErrorKind::Decode);
+        assert_eq!(error.record(), 1);
+        // the record after them is read
+        assert_eq!(read.next().unwrap().unwrap(), events()[0]);
+    }
+}
@@ -0,0 +1,278 @@
+//! Rendering the proto messages for the logs without the personal data. The derived `FromProto` of the
+//! structs with the fields marked `#[proto(sensitive)]` implements `ProtoRedact` of the proto message,
+//! its `render_proto_for_log` shows the values of the sensitive fields as `[redacted]`.
+//! ```ignore
+//! #[derive(FromProto)]
+//! #[proto(type = "proto::Customer")]
+//! struct Customer {
+//!     name: String,
+//!     #[proto(sensitive)]
+//!     email: String,
+//! }
+//!
+//! // ProtoCustomer { name: "Alice", email: [redacted], .. }
+//! log::info!("Received {}", proto.render_proto_for_log());
+//!
+//! // hand-written
+//! impl ProtoRedact for proto::Card {
+//!     fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+//!         f.debug_struct("Card")
+//!             .field("number", &RedactedDebug(&self.number))
+//!             .field("expiry", &self.expiry)
+//!             .finish()
+//!     }
+//! }
+//! ```
+//! The other fields are rendered with their `ProtoRedact`, when the type of the field has it, like the
+//! nested messages with their own sensitive fields in `Option`, `Vec` and the maps, and with `Debug`
+//! otherwise. The messages without the sensitive fields, which have nested ones, derive it with
+//! `#[proto(redact)]`. Only the fields of the struct are rendered, the other proto fields and the
+//! fields nested with the `a.b` names are left out, as the `..` at the end shows.
+//!
+//! In `attach_to_errors` the derived conversions render their proto messages, before they are
+//! converted, and the failed ones attach the rendering to the `ConversionError`, like
+//! `items[3].price: The price is negative, in ProtoItem { sku: "A-1", price: -5, .. }`. The rendering
+//! is the one of the innermost failed message, and it is made only in the scope, as it costs a copy of
+//! the message.
+
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// The proto value rendered with the sensitive values redacted
+pub trait ProtoRedact {
+    /// Write the value as `Debug` does, with the sensitive values as `[redacted]`
+    fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    /// The rendering of the value for the logs
+    fn render_proto_for_log(&self) -> String {
+        format!("{:?}", Redacted(self))
+    }
+}
+
+/// The `Debug` of the value with `ProtoRedact`, like `log::info!("{:?}", Redacted(&proto))`
+pub struct Redacted<'a, T: ?Sized>(pub &'a T);
+
+impl<T: ProtoRedact + ?Sized> fmt::Debug for Redacted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_redacted(f)
+    }
+}
+
+/// The sensitive value, its `Debug` is `[redacted]`, for the hand-written `ProtoRedact` and `Debug`
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub struct RedactedDebug<T>(pub T);
+
+impl<T> fmt::Debug for RedactedDebug<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+impl<T> ProtoRedact for RedactedDebug<T> {
+    fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl<T: ProtoRedact> ProtoRedact for Option<T> {
+    fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            None => f.write_str("None"),
+            Some(value) => f.debug_tuple("Some").field(&Redacted(value)).finish(),
+        }
+    }
+}
+
+impl<T: ProtoRedact + ?Sized> ProtoRedact for Box<T> {
+    fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt_redacted(f)
+    }
+}
+
+impl<T: ProtoRedact> ProtoRedact for [T] {
+    fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter().map(Redacted)).finish()
+    }
+}
+
+impl<T: ProtoRedact> ProtoRedact for Vec<T> {
+    fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt_redacted(f)
+    }
+}
+
+impl<K: fmt::Debug, V: ProtoRedact, S> ProtoRedact for HashMap<K, V, S> {
+    fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries = self.iter().map(|(key, value)| (key, Redacted(value)));
+        f.debug_map().entries(entries).finish()
+    }
+}
+
+impl<K: fmt::Debug, V: ProtoRedact> ProtoRedact for BTreeMap<K, V> {
+    fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries = self.iter().map(|(key, value)| (key, Redacted(value)));
+        f.debug_map().entries(entries).finish()
+    }
+}
+
+thread_local! {
+    static ATTACHING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run the conversions in `f` attaching the redacted renderings of the failed proto messages to their
+/// errors
+pub fn attach_to_errors<R>(f: impl FnOnce() -> R) -> R {
+    /// Restores the flag of the outer scope, also when `f` panics
+    struct Restore(bool);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            ATTACHING.with(|attaching| attaching.set(self.0));
+        }
+    }
+
+    let _restore = Restore(ATTACHING.with(|attaching| attaching.replace(true)));
+    f()
+}
+
+/// The rendering of the `proto` message to attach to the errors of its conversion, only in
+/// `attach_to_errors`, used by the derive macros
+#[doc(hidden)]
+pub fn render_for_errors<P: ProtoRedact + ?Sized>(proto: &P) -> Option<String> {
+    ATTACHING
+        .with(Cell::get)
+        .then(|| proto.render_proto_for_log())
+}
+
+/// Attach the `rendered` message to the error of the conversion, used by the derive macros
+#[doc(hidden)]
+pub fn attach<T>(
+    rendered: Option<String>,
+    convert: impl FnOnce() -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    match rendered {
+        None => convert(),
+        Some(rendered) => convert().map_err(|error| crate::error::with_rendered(error, rendered)),
+    }
+}
+
+/// Rendering the fields of the derived `ProtoRedact` with their `ProtoRedact`, when they have it, and
+/// with `Debug` otherwise, as `(&&Field(&value)).field()`
+#[doc(hidden)]
+pub mod __private {
+    use std::fmt;
+
+    use super::{ProtoRedact, Redacted};
+
+    pub struct Field<'a, T: ?Sized>(pub &'a T);
+
+    pub trait ViaRedact<'a, T: ?Sized> {
+        fn field(&self) -> Redacted<'a, T>;
+    }
+
+    impl<'a, T: ProtoRedact + ?Sized> ViaRedact<'a, T> for &Field<'a, T> {
+        fn field(&self) -> Redacted<'a, T> {
+            Redacted(self.0)
+        }
+    }
+
+    pub trait ViaDebug<'a, T: ?Sized> {
+        fn field(&self) -> &'a T;
+    }
+
+    impl<'a, T: fmt::Debug + ?Sized> ViaDebug<'a, T> for Field<'a, T> {
+        fn field(&self) -> &'a T {
+            self.0
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::fmt;
+
+    use super::{attach_to_errors, ProtoRedact, Redacted, RedactedDebug};
+    use crate::error::ConversionError;
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoCard {
+        number: String,
+        expiry: String,
+    }
+
+    impl ProtoRedact for ProtoCard {
+        fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("ProtoCard")
+                .field("number", &RedactedDebug(&self.number))
+                .field("expiry", &self.expiry)
+                .finish()
+        }
+    }
+
+    impl_traits!(prelude);
+
+    impl FromProto for ProtoCard {
+        type Result = u32;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            let rendered = super::render_for_errors(&self);
+            super::attach(rendered, || {
+                anyhow::ensure!(self.number.len() == 16, "The card number is not 16 digits");
+                Ok(self.number.len() as u32)
+            })
+        }
+    }
+
+    fn card(number: &str) -> ProtoCard {
+        ProtoCard {
+            number: number.to_owned(),
+            expiry: "12/30".to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            card("4111111111111111").render_proto_for_log(),
+            r#"ProtoCard { number: [redacted], expiry: "12/30" }"#
+        );
+        let cards = vec![Some(card("1")), None];
+        assert_eq!(
+            cards.render_proto_for_log(),
+            r#"[Some(ProtoCard { number: [redacted], expiry: "12/30" }), None]"#
+        );
+        let cards = BTreeMap::from([("main", card("1"))]);
+        assert_eq!(
+            format!("{:?}", Redacted(&cards)),
+            r#"{"main": ProtoCard { number: [redacted], expiry: "12/30" }}"#
+        );
+        assert_eq!(format!("{:?}", RedactedDebug("secret")), "[redacted]");
+    }
+
+    #[test]
+    fn test_attach_to_errors() {
+        let error = vec![card("4111111111111111"), card("41")]
+            .from_proto()
+            .unwrap_err();
+        assert_eq!(error.to_string(), "[1]: The card number is not 16 digits");
+
+        let error = attach_to_errors(|| vec![card("4111111111111111"), card("41")].from_proto())
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            r#"[1]: The card number is not 16 digits, in ProtoCard { number: [redacted], expiry: "12/30" }"#
+        );
+        let conversion = error.downcast_ref::<ConversionError>().unwrap();
+        assert!(conversion.rendered().unwrap().starts_with("ProtoCard {"));
+        // not attached out of the scope
+        assert!(card("41")
+            .from_proto()
+            .unwrap_err()
+            .to_string()
+            .ends_with("digits"));
+    }
+}
@@ -0,0 +1,94 @@
+//! The mappings of the derived types to their proto types, for the tooling describing the APIs, like
+//! the generators of the OpenAPI documents, enabled with the `reflect` feature.
+//! ```ignore
+//! let mapping = User::proto_mapping();
+//! assert_eq!(mapping.proto_type, "proto::User");
+//! for field in mapping.fields.iter().filter(|field| !field.skipped) {
+//!     schema.property(field.proto_name, field.required);
+//! }
+//! ```
+//! The structs deriving `FromProto` implement `HasProtoMapping` of the proto type of their `type`,
+//! the fields are in the order of the struct. Without the feature, neither the trait nor its impls
+//! exist.
+
+/// The mapping of the derived type to its proto type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtoMapping {
+    /// The name of the rust type
+    pub rust_type: &'static str,
+    /// The path of the proto type, as it is given in `type`
+    pub proto_type: &'static str,
+    pub fields: &'static [FieldMapping],
+}
+
+impl ProtoMapping {
+    /// The mapping of the rust field `rust_name`
+    pub fn field(&self, rust_name: &str) -> Option<&FieldMapping> {
+        self.fields
+            .iter()
+            .find(|field| field.rust_name == rust_name)
+    }
+}
+
+/// The mapping of one field of the derived struct
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldMapping {
+    /// The name of the rust field
+    pub rust_name: &'static str,
+    /// The name of the proto field after the renames, the fields of `path` have the dotted path from
+    /// the outermost message, like `contact.address.city`
+    pub proto_name: &'static str,
+    /// The missing proto field is an error, the rust field is not an `Option`, a collection, skipped
+    /// or defaulted
+    pub required: bool,
+    /// The field has no proto counterpart
+    pub skipped: bool,
+    /// The conversion of the field other than its traits, like the path of the `with` module or
+    /// `"transform"` of `#[proto(transform)]`, `None` for the traits
+    pub transform: Option<&'static str>,
+}
+
+/// The types with the mapping to their proto type, implemented by the derive
+pub trait HasProtoMapping {
+    fn proto_mapping() -> &'static ProtoMapping;
+}
+
+/// The impls of the derive, they are left out without the feature
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __reflect {
+    ($($item: tt)*) => {
+        $($item)*
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Account;
+
+    impl HasProtoMapping for Account {
+        fn proto_mapping() -> &'static ProtoMapping {
+            static MAPPING: ProtoMapping = ProtoMapping {
+                rust_type: "Account",
+                proto_type: "proto::Account",
+                fields: &[FieldMapping {
+                    rust_name: "id",
+                    proto_name: "account_id",
+                    required: true,
+                    skipped: false,
+                    transform: None,
+                }],
+            };
+            &MAPPING
+        }
+    }
+
+    #[test]
+    fn test_field() {
+        let mapping = Account::proto_mapping();
+        assert_eq!(mapping.field("id").unwrap().proto_name, "account_id");
+        assert!(mapping.field("account_id").is_none());
+    }
+}
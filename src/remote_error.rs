@@ -0,0 +1,538 @@
+//! The errors of the other services, received as the `google.rpc.Status` like messages
+//! ```proto
+//! message Status {
+//!   int32 code = 1;
+//!   string message = 2;
+//!   repeated google.protobuf.Any details = 3;
+//! }
+//! ```
+//! converted in to `RemoteError`, which is `std::error::Error`, so it is the source of the `anyhow`
+//! chains of the calls. The code is `RpcCode`, the message is kept as it is, and the details are decoded
+//! by their type urls with the ones registered in the `DetailRegistry`, which is the `EnvelopeRegistry`
+//! of the details.
+//! ```ignore
+//! impl_proto_status!(proto::Status, proto::Any);
+//!
+//! let mut registry = DetailRegistry::new();
+//! registry.insert_prost::<proto::RetryInfo>("type.googleapis.com/google.rpc.RetryInfo");
+//!
+//! let error = registry.from_proto_status(status);
+//! if let Some(retry) = error.find_detail::<proto::RetryInfo>() {
+//!     // retry after the delay
+//! }
+//! ```
+//! The unknown codes are `RpcCode::Other`, and the details of the unknown type urls or failing to
+//! decode are kept as the raw ones, so they never fail the conversion, and all of them are written back
+//! as they were received. The own errors are built with `RemoteError::new` and `with_detail`, and
+//! converted in to the same messages with `ToProto` of `impl_proto_status!`, or `to_proto_status`.
+
+use std::any::Any;
+use std::fmt;
+
+use crate::envelope::EnvelopeRegistry;
+
+/// The decoded value of a detail
+type DetailValue = Box<dyn Any + Send + Sync>;
+
+/// The canonical codes of the statuses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RpcCode {
+    Ok,
+    Cancelled,
+    Unknown,
+    InvalidArgument,
+    DeadlineExceeded,
+    NotFound,
+    AlreadyExists,
+    PermissionDenied,
+    ResourceExhausted,
+    FailedPrecondition,
+    Aborted,
+    OutOfRange,
+    Unimplemented,
+    Internal,
+    Unavailable,
+    DataLoss,
+    Unauthenticated,
+    /// The code, which is not known
+    Other(i32),
+}
+
+impl RpcCode {
+    /// The known codes in the order of their values
+    const KNOWN: [RpcCode; 17] = [
+        RpcCode::Ok,
+        RpcCode::Cancelled,
+        RpcCode::Unknown,
+        RpcCode::InvalidArgument,
+        RpcCode::DeadlineExceeded,
+        RpcCode::NotFound,
+        RpcCode::AlreadyExists,
+        RpcCode::PermissionDenied,
+        RpcCode::ResourceExhausted,
+        RpcCode::FailedPrecondition,
+        RpcCode::Aborted,
+        RpcCode::OutOfRange,
+        RpcCode::Unimplemented,
+        RpcCode::Internal,
+        RpcCode::Unavailable,
+        RpcCode::DataLoss,
+        RpcCode::Unauthenticated,
+    ];
+
+    /// The code of the proto value
+    pub fn from_i32(value: i32) -> Self {
+        usize::try_from(value)
+            .ok()
+            .and_then(|index| Self::KNOWN.get(index).copied())
+            .unwrap_or(RpcCode::Other(value))
+    }
+
+    /// The proto value of the code
+    pub fn to_i32(self) -> i32 {
+        match self {
+            RpcCode::Other(value) => value,
+            known => Self::KNOWN
+                .iter()
+                .position(|code| *code == known)
+                .expect("the known code is in `KNOWN`") as i32,
+        }
+    }
+
+    /// The code as it is sent, like `NOT_FOUND`
+    pub fn as_str(self) -> Option<&'static str> {
+        Some(match self {
+            RpcCode::Ok => "OK",
+            RpcCode::Cancelled => "CANCELLED",
+            RpcCode::Unknown => "UNKNOWN",
+            RpcCode::InvalidArgument => "INVALID_ARGUMENT",
+            RpcCode::DeadlineExceeded => "DEADLINE_EXCEEDED",
+            RpcCode::NotFound => "NOT_FOUND",
+            RpcCode::AlreadyExists => "ALREADY_EXISTS",
+            RpcCode::PermissionDenied => "PERMISSION_DENIED",
+            RpcCode::ResourceExhausted => "RESOURCE_EXHAUSTED",
+            RpcCode::FailedPrecondition => "FAILED_PRECONDITION",
+            RpcCode::Aborted => "ABORTED",
+            RpcCode::OutOfRange => "OUT_OF_RANGE",
+            RpcCode::Unimplemented => "UNIMPLEMENTED",
+            RpcCode::Internal => "INTERNAL",
+            RpcCode::Unavailable => "UNAVAILABLE",
+            RpcCode::DataLoss => "DATA_LOSS",
+            RpcCode::Unauthenticated => "UNAUTHENTICATED",
+            RpcCode::Other(_) => return None,
+        })
+    }
+}
+
+impl fmt::Display for RpcCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.as_str() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "code {}", self.to_i32()),
+        }
+    }
+}
+
+/// The detail of the status, the type url and the encoded message, with its value, when it decoded
+pub struct RemoteDetail {
+    type_url: String,
+    value: Vec<u8>,
+    decoded: Option<DetailValue>,
+}
+
+impl RemoteDetail {
+    /// The type url of the detail, like `type.googleapis.com/google.rpc.RetryInfo`
+    pub fn type_url(&self) -> &str {
+        &self.type_url
+    }
+
+    /// The encoded message of the detail
+    pub fn value(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// The detail decoded with the registry, it is raw, when its type url is not registered or it
+    /// failed to decode
+    pub fn is_decoded(&self) -> bool {
+        self.decoded.is_some()
+    }
+
+    /// The decoded value, if it is `D`
+    pub fn get<D: Any>(&self) -> Option<&D> {
+        self.decoded.as_ref()?.downcast_ref()
+    }
+}
+
+impl fmt::Debug for RemoteDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteDetail")
+            .field("type_url", &self.type_url)
+            .field("len", &self.value.len())
+            .field("decoded", &self.is_decoded())
+            .finish()
+    }
+}
+
+/// The error of the other service, converted from its status
+#[derive(Debug)]
+pub struct RemoteError {
+    code: RpcCode,
+    message: String,
+    details: Vec<RemoteDetail>,
+}
+
+impl RemoteError {
+    /// The own error of the `code`, without any details
+    pub fn new(code: RpcCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: Vec::new(),
+        }
+    }
+
+    /// Add the detail `value`, with its type url and encoded message written in to the status
+    pub fn with_detail<D: Any + Send + Sync>(
+        mut self,
+        type_url: impl Into<String>,
+        encoded: Vec<u8>,
+        value: D,
+    ) -> Self {
+        self.details.push(RemoteDetail {
+            type_url: type_url.into(),
+            value: encoded,
+            decoded: Some(Box::new(value)),
+        });
+        self
+    }
+
+    /// Add the detail of the prost message, encoded in to the status
+    #[cfg(feature = "prost")]
+    pub fn with_message<M>(self, type_url: impl Into<String>, message: M) -> Self
+    where
+        M: prost::Message + Send + Sync + 'static,
+    {
+        let encoded = message.encode_to_vec();
+        self.with_detail(type_url, encoded, message)
+    }
+
+    pub fn code(&self) -> RpcCode {
+        self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// All the details, the decoded and the raw ones, in the order of the status
+    pub fn details(&self) -> &[RemoteDetail] {
+        &self.details
+    }
+
+    /// The first detail decoded in to `D`
+    pub fn find_detail<D: Any>(&self) -> Option<&D> {
+        self.details.iter().find_map(RemoteDetail::get)
+    }
+
+    /// The details, which are not decoded
+    pub fn raw_details(&self) -> impl Iterator<Item = &RemoteDetail> {
+        self.details.iter().filter(|detail| !detail.is_decoded())
+    }
+
+    /// The status with the code, the message and the details as they were received
+    pub fn to_proto_status<S: StatusProto>(&self) -> S {
+        let details = self
+            .details
+            .iter()
+            .map(|detail| (detail.type_url.clone(), detail.value.clone()))
+            .collect();
+        S::from_parts(self.code.to_i32(), self.message.clone(), details)
+    }
+}
+
+impl fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for RemoteError {}
+
+/// The `google.rpc.Status` like message, implemented by `impl_proto_status!`
+pub trait StatusProto {
+    /// The code, the message and the type urls and the encoded messages of the details
+    fn into_parts(self) -> (i32, String, Vec<(String, Vec<u8>)>);
+
+    /// The status of the parts
+    fn from_parts(code: i32, message: String, details: Vec<(String, Vec<u8>)>) -> Self;
+}
+
+/// The decodings of the details of the statuses by their type urls
+#[derive(Debug, Default)]
+pub struct DetailRegistry {
+    details: EnvelopeRegistry<DetailValue>,
+}
+
+impl DetailRegistry {
+    /// The registry without any details, all of them are raw
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the detail of the type url, decoded with `decode` and converted with `convert`
+    pub fn insert<P: 'static, D: Any + Send + Sync>(
+        &mut self,
+        type_url: impl Into<String>,
+        decode: impl Fn(&[u8]) -> anyhow::Result<P> + Send + Sync + 'static,
+        convert: impl Fn(P) -> anyhow::Result<D> + Send + Sync + 'static,
+    ) {
+        self.details.insert(type_url, decode, move |message| {
+            convert(message).map(|value| Box::new(value) as DetailValue)
+        });
+    }
+
+    /// Register the detail of the prost message `P`, which is the value of the detail
+    #[cfg(feature = "prost")]
+    pub fn insert_prost<P>(&mut self, type_url: impl Into<String>)
+    where
+        P: prost::Message + Default + Send + Sync + 'static,
+    {
+        self.insert(type_url, |payload| Ok(P::decode(payload)?), Ok::<P, _>);
+    }
+
+    /// The detail of the type url and the encoded message, raw when it fails to decode
+    pub fn detail(&self, type_url: String, value: Vec<u8>) -> RemoteDetail {
+        let decoded = self.details.from_proto_envelope(&type_url, &value).ok();
+        RemoteDetail {
+            type_url,
+            value,
+            decoded,
+        }
+    }
+
+    /// Convert the status, the unknown codes and details are kept, so it never fails
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_proto_status<S: StatusProto>(&self, status: S) -> RemoteError {
+        let (code, message, details) = status.into_parts();
+        RemoteError {
+            code: RpcCode::from_i32(code),
+            message,
+            details: details
+                .into_iter()
+                .map(|(type_url, value)| self.detail(type_url, value))
+                .collect(),
+        }
+    }
+}
+
+/// Implement `StatusProto` of the status message with the `code`, the `message` and the repeated
+/// `details` of the `Any` message with the `type_url` and the `value`, `FromProto` of it in to
+/// `RemoteError` with the raw details and `ToProto` of `RemoteError` back, the traits of the
+/// `impl_traits!(prelude)` have to be in the scope.
+/// ```ignore
+/// impl_proto_status!(proto::Status, proto::Any);
+/// ```
+/// The details are decoded by `DetailRegistry::from_proto_status`, see `remote_error`.
+#[macro_export]
+macro_rules! impl_proto_status {
+    ($status: path, $any: path) => {
+        impl $crate::remote_error::StatusProto for $status {
+            fn into_parts(self) -> (i32, String, Vec<(String, Vec<u8>)>) {
+                let details = self
+                    .details
+                    .into_iter()
+                    .map(|detail| (detail.type_url, detail.value))
+                    .collect();
+                (self.code, self.message, details)
+            }
+
+            #[allow(clippy::needless_update)]
+            fn from_parts(code: i32, message: String, details: Vec<(String, Vec<u8>)>) -> Self {
+                $status {
+                    code,
+                    message,
+                    details: details
+                        .into_iter()
+                        .map(|(type_url, value)| {
+                            let mut detail = <$any as ::core::default::Default>::default();
+                            detail.type_url = type_url;
+                            detail.value = value;
+                            detail
+                        })
+                        .collect(),
+                    ..::core::default::Default::default()
+                }
+            }
+        }
+
+        /// The details are raw, `DetailRegistry::from_proto_status` decodes them
+        impl FromProto for $status {
+            type Result = $crate::remote_error::RemoteError;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                Ok($crate::remote_error::DetailRegistry::new().from_proto_status(self))
+            }
+        }
+
+        impl ToProto for $crate::remote_error::RemoteError {
+            type Result = $status;
+
+            fn to_proto(&self) -> Self::Result {
+                self.to_proto_status()
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoAny {
+        type_url: String,
+        value: Vec<u8>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoStatus {
+        code: i32,
+        message: String,
+        details: Vec<ProtoAny>,
+    }
+
+    impl_proto_status!(ProtoStatus, ProtoAny);
+
+    const RETRY_INFO: &str = "type.googleapis.com/google.rpc.RetryInfo";
+    const DEBUG_INFO: &str = "type.googleapis.com/google.rpc.DebugInfo";
+
+    /// The delay of the retry in seconds
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct RetryInfo {
+        delay: u32,
+    }
+
+    fn decode_retry(payload: &[u8]) -> anyhow::Result<u32> {
+        let bytes: [u8; 4] = payload.try_into()?;
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    fn registry() -> DetailRegistry {
+        let mut registry = DetailRegistry::new();
+        registry.insert(RETRY_INFO, decode_retry, |delay| Ok(RetryInfo { delay }));
+        registry
+    }
+
+    fn detail(type_url: &str, value: &[u8]) -> ProtoAny {
+        ProtoAny {
+            type_url: type_url.to_owned(),
+            value: value.to_vec(),
+        }
+    }
+
+    fn status() -> ProtoStatus {
+        ProtoStatus {
+            code: 14,
+            message: "The shard is rebalancing".to_owned(),
+            details: vec![
+                detail(DEBUG_INFO, b"stack"),
+                detail(RETRY_INFO, &30u32.to_be_bytes()),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_details() {
+        let error = registry().from_proto_status(status());
+        assert_eq!(error.code(), RpcCode::Unavailable);
+        assert_eq!(error.message(), "The shard is rebalancing");
+        assert_eq!(error.to_string(), "UNAVAILABLE: The shard is rebalancing");
+        assert_eq!(
+            error.find_detail::<RetryInfo>(),
+            Some(&RetryInfo { delay: 30 })
+        );
+        assert_eq!(error.find_detail::<String>(), None);
+
+        let details = error.details();
+        assert_eq!(details.len(), 2);
+        assert!(!details[0].is_decoded());
+        assert_eq!(details[0].type_url(), DEBUG_INFO);
+        assert_eq!(details[0].value(), b"stack");
+        assert_eq!(details[1].get::<RetryInfo>().unwrap().delay, 30);
+        let raw: Vec<_> = error.raw_details().map(RemoteDetail::type_url).collect();
+        assert_eq!(raw, [DEBUG_INFO]);
+
+        // the detail failing to decode is kept raw
+        let broken = ProtoStatus {
+            details: vec![detail(RETRY_INFO, b"now")],
+            ..status()
+        };
+        let error = registry().from_proto_status(broken.clone());
+        assert_eq!(error.find_detail::<RetryInfo>(), None);
+        assert_eq!(error.raw_details().count(), 1);
+        assert_eq!(error.to_proto(), broken);
+    }
+
+    #[test]
+    fn test_codes() {
+        for value in 0..17 {
+            assert_eq!(RpcCode::from_i32(value).to_i32(), value);
+        }
+        assert_eq!(RpcCode::from_i32(5), RpcCode::NotFound);
+        assert_eq!(RpcCode::from_i32(16), RpcCode::Unauthenticated);
+        assert_eq!(RpcCode::from_i32(99), RpcCode::Other(99));
+        assert_eq!(RpcCode::from_i32(-1), RpcCode::Other(-1));
+        assert_eq!(RpcCode::Other(99).to_i32(), 99);
+
+        let error = ProtoStatus {
+            code: 99,
+            message: "The quota of the tenant".to_owned(),
+            details: Vec::new(),
+        }
+        .from_proto()
+        .unwrap();
+        assert_eq!(error.code(), RpcCode::Other(99));
+        assert_eq!(error.to_string(), "code 99: The quota of the tenant");
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let error = registry().from_proto_status(status());
+        assert_eq!(error.to_proto(), status());
+        // without the registry, all the details are raw
+        let error = status().from_proto().unwrap();
+        assert_eq!(error.raw_details().count(), 2);
+        assert_eq!(error.to_proto(), status());
+    }
+
+    #[test]
+    fn test_own_error() {
+        let error = RemoteError::new(RpcCode::ResourceExhausted, "Too many requests").with_detail(
+            RETRY_INFO,
+            5u32.to_be_bytes().to_vec(),
+            RetryInfo { delay: 5 },
+        );
+        assert_eq!(error.find_detail::<RetryInfo>().unwrap().delay, 5);
+        let status: ProtoStatus = error.to_proto_status();
+        assert_eq!(status.code, 8);
+        assert_eq!(status.details, [detail(RETRY_INFO, &5u32.to_be_bytes())]);
+        let received = registry().from_proto_status(status);
+        assert_eq!(
+            received.find_detail::<RetryInfo>(),
+            Some(&RetryInfo { delay: 5 })
+        );
+
+        // the source of the chains of the calls
+        let chain = anyhow::Error::new(error).context("The search failed");
+        assert_eq!(
+            format!("{chain:#}"),
+            "The search failed: RESOURCE_EXHAUSTED: Too many requests"
+        );
+        let remote = chain.downcast_ref::<RemoteError>().unwrap();
+        assert_eq!(remote.code(), RpcCode::ResourceExhausted);
+    }
+}
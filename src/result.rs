@@ -0,0 +1,273 @@
+//! The proto messages of the results, which are either the value or the error.
+//! ```proto
+//! message GetThingResult {
+//!   oneof result {
+//!     Thing ok = 1;
+//!     Error err = 2;
+//!   }
+//! }
+//! ```
+//! They convert in to `Result<Thing, DomainError>` with `impl_proto_result!`, where `result` is the
+//! oneof field, `Ok` and `Err` the variants of the prost oneof enum with their proto field names, and
+//! `Thing` and `DomainError` the domain types of their values. The values convert with their own
+//! conversions, the message without either of them is the missing `result` field, and the `Result`
+//! writes its variant back.
+//! ```ignore
+//! impl_proto_result!(GetThingResult, result: get_thing_result::Result {
+//!     Ok(ok) => Thing,
+//!     Err(err) => DomainError,
+//! });
+//! ```
+//! The `Vec` of the messages converts in to `Vec<Result<Thing, DomainError>>`, and the oneof fields of
+//! the other messages convert in to the `Result` fields of the derived conversions, also back.
+//! ```ignore
+//! #[derive(FromProto, ToProto)]
+//! #[proto(type = "proto::Reply")]
+//! struct Reply {
+//!     id: String,
+//!     result: Result<Thing, DomainError>,
+//! }
+//! ```
+
+/// Implement `FromProto` of the result message and its oneof enum in to the `Result` of the domain
+/// types, and `ToProto` of the `Result` back, the traits of the `impl_traits!(prelude)` have to be in
+/// the scope.
+/// ```ignore
+/// impl_proto_result!(GetThingResult, result: get_thing_result::Result {
+///     Ok(ok) => Thing,
+///     Err(err) => DomainError,
+/// });
+/// ```
+/// The message has only the oneof field.
+#[macro_export]
+macro_rules! impl_proto_result {
+    (
+        $type: path,
+        $field: ident: $oneof: path {
+            $ok_variant: ident ($ok_name: ident) => $ok: ty,
+            $err_variant: ident ($err_name: ident) => $err: ty $(,)?
+        }
+    ) => {
+        /// The oneof converts in to the `Result` of its value
+        impl FromProto for $oneof {
+            type Result = ::core::result::Result<$ok, $err>;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                use $oneof as Oneof;
+                match self {
+                    Oneof::$ok_variant(value) => Ok(::core::result::Result::Ok(
+                        FromProtoField::from_proto_field(value, stringify!($ok_name))?,
+                    )),
+                    Oneof::$err_variant(value) => Ok(::core::result::Result::Err(
+                        FromProtoField::from_proto_field(value, stringify!($err_name))?,
+                    )),
+                }
+            }
+        }
+
+        /// The result message without the value or the error is the missing oneof field
+        impl FromProto for $type {
+            type Result = ::core::result::Result<$ok, $err>;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                <Option<$oneof> as FromProtoField<
+                    Self::Result,
+                    $crate::__private::field::Required,
+                >>::from_proto_field(self.$field, stringify!($field))
+            }
+        }
+
+        impl ToProto for ::core::result::Result<$ok, $err> {
+            type Result = $type;
+
+            fn to_proto(&self) -> Self::Result {
+                $type {
+                    $field: ToProtoAlias::to_proto_alias(self),
+                }
+            }
+        }
+
+        impl ToProtoAlias<$oneof> for ::core::result::Result<$ok, $err> {
+            fn to_proto_alias(&self) -> $oneof {
+                use $oneof as Oneof;
+                match self {
+                    ::core::result::Result::Ok(value) => {
+                        Oneof::$ok_variant(ToProtoField::to_proto_field(value))
+                    }
+                    ::core::result::Result::Err(value) => {
+                        Oneof::$err_variant(ToProtoField::to_proto_field(value))
+                    }
+                }
+            }
+        }
+
+        /// The oneof fields of the messages
+        impl ToProtoAlias<Option<$oneof>> for ::core::result::Result<$ok, $err> {
+            fn to_proto_alias(&self) -> Option<$oneof> {
+                Some(ToProtoAlias::<$oneof>::to_proto_alias(self))
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoThing {
+        size: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Thing {
+        size: u8,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoError {
+        code: i32,
+        message: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum DomainError {
+        NotFound,
+        Other(String),
+    }
+
+    pub mod proto_get_thing_result {
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Result {
+            Ok(super::ProtoThing),
+            Err(super::ProtoError),
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoGetThingResult {
+        result: Option<proto_get_thing_result::Result>,
+    }
+
+    impl_traits!(prelude);
+    impl_proto_result!(ProtoGetThingResult, result: proto_get_thing_result::Result {
+        Ok(ok) => Thing,
+        Err(err) => DomainError,
+    });
+
+    impl FromProto for ProtoThing {
+        type Result = Thing;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            let size = u8::try_from(self.size)
+                .map_err(|_| anyhow::anyhow!("The size {} is invalid", self.size))?;
+            Ok(Thing { size })
+        }
+    }
+
+    impl ToProto for Thing {
+        type Result = ProtoThing;
+
+        fn to_proto(&self) -> Self::Result {
+            ProtoThing {
+                size: self.size.into(),
+            }
+        }
+    }
+
+    impl FromProto for ProtoError {
+        type Result = DomainError;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            match self.code {
+                404 => Ok(DomainError::NotFound),
+                0 => anyhow::bail!("The error has no code"),
+                _ => Ok(DomainError::Other(self.message)),
+            }
+        }
+    }
+
+    impl ToProto for DomainError {
+        type Result = ProtoError;
+
+        fn to_proto(&self) -> Self::Result {
+            match self {
+                DomainError::NotFound => ProtoError {
+                    code: 404,
+                    message: String::new(),
+                },
+                DomainError::Other(message) => ProtoError {
+                    code: 500,
+                    message: message.clone(),
+                },
+            }
+        }
+    }
+
+    fn ok(size: i64) -> ProtoGetThingResult {
+        ProtoGetThingResult {
+            result: Some(proto_get_thing_result::Result::Ok(ProtoThing { size })),
+        }
+    }
+
+    fn err(code: i32, message: &str) -> ProtoGetThingResult {
+        ProtoGetThingResult {
+            result: Some(proto_get_thing_result::Result::Err(ProtoError {
+                code,
+                message: message.to_owned(),
+            })),
+        }
+    }
+
+    #[test]
+    fn test_ok() {
+        let result = ok(3).from_proto().unwrap();
+        assert_eq!(result, Ok(Thing { size: 3 }));
+        assert_eq!(ToProto::to_proto(&result), ok(3));
+
+        let error = ok(300).from_proto().unwrap_err();
+        assert_eq!(error.to_string(), "result.ok: The size 300 is invalid");
+    }
+
+    #[test]
+    fn test_err() {
+        let result = err(404, "").from_proto().unwrap();
+        assert_eq!(result, Err(DomainError::NotFound));
+        assert_eq!(ToProto::to_proto(&result), err(404, ""));
+        let result = err(500, "boom").from_proto().unwrap();
+        assert_eq!(result, Err(DomainError::Other("boom".to_owned())));
+        assert_eq!(ToProto::to_proto(&result), err(500, "boom"));
+
+        let error = err(0, "").from_proto().unwrap_err();
+        assert_eq!(error.to_string(), "result.err: The error has no code");
+    }
+
+    #[test]
+    fn test_missing() {
+        let error = ProtoGetThingResult { result: None }
+            .from_proto()
+            .unwrap_err();
+        assert_eq!(error.to_string(), "result: Required is missing");
+    }
+
+    #[test]
+    fn test_vec() {
+        let proto = vec![ok(1), err(404, ""), ok(2)];
+        let results = proto.clone().from_proto().unwrap();
+        assert_eq!(
+            results,
+            [
+                Ok(Thing { size: 1 }),
+                Err(DomainError::NotFound),
+                Ok(Thing { size: 2 })
+            ]
+        );
+        assert_eq!(ToProto::to_proto(&results), proto);
+
+        let error = vec![ok(1), ProtoGetThingResult { result: None }]
+            .from_proto()
+            .unwrap_err();
+        assert_eq!(error.to_string(), "[1].result: Required is missing");
+    }
+}
@@ -0,0 +1,374 @@
+//! Checking the `FromProto` and `ToProto` of a type are inverse, for the unit tests of the services.
+//! The prelude gets the `roundtrip` module with `check_proto_roundtrip` and `check_domain_roundtrip`,
+//! which return the `RoundtripDiff` of the value, which changed or failed to convert, and
+//! `check_proto_roundtrip_alias` and `check_domain_roundtrip_alias` of the `ToProtoAlias` conversions.
+//! ```ignore
+//! #[test]
+//! fn test_user() -> anyhow::Result<()> {
+//!     proto::roundtrip::check_proto_roundtrip(&proto::User { name: "eve".into(), ..fixture() })?;
+//!     Ok(())
+//! }
+//! ```
+//! The diff has the lines of the pretty `Debug` of the values, so every changed field is on its own
+//! line.
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    /// The conversion failed with the error
+    Failed(String),
+    /// The value converted back is different
+    Changed {
+        /// The `Debug` of the value converted back
+        back: String,
+        /// The pretty `Debug` lines of the original value and of the one converted back
+        diff: Vec<Line>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Line {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// The line marked with `-` when it was removed, and with `+` when it was added
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Line::Same(line) => write!(f, "  {line}"),
+            Line::Removed(line) => write!(f, "- {line}"),
+            Line::Added(line) => write!(f, "+ {line}"),
+        }
+    }
+}
+
+/// The value, which was not the same after converting it there and back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundtripDiff {
+    /// Which value was converted, the proto one or the domain one
+    proto: bool,
+    original: String,
+    /// The value converted in between, the domain value of the proto one
+    converted: Option<String>,
+    outcome: Outcome,
+}
+
+impl RoundtripDiff {
+    /// The proto value failed to convert, or the converted value failed to convert back
+    #[doc(hidden)]
+    pub fn failed(proto: bool, original: &impl fmt::Debug, error: anyhow::Error) -> Self {
+        Self {
+            proto,
+            original: format!("{original:?}"),
+            converted: None,
+            outcome: Outcome::Failed(format!("{error:#}")),
+        }
+    }
+
+    /// The value converted back is different from the original one
+    #[doc(hidden)]
+    pub fn changed<T: fmt::Debug>(
+        proto: bool,
+        original: &T,
+        converted: Option<&dyn fmt::Debug>,
+        back: &T,
+    ) -> Self {
+        let before = format!("{original:#?}");
+        let after = format!("{back:#?}");
+        Self {
+            proto,
+            original: format!("{original:?}"),
+            converted: converted.map(|converted| format!("{converted:?}")),
+            outcome: Outcome::Changed {
+                back: format!("{back:?}"),
+                diff: diff(&before, &after),
+            },
+        }
+    }
+
+    /// The conversion failed, instead of converting in to a different value
+    pub fn is_failed(&self) -> bool {
+        matches!(self.outcome, Outcome::Failed(_))
+    }
+
+    /// The lines of the pretty `Debug`, which are different, prefixed with `-` for the original value
+    /// and `+` for the one converted back
+    pub fn changed_lines(&self) -> Vec<String> {
+        let Outcome::Changed { diff, .. } = &self.outcome else {
+            return Vec::new();
+        };
+        diff.iter()
+            .filter_map(|line| match line {
+                Line::Same(_) => None,
+                Line::Removed(line) => Some(format!("- {}", line.trim())),
+                Line::Added(line) => Some(format!("+ {}", line.trim())),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for RoundtripDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (subject, label) = if self.proto {
+            ("proto value", "proto")
+        } else {
+            ("value", "value")
+        };
+        match &self.outcome {
+            Outcome::Failed(error) if self.proto => write!(
+                f,
+                "The proto value failed to convert: {error}\n  proto: {}",
+                self.original
+            ),
+            Outcome::Failed(error) => write!(
+                f,
+                "The converted proto value failed to convert back: {error}\n  value: {}",
+                self.original
+            ),
+            Outcome::Changed { back, diff } => {
+                write!(
+                    f,
+                    "The {subject} changed in the round trip\n  {label}: {}",
+                    self.original
+                )?;
+                if let Some(converted) = &self.converted {
+                    write!(f, "\n  value: {converted}")?;
+                }
+                write!(f, "\n   back: {back}")?;
+                // the single line values are already the whole diff
+                if diff.len() > 2 {
+                    for line in diff {
+                        write!(f, "\n  {line}")?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for RoundtripDiff {}
+
+/// The lines of the `before` and `after`, by their longest common subsequence
+pub(crate) fn diff(before: &str, after: &str) -> Vec<Line> {
+    let before: Vec<_> = before.lines().collect();
+    let after: Vec<_> = after.lines().collect();
+    // the length of the common subsequence of the remaining lines
+    let mut common = vec![vec![0usize; after.len() + 1]; before.len() + 1];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            common[i][j] = if before[i] == after[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < before.len() || j < after.len() {
+        if i < before.len() && j < after.len() && before[i] == after[j] {
+            lines.push(Line::Same(before[i].to_owned()));
+            i += 1;
+            j += 1;
+        } else if j == after.len() || (i < before.len() && common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(Line::Removed(before[i].to_owned()));
+            i += 1;
+        } else {
+            lines.push(Line::Added(after[j].to_owned()));
+            j += 1;
+        }
+    }
+    lines
+}
+
+/// Generate the round trip checks, this is part of the `impl_traits!(prelude)`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_roundtrip {
+    (prelude) => {
+        pub use super::roundtrip;
+    };
+    () => {
+        /// Check the conversions are inverse, see `proto_conve::roundtrip`
+        #[allow(dead_code)]
+        pub mod roundtrip {
+            use super::{FromProto, ToProto, ToProtoAlias};
+            use $crate::roundtrip::RoundtripDiff;
+
+            /// Check the proto value is the same after converting it from proto and back
+            pub fn check_proto_roundtrip<P>(proto: &P) -> Result<(), RoundtripDiff>
+            where
+                P: Clone + PartialEq + std::fmt::Debug + FromProto,
+                P::Result: ToProto<Result = P> + std::fmt::Debug,
+            {
+                let value = proto
+                    .clone()
+                    .from_proto()
+                    .map_err(|error| RoundtripDiff::failed(true, proto, error))?;
+                let back = value.to_proto();
+                if back == *proto {
+                    Ok(())
+                } else {
+                    Err(RoundtripDiff::changed(true, proto, Some(&value), &back))
+                }
+            }
+
+            /// Check the value is the same after converting it in to proto and back
+            pub fn check_domain_roundtrip<T>(value: &T) -> Result<(), RoundtripDiff>
+            where
+                T: ToProto + PartialEq + std::fmt::Debug,
+                T::Result: FromProto<Result = T> + std::fmt::Debug,
+            {
+                let back = value
+                    .to_proto()
+                    .from_proto()
+                    .map_err(|error| RoundtripDiff::failed(false, value, error))?;
+                if back == *value {
+                    Ok(())
+                } else {
+                    Err(RoundtripDiff::changed(false, value, None, &back))
+                }
+            }
+
+            /// Check the proto value is the same after converting it from proto and back with
+            /// `ToProtoAlias`, for the values converting in to several proto types
+            pub fn check_proto_roundtrip_alias<P>(proto: &P) -> Result<(), RoundtripDiff>
+            where
+                P: Clone + PartialEq + std::fmt::Debug + FromProto,
+                P::Result: ToProtoAlias<P> + std::fmt::Debug,
+            {
+                let value = proto
+                    .clone()
+                    .from_proto()
+                    .map_err(|error| RoundtripDiff::failed(true, proto, error))?;
+                let back = ToProtoAlias::<P>::to_proto_alias(&value);
+                if back == *proto {
+                    Ok(())
+                } else {
+                    Err(RoundtripDiff::changed(true, proto, Some(&value), &back))
+                }
+            }
+
+            /// Check the value is the same after converting it in to the proto type `P` with
+            /// `ToProtoAlias` and back
+            pub fn check_domain_roundtrip_alias<P, T>(value: &T) -> Result<(), RoundtripDiff>
+            where
+                T: ToProtoAlias<P> + PartialEq + std::fmt::Debug,
+                P: FromProto<Result = T> + std::fmt::Debug,
+            {
+                let back = ToProtoAlias::<P>::to_proto_alias(value)
+                    .from_proto()
+                    .map_err(|error| RoundtripDiff::failed(false, value, error))?;
+                if back == *value {
+                    Ok(())
+                } else {
+                    Err(RoundtripDiff::changed(false, value, None, &back))
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoEvent {
+        name: String,
+        seconds: i64,
+        nanos: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Event {
+        name: String,
+        millis: i64,
+    }
+
+    impl_traits!(prelude);
+
+    // the nanos below the millis are lost, so the conversions are not inverse
+    impl FromProto for ProtoEvent {
+        type Result = Event;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            anyhow::ensure!(self.nanos >= 0, "The nanos {} are negative", self.nanos);
+            Ok(Event {
+                name: self.name,
+                millis: self.seconds * 1000 + self.nanos as i64 / 1_000_000,
+            })
+        }
+    }
+
+    impl ToProto for Event {
+        type Result = ProtoEvent;
+
+        fn to_proto(&self) -> Self::Result {
+            ProtoEvent {
+                name: self.name.clone(),
+                seconds: self.millis.div_euclid(1000),
+                nanos: self.millis.rem_euclid(1000) as i32 * 1_000_000,
+            }
+        }
+    }
+
+    fn event(nanos: i32) -> ProtoEvent {
+        ProtoEvent {
+            name: "start".to_owned(),
+            seconds: 3,
+            nanos,
+        }
+    }
+
+    #[test]
+    fn test_symmetric() {
+        roundtrip::check_proto_roundtrip(&event(5_000_000)).unwrap();
+        let value = Event {
+            name: "stop".to_owned(),
+            millis: -1500,
+        };
+        roundtrip::check_domain_roundtrip(&value).unwrap();
+    }
+
+    #[test]
+    fn test_changed() {
+        let diff = roundtrip::check_proto_roundtrip(&event(5_000_001)).unwrap_err();
+        assert!(!diff.is_failed());
+        assert_eq!(
+            diff.changed_lines(),
+            ["- nanos: 5000001,", "+ nanos: 5000000,"]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "The proto value changed in the round trip\n  proto: ProtoEvent { name: \"start\", \
+             seconds: 3, nanos: 5000001 }\n  value: Event { name: \"start\", millis: 3005 }\n   \
+             back: ProtoEvent { name: \"start\", seconds: 3, nanos: 5000000 }\n    ProtoEvent {\n        \
+             name: \"start\",\n        seconds: 3,\n  -     nanos: 5000001,\n  +     nanos: 5000000,\n    }"
+        );
+    }
+
+    #[test]
+    fn test_failed() {
+        let diff = roundtrip::check_proto_roundtrip(&event(-1)).unwrap_err();
+        assert!(diff.is_failed());
+        assert!(diff.changed_lines().is_empty());
+        assert_eq!(
+            diff.to_string(),
+            "The proto value failed to convert: The nanos -1 are negative\n  proto: ProtoEvent { \
+             name: \"start\", seconds: 3, nanos: -1 }"
+        );
+    }
+
+    #[test]
+    fn test_scalar() {
+        assert!(roundtrip::check_domain_roundtrip(&7u32).is_ok());
+        let diff = super::diff("1\n2\n3", "1\n4\n3");
+        assert_eq!(diff.len(), 4);
+    }
+}
@@ -0,0 +1,496 @@
+//! Physical quantities carried in the scaled proto integers, like `int32 temperature_milli_c` or
+//! `uint32 price_cents`, converted in to `f64` instead of dividing them by hand.
+//! ```ignore
+//! type MilliCelsius = proto_conve::Scaled<i32, 1, 1000>;
+//! type Cents = proto_conve::Scaled<u32, 1, 100>;
+//! type Kilometers = proto_conve::Scaled<i64, 1000, 1, proto_conve::scaled::TowardZero>;
+//! ```
+//! The value of `Scaled<W, NUM, DEN>` is the proto integer `W` multiplied by `NUM / DEN`, so it converts
+//! from the proto integer with `FromProtoAlias<W>`, the `NUM` or `DEN` of zero fails to build. Back in
+//! to proto the value is divided by the scale
+//! and rounded, half to even unless the rounding is given, with `TryToProto`, as the values, which do
+//! not fit in to `W` after the scaling, like the overflowing or not finite ones, are an error.
+//!
+//! The other way around, the money and the quantities sent in the proto `double`, like `double price`,
+//! convert in to the integer amounts with `FloatToScaledInt`, without accumulating the float errors.
+//! ```ignore
+//! type Cents = proto_conve::FloatToScaledInt<i64, 100>;
+//! type MicroUnits = proto_conve::FloatToScaledInt<i64, 1_000_000, 10>;
+//! ```
+//! The proto value multiplied by the scale has to be within the tolerance of an integer, given in the
+//! millionths of the integer unit, `1_000` by default, so `19.99` is `1999` cents, while `19.991` is
+//! an error. The not finite values and the ones, which do not fit in to the integer, are an error too.
+//! Back in to proto, the integer is divided by the scale.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::violation::{coded, ErrorCode};
+
+/// How the scaled values are rounded to the proto integers
+pub trait Rounding {
+    /// The value rounded to an integer
+    fn round(value: f64) -> f64;
+}
+
+/// Round half to even, `2.5` is `2` and `3.5` is `4`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct HalfEven;
+
+impl Rounding for HalfEven {
+    fn round(value: f64) -> f64 {
+        value.round_ties_even()
+    }
+}
+
+/// Round half away from zero, `2.5` is `3` and `-2.5` is `-3`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct HalfAwayFromZero;
+
+impl Rounding for HalfAwayFromZero {
+    fn round(value: f64) -> f64 {
+        value.round()
+    }
+}
+
+/// Truncate the fraction, `2.7` is `2` and `-2.7` is `-2`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct TowardZero;
+
+impl Rounding for TowardZero {
+    fn round(value: f64) -> f64 {
+        value.trunc()
+    }
+}
+
+/// Round down, `-2.5` is `-3`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Floor;
+
+impl Rounding for Floor {
+    fn round(value: f64) -> f64 {
+        value.floor()
+    }
+}
+
+/// Round up, `2.5` is `3`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Ceil;
+
+impl Rounding for Ceil {
+    fn round(value: f64) -> f64 {
+        value.ceil()
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The proto integers carrying the scaled values
+pub trait ScaledInt: Copy + fmt::Display + sealed::Sealed {
+    /// The name of the type in the errors
+    const NAME: &'static str;
+    const MIN: Self;
+    const MAX: Self;
+
+    /// The integer as `f64`, the ones over 2^53 are rounded
+    fn to_f64(self) -> f64;
+
+    /// The rounded value, if it fits
+    fn from_f64(value: f64) -> Option<Self>;
+}
+
+macro_rules! impl_scaled_int {
+    ($($type: ty),*) => {
+        $(
+            impl sealed::Sealed for $type {}
+
+            impl ScaledInt for $type {
+                const NAME: &'static str = stringify!($type);
+                const MIN: Self = <$type>::MIN;
+                const MAX: Self = <$type>::MAX;
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                fn from_f64(value: f64) -> Option<Self> {
+                    // the upper bound is a power of two, so it is exact as `f64`
+                    let max = <$type>::MAX as f64 + 1.0;
+                    (value >= <$type>::MIN as f64 && value < max).then_some(value as $type)
+                }
+            }
+        )*
+    };
+}
+
+impl_scaled_int!(i8, i16, i32, i64, u8, u16, u32, u64);
+
+/// The value carried in the proto integer `W` multiplied by `NUM / DEN`, rounded by `R` back in to
+/// proto
+pub struct Scaled<W, const NUM: i64, const DEN: i64, R = HalfEven> {
+    value: f64,
+    marker: PhantomData<fn() -> (W, R)>,
+}
+
+impl<W, const NUM: i64, const DEN: i64, R> Scaled<W, NUM, DEN, R> {
+    /// The scale of zero does not convert, so the types with it fail to build, where they are used
+    const NONZERO: () = assert!(
+        NUM != 0 && DEN != 0,
+        "the scale of `Scaled` can not be zero"
+    );
+
+    /// The value in the units of the rust side, like `21.5` °C of the milli-Celsius
+    pub const fn new(value: f64) -> Self {
+        let () = Self::NONZERO;
+        Self {
+            value,
+            marker: PhantomData,
+        }
+    }
+
+    /// The value
+    pub fn get(self) -> f64 {
+        self.value
+    }
+}
+
+impl<W: ScaledInt, const NUM: i64, const DEN: i64, R: Rounding> Scaled<W, NUM, DEN, R> {
+    /// The value of the proto integer
+    pub fn from_wire(wire: W) -> Self {
+        Self::new(match NUM {
+            1 => wire.to_f64() / DEN as f64,
+            _ => wire.to_f64() * NUM as f64 / DEN as f64,
+        })
+    }
+
+    /// The proto integer of the value, it is an error, when the value does not fit in to it
+    pub fn to_wire(self) -> anyhow::Result<W> {
+        let scaled = match NUM {
+            1 => self.value * DEN as f64,
+            _ => self.value * DEN as f64 / NUM as f64,
+        };
+        match W::from_f64(R::round(scaled)) {
+            Some(wire) => Ok(wire),
+            None => anyhow::bail!(
+                "The value {} scaled by {DEN}/{NUM} does not fit in to {}",
+                self.value,
+                W::NAME
+            ),
+        }
+    }
+}
+
+impl<W, const NUM: i64, const DEN: i64, R> fmt::Debug for Scaled<W, NUM, DEN, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Scaled").field(&self.value).finish()
+    }
+}
+
+impl<W, const NUM: i64, const DEN: i64, R> fmt::Display for Scaled<W, NUM, DEN, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<W, const NUM: i64, const DEN: i64, R> Clone for Scaled<W, NUM, DEN, R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<W, const NUM: i64, const DEN: i64, R> Copy for Scaled<W, NUM, DEN, R> {}
+
+impl<W, const NUM: i64, const DEN: i64, R> PartialEq for Scaled<W, NUM, DEN, R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<W, const NUM: i64, const DEN: i64, R> PartialOrd for Scaled<W, NUM, DEN, R> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<W, const NUM: i64, const DEN: i64, R> Default for Scaled<W, NUM, DEN, R> {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+impl<W, const NUM: i64, const DEN: i64, R> From<f64> for Scaled<W, NUM, DEN, R> {
+    fn from(value: f64) -> Self {
+        Self::new(value)
+    }
+}
+
+/// The integer amount `T` carried in the proto `double` multiplied by `SCALE`, like the cents of the
+/// price, which has to be within `TOLERANCE_PPM` millionths of the integer unit
+pub struct FloatToScaledInt<T, const SCALE: i64, const TOLERANCE_PPM: u32 = 1_000>(T);
+
+impl<T, const SCALE: i64, const TOLERANCE_PPM: u32> FloatToScaledInt<T, SCALE, TOLERANCE_PPM> {
+    /// The amount in the integer units, like `1999` cents
+    pub const fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// The amount
+    pub fn get(self) -> T {
+        self.0
+    }
+}
+
+impl<T: ScaledInt, const SCALE: i64, const TOLERANCE_PPM: u32>
+    FloatToScaledInt<T, SCALE, TOLERANCE_PPM>
+{
+    /// The amount of the proto value, it is an error, when it is not finite, it is not within the
+    /// tolerance of an integer after the scaling, or the integer does not fit in to `T`
+    pub fn from_wire(wire: f64) -> anyhow::Result<Self> {
+        let scaled = wire * SCALE as f64;
+        if !scaled.is_finite() {
+            return Err(coded(
+                ErrorCode::InvalidFormat,
+                format!("The value {wire} scaled by {SCALE} is not finite"),
+            ));
+        }
+        let nearest = scaled.round();
+        let Some(value) = T::from_f64(nearest) else {
+            let bound = if nearest > 0.0 { T::MAX } else { T::MIN };
+            return Err(coded(
+                ErrorCode::OutOfRange,
+                format!(
+                    "The value {wire} scaled by {SCALE} does not fit in to {}, the nearest is {bound}",
+                    T::NAME
+                ),
+            ));
+        };
+        let tolerance = f64::from(TOLERANCE_PPM) / 1_000_000.0;
+        if (scaled - nearest).abs() > tolerance {
+            return Err(coded(
+                ErrorCode::InvalidFormat,
+                format!(
+                    "The value {wire} scaled by {SCALE} is not within {tolerance} of an integer, \
+                     the nearest is {value}"
+                ),
+            ));
+        }
+        Ok(Self(value))
+    }
+
+    /// The proto value of the amount, the integers over 2^53 are rounded
+    pub fn to_wire(self) -> f64 {
+        self.0.to_f64() / SCALE as f64
+    }
+}
+
+impl<T: fmt::Debug, const SCALE: i64, const TOLERANCE_PPM: u32> fmt::Debug
+    for FloatToScaledInt<T, SCALE, TOLERANCE_PPM>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FloatToScaledInt").field(&self.0).finish()
+    }
+}
+
+impl<T: fmt::Display, const SCALE: i64, const TOLERANCE_PPM: u32> fmt::Display
+    for FloatToScaledInt<T, SCALE, TOLERANCE_PPM>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: Clone, const SCALE: i64, const TOLERANCE_PPM: u32> Clone
+    for FloatToScaledInt<T, SCALE, TOLERANCE_PPM>
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Copy, const SCALE: i64, const TOLERANCE_PPM: u32> Copy
+    for FloatToScaledInt<T, SCALE, TOLERANCE_PPM>
+{
+}
+
+impl<T: PartialEq, const SCALE: i64, const TOLERANCE_PPM: u32> PartialEq
+    for FloatToScaledInt<T, SCALE, TOLERANCE_PPM>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: Eq, const SCALE: i64, const TOLERANCE_PPM: u32> Eq
+    for FloatToScaledInt<T, SCALE, TOLERANCE_PPM>
+{
+}
+
+impl<T: PartialOrd, const SCALE: i64, const TOLERANCE_PPM: u32> PartialOrd
+    for FloatToScaledInt<T, SCALE, TOLERANCE_PPM>
+{
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.0.partial_cmp(&other.0)
+    }
+}
+
+impl<T: Default, const SCALE: i64, const TOLERANCE_PPM: u32> Default
+    for FloatToScaledInt<T, SCALE, TOLERANCE_PPM>
+{
+    fn default() -> Self {
+        Self(T::default())
+    }
+}
+
+impl<T, const SCALE: i64, const TOLERANCE_PPM: u32> From<T>
+    for FloatToScaledInt<T, SCALE, TOLERANCE_PPM>
+{
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    type MilliCelsius = Scaled<i32, 1, 1000>;
+    type Cents = Scaled<u32, 1, 100>;
+    type Tenths = Scaled<i16, 1, 10>;
+    type RoundedTenths<R> = Scaled<i16, 1, 10, R>;
+    type Kilometers = Scaled<i64, 1000, 1>;
+
+    impl_traits!(prelude);
+
+    #[test]
+    fn test_roundtrip() {
+        for wire in [0, 1, -1, 21_500, -40_125, i32::MAX, i32::MIN] {
+            let value = MilliCelsius::from_proto_alias(wire).unwrap();
+            assert_eq!(value.try_to_proto().unwrap(), wire);
+        }
+        assert_eq!(MilliCelsius::from_proto_alias(21_500).unwrap().get(), 21.5);
+        assert_eq!(MilliCelsius::from_proto_alias(-1).unwrap().get(), -0.001);
+        assert_eq!(Cents::from_proto_alias(1999u32).unwrap().get(), 19.99);
+        assert_eq!(Cents::new(19.99).try_to_proto().unwrap(), 1999);
+        assert_eq!(Kilometers::from_proto_alias(42i64).unwrap().get(), 42_000.0);
+        assert_eq!(Kilometers::new(41_600.0).try_to_proto().unwrap(), 42);
+    }
+
+    #[test]
+    fn test_rounding() {
+        assert_eq!(Tenths::new(0.25).try_to_proto().unwrap(), 2);
+        assert_eq!(Tenths::new(0.75).try_to_proto().unwrap(), 8);
+        assert_eq!(Tenths::new(-0.25).try_to_proto().unwrap(), -2);
+        assert_eq!(Tenths::new(0.26).try_to_proto().unwrap(), 3);
+
+        assert_eq!(
+            RoundedTenths::<HalfAwayFromZero>::new(0.25)
+                .try_to_proto()
+                .unwrap(),
+            3
+        );
+        assert_eq!(
+            RoundedTenths::<HalfAwayFromZero>::new(-0.25)
+                .try_to_proto()
+                .unwrap(),
+            -3
+        );
+        assert_eq!(
+            RoundedTenths::<TowardZero>::new(-0.29)
+                .try_to_proto()
+                .unwrap(),
+            -2
+        );
+        assert_eq!(
+            RoundedTenths::<Floor>::new(-0.21).try_to_proto().unwrap(),
+            -3
+        );
+        assert_eq!(RoundedTenths::<Ceil>::new(0.21).try_to_proto().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_overflow() {
+        assert_eq!(Tenths::new(3276.7).try_to_proto().unwrap(), i16::MAX);
+        let error = Tenths::new(3276.8).try_to_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The value 3276.8 scaled by 10/1 does not fit in to i16"
+        );
+        assert!(Tenths::new(-3276.9).try_to_proto().is_err());
+        let error = Cents::new(-0.01).try_to_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The value -0.01 scaled by 100/1 does not fit in to u32"
+        );
+        // rounded to zero, so it fits
+        assert_eq!(Cents::new(-0.001).try_to_proto().unwrap(), 0);
+        assert!(MilliCelsius::new(f64::NAN).try_to_proto().is_err());
+        assert!(MilliCelsius::new(f64::INFINITY).try_to_proto().is_err());
+        assert!(Scaled::<i64, 1, 1>::new(9.3e18).try_to_proto().is_err());
+    }
+
+    #[test]
+    fn test_list() {
+        let values = Vec::<MilliCelsius>::from_proto_alias(vec![-500, 1500]).unwrap();
+        assert_eq!(values, [MilliCelsius::new(-0.5), MilliCelsius::new(1.5)]);
+    }
+
+    type PriceCents = FloatToScaledInt<i64, 100>;
+
+    #[test]
+    fn test_float_amounts() {
+        assert_eq!(PriceCents::from_proto_alias(19.99).unwrap().get(), 1999);
+        assert_eq!(PriceCents::from_proto_alias(-0.07).unwrap().get(), -7);
+        // 0.30000000000000004 is within the tolerance of 30 cents
+        assert_eq!(PriceCents::from_proto_alias(0.1 + 0.2).unwrap().get(), 30);
+        let micros = FloatToScaledInt::<i64, 1_000_000>::from_proto_alias(0.1 + 0.7).unwrap();
+        assert_eq!(micros.get(), 800_000);
+        assert_eq!(
+            ToProtoAlias::<f64>::to_proto_alias(&PriceCents::new(1999)),
+            19.99
+        );
+        for wire in [0.0, 0.01, 12_345.67, -99.5] {
+            let cents = PriceCents::from_proto_alias(wire).unwrap();
+            assert_eq!(cents.to_proto_alias(), wire);
+        }
+    }
+
+    #[test]
+    fn test_float_amounts_rejected() {
+        let error = PriceCents::from_proto_alias(19.991).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The value 19.991 scaled by 100 is not within 0.001 of an integer, the nearest is 1999"
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::InvalidFormat);
+        // the wider tolerance of the sloppy producers
+        let cents = FloatToScaledInt::<i64, 100, 200_000>::from_proto_alias(19.991).unwrap();
+        assert_eq!(cents.get(), 1999);
+
+        let error = PriceCents::from_proto_alias(f64::NAN).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The value NaN scaled by 100 is not finite"
+        );
+        assert!(PriceCents::from_proto_alias(f64::INFINITY).is_err());
+        assert!(PriceCents::from_proto_alias(f64::NEG_INFINITY).is_err());
+
+        let error = PriceCents::from_proto_alias(1e17).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "The value 100000000000000000 scaled by 100 does not fit in to i64, the nearest is {}",
+                i64::MAX
+            )
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::OutOfRange);
+        let error = FloatToScaledInt::<u32, 100>::from_proto_alias(-0.01).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The value -0.01 scaled by 100 does not fit in to u32, the nearest is 0"
+        );
+    }
+}
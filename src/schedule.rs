@@ -0,0 +1,345 @@
+//! The schedules of the repeated instants, like the jobs run every hour from a start,
+//! ```proto
+//! message Schedule {
+//!   google.protobuf.Timestamp start = 1;
+//!   google.protobuf.Duration interval = 2;
+//!   uint32 count = 3;
+//! }
+//! ```
+//! converted in to `Schedule` with `impl_traits!(Schedule, schedule::Schedule { start, interval, count
+//! })`, where `start`, `interval` and `count` are the names of the fields, whose messages convert in to
+//! `chrono::DateTime<chrono::Utc>` and `chrono::Duration`. Both messages are required.
+//!
+//! The `count` of `0` is the schedule without the end, `Repeat::Forever`, the other ones are the number
+//! of the occurrences including the start. The last occurrence is computed at the conversion, so the
+//! schedules ending after the last instant of chrono are an error naming the overflowing part, and the
+//! occurrences of the bounded ones never overflow. The negative intervals are an error, the zero one
+//! only for the single occurrence.
+
+use std::fmt;
+use std::num::NonZeroU32;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::chrono_compat;
+use crate::error::in_field;
+use crate::violation::{coded, ErrorCode};
+
+/// How many times the schedule occurs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Repeat {
+    /// Without the end, the proto `count` of `0`
+    Forever,
+    /// The number of the occurrences, including the start
+    Times(NonZeroU32),
+}
+
+impl Repeat {
+    /// The repeat of the proto `count`
+    pub fn from_count(count: u32) -> Self {
+        NonZeroU32::new(count).map_or(Repeat::Forever, Repeat::Times)
+    }
+
+    /// The proto `count`, `0` for `Forever`
+    pub fn count(self) -> u32 {
+        match self {
+            Repeat::Forever => 0,
+            Repeat::Times(times) => times.get(),
+        }
+    }
+}
+
+impl fmt::Display for Repeat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Repeat::Forever => f.write_str("forever"),
+            Repeat::Times(times) => write!(f, "{times} times"),
+        }
+    }
+}
+
+/// The instants from the `start` every `interval`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Schedule {
+    start: DateTime<Utc>,
+    interval: Duration,
+    repeat: Repeat,
+    last: Option<DateTime<Utc>>,
+}
+
+impl Schedule {
+    /// The schedule, if its interval is valid and its last occurrence is in the chrono range. The
+    /// errors have the path of the proto field, which is wrong.
+    pub fn new(start: DateTime<Utc>, interval: Duration, repeat: Repeat) -> anyhow::Result<Self> {
+        let single = repeat == Repeat::Times(NonZeroU32::MIN);
+        if interval < Duration::zero() {
+            let error = anyhow::anyhow!("The interval {} is negative", duration(interval));
+            return Err(in_field(error, "interval"));
+        }
+        if interval == Duration::zero() && !single {
+            let error = anyhow::anyhow!("The interval is zero, but the schedule repeats {repeat}");
+            return Err(in_field(error, "interval"));
+        }
+        let last = match repeat {
+            Repeat::Forever => None,
+            Repeat::Times(times) => Some(last(start, interval, times.get() - 1)?),
+        };
+        Ok(Self {
+            start,
+            interval,
+            repeat,
+            last,
+        })
+    }
+
+    /// The first occurrence
+    pub fn start(&self) -> DateTime<Utc> {
+        self.start
+    }
+
+    /// The time between the occurrences
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn repeat(&self) -> Repeat {
+        self.repeat
+    }
+
+    /// The last occurrence, `None` for `Repeat::Forever`
+    pub fn last(&self) -> Option<DateTime<Utc>> {
+        self.last
+    }
+
+    /// The occurrences in order, the ones of `Repeat::Forever` end at the last instant of chrono
+    pub fn occurrences(&self) -> Occurrences {
+        Occurrences {
+            next: Some(self.start),
+            interval: self.interval,
+            remaining: match self.repeat {
+                Repeat::Forever => None,
+                Repeat::Times(times) => Some(times.get()),
+            },
+        }
+    }
+}
+
+impl IntoIterator for &Schedule {
+    type Item = DateTime<Utc>;
+    type IntoIter = Occurrences;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.occurrences()
+    }
+}
+
+/// The iterator of the occurrences of `Schedule`
+#[derive(Debug, Clone)]
+pub struct Occurrences {
+    next: Option<DateTime<Utc>>,
+    interval: Duration,
+    /// `None` without the end
+    remaining: Option<u32>,
+}
+
+impl Iterator for Occurrences {
+    type Item = DateTime<Utc>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        self.next = match &mut self.remaining {
+            Some(1) => None,
+            Some(remaining) => {
+                *remaining -= 1;
+                // checked by `Schedule::new`
+                current.checked_add_signed(self.interval)
+            }
+            None => current.checked_add_signed(self.interval),
+        };
+        Some(current)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match (self.next, self.remaining) {
+            (None, _) => (0, Some(0)),
+            (Some(_), Some(remaining)) => (remaining as usize, Some(remaining as usize)),
+            (Some(_), None) => (1, None),
+        }
+    }
+}
+
+/// The duration in the errors, like `3600s`
+fn duration(value: Duration) -> String {
+    let (seconds, nanos) = chrono_compat::duration_parts(&value);
+    match nanos {
+        0 => format!("{seconds}s"),
+        nanos => format!("{seconds}.{:09}s", nanos.unsigned_abs()),
+    }
+}
+
+/// The occurrence `repeats` intervals after the start
+fn last(start: DateTime<Utc>, interval: Duration, repeats: u32) -> anyhow::Result<DateTime<Utc>> {
+    let (seconds, nanos) = chrono_compat::duration_parts(&interval);
+    let total = (i128::from(seconds) * 1_000_000_000 + i128::from(nanos)) * i128::from(repeats);
+    let offset = i64::try_from(total.div_euclid(1_000_000_000))
+        .ok()
+        .and_then(chrono_compat::try_seconds)
+        .and_then(|offset| {
+            let nanos = chrono_compat::nanoseconds(total.rem_euclid(1_000_000_000) as i64);
+            chrono_compat::checked_add(offset, nanos)
+        });
+    let Some(offset) = offset else {
+        let message = format!(
+            "The interval {} repeated {repeats} times is out of the range of the durations",
+            duration(interval)
+        );
+        return Err(in_field(coded(ErrorCode::OutOfRange, message), "count"));
+    };
+    start.checked_add_signed(offset).ok_or_else(|| {
+        let message = format!(
+            "The last occurrence {} after the start {} is after the last instant",
+            duration(offset),
+            chrono_compat::format(&start)
+        );
+        in_field(coded(ErrorCode::OutOfRange, message), "start")
+    })
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Timestamp {
+        seconds: i64,
+        nanos: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoDuration {
+        seconds: i64,
+        nanos: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoSchedule {
+        start: Option<Timestamp>,
+        interval: Option<ProtoDuration>,
+        count: u32,
+    }
+
+    impl_traits!(prelude);
+    impl_traits!(Timestamp, chrono::DateTime<chrono::Utc>);
+    impl_traits!(ProtoDuration, chrono::Duration);
+    impl_traits!(
+        ProtoSchedule,
+        schedule::Schedule {
+            start,
+            interval,
+            count
+        }
+    );
+
+    fn proto(seconds: i64, interval: i64, count: u32) -> ProtoSchedule {
+        ProtoSchedule {
+            start: Some(Timestamp { seconds, nanos: 0 }),
+            interval: Some(ProtoDuration {
+                seconds: interval,
+                nanos: 0,
+            }),
+            count,
+        }
+    }
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        chrono_compat::from_unix(seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn test_bounded() {
+        let schedule = proto(1_000, 3_600, 3).from_proto().unwrap();
+        assert_eq!(schedule.repeat(), Repeat::from_count(3));
+        assert_eq!(schedule.last(), Some(at(8_200)));
+        let occurrences: Vec<_> = schedule.occurrences().collect();
+        assert_eq!(occurrences, [at(1_000), at(4_600), at(8_200)]);
+        assert_eq!(schedule.occurrences().size_hint(), (3, Some(3)));
+
+        // the single occurrence can have no interval
+        let schedule = proto(1_000, 0, 1).from_proto().unwrap();
+        assert_eq!((&schedule).into_iter().collect::<Vec<_>>(), [at(1_000)]);
+    }
+
+    #[test]
+    fn test_forever() {
+        let schedule = proto(0, 60, 0).from_proto().unwrap();
+        assert_eq!(schedule.repeat(), Repeat::Forever);
+        assert_eq!(schedule.last(), None);
+        let occurrences: Vec<_> = schedule.occurrences().take(3).collect();
+        assert_eq!(occurrences, [at(0), at(60), at(120)]);
+
+        // until the last instant
+        let max = chrono_compat::max_unix_seconds();
+        let schedule = proto(max - 90, 60, 0).from_proto().unwrap();
+        assert_eq!(schedule.occurrences().count(), 2);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let max = chrono_compat::max_unix_seconds();
+        let error = proto(max - 90, 60, 3).from_proto().unwrap_err();
+        let message = format!(
+            "start: The last occurrence 120s after the start {} is after the last instant",
+            chrono_compat::format(&at(max - 90))
+        );
+        assert_eq!(error.to_string(), message);
+        assert_eq!(ErrorCode::of(&error), ErrorCode::OutOfRange);
+        assert!(proto(max - 90, 60, 2).from_proto().is_ok());
+
+        let error = proto(0, i64::MAX / 1000, u32::MAX)
+            .from_proto()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "count: The interval 9223372036854775s repeated 4294967294 times is out of the range of \
+             the durations"
+        );
+    }
+
+    #[test]
+    fn test_invalid_interval() {
+        let error = proto(0, 0, 2).from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "interval: The interval is zero, but the schedule repeats 2 times"
+        );
+        let error = proto(0, 0, 0).from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "interval: The interval is zero, but the schedule repeats forever"
+        );
+        let error = proto(0, -60, 2).from_proto().unwrap_err();
+        assert_eq!(error.to_string(), "interval: The interval -60s is negative");
+
+        let error = ProtoSchedule {
+            interval: None,
+            ..proto(0, 60, 2)
+        }
+        .from_proto()
+        .unwrap_err();
+        assert_eq!(error.to_string(), "interval: Required is missing");
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for proto in [proto(1_000, 3_600, 3), proto(-5, 1, 0), proto(7, 0, 1)] {
+            let schedule = proto.clone().from_proto().unwrap();
+            let back: ProtoSchedule = schedule.to_proto_alias();
+            assert_eq!(back, proto);
+        }
+        let schedule = Schedule::new(at(0), chrono_compat::seconds(60), Repeat::Forever).unwrap();
+        let back: Option<ProtoSchedule> = schedule.to_proto_alias();
+        assert_eq!(back, Some(proto(0, 60, 0)));
+    }
+}
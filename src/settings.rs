@@ -0,0 +1,293 @@
+//! The string settings, like `map<string, string> settings = 5;`, converted in to the typed config
+//! structs with `impl_proto_settings!`.
+//! ```ignore
+//! impl_proto_settings!(Config {
+//!     (timeout: chrono::Duration, via = seconds, key = "timeout_seconds"),
+//!     (retries: u32, default = 3),
+//!     (mode: Mode),
+//! });
+//! ```
+//! The values are parsed with `FromStr` and written with `Display`, or with the `from_proto` and
+//! `to_proto` of the module given with `via`. The keys are the field names, unless given with `key`.
+//! The missing keys are an error, unless the field has a `default`. The errors have the key in the
+//! path, like `settings["retries"]: Invalid value "many": invalid digit found in string`.
+//!
+//! The unknown keys are ignored, with `impl_proto_settings!(deny_unknown Config { .. })` they are an
+//! error. The settings are always written with all the keys.
+//!
+//! The prelude converts any `HashMap<K, V>` with `FromProto`, so the settings are converted with
+//! `FromProtoAlias<HashMap<String, String>>` and `ToProtoAlias<HashMap<String, String>>`, which are
+//! also used for the derived fields of the config structs.
+
+use std::collections::HashMap;
+
+/// The settings, as they are in the proto map
+pub type Settings = HashMap<String, String>;
+
+/// Remove and convert the value of the `key`, used by `impl_proto_settings!`
+#[doc(hidden)]
+pub fn take<T>(
+    settings: &mut Settings,
+    key: &'static str,
+    convert: impl FnOnce(String) -> anyhow::Result<T>,
+) -> anyhow::Result<Option<T>> {
+    match settings.remove(key) {
+        Some(value) => convert(value)
+            .map(Some)
+            .map_err(|error| crate::error::at_key(error, &key)),
+        None => Ok(None),
+    }
+}
+
+/// The required `key` is missing, used by `impl_proto_settings!`
+#[doc(hidden)]
+pub fn missing(key: &'static str) -> anyhow::Error {
+    crate::error::at_key(anyhow::anyhow!("Required is missing"), &key)
+}
+
+/// The settings left after taking the known keys are an error, used by `impl_proto_settings!`
+#[doc(hidden)]
+pub fn deny_unknown(settings: Settings) -> anyhow::Result<()> {
+    let mut keys: Vec<_> = settings.into_keys().collect();
+    keys.sort_unstable();
+    match keys.as_slice() {
+        [] => Ok(()),
+        [key] => anyhow::bail!("The key {key:?} is unknown"),
+        keys => anyhow::bail!("The keys {keys:?} are unknown"),
+    }
+}
+
+/// Implement `FromProtoAlias<HashMap<String, String>>` of the config struct and `ToProtoAlias` back,
+/// the traits of the `impl_traits!(prelude)` have to be in the scope.
+/// ```ignore
+/// impl_proto_settings!(Config {
+///     (timeout: chrono::Duration, via = seconds, key = "timeout_seconds"),
+///     (retries: u32, default = 3),
+///     (mode: Mode),
+/// });
+/// ```
+/// The options of a field are given in the order `via`, `key` and `default`.
+#[macro_export]
+macro_rules! impl_proto_settings {
+    (deny_unknown $type: ident { $($fields: tt)* }) => {
+        $crate::impl_proto_settings!(@impl $type, true, $($fields)*);
+    };
+    ($type: ident { $($fields: tt)* }) => {
+        $crate::impl_proto_settings!(@impl $type, false, $($fields)*);
+    };
+    (@impl $type: ident, $deny_unknown: literal, $((
+        $field: ident: $field_type: ty
+        $(, via = $($via: ident)::+)?
+        $(, key = $key: literal)?
+        $(, default = $default: expr)?
+    )),* $(,)?) => {
+        impl FromProtoAlias<$crate::settings::Settings> for $type {
+            fn from_proto_alias(mut settings: $crate::settings::Settings) -> anyhow::Result<Self> {
+                let value = Self {
+                    $($field: {
+                        let key = $crate::impl_proto_settings!(@key $field $(, $key)?);
+                        let value: Option<$field_type> = $crate::settings::take(
+                            &mut settings,
+                            key,
+                            $crate::impl_proto_settings!(@from $(, $($via)::+)?),
+                        )?;
+                        match value {
+                            Some(value) => value,
+                            None => $crate::impl_proto_settings!(@missing key $(, $default)?),
+                        }
+                    },)*
+                };
+                if $deny_unknown {
+                    $crate::settings::deny_unknown(settings)?;
+                }
+                Ok(value)
+            }
+        }
+
+        impl ToProtoAlias<$crate::settings::Settings> for $type {
+            fn to_proto_alias(&self) -> $crate::settings::Settings {
+                let mut settings = $crate::settings::Settings::new();
+                $(
+                    let key = $crate::impl_proto_settings!(@key $field $(, $key)?);
+                    let value = $crate::impl_proto_settings!(@to &self.$field $(, $($via)::+)?);
+                    settings.insert(key.to_owned(), value);
+                )*
+                settings
+            }
+        }
+
+        /// The optional settings of the fields
+        impl ToProtoAlias<Option<$crate::settings::Settings>> for $type {
+            fn to_proto_alias(&self) -> Option<$crate::settings::Settings> {
+                Some(ToProtoAlias::<$crate::settings::Settings>::to_proto_alias(self))
+            }
+        }
+    };
+    (@key $field: ident, $key: literal) => {
+        $key
+    };
+    (@key $field: ident) => {
+        stringify!($field)
+    };
+    (@from, $($via: ident)::+) => {
+        $($via)::+::from_proto
+    };
+    (@from) => {
+        $crate::bag::parse
+    };
+    (@to $value: expr, $($via: ident)::+) => {
+        $($via)::+::to_proto($value)
+    };
+    (@to $value: expr) => {
+        ::std::string::ToString::to_string($value)
+    };
+    (@missing $key: ident, $default: expr) => {
+        $default
+    };
+    (@missing $key: ident) => {
+        return Err($crate::settings::missing($key))
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use std::fmt;
+    use std::str::FromStr;
+
+    use super::Settings;
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Mode {
+        Fast,
+        Safe,
+    }
+
+    impl FromStr for Mode {
+        type Err = String;
+
+        fn from_str(value: &str) -> Result<Self, String> {
+            match value {
+                "fast" => Ok(Mode::Fast),
+                "safe" => Ok(Mode::Safe),
+                _ => Err("expected fast or safe".to_owned()),
+            }
+        }
+    }
+
+    impl fmt::Display for Mode {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Mode::Fast => write!(f, "fast"),
+                Mode::Safe => write!(f, "safe"),
+            }
+        }
+    }
+
+    /// The durations in whole seconds, like `30s`
+    mod seconds {
+        pub fn from_proto(value: String) -> anyhow::Result<chrono::Duration> {
+            match value.strip_suffix('s').map(str::parse) {
+                Some(Ok(seconds)) => Ok(chrono::Duration::seconds(seconds)),
+                _ => anyhow::bail!("Invalid duration {value:?}"),
+            }
+        }
+
+        pub fn to_proto(value: &chrono::Duration) -> String {
+            format!("{}s", value.num_seconds())
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Config {
+        timeout: chrono::Duration,
+        retries: u32,
+        mode: Mode,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct StrictConfig {
+        retries: u32,
+    }
+
+    impl_traits!(prelude);
+    impl_proto_settings!(Config {
+        (timeout: chrono::Duration, via = seconds, key = "timeout_seconds"),
+        (retries: u32, default = 3),
+        (mode: Mode),
+    });
+    impl_proto_settings!(deny_unknown StrictConfig { (retries: u32, default = 3) });
+
+    fn settings(entries: &[(&str, &str)]) -> Settings {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_settings() {
+        let proto = settings(&[
+            ("timeout_seconds", "30s"),
+            ("retries", "5"),
+            ("mode", "safe"),
+        ]);
+        let config = Config::from_proto_alias(proto.clone()).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                timeout: chrono::Duration::seconds(30),
+                retries: 5,
+                mode: Mode::Safe,
+            }
+        );
+        assert_eq!(ToProtoAlias::<Settings>::to_proto_alias(&config), proto);
+    }
+
+    #[test]
+    fn test_default() {
+        let proto = settings(&[("timeout_seconds", "1s"), ("mode", "fast"), ("other", "x")]);
+        let config = Config::from_proto_alias(proto).unwrap();
+        assert_eq!(config.retries, 3);
+        // the default is written too
+        let proto = ToProtoAlias::<Settings>::to_proto_alias(&config);
+        assert_eq!(proto["retries"], "3");
+        assert!(!proto.contains_key("other"));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let error = Config::from_proto_alias(settings(&[("timeout_seconds", "1s")])).unwrap_err();
+        assert_eq!(error.to_string(), "[\"mode\"]: Required is missing");
+
+        let proto = settings(&[("timeout_seconds", "1s"), ("mode", "slow")]);
+        let error = Config::from_proto_alias(proto).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "[\"mode\"]: Invalid value \"slow\": expected fast or safe"
+        );
+
+        let proto = settings(&[("timeout_seconds", "soon"), ("mode", "fast")]);
+        let error = Config::from_proto_alias(proto).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "[\"timeout_seconds\"]: Invalid duration \"soon\""
+        );
+    }
+
+    #[test]
+    fn test_deny_unknown() {
+        let config = StrictConfig::from_proto_alias(settings(&[("retries", "1")])).unwrap();
+        assert_eq!(config.retries, 1);
+
+        let proto = settings(&[("retries", "1"), ("timeout", "1s"), ("mode", "fast")]);
+        let error = StrictConfig::from_proto_alias(proto).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The keys [\"mode\", \"timeout\"] are unknown"
+        );
+        let error = StrictConfig::from_proto_alias(settings(&[("mode", "fast")])).unwrap_err();
+        assert_eq!(error.to_string(), "The key \"mode\" is unknown");
+    }
+}
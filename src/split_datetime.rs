@@ -0,0 +1,573 @@
+//! The date times of the legacy schemas split in to the string fields of the date, the time and the
+//! time zone, like
+//! ```proto
+//! message Appointment {
+//!   // like "2024-03-01"
+//!   string date = 1;
+//!   // like "14:30:00", or "14:30:00.250"
+//!   string time = 2;
+//!   // like "Europe/Prague"
+//!   string tz = 3;
+//! }
+//! ```
+//! are reassembled with `impl_proto_split_datetime!`, where the names are the ones of the fields, in to
+//! the type the fields are expected to give:
+//! ```ignore
+//! impl_proto_split_datetime!(proto::Birthday { date, time } => chrono::NaiveDate);
+//! impl_proto_split_datetime!(proto::Meeting { date, time } => chrono::NaiveDateTime);
+//! impl_proto_split_datetime!(proto::Appointment { date, time, tz } => chrono::DateTime<chrono_tz::Tz>);
+//! impl_proto_split_datetime!(proto::Reminder { date, time } => Option<chrono::NaiveDateTime>);
+//! ```
+//! The empty strings are the absent parts. All of the parts of the type have to be present, only some
+//! of them present are `ErrorCode::MissingField` of the missing one, naming the given ones, and the
+//! time given for `chrono::NaiveDate` is `ErrorCode::Invalid`. All of them absent are the missing
+//! field error of the date, or `None` of the `Option` types. The parts, which do not parse, are
+//! `ErrorCode::InvalidFormat` of their field, the zoned date times need the `chrono-tz` feature.
+//!
+//! The local times repeated by the DST, like 02:30 in Prague on the last Sunday of October, are the
+//! earlier of the two instants, as the first one the clock shows, the ones skipped by the DST are an
+//! error. Into proto the parts are written in the canonical forms, `2024-03-01`, `14:30:00` with the
+//! fraction of the second only when there is one, and the IANA name of the zone, the time of
+//! `chrono::NaiveDate` is empty.
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::error::in_field;
+use crate::violation::{coded, ErrorCode};
+
+fn date(name: &'static str, value: &str) -> anyhow::Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+        in_field(
+            coded(
+                ErrorCode::InvalidFormat,
+                format!("The date `{value}` is not a valid date of the form YYYY-MM-DD"),
+            ),
+            name,
+        )
+    })
+}
+
+fn time(name: &'static str, value: &str) -> anyhow::Result<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M:%S%.f").map_err(|_| {
+        in_field(
+            coded(
+                ErrorCode::InvalidFormat,
+                format!("The time `{value}` is not a valid time of the form HH:MM:SS"),
+            ),
+            name,
+        )
+    })
+}
+
+/// All the `parts` are present, or none of them, the names of the given and the missing ones are in
+/// the error of the others
+fn present(parts: &[(&'static str, &str)]) -> anyhow::Result<bool> {
+    let (given, missing): (Vec<_>, Vec<_>) = parts
+        .iter()
+        .copied()
+        .partition(|(_, value)| !value.is_empty());
+    match (given.is_empty(), missing.first()) {
+        (true, _) => Ok(false),
+        (false, None) => Ok(true),
+        (false, Some((name, _))) => {
+            let names = |parts: &[(&str, &str)]| {
+                parts
+                    .iter()
+                    .map(|(name, _)| format!("`{name}`"))
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            };
+            let verb = |parts: &[(&str, &str)]| if parts.len() > 1 { "are" } else { "is" };
+            Err(in_field(
+                coded(
+                    ErrorCode::MissingField,
+                    format!(
+                        "The {} {} missing, while the {} {} given",
+                        names(&missing),
+                        verb(&missing),
+                        names(&given),
+                        verb(&given)
+                    ),
+                ),
+                name,
+            ))
+        }
+    }
+}
+
+/// The date of the proto parts, the time has to be absent, used by `impl_proto_split_datetime!`
+#[doc(hidden)]
+pub fn to_date(
+    (date_name, date_value): (&'static str, &str),
+    (time_name, time_value): (&'static str, &str),
+) -> anyhow::Result<Option<NaiveDate>> {
+    if !time_value.is_empty() {
+        return Err(in_field(
+            coded(
+                ErrorCode::Invalid,
+                format!("The time `{time_value}` is given for the date without the time"),
+            ),
+            time_name,
+        ));
+    }
+    if date_value.is_empty() {
+        return Ok(None);
+    }
+    date(date_name, date_value).map(Some)
+}
+
+/// The local date time of the proto parts, used by `impl_proto_split_datetime!`
+#[doc(hidden)]
+pub fn to_date_time(
+    (date_name, date_value): (&'static str, &str),
+    (time_name, time_value): (&'static str, &str),
+) -> anyhow::Result<Option<NaiveDateTime>> {
+    if !present(&[(date_name, date_value), (time_name, time_value)])? {
+        return Ok(None);
+    }
+    Ok(Some(NaiveDateTime::new(
+        date(date_name, date_value)?,
+        time(time_name, time_value)?,
+    )))
+}
+
+/// The date time in the zone of the proto parts, the repeated local times are the earlier ones, used
+/// by `impl_proto_split_datetime!`
+#[cfg(feature = "chrono-tz")]
+#[doc(hidden)]
+pub fn to_zoned(
+    (date_name, date_value): (&'static str, &str),
+    (time_name, time_value): (&'static str, &str),
+    (zone_name, zone_value): (&'static str, &str),
+) -> anyhow::Result<Option<chrono::DateTime<chrono_tz::Tz>>> {
+    use chrono::{LocalResult, TimeZone};
+
+    if !present(&[
+        (date_name, date_value),
+        (time_name, time_value),
+        (zone_name, zone_value),
+    ])? {
+        return Ok(None);
+    }
+    let local = NaiveDateTime::new(date(date_name, date_value)?, time(time_name, time_value)?);
+    let zone =
+        crate::business_date::zone(zone_value).map_err(|error| in_field(error, zone_name))?;
+    match zone.from_local_datetime(&local) {
+        LocalResult::Single(value) | LocalResult::Ambiguous(value, _) => Ok(Some(value)),
+        LocalResult::None => Err(in_field(
+            coded(
+                ErrorCode::Invalid,
+                format!("The local time {local} is skipped by the DST in the time zone {zone}"),
+            ),
+            time_name,
+        )),
+    }
+}
+
+/// All the parts are missing of the required value, used by `impl_proto_split_datetime!`
+#[doc(hidden)]
+pub fn required<T>(value: Option<T>, date_name: &'static str) -> anyhow::Result<T> {
+    value.ok_or_else(|| {
+        in_field(
+            coded(ErrorCode::MissingField, "The date is missing"),
+            date_name,
+        )
+    })
+}
+
+/// The canonical form of the date, used by `impl_proto_split_datetime!`
+#[doc(hidden)]
+pub fn date_string(value: &NaiveDate) -> String {
+    value.format("%Y-%m-%d").to_string()
+}
+
+/// The canonical form of the time, with the fraction only when there is one, used by
+/// `impl_proto_split_datetime!`
+#[doc(hidden)]
+pub fn time_string(value: &NaiveTime) -> String {
+    value.format("%H:%M:%S%.f").to_string()
+}
+
+/// Implement `FromProto` of the message of the split string parts of the date time, and
+/// `ToProtoAlias` back in to it, the traits of the `impl_traits!(prelude)` have to be in the scope.
+/// ```ignore
+/// impl_proto_split_datetime!(proto::Meeting { date, time } => chrono::NaiveDateTime);
+/// impl_proto_split_datetime!(proto::Appointment { date, time, tz } => chrono::DateTime<chrono_tz::Tz>);
+/// ```
+/// The types are `chrono::NaiveDate`, `chrono::NaiveDateTime`, `chrono::DateTime<chrono_tz::Tz>` with
+/// the `chrono-tz` feature, and their `Option`, see `split_datetime`.
+#[macro_export]
+macro_rules! impl_proto_split_datetime {
+    ($proto: path { $date: ident, $time: ident $(,)? } => chrono::NaiveDate) => {
+        $crate::impl_proto_split_datetime!(@required $proto, chrono::NaiveDate, $date);
+        $crate::impl_proto_split_datetime!(@date $proto { $date, $time });
+    };
+    ($proto: path { $date: ident, $time: ident $(,)? } => Option<chrono::NaiveDate>) => {
+        $crate::impl_proto_split_datetime!(@optional $proto, chrono::NaiveDate);
+        $crate::impl_proto_split_datetime!(@date $proto { $date, $time });
+    };
+    ($proto: path { $date: ident, $time: ident $(,)? } => chrono::NaiveDateTime) => {
+        $crate::impl_proto_split_datetime!(@required $proto, chrono::NaiveDateTime, $date);
+        $crate::impl_proto_split_datetime!(@date_time $proto { $date, $time });
+    };
+    ($proto: path { $date: ident, $time: ident $(,)? } => Option<chrono::NaiveDateTime>) => {
+        $crate::impl_proto_split_datetime!(@optional $proto, chrono::NaiveDateTime);
+        $crate::impl_proto_split_datetime!(@date_time $proto { $date, $time });
+    };
+    (
+        $proto: path { $date: ident, $time: ident, $tz: ident $(,)? }
+            => chrono::DateTime<chrono_tz::Tz>
+    ) => {
+        $crate::impl_proto_split_datetime!(
+            @required $proto,
+            chrono::DateTime<$crate::__private::chrono_tz::Tz>,
+            $date
+        );
+        $crate::impl_proto_split_datetime!(@zoned $proto { $date, $time, $tz });
+    };
+    (
+        $proto: path { $date: ident, $time: ident, $tz: ident $(,)? }
+            => Option<chrono::DateTime<chrono_tz::Tz>>
+    ) => {
+        $crate::impl_proto_split_datetime!(
+            @optional $proto,
+            chrono::DateTime<$crate::__private::chrono_tz::Tz>
+        );
+        $crate::impl_proto_split_datetime!(@zoned $proto { $date, $time, $tz });
+    };
+    // the proto message converts in to the optional value of the parts, the required one is the value
+    (@required $proto: path, $rust: ty, $date: ident) => {
+        impl FromProto for $proto {
+            type Result = $rust;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                $crate::split_datetime::required(
+                    FromProtoAlias::<$proto>::from_proto_alias(self)?,
+                    stringify!($date),
+                )
+            }
+        }
+
+        impl ToProtoAlias<$proto> for $rust {
+            fn to_proto_alias(&self) -> $proto {
+                ToProtoAlias::<$proto>::to_proto_alias(&Some(*self))
+            }
+        }
+
+        /// The optional message fields
+        impl ToProtoAlias<Option<$proto>> for $rust {
+            fn to_proto_alias(&self) -> Option<$proto> {
+                Some(ToProtoAlias::<$proto>::to_proto_alias(self))
+            }
+        }
+    };
+    (@optional $proto: path, $rust: ty) => {
+        impl FromProto for $proto {
+            type Result = Option<$rust>;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                FromProtoAlias::<$proto>::from_proto_alias(self)
+            }
+        }
+    };
+    (@date $proto: path { $date: ident, $time: ident }) => {
+        /// All the parts absent are `None`
+        impl FromProtoAlias<$proto> for Option<chrono::NaiveDate> {
+            fn from_proto_alias(value: $proto) -> anyhow::Result<Self> {
+                $crate::split_datetime::to_date(
+                    (stringify!($date), &value.$date),
+                    (stringify!($time), &value.$time),
+                )
+            }
+        }
+
+        /// `None` is written as all the parts absent
+        impl ToProtoAlias<$proto> for Option<chrono::NaiveDate> {
+            fn to_proto_alias(&self) -> $proto {
+                let mut proto = <$proto as ::core::default::Default>::default();
+                if let Some(value) = self {
+                    proto.$date = $crate::split_datetime::date_string(value);
+                }
+                proto
+            }
+        }
+    };
+    (@date_time $proto: path { $date: ident, $time: ident }) => {
+        /// All the parts absent are `None`
+        impl FromProtoAlias<$proto> for Option<chrono::NaiveDateTime> {
+            fn from_proto_alias(value: $proto) -> anyhow::Result<Self> {
+                $crate::split_datetime::to_date_time(
+                    (stringify!($date), &value.$date),
+                    (stringify!($time), &value.$time),
+                )
+            }
+        }
+
+        /// `None` is written as all the parts absent
+        impl ToProtoAlias<$proto> for Option<chrono::NaiveDateTime> {
+            fn to_proto_alias(&self) -> $proto {
+                let mut proto = <$proto as ::core::default::Default>::default();
+                if let Some(value) = self {
+                    proto.$date = $crate::split_datetime::date_string(&value.date());
+                    proto.$time = $crate::split_datetime::time_string(&value.time());
+                }
+                proto
+            }
+        }
+    };
+    (@zoned $proto: path { $date: ident, $time: ident, $tz: ident }) => {
+        /// All the parts absent are `None`
+        impl FromProtoAlias<$proto> for Option<chrono::DateTime<$crate::__private::chrono_tz::Tz>> {
+            fn from_proto_alias(value: $proto) -> anyhow::Result<Self> {
+                $crate::split_datetime::to_zoned(
+                    (stringify!($date), &value.$date),
+                    (stringify!($time), &value.$time),
+                    (stringify!($tz), &value.$tz),
+                )
+            }
+        }
+
+        /// `None` is written as all the parts absent
+        impl ToProtoAlias<$proto> for Option<chrono::DateTime<$crate::__private::chrono_tz::Tz>> {
+            fn to_proto_alias(&self) -> $proto {
+                let mut proto = <$proto as ::core::default::Default>::default();
+                if let Some(value) = self {
+                    let local = value.naive_local();
+                    proto.$date = $crate::split_datetime::date_string(&local.date());
+                    proto.$time = $crate::split_datetime::time_string(&local.time());
+                    proto.$tz = value.timezone().name().to_owned();
+                }
+                proto
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoBirthday {
+        date: String,
+        time: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoMeeting {
+        date: String,
+        time: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoReminder {
+        day: String,
+        at: String,
+    }
+
+    impl_proto_split_datetime!(ProtoBirthday { date, time } => chrono::NaiveDate);
+    impl_proto_split_datetime!(ProtoMeeting { date, time } => chrono::NaiveDateTime);
+    impl_proto_split_datetime!(ProtoReminder { day, at } => Option<chrono::NaiveDateTime>);
+
+    fn parts<P: Default>(date: &str, time: &str, build: fn(&mut P, String, String)) -> P {
+        let mut proto = P::default();
+        build(&mut proto, date.to_owned(), time.to_owned());
+        proto
+    }
+
+    fn meeting(date: &str, time: &str) -> ProtoMeeting {
+        parts(date, time, |proto: &mut ProtoMeeting, date, time| {
+            proto.date = date;
+            proto.time = time;
+        })
+    }
+
+    fn reminder(day: &str, at: &str) -> ProtoReminder {
+        parts(day, at, |proto: &mut ProtoReminder, day, at| {
+            proto.day = day;
+            proto.at = at;
+        })
+    }
+
+    fn date_time(value: &str) -> NaiveDateTime {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_date_time() {
+        let value = meeting("2024-03-01", "14:30:00").from_proto().unwrap();
+        assert_eq!(value, date_time("2024-03-01T14:30:00"));
+        assert_eq!(
+            ToProtoAlias::<ProtoMeeting>::to_proto_alias(&value),
+            meeting("2024-03-01", "14:30:00")
+        );
+        let value = meeting("2024-03-01", "14:30:00.25").from_proto().unwrap();
+        assert_eq!(value, date_time("2024-03-01T14:30:00.250"));
+        assert_eq!(
+            ToProtoAlias::<ProtoMeeting>::to_proto_alias(&value),
+            meeting("2024-03-01", "14:30:00.250")
+        );
+    }
+
+    #[test]
+    fn test_presence() {
+        // all the combinations of the present and absent parts
+        let error = meeting("", "").from_proto().unwrap_err();
+        assert_eq!(error.to_string(), "date: The date is missing");
+        assert_eq!(ErrorCode::of(&error), ErrorCode::MissingField);
+        let error = meeting("2024-03-01", "").from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "time: The `time` is missing, while the `date` is given"
+        );
+        let error = meeting("", "14:30:00").from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "date: The `date` is missing, while the `time` is given"
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::MissingField);
+        assert!(meeting("2024-03-01", "14:30:00").from_proto().is_ok());
+
+        assert_eq!(reminder("", "").from_proto().unwrap(), None);
+        let error = reminder("", "08:00:00").from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day: The `day` is missing, while the `at` is given"
+        );
+        assert_eq!(
+            reminder("2024-03-01", "08:00:00").from_proto().unwrap(),
+            Some(date_time("2024-03-01T08:00:00"))
+        );
+        assert_eq!(
+            ToProtoAlias::<ProtoReminder>::to_proto_alias(&None::<NaiveDateTime>),
+            reminder("", "")
+        );
+    }
+
+    #[test]
+    fn test_date() {
+        let birthday = ProtoBirthday {
+            date: "1990-07-14".to_owned(),
+            time: String::new(),
+        };
+        let value = birthday.clone().from_proto().unwrap();
+        assert_eq!(value, NaiveDate::from_ymd_opt(1990, 7, 14).unwrap());
+        assert_eq!(
+            ToProtoAlias::<ProtoBirthday>::to_proto_alias(&value),
+            birthday
+        );
+
+        let mut with_time = birthday.clone();
+        with_time.time = "06:00:00".to_owned();
+        let error = with_time.from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "time: The time `06:00:00` is given for the date without the time"
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::Invalid);
+        let error = ProtoBirthday::default().from_proto().unwrap_err();
+        assert_eq!(error.to_string(), "date: The date is missing");
+    }
+
+    #[test]
+    fn test_invalid_parts() {
+        let error = meeting("2024-03-01", "25:00:00").from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "time: The time `25:00:00` is not a valid time of the form HH:MM:SS"
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::InvalidFormat);
+        let error = meeting("2024-02-30", "14:30:00").from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "date: The date `2024-02-30` is not a valid date of the form YYYY-MM-DD"
+        );
+        assert!(meeting("01/03/2024", "14:30:00").from_proto().is_err());
+        assert!(meeting("2024-03-01", "14:30").from_proto().is_err());
+    }
+
+    #[cfg(feature = "chrono-tz")]
+    mod zoned {
+        use chrono::{DateTime, Utc};
+        use chrono_tz::Tz;
+
+        use super::*;
+
+        #[derive(Debug, Clone, PartialEq, Default)]
+        pub struct ProtoAppointment {
+            date: String,
+            time: String,
+            tz: String,
+        }
+
+        impl_proto_split_datetime!(ProtoAppointment { date, time, tz } => chrono::DateTime<chrono_tz::Tz>);
+
+        fn appointment(date: &str, time: &str, tz: &str) -> ProtoAppointment {
+            ProtoAppointment {
+                date: date.to_owned(),
+                time: time.to_owned(),
+                tz: tz.to_owned(),
+            }
+        }
+
+        fn utc(value: &str) -> DateTime<Utc> {
+            value.parse().unwrap()
+        }
+
+        #[test]
+        fn test_zoned() {
+            let proto = appointment("2024-03-01", "14:30:00", "Europe/Prague");
+            let value: DateTime<Tz> = proto.clone().from_proto().unwrap();
+            assert_eq!(value, utc("2024-03-01T13:30:00Z"));
+            assert_eq!(
+                ToProtoAlias::<ProtoAppointment>::to_proto_alias(&value),
+                proto
+            );
+
+            let error = appointment("2024-03-01", "14:30:00", "")
+                .from_proto()
+                .unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "tz: The `tz` is missing, while the `date` and `time` are given"
+            );
+            let error = appointment("", "", "Europe/Prague")
+                .from_proto()
+                .unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "date: The `date` and `time` are missing, while the `tz` is given"
+            );
+            let error = appointment("2024-03-01", "14:30:00", "Europe/Praha")
+                .from_proto()
+                .unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "tz: The time zone `Europe/Praha` is unknown"
+            );
+        }
+
+        #[test]
+        fn test_dst() {
+            // Prague repeats 02:00 to 03:00 on 2024-10-27, the earlier is of the summer time
+            let value: DateTime<Tz> = appointment("2024-10-27", "02:30:00", "Europe/Prague")
+                .from_proto()
+                .unwrap();
+            assert_eq!(value, utc("2024-10-27T00:30:00Z"));
+            // and skips 02:00 to 03:00 on 2024-03-31
+            let error = appointment("2024-03-31", "02:30:00", "Europe/Prague")
+                .from_proto()
+                .unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "time: The local time 2024-03-31 02:30:00 is skipped by the DST in the time zone \
+                 Europe/Prague"
+            );
+            assert_eq!(ErrorCode::of(&error), ErrorCode::Invalid);
+        }
+    }
+}
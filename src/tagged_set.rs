@@ -0,0 +1,338 @@
+//! The repeated string tags, like `repeated string tags`, of which some are the names of the variants
+//! of a known enum and the rest are free-form, converted in to `TaggedSet` of the known variants and
+//! the free-form tags.
+//! ```ignore
+//! #[derive(Debug, Clone, Copy, PartialEq, Eq, FromProto, ToProto)]
+//! #[proto(string, case_insensitive)]
+//! enum Topic {
+//!     Billing,
+//!     #[proto(alias = "ops")]
+//!     Operations,
+//! }
+//!
+//! #[derive(FromProto, ToProto)]
+//! #[proto(type = "proto::Ticket")]
+//! struct Ticket {
+//!     // repeated string tags = 1;
+//!     tags: TaggedSet<Topic>,
+//! }
+//! ```
+//! The tags are matched with the names of the derived `#[proto(string)]` enums, `ProtoNamed`, with
+//! their aliases and case policy, the lenient matches record the deprecation notices as the other
+//! string enums. The tags of none of the variants are kept as they are, in their order.
+//!
+//! The duplicate tags collapse in to one, also the different names of the same variant, like `ops`
+//! and `OPERATIONS`, `FromProtoLossy` reports them as `LossyEvent::DuplicateTag`. The set converts
+//! back in to the canonical names of the known variants, in the order of the variants, followed by the
+//! free-form tags in their order, so the aliases are written as the canonical names.
+
+use std::fmt;
+
+use crate::enumeration::ProtoNamed;
+use crate::lossy::{ConversionReport, LossyEvent};
+
+/// The set of the known variants of the enum `E` and of the free-form tags
+#[derive(Clone, PartialEq, Eq)]
+pub struct TaggedSet<E> {
+    /// In the order of the variants
+    known: Vec<E>,
+    /// In the order of the proto tags, without the duplicates
+    free_form: Vec<String>,
+}
+
+impl<E: ProtoNamed> TaggedSet<E> {
+    /// The set without any tag
+    pub fn new() -> Self {
+        Self {
+            known: Vec::new(),
+            free_form: Vec::new(),
+        }
+    }
+
+    /// The set of the proto tags, the duplicates collapse in to one
+    pub fn from_tags(tags: Vec<String>) -> Self {
+        Self::collect(tags, None)
+    }
+
+    fn collect(tags: Vec<String>, mut report: Option<&mut ConversionReport>) -> Self {
+        let mut set = Self::new();
+        for (index, tag) in tags.into_iter().enumerate() {
+            let inserted = match E::match_name(&tag) {
+                Ok((known, _)) => set.insert(known),
+                Err(_) => set.insert_free_form(tag.clone()),
+            };
+            if let (Some(report), false) = (report.as_deref_mut(), inserted) {
+                report.append(
+                    ConversionReport::event(LossyEvent::DuplicateTag { tag }).at_index(index),
+                );
+            }
+        }
+        set
+    }
+
+    /// The canonical names of the known variants, in their order, and the free-form tags
+    pub fn to_tags(&self) -> Vec<String> {
+        self.known
+            .iter()
+            .map(|known| known.canonical_name().to_owned())
+            .chain(self.free_form.iter().cloned())
+            .collect()
+    }
+
+    /// Whether the variant is in the set
+    pub fn contains(&self, value: &E) -> bool {
+        self.position(value).is_ok()
+    }
+
+    /// Whether the tag is in the set, the name of a known variant, also its alias, or a free-form tag
+    pub fn contains_tag(&self, tag: &str) -> bool {
+        match E::match_name(tag) {
+            Ok((known, _)) => self.contains(&known),
+            Err(_) => self.free_form.iter().any(|free_form| free_form == tag),
+        }
+    }
+
+    /// Add the variant, `true` when it was not in the set
+    pub fn insert(&mut self, value: E) -> bool {
+        match self.position(&value) {
+            Ok(_) => false,
+            Err(position) => {
+                self.known.insert(position, value);
+                true
+            }
+        }
+    }
+
+    /// Add the tag, as the known variant of its name, or as the free-form tag, `true` when it was not
+    /// in the set
+    pub fn insert_tag(&mut self, tag: impl Into<String>) -> bool {
+        let tag = tag.into();
+        match E::match_name(&tag) {
+            Ok((known, _)) => self.insert(known),
+            Err(_) => self.insert_free_form(tag),
+        }
+    }
+
+    fn insert_free_form(&mut self, tag: String) -> bool {
+        if self.free_form.contains(&tag) {
+            return false;
+        }
+        self.free_form.push(tag);
+        true
+    }
+
+    /// Remove the variant, `true` when it was in the set
+    pub fn remove(&mut self, value: &E) -> bool {
+        match self.position(value) {
+            Ok(position) => {
+                self.known.remove(position);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Remove the tag, of a known variant or free-form, `true` when it was in the set
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        if let Ok((known, _)) = E::match_name(tag) {
+            return self.remove(&known);
+        }
+        let len = self.free_form.len();
+        self.free_form.retain(|free_form| free_form != tag);
+        self.free_form.len() != len
+    }
+
+    /// The known variants, in their order
+    pub fn known(&self) -> impl Iterator<Item = &E> + '_ {
+        self.known.iter()
+    }
+
+    /// The free-form tags, in the order of the proto tags
+    pub fn free_form(&self) -> &[String] {
+        &self.free_form
+    }
+
+    /// The number of the known variants and the free-form tags
+    pub fn len(&self) -> usize {
+        self.known.len() + self.free_form.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.known.is_empty() && self.free_form.is_empty()
+    }
+
+    /// The position of the variant in the known ones, or where it is inserted
+    fn position(&self, value: &E) -> Result<usize, usize> {
+        let index = value.name_index();
+        self.known
+            .binary_search_by_key(&index, |known| known.name_index())
+    }
+}
+
+/// Convert the repeated tags with the report of the duplicate tags, used by the prelude
+#[doc(hidden)]
+pub fn from_proto_lossy<E: ProtoNamed>(
+    tags: Vec<String>,
+) -> anyhow::Result<(TaggedSet<E>, ConversionReport)> {
+    let mut report = ConversionReport::new();
+    let set = TaggedSet::collect(tags, Some(&mut report));
+    Ok((set, report))
+}
+
+impl<E: ProtoNamed> Default for TaggedSet<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: ProtoNamed> fmt::Debug for TaggedSet<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.to_tags()).finish()
+    }
+}
+
+/// Convert the repeated string tags in to `TaggedSet`, this is part of the `impl_traits!(prelude)`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_tagged_set {
+    (prelude) => {};
+    () => {
+        impl<E: $crate::enumeration::ProtoNamed> FromProtoAlias<Vec<String>>
+            for $crate::tagged_set::TaggedSet<E>
+        {
+            fn from_proto_alias(value: Vec<String>) -> anyhow::Result<Self> {
+                Ok(Self::from_tags(value))
+            }
+
+            fn from_proto_alias_lossy(
+                value: Vec<String>,
+            ) -> anyhow::Result<(Self, $crate::lossy::ConversionReport)> {
+                $crate::tagged_set::from_proto_lossy(value)
+            }
+        }
+
+        impl<E: $crate::enumeration::ProtoNamed> ToProtoAlias<Vec<String>>
+            for $crate::tagged_set::TaggedSet<E>
+        {
+            fn to_proto_alias(&self) -> Vec<String> {
+                self.to_tags()
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::enumeration::{from_proto_name, NameMatch, VariantNames};
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    /// The enum as derived with `#[proto(string, case_insensitive)]`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Topic {
+        Billing,
+        Operations,
+        Security,
+    }
+
+    impl ProtoNamed for Topic {
+        const NAMES: &'static [&'static str] = &["BILLING", "OPERATIONS", "SECURITY"];
+
+        fn name_index(&self) -> usize {
+            *self as usize
+        }
+
+        fn match_name(value: &str) -> anyhow::Result<(Self, NameMatch)> {
+            const NAMES: &[VariantNames] = &[
+                (
+                    "BILLING",
+                    &[],
+                    "use \"BILLING\", the canonical name of Topic",
+                ),
+                (
+                    "OPERATIONS",
+                    &["ops"],
+                    "use \"OPERATIONS\", the canonical name of Topic",
+                ),
+                (
+                    "SECURITY",
+                    &[],
+                    "use \"SECURITY\", the canonical name of Topic",
+                ),
+            ];
+            let (index, matched) = from_proto_name(value, "Topic", NAMES, true)?;
+            let topic = [Topic::Billing, Topic::Operations, Topic::Security][index];
+            Ok((topic, matched))
+        }
+    }
+
+    type Tags = TaggedSet<Topic>;
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let set =
+            Tags::from_proto_alias(tags(&["team-search", "SECURITY", "ops", "p1", "billing"]))
+                .unwrap();
+        assert_eq!(
+            set.known().collect::<Vec<_>>(),
+            [&Topic::Billing, &Topic::Operations, &Topic::Security]
+        );
+        assert_eq!(set.free_form(), ["team-search", "p1"]);
+        assert_eq!(set.len(), 5);
+        assert_eq!(
+            ToProtoAlias::<Vec<String>>::to_proto_alias(&set),
+            tags(&["BILLING", "OPERATIONS", "SECURITY", "team-search", "p1"])
+        );
+        let back = Tags::from_proto_alias(set.to_tags()).unwrap();
+        assert_eq!(back, set);
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let proto = tags(&["ops", "p1", "OPERATIONS", "p1", "P1", "Operations"]);
+        let set = Tags::from_proto_alias(proto.clone()).unwrap();
+        assert_eq!(set.to_tags(), tags(&["OPERATIONS", "p1", "P1"]));
+
+        let (lossy, report) = Tags::from_proto_alias_lossy(proto).unwrap();
+        assert_eq!(lossy, set);
+        assert_eq!(report.len(), 3);
+        assert_eq!(
+            report.events("[2]").collect::<Vec<_>>(),
+            [&LossyEvent::DuplicateTag {
+                tag: "OPERATIONS".to_owned()
+            }]
+        );
+        assert_eq!(
+            report.events("[3]").next().unwrap().to_string(),
+            "The duplicate tag \"p1\" was collapsed"
+        );
+    }
+
+    #[test]
+    fn test_queries() {
+        let mut set = Tags::new();
+        assert!(set.is_empty());
+        assert!(set.insert_tag("Ops"));
+        assert!(!set.insert(Topic::Operations));
+        assert!(set.insert_tag("team-search"));
+        assert!(!set.insert_tag("team-search"));
+        assert!(set.insert(Topic::Billing));
+        assert!(set.contains(&Topic::Operations));
+        assert!(set.contains_tag("operations"));
+        assert!(set.contains_tag("team-search"));
+        assert!(!set.contains_tag("Team-Search"));
+        assert!(!set.contains(&Topic::Security));
+
+        assert!(set.remove_tag("OPS"));
+        assert!(!set.remove(&Topic::Operations));
+        assert!(set.remove_tag("team-search"));
+        assert_eq!(format!("{set:?}"), "{\"BILLING\"}");
+    }
+}
@@ -0,0 +1,540 @@
+//! Helpers for testing the conversions, with the `test-util` feature.
+//!
+//! The prelude gets `assert_roundtrip_from` and `assert_roundtrip_to`, asserting the value is the same
+//! after converting it there and back, they panic with the `roundtrip::RoundtripDiff`, which has the
+//! lines of `proto_diff`. The values converting with `ToProtoAlias`, like the chrono types, are
+//! asserted with `assert_roundtrip_from_alias` and `assert_roundtrip_to_alias::<Proto, _>`, and
+//! `assert_validate_consistent` asserts the dry run of `ValidateFromProto` agrees with `FromProto`.
+//! With the `prost` feature, `proto_diff_encoded` compares the encoded messages, so also the messages
+//! equal, but not encoded the same, like of the maps in other order, are found. The proptest strategies generate the valid parts of the seconds and nanos proto messages,
+//! for fuzzing the chrono conversions.
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn test_duration((seconds, nanos) in test_util::duration_parts()) {
+//!         assert_roundtrip_from_alias(proto::Duration { seconds, nanos: nanos as i32 });
+//!     }
+//! }
+//! ```
+//! The benchmarks of the conversions, of this crate in `benches/conversions.rs` and of the other
+//! crates on their own types, build their inputs with `synthetic` and `synthetic_map` of the values of
+//! `Synthetic`, the same on every run, in the sizes of the suite, like `SCALAR_MESSAGES`.
+//! ```ignore
+//! let readings = test_util::synthetic(test_util::SCALAR_MESSAGES, |index, values| proto::Reading {
+//!     sensor: index as u64,
+//!     value: values.f64_in(-40.0..50.0),
+//! });
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::ops::Range;
+use std::ops::RangeInclusive;
+
+use proptest::strategy::Strategy;
+
+/// The seconds of `chrono::Duration`, with any nanos
+pub const DURATION_SECONDS: RangeInclusive<i64> = -(i64::MAX / 1000 - 1)..=i64::MAX / 1000 - 1;
+
+/// The seconds of `chrono::DateTime<Utc>` from `MIN_UTC` to `MAX_UTC`
+pub const DATE_TIME_SECONDS: RangeInclusive<i64> = -8_334_601_228_800..=8_210_266_876_799;
+
+/// The nanos of the seconds
+pub const NANOS: RangeInclusive<u32> = 0..=999_999_999;
+
+/// The seconds in the range with the valid nanos
+pub fn seconds_nanos(seconds: RangeInclusive<i64>) -> impl Strategy<Value = (i64, u32)> {
+    (seconds, NANOS)
+}
+
+/// The seconds and nanos of any `chrono::Duration`
+pub fn duration_parts() -> impl Strategy<Value = (i64, u32)> {
+    seconds_nanos(DURATION_SECONDS)
+}
+
+/// The seconds and nanos of any `chrono::DateTime<Utc>`
+pub fn date_time_parts() -> impl Strategy<Value = (i64, u32)> {
+    seconds_nanos(DATE_TIME_SECONDS)
+}
+
+/// The lines of the pretty `Debug` of the values, the `expected` lines are marked with `-` and the
+/// `actual` ones with `+`, `None` when the values are equal
+pub fn proto_diff<P: PartialEq + fmt::Debug>(expected: &P, actual: &P) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+    let lines = crate::roundtrip::diff(&format!("{expected:#?}"), &format!("{actual:#?}"));
+    let lines: Vec<_> = lines.iter().map(ToString::to_string).collect();
+    Some(lines.join("\n"))
+}
+
+/// The first offset, where the encoded messages differ, with both of the lengths and the lines of
+/// `proto_diff`, `None` when they are encoded the same
+#[cfg(feature = "prost")]
+pub fn proto_diff_encoded<M>(expected: &M, actual: &M) -> Option<String>
+where
+    M: prost::Message + PartialEq + fmt::Debug,
+{
+    let expected_bytes = expected.encode_to_vec();
+    let actual_bytes = actual.encode_to_vec();
+    if expected_bytes == actual_bytes {
+        return None;
+    }
+    let offset = expected_bytes
+        .iter()
+        .zip(&actual_bytes)
+        .position(|(expected, actual)| expected != actual)
+        .unwrap_or(expected_bytes.len().min(actual_bytes.len()));
+    let mut diff = format!(
+        "The encoded messages differ at the byte {offset}, the expected one has {} bytes and the \
+         actual one {}",
+        expected_bytes.len(),
+        actual_bytes.len()
+    );
+    match proto_diff(expected, actual) {
+        Some(lines) => diff.push_str(&format!("\n{lines}")),
+        None => diff.push_str("\nThe messages are equal, but encoded differently"),
+    }
+    Some(diff)
+}
+
+/// The count of the scalar messages of the repeated field in the suite
+pub const SCALAR_MESSAGES: usize = 100_000;
+
+/// The count of the messages with the timestamps and the strings in the suite
+pub const TIMESTAMP_MESSAGES: usize = 10_000;
+
+/// The count of the entries of the map with the typed keys in the suite
+pub const MAP_ENTRIES: usize = 1_000;
+
+/// The seed of the inputs of `synthetic` and `synthetic_map`
+pub const SEED: u64 = 0x5eed_c0de;
+
+/// The pseudo-random values of the synthetic inputs, the same for the same seed on every run and
+/// platform, the splitmix64 sequence, not for anything but the inputs
+#[derive(Debug, Clone)]
+pub struct Synthetic {
+    state: u64,
+}
+
+impl Synthetic {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        value ^ (value >> 31)
+    }
+
+    /// The integer in the `range`, which is not empty
+    pub fn i64_in(&mut self, range: Range<i64>) -> i64 {
+        let width = range.end.abs_diff(range.start);
+        range.start.wrapping_add((self.next_u64() % width) as i64)
+    }
+
+    /// The float in the `range`
+    pub fn f64_in(&mut self, range: Range<f64>) -> f64 {
+        let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        range.start + unit * (range.end - range.start)
+    }
+
+    pub fn bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    /// The lower case ASCII word of the length in the `range`
+    pub fn word(&mut self, len: Range<usize>) -> String {
+        let len = self.i64_in(len.start as i64..len.end as i64) as usize;
+        (0..len)
+            .map(|_| char::from(b'a' + (self.next_u64() % 26) as u8))
+            .collect()
+    }
+
+    /// The seconds and nanos of an instant between 2000 and 2040
+    pub fn timestamp_parts(&mut self) -> (i64, i32) {
+        let seconds = self.i64_in(946_684_800..2_208_988_800);
+        let nanos = self.i64_in(0..1_000_000_000) as i32;
+        (seconds, nanos)
+    }
+}
+
+/// The `count` inputs made by `element` of their index and the values from `SEED`
+pub fn synthetic<P>(count: usize, mut element: impl FnMut(usize, &mut Synthetic) -> P) -> Vec<P> {
+    let mut values = Synthetic::new(SEED);
+    (0..count)
+        .map(|index| element(index, &mut values))
+        .collect()
+}
+
+/// The map of the `count` entries made by `entry` of their index and the values from `SEED`, the keys of
+/// the different indices have to differ
+pub fn synthetic_map<K: Eq + Hash, V>(
+    count: usize,
+    mut entry: impl FnMut(usize, &mut Synthetic) -> (K, V),
+) -> HashMap<K, V> {
+    let mut values = Synthetic::new(SEED);
+    let map: HashMap<K, V> = (0..count).map(|index| entry(index, &mut values)).collect();
+    assert_eq!(
+        map.len(),
+        count,
+        "the keys of the synthetic map are not unique"
+    );
+    map
+}
+
+/// The round trip assertions of the prelude
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_test_util {
+    (prelude) => {
+        pub use super::{
+            assert_roundtrip_from, assert_roundtrip_from_alias, assert_roundtrip_to,
+            assert_roundtrip_to_alias, assert_validate_consistent,
+        };
+        pub use $crate::fault::FaultyProto;
+    };
+    () => {
+        impl<P: FromProto> FromProto for $crate::fault::FaultyProto<P> {
+            type Result = P::Result;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                self.convert(P::from_proto)
+            }
+        }
+
+        /// Assert the proto value is the same after converting it from proto and back
+        #[allow(dead_code)]
+        #[track_caller]
+        pub fn assert_roundtrip_from<P>(proto: P)
+        where
+            P: Clone + PartialEq + std::fmt::Debug + FromProto,
+            P::Result: ToProto<Result = P> + std::fmt::Debug,
+        {
+            if let Err(diff) = roundtrip::check_proto_roundtrip(&proto) {
+                panic!("{diff}");
+            }
+        }
+
+        /// Assert the value is the same after converting it in to proto and back
+        #[allow(dead_code)]
+        #[track_caller]
+        pub fn assert_roundtrip_to<T>(value: T)
+        where
+            T: ToProto + PartialEq + std::fmt::Debug,
+            T::Result: FromProto<Result = T> + std::fmt::Debug,
+        {
+            if let Err(diff) = roundtrip::check_domain_roundtrip(&value) {
+                panic!("{diff}");
+            }
+        }
+
+        /// Assert the proto value is the same after converting it from proto and back with
+        /// `ToProtoAlias`, like of the chrono types converting in to several proto types
+        #[allow(dead_code)]
+        #[track_caller]
+        pub fn assert_roundtrip_from_alias<P>(proto: P)
+        where
+            P: Clone + PartialEq + std::fmt::Debug + FromProto,
+            P::Result: ToProtoAlias<P> + std::fmt::Debug,
+        {
+            if let Err(diff) = roundtrip::check_proto_roundtrip_alias(&proto) {
+                panic!("{diff}");
+            }
+        }
+
+        /// Assert the value is the same after converting it in to the proto type `P` with
+        /// `ToProtoAlias` and back
+        #[allow(dead_code)]
+        #[track_caller]
+        pub fn assert_roundtrip_to_alias<P, T>(value: T)
+        where
+            T: ToProtoAlias<P> + PartialEq + std::fmt::Debug,
+            P: FromProto<Result = T> + std::fmt::Debug,
+        {
+            if let Err(diff) = roundtrip::check_domain_roundtrip_alias::<P, T>(&value) {
+                panic!("{diff}");
+            }
+        }
+
+        /// Assert `validate_proto` agrees with `from_proto`, the proto value without any violation
+        /// converts, and the error of the failed conversion is among the violations, see
+        /// `proto_conve::dry_run`
+        #[allow(dead_code)]
+        #[track_caller]
+        pub fn assert_validate_consistent<P>(proto: P)
+        where
+            P: Clone + std::fmt::Debug + ValidateFromProto,
+        {
+            let violations = proto.validate_proto();
+            match proto.clone().from_proto() {
+                Ok(_) if violations.is_empty() => {}
+                Ok(_) => panic!("{proto:?} converts, but it has the violations {violations:?}"),
+                Err(error) => {
+                    let violation = $crate::violation::FieldViolation::of(&error);
+                    if !violations.contains(&violation) {
+                        panic!(
+                            "The conversion of {proto:?} failed with {violation:?}, which is not \
+                             among the violations {violations:?}"
+                        );
+                    }
+                }
+            }
+        }
+    };
+}
+
+/// The property test of the `verify_roundtrip` flag of the `impl_traits!` arms, named by the proto
+/// type. The proto values of the seconds and nanos generators, also of the negated durations, with the
+/// negative nanos of the signed ones, are converted from proto and back, the durations are expected in
+/// their canonical encoding of `CanonicalProto`, the instants as they were. The attributes after the
+/// type are of the test, like the `should_panic` of the broken arms in the tests of this crate.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __verify_roundtrip {
+    (@duration $type: ident $(, #[$attr: meta])*) => {
+        $crate::__verify_roundtrip!(
+            @parts $type,
+            $crate::test_util::duration_parts(),
+            true,
+            |proto| {
+                let mut expected = ::core::clone::Clone::clone(proto);
+                CanonicalProto::canonicalize(&mut expected);
+                expected
+            }
+            $(, #[$attr])*
+        );
+    };
+    (@date_time $type: ident) => {
+        $crate::__verify_roundtrip!(@parts $type, $crate::test_util::date_time_parts(), false, |proto| {
+            ::core::clone::Clone::clone(proto)
+        });
+    };
+    (@parts $type: ident, $parts: expr, $negative: expr, $expected: expr $(, #[$attr: meta])*) => {
+        #[cfg(test)]
+        #[test]
+        $(#[$attr])*
+        #[allow(non_snake_case)]
+        fn $type() {
+            use $crate::__private::proptest::prelude::{any, ProptestConfig, TestCaseError};
+
+            let expected: fn(&$type) -> $type = $expected;
+            // the failures are not persisted in to the sources of the crate invoking the arm
+            let config = ProptestConfig {
+                failure_persistence: None,
+                ..ProptestConfig::default()
+            };
+            $crate::__private::proptest::proptest!(config, |((seconds, nanos) in $parts, negative in any::<bool>())| {
+                let Ok(positive) = ::core::convert::TryFrom::try_from(nanos as i64) else {
+                    return Ok(());
+                };
+                let proto = $type { seconds, nanos: positive };
+                // the negative nanos only of the durations, the unsigned ones are of the negated
+                // duration in its canonical encoding
+                let encoding = $crate::__private::nanos::encoding(&proto.nanos, false);
+                let signed = encoding == $crate::__private::nanos::Encoding::Signed;
+                let proto = match (negative && $negative, signed) {
+                    (true, true) => {
+                        let Ok(nanos) = ::core::convert::TryFrom::try_from(-(nanos as i64)) else {
+                            return Ok(());
+                        };
+                        $type { seconds, nanos }
+                    }
+                    (true, false) => {
+                        let nanos = $crate::__private::duration_nanos(seconds, nanos as i64, encoding);
+                        let negated = $crate::__private::nanos::canonical(-nanos, false, |seconds, nanos| {
+                            $type { seconds, nanos }
+                        });
+                        let Some(negated) = negated else {
+                            return Ok(());
+                        };
+                        negated
+                    }
+                    (false, _) => proto,
+                };
+                let value = FromProto::from_proto(::core::clone::Clone::clone(&proto))
+                    .map_err(|error| TestCaseError::fail(format!("{error:#}")))?;
+                let back: $type = ToProtoAlias::to_proto_alias(&value);
+                $crate::__private::proptest::prop_assert_eq!(back, expected(&proto));
+            });
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoTimestamp {
+        seconds: i64,
+        nanos: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Seconds(i64);
+
+    impl_traits!(prelude);
+
+    // the subsecond nanos are dropped, so the conversions are not inverse
+    impl FromProto for ProtoTimestamp {
+        type Result = Seconds;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(Seconds(self.seconds))
+        }
+    }
+
+    impl ToProto for Seconds {
+        type Result = ProtoTimestamp;
+
+        fn to_proto(&self) -> Self::Result {
+            ProtoTimestamp {
+                seconds: self.0,
+                nanos: 0,
+            }
+        }
+    }
+
+    #[test]
+    fn test_proto_diff() {
+        let proto = ProtoTimestamp {
+            seconds: 3,
+            nanos: 500,
+        };
+        let back = proto.clone().from_proto().unwrap().to_proto();
+        assert_eq!(
+            proto_diff(&proto, &back).unwrap(),
+            "  ProtoTimestamp {\n      seconds: 3,\n-     nanos: 500,\n+     nanos: 0,\n  }"
+        );
+        assert_eq!(proto_diff(&back, &back.clone()), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "-     nanos: 500,\n  +     nanos: 0,")]
+    fn test_roundtrip_diff() {
+        assert_roundtrip_from(ProtoTimestamp {
+            seconds: 3,
+            nanos: 500,
+        });
+    }
+
+    #[cfg(feature = "prost")]
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Score {
+        #[prost(double, tag = "1")]
+        value: f64,
+    }
+
+    #[cfg(feature = "prost")]
+    #[test]
+    fn test_proto_diff_encoded() {
+        let expected = Score { value: 0.5 };
+        let actual = Score::default();
+        assert!(proto_diff_encoded(&expected, &actual).unwrap().starts_with(
+            "The encoded messages differ at the byte 0, the expected one has 9 bytes and the \
+             actual one 0\n"
+        ));
+
+        let diff = proto_diff_encoded(&Score { value: 1.0 }, &Score { value: 2.0 }).unwrap();
+        assert!(diff.starts_with("The encoded messages differ at the byte 7, the expected one"));
+        assert!(diff.ends_with("-     value: 1.0,\n+     value: 2.0,\n  }"));
+        assert_eq!(proto_diff_encoded(&actual, &Score { value: 0.0 }), None);
+    }
+
+    #[test]
+    fn test_synthetic() {
+        let words = synthetic(100, |_, values| values.word(3..9));
+        assert_eq!(words, synthetic(100, |_, values| values.word(3..9)));
+        assert!(words.iter().all(
+            |word| (3..9).contains(&word.len()) && word.bytes().all(|b| b.is_ascii_lowercase())
+        ));
+
+        let parts = synthetic(1_000, |_, values| values.timestamp_parts());
+        assert!(parts
+            .iter()
+            .all(
+                |(seconds, nanos)| (946_684_800..2_208_988_800).contains(seconds)
+                    && (0..1_000_000_000).contains(nanos)
+            ));
+        let mut values = Synthetic::new(SEED);
+        assert!((0..1_000).all(|_| (-1.5..2.5).contains(&values.f64_in(-1.5..2.5))));
+        assert!((0..1_000).all(|_| (-3..3).contains(&values.i64_in(-3..3))));
+
+        let map = synthetic_map(MAP_ENTRIES, |index, values| (index, values.bool()));
+        assert_eq!(map.len(), MAP_ENTRIES);
+    }
+
+    #[test]
+    #[should_panic(expected = "the keys of the synthetic map are not unique")]
+    fn test_synthetic_duplicate_keys() {
+        synthetic_map(10, |index, _| (index % 3, ()));
+    }
+
+    /// The duration of the broken arm
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProtoSpan {
+        seconds: i64,
+        nanos: i32,
+    }
+
+    // the arm drops the sign of the nanos in to proto, its impls are of the same types both ways, so
+    // only the property test finds it
+    impl FromProto for ProtoSpan {
+        type Result = chrono::Duration;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(chrono::Duration::seconds(self.seconds)
+                + chrono::Duration::nanoseconds(self.nanos as i64))
+        }
+    }
+
+    impl ToProtoAlias<ProtoSpan> for chrono::Duration {
+        fn to_proto_alias(&self) -> ProtoSpan {
+            let (seconds, nanos) = crate::chrono_compat::duration_parts(self);
+            ProtoSpan {
+                seconds,
+                nanos: nanos.abs(),
+            }
+        }
+    }
+
+    impl CanonicalProto for ProtoSpan {
+        fn is_canonical(&self) -> bool {
+            let mut canonical = self.clone();
+            canonical.canonicalize();
+            canonical == *self
+        }
+
+        fn canonicalize(&mut self) {
+            let encoding = crate::__private::nanos::Encoding::Signed;
+            let nanos = crate::__private::duration_nanos(self.seconds, self.nanos as i64, encoding);
+            if let Some((seconds, nanos)) = crate::__private::canonical_duration(nanos, encoding) {
+                self.seconds = seconds;
+                self.nanos = nanos as i32;
+            }
+        }
+    }
+
+    impl_traits!(@inverse ProtoSpan, chrono::Duration);
+    crate::__verify_roundtrip!(@duration ProtoSpan, #[should_panic(expected = "Test failed: assertion failed")]);
+
+    #[test]
+    fn test_ranges() {
+        let min = chrono::DateTime::<chrono::Utc>::MIN_UTC;
+        let max = chrono::DateTime::<chrono::Utc>::MAX_UTC;
+        assert_eq!(DATE_TIME_SECONDS, min.timestamp()..=max.timestamp());
+        assert_eq!(max.timestamp_subsec_nanos(), *NANOS.end());
+
+        let max = chrono::Duration::seconds(*DURATION_SECONDS.end());
+        assert!(max
+            .checked_add(&chrono::Duration::nanoseconds(*NANOS.end() as i64))
+            .is_some());
+    }
+}
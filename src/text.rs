@@ -0,0 +1,338 @@
+//! Strings checked and normalized at the conversion, instead of in every handler.
+//! ```ignore
+//! #[derive(FromProto, ToProto)]
+//! #[proto(type = "proto::User")]
+//! struct User {
+//!     name: proto_conve::text::NonEmptyString,
+//!     email: proto_conve::text::Normalized<proto_conve::text::Lowercase>,
+//! }
+//! ```
+//! They convert from and to the proto `String` through `FromProtoAlias<String>` and
+//! `ToProtoAlias<String>`, so they are also used in `Option` and `Vec`.
+//!
+//! The hygiene of the strings of the other producers:
+//! - `NfcString`, with the `unicode` feature, is normalized to the Unicode NFC, so the names sent in
+//!   NFD, like by the file systems of macOS, are equal to the same ones in NFC
+//! - `StrictUtf8Bytes` is the text of a proto `bytes` field, its errors have the offset of the first
+//!   byte, which is not valid UTF-8
+//! - `BoundedString<MAX>` is at most `MAX` characters long
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use crate::violation::{coded, CodedError, ErrorCode};
+
+/// Trim the whitespace without copying the already trimmed string
+fn trim(value: String) -> String {
+    if value.trim().len() == value.len() {
+        value
+    } else {
+        value.trim().to_owned()
+    }
+}
+
+/// The string, which is not empty or only whitespace. It is trimmed, unless `TRIM` is `false`, then
+/// only the empty string is rejected and the value is kept as it is.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NonEmptyString<const TRIM: bool = true>(String);
+
+impl<const TRIM: bool> NonEmptyString<TRIM> {
+    /// The string, if it is not empty
+    pub fn new(value: impl Into<String>) -> anyhow::Result<Self> {
+        let value = value.into();
+        let value = if TRIM { trim(value) } else { value };
+        anyhow::ensure!(!value.is_empty(), "The value is empty");
+        Ok(Self(value))
+    }
+
+    /// The string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The owned string
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+/// The string without the leading and trailing whitespace
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct TrimmedString(String);
+
+impl TrimmedString {
+    /// The trimmed string
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(trim(value.into()))
+    }
+
+    /// The string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The owned string
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+/// The normalization of `Normalized` strings, like case folding
+pub trait Normalize {
+    /// Normalize the value
+    fn normalize(value: String) -> String;
+}
+
+/// Trimmed and lowercase, for the case insensitive values like emails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Lowercase;
+
+impl Normalize for Lowercase {
+    fn normalize(value: String) -> String {
+        trim(value).to_lowercase()
+    }
+}
+
+/// The string normalized with `N`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Normalized<N>(String, PhantomData<N>);
+
+impl<N: Normalize> Normalized<N> {
+    /// The normalized string
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(N::normalize(value.into()), PhantomData)
+    }
+}
+
+impl<N> Normalized<N> {
+    /// The string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The owned string
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+/// The string normalized in to the Unicode NFC, with the `unicode` feature
+#[cfg(feature = "unicode")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Nfc;
+
+#[cfg(feature = "unicode")]
+impl Normalize for Nfc {
+    fn normalize(value: String) -> String {
+        use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+        // most of the strings are in NFC already
+        if is_nfc_quick(value.chars()) == IsNormalized::Yes {
+            value
+        } else {
+            value.nfc().collect()
+        }
+    }
+}
+
+/// The string in the Unicode NFC, with the `unicode` feature
+#[cfg(feature = "unicode")]
+pub type NfcString = Normalized<Nfc>;
+
+/// The text of a proto `bytes` field, which has to be valid UTF-8
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct StrictUtf8Bytes(String);
+
+impl StrictUtf8Bytes {
+    /// The text of the bytes, the error has the offset of the first invalid sequence
+    pub fn new(value: Vec<u8>) -> anyhow::Result<Self> {
+        match String::from_utf8(value) {
+            Ok(value) => Ok(Self(value)),
+            Err(error) => Err(coded(
+                ErrorCode::InvalidFormat,
+                format_args!(
+                    "The bytes are not valid UTF-8 at the offset {}",
+                    error.utf8_error().valid_up_to()
+                ),
+            )),
+        }
+    }
+
+    /// The string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The owned string
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+/// The string of at most `MAX` characters
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct BoundedString<const MAX: usize>(String);
+
+impl<const MAX: usize> BoundedString<MAX> {
+    /// The string, if it is not longer than `MAX` characters
+    pub fn new(value: impl Into<String>) -> anyhow::Result<Self> {
+        let value = value.into();
+        // the characters are not more than the bytes
+        if value.len() > MAX {
+            let length = value.chars().count();
+            anyhow::ensure!(
+                length <= MAX,
+                CodedError::new(
+                    ErrorCode::OutOfRange,
+                    format!("The string has {length} characters, over the maximum {MAX}"),
+                )
+            );
+        }
+        Ok(Self(value))
+    }
+
+    /// The string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The owned string
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+macro_rules! impl_str {
+    ($([$($param:tt)*] $type:ty;)*) => {$(
+        impl<$($param)*> Deref for $type {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl<$($param)*> AsRef<str> for $type {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl<$($param)*> fmt::Display for $type {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    )*};
+}
+
+impl_str! {
+    [const TRIM: bool] NonEmptyString<TRIM>;
+    [] TrimmedString;
+    [N] Normalized<N>;
+    [] StrictUtf8Bytes;
+    [const MAX: usize] BoundedString<MAX>;
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    #[test]
+    fn test_non_empty() {
+        let name = NonEmptyString::<true>::from_proto_alias("  Ann ".to_string()).unwrap();
+        assert_eq!(name.as_str(), "Ann");
+        let error = NonEmptyString::<true>::from_proto_alias("   ".to_string()).unwrap_err();
+        assert_eq!(error.to_string(), "The value is empty");
+        assert!(NonEmptyString::<true>::from_proto_alias(String::new()).is_err());
+
+        let raw = NonEmptyString::<false>::from_proto_alias(" ".to_string()).unwrap();
+        assert_eq!(raw.as_str(), " ");
+        assert!(NonEmptyString::<false>::from_proto_alias(String::new()).is_err());
+    }
+
+    #[test]
+    fn test_trimmed() {
+        let value = TrimmedString::from_proto_alias("\t token \n".to_string()).unwrap();
+        assert_eq!(&*value, "token");
+        let proto: String = value.to_proto_alias();
+        assert_eq!(TrimmedString::from_proto_alias(proto).unwrap(), value);
+
+        let email = Normalized::<Lowercase>::from_proto_alias(" Ann@Example.COM".to_string());
+        assert_eq!(email.unwrap().as_str(), "ann@example.com");
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn test_nfc() {
+        // `e` and the combining acute accent
+        let decomposed = "Rene\u{301}e".to_string();
+        let name = NfcString::from_proto_alias(decomposed).unwrap();
+        assert_eq!(name.as_str(), "Ren\u{e9}e");
+        assert_eq!(
+            name,
+            NfcString::from_proto_alias("Ren\u{e9}e".to_string()).unwrap()
+        );
+
+        let names = Vec::<NfcString>::from_proto_alias(vec!["a\u{30a}".to_string()]).unwrap();
+        assert_eq!(names[0].as_str(), "\u{e5}");
+    }
+
+    #[test]
+    fn test_strict_utf8() {
+        let text = StrictUtf8Bytes::from_proto_alias("Zürich".as_bytes().to_vec()).unwrap();
+        assert_eq!(text.as_str(), "Zürich");
+        let proto: Vec<u8> = text.to_proto_alias();
+        assert_eq!(proto, "Zürich".as_bytes());
+
+        // the lone continuation byte after `ab`
+        let error = StrictUtf8Bytes::from_proto_alias(vec![b'a', b'b', 0x80, b'c']).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The bytes are not valid UTF-8 at the offset 2"
+        );
+        // the encoded surrogate of the JS producers
+        let error = Option::<StrictUtf8Bytes>::from_proto_alias(Some(vec![0xed, 0xa0, 0x80]));
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "The bytes are not valid UTF-8 at the offset 0"
+        );
+    }
+
+    #[test]
+    fn test_bounded() {
+        let name =
+            BoundedString::<5>::from_proto_alias("Zürich".chars().take(5).collect()).unwrap();
+        assert_eq!(name.as_str(), "Züric");
+        // the characters, not the bytes
+        assert!(BoundedString::<6>::from_proto_alias("Zürich".to_string()).is_ok());
+
+        let error = BoundedString::<5>::from_proto_alias("Zürich".to_string()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The string has 6 characters, over the maximum 5"
+        );
+        let error = Vec::<BoundedString<2>>::from_proto_alias(vec!["ab".into(), "abc".into()]);
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "[1]: The string has 3 characters, over the maximum 2"
+        );
+    }
+
+    #[test]
+    fn test_option() {
+        let none = Option::<NonEmptyString>::from_proto_alias(None::<String>).unwrap();
+        assert_eq!(none, None);
+        let some = Option::<NonEmptyString>::from_proto_alias(Some("id".to_string())).unwrap();
+        assert_eq!(some.unwrap().as_str(), "id");
+        assert!(Option::<NonEmptyString>::from_proto_alias(Some(String::new())).is_err());
+
+        let error = Vec::<NonEmptyString>::from_proto_alias(vec!["a".into(), " ".into()]);
+        assert_eq!(error.unwrap_err().to_string(), "[1]: The value is empty");
+    }
+}
@@ -0,0 +1,983 @@
+//! The protobuf text format, like the `.textproto` fixtures of the tests, read in to the proto
+//! messages and converted in to their domain types with `from_text_proto` of the prelude, enabled with
+//! the `text-format` feature.
+//! ```ignore
+//! // prost_build::Config::new().type_attribute(".", "#[derive(::proto_conve::TextProto)]")
+//! let user = from_text_proto::<proto::User, _>(include_str!("fixtures/user.textproto"))?;
+//! ```
+//! The text is read without the descriptors of the messages, through their `TextProto`, which the
+//! proto messages and the oneof enums derive, the prost ones with the `type_attribute` of the
+//! derive. The subset of the format of the fixtures is read:
+//! - the integers in decimal, hex or octal, the floats with `inf` and `nan`, the `bool` values, and
+//!   the enum values by their names or numbers
+//! - the strings and the bytes in the single or double quotes with their escapes, the adjacent ones
+//!   concatenated
+//! - the nested messages in `{ }` or `< >`, with or without the colon before them
+//! - the repeated fields written once per element or as the `[ ]` list, and the map entries of their
+//!   `key` and `value`
+//! - the oneof fields by the names of their variants, and the `#` comments
+//!
+//! The extensions, the expanded `Any` messages and the groups are not supported. The fields are
+//! merged as in the binary format, the last scalar wins, the messages are merged and the repeated
+//! fields are appended. The names are the ones of the prost fields, which are the proto names of the
+//! snake case fields.
+//!
+//! The syntax errors are `TextFormatError` with the line and the column from one, and so are the
+//! values of the wrong types and the unknown fields, with the path of their field. `TextFormatError::of`
+//! finds it in the errors, their code is `InvalidFormat`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hash;
+
+use crate::error::{at_index, at_key, in_field, ConversionError};
+
+/// The position in the text, the line and the column are from one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TextPosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for TextPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// The malformed text, or the value which does not fit its field, at its position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextFormatError {
+    position: TextPosition,
+    message: String,
+}
+
+impl TextFormatError {
+    pub fn new(position: TextPosition, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+
+    pub fn position(&self) -> TextPosition {
+        self.position
+    }
+
+    /// The message without the position
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The text format error of the `error`, also in the `ConversionError` with its path
+    pub fn of(error: &anyhow::Error) -> Option<&TextFormatError> {
+        let reason = match error.downcast_ref::<ConversionError>() {
+            Some(conversion) => conversion.reason(),
+            None => error,
+        };
+        reason.chain().find_map(|error| error.downcast_ref())
+    }
+}
+
+impl fmt::Display for TextFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for TextFormatError {}
+
+/// The parsed value of a field at its position
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextValue {
+    pub position: TextPosition,
+    pub kind: TextKind,
+}
+
+/// What the value is, as it is written
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextKind {
+    /// The identifier, like `true`, `inf` or the name of an enum value, with its sign
+    Identifier(String),
+    /// The number as it is written, with its sign
+    Number(String),
+    /// The bytes of the quoted strings after their escapes
+    Quoted(Vec<u8>),
+    /// The fields of the message in their order
+    Message(Vec<TextField>),
+    /// The elements of the `[ ]` list
+    List(Vec<TextValue>),
+}
+
+/// The field as it is written in the message
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextField {
+    pub name: String,
+    /// The position of the name
+    pub position: TextPosition,
+    pub value: TextValue,
+}
+
+impl TextValue {
+    /// The error of the value, which is not the `expected` one, like `"an integer"`
+    pub fn unexpected(&self, expected: &str) -> anyhow::Error {
+        let found = match &self.kind {
+            TextKind::Identifier(text) | TextKind::Number(text) => format!("`{text}`"),
+            TextKind::Quoted(_) => "a string".to_owned(),
+            TextKind::Message(_) => "a message".to_owned(),
+            TextKind::List(_) => "a list".to_owned(),
+        };
+        self.error(format!("Expected {expected}, found {found}"))
+    }
+
+    /// The error at the position of the value
+    pub fn error(&self, message: impl Into<String>) -> anyhow::Error {
+        TextFormatError::new(self.position, message).into()
+    }
+}
+
+/// The proto value merged from the text format, derived by the proto messages with
+/// `#[derive(TextProto)]`
+pub trait TextProto {
+    /// Merge the `value` in to `self`, the messages field by field and the repeated fields appending
+    fn merge_text(&mut self, value: TextValue) -> anyhow::Result<()>;
+}
+
+/// The oneof enum, its variants are set by the names of their fields, derived with
+/// `#[derive(TextProto)]`
+pub trait TextOneof: Sized {
+    /// The variant of the field `name` exists
+    fn has_text_field(name: &str) -> bool;
+
+    /// Merge the `value` of the field `name` in to the `oneof`, the other variant is replaced
+    fn merge_text_oneof(
+        oneof: &mut Option<Self>,
+        name: &str,
+        value: TextValue,
+    ) -> anyhow::Result<()>;
+}
+
+/// The proto message `P` of the `text`
+pub fn parse<P: TextProto + Default>(text: &str) -> anyhow::Result<P> {
+    let value = Parser::new(text)?.document()?;
+    let mut proto = P::default();
+    proto.merge_text(value)?;
+    Ok(proto)
+}
+
+/// Merge the fields of the message `value` with `merge`, which is `None` for the unknown fields of
+/// the `message`, like `` `User` ``
+#[doc(hidden)]
+pub fn merge_fields(
+    value: TextValue,
+    message: &str,
+    mut merge: impl FnMut(&str, TextValue) -> Option<anyhow::Result<()>>,
+) -> anyhow::Result<()> {
+    let fields = match value.kind {
+        TextKind::Message(fields) => fields,
+        _ => return Err(value.unexpected("a message")),
+    };
+    for TextField {
+        name,
+        position,
+        value,
+    } in fields
+    {
+        match merge(&name, value) {
+            Some(result) => result?,
+            None => {
+                let message = format!("Unknown field `{name}` of {message}");
+                return Err(TextFormatError::new(position, message).into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The oneof of the field has the variant of the field `name`
+#[doc(hidden)]
+pub fn has_oneof_field<O: TextOneof>(_: &Option<O>, name: &str) -> bool {
+    O::has_text_field(name)
+}
+
+/// Append the elements of the list, or the one value
+fn repeated<T>(
+    values: &mut Vec<T>,
+    value: TextValue,
+    mut element: impl FnMut(TextValue) -> anyhow::Result<T>,
+) -> anyhow::Result<()> {
+    let elements = match value.kind {
+        TextKind::List(elements) => elements,
+        _ => vec![value],
+    };
+    for value in elements {
+        let index = values.len();
+        values.push(element(value).map_err(|error| at_index(error, index))?);
+    }
+    Ok(())
+}
+
+fn integer<T: TryFrom<i128>>(value: &TextValue, type_name: &str) -> anyhow::Result<T> {
+    let TextKind::Number(number) = &value.kind else {
+        return Err(value.unexpected("an integer"));
+    };
+    let (negative, digits) = match number.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, number.as_str()),
+    };
+    let parsed = match digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+        Some(hex) => i128::from_str_radix(hex, 16),
+        None if digits.len() > 1 && digits.starts_with('0') => i128::from_str_radix(digits, 8),
+        None => digits.parse(),
+    };
+    let integer = match parsed {
+        Ok(integer) if negative => -integer,
+        Ok(integer) => integer,
+        Err(_) => return Err(value.unexpected("an integer")),
+    };
+    T::try_from(integer).map_err(|_| {
+        value.error(format!(
+            "The integer {integer} is out of the range of `{type_name}`"
+        ))
+    })
+}
+
+macro_rules! text_integers {
+    ($($type: ty),*) => {
+        $(
+            impl TextProto for $type {
+                fn merge_text(&mut self, value: TextValue) -> anyhow::Result<()> {
+                    *self = integer(&value, stringify!($type))?;
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+text_integers!(i32, i64, u32, u64);
+
+fn float(value: &TextValue) -> anyhow::Result<f64> {
+    let (TextKind::Identifier(text) | TextKind::Number(text)) = &value.kind else {
+        return Err(value.unexpected("a float"));
+    };
+    let (negative, magnitude) = match text.strip_prefix('-') {
+        Some(magnitude) => (true, magnitude.to_ascii_lowercase()),
+        None => (false, text.to_ascii_lowercase()),
+    };
+    let magnitude = match magnitude.as_str() {
+        "inf" | "infinity" => f64::INFINITY,
+        "nan" => f64::NAN,
+        number if matches!(value.kind, TextKind::Number(_)) => {
+            match number.strip_suffix('f').unwrap_or(number).parse() {
+                Ok(float) => float,
+                // the hex and octal integers
+                Err(_) => integer::<i128>(value, "f64")?.unsigned_abs() as f64,
+            }
+        }
+        _ => return Err(value.unexpected("a float")),
+    };
+    Ok(if negative { -magnitude } else { magnitude })
+}
+
+impl TextProto for f64 {
+    fn merge_text(&mut self, value: TextValue) -> anyhow::Result<()> {
+        *self = float(&value)?;
+        Ok(())
+    }
+}
+
+impl TextProto for f32 {
+    fn merge_text(&mut self, value: TextValue) -> anyhow::Result<()> {
+        *self = float(&value)? as f32;
+        Ok(())
+    }
+}
+
+impl TextProto for bool {
+    fn merge_text(&mut self, value: TextValue) -> anyhow::Result<()> {
+        *self = match &value.kind {
+            TextKind::Identifier(name) if matches!(name.as_str(), "true" | "True" | "t") => true,
+            TextKind::Identifier(name) if matches!(name.as_str(), "false" | "False" | "f") => false,
+            TextKind::Number(number) if number == "1" => true,
+            TextKind::Number(number) if number == "0" => false,
+            _ => return Err(value.unexpected("a bool")),
+        };
+        Ok(())
+    }
+}
+
+fn bytes(value: TextValue) -> anyhow::Result<Vec<u8>> {
+    match value.kind {
+        TextKind::Quoted(bytes) => Ok(bytes),
+        _ => Err(value.unexpected("a string")),
+    }
+}
+
+impl TextProto for String {
+    fn merge_text(&mut self, value: TextValue) -> anyhow::Result<()> {
+        let position = value.position;
+        *self = String::from_utf8(bytes(value)?)
+            .map_err(|_| TextFormatError::new(position, "The string is not valid UTF-8"))?;
+        Ok(())
+    }
+}
+
+impl<T: TextProto + Default> TextProto for Option<T> {
+    fn merge_text(&mut self, value: TextValue) -> anyhow::Result<()> {
+        self.get_or_insert_with(T::default).merge_text(value)
+    }
+}
+
+impl<T: TextProto + ?Sized> TextProto for Box<T> {
+    fn merge_text(&mut self, value: TextValue) -> anyhow::Result<()> {
+        (**self).merge_text(value)
+    }
+}
+
+impl<T: TextProto + Default> TextProto for Vec<T> {
+    fn merge_text(&mut self, value: TextValue) -> anyhow::Result<()> {
+        repeated(self, value, |value| {
+            let mut element = T::default();
+            element.merge_text(value)?;
+            Ok(element)
+        })
+    }
+}
+
+/// The map entries of the `value`, the errors of the values have their keys
+fn entries<K, V>(value: TextValue, mut insert: impl FnMut(K, V)) -> anyhow::Result<()>
+where
+    K: TextProto + Default + fmt::Display,
+    V: TextProto + Default,
+{
+    let entries = match value.kind {
+        TextKind::List(entries) => entries,
+        _ => vec![value],
+    };
+    for entry in entries {
+        let mut key = K::default();
+        let mut text = None;
+        merge_fields(entry, "the map entry", |name, value| match name {
+            "key" => Some(
+                key.merge_text(value)
+                    .map_err(|error| in_field(error, "key")),
+            ),
+            "value" => {
+                text = Some(value);
+                Some(Ok(()))
+            }
+            _ => None,
+        })?;
+        let mut value = V::default();
+        if let Some(text) = text {
+            value
+                .merge_text(text)
+                .map_err(|error| at_key(error, &key))?;
+        }
+        insert(key, value);
+    }
+    Ok(())
+}
+
+impl<K, V, S> TextProto for HashMap<K, V, S>
+where
+    K: TextProto + Default + fmt::Display + Eq + Hash,
+    V: TextProto + Default,
+    S: std::hash::BuildHasher,
+{
+    fn merge_text(&mut self, value: TextValue) -> anyhow::Result<()> {
+        entries(value, |key, value| {
+            self.insert(key, value);
+        })
+    }
+}
+
+impl<K, V> TextProto for BTreeMap<K, V>
+where
+    K: TextProto + Default + fmt::Display + Ord,
+    V: TextProto + Default,
+{
+    fn merge_text(&mut self, value: TextValue) -> anyhow::Result<()> {
+        entries(value, |key, value| {
+            self.insert(key, value);
+        })
+    }
+}
+
+#[cfg(feature = "prost-types")]
+impl TextProto for prost_types::Timestamp {
+    fn merge_text(&mut self, value: TextValue) -> anyhow::Result<()> {
+        merge_fields(
+            value,
+            "`google.protobuf.Timestamp`",
+            |name, value| match name {
+                "seconds" => Some(
+                    self.seconds
+                        .merge_text(value)
+                        .map_err(|error| in_field(error, "seconds")),
+                ),
+                "nanos" => Some(
+                    self.nanos
+                        .merge_text(value)
+                        .map_err(|error| in_field(error, "nanos")),
+                ),
+                _ => None,
+            },
+        )
+    }
+}
+
+#[cfg(feature = "prost-types")]
+impl TextProto for prost_types::Duration {
+    fn merge_text(&mut self, value: TextValue) -> anyhow::Result<()> {
+        merge_fields(
+            value,
+            "`google.protobuf.Duration`",
+            |name, value| match name {
+                "seconds" => Some(
+                    self.seconds
+                        .merge_text(value)
+                        .map_err(|error| in_field(error, "seconds")),
+                ),
+                "nanos" => Some(
+                    self.nanos
+                        .merge_text(value)
+                        .map_err(|error| in_field(error, "nanos")),
+                ),
+                _ => None,
+            },
+        )
+    }
+}
+
+/// The `bytes` fields, `#[prost(bytes)]` of the derive
+#[doc(hidden)]
+pub trait TextBytes {
+    fn merge_text_bytes(&mut self, value: TextValue) -> anyhow::Result<()>;
+}
+
+impl TextBytes for Vec<u8> {
+    fn merge_text_bytes(&mut self, value: TextValue) -> anyhow::Result<()> {
+        *self = bytes(value)?;
+        Ok(())
+    }
+}
+
+impl TextBytes for Vec<Vec<u8>> {
+    fn merge_text_bytes(&mut self, value: TextValue) -> anyhow::Result<()> {
+        repeated(self, value, bytes)
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl TextBytes for bytes::Bytes {
+    fn merge_text_bytes(&mut self, value: TextValue) -> anyhow::Result<()> {
+        *self = bytes(value)?.into();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl TextBytes for Vec<bytes::Bytes> {
+    fn merge_text_bytes(&mut self, value: TextValue) -> anyhow::Result<()> {
+        repeated(self, value, |value| Ok(bytes(value)?.into()))
+    }
+}
+
+impl<B: TextBytes + Default> TextBytes for Option<B> {
+    fn merge_text_bytes(&mut self, value: TextValue) -> anyhow::Result<()> {
+        self.get_or_insert_with(B::default).merge_text_bytes(value)
+    }
+}
+
+/// The raw `i32` fields of the prost enums, `#[prost(enumeration = "...")]` of the derive, with the
+/// values of the names from `from_name`
+#[doc(hidden)]
+pub trait TextEnum {
+    fn merge_text_enum(
+        &mut self,
+        value: TextValue,
+        from_name: fn(&str) -> Option<i32>,
+    ) -> anyhow::Result<()>;
+}
+
+fn enum_value(value: &TextValue, from_name: fn(&str) -> Option<i32>) -> anyhow::Result<i32> {
+    match &value.kind {
+        TextKind::Identifier(name) => {
+            from_name(name).ok_or_else(|| value.error(format!("Unknown enum value `{name}`")))
+        }
+        _ => integer(value, "i32"),
+    }
+}
+
+impl TextEnum for i32 {
+    fn merge_text_enum(
+        &mut self,
+        value: TextValue,
+        from_name: fn(&str) -> Option<i32>,
+    ) -> anyhow::Result<()> {
+        *self = enum_value(&value, from_name)?;
+        Ok(())
+    }
+}
+
+impl TextEnum for Option<i32> {
+    fn merge_text_enum(
+        &mut self,
+        value: TextValue,
+        from_name: fn(&str) -> Option<i32>,
+    ) -> anyhow::Result<()> {
+        *self = Some(enum_value(&value, from_name)?);
+        Ok(())
+    }
+}
+
+impl TextEnum for Vec<i32> {
+    fn merge_text_enum(
+        &mut self,
+        value: TextValue,
+        from_name: fn(&str) -> Option<i32>,
+    ) -> anyhow::Result<()> {
+        repeated(self, value, |value| enum_value(&value, from_name))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    Number(String),
+    Quoted(Vec<u8>),
+    Punct(char),
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Identifier(text) | Token::Number(text) => write!(f, "`{text}`"),
+            Token::Quoted(_) => f.write_str("a string"),
+            Token::Punct(punct) => write!(f, "`{punct}`"),
+            Token::End => f.write_str("the end"),
+        }
+    }
+}
+
+/// The characters of the text with the position of the next one
+struct Cursor<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    position: TextPosition,
+}
+
+impl Cursor<'_> {
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let next = self.chars.next()?;
+        if next == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+        Some(next)
+    }
+
+    fn bump_while(&mut self, text: &mut String, mut matches: impl FnMut(char) -> bool) {
+        while let Some(next) = self.peek().filter(|&next| matches(next)) {
+            text.push(next);
+            self.bump();
+        }
+    }
+
+    fn tokens(mut self) -> Result<Vec<(Token, TextPosition)>, TextFormatError> {
+        let mut tokens = Vec::new();
+        loop {
+            let position = self.position;
+            let Some(next) = self.peek() else {
+                tokens.push((Token::End, position));
+                return Ok(tokens);
+            };
+            let token = match next {
+                next if next.is_whitespace() => {
+                    self.bump();
+                    continue;
+                }
+                '#' => {
+                    while self.bump().is_some_and(|next| next != '\n') {}
+                    continue;
+                }
+                next if next.is_alphabetic() || next == '_' => {
+                    let mut name = String::new();
+                    self.bump_while(&mut name, |next| next.is_alphanumeric() || next == '_');
+                    Token::Identifier(name)
+                }
+                next if next.is_ascii_digit() || next == '.' => {
+                    let mut number = String::new();
+                    let mut previous = next;
+                    // the signs of the exponents, like `1e-3`
+                    self.bump_while(&mut number, |next| {
+                        let matches = next.is_alphanumeric()
+                            || next == '.'
+                            || (matches!(next, '-' | '+') && matches!(previous, 'e' | 'E'));
+                        previous = next;
+                        matches
+                    });
+                    Token::Number(number)
+                }
+                '"' | '\'' => Token::Quoted(self.string(position)?),
+                '{' | '}' | '<' | '>' | '[' | ']' | ':' | ',' | ';' | '-' => {
+                    self.bump();
+                    Token::Punct(next)
+                }
+                next => {
+                    return Err(TextFormatError::new(
+                        position,
+                        format!("Unexpected character `{next}`"),
+                    ))
+                }
+            };
+            tokens.push((token, position));
+        }
+    }
+
+    /// The bytes of the quoted string starting at the `start`
+    fn string(&mut self, start: TextPosition) -> Result<Vec<u8>, TextFormatError> {
+        let quote = self.bump();
+        let mut bytes = Vec::new();
+        loop {
+            let position = self.position;
+            match self.bump() {
+                None | Some('\n') => {
+                    return Err(TextFormatError::new(start, "Unterminated string"))
+                }
+                next if next == quote => return Ok(bytes),
+                Some('\\') => self.escape(position, &mut bytes)?,
+                Some(next) => {
+                    let mut buffer = [0; 4];
+                    bytes.extend_from_slice(next.encode_utf8(&mut buffer).as_bytes());
+                }
+            }
+        }
+    }
+
+    /// The escape after the backslash at the `position`
+    fn escape(
+        &mut self,
+        position: TextPosition,
+        bytes: &mut Vec<u8>,
+    ) -> Result<(), TextFormatError> {
+        let invalid =
+            |escape: &str| TextFormatError::new(position, format!("Invalid escape `\\{escape}`"));
+        let next = self.bump().ok_or_else(|| invalid(""))?;
+        let byte = match next {
+            'n' => b'\n',
+            'r' => b'\r',
+            't' => b'\t',
+            'a' => 0x07,
+            'b' => 0x08,
+            'f' => 0x0c,
+            'v' => 0x0b,
+            '\\' | '\'' | '"' | '?' => next as u8,
+            '0'..='7' => {
+                let mut digits = next.to_string();
+                self.bump_limited(&mut digits, 2, |next| next.is_digit(8));
+                u8::from_str_radix(&digits, 8).map_err(|_| invalid(&digits))?
+            }
+            'x' | 'X' => {
+                let mut digits = String::new();
+                self.bump_limited(&mut digits, 2, |next| next.is_ascii_hexdigit());
+                u8::from_str_radix(&digits, 16).map_err(|_| invalid(&format!("{next}{digits}")))?
+            }
+            'u' | 'U' => {
+                let mut digits = String::new();
+                let count = if next == 'u' { 4 } else { 8 };
+                self.bump_limited(&mut digits, count, |next| next.is_ascii_hexdigit());
+                let escaped = u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .filter(|_| digits.len() == count)
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| invalid(&format!("{next}{digits}")))?;
+                let mut buffer = [0; 4];
+                bytes.extend_from_slice(escaped.encode_utf8(&mut buffer).as_bytes());
+                return Ok(());
+            }
+            next => return Err(invalid(&next.to_string())),
+        };
+        bytes.push(byte);
+        Ok(())
+    }
+
+    fn bump_limited(&mut self, text: &mut String, limit: usize, matches: impl Fn(char) -> bool) {
+        let mut count = 0;
+        self.bump_while(text, |next| {
+            count += 1;
+            count <= limit && matches(next)
+        });
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, TextPosition)>,
+    index: usize,
+}
+
+impl Parser {
+    fn new(text: &str) -> Result<Self, TextFormatError> {
+        let cursor = Cursor {
+            chars: text.chars().peekable(),
+            position: TextPosition { line: 1, column: 1 },
+        };
+        Ok(Self {
+            tokens: cursor.tokens()?,
+            index: 0,
+        })
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.index].0
+    }
+
+    fn next(&mut self) -> (Token, TextPosition) {
+        let (token, position) = self.tokens[self.index].clone();
+        // the end stays the next one
+        if token != Token::End {
+            self.index += 1;
+        }
+        (token, position)
+    }
+
+    fn unexpected(expected: &str, (token, position): (Token, TextPosition)) -> TextFormatError {
+        TextFormatError::new(position, format!("Expected {expected}, found {token}"))
+    }
+
+    /// The whole text is the fields of the message
+    fn document(&mut self) -> Result<TextValue, TextFormatError> {
+        let position = TextPosition { line: 1, column: 1 };
+        let fields = self.fields(None)?;
+        Ok(TextValue {
+            position,
+            kind: TextKind::Message(fields),
+        })
+    }
+
+    /// The fields up to and with the `close` of the message, or up to the end
+    fn fields(&mut self, close: Option<char>) -> Result<Vec<TextField>, TextFormatError> {
+        let mut fields = Vec::new();
+        loop {
+            match (self.peek(), close) {
+                (Token::Punct(punct), Some(close)) if *punct == close => {
+                    self.next();
+                    return Ok(fields);
+                }
+                (Token::End, None) => return Ok(fields),
+                (Token::End, Some(close)) => {
+                    return Err(Self::unexpected(&format!("`{close}`"), self.next()))
+                }
+                _ => fields.push(self.field()?),
+            }
+        }
+    }
+
+    fn field(&mut self) -> Result<TextField, TextFormatError> {
+        let (name, position) = match self.next() {
+            (Token::Identifier(name), position) => (name, position),
+            (Token::Punct('['), position) => {
+                return Err(TextFormatError::new(
+                    position,
+                    "The extensions and the expanded `Any` messages are not supported",
+                ))
+            }
+            next => return Err(Self::unexpected("a field name", next)),
+        };
+        let value = match self.peek() {
+            Token::Punct(':') => {
+                self.next();
+                self.value(true)?
+            }
+            // the messages without the colon
+            Token::Punct('{' | '<') => self.value(false)?,
+            _ => return Err(Self::unexpected("`:` or a message", self.next())),
+        };
+        if matches!(self.peek(), Token::Punct(',' | ';')) {
+            self.next();
+        }
+        Ok(TextField {
+            name,
+            position,
+            value,
+        })
+    }
+
+    fn value(&mut self, list: bool) -> Result<TextValue, TextFormatError> {
+        let (token, position) = self.next();
+        let kind = match token {
+            Token::Punct('{') => TextKind::Message(self.fields(Some('}'))?),
+            Token::Punct('<') => TextKind::Message(self.fields(Some('>'))?),
+            Token::Punct('[') if list => {
+                let mut elements = Vec::new();
+                if *self.peek() == Token::Punct(']') {
+                    self.next();
+                } else {
+                    loop {
+                        elements.push(self.value(false)?);
+                        match self.next() {
+                            (Token::Punct(','), _) => {}
+                            (Token::Punct(']'), _) => break,
+                            next => return Err(Self::unexpected("`,` or `]`", next)),
+                        }
+                    }
+                }
+                TextKind::List(elements)
+            }
+            Token::Punct('-') => match self.next() {
+                (Token::Number(number), _) => TextKind::Number(format!("-{number}")),
+                (Token::Identifier(name), _) => TextKind::Identifier(format!("-{name}")),
+                next => return Err(Self::unexpected("a number", next)),
+            },
+            Token::Identifier(name) => TextKind::Identifier(name),
+            Token::Number(number) => TextKind::Number(number),
+            Token::Quoted(mut bytes) => {
+                while let Token::Quoted(more) = self.peek() {
+                    bytes.extend_from_slice(more);
+                    self.next();
+                }
+                TextKind::Quoted(bytes)
+            }
+            token => return Err(Self::unexpected("a value", (token, position))),
+        };
+        Ok(TextValue { position, kind })
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_text_format {
+    (prelude) => {
+        pub use super::from_text_proto;
+    };
+    () => {
+        /// Parse the protobuf text format in to `P` and convert it in to its domain type, see
+        /// `proto_conve::text_format`
+        #[allow(dead_code)]
+        pub fn from_text_proto<P, T>(text: &str) -> anyhow::Result<T>
+        where
+            P: FromProto<Result = T> + $crate::text_format::TextProto + Default,
+        {
+            $crate::text_format::parse::<P>(text)?.from_proto()
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{merge_fields, parse, TextFormatError, TextPosition, TextProto, TextValue};
+    use crate::error::in_field;
+    use crate::violation::ErrorCode;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    struct ProtoSample {
+        count: i64,
+        ratio: f64,
+        flags: Vec<bool>,
+        name: String,
+        labels: BTreeMap<String, u32>,
+        child: Option<Box<ProtoSample>>,
+    }
+
+    impl TextProto for ProtoSample {
+        fn merge_text(&mut self, value: TextValue) -> anyhow::Result<()> {
+            merge_fields(value, "`Sample`", |name, value| {
+                let (name, result) = match name {
+                    "count" => ("count", self.count.merge_text(value)),
+                    "ratio" => ("ratio", self.ratio.merge_text(value)),
+                    "flags" => ("flags", self.flags.merge_text(value)),
+                    "name" => ("name", self.name.merge_text(value)),
+                    "labels" => ("labels", self.labels.merge_text(value)),
+                    "child" => ("child", self.child.merge_text(value)),
+                    _ => return None,
+                };
+                Some(result.map_err(|error| in_field(error, name)))
+            })
+        }
+    }
+
+    #[test]
+    fn test_scalars() {
+        let text = r#"
+            # the comments are skipped
+            count: -0x10
+            ratio: 1.5e-1
+            flags: [true, f, 1]
+            flags: False
+            name: "caf\xc3\xa9 " 'au \"lait\"\n'
+            labels { key: "b" value: 2 }
+            labels: [{ key: "a", value: 010 }]
+            child < count: 3; child { ratio: -inf } >
+            child { name: "merged" }
+        "#;
+        let sample: ProtoSample = parse(text).unwrap();
+        assert_eq!(sample.count, -16);
+        assert_eq!(sample.ratio, 0.15);
+        assert_eq!(sample.flags, [true, false, true, false]);
+        assert_eq!(sample.name, "café au \"lait\"\n");
+        assert_eq!(
+            sample.labels,
+            BTreeMap::from([("a".into(), 8), ("b".into(), 2)])
+        );
+        let child = sample.child.unwrap();
+        assert_eq!((child.count, child.name.as_str()), (3, "merged"));
+        assert_eq!(child.child.unwrap().ratio, f64::NEG_INFINITY);
+        assert_eq!(parse::<ProtoSample>("").unwrap(), ProtoSample::default());
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = parse::<ProtoSample>("count: 1\nchild {\n  name: \"open\n}").unwrap_err();
+        assert_eq!(error.to_string(), "Unterminated string at line 3, column 9");
+
+        let error = parse::<ProtoSample>("child {\n  count: 1\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Expected `}`, found the end at line 3, column 1"
+        );
+
+        let error = parse::<ProtoSample>("count 1").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Expected `:` or a message, found `1` at line 1, column 7"
+        );
+
+        let error = parse::<ProtoSample>("child { flags: [true, \"yes\"] }").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "child.flags[1]: Expected a bool, found a string at line 1, column 23"
+        );
+        assert_eq!(
+            TextFormatError::of(&error).unwrap().position(),
+            TextPosition {
+                line: 1,
+                column: 23
+            }
+        );
+
+        let error = parse::<ProtoSample>("labels { key: \"a\" value: -1 }").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "labels[\"a\"]: The integer -1 is out of the range of `u32` at line 1, column 26"
+        );
+
+        let error = parse::<ProtoSample>("\n  size: 3").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown field `size` of `Sample` at line 2, column 3"
+        );
+        let error = parse::<ProtoSample>("[ext.field]: 1").unwrap_err();
+        assert_eq!(ErrorCode::of(&error), ErrorCode::InvalidFormat,);
+    }
+}
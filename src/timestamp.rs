@@ -0,0 +1,107 @@
+//! The range of the proto `Timestamp`, `0001-01-01T00:00:00Z` to `9999-12-31T23:59:59.999999999Z`,
+//! checked with `impl_traits!(Timestamp, chrono::DateTime<chrono::Utc>, spec_range)`. The other
+//! languages reject the instants out of it, even though chrono can represent them.
+
+/// The seconds of `0001-01-01T00:00:00Z`
+pub const MIN_SECONDS: i64 = -62_135_596_800;
+
+/// The seconds of `9999-12-31T23:59:59Z`
+pub const MAX_SECONDS: i64 = 253_402_300_799;
+
+/// The error of the rendered instant out of the range, used by the prelude
+#[doc(hidden)]
+pub fn out_of_range(rendered: String) -> anyhow::Error {
+    anyhow::anyhow!(
+        "The timestamp {rendered} is out of the range 0001-01-01T00:00:00Z to \
+         9999-12-31T23:59:59.999999999Z"
+    )
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::impl_traits;
+    use crate::mode::ConversionMode;
+
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct Timestamp {
+        seconds: i64,
+        nanos: i32,
+    }
+
+    impl_traits!(prelude);
+    impl_traits!(Timestamp, chrono::DateTime<chrono::Utc>, spec_range);
+
+    fn instant(rfc3339: &str) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::parse_from_rfc3339(rfc3339)
+            .unwrap()
+            .to_utc()
+    }
+
+    #[test]
+    fn test_first() {
+        let first = instant("0001-01-01T00:00:00Z");
+        let proto = first.try_to_proto().unwrap();
+        assert_eq!(
+            proto,
+            Timestamp {
+                seconds: super::MIN_SECONDS,
+                nanos: 0
+            }
+        );
+        assert_eq!(proto.from_proto().unwrap(), first);
+
+        let before = first - chrono::Duration::nanoseconds(1);
+        assert_eq!(
+            before.try_to_proto().unwrap_err().to_string(),
+            "The timestamp 0000-12-31T23:59:59.999999999Z is out of the range \
+             0001-01-01T00:00:00Z to 9999-12-31T23:59:59.999999999Z"
+        );
+        let proto = Timestamp {
+            seconds: super::MIN_SECONDS - 1,
+            nanos: 999_999_999,
+        };
+        assert_eq!(
+            proto.clone().from_proto().unwrap_err().to_string(),
+            "The timestamp 0000-12-31T23:59:59.999999999Z is out of the range \
+             0001-01-01T00:00:00Z to 9999-12-31T23:59:59.999999999Z"
+        );
+        assert!(proto
+            .clone()
+            .from_proto_mode(ConversionMode::Strict)
+            .is_err());
+        assert_eq!(
+            proto.from_proto_mode(ConversionMode::Lenient).unwrap(),
+            instant("0001-01-01T00:00:00.999999999Z")
+        );
+    }
+
+    #[test]
+    fn test_last() {
+        let last = instant("9999-12-31T23:59:59.999999999Z");
+        let proto = last.try_to_proto().unwrap();
+        assert_eq!(
+            proto,
+            Timestamp {
+                seconds: super::MAX_SECONDS,
+                nanos: 999_999_999
+            }
+        );
+        assert_eq!(proto.from_proto().unwrap(), last);
+
+        let after = last + chrono::Duration::nanoseconds(1);
+        assert_eq!(
+            after.try_to_proto().unwrap_err().to_string(),
+            "The timestamp +10000-01-01T00:00:00Z is out of the range 0001-01-01T00:00:00Z \
+             to 9999-12-31T23:59:59.999999999Z"
+        );
+        let proto = Timestamp {
+            seconds: super::MAX_SECONDS + 1,
+            nanos: 0,
+        };
+        assert!(proto.clone().from_proto().is_err());
+        let (value, report) = proto.from_proto_lossy().unwrap();
+        assert_eq!(value, instant("9999-12-31T23:59:59Z"));
+        assert_eq!(report.len(), 1);
+    }
+}
@@ -0,0 +1,181 @@
+//! Tracing of the failed conversions with the `tracing` feature. The failures are `warn` events with
+//! the `proto_type`, `target_type`, `field_path` and `reason` fields, the `Vec` conversions are in
+//! `debug` spans with the `count` of the elements and the `elapsed_us` they took. Without the feature,
+//! the hooks are empty.
+
+#[cfg(feature = "tracing")]
+use crate::error::ConversionError;
+
+/// Trace the failed conversion of the proto type `P` in to `T`, and return the error
+#[doc(hidden)]
+#[inline]
+pub fn failed<P: ?Sized, T: ?Sized>(error: anyhow::Error) -> anyhow::Error {
+    #[cfg(feature = "tracing")]
+    {
+        let (field_path, reason) = match error.downcast_ref::<ConversionError>() {
+            Some(conversion) => (conversion.path_string(), conversion.reason().to_string()),
+            None => (String::new(), error.to_string()),
+        };
+        tracing::warn!(
+            proto_type = std::any::type_name::<P>(),
+            target_type = std::any::type_name::<T>(),
+            field_path,
+            reason,
+            "Proto conversion failed"
+        );
+    }
+    error
+}
+
+/// The span of a batch conversion, recording how long it took when it is dropped
+#[doc(hidden)]
+pub struct Batch {
+    #[cfg(feature = "tracing")]
+    span: tracing::span::EnteredSpan,
+    #[cfg(feature = "tracing")]
+    start: crate::clock::Started,
+}
+
+impl Batch {
+    /// Enter the span of the conversion of `count` elements in to `T`
+    #[inline]
+    pub fn new<T: ?Sized>(count: usize) -> Self {
+        let _ = count;
+        Self {
+            #[cfg(feature = "tracing")]
+            span: tracing::debug_span!(
+                "proto_batch",
+                target_type = std::any::type_name::<T>(),
+                count,
+                elapsed_us = tracing::field::Empty,
+            )
+            .entered(),
+            #[cfg(feature = "tracing")]
+            start: crate::clock::Started::now(),
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for Batch {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed().as_micros() as u64;
+        self.span.record("elapsed_us", elapsed);
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+#[allow(dead_code)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::Layer;
+
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    type Fields = BTreeMap<String, String>;
+
+    /// Collects the fields of the events and the new spans
+    #[derive(Clone, Default)]
+    struct Collect {
+        events: Arc<Mutex<Vec<Fields>>>,
+        spans: Arc<Mutex<Vec<Fields>>>,
+    }
+
+    struct Visitor<'a>(&'a mut Fields);
+
+    impl Visit for Visitor<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+    }
+
+    impl<S: tracing::Subscriber> Layer<S> for Collect {
+        fn on_event(&self, event: &tracing::Event<'_>, _: Context<'_, S>) {
+            let mut fields = Fields::new();
+            event.record(&mut Visitor(&mut fields));
+            self.events.lock().unwrap().push(fields);
+        }
+
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            _: &tracing::span::Id,
+            _: Context<'_, S>,
+        ) {
+            let mut fields = Fields::new();
+            attrs.record(&mut Visitor(&mut fields));
+            self.spans.lock().unwrap().push(fields);
+        }
+    }
+
+    fn collect(f: impl FnOnce()) -> Collect {
+        let collect = Collect::default();
+        let subscriber = tracing_subscriber::registry().with(collect.clone());
+        tracing::subscriber::with_default(subscriber, f);
+        collect
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Item {
+        pub name: Option<String>,
+    }
+
+    impl FromProto for Item {
+        type Result = String;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            self.name.from_proto_field("name")
+        }
+    }
+
+    #[test]
+    fn test_missing_required() {
+        let collect = collect(|| {
+            let items = vec![
+                Item {
+                    name: Some("a".to_string()),
+                },
+                Item { name: None },
+            ];
+            assert!(items.from_proto().is_err());
+        });
+        let events = collect.events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0]["proto_type"],
+            "core::option::Option<alloc::string::String>"
+        );
+        assert_eq!(events[0]["target_type"], "alloc::string::String");
+        assert_eq!(events[0]["field_path"], "name");
+        assert_eq!(events[0]["reason"], "Required is missing");
+        assert_eq!(events[1]["field_path"], "[1].name");
+
+        let spans = collect.spans.lock().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0]["count"], "2");
+    }
+
+    #[test]
+    fn test_success() {
+        let collect = collect(|| {
+            let items = vec![Item {
+                name: Some("a".to_string()),
+            }];
+            assert_eq!(items.from_proto().unwrap(), ["a"]);
+            assert_eq!(Some(1u32).required().unwrap(), 1);
+        });
+        assert!(collect.events.lock().unwrap().is_empty());
+        assert_eq!(collect.spans.lock().unwrap().len(), 1);
+    }
+}
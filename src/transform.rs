@@ -0,0 +1,183 @@
+//! Transforming the fields at the conversion boundary, like encrypting the personal data before it
+//! leaves the process. The `String`, `Option<String>` and `Vec<String>` fields marked with
+//! `#[proto(transform)]` are decoded from proto and encoded in to proto with the `ProtoTransform` of
+//! the conversion context, given with `transform::scope`.
+//! ```ignore
+//! #[derive(FromProto, ToProto)]
+//! #[proto(type = "proto::Customer")]
+//! struct Customer {
+//!     name: String,
+//!     #[proto(transform)]
+//!     email: String,
+//! }
+//!
+//! let customer = transform::scope(kms.clone(), || proto.from_proto())?;
+//! let proto = transform::scope(kms, || customer.try_to_proto())?;
+//! ```
+//! The encoding can fail, so the structs with the transformed fields derive `TryToProto` instead of
+//! `ToProto`. Converting them out of a scope is an error, the fields are never passed through as they
+//! are.
+
+use std::cell::RefCell;
+use std::sync::Arc;
+
+/// Encode the plain values in to the proto values and decode them back, like encrypting them
+pub trait ProtoTransform {
+    /// The proto value of the plain value
+    fn encode(&self, plaintext: &str) -> anyhow::Result<String>;
+
+    /// The plain value of the proto value
+    fn decode(&self, encoded: &str) -> anyhow::Result<String>;
+}
+
+thread_local! {
+    static TRANSFORM: RefCell<Option<Arc<dyn ProtoTransform>>> = const { RefCell::new(None) };
+}
+
+/// Restores the transform of the outer scope, also when the conversion panics
+struct Restore(Option<Arc<dyn ProtoTransform>>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let outer = self.0.take();
+        TRANSFORM.with(|transform| *transform.borrow_mut() = outer);
+    }
+}
+
+/// Run the conversions in `f` with the `transform` of the transformed fields
+pub fn scope<R>(transform: Arc<dyn ProtoTransform>, f: impl FnOnce() -> R) -> R {
+    let outer = TRANSFORM.with(|current| current.borrow_mut().replace(transform));
+    let _restore = Restore(outer);
+    f()
+}
+
+/// The transform of the scope
+fn current() -> anyhow::Result<Arc<dyn ProtoTransform>> {
+    TRANSFORM
+        .with(|transform| transform.borrow().clone())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "The field is transformed, but it is not converted in a scope with the ProtoTransform"
+            )
+        })
+}
+
+/// The proto values of the transformed fields
+pub trait TransformProto: Sized {
+    /// Decode the proto value
+    fn decode(self, transform: &dyn ProtoTransform) -> anyhow::Result<Self>;
+
+    /// Encode the value in to proto value
+    fn encode(&self, transform: &dyn ProtoTransform) -> anyhow::Result<Self>;
+}
+
+impl TransformProto for String {
+    fn decode(self, transform: &dyn ProtoTransform) -> anyhow::Result<Self> {
+        transform.decode(&self)
+    }
+
+    fn encode(&self, transform: &dyn ProtoTransform) -> anyhow::Result<Self> {
+        transform.encode(self)
+    }
+}
+
+impl<P: TransformProto> TransformProto for Option<P> {
+    fn decode(self, transform: &dyn ProtoTransform) -> anyhow::Result<Self> {
+        self.map(|value| value.decode(transform)).transpose()
+    }
+
+    fn encode(&self, transform: &dyn ProtoTransform) -> anyhow::Result<Self> {
+        self.as_ref()
+            .map(|value| value.encode(transform))
+            .transpose()
+    }
+}
+
+impl<P: TransformProto> TransformProto for Vec<P> {
+    fn decode(self, transform: &dyn ProtoTransform) -> anyhow::Result<Self> {
+        self.into_iter()
+            .enumerate()
+            .map(|(index, value)| {
+                value
+                    .decode(transform)
+                    .map_err(|error| crate::error::at_index(error, index))
+            })
+            .collect()
+    }
+
+    fn encode(&self, transform: &dyn ProtoTransform) -> anyhow::Result<Self> {
+        self.iter()
+            .enumerate()
+            .map(|(index, value)| {
+                value
+                    .encode(transform)
+                    .map_err(|error| crate::error::at_index(error, index))
+            })
+            .collect()
+    }
+}
+
+/// Decode the proto field with the transform of the scope, used by `#[proto(transform)]`
+#[doc(hidden)]
+pub fn from_proto<P: TransformProto>(proto: P) -> anyhow::Result<P> {
+    proto.decode(&*current()?)
+}
+
+/// Encode the field with the transform of the scope, used by `#[proto(transform)]`
+#[doc(hidden)]
+pub fn to_proto<P: TransformProto>(value: &P) -> anyhow::Result<P> {
+    value.encode(&*current()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The bytes written in hex
+    struct Hex;
+
+    impl ProtoTransform for Hex {
+        fn encode(&self, plaintext: &str) -> anyhow::Result<String> {
+            Ok(plaintext
+                .bytes()
+                .map(|byte| format!("{byte:02x}"))
+                .collect())
+        }
+
+        fn decode(&self, encoded: &str) -> anyhow::Result<String> {
+            anyhow::ensure!(
+                encoded.len().is_multiple_of(2),
+                "The hex {encoded:?} has odd length"
+            );
+            let bytes = (0..encoded.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&encoded[i..i + 2], 16))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(String::from_utf8(bytes)?)
+        }
+    }
+
+    #[test]
+    fn test_scope() {
+        let hex: Arc<dyn ProtoTransform> = Arc::new(Hex);
+        let encoded = scope(hex.clone(), || to_proto(&vec!["eve".to_owned()])).unwrap();
+        assert_eq!(encoded, ["657665"]);
+        let decoded = scope(hex.clone(), || from_proto(encoded)).unwrap();
+        assert_eq!(decoded, ["eve"]);
+
+        let error = scope(hex, || from_proto(vec!["65".to_owned(), "6".to_owned()])).unwrap_err();
+        assert_eq!(error.to_string(), "[1]: The hex \"6\" has odd length");
+    }
+
+    #[test]
+    fn test_missing_scope() {
+        let error = from_proto(None::<String>).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The field is transformed, but it is not converted in a scope with the ProtoTransform"
+        );
+        // the scope ends with the conversion
+        scope(Arc::new(Hex), || to_proto(&"a".to_owned())).unwrap();
+        assert!(to_proto(&"a".to_owned()).is_err());
+    }
+}
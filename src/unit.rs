@@ -0,0 +1,382 @@
+//! The measurements with their units, sent as the messages of the value and the unit
+//! ```proto
+//! message Measurement {
+//!   double value = 1;
+//!   string unit = 2;
+//! }
+//! ```
+//! converted in to `Quantity` with `impl_proto_quantity!`, which parses the unit and normalizes the
+//! value to the canonical unit of its dimension, the seconds of the durations, the meters of the
+//! lengths and the degrees Celsius of the temperatures. The units of the durations and the lengths are
+//! their exact ratios to the canonical ones, like `250 ms` is `0.25 s`, the temperatures in kelvins or
+//! degrees Fahrenheit are shifted too.
+//! ```ignore
+//! impl_proto_quantity!(proto::Measurement { value, unit });
+//!
+//! let latency: Quantity = proto.latency.from_proto_field("latency")?;
+//! let timeout = latency.as_duration().context("The latency is not a duration")?;
+//! ```
+//! The unknown units are an error with the raw unit, `OpenQuantity` keeps them as they were sent, like
+//! `OpenEnum` keeps the unknown enum values. The quantities are converted back in to the proto messages
+//! with the value in the canonical unit, the unknown ones with their raw unit. The durations convert in
+//! to the quantities with `Quantity::from_duration` and back with `Quantity::as_duration`, so in to the
+//! proto durations with the chrono arms of `impl_traits!`.
+
+use std::fmt;
+
+use crate::violation::{coded, ErrorCode};
+
+/// The canonical unit of a dimension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    /// The durations
+    Second,
+    /// The lengths
+    Meter,
+    /// The temperatures
+    Celsius,
+}
+
+impl Unit {
+    /// The symbol of the unit in the proto messages
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Second => "s",
+            Unit::Meter => "m",
+            Unit::Celsius => "°C",
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// The value of the unit in the canonical one, `(value + OFFSET) * NUMERATOR / DENOMINATOR`, the
+/// ratios are integers, so the exact multiples are exact
+struct UnitOf {
+    symbol: &'static str,
+    unit: Unit,
+    numerator: f64,
+    denominator: f64,
+    offset: f64,
+}
+
+const fn unit(symbol: &'static str, unit: Unit, numerator: f64, denominator: f64) -> UnitOf {
+    UnitOf {
+        symbol,
+        unit,
+        numerator,
+        denominator,
+        offset: 0.0,
+    }
+}
+
+const UNITS: &[UnitOf] = &[
+    unit("ns", Unit::Second, 1.0, 1e9),
+    unit("us", Unit::Second, 1.0, 1e6),
+    unit("µs", Unit::Second, 1.0, 1e6),
+    unit("ms", Unit::Second, 1.0, 1e3),
+    unit("s", Unit::Second, 1.0, 1.0),
+    unit("min", Unit::Second, 60.0, 1.0),
+    unit("h", Unit::Second, 3_600.0, 1.0),
+    unit("d", Unit::Second, 86_400.0, 1.0),
+    unit("mm", Unit::Meter, 1.0, 1e3),
+    unit("cm", Unit::Meter, 1.0, 1e2),
+    unit("m", Unit::Meter, 1.0, 1.0),
+    unit("km", Unit::Meter, 1e3, 1.0),
+    unit("°C", Unit::Celsius, 1.0, 1.0),
+    UnitOf {
+        symbol: "K",
+        unit: Unit::Celsius,
+        numerator: 1.0,
+        denominator: 1.0,
+        offset: -273.15,
+    },
+    UnitOf {
+        symbol: "°F",
+        unit: Unit::Celsius,
+        numerator: 5.0,
+        denominator: 9.0,
+        offset: -32.0,
+    },
+];
+
+/// The value in the canonical unit of its dimension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    value: f64,
+    unit: Unit,
+}
+
+impl Quantity {
+    /// The `value` in the canonical `unit`
+    pub fn new(value: f64, unit: Unit) -> Self {
+        Self { value, unit }
+    }
+
+    /// The `value` in the unit of the `symbol`, normalized to its canonical unit, the unknown units and
+    /// the values, which are not finite, are an error
+    pub fn parse(value: f64, symbol: &str) -> anyhow::Result<Self> {
+        let Some(of) = UNITS.iter().find(|of| of.symbol == symbol) else {
+            return Err(coded(
+                ErrorCode::InvalidFormat,
+                format!("The unit `{symbol}` is unknown"),
+            ));
+        };
+        if !value.is_finite() {
+            return Err(coded(
+                ErrorCode::InvalidFormat,
+                format!("The value {value} {symbol} is not finite"),
+            ));
+        }
+        Ok(Self {
+            value: (value + of.offset) * of.numerator / of.denominator,
+            unit: of.unit,
+        })
+    }
+
+    /// The value in the canonical unit
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+
+    /// The duration of the seconds rounded to the nanoseconds, `None` when it is not a duration or it
+    /// does not fit in to `chrono::Duration`
+    pub fn as_duration(&self) -> Option<chrono::Duration> {
+        if self.unit != Unit::Second || !self.value.is_finite() {
+            return None;
+        }
+        let seconds = self.value.trunc();
+        // the seconds of `i64`, which are more than the ones of the durations
+        if seconds.abs() >= 9.2e18 {
+            return None;
+        }
+        let nanos = ((self.value - seconds) * 1e9).round() as i64;
+        crate::chrono_compat::checked_add(
+            crate::chrono_compat::try_seconds(seconds as i64)?,
+            crate::chrono_compat::nanoseconds(nanos),
+        )
+    }
+
+    /// The seconds of the `duration`
+    pub fn from_duration(duration: &chrono::Duration) -> Self {
+        let (seconds, nanos) = crate::chrono_compat::duration_parts(duration);
+        // one rounding of the nanoseconds, so the ones of the exact seconds are exact
+        let nanos = i128::from(seconds) * 1_000_000_000 + i128::from(nanos);
+        Self::new(nanos as f64 / 1e9, Unit::Second)
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.value, self.unit)
+    }
+}
+
+/// The quantity of a known unit, or the value of the unit, which is not known, like of the newer peers
+#[derive(Debug, Clone, PartialEq)]
+pub enum OpenQuantity {
+    /// The value normalized to its canonical unit
+    Known(Quantity),
+    /// The value and the raw unit, as they were sent
+    Unknown { value: f64, unit: String },
+}
+
+impl OpenQuantity {
+    /// The `value` in the unit of the `symbol`, normalized, when it is known
+    pub fn parse(value: f64, symbol: &str) -> anyhow::Result<Self> {
+        match UNITS.iter().any(|of| of.symbol == symbol) {
+            true => Quantity::parse(value, symbol).map(OpenQuantity::Known),
+            false => Ok(OpenQuantity::Unknown {
+                value,
+                unit: symbol.to_owned(),
+            }),
+        }
+    }
+
+    /// The quantity of the known unit
+    pub fn known(&self) -> Option<&Quantity> {
+        match self {
+            OpenQuantity::Known(quantity) => Some(quantity),
+            OpenQuantity::Unknown { .. } => None,
+        }
+    }
+
+    /// The value and the symbol of its unit, the canonical one of the known units
+    pub fn parts(&self) -> (f64, &str) {
+        match self {
+            OpenQuantity::Known(quantity) => (quantity.value, quantity.unit.symbol()),
+            OpenQuantity::Unknown { value, unit } => (*value, unit),
+        }
+    }
+}
+
+impl From<Quantity> for OpenQuantity {
+    fn from(quantity: Quantity) -> Self {
+        OpenQuantity::Known(quantity)
+    }
+}
+
+/// Implement `FromProto` of the message of the value and the unit in to `Quantity`, `FromProtoAlias`
+/// of it in to `OpenQuantity` and `ToProtoAlias` of both back, the traits of the
+/// `impl_traits!(prelude)` have to be in the scope.
+/// ```ignore
+/// impl_proto_quantity!(proto::Measurement { value, unit });
+/// ```
+/// The unit is parsed and the value normalized, see `unit`.
+#[macro_export]
+macro_rules! impl_proto_quantity {
+    ($proto: path { $value: ident, $unit: ident $(,)? }) => {
+        /// The unknown units are an error
+        impl FromProto for $proto {
+            type Result = $crate::unit::Quantity;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                $crate::unit::Quantity::parse(self.$value, &self.$unit)
+                    .map_err(|error| $crate::error::in_field(error, stringify!($unit)))
+            }
+        }
+
+        /// The unknown units are kept
+        impl FromProtoAlias<$proto> for $crate::unit::OpenQuantity {
+            fn from_proto_alias(proto: $proto) -> anyhow::Result<Self> {
+                $crate::unit::OpenQuantity::parse(proto.$value, &proto.$unit)
+                    .map_err(|error| $crate::error::in_field(error, stringify!($unit)))
+            }
+        }
+
+        impl ToProtoAlias<$proto> for $crate::unit::Quantity {
+            #[allow(clippy::needless_update)]
+            fn to_proto_alias(&self) -> $proto {
+                $proto {
+                    $value: self.value(),
+                    $unit: self.unit().symbol().to_owned(),
+                    ..::core::default::Default::default()
+                }
+            }
+        }
+
+        impl ToProtoAlias<$proto> for $crate::unit::OpenQuantity {
+            #[allow(clippy::needless_update)]
+            fn to_proto_alias(&self) -> $proto {
+                let (value, unit) = self.parts();
+                $proto {
+                    $value: value,
+                    $unit: unit.to_owned(),
+                    ..::core::default::Default::default()
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoMeasurement {
+        value: f64,
+        unit: String,
+    }
+
+    impl_proto_quantity!(ProtoMeasurement { value, unit });
+
+    fn measurement(value: f64, unit: &str) -> ProtoMeasurement {
+        ProtoMeasurement {
+            value,
+            unit: unit.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        let latency = measurement(250.0, "ms").from_proto().unwrap();
+        assert_eq!(latency, Quantity::new(0.25, Unit::Second));
+        assert_eq!(latency.to_string(), "0.25 s");
+        assert_eq!(
+            measurement(1.5, "km").from_proto().unwrap(),
+            Quantity::new(1_500.0, Unit::Meter)
+        );
+        assert_eq!(measurement(3.0, "min").from_proto().unwrap().value(), 180.0);
+        assert_eq!(
+            measurement(212.0, "°F").from_proto().unwrap(),
+            Quantity::new(100.0, Unit::Celsius)
+        );
+        let error = measurement(f64::NAN, "s").from_proto().unwrap_err();
+        assert_eq!(error.to_string(), "unit: The value NaN s is not finite");
+    }
+
+    #[test]
+    fn test_unknown_unit() {
+        let error = measurement(3.0, "furlong").from_proto().unwrap_err();
+        assert_eq!(error.to_string(), "unit: The unit `furlong` is unknown");
+        assert_eq!(ErrorCode::of(&error), ErrorCode::InvalidFormat);
+
+        // the open quantities keep it
+        let open: OpenQuantity =
+            FromProtoAlias::from_proto_alias(measurement(3.0, "furlong")).unwrap();
+        assert_eq!(
+            open,
+            OpenQuantity::Unknown {
+                value: 3.0,
+                unit: "furlong".to_owned()
+            }
+        );
+        assert_eq!(open.known(), None);
+        assert_eq!(
+            ToProtoAlias::<ProtoMeasurement>::to_proto_alias(&open),
+            measurement(3.0, "furlong")
+        );
+        let open: OpenQuantity = FromProtoAlias::from_proto_alias(measurement(5.0, "cm")).unwrap();
+        assert_eq!(open.known(), Some(&Quantity::new(0.05, Unit::Meter)));
+    }
+
+    #[test]
+    fn test_duration() {
+        let latency = measurement(250.0, "ms").from_proto().unwrap();
+        assert_eq!(
+            latency.as_duration(),
+            Some(chrono::Duration::milliseconds(250))
+        );
+        let negative = measurement(-1.5, "s").from_proto().unwrap();
+        assert_eq!(
+            negative.as_duration(),
+            Some(chrono::Duration::milliseconds(-1_500))
+        );
+        assert_eq!(Quantity::new(2.0, Unit::Meter).as_duration(), None);
+        assert_eq!(Quantity::new(1e300, Unit::Second).as_duration(), None);
+
+        let duration = chrono::Duration::microseconds(1_500_250);
+        let quantity = Quantity::from_duration(&duration);
+        assert_eq!(quantity, Quantity::new(1.50025, Unit::Second));
+        assert_eq!(quantity.as_duration(), Some(duration));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        // written back in the canonical unit
+        for (value, unit, canonical) in [
+            (250.0, "ms", measurement(0.25, "s")),
+            (2.0, "h", measurement(7_200.0, "s")),
+            (20.0, "mm", measurement(0.02, "m")),
+            (300.0, "K", measurement(26.850000000000023, "°C")),
+        ] {
+            let quantity = measurement(value, unit).from_proto().unwrap();
+            let proto = ToProtoAlias::<ProtoMeasurement>::to_proto_alias(&quantity);
+            assert_eq!(proto, canonical);
+            assert_eq!(proto.from_proto().unwrap(), quantity);
+        }
+    }
+}
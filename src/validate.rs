@@ -0,0 +1,143 @@
+//! Validation of the converted values. Implement `ProtoValidate` for the domain types and convert them
+//! with `from_proto_validated`, so the hand written conversions get the same check after the conversion
+//! as the derived ones with `#[proto(validate = "...")]`. The functions here are the building blocks for
+//! the validators, they fit in to the `validate` attribute too.
+
+use std::fmt::Display;
+
+use crate::error::{at_index, at_key};
+
+/// Check the converted value
+pub trait ProtoValidate {
+    /// Fail, when the value is not valid
+    fn validate(&self) -> anyhow::Result<()>;
+}
+
+/// Validate every element, the error has the index of the invalid one
+impl<T: ProtoValidate> ProtoValidate for Vec<T> {
+    fn validate(&self) -> anyhow::Result<()> {
+        for (index, item) in self.iter().enumerate() {
+            item.validate().map_err(|error| at_index(error, index))?;
+        }
+        Ok(())
+    }
+}
+
+/// The missing value is valid
+impl<T: ProtoValidate> ProtoValidate for Option<T> {
+    fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            Some(value) => value.validate(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: ProtoValidate> ProtoValidate for Box<T> {
+    fn validate(&self) -> anyhow::Result<()> {
+        (**self).validate()
+    }
+}
+
+/// Validate every value, the error has the key of the invalid one
+impl<K: Display, V: ProtoValidate, S> ProtoValidate for std::collections::HashMap<K, V, S> {
+    fn validate(&self) -> anyhow::Result<()> {
+        for (key, value) in self {
+            value.validate().map_err(|error| at_key(error, key))?;
+        }
+        Ok(())
+    }
+}
+
+/// The string has to have some characters other than white space
+pub fn non_empty_string(value: &str) -> anyhow::Result<()> {
+    anyhow::ensure!(!value.trim().is_empty(), "The value is empty");
+    Ok(())
+}
+
+/// The list has to have at least one element
+pub fn non_empty_collection<T>(value: &[T]) -> anyhow::Result<()> {
+    anyhow::ensure!(!value.is_empty(), "The list is empty");
+    Ok(())
+}
+
+/// The value has to be between `min` and `max`, both included
+pub fn in_range<T: PartialOrd + Display>(value: &T, min: T, max: T) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        min <= *value && *value <= max,
+        "The value {value} is not in range {min}..={max}"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone)]
+    pub struct ProtoItem {
+        name: String,
+        count: u32,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Item {
+        name: String,
+        count: u32,
+    }
+
+    impl_traits!(prelude);
+
+    impl FromProto for ProtoItem {
+        type Result = Item;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(Item {
+                name: self.name,
+                count: self.count,
+            })
+        }
+    }
+
+    impl ProtoValidate for Item {
+        fn validate(&self) -> anyhow::Result<()> {
+            non_empty_string(&self.name).map_err(|error| crate::error::in_field(error, "name"))?;
+            in_range(&self.count, 1, 10).map_err(|error| crate::error::in_field(error, "count"))
+        }
+    }
+
+    fn item(name: &str, count: u32) -> ProtoItem {
+        ProtoItem {
+            name: name.to_string(),
+            count,
+        }
+    }
+
+    #[test]
+    fn test_validated() {
+        let item = item("nails", 3).from_proto_validated().unwrap();
+        assert_eq!(item.count, 3);
+
+        let error = self::item("nails", 30).from_proto_validated().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "count: The value 30 is not in range 1..=10"
+        );
+    }
+
+    #[test]
+    fn test_validated_list() {
+        let items = vec![item("nails", 3), item(" ", 1)];
+        let error = items.from_proto_validated().unwrap_err();
+        assert_eq!(error.to_string(), "[1].name: The value is empty");
+    }
+
+    #[test]
+    fn test_validators() {
+        assert!(non_empty_collection::<u32>(&[]).is_err());
+        assert!(non_empty_collection(&[1]).is_ok());
+        assert!(in_range(&0.5, 0.0, 1.0).is_ok());
+    }
+}
@@ -0,0 +1,63 @@
+//! The verification of the derived conversions against the prost generated structs, with the `verify`
+//! feature. The derived `FromProto` of every struct adds a hidden function destructuring its proto type
+//! with the proto names of the mapped fields, the ones of `rename`, the parents of `path`, and the
+//! alternatives of `first_of`, and of every oneof enum a match of all the mapped variants of the prost
+//! oneof enum. The functions are never called, they are type checked with the crate, so the proto
+//! field renamed or removed in the `.proto` file, but still mapped by the derive, fails to compile with
+//! the error pointing at the field of the derive, like
+//! ```text
+//! error[E0026]: struct `proto::User` does not have a field named `display_name`
+//! ```
+//! The proto fields, which are not mapped, are left to `#[proto(deny_unknown_fields)]`, see
+//! `exhaustive`.
+//!
+//! `verify_all!()` placed in a test fails to compile without the `verify` feature, so the test builds,
+//! like the ones of the CI, can not leave the verification out by mistake.
+//! ```ignore
+//! #[test]
+//! fn test_proto_mappings() {
+//!     proto_conve::verify_all!();
+//! }
+//! ```
+
+/// The verifiers of the derived conversions, kept with the `verify` feature.
+#[cfg(feature = "verify")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __verify {
+    ($($item: tt)*) => {
+        $($item)*
+    };
+}
+
+/// Without the `verify` feature the derived conversions have no verifiers.
+#[cfg(not(feature = "verify"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __verify {
+    ($($item: tt)*) => {};
+}
+
+/// Assert that the derived conversions of the crate are verified against their proto types, it fails
+/// to compile without the `verify` feature, see `verify`
+#[cfg(feature = "verify")]
+#[macro_export]
+macro_rules! verify_all {
+    () => {
+        $crate::verify::verified()
+    };
+}
+
+#[cfg(not(feature = "verify"))]
+#[macro_export]
+macro_rules! verify_all {
+    () => {
+        ::core::compile_error!(
+            "the derived conversions are verified with the `verify` feature of proto-conve"
+        )
+    };
+}
+
+/// All the verifiers are checked by the compilation, so there is nothing left to run
+#[doc(hidden)]
+pub fn verified() {}
@@ -0,0 +1,90 @@
+//! The schema versions of the clients, picking the conversion of the fields whose meaning changed
+//! between the versions, with `FromProtoVersioned`.
+
+use std::fmt;
+
+/// The version of the proto schema used by the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SchemaVersion(u32);
+
+impl SchemaVersion {
+    /// The version
+    pub const fn new(version: u32) -> Self {
+        Self(version)
+    }
+
+    /// The version number
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+}
+
+impl From<u32> for SchemaVersion {
+    fn from(version: u32) -> Self {
+        Self(version)
+    }
+}
+
+impl fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}", self.0)
+    }
+}
+
+/// The error of the conversion in a version, which the type does not support, it can be found with
+/// `anyhow::Error::downcast_ref`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedVersion {
+    /// The version of the conversion
+    pub version: SchemaVersion,
+    /// The converted type
+    pub type_name: &'static str,
+}
+
+impl fmt::Display for UnsupportedVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The schema version {} is not supported by {}",
+            self.version, self.type_name
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedVersion {}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    #[test]
+    fn test_containers() {
+        let version = SchemaVersion::new(2);
+        assert!(SchemaVersion::new(2) < SchemaVersion::new(10));
+        assert_eq!(version.to_string(), "v2");
+
+        let values = vec![Some(1u32), None]
+            .from_proto_versioned(version)
+            .unwrap();
+        assert_eq!(values, [Some(1), None]);
+        let value = Box::new("a".to_string()).from_proto_versioned(version);
+        assert_eq!(*value.unwrap(), "a");
+    }
+
+    #[test]
+    fn test_error() {
+        let error = anyhow::Error::new(UnsupportedVersion {
+            version: SchemaVersion::new(4),
+            type_name: "User",
+        });
+        assert_eq!(
+            error.to_string(),
+            "The schema version v4 is not supported by User"
+        );
+        assert!(error.downcast_ref::<UnsupportedVersion>().is_some());
+    }
+}
@@ -0,0 +1,135 @@
+//! The proto string fields of the types, which have the same representation with `FromStr` and
+//! `Display`, like the ids, versions or the enums, converted with `impl_proto_via_str!(Version)`
+//! instead of writing the conversions. The strings are parsed with `FromStr`, the errors have the
+//! string and the parse error, like `Invalid value "1.x": invalid digit found in string`, and written
+//! with `Display`.
+//!
+//! The conversions are only implemented for the types given to the macro, so the `Display` of the
+//! other types, which may be meant for the humans, is not written to the proto fields by accident.
+//! ```ignore
+//! impl_proto_via_str!(Version);
+//!
+//! let version: Version = FromProtoAlias::from_proto_alias(proto.version)?;
+//! let versions: Vec<Version> = FromProtoAlias::from_proto_alias(proto.supported_versions)?;
+//! ```
+
+/// Implement `FromProtoAlias<String>` of the type parsed with its `FromStr` and `ToProtoAlias<String>`
+/// written with its `Display`, the traits of the `impl_traits!(prelude)` have to be in the scope.
+/// ```ignore
+/// impl_proto_via_str!(Version);
+/// ```
+/// The optional and repeated strings convert in to the `Option` and `Vec` of the type, also back.
+#[macro_export]
+macro_rules! impl_proto_via_str {
+    ($type: ty) => {
+        impl FromProtoAlias<String> for $type {
+            fn from_proto_alias(value: String) -> anyhow::Result<Self> {
+                $crate::bag::parse(value)
+            }
+        }
+
+        impl ToProtoAlias<String> for $type {
+            fn to_proto_alias(&self) -> String {
+                ::std::string::ToString::to_string(self)
+            }
+        }
+
+        /// The proto3 `optional` string of the value
+        impl ToProtoAlias<Option<String>> for $type {
+            fn to_proto_alias(&self) -> Option<String> {
+                Some(::std::string::ToString::to_string(self))
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use std::fmt;
+    use std::str::FromStr;
+
+    use crate::impl_traits;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Version {
+        major: u32,
+        minor: u32,
+    }
+
+    impl FromStr for Version {
+        type Err = String;
+
+        fn from_str(value: &str) -> Result<Self, String> {
+            let (major, minor) = value.split_once('.').ok_or("expected major.minor")?;
+            let part = |part: &str| part.parse().map_err(|_| format!("invalid number {part:?}"));
+            Ok(Self {
+                major: part(major)?,
+                minor: part(minor)?,
+            })
+        }
+    }
+
+    impl fmt::Display for Version {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}.{}", self.major, self.minor)
+        }
+    }
+
+    impl_traits!(prelude);
+    impl_proto_via_str!(Version);
+
+    const V1_2: Version = Version { major: 1, minor: 2 };
+
+    #[test]
+    fn test_via_str() {
+        let version = Version::from_proto_alias("1.2".to_owned()).unwrap();
+        assert_eq!(version, V1_2);
+        assert_eq!(ToProtoAlias::<String>::to_proto_alias(&version), "1.2");
+
+        let error = Version::from_proto_alias("1.x".to_owned()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid value \"1.x\": invalid number \"x\""
+        );
+        let error = Version::from_proto_alias("1".to_owned()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid value \"1\": expected major.minor"
+        );
+    }
+
+    #[test]
+    fn test_option_and_vec() {
+        let version: Option<Version> =
+            FromProtoAlias::from_proto_alias(Some("1.2".to_owned())).unwrap();
+        assert_eq!(version, Some(V1_2));
+        let missing: Option<Version> = FromProtoAlias::from_proto_alias(None).unwrap();
+        assert_eq!(
+            ToProtoAlias::<Option<String>>::to_proto_alias(&missing),
+            None
+        );
+        assert_eq!(
+            ToProtoAlias::<Option<String>>::to_proto_alias(&V1_2).as_deref(),
+            Some("1.2")
+        );
+
+        let proto = vec!["1.2".to_owned(), "2.0".to_owned()];
+        let versions: Vec<Version> = FromProtoAlias::from_proto_alias(proto.clone()).unwrap();
+        assert_eq!(versions[1], Version { major: 2, minor: 0 });
+        assert_eq!(
+            ToProtoAlias::<Vec<String>>::to_proto_alias(&versions),
+            proto
+        );
+
+        let error = <Vec<Version> as FromProtoAlias<_>>::from_proto_alias(vec![
+            "1.2".to_owned(),
+            "two".to_owned(),
+        ])
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "[1]: Invalid value \"two\": expected major.minor"
+        );
+    }
+}
@@ -0,0 +1,340 @@
+//! The machine readable details of the failed conversions, for the structured errors of the APIs, like
+//! the `google.rpc.BadRequest` field violations or the RFC 7807 problem details.
+//! ```ignore
+//! let violations = match proto.from_proto() {
+//!     Ok(order) => return Ok(order),
+//!     Err(error) => error.to_field_violations(),
+//! };
+//! // [{"path": "items[2].amount", "code": "OUT_OF_RANGE", "message": "The value 130 is out of ..."}]
+//! let body = serde_json::to_value(&violations)?;
+//! ```
+//! Every error has its stable `ErrorCode`, taken from the typed reasons of the errors, like
+//! `error::Missing` or `budget::BudgetExceeded`, and from the `CodedError` reasons of the out of range,
+//! malformed and unknown enum values of the conversions of the crate. The other errors, like the ones
+//! of the `validate` functions, are `ErrorCode::Invalid`, unless they are the `CodedError` too. The
+//! errors collected with `ErrorMode::Collect` give one violation each, see `ToFieldViolations`.
+//!
+//! With the `serde` feature, `FieldViolation` and `ErrorCode` are `serde::Serialize`, the code as its
+//! string, like `"MISSING_FIELD"`.
+
+use std::fmt;
+
+use crate::budget::BudgetExceeded;
+use crate::envelope::EnvelopeError;
+use crate::error::{ConversionError, Missing};
+use crate::ids::InvalidId;
+use crate::version::UnsupportedVersion;
+
+/// The stable code of the conversion error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// A required field or element is missing
+    MissingField,
+    /// A value is out of its range, like the nanos or the `Bounded` integers
+    OutOfRange,
+    /// A value is malformed, like the ids or the parsed strings
+    InvalidFormat,
+    /// An enum value or name is not known
+    UnknownEnum,
+    /// The conversion exceeded its `budget::ConversionBudget`
+    BudgetExceeded,
+    /// The schema version of the client is not supported
+    UnsupportedVersion,
+    /// Any other invalid value
+    Invalid,
+}
+
+impl ErrorCode {
+    /// The code as it is sent, like `MISSING_FIELD`
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::MissingField => "MISSING_FIELD",
+            ErrorCode::OutOfRange => "OUT_OF_RANGE",
+            ErrorCode::InvalidFormat => "INVALID_FORMAT",
+            ErrorCode::UnknownEnum => "UNKNOWN_ENUM",
+            ErrorCode::BudgetExceeded => "BUDGET_EXCEEDED",
+            ErrorCode::UnsupportedVersion => "UNSUPPORTED_VERSION",
+            ErrorCode::Invalid => "INVALID",
+        }
+    }
+
+    /// The code of the conversion error, also in the `ConversionError` with its path
+    pub fn of(error: &anyhow::Error) -> Self {
+        let reason = match error.downcast_ref::<ConversionError>() {
+            Some(conversion) => conversion.reason(),
+            None => error,
+        };
+        reason
+            .chain()
+            .find_map(code_of)
+            // the message of `Required`
+            .unwrap_or(match reason.to_string() == "Required is missing" {
+                true => ErrorCode::MissingField,
+                false => ErrorCode::Invalid,
+            })
+    }
+}
+
+/// The code of one error of the chain, if it is a typed one
+fn code_of(error: &(dyn std::error::Error + 'static)) -> Option<ErrorCode> {
+    if let Some(coded) = error.downcast_ref::<CodedError>() {
+        Some(coded.code)
+    } else if error.is::<Missing>() {
+        Some(ErrorCode::MissingField)
+    } else if error.is::<BudgetExceeded>() {
+        Some(ErrorCode::BudgetExceeded)
+    } else if error.is::<UnsupportedVersion>() {
+        Some(ErrorCode::UnsupportedVersion)
+    } else if error.is::<InvalidId>() || is_text_format(error) {
+        Some(ErrorCode::InvalidFormat)
+    } else if let Some(envelope) = error.downcast_ref::<EnvelopeError>() {
+        match envelope {
+            EnvelopeError::UnknownKind { .. } => Some(ErrorCode::UnknownEnum),
+            EnvelopeError::Decode { .. } => Some(ErrorCode::InvalidFormat),
+            // the code of the reason
+            EnvelopeError::Conversion { .. } => None,
+        }
+    } else {
+        None
+    }
+}
+
+#[cfg(feature = "text-format")]
+fn is_text_format(error: &(dyn std::error::Error + 'static)) -> bool {
+    error.is::<crate::text_format::TextFormatError>()
+}
+
+#[cfg(not(feature = "text-format"))]
+fn is_text_format(_: &(dyn std::error::Error + 'static)) -> bool {
+    false
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The reason of the error with its code, the message is the same as without it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodedError {
+    code: ErrorCode,
+    message: String,
+}
+
+impl CodedError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+}
+
+impl fmt::Display for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CodedError {}
+
+/// The error of the value with the `code`, used by the conversions of the crate
+#[doc(hidden)]
+pub fn coded(code: ErrorCode, message: impl fmt::Display) -> anyhow::Error {
+    anyhow::Error::new(CodedError::new(code, message.to_string()))
+}
+
+/// One failed field of the conversion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldViolation {
+    /// The path of the field from the outermost message, like `items[2].amount`, empty for the message
+    pub path: String,
+    pub code: ErrorCode,
+    /// The message of the reason, without the path
+    pub message: String,
+}
+
+impl FieldViolation {
+    /// The violation of the conversion error
+    pub fn of(error: &anyhow::Error) -> Self {
+        let (path, reason) = match error.downcast_ref::<ConversionError>() {
+            Some(conversion) => (conversion.path_string(), conversion.reason()),
+            None => (String::new(), error),
+        };
+        Self {
+            path,
+            code: ErrorCode::of(error),
+            message: format!("{reason:#}"),
+        }
+    }
+}
+
+/// The field violations of the failed conversions
+pub trait ToFieldViolations {
+    /// The violations, one per failed value in the order of the errors
+    fn to_field_violations(&self) -> Vec<FieldViolation>;
+}
+
+impl ToFieldViolations for anyhow::Error {
+    fn to_field_violations(&self) -> Vec<FieldViolation> {
+        vec![FieldViolation::of(self)]
+    }
+}
+
+/// The errors collected with `ErrorMode::Collect`, like `chunked::Chunks::errors`
+impl ToFieldViolations for [anyhow::Error] {
+    fn to_field_violations(&self) -> Vec<FieldViolation> {
+        self.iter().map(FieldViolation::of).collect()
+    }
+}
+
+impl ToFieldViolations for Vec<anyhow::Error> {
+    fn to_field_violations(&self) -> Vec<FieldViolation> {
+        self.as_slice().to_field_violations()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ErrorCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for FieldViolation {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut violation = serializer.serialize_struct("FieldViolation", 3)?;
+        violation.serialize_field("path", &self.path)?;
+        violation.serialize_field("code", &self.code)?;
+        violation.serialize_field("message", &self.message)?;
+        violation.end()
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+    use crate::prelude::ErrorMode;
+
+    impl_traits!(prelude);
+
+    type Percent = crate::Bounded<u8, 0, 100>;
+
+    #[derive(Debug, Clone)]
+    pub struct ProtoPayment {
+        pub amount: Option<u32>,
+        pub nanos: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Payment {
+        pub amount: Percent,
+    }
+
+    impl FromProto for ProtoPayment {
+        type Result = Payment;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            crate::mode::nanos(self.nanos.into(), crate::mode::ConversionMode::Strict)
+                .map_err(|error| crate::error::in_field(error, "nanos"))?;
+            let amount = self
+                .amount
+                .ok_or_else(|| crate::error::missing_field("amount"))?;
+            let amount = Percent::from_proto_alias(amount)
+                .map_err(|error| crate::error::in_field(error, "amount"))?;
+            Ok(Payment { amount })
+        }
+    }
+
+    #[test]
+    fn test_codes() {
+        let error = Percent::from_proto_alias(101u32).unwrap_err();
+        assert_eq!(ErrorCode::of(&error), ErrorCode::OutOfRange);
+        assert_eq!(
+            ErrorCode::of(&crate::error::missing_field("name")),
+            ErrorCode::MissingField
+        );
+        let error = crate::error::in_field(anyhow::anyhow!("The name is taken"), "name");
+        assert_eq!(ErrorCode::of(&error), ErrorCode::Invalid);
+        // the context keeps the code of the reason
+        let error = coded(ErrorCode::InvalidFormat, "Not a date").context("Invalid request");
+        assert_eq!(ErrorCode::of(&error), ErrorCode::InvalidFormat);
+        let error = crate::enumeration::unknown_value(7, "Color");
+        assert_eq!(ErrorCode::of(&error), ErrorCode::UnknownEnum);
+        assert_eq!(ErrorCode::UnknownEnum.to_string(), "UNKNOWN_ENUM");
+    }
+
+    #[test]
+    fn test_batch_violations() {
+        let payment = |amount, nanos| ProtoPayment { amount, nanos };
+        let protos = vec![
+            payment(Some(5), 0),
+            payment(None, 0),
+            payment(Some(130), 0),
+            payment(Some(7), 0),
+            payment(Some(7), -1),
+        ];
+        let mut chunks = protos.from_proto_chunked(10, ErrorMode::Collect, |_| {});
+        let payments = chunks.next().unwrap().unwrap();
+        assert_eq!(payments.len(), 2);
+
+        let violations = chunks.errors().to_field_violations();
+        let violations: Vec<_> = violations
+            .iter()
+            .map(|violation| {
+                (
+                    violation.path.as_str(),
+                    violation.code,
+                    violation.message.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            violations,
+            [
+                ("[1].amount", ErrorCode::MissingField, "Required is missing"),
+                (
+                    "[2].amount",
+                    ErrorCode::OutOfRange,
+                    "The value 130 is out of range 0..=100"
+                ),
+                (
+                    "[4].nanos",
+                    ErrorCode::OutOfRange,
+                    "Invalid nanos -1, they have to be in 0..1e9"
+                ),
+            ]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize() {
+        let error = ProtoPayment {
+            amount: Some(130),
+            nanos: 0,
+        }
+        .from_proto()
+        .unwrap_err();
+        let json = serde_json::to_value(error.to_field_violations()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "path": "amount",
+                "code": "OUT_OF_RANGE",
+                "message": "The value 130 is out of range 0..=100",
+            }])
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! The integer wire types of the proto fields, which prost maps to the plain Rust integers, so the
+//! `sint32` and the `sfixed32` are `i32`, and the `fixed64` is `u64`, and the conversions can not tell
+//! the intended signedness from the prost types. `Sint32`, `Sint64`, `Sfixed32`, `Sfixed64`, `Fixed32`
+//! and `Fixed64` name the wire type in the domain types, they convert in to their prost integers as
+//! they are, and in to the other domain integers with the checked `to_domain`, so the negative `sint32`
+//! is an error for the `u32`, instead of wrapping in to a large number.
+//!
+//! The derived conversions check the integer fields with `#[proto(wire = "sint32")]`, also of `int32`,
+//! `int64`, `uint32`, `uint64`, `sint64`, `fixed32`, `fixed64`, `sfixed32` and `sfixed64`, the proto
+//! values out of the range of the field are `ErrorCode::OutOfRange` naming the wire type.
+//! ```ignore
+//! #[derive(FromProto, ToProto)]
+//! #[proto(type = "proto::Reading")]
+//! struct Reading {
+//!     // the proto field is `sint32 offset = 1`
+//!     #[proto(wire = "sint32")]
+//!     offset: i16,
+//! }
+//! ```
+//! `ToProto` needs the field to convert in to the prost integer without a loss, with `Into`, so the
+//! `u32` field of the `sint32` fails to compile instead of writing the large values negative, such
+//! fields derive only `FromProto`, or have the wider wire type.
+
+use std::any::type_name;
+use std::fmt;
+
+use crate::violation::{coded, ErrorCode};
+
+/// The proto value is out of the range of the domain type `T`
+fn out_of_range<T>(wire: &str, value: impl fmt::Display) -> anyhow::Error {
+    coded(
+        ErrorCode::OutOfRange,
+        format!(
+            "The {wire} value {value} is out of the range of {}",
+            type_name::<T>()
+        ),
+    )
+}
+
+macro_rules! wire_types {
+    ($($(#[$attr: meta])* $type: ident($prost: ty), $wire: literal;)*) => {$(
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+        pub struct $type(pub $prost);
+
+        impl $type {
+            /// The prost integer of the field
+            pub fn get(self) -> $prost {
+                self.0
+            }
+
+            /// The domain integer `T` of the value, the values out of its range are an error
+            pub fn to_domain<T: TryFrom<$prost>>(self) -> anyhow::Result<T> {
+                T::try_from(self.0).map_err(|_| out_of_range::<T>($wire, self.0))
+            }
+
+            /// The value of the domain integer, which converts without a loss
+            pub fn from_domain<T: Into<$prost>>(value: T) -> Self {
+                Self(value.into())
+            }
+        }
+
+        impl From<$prost> for $type {
+            fn from(value: $prost) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$type> for $prost {
+            fn from(value: $type) -> Self {
+                value.0
+            }
+        }
+
+        impl fmt::Display for $type {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    )*};
+}
+
+wire_types! {
+    /// The `sint32` field, zigzag encoded, for the often negative values
+    Sint32(i32), "sint32";
+    /// The `sint64` field, zigzag encoded, for the often negative values
+    Sint64(i64), "sint64";
+    /// The `sfixed32` field, always four bytes
+    Sfixed32(i32), "sfixed32";
+    /// The `sfixed64` field, always eight bytes
+    Sfixed64(i64), "sfixed64";
+    /// The `fixed32` field, unsigned and always four bytes
+    Fixed32(u32), "fixed32";
+    /// The `fixed64` field, unsigned and always eight bytes
+    Fixed64(u64), "fixed64";
+}
+
+macro_rules! wire_modules {
+    ($($wire: ident($prost: ty);)*) => {$(
+        /// The checked conversions of the field of the wire type, used by `#[proto(wire = "...")]`
+        pub mod $wire {
+            /// The domain integer of the proto value, the values out of its range are an error
+            pub fn from_proto<T: TryFrom<$prost>>(value: $prost) -> anyhow::Result<T> {
+                T::try_from(value).map_err(|_| super::out_of_range::<T>(stringify!($wire), value))
+            }
+
+            /// The proto value of the domain integer, which converts without a loss
+            pub fn to_proto<T: Copy + Into<$prost>>(value: &T) -> $prost {
+                (*value).into()
+            }
+        }
+    )*};
+}
+
+wire_modules! {
+    int32(i32);
+    int64(i64);
+    uint32(u32);
+    uint64(u64);
+    sint32(i32);
+    sint64(i64);
+    fixed32(u32);
+    fixed64(u64);
+    sfixed32(i32);
+    sfixed64(i64);
+}
+
+/// Implement the identity conversions of the wire types in to their prost integers, this is part of
+/// the `impl_traits!(prelude)`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_wire {
+    (prelude) => {};
+    () => {
+        $crate::__impl_wire!(@types
+            Sint32(i32), Sint64(i64), Sfixed32(i32), Sfixed64(i64), Fixed32(u32), Fixed64(u64)
+        );
+    };
+    (@types $($type: ident($prost: ty)),*) => {$(
+        impl FromProtoAlias<$prost> for $crate::wire::$type {
+            fn from_proto_alias(value: $prost) -> anyhow::Result<Self> {
+                Ok(Self(value))
+            }
+        }
+
+        impl ToProtoAlias<$prost> for $crate::wire::$type {
+            fn to_proto_alias(&self) -> $prost {
+                self.0
+            }
+        }
+    )*};
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::impl_traits;
+
+    impl_traits!(prelude);
+
+    #[test]
+    fn test_signed() {
+        let offset = Sint32::from_proto_alias(-7).unwrap();
+        assert_eq!(offset.to_domain::<i16>().unwrap(), -7);
+        assert_eq!(offset.to_domain::<i64>().unwrap(), -7);
+        let error = offset.to_domain::<u32>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The sint32 value -7 is out of the range of u32"
+        );
+        assert_eq!(ErrorCode::of(&error), ErrorCode::OutOfRange);
+        assert_eq!(ToProtoAlias::<i32>::to_proto_alias(&offset), -7);
+
+        let error = sfixed64::from_proto::<i32>(i64::MIN).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("The sfixed64 value {} is out of the range of i32", i64::MIN)
+        );
+    }
+
+    #[test]
+    fn test_fixed() {
+        for value in [0, 1, u64::from(u32::MAX), u64::MAX] {
+            let fixed = Fixed64::from_proto_alias(value).unwrap();
+            assert_eq!(ToProtoAlias::<u64>::to_proto_alias(&fixed), value);
+            assert_eq!(
+                fixed64::to_proto(&fixed64::from_proto::<u64>(value).unwrap()),
+                value
+            );
+        }
+        let fixed = Fixed32::from_domain(40_000u16);
+        assert_eq!(fixed.to_domain::<u16>().unwrap(), 40_000);
+        assert!(fixed.to_domain::<i16>().is_err());
+        assert_eq!(
+            Sfixed64::from_domain(i32::MIN).to_domain::<i32>().unwrap(),
+            i32::MIN
+        );
+    }
+}
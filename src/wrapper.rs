@@ -0,0 +1,156 @@
+//! The wrapper messages of a single field, like `message UserId { string value = 1; }` or
+//! `message Temperature { double celsius = 1; }`, wrapping the value for its presence or for the
+//! fields added later, converted in to the rust type of the value with `impl_proto_wrapper!`.
+//! ```ignore
+//! impl_proto_id!(UserId(String) as string_raw);
+//! impl_proto_wrapper!(proto::UserId, value: String => UserId);
+//!
+//! let owner: UserId = proto.owner.from_proto_field("owner")?;
+//! ```
+//! The field converts in to the rust type with `FromProtoField`, so with the conversion and the
+//! validation of the type, and its errors have the field in the path, like `owner.value: Invalid
+//! UserId: the value is empty`. The wrappers in the `Option` convert in to the `Option` of the rust
+//! type, and as the required fields they are missing with the name of the outer field. Back in to proto,
+//! the wrapper is constructed with the field of `ToProtoField` and the defaults of the others.
+//!
+//! The derived conversions of the newtypes convert the wrappers too, with
+//! `#[proto(type = "proto::UserId", wrapper = "value")]` on `struct UserId(String)`, converting the
+//! field in to the type of the newtype.
+
+/// Implement `FromProto` of the wrapper message in to the rust type of its single field and `ToProto`
+/// back, `impl_traits!(prelude)` have to be in the scope.
+/// ```ignore
+/// impl_proto_wrapper!(proto::UserId, value: String => UserId);
+/// impl_proto_wrapper!(proto::Temperature, celsius: f64 => Celsius);
+/// ```
+/// The field converts with `FromProtoField` and `ToProtoField`, see `wrapper`.
+#[macro_export]
+macro_rules! impl_proto_wrapper {
+    ($proto: path, $field: ident: $wire: ty => $type: ty) => {
+        impl FromProto for $proto {
+            type Result = $type;
+
+            fn from_proto(self) -> anyhow::Result<Self::Result> {
+                <$wire as FromProtoField<$type, _>>::from_proto_field(
+                    self.$field,
+                    stringify!($field),
+                )
+            }
+        }
+
+        impl ToProto for $type {
+            type Result = $proto;
+
+            #[allow(clippy::needless_update)]
+            fn to_proto(&self) -> Self::Result {
+                $proto {
+                    $field: <$type as ToProtoField<$wire, _>>::to_proto_field(self),
+                    ..::core::default::Default::default()
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::{impl_proto_id, impl_traits};
+
+    impl_traits!(prelude);
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoUserId {
+        pub value: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ProtoDocument {
+        pub owner: Option<ProtoUserId>,
+        pub reviewer: Option<ProtoUserId>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct UserId(String);
+
+    impl_proto_id!(UserId(String) as string_raw);
+    impl_proto_wrapper!(ProtoUserId, value: String => UserId);
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Document {
+        owner: UserId,
+        reviewer: Option<UserId>,
+    }
+
+    impl FromProto for ProtoDocument {
+        type Result = Document;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(Document {
+                owner: self.owner.from_proto_field("owner")?,
+                reviewer: self.reviewer.from_proto_field("reviewer")?,
+            })
+        }
+    }
+
+    fn user_id(value: &str) -> ProtoUserId {
+        ProtoUserId {
+            value: value.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let id = user_id("ann").from_proto().unwrap();
+        assert_eq!(id, UserId("ann".to_owned()));
+        // `UserId` has `ToProtoAlias<String>` too
+        assert_eq!(ToProto::to_proto(&id), user_id("ann"));
+    }
+
+    #[test]
+    fn test_invalid_value() {
+        let error = user_id("").from_proto().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "value: Invalid UserId: the value is empty"
+        );
+
+        let error = ProtoDocument {
+            owner: Some(user_id("")),
+            reviewer: None,
+        }
+        .from_proto()
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "owner.value: Invalid UserId: the value is empty"
+        );
+    }
+
+    #[test]
+    fn test_option() {
+        assert_eq!(
+            Some(user_id("bob")).from_proto().unwrap(),
+            Some(UserId("bob".to_owned()))
+        );
+        assert_eq!(None::<ProtoUserId>.from_proto().unwrap(), None);
+
+        let document = ProtoDocument {
+            owner: Some(user_id("ann")),
+            reviewer: None,
+        }
+        .from_proto()
+        .unwrap();
+        assert_eq!(document.reviewer, None);
+        assert_eq!(ToProto::to_proto(&document.reviewer), None);
+        assert_eq!(Some(&document.owner).to_proto(), Some(user_id("ann")));
+
+        let error = ProtoDocument {
+            owner: None,
+            reviewer: Some(user_id("bob")),
+        }
+        .from_proto()
+        .unwrap_err();
+        assert_eq!(error.to_string(), "owner: Required is missing");
+    }
+}
@@ -0,0 +1,105 @@
+//! The allocations of the conversions, counted by the global allocator of this test binary.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use proto_conve::error::{ConversionError, MissingField, PathSegment};
+use proto_conve::impl_traits;
+
+/// Counts the allocations of the current thread
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// The number of the allocations made by `f` in this thread
+fn allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+impl_traits!(prelude);
+
+#[derive(Debug, Clone, Default)]
+pub struct ProtoReading {
+    sensor: Option<String>,
+    unit: Option<String>,
+    value: Option<f64>,
+}
+
+/// Check all the required fields, like a hot path rejecting the malformed messages
+fn check(proto: &ProtoReading) -> Result<(), MissingField> {
+    proto.sensor.as_ref().required_lite("sensor")?;
+    proto.unit.as_ref().required_lite("unit")?;
+    proto.value.required_lite("value")?;
+    Ok(())
+}
+
+#[test]
+fn test_required_lite() {
+    let protos = vec![ProtoReading::default(); 1_000];
+    let (missing, count) =
+        allocations(|| protos.iter().filter(|proto| check(proto).is_err()).count());
+    assert_eq!(missing, 1_000);
+    assert_eq!(count, 0);
+
+    let error = check(&protos[0]).unwrap_err();
+    assert_eq!(error, MissingField::new("sensor"));
+    assert_eq!(error.to_string(), "sensor: Required is missing");
+
+    // the error allocates only at the boundary
+    let (error, count) = allocations(|| anyhow::Error::from(error));
+    assert!(count > 0);
+    assert_eq!(error.to_string(), "sensor: Required is missing");
+    let error = error.downcast::<ConversionError>().unwrap();
+    assert_eq!(
+        error.path().collect::<Vec<_>>(),
+        [&PathSegment::Field("sensor")]
+    );
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ProtoProfile {
+    name: String,
+    emails: Vec<String>,
+    labels: std::collections::HashMap<String, String>,
+    reading: Option<ProtoReading>,
+}
+
+proto_conve::impl_proto_default!(ProtoProfile {
+    name,
+    emails,
+    labels,
+    reading
+});
+
+#[test]
+fn test_is_proto_default() {
+    use proto_conve::proto_default::ProtoDefault;
+
+    let profiles = vec![ProtoProfile::default(); 1_000];
+    let (defaults, count) = allocations(|| {
+        profiles
+            .iter()
+            .filter(|profile| profile.is_proto_default())
+            .count()
+    });
+    assert_eq!(defaults, 1_000);
+    assert_eq!(count, 0);
+}
@@ -0,0 +1,194 @@
+//! The calls of the API before the renames, as the downstream crates written against it make them.
+//! They have to compile with only the deprecation warnings pointing at the replacements, the impls of
+//! `ToProtoAlias` included. The deprecations are allowed here, the `deprecated_to_proto_alias` and `legacy_duration` cases of the ui tests of the
+//! derive crate deny them, so the notes of the replacements are checked there.
+#![deny(warnings)]
+#![allow(deprecated)]
+
+use proto_conve::impl_traits;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ProtoDuration {
+    seconds: i64,
+    nanos: u32,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ProtoDateTimeUtc {
+    seconds: i64,
+    nanos: u32,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ProtoUser {
+    id: Option<u64>,
+    active: bool,
+    created: Option<ProtoDateTimeUtc>,
+    sessions: Vec<ProtoDuration>,
+    level: i32,
+}
+
+impl_traits!(prelude);
+impl_traits!(ProtoDuration, chrono::Duration);
+impl_traits!(ProtoDateTimeUtc, chrono::DateTime<chrono::Utc>);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Level {
+    Guest,
+    Admin,
+}
+
+impl ToProto for Level {
+    type Result = i32;
+
+    fn to_proto(&self) -> i32 {
+        match self {
+            Level::Guest => 0,
+            Level::Admin => 1,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Session(chrono::Duration);
+
+impl ToProtoOption<ProtoDuration> for Session {
+    fn to_proto(&self) -> Option<ProtoDuration> {
+        (self.0 > chrono::Duration::zero()).then(|| self.0.to_proto())
+    }
+}
+
+/// The tag of the user, sent as the string
+#[derive(Debug, PartialEq, Clone)]
+pub struct Tag(String);
+
+impl ToProtoAlias<String> for Tag {
+    fn to_proto(&self) -> String {
+        self.0.to_lowercase()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct User {
+    id: u64,
+    active: bool,
+    created: Option<chrono::DateTime<chrono::Utc>>,
+    sessions: Vec<chrono::Duration>,
+    level: Level,
+}
+
+impl FromProto for ProtoUser {
+    type Result = User;
+
+    fn from_proto(self) -> anyhow::Result<Self::Result> {
+        Ok(User {
+            id: self.id.required()?,
+            active: self.active,
+            created: self.created.from_proto()?,
+            sessions: self.sessions.from_proto()?,
+            level: match self.level {
+                0 => Level::Guest,
+                1 => Level::Admin,
+                level => anyhow::bail!("Unknown level {level}"),
+            },
+        })
+    }
+}
+
+impl ToProto for User {
+    type Result = ProtoUser;
+
+    fn to_proto(&self) -> Self::Result {
+        ProtoUser {
+            id: Some(self.id),
+            active: self.active.to_proto(),
+            created: self.created.to_proto(),
+            sessions: self.sessions.to_proto(),
+            level: self.level.to_proto(),
+        }
+    }
+}
+
+#[test]
+fn test_duration() {
+    let pd = ProtoDuration {
+        seconds: 1,
+        nanos: 2,
+    };
+    let d = pd.clone().from_proto().unwrap();
+    let pd2 = d.to_proto();
+    assert_eq!(pd, pd2);
+
+    let pd = ProtoDuration {
+        seconds: -1,
+        nanos: 2,
+    };
+    let d = pd.clone().from_proto().unwrap();
+    let pd2 = d.to_proto();
+    assert_eq!(pd, pd2);
+    assert_eq!(d.to_proto_option(), Some(pd));
+}
+
+#[test]
+fn test_date_time() {
+    let pd = ProtoDateTimeUtc {
+        seconds: -1,
+        nanos: 2,
+    };
+    let d = pd.clone().from_proto().unwrap();
+    let pd2 = d.to_proto();
+    assert_eq!(pd, pd2);
+}
+
+#[test]
+fn test_required() {
+    assert_eq!(Some(3).required().unwrap(), 3);
+    let error = None::<u32>.required().unwrap_err();
+    assert_eq!(error.to_string(), "Required is missing");
+}
+
+#[test]
+fn test_user() {
+    let proto = ProtoUser {
+        id: Some(7),
+        active: true,
+        created: Some(ProtoDateTimeUtc {
+            seconds: 60,
+            nanos: 0,
+        }),
+        sessions: vec![ProtoDuration {
+            seconds: 5,
+            nanos: 0,
+        }],
+        level: 1,
+    };
+    let user = proto.clone().from_proto().unwrap();
+    assert_eq!(user.level, Level::Admin);
+    assert_eq!(user.to_proto(), proto);
+    assert_eq!(Level::Guest.to_proto_option(), Some(0));
+    assert_eq!(false.to_proto_option(), Some(false));
+
+    let error = ProtoUser { id: None, ..proto }.from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "Required is missing");
+}
+
+#[test]
+fn test_to_proto_option() {
+    let session = Session(chrono::Duration::seconds(3));
+    let proto = ToProtoOption::to_proto(&session).unwrap();
+    assert_eq!(proto.seconds, 3);
+    assert_eq!(
+        ToProtoOption::to_proto(&Session(chrono::Duration::zero())),
+        None
+    );
+}
+
+#[test]
+fn test_to_proto_alias() {
+    let tag = Tag("Admin".to_string());
+    assert_eq!(tag.to_proto(), "admin");
+    assert_eq!(tag.to_proto_option().as_deref(), Some("admin"));
+    // the renamed methods call the old impl
+    assert_eq!(tag.to_proto_alias(), "admin");
+    assert_eq!(tag.to_proto_alias_option().as_deref(), Some("admin"));
+}
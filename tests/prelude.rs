@@ -0,0 +1,163 @@
+//! The conversions used with only the `prelude` module of the traits in scope.
+
+/// The traits of the conversions with the proto types
+mod proto {
+    proto_conve::impl_traits!(prelude);
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Duration {
+        pub seconds: i64,
+        pub nanos: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct UserId {
+        pub value: u64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Job {
+        pub owner: Option<UserId>,
+        pub watchers: Vec<UserId>,
+        pub timeout: Option<Duration>,
+        pub percent: u32,
+        pub temperature_milli_c: i32,
+    }
+
+    proto_conve::impl_traits!(Duration, chrono::Duration);
+}
+
+mod domain {
+    use crate::proto::prelude::*;
+    use crate::proto::{self, Duration, UserId};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Id(pub u64);
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Job {
+        pub owner: Id,
+        pub watchers: Vec<Id>,
+        pub timeout: Option<chrono::Duration>,
+        pub percent: Bounded<u8, 0, 100>,
+        pub temperature: Scaled<i32, 1, 1000>,
+    }
+
+    impl FromProto for UserId {
+        type Result = Id;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(Id(self.value))
+        }
+    }
+
+    /// The id is both the message and the string of the references
+    impl ToProto for Id {
+        type Result = UserId;
+
+        fn to_proto(&self) -> Self::Result {
+            UserId { value: self.0 }
+        }
+    }
+
+    impl ToProtoAlias<String> for Id {
+        fn to_proto_alias(&self) -> String {
+            self.0.to_string()
+        }
+    }
+
+    impl FromProto for proto::Job {
+        type Result = Job;
+
+        fn from_proto(self) -> anyhow::Result<Self::Result> {
+            Ok(Job {
+                owner: self.owner.required_lite("owner")?.from_proto()?,
+                watchers: self.watchers.from_proto()?,
+                timeout: self.timeout.from_proto()?,
+                percent: FromProtoAlias::from_proto_alias(self.percent)?,
+                temperature: FromProtoAlias::from_proto_alias(self.temperature_milli_c)?,
+            })
+        }
+    }
+
+    impl TryToProto for Job {
+        type Result = proto::Job;
+
+        fn try_to_proto(&self) -> anyhow::Result<Self::Result> {
+            // the deprecated names of `ToProtoAlias` are the same, so the calls of them name `ToProto`
+            Ok(proto::Job {
+                owner: ToProto::to_proto_option(&self.owner),
+                watchers: ToProto::to_proto(&self.watchers),
+                timeout: self.timeout.to_proto_alias(),
+                percent: self.percent.to_proto_alias(),
+                temperature_milli_c: self.temperature.try_to_proto()?,
+            })
+        }
+    }
+
+    pub fn duration(seconds: i64) -> Duration {
+        Duration { seconds, nanos: 0 }
+    }
+}
+
+use domain::{Id, Job};
+use proto::prelude::*;
+
+fn job() -> proto::Job {
+    proto::Job {
+        owner: Some(proto::UserId { value: 7 }),
+        watchers: vec![proto::UserId { value: 8 }, proto::UserId { value: 9 }],
+        timeout: Some(domain::duration(30)),
+        percent: 40,
+        temperature_milli_c: -1500,
+    }
+}
+
+#[test]
+fn test_message() {
+    let job: Job = job().from_proto().unwrap();
+    assert_eq!(job.owner, Id(7));
+    assert_eq!(job.watchers, [Id(8), Id(9)]);
+    assert_eq!(job.timeout, Some(chrono::Duration::seconds(30)));
+    assert_eq!(job.percent.get(), 40);
+    assert_eq!(job.temperature.get(), -1.5);
+    assert_eq!(job.try_to_proto().unwrap(), self::job());
+
+    let error = proto::Job::default().from_proto().unwrap_err();
+    assert_eq!(error.to_string(), "owner: Required is missing");
+}
+
+#[test]
+fn test_both_traits() {
+    let id = Id(5);
+    assert_eq!(ToProto::to_proto(&id), proto::UserId { value: 5 });
+    let reference: String = id.to_proto_alias();
+    assert_eq!(reference, "5");
+    assert_eq!(
+        ToProto::to_proto_option(&id),
+        Some(proto::UserId { value: 5 })
+    );
+    assert_eq!(
+        ToProtoAlias::<String>::to_proto_alias_option(&id).as_deref(),
+        Some("5")
+    );
+    assert_eq!(id.into_proto(), proto::UserId { value: 5 });
+    assert_eq!(Some(id).to_proto_or_default(), proto::UserId { value: 5 });
+    assert_eq!(
+        [id, Id(6)].iter().to_proto_vec(),
+        [proto::UserId { value: 5 }, proto::UserId { value: 6 }]
+    );
+}
+
+#[test]
+fn test_wrappers() {
+    let temperature = Scaled::<i32, 1, 1000>::new(2.5);
+    assert_eq!(temperature.try_to_proto().unwrap(), 2500);
+    let duration: proto::Duration = chrono::Duration::seconds(2).to_proto_alias();
+    assert_eq!(duration, domain::duration(2));
+    assert_eq!(
+        duration.from_proto_mode(ConversionMode::Lenient).unwrap(),
+        chrono::Duration::seconds(2)
+    );
+    assert_eq!(Patch::<u32>::Unchanged, Patch::Unchanged);
+}
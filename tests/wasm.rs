@@ -0,0 +1,42 @@
+//! The JS time conversions in the browser, run with `wasm-pack test --headless --firefox --features wasm`.
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use wasm_bindgen_test::wasm_bindgen_test;
+
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+mod proto {
+    proto_conve::impl_traits!(prelude);
+    proto_conve::impl_traits!(js chrono::DateTime<chrono::Utc>);
+}
+
+use proto::prelude::*;
+
+type DateTime = chrono::DateTime<chrono::Utc>;
+
+#[wasm_bindgen_test]
+fn test_date_roundtrip() {
+    let now = js_sys::Date::new_0();
+    let value = DateTime::from_proto_alias(now.clone()).unwrap();
+    assert_eq!(value.timestamp_millis() as f64, now.get_time());
+    let date: js_sys::Date = value.to_proto_alias();
+    assert_eq!(date.get_time(), now.get_time());
+    assert_eq!(
+        String::from(date.to_iso_string()),
+        value.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_millis_roundtrip() {
+    let now = js_sys::Date::now();
+    let value = DateTime::from_proto_alias(now).unwrap();
+    assert_eq!(ToProtoAlias::<f64>::to_proto_alias(&value), now.trunc());
+    assert!(DateTime::from_proto_alias(js_sys::Date::new(&f64::NAN.into()).get_time()).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_elapsed_without_clock() {
+    let started = proto_conve::clock::Started::now();
+    assert_eq!(started.elapsed(), std::time::Duration::ZERO);
+}